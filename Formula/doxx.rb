@@ -10,6 +10,8 @@ class Doxx < Formula
 
   def install
     system "cargo", "install", *std_cargo_args
+    generate_completions_from_executable(bin/"doxx", "completions")
+    (man1/"doxx.1").write Utils.safe_popen_read(bin/"doxx", "manpage")
   end
 
   test do
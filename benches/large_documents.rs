@@ -0,0 +1,87 @@
+//! Benchmarks on large generated documents
+//!
+//! Generate the documents first with `cargo run --release --bin generate_bench_docs`,
+//! then run `cargo bench --bench large_documents`. The budgets below are for a
+//! release build on a recent laptop; a change to the parser that pushes a
+//! benchmark past its budget needs a reason.
+//!
+//! | Benchmark | Budget |
+//! |-----------|--------|
+//! | `load_document/large-paragraphs` (10,000 paragraphs) | 1 s |
+//! | `load_document/large-tables` (500 tables) | 1 s |
+//! | `load_document/large-images` (200 images, extracted) | 1 s |
+//! | `search_document/*` (10,000 paragraphs) | 20 ms |
+//! | `ansi_export/*` | 500 ms |
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use doxx::document::{load_document, search_document, Document, ImageOptions};
+use doxx::export::{export_document_to_string, ExportOptions};
+use doxx::{ColorDepth, ExportFormat};
+use std::path::PathBuf;
+
+/// A document written by `generate_bench_docs`
+fn fixture(name: &str) -> PathBuf {
+    let path = PathBuf::from(format!("benches/fixtures/{name}.docx"));
+    assert!(
+        path.exists(),
+        "{} is missing; run `cargo run --release --bin generate_bench_docs` first",
+        path.display()
+    );
+    path
+}
+
+fn load(name: &str) -> Document {
+    load_document(&fixture(name), ImageOptions::default()).unwrap()
+}
+
+fn bench_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load_document");
+    group.sample_size(10);
+    for name in ["large-paragraphs", "large-tables"] {
+        let path = fixture(name);
+        group.bench_function(name, |b| {
+            b.iter(|| load_document(&path, ImageOptions::default()).unwrap())
+        });
+    }
+
+    // Images are only extracted when they'll be shown
+    let path = fixture("large-images");
+    let options = ImageOptions {
+        enabled: true,
+        ..Default::default()
+    };
+    group.bench_function("large-images", |b| {
+        b.iter(|| load_document(&path, options.clone()).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_search(c: &mut Criterion) {
+    let document = load("large-paragraphs");
+    let mut group = c.benchmark_group("search_document");
+    // A word in most paragraphs, one in a few, and one in none
+    for query in ["revenue", "Ledger kickoff", "nowhere"] {
+        group.bench_function(query, |b| b.iter(|| search_document(&document, query)));
+    }
+    group.finish();
+}
+
+fn bench_ansi_export(c: &mut Criterion) {
+    let options = ExportOptions {
+        terminal_width: Some(100),
+        color_depth: ColorDepth::TrueColor,
+        ..Default::default()
+    };
+    let mut group = c.benchmark_group("ansi_export");
+    group.sample_size(10);
+    for name in ["large-paragraphs", "large-tables"] {
+        let document = load(name);
+        group.bench_function(name, |b| {
+            b.iter(|| export_document_to_string(&document, &ExportFormat::Ansi, &options).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_load, bench_search, bench_ansi_export);
+criterion_main!(benches);
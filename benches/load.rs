@@ -0,0 +1,24 @@
+//! Document loading benchmarks
+//!
+//! Run with `cargo bench --bench load`. `tiny-runs.docx` writes one run per
+//! character (about 96,000 runs), as some generators do, and should load in
+//! well under a second.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use doxx::document::{load_document, ImageOptions};
+use std::path::Path;
+
+fn bench_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load_document");
+    group.sample_size(10);
+    for name in ["tiny-runs", "business-report", "tables-heavy", "equations"] {
+        let path = format!("tests/fixtures/{name}.docx");
+        group.bench_function(name, |b| {
+            b.iter(|| load_document(Path::new(&path), ImageOptions::default()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_load);
+criterion_main!(benches);
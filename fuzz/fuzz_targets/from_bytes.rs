@@ -0,0 +1,13 @@
+//! Arbitrary bytes as a .docx package
+//!
+//! Most inputs stop at the ZIP reader; seed the corpus with the fixtures in
+//! tests/fixtures to reach the parser.
+
+#![no_main]
+
+use doxx::document::{Document, ImageOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Document::from_bytes(data, ImageOptions::default());
+});
@@ -0,0 +1,24 @@
+//! Fuzzed Office Math in a paragraph, inline and as a display equation
+//!
+//! The equation scanners hand each equation's markup to the OMML parser as
+//! it stands in the document, so this feeds it unbalanced tags, stray
+//! entities and multi-byte characters wherever they can fall.
+
+#![no_main]
+
+mod package;
+
+use doxx::document::{Document, ImageOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|math: &str| {
+    let document = format!(
+        "<w:document {}><w:body>\
+         <w:p><w:r><w:t>Inline </w:t></w:r><m:oMath>{math}</m:oMath></w:p>\
+         <w:p><m:oMathPara><m:oMath>{math}</m:oMath></m:oMathPara></w:p>\
+         </w:body></w:document>",
+        package::NAMESPACES
+    );
+    let data = package::docx(&[("word/document.xml", document.as_bytes())]);
+    let _ = Document::from_bytes(&data, ImageOptions::default());
+});
@@ -0,0 +1,26 @@
+//! Fuzzed document, styles and numbering parts in a well-formed package
+//!
+//! Reaches the XML scanners and the list and heading heuristics, which read
+//! levels, numbering IDs and indents straight from the markup.
+
+#![no_main]
+
+mod package;
+
+use arbitrary::Arbitrary;
+use doxx::document::{Document, ImageOptions};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Parts<'a> {
+    document: &'a [u8],
+    styles: Option<&'a [u8]>,
+    numbering: Option<&'a [u8]>,
+}
+
+fuzz_target!(|parts: Parts| {
+    let mut entries: Vec<(&str, &[u8])> = vec![("word/document.xml", parts.document)];
+    entries.extend(parts.styles.map(|styles| ("word/styles.xml", styles)));
+    entries.extend(parts.numbering.map(|numbering| ("word/numbering.xml", numbering)));
+    let _ = Document::from_bytes(&package::docx(&entries), ImageOptions::default());
+});
@@ -0,0 +1,46 @@
+/*
+ * C interface to doxx's .docx parser.
+ *
+ * Build the shared library with
+ * `cargo rustc --release --lib --crate-type cdylib --features ffi`
+ * (add `--no-default-features` to leave out the terminal viewer's
+ * dependencies). It is written to target/release as libdoxx.so, libdoxx.dylib
+ * or doxx.dll.
+ *
+ * Functions that return `char *` hand back a NUL-terminated UTF-8 string that
+ * the caller owns and must release with doxx_string_free(). On failure they
+ * return NULL, and doxx_last_error() says why.
+ */
+
+#ifndef DOXX_H
+#define DOXX_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Parse a .docx held in memory and return it as doxx's JSON export. */
+char *doxx_parse(const uint8_t *bytes, size_t len);
+
+/* Parse a .docx held in memory and return it as Markdown. */
+char *doxx_export_markdown(const uint8_t *bytes, size_t len);
+
+/* Release a string returned by doxx_parse() or doxx_export_markdown().
+ * NULL is ignored. */
+void doxx_string_free(char *string);
+
+/* Why the last call on this thread returned NULL, or NULL after a success.
+ * Owned by the library and valid until the thread's next doxx_ call. */
+const char *doxx_last_error(void);
+
+/* The library version, such as "0.1.2". Static; don't free it. */
+const char *doxx_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DOXX_H */
@@ -1,32 +1,76 @@
 use anyhow::Result;
-use crossterm::style::{
-    Attribute, Color as CrosstermColor, ResetColor, SetAttribute, SetForegroundColor,
-};
+use crossterm::style::{Attribute, ResetColor, SetAttribute};
+use std::borrow::Cow;
 use std::fmt::Write;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
-use crate::{document::*, ColorDepth};
+use crate::{
+    bidi,
+    document::*,
+    terminal_image::TerminalImageRenderer,
+    wrap::{
+        align_line, default_width, labeled_rule, number_line, pad_to_width, strip_ansi,
+        visible_width, wrap_words_with, BreakOptions, WrapMode,
+    },
+    ChartStyle, ColorDepth, LineBreaking,
+};
+
+/// Narrowest a text column can be and still be laid out side by side
+const MIN_COLUMN_WIDTH: usize = 24;
+
+/// Space between text columns laid out side by side
+const COLUMN_GAP: usize = 3;
 
 pub struct AnsiOptions {
     pub terminal_width: usize,
     pub color_depth: ColorDepth,
+    pub line_breaking: LineBreaking,
+    /// Let optimal breaking split words at hyphens and soft hyphens; off anyway
+    /// for documents in languages that don't hyphenate
+    pub hyphenate: bool,
+    /// Chart the numeric columns below each table (`--charts`)
+    pub charts: Option<ChartStyle>,
+    /// How numbers in table cells are written, for charts; the document's
+    /// own by default
+    pub locale: Option<CellLocale>,
 }
 
 impl Default for AnsiOptions {
     fn default() -> Self {
         Self {
-            terminal_width: std::env::var("COLUMNS")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(80),
+            terminal_width: default_width(),
             color_depth: ColorDepth::Auto,
+            line_breaking: LineBreaking::Greedy,
+            hyphenate: true,
+            charts: None,
+            locale: None,
+        }
+    }
+}
+
+/// Draws an image element where its placeholder would go, given the output
+/// so far; returns whether it did
+type DrawImage<'a> = dyn FnMut(&mut String, &DocumentElement) -> Result<bool> + 'a;
+
+impl AnsiOptions {
+    /// The options with the colour depth detected and the document's own
+    /// hyphenation and locale filled in
+    fn for_document(&self, document: &Document) -> Self {
+        Self {
+            terminal_width: self.terminal_width,
+            color_depth: resolve_color_depth(&self.color_depth),
+            line_breaking: self.line_breaking,
+            hyphenate: self.hyphenate && document.hyphenates(),
+            charts: self.charts,
+            locale: Some(self.locale.unwrap_or_else(|| document.cell_locale())),
         }
     }
 }
 
 pub fn export_to_ansi_with_options(document: &Document, options: &AnsiOptions) -> Result<String> {
     let mut output = String::new();
+    let options = &options.for_document(document);
 
     // Add document title
     write_ansi_heading(&mut output, &document.title, 1, options)?;
@@ -45,6 +89,9 @@ pub fn export_to_ansi_with_options(document: &Document, options: &AnsiOptions) -
     if let Some(author) = &document.metadata.author {
         writeln!(output, "- Author: {author}")?;
     }
+    if let Some(language) = &document.metadata.language {
+        writeln!(output, "- Language: {language}")?;
+    }
     output.push('\n');
 
     // Separator
@@ -52,72 +99,356 @@ pub fn export_to_ansi_with_options(document: &Document, options: &AnsiOptions) -
     writeln!(output, "{separator}")?;
     output.push('\n');
 
-    // Convert document content
-    for element in &document.elements {
-        match element {
-            DocumentElement::Heading {
-                level,
-                text,
-                number,
-            } => {
-                let heading_text = if let Some(number) = number {
-                    format!("{number} {text}")
-                } else {
-                    text.clone()
-                };
-                write_ansi_heading(&mut output, &heading_text, *level, options)?;
-                output.push('\n');
-            }
-            DocumentElement::Paragraph { runs } => {
-                if runs.is_empty() || runs.iter().all(|run| run.text.trim().is_empty()) {
-                    continue;
+    write_ansi_body(&mut output, document, options, &mut |_, _| Ok(false))?;
+    Ok(output)
+}
+
+/// Print the document as it reads in the terminal, for `--inline`: laid out as
+/// ANSI export lays it out, under a header with its name, page and word counts
+/// and above a closing rule, with images drawn by `images` where it can.
+/// Without `color` the text is plain.
+pub fn print_inline(
+    document: &Document,
+    options: &AnsiOptions,
+    images: Option<&TerminalImageRenderer>,
+    color: bool,
+) -> Result<()> {
+    use std::io::Write as _;
+
+    let options = &options.for_document(document);
+    let emit = |text: &str| -> Result<()> {
+        let mut stdout = std::io::stdout().lock();
+        match color {
+            true => stdout.write_all(text.as_bytes())?,
+            false => stdout.write_all(strip_ansi(text).as_bytes())?,
+        }
+        stdout.flush()?;
+        Ok(())
+    };
+
+    let rule_color = format_ansi_color(Some("#666666"), options); // Dark gray
+    let name = std::path::Path::new(&document.metadata.file_path)
+        .file_name()
+        .map_or_else(
+            || document.metadata.file_path.clone(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+    let mut output = String::new();
+    writeln!(
+        output,
+        "{rule_color}{}{}",
+        labeled_rule(&name, '─', options.terminal_width),
+        format_ansi_reset()
+    )?;
+    let pages = match document.metadata.page_count {
+        1 => "1 page".to_string(),
+        pages => format!("{pages} pages"),
+    };
+    writeln!(
+        output,
+        "{}{rule_color} · {pages} · {} words · about {} min{}",
+        format_ansi_text(&document.title, true, false, false, false, None, options),
+        document.metadata.word_count,
+        reading_minutes(document),
+        format_ansi_reset()
+    )?;
+    output.push('\n');
+
+    let mut draw = |output: &mut String, element: &DocumentElement| -> Result<bool> {
+        let (
+            Some(renderer),
+            DocumentElement::Image {
+                description,
+                image_path: Some(path),
+                ..
+            },
+        ) = (images, element)
+        else {
+            return Ok(false);
+        };
+        // The image is drawn straight to the terminal, after the text before it
+        emit(output)?;
+        output.clear();
+        renderer.render_image_from_path(path, description)?;
+        Ok(true)
+    };
+    write_ansi_body(&mut output, document, options, &mut draw)?;
+    while output.ends_with("\n\n") {
+        output.pop();
+    }
+    writeln!(
+        output,
+        "{rule_color}{}{}",
+        "─".repeat(options.terminal_width),
+        format_ansi_reset()
+    )?;
+    emit(&output)
+}
+
+/// Write the document's content, one section at a time
+fn write_ansi_body(
+    output: &mut String,
+    document: &Document,
+    options: &AnsiOptions,
+    draw: &mut DrawImage,
+) -> Result<()> {
+    if document.sections.is_empty() {
+        write_ansi_section(output, &document.elements, 1, options, draw)?;
+    }
+    for (index, section) in document.sections.iter().enumerate() {
+        if index > 0 {
+            let marker = format!("── {} ──", section.label(index + 1));
+            writeln!(
+                output,
+                "{}{}{}",
+                format_ansi_color(Some("#666666"), options), // Dark gray
+                marker,
+                format_ansi_reset()
+            )?;
+            output.push('\n');
+        }
+        write_ansi_section(
+            output,
+            &document.elements[section.start..section.end],
+            section.page.columns.into(),
+            options,
+            draw,
+        )?;
+    }
+    Ok(())
+}
+
+/// Write a section's elements, side by side in its text columns when the
+/// terminal is wide enough for them and one after another otherwise; images
+/// are only drawn in sections of one column
+fn write_ansi_section(
+    output: &mut String,
+    elements: &[DocumentElement],
+    columns: usize,
+    options: &AnsiOptions,
+    draw: &mut DrawImage,
+) -> Result<()> {
+    let width = options
+        .terminal_width
+        .saturating_sub(COLUMN_GAP * columns.saturating_sub(1))
+        / columns.max(1);
+    if columns < 2 || width < MIN_COLUMN_WIDTH {
+        for element in elements {
+            match element {
+                DocumentElement::Image {
+                    caption, placement, ..
+                } if draw(output, element)? => {
+                    write_ansi_image_notes(output, caption.as_deref(), placement.as_ref(), options)?
                 }
-                write_ansi_paragraph(&mut output, runs, options)?;
-                output.push('\n');
+                _ => write_ansi_element(output, element, options)?,
             }
-            DocumentElement::List { items, ordered } => {
-                write_ansi_list(&mut output, items, *ordered, options)?;
-                output.push('\n');
+        }
+        return Ok(());
+    }
+
+    let column_options = AnsiOptions {
+        terminal_width: width,
+        color_depth: options.color_depth.clone(),
+        line_breaking: options.line_breaking,
+        hyphenate: options.hyphenate,
+        charts: options.charts,
+        locale: options.locale,
+    };
+    let mut text = String::new();
+    for element in elements {
+        write_ansi_element(&mut text, element, &column_options)?;
+    }
+
+    // Fill the columns in turn, like newspaper columns, to equal heights
+    let mut lines: Vec<&str> = text.lines().collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let height = lines.len().div_ceil(columns);
+    for row in 0..height {
+        let mut line = String::new();
+        for column in 0..columns {
+            let cell = lines.get(column * height + row).copied().unwrap_or("");
+            line.push_str(cell);
+            if column + 1 < columns {
+                let padding = width.saturating_sub(visible_width(cell)) + COLUMN_GAP;
+                line.push_str(&" ".repeat(padding));
             }
-            DocumentElement::Table { table } => {
-                write_ansi_table(&mut output, table, options)?;
+        }
+        writeln!(output, "{}", line.trim_end())?;
+    }
+    output.push('\n');
+    Ok(())
+}
+
+fn write_ansi_element(
+    output: &mut String,
+    element: &DocumentElement,
+    options: &AnsiOptions,
+) -> Result<()> {
+    match element {
+        DocumentElement::Heading {
+            level,
+            text,
+            number,
+        } => {
+            let heading_text = if let Some(number) = number {
+                format!("{number} {text}")
+            } else {
+                text.clone()
+            };
+            let heading_text = bidi::visual_line(&heading_text);
+            write_ansi_heading(output, &heading_text, *level, options)?;
+            output.push('\n');
+        }
+        DocumentElement::Paragraph {
+            runs,
+            alignment,
+            shading,
+            rtl,
+            spacing,
+            ..
+        } => {
+            let blank = runs.is_empty() || runs.iter().all(|run| run.text.trim().is_empty());
+            // Empty paragraphs only take up a line when spacing is preserved
+            let spacing = match spacing {
+                Some(spacing) => *spacing,
+                None if blank => return Ok(()),
+                None => ParagraphSpacing::default(),
+            };
+            output.push_str(&"\n".repeat(spacing.before));
+            if blank {
                 output.push('\n');
+            } else {
+                write_ansi_paragraph(output, runs, *alignment, shading.as_deref(), *rtl, options)?;
             }
-            DocumentElement::Image { description, .. } => {
-                writeln!(
-                    output,
-                    "{}🖼️  [Image: {}]{}",
-                    format_ansi_color(Some("#FF00FF"), options), // Magenta
-                    description,
-                    format_ansi_reset()
-                )?;
-                output.push('\n');
+            output.push_str(&"\n".repeat(spacing.after));
+        }
+        DocumentElement::List { items, ordered } => {
+            write_ansi_list(output, items, *ordered, options)?;
+            output.push('\n');
+        }
+        DocumentElement::BlockQuote { runs } => {
+            // Wrap inside the quote bar and its space
+            let width = options.terminal_width.saturating_sub(2).max(1);
+            let bar = format!(
+                "{}│{} ",
+                format_ansi_color(Some("#888888"), options),
+                format_ansi_reset()
+            );
+            for line in wrap_formatted_runs(runs, width, false, false, options) {
+                writeln!(output, "{bar}{line}{}", format_ansi_reset())?;
             }
-            DocumentElement::Equation { latex, .. } => {
+            output.push('\n');
+        }
+        DocumentElement::CodeBlock { code } => {
+            for line in code.lines() {
                 writeln!(
                     output,
-                    "{}📐 {}{}",
-                    format_ansi_color(Some("#00AAFF"), options), // Cyan
-                    latex,
+                    "{}    {line}{}",
+                    format_ansi_color(Some("#00FF00"), options), // Green
                     format_ansi_reset()
                 )?;
-                output.push('\n');
             }
-            DocumentElement::PageBreak => {
-                let separator = "─".repeat(std::cmp::min(60, options.terminal_width));
-                writeln!(
-                    output,
-                    "{}{}{}",
-                    format_ansi_color(Some("#666666"), options), // Dark gray
-                    separator,
-                    format_ansi_reset()
-                )?;
-                output.push('\n');
+            output.push('\n');
+        }
+        DocumentElement::Table { table } => {
+            write_ansi_table(output, table, options)?;
+            output.push('\n');
+        }
+        DocumentElement::Image {
+            description,
+            caption,
+            placement,
+            ..
+        } => {
+            writeln!(
+                output,
+                "{}🖼️  [Image: {}]{}",
+                format_ansi_color(Some("#FF00FF"), options), // Magenta
+                description,
+                format_ansi_reset()
+            )?;
+            write_ansi_image_notes(output, caption.as_deref(), placement.as_ref(), options)?;
+        }
+        DocumentElement::Equation { latex, number, .. } => {
+            let line = format!(
+                "{}📐 {}{}",
+                format_ansi_color(Some("#00AAFF"), options), // Cyan
+                latex,
+                format_ansi_reset()
+            );
+            match number {
+                Some(number) => {
+                    writeln!(
+                        output,
+                        "{}",
+                        number_line(&line, number, options.terminal_width)
+                    )?;
+                }
+                None => writeln!(output, "{line}")?,
             }
+            output.push('\n');
+        }
+        DocumentElement::PageBreak { page } => {
+            let width = std::cmp::min(60, options.terminal_width);
+            let separator = labeled_rule(&format!("page {page}"), '─', width);
+            writeln!(
+                output,
+                "{}{}{}",
+                format_ansi_color(Some("#666666"), options), // Dark gray
+                separator,
+                format_ansi_reset()
+            )?;
+            output.push('\n');
+        }
+        DocumentElement::FileBoundary { name } => {
+            let width = std::cmp::min(60, options.terminal_width);
+            writeln!(
+                output,
+                "{}{}{}",
+                format_ansi_color(Some("#666666"), options),
+                labeled_rule(name, '═', width),
+                format_ansi_reset()
+            )?;
+            output.push('\n');
+        }
+        DocumentElement::HorizontalRule => {
+            let separator = "─".repeat(std::cmp::min(60, options.terminal_width));
+            writeln!(
+                output,
+                "{}{}{}",
+                format_ansi_color(Some("#666666"), options), // Dark gray
+                separator,
+                format_ansi_reset()
+            )?;
+            output.push('\n');
         }
     }
+    Ok(())
+}
 
-    Ok(output)
+/// An image's caption and where it was placed, below it
+fn write_ansi_image_notes(
+    output: &mut String,
+    caption: Option<&str>,
+    placement: Option<&ImagePlacement>,
+    options: &AnsiOptions,
+) -> Result<()> {
+    if let Some(caption) = caption {
+        let caption = format_ansi_text(caption, false, true, false, false, None, options);
+        writeln!(output, "{caption}{}", format_ansi_reset())?;
+    }
+    if let Some(note) = placement.and_then(ImagePlacement::describe) {
+        writeln!(
+            output,
+            "{}({note}){}",
+            format_ansi_color(Some("#888888"), options),
+            format_ansi_reset()
+        )?;
+    }
+    output.push('\n');
+    Ok(())
 }
 
 fn write_ansi_heading(
@@ -156,154 +487,95 @@ fn write_ansi_heading(
 fn write_ansi_paragraph(
     output: &mut String,
     runs: &[FormattedRun],
+    alignment: TextAlignment,
+    shading: Option<&str>,
+    rtl: bool,
     options: &AnsiOptions,
 ) -> Result<()> {
-    let wrapped_lines = wrap_formatted_runs(runs, options);
-    for line in wrapped_lines {
-        writeln!(output, "{}{}", line, format_ansi_reset())?;
-    }
-    Ok(())
-}
-
-/// Wrap formatted text runs to terminal width while preserving formatting
-fn wrap_formatted_runs(runs: &[FormattedRun], options: &AnsiOptions) -> Vec<String> {
     if runs.is_empty() {
-        return vec![];
-    }
-
-    let max_width = options.terminal_width;
-    let mut lines = Vec::new();
-    let mut current_line = String::new();
-    let mut current_width = 0;
-    let mut line_needs_formatting = false;
-
-    for run in runs {
-        let graphemes: Vec<&str> = run.text.graphemes(true).collect();
-        let mut word = String::new();
-        let mut word_width = 0;
-
-        // Apply formatting at start of run
-        let format_start = get_ansi_format_start(
-            run.formatting.bold,
-            run.formatting.italic,
-            run.formatting.underline,
-            run.formatting.strikethrough,
-            run.formatting.color.as_deref(),
-            options,
-        );
-
-        for grapheme in graphemes {
-            let grapheme_width = UnicodeWidthStr::width(grapheme);
-
-            if grapheme == " " || grapheme == "\n" {
-                // End of word - try to add it to the current line
-                if !word.is_empty() {
-                    if current_width + word_width > max_width && current_width > 0 {
-                        // Word doesn't fit on current line, start new line
-                        if line_needs_formatting {
-                            current_line.push_str(&format_ansi_reset());
-                        }
-                        lines.push(current_line.clone());
-                        current_line.clear();
-                        current_width = 0;
-                        line_needs_formatting = false;
-                    }
-
-                    // Apply formatting if not already applied on this line
-                    if !line_needs_formatting && !format_start.is_empty() {
-                        current_line.push_str(&format_start);
-                        line_needs_formatting = true;
-                    }
-
-                    current_line.push_str(&word);
-                    current_width += word_width;
-
-                    word.clear();
-                    word_width = 0;
-                }
-
-                // Handle space or newline
-                if grapheme == "\n" {
-                    if line_needs_formatting {
-                        current_line.push_str(&format_ansi_reset());
-                    }
-                    lines.push(current_line.clone());
-                    current_line.clear();
-                    current_width = 0;
-                    line_needs_formatting = false;
-                } else if current_width < max_width {
-                    current_line.push(' ');
-                    current_width += 1;
-                }
-            } else {
-                // Building a word
-                word.push_str(grapheme);
-                word_width += grapheme_width;
-            }
-        }
-
-        // Handle remaining word at end of run
-        if !word.is_empty() {
-            if current_width + word_width > max_width && current_width > 0 {
-                if line_needs_formatting {
-                    current_line.push_str(&format_ansi_reset());
-                }
-                lines.push(current_line.clone());
-                current_line.clear();
-                current_width = 0;
-                line_needs_formatting = false;
-            }
+        return Ok(());
+    }
 
-            // Apply formatting if not already applied on this line
-            if !line_needs_formatting && !format_start.is_empty() {
-                current_line.push_str(&format_start);
-                line_needs_formatting = true;
+    // Shaded paragraphs fill every line to the full width with the shading colour
+    let shading_start = shading.and_then(|hex| {
+        let background = quantize_color(hex, &options.color_depth)?;
+        Some(format!(
+            "{}{}",
+            background.background(),
+            format_ansi_color(Some(contrasting_text_color(hex)), options)
+        ))
+    });
+
+    let justify = alignment == TextAlignment::Justify;
+    let wrapped_lines = wrap_formatted_runs(runs, options.terminal_width, justify, rtl, options);
+    let last_line = wrapped_lines.len().saturating_sub(1);
+    for (i, line) in wrapped_lines.iter().enumerate() {
+        // The last line of a justified right-to-left paragraph sits flush right
+        let alignment = match alignment {
+            TextAlignment::Justify if rtl && i == last_line => TextAlignment::Right,
+            alignment => alignment,
+        };
+        let line = align_line(line, alignment, options.terminal_width, i == last_line);
+        match &shading_start {
+            Some(start) => {
+                // Runs reset all attributes when they end, so restore the shading after each
+                let reset = format_ansi_reset();
+                let line = line.replace(&reset, &format!("{reset}{start}"));
+                let padding = options.terminal_width.saturating_sub(visible_width(&line));
+                writeln!(output, "{start}{line}{}{reset}", " ".repeat(padding))?;
             }
-
-            current_line.push_str(&word);
-            current_width += word_width;
+            None => writeln!(output, "{}{}", line, format_ansi_reset())?,
         }
-
-        // Reset formatting at end of run if it was applied
-        if line_needs_formatting && !current_line.is_empty() {
-            current_line.push_str(&format_ansi_reset());
-            line_needs_formatting = false;
-        }
-    }
-
-    // Add final line if not empty
-    if !current_line.is_empty() {
-        lines.push(current_line);
     }
-
-    lines
+    Ok(())
 }
 
 /// Get ANSI formatting codes for start of formatted text
-fn get_ansi_format_start(
-    bold: bool,
-    italic: bool,
-    underline: bool,
-    strikethrough: bool,
-    color: Option<&str>,
-    options: &AnsiOptions,
-) -> String {
+fn get_ansi_format_start(formatting: &TextFormatting, options: &AnsiOptions) -> String {
     let mut result = String::new();
 
-    if bold {
+    if formatting.bold {
         result.push_str(&format!("{}", SetAttribute(Attribute::Bold)));
     }
-    if italic {
+    if formatting.italic {
         result.push_str(&format!("{}", SetAttribute(Attribute::Italic)));
     }
-    if underline {
+    if formatting.underline {
         result.push_str(&format!("{}", SetAttribute(Attribute::Underlined)));
     }
-    if strikethrough {
+    if formatting.strikethrough {
         result.push_str(&format!("{}", SetAttribute(Attribute::CrossedOut)));
     }
-    if let Some(color_hex) = color {
-        result.push_str(&format_ansi_color(Some(color_hex), options));
+    if matches!(options.color_depth, ColorDepth::Monochrome) {
+        // Without colour, coloured text stands out in bold and highlighted
+        // text is underlined instead
+        let colored = formatting
+            .color
+            .as_deref()
+            .and_then(parse_hex_color)
+            .is_some_and(|(r, g, b)| r.max(g).max(b) - r.min(g).min(b) > CHROMATIC_SPREAD);
+        if colored && !formatting.bold {
+            result.push_str(&format!("{}", SetAttribute(Attribute::Bold)));
+        }
+        if formatting.background_hex().is_some() && !formatting.underline {
+            result.push_str(&format!("{}", SetAttribute(Attribute::Underlined)));
+        }
+        return result;
+    }
+    let background = formatting
+        .background_hex()
+        .and_then(|hex| quantize_color(hex, &options.color_depth).map(|color| (hex, color)));
+    match (&formatting.color, background) {
+        (Some(color_hex), _) => result.push_str(&format_ansi_color(Some(color_hex), options)),
+        // Keep uncoloured text readable on whatever background it sits on
+        (None, Some((hex, _))) => result.push_str(&format_ansi_color(
+            Some(contrasting_text_color(hex)),
+            options,
+        )),
+        (None, None) => {}
+    }
+    if let Some((_, color)) = background {
+        result.push_str(&color.background());
     }
 
     result
@@ -325,11 +597,18 @@ fn write_ansi_list(
         let indent = "  ".repeat(item.level as usize);
         let bullet_color = format_ansi_color(Some("#0066FF"), options); // Blue
         let prefix = format!("{}{}{}", bullet_color, indent, bullet);
-        let prefix_visual_width = indent.len() + bullet.len();
+        let prefix_visual_width = indent.len() + bullet.width();
 
         // Wrap item text with proper indentation
         let available_width = options.terminal_width.saturating_sub(prefix_visual_width);
-        let wrapped_lines = wrap_formatted_runs_with_width(&item.runs, available_width, options);
+        let item_text: String = item.runs.iter().map(|run| run.text.as_str()).collect();
+        let wrapped_lines = wrap_formatted_runs(
+            &item.runs,
+            available_width,
+            false,
+            bidi::is_rtl(&item_text),
+            options,
+        );
 
         for (line_idx, line) in wrapped_lines.iter().enumerate() {
             if line_idx == 0 {
@@ -344,126 +623,133 @@ fn write_ansi_list(
     Ok(())
 }
 
-/// Wrap formatted text runs to a specific width
-fn wrap_formatted_runs_with_width(
+/// Wrap formatted text runs to a specific width while preserving formatting
+///
+/// Each line comes out in visual order, with `rtl` as the paragraph's base direction.
+fn wrap_formatted_runs(
     runs: &[FormattedRun],
     max_width: usize,
+    justify: bool,
+    rtl: bool,
     options: &AnsiOptions,
 ) -> Vec<String> {
-    if runs.is_empty() || max_width == 0 {
-        return vec![String::new()];
-    }
-
-    let mut lines = Vec::new();
-    let mut current_line = String::new();
-    let mut current_width = 0;
-    let mut line_needs_formatting = false;
-
-    for run in runs {
-        let graphemes: Vec<&str> = run.text.graphemes(true).collect();
-        let mut word = String::new();
-        let mut word_width = 0;
-
-        // Get formatting codes for this run
-        let format_start = get_ansi_format_start(
-            run.formatting.bold,
-            run.formatting.italic,
-            run.formatting.underline,
-            run.formatting.strikethrough,
-            run.formatting.color.as_deref(),
-            options,
-        );
-
-        for grapheme in graphemes {
-            let grapheme_width = UnicodeWidthStr::width(grapheme);
-
-            if grapheme == " " || grapheme == "\n" {
-                // End of word - try to add it to the current line
-                if !word.is_empty() {
-                    if current_width + word_width > max_width && current_width > 0 {
-                        // Word doesn't fit on current line, start new line
-                        if line_needs_formatting {
-                            current_line.push_str(&format_ansi_reset());
-                        }
-                        lines.push(current_line.clone());
-                        current_line.clear();
-                        current_width = 0;
-                        line_needs_formatting = false;
-                    }
-
-                    // Apply formatting if not already applied on this line
-                    if !line_needs_formatting && !format_start.is_empty() {
-                        current_line.push_str(&format_start);
-                        line_needs_formatting = true;
-                    }
-
-                    current_line.push_str(&word);
-                    current_width += word_width;
-
-                    word.clear();
-                    word_width = 0;
-                }
-
-                // Handle space or newline
-                if grapheme == "\n" {
-                    if line_needs_formatting {
-                        current_line.push_str(&format_ansi_reset());
-                    }
-                    lines.push(current_line.clone());
-                    current_line.clear();
-                    current_width = 0;
-                    line_needs_formatting = false;
-                } else if current_width < max_width {
-                    current_line.push(' ');
-                    current_width += 1;
-                }
-            } else {
-                // Building a word
-                word.push_str(grapheme);
-                word_width += grapheme_width;
+    let texts: Vec<_> = runs
+        .iter()
+        .map(|run| run.formatting.display_text(&run.text))
+        .collect();
+    let segments: Vec<&str> = texts.iter().map(|text| text.as_ref()).collect();
+    let formats: Vec<String> = runs
+        .iter()
+        .map(|run| get_ansi_format_start(&run.formatting, options))
+        .collect();
+    let styled = |segment: Option<usize>| segment.is_some_and(|i| !formats[i].is_empty());
+
+    let break_options = BreakOptions {
+        breaking: options.line_breaking,
+        hyphenate: options.hyphenate,
+        justify,
+        ideographic: true,
+    };
+    wrap_words_with(
+        &segments,
+        WrapMode::Columns(max_width.max(1)),
+        break_options,
+    )
+    .iter()
+    .map(|words| {
+        // The gap between words only carries styling shared by both sides
+        let mut pieces: Vec<(Option<usize>, &str)> = Vec::new();
+        for (i, word) in words.iter().enumerate() {
+            if i > 0 {
+                let before = pieces.last().and_then(|(segment, _)| *segment);
+                let after = word.pieces.first().map(|(segment, _)| *segment);
+                pieces.push((before.filter(|&segment| Some(segment) == after), " "));
             }
+            pieces.extend(
+                word.pieces
+                    .iter()
+                    .map(|&(segment, text)| (Some(segment), text)),
+            );
         }
 
-        // Handle remaining word at end of run
-        if !word.is_empty() {
-            if current_width + word_width > max_width && current_width > 0 {
-                if line_needs_formatting {
-                    current_line.push_str(&format_ansi_reset());
+        let mut line = String::new();
+        let mut active = None;
+        for (segment, text) in visual_pieces(pieces, runs, rtl) {
+            if active != segment {
+                if styled(active) {
+                    line.push_str(&format_ansi_reset());
                 }
-                lines.push(current_line.clone());
-                current_line.clear();
-                current_width = 0;
-                line_needs_formatting = false;
-            }
-
-            // Apply formatting if not already applied on this line
-            if !line_needs_formatting && !format_start.is_empty() {
-                current_line.push_str(&format_start);
-                line_needs_formatting = true;
+                if let Some(segment) = segment {
+                    line.push_str(&formats[segment]);
+                }
+                active = segment;
             }
-
-            current_line.push_str(&word);
-            current_width += word_width;
+            line.push_str(text);
         }
-
-        // Reset formatting at end of run if it was applied
-        if line_needs_formatting && !current_line.is_empty() {
-            current_line.push_str(&format_ansi_reset());
-            line_needs_formatting = false;
+        if styled(active) {
+            line.push_str(&format_ansi_reset());
         }
-    }
-
-    // Add final line if not empty
-    if !current_line.is_empty() {
-        lines.push(current_line);
-    }
+        line
+    })
+    .collect()
+}
 
-    // Return at least one line even if empty
-    if lines.is_empty() {
-        lines.push(String::new());
+/// Put a line's `(segment, text)` pieces into visual order
+///
+/// Lines with right-to-left text come back split into graphemes.
+fn visual_pieces<'a>(
+    pieces: Vec<(Option<usize>, &'a str)>,
+    runs: &[FormattedRun],
+    rtl: bool,
+) -> Vec<(Option<usize>, &'a str)> {
+    let run_rtl = |segment: Option<usize>| segment.is_some_and(|i| runs[i].formatting.rtl);
+    if !rtl
+        && pieces
+            .iter()
+            .all(|&(segment, text)| text.is_ascii() && !run_rtl(segment))
+    {
+        return pieces;
     }
 
-    lines
+    // A gap inside a right-to-left run stays part of it
+    let flags: Vec<bool> = (0..pieces.len())
+        .map(|i| match pieces[i].0 {
+            Some(segment) => runs[segment].formatting.rtl,
+            None => {
+                i > 0
+                    && i + 1 < pieces.len()
+                    && run_rtl(pieces[i - 1].0)
+                    && run_rtl(pieces[i + 1].0)
+            }
+        })
+        .collect();
+    let graphemes: Vec<(Option<usize>, &str, bool)> = pieces
+        .iter()
+        .zip(flags)
+        .flat_map(|(&(segment, text), flag)| {
+            text.graphemes(true)
+                .map(move |grapheme| (segment, grapheme, flag))
+        })
+        .collect();
+    let order = bidi::visual_order(
+        &graphemes
+            .iter()
+            .map(|&(_, grapheme, flag)| (grapheme, flag))
+            .collect::<Vec<_>>(),
+        rtl,
+    );
+    order
+        .into_iter()
+        .map(|(i, reversed)| {
+            let (segment, grapheme, _) = graphemes[i];
+            let grapheme = if reversed {
+                bidi::mirror(grapheme)
+            } else {
+                grapheme
+            };
+            (segment, grapheme)
+        })
+        .collect()
 }
 
 fn write_ansi_table(output: &mut String, table: &TableData, options: &AnsiOptions) -> Result<()> {
@@ -482,38 +768,113 @@ fn write_ansi_table(output: &mut String, table: &TableData, options: &AnsiOption
         output.push('\n');
     }
 
-    // Simple table rendering for ANSI
+    // Simple table rendering for ANSI, padded to the table's column widths
     if !table.headers.is_empty() {
+        let widths: Vec<usize> = table
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                table
+                    .metadata
+                    .column_widths
+                    .get(i)
+                    .copied()
+                    .unwrap_or_else(|| header.display_width())
+            })
+            .collect();
+        let cell = |i: usize, cell: &TableCell, styled: bool| {
+            let width = widths.get(i).copied().unwrap_or(0);
+            let text = bidi::visual_line(&cell.content);
+            let mut padded = pad_to_width(&text, cell.alignment, width);
+            // Runs keep their formatting, unless they had to be reordered
+            let reordered = matches!(text, Cow::Owned(_));
+            let at = padded
+                .find(cell.content.as_str())
+                .filter(|_| styled && !reordered && !cell.content.is_empty());
+            if let Some(at) = at {
+                let runs: String = cell
+                    .formatted_runs()
+                    .iter()
+                    .map(
+                        |run| match get_ansi_format_start(&run.formatting, options) {
+                            start if start.is_empty() => run.text.clone(),
+                            start => format!("{start}{}{}", run.text, format_ansi_reset()),
+                        },
+                    )
+                    .collect();
+                padded.replace_range(at..at + cell.content.len(), &runs);
+            }
+            padded
+        };
+
         // Headers
         write!(output, "│")?;
-        for header in &table.headers {
+        for (i, header) in table.headers.iter().enumerate() {
             write!(
                 output,
                 " {}{}{} │",
                 format_ansi_text("", true, false, false, false, None, options),
-                header.content,
+                cell(i, header, false),
                 format_ansi_reset()
             )?;
         }
         writeln!(output)?;
 
         // Separator
-        write!(output, "├")?;
-        for _ in &table.headers {
-            write!(output, "─────┼")?;
-        }
-        writeln!(output, "┤")?;
+        let rules: Vec<String> = widths.iter().map(|width| "─".repeat(width + 2)).collect();
+        writeln!(output, "├{}┤", rules.join("┼"))?;
 
         // Rows
         for row in &table.rows {
             write!(output, "│")?;
-            for cell in row {
-                write!(output, " {} │", cell.content)?;
+            for (i, content) in row.iter().enumerate() {
+                write!(output, " {} │", cell(i, content, true))?;
             }
             writeln!(output)?;
         }
     }
 
+    if let Some(style) = options.charts {
+        write_ansi_charts(output, table, style, options)?;
+    }
+
+    Ok(())
+}
+
+/// Sparklines or bars of the table's numeric columns, below it
+fn write_ansi_charts(
+    output: &mut String,
+    table: &TableData,
+    style: ChartStyle,
+    options: &AnsiOptions,
+) -> Result<()> {
+    let lines = chart::table_charts(
+        table,
+        options.locale.unwrap_or_default(),
+        style,
+        options.terminal_width,
+    );
+    if lines.is_empty() {
+        return Ok(());
+    }
+    output.push('\n');
+    for line in lines {
+        if line.heading {
+            let label = format_ansi_text(&line.label, true, false, false, false, None, options);
+            writeln!(output, "{label}{}", format_ansi_reset())?;
+            continue;
+        }
+        writeln!(
+            output,
+            "{}  {}{}{}  {}",
+            line.label,
+            format_ansi_color(Some("#0066FF"), options), // Blue
+            line.graph,
+            format_ansi_reset(),
+            line.value
+        )?;
+    }
     Ok(())
 }
 
@@ -560,88 +921,172 @@ fn format_ansi_color(color_hex: Option<&str>, options: &AnsiOptions) -> String {
         return String::new();
     };
 
-    match convert_hex_to_crossterm_color(hex, &options.color_depth) {
-        Some(color) => format!("{}", SetForegroundColor(color)),
-        None => String::new(),
-    }
+    quantize_color(hex, &options.color_depth).map_or_else(String::new, AnsiColor::foreground)
 }
 
 fn format_ansi_reset() -> String {
     format!("{ResetColor}")
 }
 
-fn convert_hex_to_crossterm_color(hex: &str, color_depth: &ColorDepth) -> Option<CrosstermColor> {
-    // Remove # if present and ensure we have 6 characters
+/// Runs on one line, each in its formatting and colours
+///
+/// For output that isn't laid out as ANSI export is, like the preview doxx
+/// prints when it isn't run in a terminal.
+pub fn format_runs(runs: &[FormattedRun], color_depth: &ColorDepth) -> String {
+    let options = AnsiOptions {
+        color_depth: resolve_color_depth(color_depth),
+        ..Default::default()
+    };
+    runs.iter()
+        .map(|run| {
+            let text = run.formatting.display_text(&run.text);
+            match get_ansi_format_start(&run.formatting, &options) {
+                start if start.is_empty() => text.into_owned(),
+                start => format!("{start}{text}{}", format_ansi_reset()),
+            }
+        })
+        .collect()
+}
+
+fn resolve_color_depth(color_depth: &ColorDepth) -> ColorDepth {
+    match color_depth {
+        ColorDepth::Auto => detect_color_depth(),
+        depth => depth.clone(),
+    }
+}
+
+/// The colour depth of the terminal, from `COLORTERM` and `TERM`
+fn detect_color_depth() -> ColorDepth {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+        return ColorDepth::TrueColor;
+    }
+    match var("TERM").as_str() {
+        "dumb" => ColorDepth::Monochrome,
+        term if term.contains("256color") || term.contains("kitty") => ColorDepth::Extended,
+        _ => ColorDepth::Standard,
+    }
+}
+
+/// How far apart a colour's channels are before it counts as a colour rather
+/// than a shade of grey, which monochrome output doesn't emphasise
+const CHROMATIC_SPREAD: u8 = 48;
+
+/// The xterm defaults for the 16 standard colours, which colours are matched to
+const STANDARD_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The levels of each channel in the 256-colour palette's 6×6×6 cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// A document colour reduced to what the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnsiColor {
+    /// One of the 16 standard colours, which every colour terminal has
+    Standard(u8),
+    /// An entry of the 256-colour palette
+    Extended(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl AnsiColor {
+    fn foreground(self) -> String {
+        match self {
+            Self::Standard(index @ 0..=7) => format!("\x1b[{}m", 30 + index),
+            Self::Standard(index) => format!("\x1b[{}m", 90 + index - 8),
+            Self::Extended(index) => format!("\x1b[38;5;{index}m"),
+            Self::Rgb(r, g, b) => format!("\x1b[38;2;{r};{g};{b}m"),
+        }
+    }
+
+    fn background(self) -> String {
+        match self {
+            Self::Standard(index @ 0..=7) => format!("\x1b[{}m", 40 + index),
+            Self::Standard(index) => format!("\x1b[{}m", 100 + index - 8),
+            Self::Extended(index) => format!("\x1b[48;5;{index}m"),
+            Self::Rgb(r, g, b) => format!("\x1b[48;2;{r};{g};{b}m"),
+        }
+    }
+}
+
+fn parse_hex_color(hex: &str) -> Option<(u8, u8, u8)> {
     let hex = hex.trim_start_matches('#');
     if hex.len() != 6 {
         return None;
     }
+    Some((
+        u8::from_str_radix(hex.get(0..2)?, 16).ok()?,
+        u8::from_str_radix(hex.get(2..4)?, 16).ok()?,
+        u8::from_str_radix(hex.get(4..6)?, 16).ok()?,
+    ))
+}
 
-    // Parse RGB components
-    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-
+/// The nearest colour to `hex` the colour depth has, or `None` in monochrome
+fn quantize_color(hex: &str, color_depth: &ColorDepth) -> Option<AnsiColor> {
+    let (r, g, b) = parse_hex_color(hex)?;
     match color_depth {
         ColorDepth::Monochrome => None,
-        ColorDepth::Standard => {
-            // Convert to 16 colors (approximation)
-            let color_index = rgb_to_ansi_16(r, g, b);
-            Some(CrosstermColor::AnsiValue(color_index))
-        }
-        ColorDepth::Extended => {
-            // Convert to 256 colors
-            let color_index = rgb_to_ansi_256(r, g, b);
-            Some(CrosstermColor::AnsiValue(color_index))
-        }
-        ColorDepth::TrueColor | ColorDepth::Auto => {
-            // Use full RGB
-            Some(CrosstermColor::Rgb { r, g, b })
-        }
+        ColorDepth::Standard => Some(AnsiColor::Standard(nearest_standard_color((r, g, b)))),
+        ColorDepth::Extended => Some(AnsiColor::Extended(nearest_extended_color((r, g, b)))),
+        ColorDepth::TrueColor | ColorDepth::Auto => Some(AnsiColor::Rgb(r, g, b)),
     }
 }
 
-fn rgb_to_ansi_16(r: u8, g: u8, b: u8) -> u8 {
-    // Simple mapping to 16 colors
-    let r_bright = r > 127;
-    let g_bright = g > 127;
-    let b_bright = b > 127;
-
-    let base = match (r > 64, g > 64, b > 64) {
-        (false, false, false) => 0, // Black
-        (false, false, true) => 4,  // Blue
-        (false, true, false) => 2,  // Green
-        (false, true, true) => 6,   // Cyan
-        (true, false, false) => 1,  // Red
-        (true, false, true) => 5,   // Magenta
-        (true, true, false) => 3,   // Yellow
-        (true, true, true) => 7,    // White
-    };
+/// How different two colours look, weighting green most and blue least as
+/// the eye does
+fn color_distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let channel = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    2 * channel(a.0, b.0) + 4 * channel(a.1, b.1) + 3 * channel(a.2, b.2)
+}
 
-    // Add 8 for bright colors if any component is very bright
-    if r_bright || g_bright || b_bright {
-        base + 8
-    } else {
-        base
-    }
+fn nearest_standard_color(rgb: (u8, u8, u8)) -> u8 {
+    (0..16u8)
+        .min_by_key(|&index| color_distance(rgb, STANDARD_PALETTE[index as usize]))
+        .unwrap_or(0)
 }
 
-fn rgb_to_ansi_256(r: u8, g: u8, b: u8) -> u8 {
-    // 256-color conversion
-    if r == g && g == b {
-        // Grayscale
-        if r < 8 {
-            16
-        } else if r > 247 {
-            231
-        } else {
-            232 + (r - 8) / 10
-        }
-    } else {
-        // Color cube: 16 + 36*r + 6*g + b
-        let r_index = (r as f32 / 255.0 * 5.0) as u8;
-        let g_index = (g as f32 / 255.0 * 5.0) as u8;
-        let b_index = (b as f32 / 255.0 * 5.0) as u8;
-        16 + 36 * r_index + 6 * g_index + b_index
-    }
+/// The nearest entry of the colour cube or the grey ramp; the first 16
+/// entries are left out, since terminals change them with their theme
+fn nearest_extended_color(rgb: (u8, u8, u8)) -> u8 {
+    let level = |channel: u8| {
+        (0..6u8)
+            .min_by_key(|&i| CUBE_LEVELS[i as usize].abs_diff(channel))
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (
+        16 + 36 * r + 6 * g + b,
+        (
+            CUBE_LEVELS[r as usize],
+            CUBE_LEVELS[g as usize],
+            CUBE_LEVELS[b as usize],
+        ),
+    );
+
+    // The grey ramp runs from 8 to 238 in steps of 10
+    let average = (rgb.0 as u16 + rgb.1 as u16 + rgb.2 as u16) / 3;
+    let step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let grey = 8 + 10 * step;
+    let ramp = (232 + step, (grey, grey, grey));
+
+    [cube, ramp]
+        .into_iter()
+        .min_by_key(|(_, color)| color_distance(rgb, *color))
+        .map_or(cube.0, |(index, _)| index)
 }
@@ -0,0 +1,189 @@
+//! Bidirectional text layout for the viewer and ANSI export
+//!
+//! Documents store text in logical (reading) order. Terminals lay cells out
+//! left to right, so right-to-left scripts such as Arabic and Hebrew have to be
+//! put into visual order one wrapped line at a time, using the Unicode
+//! Bidirectional Algorithm (UAX #9). Plain text and Markdown exports keep
+//! logical order; whatever displays them runs the algorithm itself.
+
+use std::borrow::Cow;
+use unicode_bidi::{get_base_direction, Direction, Level, ParagraphBidiInfo};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Right-to-left embedding, wrapped around `w:rtl` runs in left-to-right paragraphs
+const RLE: char = '\u{202B}';
+/// Pop directional formatting, closing an [`RLE`]
+const PDF: char = '\u{202C}';
+
+/// Whether text reads right to left, judged by its first strong character
+pub fn is_rtl(text: &str) -> bool {
+    get_base_direction(text) == Direction::Rtl
+}
+
+/// Visual order of one line's pieces
+///
+/// `pieces` hold the line's text in logical order, each flagged if it comes
+/// from a right-to-left run (`w:rtl`). A piece is laid out as a unit at the
+/// level of its first character, so callers pass graphemes (or anything
+/// smaller than a direction change). `rtl` is the paragraph's base direction;
+/// runs only need embedding when they run against it.
+///
+/// Returns piece indices from left to right, each with whether it is displayed
+/// right to left, in which case brackets should be [mirrored](mirror).
+pub fn visual_order(pieces: &[(&str, bool)], rtl: bool) -> Vec<(usize, bool)> {
+    let mut text = String::new();
+    let mut starts = Vec::with_capacity(pieces.len());
+    let mut in_rtl_run = false;
+    for &(piece, rtl_run) in pieces {
+        let rtl_run = rtl_run && !rtl;
+        if rtl_run != in_rtl_run {
+            text.push(if rtl_run { RLE } else { PDF });
+            in_rtl_run = rtl_run;
+        }
+        starts.push(text.len());
+        text.push_str(piece);
+    }
+
+    let base = if rtl { Level::rtl() } else { Level::ltr() };
+    let info = ParagraphBidiInfo::new(&text, Some(base));
+    if !info.has_rtl() {
+        return (0..pieces.len()).map(|i| (i, false)).collect();
+    }
+
+    let levels = info.reordered_levels(0..text.len());
+    let piece_levels: Vec<Level> = starts
+        .iter()
+        .map(|&start| levels.get(start).copied().unwrap_or(base))
+        .collect();
+    ParagraphBidiInfo::reorder_visual(&piece_levels)
+        .into_iter()
+        .map(|i| (i, piece_levels[i].is_rtl()))
+        .collect()
+}
+
+/// Swap a bracket-like character for its mirror image, for right-to-left display
+pub fn mirror(grapheme: &str) -> &str {
+    match grapheme {
+        "(" => ")",
+        ")" => "(",
+        "[" => "]",
+        "]" => "[",
+        "{" => "}",
+        "}" => "{",
+        "<" => ">",
+        ">" => "<",
+        "«" => "»",
+        "»" => "«",
+        "‹" => "›",
+        "›" => "‹",
+        other => other,
+    }
+}
+
+/// One line of plain text in visual order, with its direction taken from its
+/// first strong character
+///
+/// Text without right-to-left characters is returned as is.
+pub fn visual_line(text: &str) -> Cow<'_, str> {
+    let graphemes: Vec<(&str, bool)> = text.graphemes(true).map(|g| (g, false)).collect();
+    let order = visual_order(&graphemes, is_rtl(text));
+    if order.iter().enumerate().all(|(i, &(index, _))| i == index) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        order
+            .into_iter()
+            .map(|(i, reversed)| {
+                let grapheme = graphemes[i].0;
+                if reversed {
+                    mirror(grapheme)
+                } else {
+                    grapheme
+                }
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pieces(text: &str) -> Vec<(&str, bool)> {
+        text.graphemes(true).map(|g| (g, false)).collect()
+    }
+
+    fn reorder(pieces: &[(&str, bool)], rtl: bool) -> String {
+        visual_order(pieces, rtl)
+            .into_iter()
+            .map(|(i, reversed)| {
+                if reversed {
+                    mirror(pieces[i].0)
+                } else {
+                    pieces[i].0
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ltr_text_is_untouched() {
+        let line = pieces("Plain (English) text.");
+        assert_eq!(reorder(&line, false), "Plain (English) text.");
+        assert!(matches!(visual_line("Plain text"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_hebrew_reverses_and_keeps_numbers() {
+        // "shalom olam 2024" in a right-to-left paragraph
+        let line = pieces("שלום עולם 2024");
+        assert_eq!(reorder(&line, true), "2024 םלוע םולש");
+        assert!(is_rtl("שלום world"));
+        assert!(!is_rtl("world שלום"));
+    }
+
+    #[test]
+    fn test_mixed_direction_runs() {
+        // An English word inside Arabic keeps its own order
+        let line = pieces("مرحبا doxx عالم");
+        assert_eq!(reorder(&line, true), "ملاع doxx ابحرم");
+
+        // Arabic inside an English sentence is reversed in place
+        let line = pieces("Say مرحبا now.");
+        assert_eq!(reorder(&line, false), "Say ابحرم now.");
+    }
+
+    #[test]
+    fn test_brackets_mirror_and_punctuation_trails() {
+        assert_eq!(visual_line("שלום (עולם)."), ".(םלוע) םולש");
+    }
+
+    #[test]
+    fn test_combining_marks_stay_with_their_base() {
+        // Hebrew letters with niqqud: each grapheme moves as a whole
+        let line = pieces("שָׁלוֹם");
+        let reversed: String = line.iter().rev().map(|(g, _)| *g).collect();
+        assert_eq!(reorder(&line, true), reversed);
+    }
+
+    #[test]
+    fn test_rtl_runs_are_embedded() {
+        // Trailing punctuation of a `w:rtl` run sits at the run's leading edge,
+        // even when the run itself is Latin text in a left-to-right paragraph
+        let line = [("a", true), ("b", true), ("!", true), (" c", false)];
+        assert_eq!(reorder(&line, false), "!ab c");
+        let unmarked = [("a", false), ("b", false), ("!", false), (" c", false)];
+        assert_eq!(reorder(&unmarked, false), "ab! c");
+
+        // In a right-to-left paragraph they are already at the base direction,
+        // so runs either side of a Latin word keep their places
+        let line = [
+            ("א", true),
+            (" ", true),
+            ("dx", false),
+            (" ", true),
+            ("ב", true),
+        ];
+        assert_eq!(reorder(&line, true), "ב dx א");
+    }
+}
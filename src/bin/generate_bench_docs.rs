@@ -0,0 +1,197 @@
+//! Generates the large documents the benchmarks in `benches/` load
+//!
+//! Run with `cargo run --bin generate_bench_docs`. The text comes from a fixed
+//! seed, so the documents are the same on every run.
+
+use docx_rs::*;
+use std::fs::File;
+
+type DynError = Box<dyn std::error::Error>;
+
+const OUTPUT_DIR: &str = "benches/fixtures";
+
+const PARAGRAPHS: usize = 10_000;
+const TABLES: usize = 500;
+const IMAGES: usize = 200;
+
+const WORDS: &str = "annual budget customer delivery estimate forecast growth helpdesk \
+    inventory journal kickoff ledger margin network operations pipeline quarter revenue \
+    schedule target upgrade vendor workflow yield the and of for with across during review \
+    report team regional";
+
+fn main() -> Result<(), DynError> {
+    println!("Generating benchmark documents...");
+
+    std::fs::create_dir_all(OUTPUT_DIR)?;
+
+    let mut words = Words::new(2609);
+    generate_paragraphs_doc(&mut words)?;
+    generate_tables_doc(&mut words)?;
+    generate_images_doc(&mut words)?;
+
+    println!("All benchmark documents generated successfully!");
+    Ok(())
+}
+
+/// Words picked by a small linear congruential generator
+struct Words {
+    state: u64,
+    vocabulary: Vec<&'static str>,
+}
+
+impl Words {
+    fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            vocabulary: WORDS.split_whitespace().collect(),
+        }
+    }
+
+    fn next_index(&mut self, bound: usize) -> usize {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.state >> 33) % bound as u64) as usize
+    }
+
+    fn word(&mut self) -> &'static str {
+        let index = self.next_index(self.vocabulary.len());
+        self.vocabulary[index]
+    }
+
+    fn sentence(&mut self, length: usize) -> String {
+        let mut sentence: Vec<&str> = (0..length).map(|_| self.word()).collect();
+        let first = sentence[0][..1].to_uppercase() + &sentence[0][1..];
+        sentence[0] = &first;
+        format!("{}.", sentence.join(" "))
+    }
+}
+
+/// A body paragraph of a few sentences, some words bold or italic
+fn body_paragraph(words: &mut Words) -> Paragraph {
+    let mut paragraph = Paragraph::new();
+    for _ in 0..3 {
+        let length = 8 + words.next_index(10);
+        let run = Run::new().add_text(format!("{} ", words.sentence(length)));
+        paragraph = paragraph.add_run(match words.next_index(6) {
+            0 => run.bold(),
+            1 => run.italic(),
+            _ => run,
+        });
+    }
+    paragraph
+}
+
+fn heading(text: String, level: usize) -> Paragraph {
+    Paragraph::new()
+        .add_run(Run::new().add_text(text))
+        .style(&format!("Heading{level}"))
+}
+
+fn save(doc: Docx, name: &str) -> Result<(), DynError> {
+    let path = format!("{OUTPUT_DIR}/{name}");
+    let file = File::create(&path)?;
+    doc.build().pack(file)?;
+    println!("Generated: {path}");
+    Ok(())
+}
+
+fn generate_paragraphs_doc(words: &mut Words) -> Result<(), DynError> {
+    let mut doc = Docx::new().add_paragraph(heading("Large Paragraphs Benchmark".into(), 1));
+    for index in 0..PARAGRAPHS {
+        if index % 50 == 0 {
+            let title = words.sentence(4);
+            doc = doc.add_paragraph(heading(title.trim_end_matches('.').into(), 2));
+        }
+        doc = doc.add_paragraph(body_paragraph(words));
+    }
+    save(doc, "large-paragraphs.docx")
+}
+
+fn generate_tables_doc(words: &mut Words) -> Result<(), DynError> {
+    let cell = |text: String| {
+        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(text)))
+    };
+
+    let mut doc = Docx::new().add_paragraph(heading("Large Tables Benchmark".into(), 1));
+    for index in 0..TABLES {
+        doc = doc.add_paragraph(Paragraph::new().add_run(Run::new().add_text(format!(
+            "Table {}: {}",
+            index + 1,
+            words.sentence(5)
+        ))));
+        let header = TableRow::new(
+            ["Region", "Quarter", "Revenue", "Notes"]
+                .iter()
+                .map(|title| {
+                    TableCell::new()
+                        .add_paragraph(Paragraph::new().add_run(Run::new().add_text(*title).bold()))
+                })
+                .collect(),
+        );
+        let mut rows = vec![header];
+        for row in 0..5 {
+            rows.push(TableRow::new(vec![
+                cell(words.word().to_string()),
+                cell(format!("Q{}", row % 4 + 1)),
+                cell(format!(
+                    "${},{:03}",
+                    words.next_index(900) + 100,
+                    words.next_index(1000)
+                )),
+                cell(words.sentence(4)),
+            ]));
+        }
+        doc = doc.add_table(Table::new(rows));
+    }
+    save(doc, "large-tables.docx")
+}
+
+fn generate_images_doc(words: &mut Words) -> Result<(), DynError> {
+    let mut doc = Docx::new().add_paragraph(heading("Large Images Benchmark".into(), 1));
+    for index in 0..IMAGES {
+        doc = doc
+            .add_paragraph(body_paragraph(words))
+            .add_paragraph(
+                Paragraph::new().add_run(Run::new().add_image(Pic::new(&gradient_bmp(index)))),
+            )
+            .add_paragraph(
+                Paragraph::new().add_run(Run::new().add_text(format!("Figure {}", index + 1))),
+            );
+    }
+    save(doc, "large-images.docx")
+}
+
+/// A small 24-bit BMP gradient, coloured differently for each `seed`
+///
+/// BMP needs no compression, so it's written by hand; docx-rs converts it to PNG.
+fn gradient_bmp(seed: usize) -> Vec<u8> {
+    const WIDTH: u32 = 64;
+    const HEIGHT: u32 = 48;
+    let row_size = (WIDTH * 3).div_ceil(4) * 4;
+    let pixel_bytes = row_size * HEIGHT;
+
+    let mut bmp = Vec::with_capacity(54 + pixel_bytes as usize);
+    bmp.extend(b"BM");
+    bmp.extend((54 + pixel_bytes).to_le_bytes());
+    bmp.extend(0u32.to_le_bytes());
+    bmp.extend(54u32.to_le_bytes());
+    bmp.extend(40u32.to_le_bytes());
+    bmp.extend(WIDTH.to_le_bytes());
+    bmp.extend(HEIGHT.to_le_bytes());
+    bmp.extend(1u16.to_le_bytes());
+    bmp.extend(24u16.to_le_bytes());
+    bmp.extend([0u8; 24]);
+
+    let red = (seed * 37 % 256) as u8;
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let blue = (x * 255 / WIDTH) as u8;
+            let green = (y * 255 / HEIGHT) as u8;
+            bmp.extend([blue, green, red]);
+        }
+        bmp.resize(bmp.len() + (row_size - WIDTH * 3) as usize, 0);
+    }
+    bmp
+}
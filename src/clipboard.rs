@@ -0,0 +1,258 @@
+//! Copying from the viewer: the system clipboard, OSC 52, or a file
+//!
+//! Over SSH the system clipboard belongs to the remote host, so the viewer asks
+//! the local terminal to set its clipboard with an OSC 52 escape sequence.
+//! Terminals cap how much they accept and drop anything larger without a word,
+//! so text over the limit is written to a file instead of being cut short.
+//! Terminal multiplexers need the sequence wrapped to pass it through, and GNU
+//! Screen only passes short strings, so the sequence is split into chunks.
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::ClipboardMode;
+
+/// Default cap on the base64 payload of an OSC 52 sequence, in bytes
+///
+/// xterm, hterm and several other terminals stop at around 100 kB.
+pub const DEFAULT_OSC52_LIMIT: usize = 100_000;
+
+/// Longest piece of a sequence GNU Screen passes through in one DCS string
+const SCREEN_CHUNK: usize = 76;
+
+/// Where copied text ended up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CopyTarget {
+    /// The system clipboard
+    System,
+    /// The terminal's clipboard, via OSC 52
+    Terminal,
+    /// A file, because the text was over the OSC 52 limit (`too_large`) or
+    /// because files were asked for
+    File { path: PathBuf, too_large: bool },
+}
+
+impl CopyTarget {
+    /// Status line text for `what` having been copied here
+    pub fn describe(&self, what: &str, limit: usize) -> String {
+        match self {
+            Self::System => format!("Copied {what} to clipboard."),
+            Self::Terminal => format!("Copied {what} to the terminal clipboard (OSC 52)."),
+            Self::File {
+                path,
+                too_large: true,
+            } => format!(
+                "{} is over the OSC 52 limit ({limit} bytes); saved to {}",
+                capitalize(what),
+                path.display()
+            ),
+            Self::File { path, .. } => format!("Saved {what} to {}", path.display()),
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A terminal multiplexer that OSC 52 sequences have to be passed through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    /// The multiplexer the viewer is running in, from its environment variables
+    pub fn detect() -> Option<Self> {
+        if std::env::var_os("TMUX").is_some() {
+            Some(Self::Tmux)
+        } else if std::env::var_os("STY").is_some() {
+            Some(Self::Screen)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether the viewer runs in an SSH session, where the system clipboard is
+/// the remote host's
+pub fn over_ssh() -> bool {
+    std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some()
+}
+
+/// OSC 52 escape sequences that set the clipboard to `text`, ready to write
+/// to the terminal in order
+///
+/// Returns `None` when the base64 payload would be over `limit` bytes.
+pub fn osc52_sequences(
+    text: &str,
+    limit: usize,
+    multiplexer: Option<Multiplexer>,
+) -> Option<Vec<String>> {
+    let payload = STANDARD.encode(text);
+    if payload.len() > limit {
+        return None;
+    }
+    let sequence = format!("\x1b]52;c;{payload}\x07");
+
+    Some(match multiplexer {
+        None => vec![sequence],
+        // tmux passes DCS strings through, with ESC doubled inside them
+        Some(Multiplexer::Tmux) => vec![format!(
+            "\x1bPtmux;{}\x1b\\",
+            sequence.replace('\x1b', "\x1b\x1b")
+        )],
+        // Screen limits the length of each DCS string, but joins consecutive ones
+        Some(Multiplexer::Screen) => sequence
+            .as_bytes()
+            .chunks(SCREEN_CHUNK)
+            .map(|chunk| format!("\x1bP{}\x1b\\", String::from_utf8_lossy(chunk)))
+            .collect(),
+    })
+}
+
+/// Copies text according to a [`ClipboardMode`]
+pub struct Copier {
+    mode: ClipboardMode,
+    /// Cap on the OSC 52 payload, in bytes
+    pub limit: usize,
+    multiplexer: Option<Multiplexer>,
+    system: Option<arboard::Clipboard>,
+    /// File text goes to when it can't go to a clipboard
+    fallback: PathBuf,
+}
+
+impl Copier {
+    pub fn new(mode: ClipboardMode, limit: usize) -> Self {
+        // `auto` only uses the system clipboard when it's the user's own
+        let system = match mode {
+            ClipboardMode::System => arboard::Clipboard::new().ok(),
+            ClipboardMode::Auto if !over_ssh() => arboard::Clipboard::new().ok(),
+            _ => None,
+        };
+        Self {
+            mode,
+            limit,
+            multiplexer: Multiplexer::detect(),
+            system,
+            fallback: std::env::temp_dir()
+                .join(format!("doxx-clipboard-{}.txt", std::process::id())),
+        }
+    }
+
+    /// Copy `text`, writing any escape sequences to `terminal`
+    pub fn copy(&mut self, text: &str, terminal: &mut impl Write) -> Result<CopyTarget> {
+        if self.mode == ClipboardMode::File {
+            return self.save(text, false);
+        }
+        if let Some(clipboard) = &mut self.system {
+            clipboard
+                .set_text(text)
+                .context("Failed to copy to clipboard")?;
+            return Ok(CopyTarget::System);
+        }
+        if self.mode == ClipboardMode::System {
+            anyhow::bail!("Clipboard not available");
+        }
+
+        match osc52_sequences(text, self.limit, self.multiplexer) {
+            Some(sequences) => {
+                for sequence in sequences {
+                    terminal.write_all(sequence.as_bytes())?;
+                }
+                terminal.flush()?;
+                Ok(CopyTarget::Terminal)
+            }
+            None => self.save(text, true),
+        }
+    }
+
+    fn save(&self, text: &str, too_large: bool) -> Result<CopyTarget> {
+        std::fs::write(&self.fallback, text)
+            .with_context(|| format!("Failed to write {}", self.fallback.display()))?;
+        Ok(CopyTarget::File {
+            path: self.fallback.clone(),
+            too_large,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(
+            osc52_sequences("hi", DEFAULT_OSC52_LIMIT, None).unwrap(),
+            vec!["\x1b]52;c;aGk=\x07"]
+        );
+        assert_eq!(
+            osc52_sequences("hi", DEFAULT_OSC52_LIMIT, Some(Multiplexer::Tmux)).unwrap(),
+            vec!["\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\"]
+        );
+    }
+
+    #[test]
+    fn test_osc52_limit_applies_to_the_payload() {
+        // Three bytes of text encode to four of base64
+        assert!(osc52_sequences(&"x".repeat(30), 40, None).is_some());
+        assert!(osc52_sequences(&"x".repeat(31), 40, None).is_none());
+    }
+
+    #[test]
+    fn test_screen_sequences_are_chunked() {
+        let text = "a".repeat(200);
+        let chunks =
+            osc52_sequences(&text, DEFAULT_OSC52_LIMIT, Some(Multiplexer::Screen)).unwrap();
+        assert!(chunks.len() > 1);
+        let mut joined = String::new();
+        for chunk in &chunks {
+            let inner = chunk
+                .strip_prefix("\x1bP")
+                .and_then(|chunk| chunk.strip_suffix("\x1b\\"))
+                .unwrap();
+            assert!(inner.len() <= SCREEN_CHUNK);
+            joined.push_str(inner);
+        }
+        assert_eq!(
+            joined,
+            osc52_sequences(&text, DEFAULT_OSC52_LIMIT, None).unwrap()[0]
+        );
+    }
+
+    #[test]
+    fn test_large_text_falls_back_to_a_file() {
+        let mut copier = Copier::new(ClipboardMode::Osc52, 8);
+        let mut terminal = Vec::new();
+
+        assert_eq!(
+            copier.copy("hi", &mut terminal).unwrap(),
+            CopyTarget::Terminal
+        );
+        assert!(!terminal.is_empty());
+
+        terminal.clear();
+        let target = copier.copy("well over the limit", &mut terminal).unwrap();
+        let CopyTarget::File { path, too_large } = &target else {
+            panic!("Expected a file, got {target:?}");
+        };
+        assert!(too_large);
+        assert!(terminal.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "well over the limit"
+        );
+        assert!(target
+            .describe("document", 8)
+            .starts_with("Document is over the OSC 52 limit (8 bytes); saved to "));
+        std::fs::remove_file(path).unwrap();
+    }
+}
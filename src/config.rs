@@ -0,0 +1,283 @@
+//! User configuration
+//!
+//! Settings kept between sessions in `config.toml`, next to the saved reading
+//! positions. The viewer writes the pane layout back whenever it is changed.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+/// Narrowest and widest a side pane can be, in percent of the screen
+const PANE_WIDTH_RANGE: (u16, u16) = (10, 50);
+
+/// How much each resize key press moves a pane's edge, in percent
+const PANE_WIDTH_STEP: u16 = 5;
+
+/// Everything in the configuration file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Language tag table cells' numbers and dates are read in, unless
+    /// `--locale` is given (`locale = "de-DE"`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    pub layout: LayoutConfig,
+    #[serde(skip_serializing_if = "SpellingConfig::is_empty")]
+    pub spelling: SpellingConfig,
+    #[serde(skip_serializing_if = "ScanConfig::is_empty")]
+    pub scan: ScanConfig,
+    #[serde(skip_serializing_if = "CacheConfig::is_empty")]
+    pub cache: CacheConfig,
+}
+
+/// The parse cache, which `--cache` turns on for a single run
+///
+/// ```toml
+/// [cache]
+/// enabled = true
+/// max_size_mb = 512
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Cache every document opened, as if `--cache` were given
+    pub enabled: bool,
+    /// Megabytes the cache is trimmed to (default 256)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+}
+
+impl CacheConfig {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The cache in the platform's cache directory, trimmed to the configured size
+    pub fn parse_cache(&self) -> Result<crate::document::cache::ParseCache> {
+        let max_bytes = self
+            .max_size_mb
+            .map_or(crate::document::cache::DEFAULT_MAX_BYTES, |megabytes| {
+                megabytes * 1024 * 1024
+            });
+        crate::document::cache::ParseCache::open_default(max_bytes)
+    }
+}
+
+/// Rules of your own for `doxx scan`, by name
+///
+/// ```toml
+/// [scan.rules]
+/// employee-id = 'EMP-\d{6}'
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanConfig {
+    /// Regular expressions, each run with `--rules <name>`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rules: BTreeMap<String, String>,
+}
+
+impl ScanConfig {
+    fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The configured rules, or an error naming the one that doesn't compile
+    pub fn scan_rules(&self) -> Result<Vec<crate::document::scan::ScanRule>> {
+        self.rules
+            .iter()
+            .map(|(name, pattern)| {
+                crate::document::scan::ScanRule::custom(name, pattern)
+                    .with_context(|| format!("In the config file's scan rule {name}"))
+            })
+            .collect()
+    }
+
+    /// What `--redact` masks: the rules named, built in or configured
+    pub fn redactor(
+        &self,
+        names: &[String],
+        style: crate::Redaction,
+    ) -> Result<crate::document::scan::Redactor> {
+        Ok(crate::document::scan::Redactor {
+            rules: crate::document::scan::resolve_rules(names, &self.scan_rules()?)?,
+            style,
+        })
+    }
+}
+
+/// Which dictionaries the spellchecker uses
+///
+/// ```toml
+/// [spelling]
+/// language = "en-GB"
+/// dictionary_dirs = ["~/dictionaries"]
+///
+/// [spelling.dictionaries]
+/// de = "/opt/dictionaries/de_DE_frami"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpellingConfig {
+    /// Language to check documents in that don't declare one and aren't
+    /// recognised
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// A dictionary for a language, by the path to its `.dic` and `.aff`
+    /// without the extension
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub dictionaries: BTreeMap<String, PathBuf>,
+    /// Directories searched for dictionaries before the usual ones
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dictionary_dirs: Vec<PathBuf>,
+}
+
+impl SpellingConfig {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// A spellchecker using these dictionaries; none is read until it's used
+    #[cfg(feature = "spellcheck")]
+    pub fn spell_checker(&self) -> crate::document::spelling::SpellChecker {
+        let expand = |path: &PathBuf| match path.strip_prefix("~") {
+            Ok(rest) => dirs::home_dir().map_or_else(|| path.clone(), |home| home.join(rest)),
+            Err(_) => path.clone(),
+        };
+        crate::document::spelling::SpellChecker::new(
+            self.dictionaries
+                .iter()
+                .map(|(tag, path)| (tag.clone(), expand(path)))
+                .collect(),
+            self.dictionary_dirs.iter().map(expand).collect(),
+        )
+    }
+}
+
+/// Which panes the viewer shows beside the document, and how wide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Outline tree on the left
+    pub outline: bool,
+    /// Document details and the image in view on the right
+    pub preview: bool,
+    /// Width of the outline pane, in percent of the screen
+    pub outline_width: u16,
+    /// Width of the preview pane, in percent of the screen
+    pub preview_width: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            outline: false,
+            preview: false,
+            outline_width: 25,
+            preview_width: 30,
+        }
+    }
+}
+
+impl LayoutConfig {
+    /// Widen (or with `grow` false, narrow) the outline pane a step
+    pub fn resize_outline(&mut self, grow: bool) {
+        self.outline_width = step(self.outline_width, grow);
+    }
+
+    /// Widen (or with `grow` false, narrow) the preview pane a step
+    pub fn resize_preview(&mut self, grow: bool) {
+        self.preview_width = step(self.preview_width, grow);
+    }
+
+    /// Widths clamped to what a hand-edited file may have gone beyond
+    pub fn clamped(self) -> Self {
+        let (min, max) = PANE_WIDTH_RANGE;
+        Self {
+            outline_width: self.outline_width.clamp(min, max),
+            preview_width: self.preview_width.clamp(min, max),
+            ..self
+        }
+    }
+}
+
+fn step(width: u16, grow: bool) -> u16 {
+    let (min, max) = PANE_WIDTH_RANGE;
+    if grow {
+        (width + PANE_WIDTH_STEP).min(max)
+    } else {
+        width.saturating_sub(PANE_WIDTH_STEP).max(min)
+    }
+}
+
+impl Config {
+    /// Load the configuration, or the defaults if there's no file yet
+    pub fn load() -> Result<Self> {
+        let path = Self::config_file_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path).context("Failed to read config file")?;
+        let mut config: Config = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        config.layout = config.layout.clamped();
+        Ok(config)
+    }
+
+    /// Save the configuration to disk
+    pub fn save(&self) -> Result<()> {
+        let path = Self::config_file_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let contents = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        fs::write(&path, contents).context("Failed to write config file")?;
+        Ok(())
+    }
+
+    /// The platform's config directory, as for the state file:
+    /// `~/.config/doxx/config.toml` on Linux
+    fn config_file_path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Failed to determine config directory")?;
+        Ok(config_dir.join("doxx").join("config.toml"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pane_widths_stay_in_range() {
+        let mut layout = LayoutConfig::default();
+        for _ in 0..20 {
+            layout.resize_outline(true);
+            layout.resize_preview(false);
+        }
+        assert_eq!((layout.outline_width, layout.preview_width), (50, 10));
+
+        let wide = LayoutConfig {
+            outline_width: 90,
+            preview_width: 0,
+            ..LayoutConfig::default()
+        };
+        assert_eq!(wide.clamped().outline_width, 50);
+        assert_eq!(wide.clamped().preview_width, 10);
+    }
+
+    #[test]
+    fn test_missing_settings_take_defaults() {
+        let config: Config = toml::from_str("[layout]\noutline = true\n").unwrap();
+        assert_eq!(
+            config.layout,
+            LayoutConfig {
+                outline: true,
+                ..LayoutConfig::default()
+            }
+        );
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.layout, LayoutConfig::default());
+        assert!(config.spelling.is_empty());
+        assert!(!config.cache.enabled);
+    }
+}
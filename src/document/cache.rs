@@ -0,0 +1,224 @@
+//! Parsed documents kept on disk, for `--cache`
+//!
+//! Each entry is a [`Document`] in MessagePack, named after the BLAKE3 hash
+//! of the file's contents, the loading options and the doxx version that
+//! parsed it, so that a changed file, other options or a new release simply
+//! miss. Reopening a document, or exporting it several times in a
+//! script, then reads the entry instead of parsing the package again. When
+//! the entries outgrow the size limit, the ones used longest ago go first.
+//!
+//! Documents loaded with images aren't cached: their images are extracted to
+//! a temporary directory that's gone by the next run. Nor are documents with
+//! `DATE`, `TIME` or `FILENAME` fields, whose values depend on when and under
+//! what name the file is opened rather than on its contents.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::loader::load_document_with_progress;
+use super::models::*;
+use super::progress::{NoProgress, Progress};
+
+/// Size limit when none is configured
+pub const DEFAULT_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Extension of cache entries, so clearing only touches what doxx wrote
+const EXTENSION: &str = "doxx-cache";
+
+/// A directory of parsed documents, bounded in size
+#[derive(Debug, Clone)]
+pub struct ParseCache {
+    pub dir: PathBuf,
+    /// Total size the entries are trimmed to after each write
+    pub max_bytes: u64,
+}
+
+/// What a cache directory holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheUsage {
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+impl ParseCache {
+    /// The platform's cache directory: `~/.cache/doxx/documents` on Linux
+    pub fn open_default(max_bytes: u64) -> Result<Self> {
+        let dir = dirs::cache_dir().context("Failed to determine cache directory")?;
+        Ok(Self {
+            dir: dir.join("doxx").join("documents"),
+            max_bytes,
+        })
+    }
+
+    /// Load a document from the cache, or parse it and keep it there
+    ///
+    /// A cache that can't be read or written is passed over; the document is
+    /// parsed as if there were none.
+    #[allow(dead_code)] // Library entry point; the binary reports progress
+    pub fn load(
+        &self,
+        file_path: &Path,
+        image_options: ImageOptions,
+        field_options: FieldOptions,
+    ) -> Result<Document> {
+        self.load_with_progress(file_path, image_options, field_options, &NoProgress)
+    }
+
+    /// [`load`](Self::load), reporting the parse to `progress` on a miss
+    pub fn load_with_progress(
+        &self,
+        file_path: &Path,
+        image_options: ImageOptions,
+        field_options: FieldOptions,
+        progress: &dyn Progress,
+    ) -> Result<Document> {
+        let parse = |image_options| {
+            load_document_with_progress(file_path, image_options, field_options, progress)
+        };
+        if image_options.enabled {
+            return parse(image_options);
+        }
+        let Ok(bytes) = fs::read(file_path) else {
+            return parse(image_options);
+        };
+        let entry = self.dir.join(entry_name(&bytes, &field_options));
+        if let Some(mut document) = read_entry(&entry).filter(|cached| cached.volatile_fields == 0)
+        {
+            // The same contents may have been cached under another name
+            document.metadata.file_path = file_path.to_string_lossy().to_string();
+            document.title = file_path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("Untitled Document")
+                .to_string();
+            document.image_options = image_options;
+            // Used now, so it's the last to be evicted
+            if let Ok(file) = fs::File::options().append(true).open(&entry) {
+                let _ = file.set_modified(SystemTime::now());
+            }
+            return Ok(document);
+        }
+
+        let document = parse(image_options)?;
+        if document.volatile_fields > 0 {
+            return Ok(document);
+        }
+        if let Ok(data) = rmp_serde::to_vec_named(&document) {
+            if fs::create_dir_all(&self.dir).is_ok() && write_entry(&entry, &data).is_ok() {
+                let _ = self.evict();
+            }
+        }
+        Ok(document)
+    }
+
+    /// Entries and their total size
+    pub fn usage(&self) -> Result<CacheUsage> {
+        let entries = self.entries()?;
+        Ok(CacheUsage {
+            entries: entries.len(),
+            bytes: entries.iter().map(|(_, size, _)| size).sum(),
+        })
+    }
+
+    /// Remove every entry; returns what was removed
+    pub fn clear(&self) -> Result<CacheUsage> {
+        let mut removed = CacheUsage::default();
+        for (path, size, _) in self.entries()? {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            removed.entries += 1;
+            removed.bytes += size;
+        }
+        Ok(removed)
+    }
+
+    /// Remove the entries used longest ago until the rest fit the size limit
+    pub fn evict(&self) -> Result<CacheUsage> {
+        let mut entries = self.entries()?;
+        entries.sort_by_key(|(_, _, used)| *used);
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        let mut removed = CacheUsage::default();
+        for (path, size, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            fs::remove_file(&path)?;
+            total -= size;
+            removed.entries += 1;
+            removed.bytes += size;
+        }
+        Ok(removed)
+    }
+
+    /// Each entry with its size and when it was last used
+    fn entries(&self) -> Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let listing = match fs::read_dir(&self.dir) {
+            Ok(listing) => listing,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => {
+                return Err(error).with_context(|| format!("Failed to read {}", self.dir.display()))
+            }
+        };
+        let mut entries = Vec::new();
+        for entry in listing.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            entries.push((path, metadata.len(), used));
+        }
+        Ok(entries)
+    }
+}
+
+/// Name of the entry for these contents read with these options
+fn entry_name(bytes: &[u8], field_options: &FieldOptions) -> String {
+    let options = format!("{}{field_options:?}", env!("CARGO_PKG_VERSION"));
+    let options = blake3::hash(options.as_bytes()).to_hex();
+    format!(
+        "{}-{}.{EXTENSION}",
+        blake3::hash(bytes).to_hex(),
+        &options[..16]
+    )
+}
+
+/// The cached document, unless the entry is missing or from an older layout
+fn read_entry(path: &Path) -> Option<Document> {
+    let data = fs::read(path).ok()?;
+    rmp_serde::from_slice(&data).ok()
+}
+
+/// Write through a temporary file, so that a reader never sees half an entry
+fn write_entry(path: &Path, data: &[u8]) -> Result<()> {
+    let partial = path.with_extension("partial");
+    fs::write(&partial, data)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_differ_by_contents_and_options() {
+        let options = FieldOptions::default();
+        let showing = FieldOptions {
+            show_unknown: true,
+            ..options
+        };
+        assert_eq!(entry_name(b"one", &options), entry_name(b"one", &options));
+        assert_ne!(entry_name(b"one", &options), entry_name(b"two", &options));
+        assert_ne!(entry_name(b"one", &options), entry_name(b"one", &showing));
+        assert!(entry_name(b"one", &options).ends_with(".doxx-cache"));
+        // A full BLAKE3 hash of the contents leads the name
+        let name = entry_name(b"one", &options);
+        assert_eq!(&name[..64], blake3::hash(b"one").to_hex().as_str());
+    }
+}
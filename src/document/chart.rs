@@ -0,0 +1,299 @@
+//! Charts of a table's numeric columns (`--charts`)
+//!
+//! A column is charted when every cell below the header that isn't empty
+//! holds a number, amount or percentage, and at least two do. The first
+//! column names the rows rather than being charted, and a totals row at the
+//! bottom is left out so it doesn't flatten everything above it.
+
+use unicode_width::UnicodeWidthStr;
+
+use super::models::*;
+use super::table_query::parse_amount;
+use crate::ChartStyle;
+
+/// Sparkline levels, lowest first
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Partial bar blocks, one eighth of a cell wider each
+const EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Row and column names are cut to this width
+const MAX_LABEL_WIDTH: usize = 20;
+
+/// Narrowest bar or sparkline worth drawing
+const MIN_GRAPH_WIDTH: usize = 8;
+
+/// A numeric column, with each row's value and the cell as written
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSeries {
+    pub column: usize,
+    pub name: String,
+    pub values: Vec<Option<(f64, String)>>,
+}
+
+/// A line of a table's charts
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartLine {
+    /// A column's name above its bars, with no graph
+    pub heading: bool,
+    /// Padded to the width of the other labels
+    pub label: String,
+    pub graph: String,
+    /// The value a bar stands for, or a sparkline's lowest and highest
+    pub value: String,
+}
+
+/// The rows a chart covers: all of them but a totals row at the bottom
+fn charted_rows(table: &TableData) -> &[Vec<TableCell>] {
+    let is_total = |row: &Vec<TableCell>| {
+        row.first().is_some_and(|cell| {
+            let label = cell.content.trim().to_lowercase();
+            ["total", "subtotal", "grand total", "sum"]
+                .iter()
+                .any(|word| label.starts_with(word))
+        })
+    };
+    match table.rows.split_last() {
+        Some((last, rows)) if is_total(last) => rows,
+        _ => &table.rows,
+    }
+}
+
+/// The table's numeric columns, in order
+pub fn numeric_columns(table: &TableData, locale: CellLocale) -> Vec<ColumnSeries> {
+    let rows = charted_rows(table);
+    (1..table.metadata.column_count.max(table.headers.len()))
+        .filter_map(|column| {
+            let mut values = Vec::with_capacity(rows.len());
+            for row in rows {
+                let cell = row.get(column);
+                let value = match cell.map(|cell| cell.data_type) {
+                    None | Some(CellDataType::Empty) => None,
+                    Some(
+                        CellDataType::Number | CellDataType::Currency | CellDataType::Percentage,
+                    ) => {
+                        let text = cell?.content.trim();
+                        Some((parse_amount(text, locale.numbers)?, text.to_string()))
+                    }
+                    Some(_) => return None,
+                };
+                values.push(value);
+            }
+            (values.iter().flatten().count() >= 2).then(|| ColumnSeries {
+                column,
+                name: table
+                    .headers
+                    .get(column)
+                    .map(|cell| cell.content.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("Column {}", column + 1)),
+                values,
+            })
+        })
+        .collect()
+}
+
+/// A sparkline with one character per value, scaled from the lowest value to
+/// the highest; missing values are blank
+pub fn sparkline(values: &[Option<f64>]) -> String {
+    let known = values.iter().flatten();
+    let low = known.clone().copied().fold(f64::INFINITY, f64::min);
+    let high = known.copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|value| match value {
+            None => ' ',
+            Some(_) if high <= low => LEVELS[LEVELS.len() / 2],
+            Some(value) => {
+                let level = (value - low) / (high - low) * (LEVELS.len() - 1) as f64;
+                LEVELS[level.round() as usize]
+            }
+        })
+        .collect()
+}
+
+/// A bar `fraction` of `width` long, in eighths of a cell
+pub fn bar(fraction: f64, width: usize) -> String {
+    let eighths = (fraction.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    if let Some(part) = (eighths % 8).checked_sub(1) {
+        bar.push(EIGHTHS[part]);
+    }
+    bar
+}
+
+/// Charts of the table's numeric columns laid out to `width`, or nothing if
+/// it has none
+pub fn table_charts(
+    table: &TableData,
+    locale: CellLocale,
+    style: ChartStyle,
+    width: usize,
+) -> Vec<ChartLine> {
+    let columns = numeric_columns(table, locale);
+    if columns.is_empty() {
+        return Vec::new();
+    }
+    match style {
+        ChartStyle::Sparkline => sparklines(&columns, width),
+        ChartStyle::Bars => bar_charts(table, &columns, width),
+    }
+}
+
+/// Rows the charts take, the same as `table_charts(…).len()` without laying
+/// them out
+pub fn chart_height(table: &TableData, locale: CellLocale, style: ChartStyle) -> usize {
+    let columns = numeric_columns(table, locale);
+    match style {
+        ChartStyle::Sparkline => columns.len(),
+        ChartStyle::Bars => columns.iter().map(|series| 1 + series.values.len()).sum(),
+    }
+}
+
+fn sparklines(columns: &[ColumnSeries], width: usize) -> Vec<ChartLine> {
+    let label_width = label_width(columns.iter().map(|series| series.name.as_str()));
+    columns
+        .iter()
+        .map(|series| {
+            let numbers: Vec<Option<f64>> = series
+                .values
+                .iter()
+                .map(|value| value.as_ref().map(|(number, _)| *number))
+                .collect();
+            let known = || series.values.iter().flatten();
+            let low = known().min_by(|a, b| a.0.total_cmp(&b.0));
+            let high = known().max_by(|a, b| a.0.total_cmp(&b.0));
+            let value = match (low, high) {
+                (Some((_, low)), Some((_, high))) => format!("{low} – {high}"),
+                _ => String::new(),
+            };
+            // Squeeze a long column into the room there is, a value per slot
+            let room = width
+                .saturating_sub(label_width + value.width() + 4)
+                .max(MIN_GRAPH_WIDTH);
+            let numbers = if numbers.len() > room {
+                (0..room)
+                    .map(|slot| numbers[slot * numbers.len() / room])
+                    .collect()
+            } else {
+                numbers
+            };
+            ChartLine {
+                heading: false,
+                label: pad_label(&series.name, label_width),
+                graph: sparkline(&numbers),
+                value,
+            }
+        })
+        .collect()
+}
+
+fn bar_charts(table: &TableData, columns: &[ColumnSeries], width: usize) -> Vec<ChartLine> {
+    let labels: Vec<String> = charted_rows(table)
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            row.first()
+                .map(|cell| cell.content.trim().to_string())
+                .filter(|label| !label.is_empty())
+                .unwrap_or_else(|| format!("Row {}", index + 1))
+        })
+        .collect();
+    let label_width = label_width(labels.iter().map(String::as_str));
+
+    let mut lines = Vec::new();
+    for series in columns {
+        lines.push(ChartLine {
+            heading: true,
+            label: series.name.clone(),
+            graph: String::new(),
+            value: String::new(),
+        });
+        let value_width = series
+            .values
+            .iter()
+            .flatten()
+            .map(|(_, text)| text.width())
+            .max()
+            .unwrap_or(0);
+        let room = width
+            .saturating_sub(label_width + value_width + 6)
+            .max(MIN_GRAPH_WIDTH);
+        // Bars measure size, so a negative value's is as long as its magnitude
+        let largest = series
+            .values
+            .iter()
+            .flatten()
+            .map(|(number, _)| number.abs())
+            .fold(0.0, f64::max);
+        for (label, value) in labels.iter().zip(&series.values) {
+            let (graph, value) = match value {
+                Some((number, text)) if largest > 0.0 => {
+                    (bar(number.abs() / largest, room), text.clone())
+                }
+                Some((_, text)) => (String::new(), text.clone()),
+                None => (String::new(), String::new()),
+            };
+            lines.push(ChartLine {
+                heading: false,
+                label: format!("  {}", pad_label(label, label_width)),
+                graph: format!("{graph:<room$}"),
+                value,
+            });
+        }
+    }
+    lines
+}
+
+/// Width of the widest label, up to the limit
+fn label_width<'a>(labels: impl Iterator<Item = &'a str>) -> usize {
+    labels
+        .map(|label| label.width().min(MAX_LABEL_WIDTH))
+        .max()
+        .unwrap_or(0)
+}
+
+/// A label cut to `width` and padded out to it
+fn pad_label(label: &str, width: usize) -> String {
+    let mut padded = String::new();
+    let mut used = 0;
+    let cut = label.width() > width;
+    for c in label.chars() {
+        let c_width = unicode_width::UnicodeWidthChar::width(c).unwrap_or(0);
+        if used + c_width > width.saturating_sub(usize::from(cut)) {
+            break;
+        }
+        padded.push(c);
+        used += c_width;
+    }
+    if cut {
+        padded.push('…');
+        used += 1;
+    }
+    padded.push_str(&" ".repeat(width.saturating_sub(used)));
+    padded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline_scales_from_lowest_to_highest() {
+        assert_eq!(sparkline(&[Some(1.0), Some(8.0), None, Some(4.5)]), "▁█ ▅");
+        assert_eq!(sparkline(&[Some(3.0), Some(3.0)]), "▅▅");
+    }
+
+    #[test]
+    fn test_bars_draw_eighths() {
+        assert_eq!(bar(1.0, 4), "████");
+        assert_eq!(bar(0.5, 3), "█▌");
+        assert_eq!(bar(0.0, 4), "");
+    }
+
+    #[test]
+    fn test_labels_are_cut_with_an_ellipsis() {
+        assert_eq!(pad_label("Revenue", 9), "Revenue  ");
+        assert_eq!(pad_label("Customer Acquisition", 8), "Custome…");
+    }
+}
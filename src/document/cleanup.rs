@@ -4,6 +4,7 @@
 //! document elements after initial parsing.
 
 use super::models::*;
+use crate::Normalization;
 
 pub(crate) fn is_likely_sentence(text: &str) -> bool {
     let text = text.trim();
@@ -39,7 +40,14 @@ pub(crate) fn clean_word_list_markers(elements: Vec<DocumentElement>) -> Vec<Doc
     elements
         .into_iter()
         .map(|element| match element {
-            DocumentElement::Paragraph { runs } => {
+            DocumentElement::Paragraph {
+                runs,
+                alignment,
+                shading,
+                rtl,
+                spacing,
+                tab_stops,
+            } => {
                 let cleaned_runs = runs
                     .into_iter()
                     .map(|mut run| {
@@ -53,7 +61,14 @@ pub(crate) fn clean_word_list_markers(elements: Vec<DocumentElement>) -> Vec<Doc
                         run
                     })
                     .collect();
-                DocumentElement::Paragraph { runs: cleaned_runs }
+                DocumentElement::Paragraph {
+                    runs: cleaned_runs,
+                    alignment,
+                    shading,
+                    rtl,
+                    spacing,
+                    tab_stops,
+                }
             }
             DocumentElement::List { items, ordered } => {
                 let cleaned_items = items
@@ -90,3 +105,91 @@ pub(crate) fn clean_word_list_markers(elements: Vec<DocumentElement>) -> Vec<Doc
         })
         .collect()
 }
+
+/// Normalize the text of every element, for `--normalize`
+pub fn normalize_document(document: &mut Document, mode: Normalization) {
+    if mode == Normalization::None {
+        return;
+    }
+    let normalize = |text: &mut String| *text = normalize_text(text, mode);
+
+    normalize(&mut document.title);
+    for element in &mut document.elements {
+        match element {
+            DocumentElement::Heading { text, .. } => normalize(text),
+            DocumentElement::Paragraph { runs, .. } | DocumentElement::BlockQuote { runs } => {
+                runs.iter_mut().for_each(|run| normalize(&mut run.text))
+            }
+            DocumentElement::List { items, .. } => items
+                .iter_mut()
+                .flat_map(|item| &mut item.runs)
+                .for_each(|run| normalize(&mut run.text)),
+            DocumentElement::Table { table } => {
+                if let Some(title) = &mut table.metadata.title {
+                    normalize(title);
+                }
+                table
+                    .headers
+                    .iter_mut()
+                    .chain(table.rows.iter_mut().flatten())
+                    .for_each(|cell| {
+                        normalize(&mut cell.content);
+                        cell.runs
+                            .iter_mut()
+                            .for_each(|run| normalize(&mut run.text));
+                    });
+            }
+            DocumentElement::Image {
+                description,
+                caption,
+                ..
+            } => {
+                normalize(description);
+                if let Some(caption) = caption {
+                    normalize(caption);
+                }
+            }
+            // Code is kept exactly as written
+            DocumentElement::Equation { .. }
+            | DocumentElement::CodeBlock { .. }
+            | DocumentElement::PageBreak { .. }
+            | DocumentElement::HorizontalRule
+            | DocumentElement::FileBoundary { .. } => {}
+        }
+    }
+}
+
+/// Normalize a piece of text
+///
+/// Zero-width joiners and direction marks are kept: emoji, several scripts and
+/// right-to-left text need them.
+pub fn normalize_text(text: &str, mode: Normalization) -> String {
+    if mode == Normalization::None {
+        return text.to_string();
+    }
+    let mut normalized = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            // Soft hyphens, zero-width spaces, word joiners and byte order marks
+            '\u{00AD}' | '\u{200B}' | '\u{2060}' | '\u{FEFF}' | '\u{180E}' => {}
+            '\r' => {}
+            '\n' | '\t' => normalized.push(c),
+            c if c.is_control() => {}
+            // Non-breaking and fixed-width spaces
+            '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' => normalized.push(' '),
+            '\u{2028}' | '\u{2029}' => normalized.push('\n'),
+            _ if mode == Normalization::Ascii => match c {
+                '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' | '\u{2039}'
+                | '\u{203A}' => normalized.push('\''),
+                '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' | '\u{00AB}'
+                | '\u{00BB}' => normalized.push('"'),
+                '\u{2010}'..='\u{2013}' | '\u{2212}' => normalized.push('-'),
+                '\u{2014}' | '\u{2015}' => normalized.push_str("--"),
+                '\u{2026}' => normalized.push_str("..."),
+                _ => normalized.push(c),
+            },
+            _ => normalized.push(c),
+        }
+    }
+    normalized
+}
@@ -0,0 +1,106 @@
+//! Merging documents into one (`doxx a.docx b.docx --concat`)
+//!
+//! Each file's elements follow a boundary naming it, so a report split into
+//! chapter files reads, searches and navigates as a single document. The
+//! outline lists the files at the top level with their headings beneath.
+
+use std::path::Path;
+
+use super::models::*;
+
+/// Append documents one after another, each behind a file boundary
+///
+/// The merged document takes its title, path and properties from the first
+/// file; counts are added up and section ranges moved to where each file's
+/// elements ended up.
+pub fn concat_documents(documents: Vec<Document>) -> Option<Document> {
+    let mut documents = documents.into_iter();
+    let first = documents.next()?;
+    let mut merged = Document {
+        title: first.title.clone(),
+        metadata: DocumentMetadata {
+            file_size: 0,
+            word_count: 0,
+            page_count: 0,
+            ..first.metadata.clone()
+        },
+        elements: Vec::new(),
+        sections: Vec::new(),
+        image_options: first.image_options.clone(),
+        fidelity: Fidelity::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
+    };
+    for document in std::iter::once(first).chain(documents) {
+        append(&mut merged, document);
+    }
+    Some(merged)
+}
+
+fn append(merged: &mut Document, document: Document) {
+    let offset = merged.elements.len();
+    let headings_before = merged
+        .elements
+        .iter()
+        .filter(|element| matches!(element, DocumentElement::Heading { .. }))
+        .count();
+    merged.elements.push(DocumentElement::FileBoundary {
+        name: file_name(&document.metadata.file_path),
+    });
+    merged.elements.extend(document.elements);
+    let sections = document.sections.into_iter().enumerate();
+    merged
+        .sections
+        .extend(sections.map(|(index, section)| Section {
+            // The boundary belongs to the file's first section
+            start: if index == 0 {
+                offset
+            } else {
+                offset + 1 + section.start
+            },
+            end: offset + 1 + section.end,
+            ..section
+        }));
+
+    // Pages go on from where the files before left off
+    let pages_before = merged.metadata.page_count;
+    merged
+        .index_entries
+        .extend(document.index_entries.into_iter().map(|entry| IndexEntry {
+            page: pages_before + entry.page,
+            ..entry
+        }));
+    // Bookmark names are only unique within a file; the first file's one wins
+    for (name, heading) in document.heading_bookmarks {
+        merged
+            .heading_bookmarks
+            .entry(name)
+            .or_insert(headings_before + heading);
+    }
+    for (name, number) in document.equation_bookmarks {
+        merged.equation_bookmarks.entry(name).or_insert(number);
+    }
+    merged.volatile_fields += document.volatile_fields;
+    let (totals, counts) = (&mut merged.metadata, &document.metadata);
+    totals.file_size += counts.file_size;
+    totals.word_count += counts.word_count;
+    totals.page_count += counts.page_count;
+    let (totals, counts) = (&mut merged.fidelity, &document.fidelity);
+    totals.unsupported_drawings += counts.unsupported_drawings;
+    totals.charts += counts.charts;
+    totals.diagrams += counts.diagrams;
+    totals.embedded_objects += counts.embedded_objects;
+    totals.omitted_images += counts.omitted_images;
+    totals.flattened_tables += counts.flattened_tables;
+    totals.unevaluated_fields += counts.unevaluated_fields;
+}
+
+/// The file name a boundary shows, without the directories leading to it
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
@@ -0,0 +1,79 @@
+//! Matching the elements of a reloaded document against the previous load
+//!
+//! The viewer's `--watch` mode reloads the document whenever the file is saved.
+//! Elements are compared by fingerprint, so wrapped lines and decoded images
+//! can be kept for everything that didn't change, wherever it moved to.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+use super::models::DocumentElement;
+
+/// Hash of everything that affects how an element is displayed
+///
+/// Images are extracted to the same file on every load, so their fingerprint
+/// covers the bytes of the image file as well as the element itself.
+pub fn fingerprint(element: &DocumentElement) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(element)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    if let DocumentElement::Image {
+        image_path: Some(path),
+        ..
+    } = element
+    {
+        std::fs::read(path).unwrap_or_default().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// For each new element, the index of an unchanged element in the old document
+///
+/// Repeated elements (such as empty paragraphs) are paired up in order, so an
+/// old element is matched at most once.
+pub fn match_elements(old: &[u64], new: &[u64]) -> Vec<Option<usize>> {
+    let mut unmatched: HashMap<u64, VecDeque<usize>> = HashMap::new();
+    for (index, hash) in old.iter().enumerate() {
+        unmatched.entry(*hash).or_default().push_back(index);
+    }
+
+    new.iter()
+        .map(|hash| unmatched.get_mut(hash).and_then(VecDeque::pop_front))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unchanged_elements_are_matched_where_they_moved() {
+        // "b" was removed, "x" inserted and one of the two "a"s kept
+        let old = [1, 2, 1, 3];
+        let new = [9, 1, 3, 1];
+        assert_eq!(
+            match_elements(&old, &new),
+            vec![None, Some(0), Some(3), Some(2)]
+        );
+        assert_eq!(match_elements(&[1], &[1, 1]), vec![Some(0), None]);
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_content() {
+        let heading = |text: &str| DocumentElement::Heading {
+            level: 1,
+            text: text.to_string(),
+            number: None,
+        };
+        assert_eq!(
+            fingerprint(&heading("Intro")),
+            fingerprint(&heading("Intro"))
+        );
+        assert_ne!(
+            fingerprint(&heading("Intro")),
+            fingerprint(&heading("Outro"))
+        );
+    }
+}
@@ -0,0 +1,385 @@
+//! Revision-safe text extraction for legal holds and e-discovery
+//!
+//! Unlike normal loading, which shows a document the way Word displays it, this
+//! walks the raw WordprocessingML of every text-bearing part and keeps everything:
+//! hidden runs, deleted and inserted tracked changes, moved text, field codes,
+//! comments, footnotes, endnotes, headers and footers. Anything that isn't plain
+//! visible body text is labelled inline with where it came from, e.g.
+//! `[deleted by Alice, 2024-03-01T10:00:00Z: $10,000]`.
+
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::fmt::Write;
+use std::path::Path;
+
+use super::io::{validate_docx_extension, validate_docx_package, DocxPackage};
+
+/// Everything extracted from one document
+#[derive(Debug, Clone)]
+pub struct ForensicReport {
+    pub source: String,
+    /// Every part in the package, so reviewers can see what was not text-extracted
+    pub package_parts: Vec<String>,
+    pub parts: Vec<ForensicPart>,
+}
+
+/// The labelled text of one package part
+#[derive(Debug, Clone)]
+pub struct ForensicPart {
+    /// Part name inside the package, e.g. `word/header1.xml`
+    pub name: String,
+    /// What the part is: "Body", "Header", "Comments", ...
+    pub kind: &'static str,
+    /// One line per non-empty paragraph, with provenance labels inline
+    pub lines: Vec<String>,
+}
+
+/// Extract all text from a .docx, including revisions and hidden content
+pub fn extract_forensic_text(file_path: &Path) -> Result<ForensicReport> {
+    validate_docx_extension(file_path)?;
+    let package = DocxPackage::open(file_path)?;
+    validate_docx_package(&package)?;
+
+    // Headers first, then the body, footers and the annotation parts, which is
+    // the order a reader meets them on the page
+    let mut names = vec![];
+    names.extend(numbered_parts(&package, "word/header"));
+    names.push("word/document.xml".to_string());
+    names.extend(numbered_parts(&package, "word/footer"));
+    for name in [
+        "word/footnotes.xml",
+        "word/endnotes.xml",
+        "word/comments.xml",
+    ] {
+        if package.has_part(name) {
+            names.push(name.to_string());
+        }
+    }
+
+    let mut parts = Vec::new();
+    for name in names {
+        let Some(data) = package.part(&name)? else {
+            continue;
+        };
+        parts.push(ForensicPart {
+            kind: part_kind(&name),
+            lines: extract_part_lines(std::str::from_utf8(data)?)?,
+            name,
+        });
+    }
+
+    Ok(ForensicReport {
+        source: file_path.to_string_lossy().to_string(),
+        package_parts: package
+            .part_names_with_prefix("")
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        parts,
+    })
+}
+
+/// `word/header1.xml`, `word/header2.xml`, ... in numeric order
+pub(crate) fn numbered_parts(package: &DocxPackage, prefix: &str) -> Vec<String> {
+    let mut names: Vec<String> = package
+        .part_names_with_prefix(prefix)
+        .into_iter()
+        .filter(|name| name.ends_with(".xml") && !name.contains("/_rels/"))
+        .map(String::from)
+        .collect();
+    names.sort_by_key(|name| {
+        let digits: String = name.chars().filter(char::is_ascii_digit).collect();
+        digits.parse::<u32>().unwrap_or(0)
+    });
+    names
+}
+
+fn part_kind(name: &str) -> &'static str {
+    match name {
+        "word/document.xml" => "Body",
+        "word/footnotes.xml" => "Footnotes",
+        "word/endnotes.xml" => "Endnotes",
+        "word/comments.xml" => "Comments",
+        name if name.starts_with("word/header") => "Header",
+        name if name.starts_with("word/footer") => "Footer",
+        _ => "Part",
+    }
+}
+
+impl ForensicReport {
+    /// Plain-text rendering with one section per part
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "FORENSIC TEXT EXTRACTION");
+        let _ = writeln!(text, "Source: {}", self.source);
+        let _ = writeln!(text, "Package parts:");
+        for name in &self.package_parts {
+            let extracted = self.parts.iter().any(|part| &part.name == name);
+            let marker = if extracted { " (extracted)" } else { "" };
+            let _ = writeln!(text, "- {name}{marker}");
+        }
+
+        for part in &self.parts {
+            let _ = writeln!(text, "\n== {} ({}) ==", part.kind, part.name);
+            if part.lines.is_empty() {
+                let _ = writeln!(text, "(no text)");
+            }
+            for line in &part.lines {
+                let _ = writeln!(text, "{line}");
+            }
+        }
+        text
+    }
+}
+
+/// A tracked change enclosing the current position
+struct Revision {
+    kind: &'static str,
+    author: Option<String>,
+    date: Option<String>,
+}
+
+impl Revision {
+    fn label(&self) -> String {
+        let mut label = self.kind.to_string();
+        if let Some(author) = &self.author {
+            let _ = write!(label, " by {author}");
+        }
+        if let Some(date) = &self.date {
+            let _ = write!(label, ", {date}");
+        }
+        label
+    }
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
+
+/// A run property toggle such as `<w:vanish/>` is on unless `w:val` turns it off
+fn toggle_is_on(element: &BytesStart) -> bool {
+    !matches!(
+        attribute(element, b"w:val").as_deref(),
+        Some("0" | "false" | "off")
+    )
+}
+
+/// Labelled text for every paragraph of one part
+fn extract_part_lines(xml: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(false);
+
+    let mut lines = Vec::new();
+    // Element names from the root to the current element
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    let mut revisions: Vec<Revision> = Vec::new();
+    // Open paragraphs and their numbers (text boxes nest paragraphs inside paragraphs)
+    let mut paragraphs: Vec<(usize, Vec<Segment>)> = Vec::new();
+    let mut paragraph_number = 0;
+    let mut run_hidden = false;
+    // Depth of a subtree being skipped (Word's duplicate fallback content, note separators)
+    let mut skip_depth: Option<usize> = None;
+
+    let label_for = |stack: &[Vec<u8>], revisions: &[Revision], run_hidden: bool| {
+        let mut labels: Vec<String> = revisions.iter().map(Revision::label).collect();
+        if run_hidden {
+            labels.push("hidden".to_string());
+        }
+        if stack.iter().any(|name| name == b"m:oMath") {
+            labels.push("equation".to_string());
+        }
+        if stack.iter().any(|name| name == b"w:txbxContent") {
+            labels.push("text box".to_string());
+        }
+        labels.join("; ")
+    };
+
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let event = reader.read_event_into(&mut buf)?;
+        if let Some(depth) = skip_depth {
+            match event {
+                Event::Start(_) => stack.push(Vec::new()),
+                Event::End(_) => {
+                    stack.pop();
+                    if stack.len() < depth {
+                        skip_depth = None;
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Start(ref e) => {
+                let name = e.name().as_ref().to_vec();
+                match name.as_slice() {
+                    b"mc:Fallback" => skip_depth = Some(stack.len() + 1),
+                    b"w:footnote" | b"w:endnote"
+                        if attribute(e, b"w:type").is_some_and(|t| t != "normal") =>
+                    {
+                        skip_depth = Some(stack.len() + 1);
+                    }
+                    b"w:comment" | b"w:footnote" | b"w:endnote" => {
+                        let kind = match name.as_slice() {
+                            b"w:comment" => "comment",
+                            b"w:footnote" => "footnote",
+                            _ => "endnote",
+                        };
+                        let mut heading = format!("[{kind}");
+                        if let Some(id) = attribute(e, b"w:id") {
+                            let _ = write!(heading, " {id}");
+                        }
+                        if let Some(author) = attribute(e, b"w:author") {
+                            let _ = write!(heading, " by {author}");
+                        }
+                        if let Some(date) = attribute(e, b"w:date") {
+                            let _ = write!(heading, ", {date}");
+                        }
+                        heading.push(']');
+                        lines.push(heading);
+                    }
+                    b"w:del" | b"w:ins" | b"w:moveFrom" | b"w:moveTo" => {
+                        revisions.push(Revision {
+                            kind: match name.as_slice() {
+                                b"w:del" => "deleted",
+                                b"w:ins" => "inserted",
+                                b"w:moveFrom" => "moved from",
+                                _ => "moved to",
+                            },
+                            author: attribute(e, b"w:author"),
+                            date: attribute(e, b"w:date"),
+                        });
+                    }
+                    b"w:p" => {
+                        paragraph_number += 1;
+                        paragraphs.push((paragraph_number, Vec::new()));
+                    }
+                    b"w:r" => run_hidden = false,
+                    _ => {}
+                }
+                stack.push(name);
+            }
+            Event::Empty(ref e) => {
+                let name = e.name();
+                let in_run_properties = stack.len() >= 2
+                    && stack[stack.len() - 1] == b"w:rPr"
+                    && stack[stack.len() - 2] == b"w:r";
+                let label = label_for(&stack, &revisions, run_hidden);
+                let paragraph = paragraphs.last_mut().map(|(_, segments)| segments);
+                match name.as_ref() {
+                    b"w:vanish" | b"w:specVanish" | b"w:webHidden" if in_run_properties => {
+                        run_hidden = toggle_is_on(e);
+                    }
+                    b"w:tab" if stack.last().is_some_and(|n| n == b"w:r") => {
+                        if let Some(paragraph) = paragraph {
+                            push_segment(paragraph, label, "\t");
+                        }
+                    }
+                    b"w:br" | b"w:cr" if stack.last().is_some_and(|n| n == b"w:r") => {
+                        if let Some(paragraph) = paragraph {
+                            push_segment(paragraph, label, " ");
+                        }
+                    }
+                    b"w:commentReference" | b"w:footnoteReference" | b"w:endnoteReference" => {
+                        let kind = match name.as_ref() {
+                            b"w:commentReference" => "comment",
+                            b"w:footnoteReference" => "footnote",
+                            _ => "endnote",
+                        };
+                        let id = attribute(e, b"w:id").unwrap_or_default();
+                        if let Some(paragraph) = paragraph {
+                            paragraph.push(Segment::Marker(format!("[{kind} {id}]")));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(ref e) => {
+                let Some(element) = stack.last() else {
+                    continue;
+                };
+                let field = match element.as_slice() {
+                    b"w:t" | b"w:delText" | b"m:t" => false,
+                    b"w:instrText" | b"w:delInstrText" => true,
+                    _ => continue,
+                };
+                let text = e.unescape()?.into_owned();
+                let mut label = label_for(&stack, &revisions, run_hidden);
+                if field {
+                    label = if label.is_empty() {
+                        "field code".to_string()
+                    } else {
+                        format!("{label}; field code")
+                    };
+                }
+                if let Some((_, paragraph)) = paragraphs.last_mut() {
+                    push_segment(paragraph, label, &text);
+                }
+            }
+            Event::End(ref e) => {
+                match e.name().as_ref() {
+                    b"w:del" | b"w:ins" | b"w:moveFrom" | b"w:moveTo" => {
+                        revisions.pop();
+                    }
+                    b"w:p" => {
+                        if let Some((number, segments)) = paragraphs.pop() {
+                            let line = render_segments(&segments);
+                            if !line.trim().is_empty() {
+                                lines.push(format!("[p{number}] {line}"));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                stack.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(lines)
+}
+
+/// A piece of paragraph text
+enum Segment {
+    /// Text with its provenance label (empty for plain visible text)
+    Text { label: String, text: String },
+    /// A reference to a comment or note
+    Marker(String),
+}
+
+/// Append text to a paragraph, merging with the previous segment if it has the same label
+fn push_segment(paragraph: &mut Vec<Segment>, label: String, text: &str) {
+    match paragraph.last_mut() {
+        Some(Segment::Text {
+            label: last_label,
+            text: last_text,
+        }) if *last_label == label => last_text.push_str(text),
+        _ => paragraph.push(Segment::Text {
+            label,
+            text: text.to_string(),
+        }),
+    }
+}
+
+fn render_segments(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Text { label, text } if label.is_empty() => text.clone(),
+            Segment::Text { label, text } => format!("[{label}: {text}]"),
+            Segment::Marker(marker) => marker.clone(),
+        })
+        .collect()
+}
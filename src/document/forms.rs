@@ -0,0 +1,495 @@
+//! Form fields and their values for `doxx form`
+//!
+//! Word has two kinds of form field. Legacy ones are `FORMTEXT`,
+//! `FORMCHECKBOX` and `FORMDROPDOWN` fields whose `w:ffData` gives the name,
+//! the options and, for check boxes and drop-downs, the state; a text field's
+//! value is its result. Content controls (`w:sdt`) carry a title and tag,
+//! their list items or date format, and show their value as their content, or
+//! placeholder text until something is filled in. Both are read from
+//! `document.xml` in document order, so filled-in forms can be processed by
+//! scripts.
+
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+use std::path::Path;
+
+use super::io::{read_document_xml, validate_docx_extension, validate_docx_package, DocxPackage};
+
+/// What kind of field it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FormFieldKind {
+    Text,
+    RichText,
+    Checkbox,
+    Dropdown,
+    ComboBox,
+    Date,
+    Picture,
+}
+
+impl FormFieldKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::RichText => "rich text",
+            Self::Checkbox => "checkbox",
+            Self::Dropdown => "dropdown",
+            Self::ComboBox => "combo box",
+            Self::Date => "date",
+            Self::Picture => "picture",
+        }
+    }
+}
+
+/// One choice of a drop-down or combo box
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormOption {
+    /// What the list shows
+    pub text: String,
+    /// What the control stores when it's chosen, if not the text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// A form field with its current value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormField {
+    /// The legacy field's bookmark name or the control's title, else its tag
+    pub name: String,
+    /// The content control's tag
+    pub tag: Option<String>,
+    pub kind: FormFieldKind,
+    /// A `FORMTEXT`, `FORMCHECKBOX` or `FORMDROPDOWN` field rather than a
+    /// content control
+    pub legacy: bool,
+    pub options: Vec<FormOption>,
+    /// How a date is shown (`d MMMM yyyy`)
+    pub format: Option<String>,
+    /// The text shown, `true` or `false` for a check box, and the date as
+    /// `YYYY-MM-DD` where the control has it; none while it's empty or shows
+    /// its placeholder
+    pub value: Option<String>,
+}
+
+impl FormField {
+    fn content_control() -> Self {
+        Self {
+            name: String::new(),
+            tag: None,
+            kind: FormFieldKind::RichText,
+            legacy: false,
+            options: Vec::new(),
+            format: None,
+            value: None,
+        }
+    }
+
+    fn legacy() -> Self {
+        Self {
+            legacy: true,
+            kind: FormFieldKind::Text,
+            ..Self::content_control()
+        }
+    }
+}
+
+/// The form fields of a document
+#[derive(Debug, Clone, Serialize)]
+pub struct FormReport {
+    pub source: String,
+    pub fields: Vec<FormField>,
+}
+
+impl FormReport {
+    /// The report as text, a line to a field
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("Form fields in {}", self.source), String::new()];
+        if self.fields.is_empty() {
+            lines.push("No form fields".to_string());
+            return lines;
+        }
+        for field in &self.fields {
+            let mut line = if field.name.is_empty() {
+                "(unnamed)".to_string()
+            } else {
+                field.name.clone()
+            };
+            if let Some(tag) = field.tag.as_ref().filter(|tag| **tag != field.name) {
+                line.push_str(&format!(" [{tag}]"));
+            }
+            let mut about = vec![field.kind.label().to_string()];
+            if field.legacy {
+                about.push("legacy".to_string());
+            }
+            about.extend(field.format.clone());
+            line.push_str(&format!(" ({})", about.join(", ")));
+            let value = field.value.as_deref().unwrap_or("(empty)");
+            line.push_str(&format!(": {}", value.replace('\n', " / ")));
+            lines.push(line);
+            if !field.options.is_empty() {
+                let options: Vec<&str> = field.options.iter().map(|o| o.text.as_str()).collect();
+                lines.push(format!("    options: {}", options.join(", ")));
+            }
+        }
+        let filled = self
+            .fields
+            .iter()
+            .filter(|field| field.value.is_some())
+            .count();
+        lines.push(String::new());
+        lines.push(format!(
+            "{} field{}, {filled} filled",
+            self.fields.len(),
+            if self.fields.len() == 1 { "" } else { "s" }
+        ));
+        lines
+    }
+
+    /// The fields as CSV rows under a header, options separated by `|`
+    pub fn csv_lines(&self) -> Vec<String> {
+        let header = [
+            "name", "tag", "kind", "legacy", "format", "value", "options",
+        ];
+        let mut lines = vec![header.join(",")];
+        for field in &self.fields {
+            let options: Vec<&str> = field.options.iter().map(|o| o.text.as_str()).collect();
+            let row = [
+                field.name.clone(),
+                field.tag.clone().unwrap_or_default(),
+                field.kind.label().replace(' ', "-"),
+                field.legacy.to_string(),
+                field.format.clone().unwrap_or_default(),
+                field.value.clone().unwrap_or_default(),
+                options.join("|"),
+            ];
+            let row: Vec<String> = row
+                .iter()
+                .map(|cell| crate::export::csv::escape_field(cell, ','))
+                .collect();
+            lines.push(row.join(","));
+        }
+        lines
+    }
+}
+
+/// Read the form fields of a .docx file
+pub fn form_report(file_path: &Path) -> Result<FormReport> {
+    validate_docx_extension(file_path)?;
+    let package = DocxPackage::open(file_path)?;
+    validate_docx_package(&package)?;
+    Ok(FormReport {
+        source: file_path.display().to_string(),
+        fields: scan_forms(read_document_xml(&package)?)?,
+    })
+}
+
+/// A content control being read
+struct OpenControl {
+    field: FormField,
+    /// Whether it's a form field rather than a group, gallery or citation
+    is_field: bool,
+    placeholder: bool,
+    /// The stored date, `2024-03-05T00:00:00Z`
+    full_date: Option<String>,
+    in_content: bool,
+    text: String,
+}
+
+/// A legacy field being read, or a field that isn't a form field
+struct OpenLegacy {
+    field: Option<FormField>,
+    /// Index of the drop-down's chosen entry
+    result: usize,
+    checked: Option<bool>,
+    default_checked: bool,
+    in_result: bool,
+    text: String,
+}
+
+/// Form fields in `document.xml`, in document order
+fn scan_forms(document_xml: &str) -> Result<Vec<FormField>> {
+    let mut reader = Reader::from_str(document_xml);
+    // Fields are listed in the order they start; each holds its place here
+    let mut fields: Vec<Option<FormField>> = Vec::new();
+    let mut controls: Vec<(usize, OpenControl)> = Vec::new();
+    let mut legacy: Vec<(usize, OpenLegacy)> = Vec::new();
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    let mut in_text = false;
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(e) | Event::Empty(e) => {
+                let empty = matches!(event, Event::Empty(_));
+                let name = e.name().as_ref().to_vec();
+                let parent = stack.last().map(Vec::as_slice);
+                let in_properties = stack.iter().any(|name| name == b"w:sdtPr");
+                let in_ff_data = stack.iter().any(|name| name == b"w:ffData");
+                match name.as_slice() {
+                    b"w:sdt" if !empty => {
+                        fields.push(None);
+                        controls.push((
+                            fields.len() - 1,
+                            OpenControl {
+                                field: FormField::content_control(),
+                                is_field: true,
+                                placeholder: false,
+                                full_date: None,
+                                in_content: false,
+                                text: String::new(),
+                            },
+                        ));
+                    }
+                    b"w:sdtContent" if !empty => {
+                        if let Some((_, control)) = controls.last_mut() {
+                            control.in_content = true;
+                        }
+                    }
+                    _ if in_properties && parent == Some(b"w:sdtPr") => {
+                        if let Some((_, control)) = controls.last_mut() {
+                            control_property(control, &name, e);
+                        }
+                    }
+                    b"w:listItem" if in_properties => {
+                        if let Some((_, control)) = controls.last_mut() {
+                            let text = attr(e, b"w:displayText").or_else(|| attr(e, b"w:value"));
+                            let value = attr(e, b"w:value").filter(|v| Some(v) != text.as_ref());
+                            control.field.options.push(FormOption {
+                                text: text.unwrap_or_default(),
+                                value,
+                            });
+                        }
+                    }
+                    b"w:dateFormat" if in_properties => {
+                        if let Some((_, control)) = controls.last_mut() {
+                            control.field.format = attr(e, b"w:val");
+                        }
+                    }
+                    b"w14:checked" if in_properties => {
+                        if let Some((_, control)) = controls.last_mut() {
+                            control.field.value = Some(is_on(e).to_string());
+                        }
+                    }
+                    b"w:fldChar" => match attr(e, b"w:fldCharType").as_deref() {
+                        Some("begin") => {
+                            fields.push(None);
+                            legacy.push((
+                                fields.len() - 1,
+                                OpenLegacy {
+                                    field: None,
+                                    result: 0,
+                                    checked: None,
+                                    default_checked: false,
+                                    in_result: false,
+                                    text: String::new(),
+                                },
+                            ));
+                        }
+                        Some("separate") => {
+                            if let Some((_, field)) = legacy.last_mut() {
+                                field.in_result = true;
+                            }
+                        }
+                        Some("end") => {
+                            if let Some((slot, open)) = legacy.pop() {
+                                fields[slot] = finish_legacy(open);
+                            }
+                        }
+                        _ => {}
+                    },
+                    b"w:ffData" => {
+                        if let Some((_, open)) = legacy.last_mut() {
+                            open.field = Some(FormField::legacy());
+                        }
+                    }
+                    _ if in_ff_data => {
+                        if let Some((_, open)) = legacy.last_mut() {
+                            legacy_property(open, &name, parent, e);
+                        }
+                    }
+                    b"w:t" if !empty => in_text = true,
+                    b"w:tab" | b"w:br" if !in_properties => push_text(
+                        &mut controls,
+                        &mut legacy,
+                        if name == b"w:tab" { "\t" } else { "\n" },
+                    ),
+                    _ => {}
+                }
+                if !empty {
+                    stack.push(name);
+                }
+            }
+            Event::End(e) => {
+                stack.pop();
+                match e.name().as_ref() {
+                    b"w:t" => in_text = false,
+                    b"w:p" => {
+                        // Paragraphs in a control's content are lines of its value
+                        push_text(&mut controls, &mut legacy, "\n");
+                    }
+                    b"w:sdtContent" => {
+                        if let Some((_, control)) = controls.last_mut() {
+                            control.in_content = false;
+                        }
+                    }
+                    b"w:sdt" => {
+                        if let Some((slot, control)) = controls.pop() {
+                            fields[slot] = finish_control(control);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(text) if in_text => {
+                push_text(&mut controls, &mut legacy, &text.unescape()?)
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(fields.into_iter().flatten().collect())
+}
+
+/// Add text to the content of the open controls and the result of the
+/// innermost field
+fn push_text(
+    controls: &mut [(usize, OpenControl)],
+    legacy: &mut [(usize, OpenLegacy)],
+    text: &str,
+) {
+    for (_, control) in controls.iter_mut().filter(|(_, c)| c.in_content) {
+        control.text.push_str(text);
+    }
+    if let Some((_, open)) = legacy.last_mut().filter(|(_, open)| open.in_result) {
+        open.text.push_str(text);
+    }
+}
+
+/// A child of `w:sdtPr`
+fn control_property(control: &mut OpenControl, name: &[u8], e: &BytesStart) {
+    let field = &mut control.field;
+    match name {
+        b"w:alias" => field.name = attr(e, b"w:val").unwrap_or_default(),
+        b"w:tag" => field.tag = attr(e, b"w:val"),
+        b"w:showingPlcHdr" => control.placeholder = is_on(e),
+        b"w:text" => field.kind = FormFieldKind::Text,
+        b"w:dropDownList" => field.kind = FormFieldKind::Dropdown,
+        b"w:comboBox" => field.kind = FormFieldKind::ComboBox,
+        b"w:picture" => field.kind = FormFieldKind::Picture,
+        b"w14:checkbox" => field.kind = FormFieldKind::Checkbox,
+        b"w:date" => {
+            field.kind = FormFieldKind::Date;
+            control.full_date = attr(e, b"w:fullDate");
+        }
+        b"w:docPartObj"
+        | b"w:docPartList"
+        | b"w:group"
+        | b"w:citation"
+        | b"w:bibliography"
+        | b"w:equation"
+        | b"w15:repeatingSection"
+        | b"w15:repeatingSectionItem" => control.is_field = false,
+        _ => {}
+    }
+}
+
+/// A part of `w:ffData`
+fn legacy_property(open: &mut OpenLegacy, name: &[u8], parent: Option<&[u8]>, e: &BytesStart) {
+    let Some(field) = &mut open.field else {
+        return;
+    };
+    match (parent, name) {
+        (_, b"w:name") => field.name = attr(e, b"w:val").unwrap_or_default(),
+        (_, b"w:checkBox") => field.kind = FormFieldKind::Checkbox,
+        (_, b"w:ddList") => field.kind = FormFieldKind::Dropdown,
+        (Some(b"w:checkBox"), b"w:checked") => open.checked = Some(is_on(e)),
+        (Some(b"w:checkBox"), b"w:default") => {
+            open.default_checked = attr(e, b"w:val").is_some_and(|v| v == "1" || v == "true")
+        }
+        (Some(b"w:ddList"), b"w:result") => {
+            open.result = attr(e, b"w:val").and_then(|v| v.parse().ok()).unwrap_or(0)
+        }
+        (Some(b"w:ddList"), b"w:listEntry") => field.options.push(FormOption {
+            text: attr(e, b"w:val").unwrap_or_default(),
+            value: None,
+        }),
+        (Some(b"w:textInput"), b"w:format") => field.format = attr(e, b"w:val"),
+        _ => {}
+    }
+}
+
+fn finish_legacy(open: OpenLegacy) -> Option<FormField> {
+    let mut field = open.field?;
+    field.value = match field.kind {
+        FormFieldKind::Checkbox => Some(open.checked.unwrap_or(open.default_checked).to_string()),
+        FormFieldKind::Dropdown => field.options.get(open.result).map(|o| o.text.clone()),
+        _ => non_empty(&open.text),
+    };
+    Some(field)
+}
+
+fn finish_control(control: OpenControl) -> Option<FormField> {
+    if !control.is_field {
+        return None;
+    }
+    let mut field = control.field;
+    if field.name.is_empty() {
+        field.name = field.tag.clone().unwrap_or_default();
+    }
+    field.value = match field.kind {
+        FormFieldKind::Checkbox => field.value.or_else(|| Some(false.to_string())),
+        _ if control.placeholder => None,
+        FormFieldKind::Picture => None,
+        FormFieldKind::Date => control
+            .full_date
+            .map(|date| date.chars().take(10).collect())
+            .or_else(|| non_empty(&control.text)),
+        _ => non_empty(&control.text),
+    };
+    Some(field)
+}
+
+/// Text with surrounding whitespace and blank lines removed, unless nothing's left
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == name)
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+/// Whether an on/off property such as `w:checked` is on
+fn is_on(e: &BytesStart) -> bool {
+    let value = attr(e, b"w:val").or_else(|| attr(e, b"w14:val"));
+    !matches!(value.as_deref(), Some("0" | "false" | "off"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = r#"<w:document xmlns:w="w" xmlns:w14="w14"><w:body>
+<w:p><w:r><w:fldChar w:fldCharType="begin"><w:ffData><w:name w:val="Agree"/><w:checkBox><w:default w:val="0"/><w:checked/></w:checkBox></w:ffData></w:fldChar></w:r><w:r><w:instrText> FORMCHECKBOX </w:instrText></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>
+<w:p><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText> PAGE </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>3</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>
+<w:sdt><w:sdtPr><w:alias w:val="Notes"/><w:showingPlcHdr/></w:sdtPr><w:sdtContent><w:p><w:r><w:t>Click here</w:t></w:r></w:p></w:sdtContent></w:sdt>
+<w:sdt><w:sdtPr><w:docPartObj><w:docPartGallery w:val="Table of Contents"/></w:docPartObj></w:sdtPr><w:sdtContent><w:p><w:r><w:t>Contents</w:t></w:r></w:p></w:sdtContent></w:sdt>
+</w:body></w:document>"#;
+
+    #[test]
+    fn test_fields_that_are_not_form_fields_are_left_out() {
+        let fields = scan_forms(BODY).unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].name, "Agree");
+        assert_eq!(fields[0].value.as_deref(), Some("true"));
+        assert_eq!(fields[1].kind, FormFieldKind::RichText);
+        // Placeholder text isn't a value
+        assert_eq!(fields[1].value, None);
+    }
+}
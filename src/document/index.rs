@@ -0,0 +1,191 @@
+//! Index and table of authorities built from `XE` and `TA` fields, for `--index`
+//!
+//! Word builds both from fields marking the text: `XE "Contracts:breach"` for
+//! an index entry and its subentry, `TA \l "Smith v. Jones" \s "Smith" \c 1`
+//! for a citation. The fields show nothing in the text; the loader collects
+//! them and this module lists them with their pages, sorted as Word sorts
+//! them, at the end of the document.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::models::*;
+
+/// Word's names for the table of authorities' categories
+const CATEGORIES: [&str; 7] = [
+    "Cases",
+    "Statutes",
+    "Other Authorities",
+    "Rules",
+    "Treatises",
+    "Regulations",
+    "Constitutional Provisions",
+];
+
+/// An entry with the pages it's on and its subentries
+#[derive(Debug, Default)]
+struct Node {
+    /// The entry as first written
+    text: String,
+    pages: BTreeSet<usize>,
+    see: BTreeSet<String>,
+    /// By their text folded to lower case, so sorting ignores case
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    fn insert(&mut self, levels: &[String], entry: &IndexEntry) {
+        let Some((first, rest)) = levels.split_first() else {
+            match &entry.see {
+                Some(see) => self.see.insert(see.clone()),
+                None => self.pages.insert(entry.page),
+            };
+            return;
+        };
+        let child = self.children.entry(first.to_lowercase()).or_default();
+        if child.text.is_empty() {
+            child.text = first.clone();
+        }
+        child.insert(rest, entry);
+    }
+
+    /// List items for the subentries, nested by level
+    fn items(&self, level: u8, items: &mut Vec<ListItem>) {
+        for child in self.children.values() {
+            let mut text = child.text.clone();
+            for page in &child.pages {
+                text.push_str(&format!(", {page}"));
+            }
+            for see in &child.see {
+                text.push_str(&format!(", {see}"));
+            }
+            items.push(ListItem {
+                runs: vec![FormattedRun {
+                    text,
+                    formatting: TextFormatting::default(),
+                }],
+                level,
+            });
+            child.items(level.saturating_add(1), items);
+        }
+    }
+}
+
+/// The category's name in a table of authorities
+pub fn category_name(category: u8) -> String {
+    match CATEGORIES.get((category as usize).wrapping_sub(1)) {
+        Some(name) => name.to_string(),
+        None => format!("Category {category}"),
+    }
+}
+
+/// Headings and lists for the table of authorities and the index, each left
+/// out if nothing was marked for it
+pub fn index_elements(entries: &[IndexEntry]) -> Vec<DocumentElement> {
+    let mut elements = Vec::new();
+    let mut categories: BTreeMap<u8, Node> = BTreeMap::new();
+    let mut index = Node::default();
+    for entry in entries {
+        match entry.category {
+            Some(category) => categories.entry(category).or_default(),
+            None => &mut index,
+        }
+        .insert(&entry.levels, entry);
+    }
+    let list = |node: &Node| {
+        let mut items = Vec::new();
+        node.items(0, &mut items);
+        DocumentElement::List {
+            items,
+            ordered: false,
+        }
+    };
+
+    if !categories.is_empty() {
+        elements.push(DocumentElement::Heading {
+            level: 1,
+            text: "Table of Authorities".to_string(),
+            number: None,
+        });
+        for (category, node) in &categories {
+            elements.push(DocumentElement::Heading {
+                level: 2,
+                text: category_name(*category),
+                number: None,
+            });
+            elements.push(list(node));
+        }
+    }
+    if !index.children.is_empty() {
+        elements.push(DocumentElement::Heading {
+            level: 1,
+            text: "Index".to_string(),
+            number: None,
+        });
+        elements.push(list(&index));
+    }
+    elements
+}
+
+/// Put the table of authorities and index at the end of the document;
+/// returns how many entries went into them
+pub fn append_index(document: &mut Document) -> usize {
+    let elements = index_elements(&document.index_entries);
+    if !elements.is_empty() {
+        document.elements.push(DocumentElement::HorizontalRule);
+        document.elements.extend(elements);
+    }
+    document.index_entries.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(levels: &[&str], page: usize) -> IndexEntry {
+        IndexEntry {
+            levels: levels.iter().map(|level| level.to_string()).collect(),
+            category: None,
+            see: None,
+            page,
+        }
+    }
+
+    fn texts(element: &DocumentElement) -> Vec<(u8, String)> {
+        let DocumentElement::List { items, .. } = element else {
+            panic!("expected a list");
+        };
+        items
+            .iter()
+            .map(|item| (item.level, item.runs[0].text.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_entries_are_sorted_and_nested_with_their_pages() {
+        let see = IndexEntry {
+            see: Some("See Torts".to_string()),
+            ..entry(&["negligence"], 4)
+        };
+        let entries = [
+            entry(&["Contracts", "breach"], 3),
+            entry(&["contracts"], 2),
+            entry(&["Contracts", "breach"], 3),
+            entry(&["Contracts", "breach"], 1),
+            see,
+            entry(&["Arbitration"], 5),
+        ];
+        let elements = index_elements(&entries);
+        assert!(matches!(&elements[0], DocumentElement::Heading { text, .. } if text == "Index"));
+        assert_eq!(
+            texts(&elements[1]),
+            [
+                (0, "Arbitration, 5".to_string()),
+                (0, "Contracts, 2".to_string()),
+                (1, "breach, 1, 3".to_string()),
+                (0, "negligence, See Torts".to_string()),
+            ]
+        );
+        assert_eq!(category_name(2), "Statutes");
+        assert_eq!(category_name(9), "Category 9");
+    }
+}
@@ -0,0 +1,470 @@
+//! Source inspection for debugging misparsed documents
+//!
+//! Shows where an element came from: the `w:p` or `w:tbl` it was read from,
+//! the chain of paragraph styles it inherits, and its list numbering. The
+//! loader doesn't keep source positions, so elements are matched back to the
+//! body by their text, and tables, images and equations by their position
+//! among others of their kind.
+
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, Writer};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::io::{read_document_xml, validate_docx_package, DocxPackage};
+use super::models::*;
+use super::parsing::numbering::{scan_list_definitions, ListDefinitions};
+use super::parsing::styles::StyleSheet;
+
+/// One link in a paragraph's style chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleLink {
+    /// `w:styleId`, which paragraphs refer to
+    pub id: String,
+    /// The name Word shows, if the style is defined
+    pub name: Option<String>,
+}
+
+/// A paragraph's list numbering, resolved against `numbering.xml`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberingProperties {
+    pub num_id: i32,
+    pub level: u8,
+    /// The style the numbering comes from, when the paragraph doesn't set it
+    pub from_style: Option<String>,
+    /// `w:numFmt` of the level, if the list defines it
+    pub format: Option<String>,
+    /// `w:lvlText` of the level
+    pub text: Option<String>,
+    /// `w:start` of the level
+    pub start: Option<u32>,
+}
+
+/// Where one paragraph or table of an element came from
+#[derive(Debug, Clone)]
+pub struct ElementSource {
+    /// The stored XML, indented
+    pub xml: String,
+    /// The paragraph (or table) style and the styles it's based on, nearest first
+    pub style_chain: Vec<StyleLink>,
+    pub numbering: Option<NumberingProperties>,
+}
+
+/// An element and its sources; a list has one for each item
+#[derive(Debug, Clone)]
+pub struct ElementInspection {
+    pub element_index: usize,
+    pub kind: &'static str,
+    /// Empty when nothing in the body matched
+    pub sources: Vec<ElementSource>,
+}
+
+impl ElementInspection {
+    /// A plain-text report, as pasted into a bug report
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("Element {}: {}", self.element_index, self.kind)];
+        if self.sources.is_empty() {
+            lines.push("No matching w:p or w:tbl found in word/document.xml".to_string());
+        }
+        for (i, source) in self.sources.iter().enumerate() {
+            if self.sources.len() > 1 {
+                lines.push(format!("── Source {} of {}", i + 1, self.sources.len()));
+            }
+            let chain: Vec<String> = source
+                .style_chain
+                .iter()
+                .map(|link| match &link.name {
+                    Some(name) if name != &link.id => format!("{} ({name})", link.id),
+                    Some(_) => link.id.clone(),
+                    None => format!("{} (undefined)", link.id),
+                })
+                .collect();
+            lines.push(match chain.is_empty() {
+                true => "Style: none".to_string(),
+                false => format!("Style: {}", chain.join(" → ")),
+            });
+            if let Some(numbering) = &source.numbering {
+                let mut line = format!(
+                    "Numbering: numId {}, level {}",
+                    numbering.num_id, numbering.level
+                );
+                match (&numbering.format, &numbering.text) {
+                    (Some(format), Some(text)) => {
+                        line.push_str(&format!(": {format} \"{text}\""));
+                        if let Some(start) = numbering.start {
+                            line.push_str(&format!(" from {start}"));
+                        }
+                    }
+                    _ => line.push_str(" (not defined in numbering.xml)"),
+                }
+                if let Some(style) = &numbering.from_style {
+                    line.push_str(&format!(", from style {style}"));
+                }
+                lines.push(line);
+            }
+            lines.extend(source.xml.lines().map(str::to_string));
+        }
+        lines
+    }
+}
+
+/// A paragraph or table in the document body, as stored
+#[derive(Debug, Clone, Default)]
+struct SourceNode {
+    xml: String,
+    /// The text of its `w:t` runs, whitespace collapsed
+    text: String,
+    table: bool,
+    style: Option<String>,
+    num_id: Option<i32>,
+    level: Option<u8>,
+    drawing: bool,
+    math: bool,
+}
+
+/// What an element (or list item) is matched to the body by
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Probe {
+    Text(String),
+    Table,
+    Image,
+    Equation,
+}
+
+/// The parts of a package needed to inspect its elements
+pub struct SourceInspector {
+    nodes: Vec<SourceNode>,
+    styles: StyleSheet,
+    lists: ListDefinitions,
+}
+
+impl SourceInspector {
+    /// Read the body, styles and list definitions of a .docx file
+    pub fn open(file_path: &Path) -> Result<Self> {
+        let package = DocxPackage::open(file_path)?;
+        validate_docx_package(&package)?;
+        let nodes = scan_body(read_document_xml(&package)?)?;
+        let styles = match package.part("word/styles.xml")? {
+            Some(xml) => StyleSheet::parse(&String::from_utf8_lossy(xml))?,
+            None => StyleSheet::default(),
+        };
+        let lists = match package.part("word/numbering.xml")? {
+            Some(xml) => scan_list_definitions(&String::from_utf8_lossy(xml))?,
+            None => ListDefinitions::default(),
+        };
+        Ok(Self {
+            nodes,
+            styles,
+            lists,
+        })
+    }
+
+    /// Where an element of the loaded document came from
+    pub fn inspect(&self, document: &Document, element_index: usize) -> Option<ElementInspection> {
+        let element = document.elements.get(element_index)?;
+
+        // Repeated text (or the nth table) matches the nth node like it
+        let mut seen: HashMap<Probe, usize> = HashMap::new();
+        for earlier in &document.elements[..element_index] {
+            for probe in probes(earlier) {
+                *seen.entry(probe).or_default() += 1;
+            }
+        }
+        let sources = probes(element)
+            .into_iter()
+            .filter_map(|probe| {
+                let occurrence = seen.entry(probe.clone()).or_default();
+                let node = self.find(&probe, *occurrence);
+                *occurrence += 1;
+                node.map(|node| self.source(node))
+            })
+            .collect();
+
+        Some(ElementInspection {
+            element_index,
+            kind: element_kind(element),
+            sources,
+        })
+    }
+
+    /// The nth node an element matches, by its exact text if any node has it
+    ///
+    /// Evaluated fields, stripped heading numbers and list markers change the
+    /// text, so failing that it's the nth node containing the text, or whose
+    /// text ends the element's after a generated list number.
+    fn find(&self, probe: &Probe, occurrence: usize) -> Option<&SourceNode> {
+        let nth = |matches: &dyn Fn(&SourceNode) -> bool| {
+            self.nodes
+                .iter()
+                .filter(|node| matches(node))
+                .nth(occurrence)
+        };
+        match probe {
+            Probe::Text(text) => nth(&|node| !node.table && node.text == *text)
+                .or_else(|| {
+                    nth(&|node| {
+                        !node.table && !text.is_empty() && node.text.contains(text.as_str())
+                    })
+                })
+                .or_else(|| {
+                    nth(&|node| !node.table && !node.text.is_empty() && text.ends_with(&node.text))
+                }),
+            Probe::Table => nth(&|node| node.table),
+            Probe::Image => nth(&|node| !node.table && node.drawing),
+            Probe::Equation => nth(&|node| !node.table && node.math),
+        }
+    }
+
+    fn source(&self, node: &SourceNode) -> ElementSource {
+        let default = match node.table {
+            true => &self.styles.default_table,
+            false => &self.styles.default_paragraph,
+        };
+        let style_chain = self.style_chain(node.style.as_deref().or(default.as_deref()));
+        let numbering = match node.num_id {
+            Some(num_id) => Some((num_id, node.level.unwrap_or(0), None)),
+            // A style can number its paragraphs, as list and heading styles do
+            None => style_chain.iter().find_map(|link| {
+                let style = self.styles.style(&link.id)?;
+                Some((
+                    style.num_id?,
+                    style.level.unwrap_or(0),
+                    Some(link.id.clone()),
+                ))
+            }),
+        }
+        .map(|(num_id, level, from_style)| {
+            let definition = self.lists.level(num_id, level);
+            NumberingProperties {
+                num_id,
+                level,
+                from_style,
+                format: definition.map(|level| level.format.clone()),
+                text: definition.map(|level| level.text.clone()),
+                start: definition.map(|level| level.start),
+            }
+        });
+        ElementSource {
+            xml: indent_xml(&node.xml),
+            style_chain,
+            numbering,
+        }
+    }
+
+    /// A paragraph style and the styles it's based on, nearest first
+    fn style_chain(&self, style: Option<&str>) -> Vec<StyleLink> {
+        let mut chain = Vec::new();
+        let mut next = style;
+        for (id, definition) in self.styles.chain(style) {
+            chain.push(StyleLink {
+                id: id.to_string(),
+                name: definition.name.clone(),
+            });
+            next = definition.based_on.as_deref();
+        }
+        // A style named but not defined ends the chain
+        if let Some(id) = next.filter(|id| self.styles.style(id).is_none()) {
+            chain.push(StyleLink {
+                id: id.to_string(),
+                name: None,
+            });
+        }
+        chain
+    }
+}
+
+fn probes(element: &DocumentElement) -> Vec<Probe> {
+    let text = |runs: &[FormattedRun]| {
+        Probe::Text(collapse_whitespace(
+            &runs.iter().map(|run| run.text.as_str()).collect::<String>(),
+        ))
+    };
+    match element {
+        DocumentElement::Heading { text, .. } => vec![Probe::Text(collapse_whitespace(text))],
+        DocumentElement::Paragraph { runs, .. } | DocumentElement::BlockQuote { runs } => {
+            vec![text(runs)]
+        }
+        // A code block is read from one paragraph per line
+        DocumentElement::CodeBlock { code } => code
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Probe::Text(collapse_whitespace(line)))
+            .collect(),
+        DocumentElement::List { items, .. } => items.iter().map(|item| text(&item.runs)).collect(),
+        DocumentElement::Table { .. } => vec![Probe::Table],
+        DocumentElement::Image { .. } => vec![Probe::Image],
+        DocumentElement::Equation { .. } => vec![Probe::Equation],
+        DocumentElement::PageBreak { .. }
+        | DocumentElement::HorizontalRule
+        | DocumentElement::FileBoundary { .. } => Vec::new(),
+    }
+}
+
+fn element_kind(element: &DocumentElement) -> &'static str {
+    match element {
+        DocumentElement::Heading { .. } => "Heading",
+        DocumentElement::Paragraph { .. } => "Paragraph",
+        DocumentElement::BlockQuote { .. } => "Block quote",
+        DocumentElement::CodeBlock { .. } => "Code block",
+        DocumentElement::List { .. } => "List",
+        DocumentElement::Table { .. } => "Table",
+        DocumentElement::Image { .. } => "Image",
+        DocumentElement::Equation { .. } => "Equation",
+        DocumentElement::PageBreak { .. } => "Page break",
+        DocumentElement::HorizontalRule => "Horizontal rule",
+        DocumentElement::FileBoundary { .. } => "File boundary",
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn attr<T: std::str::FromStr>(e: &BytesStart, name: &[u8]) -> Option<T> {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == name)
+        .and_then(|attr| String::from_utf8_lossy(&attr.value).parse().ok())
+}
+
+/// The paragraphs and tables of the body, including those in content controls
+fn scan_body(document_xml: &str) -> Result<Vec<SourceNode>> {
+    const CONTAINERS: [&[u8]; 5] = [
+        b"w:document",
+        b"w:body",
+        b"w:sdt",
+        b"w:sdtContent",
+        b"w:customXml",
+    ];
+
+    let mut reader = Reader::from_str(document_xml);
+    let mut nodes = Vec::new();
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    // The node being read, where its XML starts and its depth in the stack
+    let mut current: Option<(SourceNode, usize, usize)> = None;
+    let mut in_text = false;
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(e) | Event::Empty(e) => {
+                let empty = matches!(event, Event::Empty(_));
+                let name = e.name().as_ref().to_vec();
+                if let Some((node, _, depth)) = &mut current {
+                    // The node's own properties, not those of a nested paragraph or table
+                    let properties: &[u8] = if node.table { b"w:tblPr" } else { b"w:pPr" };
+                    let in_properties =
+                        stack.get(*depth + 1).map(Vec::as_slice) == Some(properties);
+                    match name.as_slice() {
+                        b"w:pStyle" | b"w:tblStyle" if in_properties && node.style.is_none() => {
+                            node.style = attr(e, b"w:val");
+                        }
+                        b"w:numId" if in_properties && node.num_id.is_none() => {
+                            node.num_id = attr(e, b"w:val");
+                        }
+                        b"w:ilvl" if in_properties && node.level.is_none() => {
+                            node.level = attr(e, b"w:val");
+                        }
+                        b"w:t" => in_text = !empty,
+                        b"w:tab" | b"w:br" | b"w:cr" => node.text.push(' '),
+                        b"w:drawing" | b"w:pict" => node.drawing = true,
+                        b"m:oMath" | b"m:oMathPara" => node.math = true,
+                        _ => {}
+                    }
+                } else if matches!(name.as_slice(), b"w:p" | b"w:tbl")
+                    && stack.iter().any(|open| open == b"w:body")
+                    && stack
+                        .iter()
+                        .all(|open| CONTAINERS.contains(&open.as_slice()))
+                {
+                    let node = SourceNode {
+                        table: name == b"w:tbl",
+                        ..Default::default()
+                    };
+                    if empty {
+                        nodes.push(SourceNode {
+                            xml: document_xml[start..reader.buffer_position() as usize].to_string(),
+                            ..node
+                        });
+                    } else {
+                        current = Some((node, start, stack.len()));
+                    }
+                }
+                if !empty {
+                    stack.push(name);
+                }
+            }
+            Event::End(e) => {
+                stack.pop();
+                if e.name().as_ref() == b"w:t" {
+                    in_text = false;
+                }
+                if current
+                    .as_ref()
+                    .is_some_and(|(_, _, depth)| *depth == stack.len())
+                {
+                    if let Some((mut node, start, _)) = current.take() {
+                        node.xml =
+                            document_xml[start..reader.buffer_position() as usize].to_string();
+                        node.text = collapse_whitespace(&node.text);
+                        nodes.push(node);
+                    }
+                }
+            }
+            Event::Text(text) if in_text => {
+                if let Some((node, _, _)) = &mut current {
+                    node.text.push_str(&text.unescape()?);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(nodes)
+}
+
+/// Stored XML, one element to a line; text stays beside its tags
+fn indent_xml(xml: &str) -> String {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(Event::Text(text)) if text.iter().all(u8::is_ascii_whitespace) => {}
+            Ok(event) => {
+                if writer.write_event(event).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    String::from_utf8_lossy(&writer.into_inner()).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_nodes_skip_nested_paragraphs() {
+        let xml = r#"<w:document><w:body>
+            <w:p><w:pPr><w:pStyle w:val="Heading1"/><w:numPr><w:ilvl w:val="1"/><w:numId w:val="4"/></w:numPr></w:pPr><w:r><w:t xml:space="preserve">Intro  </w:t><w:tab/><w:t>duction</w:t></w:r></w:p>
+            <w:tbl><w:tr><w:tc><w:p><w:pPr><w:pStyle w:val="Cell"/></w:pPr><w:r><w:t>A &amp; B</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+            <w:sdt><w:sdtContent><w:p/></w:sdtContent></w:sdt>
+        </w:body></w:document>"#;
+        let nodes = scan_body(xml).unwrap();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].text, "Intro duction");
+        assert_eq!(nodes[0].style.as_deref(), Some("Heading1"));
+        assert_eq!((nodes[0].num_id, nodes[0].level), (Some(4), Some(1)));
+        assert!(nodes[0].xml.starts_with("<w:p>") && nodes[0].xml.ends_with("</w:p>"));
+        // A cell's paragraph belongs to its table
+        assert!(nodes[1].table);
+        assert_eq!(nodes[1].text, "A & B");
+        assert_eq!(nodes[1].style, None);
+        assert_eq!(nodes[2].xml, "<w:p/>");
+    }
+}
@@ -1,16 +1,276 @@
 //! File I/O operations and validation
 //!
-//! This module handles file validation and document merge operations.
+//! This module handles opening the .docx package, file validation, reading
+//! package parts, and document merge operations.
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use memmap2::Mmap;
+use std::collections::HashMap;
 use std::fs::File;
-use std::path::Path;
-use zip::ZipArchive;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
 
 use super::models::DocumentElement;
 
-/// Validates that the file is a legitimate .docx file
-pub(crate) fn validate_docx_file(file_path: &Path) -> Result<()> {
+/// Files at least this large are memory-mapped instead of read into a buffer
+const MMAP_THRESHOLD: u64 = 1024 * 1024;
+
+/// The most a buffer is sized for up front from a size the input declares
+pub(crate) const PREALLOCATION_LIMIT: u64 = 1024 * 1024;
+
+/// Parts that decompress to more than this are turned away as ZIP bombs
+const MAX_PART_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Backing storage for a package: a read-only file mapping or an owned buffer
+enum PackageBytes {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+/// Cheaply clonable handle to the package bytes, usable as a ZIP reader
+#[derive(Clone)]
+struct SharedBytes(Arc<PackageBytes>);
+
+impl AsRef<[u8]> for SharedBytes {
+    fn as_ref(&self) -> &[u8] {
+        match self.0.as_ref() {
+            PackageBytes::Mapped(mmap) => mmap,
+            PackageBytes::Owned(data) => data,
+        }
+    }
+}
+
+/// An opened .docx package shared by every parsing pass
+///
+/// The file is mapped (or, for small files, read) once. The ZIP central directory
+/// is parsed once, and each part is decompressed at most once, on first request,
+/// then handed out as a byte slice. docx-rs gets the raw package bytes; the
+/// equation scanner and image extractor read their parts from here.
+pub(crate) struct DocxPackage {
+    bytes: SharedBytes,
+    archive: ZipArchive<Cursor<SharedBytes>>,
+    parts: HashMap<String, OnceLock<Vec<u8>>>,
+}
+
+impl DocxPackage {
+    /// Open a package from disk, memory-mapping large files
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let len = file.metadata()?.len();
+
+        let bytes = if len >= MMAP_THRESHOLD {
+            // SAFETY: the mapping is read-only. As with any mmap-based reader, a file
+            // truncated by another process while we hold the map is outside our control.
+            match unsafe { Mmap::map(&file) } {
+                Ok(mmap) => PackageBytes::Mapped(mmap),
+                Err(_) => PackageBytes::Owned(read_all(file, len)?),
+            }
+        } else {
+            PackageBytes::Owned(read_all(file, len)?)
+        };
+
+        Self::from_storage(bytes)
+    }
+
+    /// Open a package held in memory
+    pub(crate) fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        Self::from_storage(PackageBytes::Owned(bytes))
+    }
+
+    fn from_storage(bytes: PackageBytes) -> Result<Self> {
+        let bytes = SharedBytes(Arc::new(bytes));
+        let archive = ZipArchive::new(Cursor::new(bytes.clone()))?;
+        let parts = archive
+            .file_names()
+            .map(|name| (name.to_string(), OnceLock::new()))
+            .collect();
+
+        Ok(Self {
+            bytes,
+            archive,
+            parts,
+        })
+    }
+
+    /// The raw package bytes
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_ref()
+    }
+
+    /// Size of the package in bytes
+    pub(crate) fn len(&self) -> u64 {
+        self.as_bytes().len() as u64
+    }
+
+    pub(crate) fn has_part(&self, name: &str) -> bool {
+        self.parts.contains_key(name)
+    }
+
+    /// Names of all parts under `prefix`, sorted
+    pub(crate) fn part_names_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .parts
+            .keys()
+            .map(String::as_str)
+            .filter(|name| name.starts_with(prefix))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Decompressed contents of a part, or `None` if the package has no such part
+    pub(crate) fn part(&self, name: &str) -> Result<Option<&[u8]>> {
+        let Some(cell) = self.parts.get(name) else {
+            return Ok(None);
+        };
+
+        if let Some(data) = cell.get() {
+            return Ok(Some(data));
+        }
+
+        // Clones share the parsed central directory, so this doesn't re-scan the ZIP
+        let mut archive = self.archive.clone();
+        let entry = archive.by_name(name)?;
+        // The declared size is the file's say-so, so it only sizes the buffer up to a point
+        let capacity = entry.size().min(self.len()).min(PREALLOCATION_LIMIT);
+        let mut data = Vec::with_capacity(capacity as usize);
+        entry.take(MAX_PART_SIZE + 1).read_to_end(&mut data)?;
+        if data.len() as u64 > MAX_PART_SIZE {
+            bail!("Invalid .docx file: {name} is too large to open");
+        }
+
+        // Another pass may have raced us here; either copy is identical
+        Ok(Some(cell.get_or_init(|| data)))
+    }
+
+    /// Whether the bytes hold more than one end-of-central-directory record
+    ///
+    /// ZIP readers disagree on which record to trust in that case, so docx-rs
+    /// may read different entries than the ones doxx opened and checked.
+    pub(crate) fn has_several_end_records(&self) -> bool {
+        const END_RECORD: &[u8] = b"PK\x05\x06";
+        self.as_bytes()
+            .windows(END_RECORD.len())
+            .filter(|window| *window == END_RECORD)
+            .nth(1)
+            .is_some()
+    }
+
+    /// A package of only the markup docx-rs parses, with `document_xml` in place
+    /// of `word/document.xml` if given
+    ///
+    /// Media and other binary parts are left out, since docx-rs has no need of
+    /// them, and the markup is copied still compressed, so this costs about as
+    /// much as the XML itself rather than a copy of the whole file. The entries
+    /// are the ones doxx opened, under a single end record.
+    pub(crate) fn markup_package(&self, document_xml: Option<&[u8]>) -> Result<Vec<u8>> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let mut archive = self.archive.clone();
+        for index in 0..archive.len() {
+            let entry = archive.by_index_raw(index)?;
+            let name = entry.name();
+            if !(name.ends_with(".xml") || name.ends_with(".rels")) {
+                continue;
+            }
+            match document_xml.filter(|_| name == "word/document.xml") {
+                Some(data) => {
+                    drop(entry);
+                    let options =
+                        SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+                    zip.start_file("word/document.xml", options)?;
+                    zip.write_all(data)?;
+                }
+                None => zip.raw_copy_file(entry)?,
+            }
+        }
+        Ok(zip.finish()?.into_inner())
+    }
+}
+
+/// Read the rest of an opened file, whose size is `len` bytes
+fn read_all(mut file: File, len: u64) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(len as usize);
+    file.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// A document path that can be opened, and opened again, like a regular file
+///
+/// ZIP archives are read by seeking, which pipes can't do, and some passes (image
+/// extraction, forensic text, repair) reopen the file. Input from a pipe or other
+/// non-regular file, as with `doxx <(curl -s …)`, is copied to a temporary file
+/// first, in a directory only this user can read that is created afresh with a
+/// random name, and removed with the copy when this is dropped.
+pub struct SeekableInput {
+    path: PathBuf,
+    temp: Option<tempfile::TempDir>,
+}
+
+impl SeekableInput {
+    pub fn open(path: &Path) -> Result<Self> {
+        let metadata = std::fs::metadata(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        if metadata.is_file() {
+            return Ok(Self {
+                path: path.to_path_buf(),
+                temp: None,
+            });
+        }
+        if metadata.is_dir() {
+            bail!("{} is a directory, not a .docx file", path.display());
+        }
+
+        let mut data = Vec::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut data))
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if data.is_empty() {
+            bail!("{} sent no data; expected a .docx file", path.display());
+        }
+        if !data.starts_with(b"PK") {
+            bail!(
+                "{} did not send a .docx file: the {} bytes read aren't a ZIP package",
+                path.display(),
+                data.len()
+            );
+        }
+
+        // Keep the input's name (`63` for /dev/fd/63), so the title still says where it came from
+        let dir = tempfile::Builder::new()
+            .prefix("doxx-input-")
+            .tempdir()
+            .context("Failed to create a temporary directory for the input")?;
+        let name = path.file_name().unwrap_or("input".as_ref());
+        let temp = dir.path().join(Path::new(name).with_extension("docx"));
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp)
+            .and_then(|mut file| file.write_all(&data))
+            .with_context(|| format!("Failed to write {}", temp.display()))?;
+        Ok(Self {
+            path: temp,
+            temp: Some(dir),
+        })
+    }
+
+    /// Path to open: the input itself, or the temporary copy of it
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the input was copied to a temporary file
+    pub fn is_buffered(&self) -> bool {
+        self.temp.is_some()
+    }
+}
+
+/// Validates that the file name looks like a .docx file, before anything is opened
+pub(crate) fn validate_docx_extension(file_path: &Path) -> Result<()> {
     // Check file extension
     let extension = file_path
         .extension()
@@ -25,13 +285,15 @@ pub(crate) fn validate_docx_file(file_path: &Path) -> Result<()> {
         );
     }
 
-    // Check ZIP structure contains word/document.xml
-    let file = File::open(file_path)?;
-    let mut archive = ZipArchive::new(file)?;
+    Ok(())
+}
 
-    if archive.by_name("word/document.xml").is_err() {
+/// Validates that the package is a Word document rather than some other ZIP
+pub(crate) fn validate_docx_package(package: &DocxPackage) -> Result<()> {
+    // Check ZIP structure contains word/document.xml
+    if !package.has_part("word/document.xml") {
         // Check if it might be an Excel file
-        if archive.by_name("xl/workbook.xml").is_ok() {
+        if package.has_part("xl/workbook.xml") {
             bail!(
                 "This appears to be an Excel file (.xlsx).\n\
                 doxx only supports Word documents (.docx)."
@@ -47,13 +309,97 @@ pub(crate) fn validate_docx_file(file_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Check the parts docx-rs reads decompress in full to at least a few bytes
+///
+/// docx-rs panics, rather than returning an error, on a part that's cut short
+/// or shorter than a byte-order mark, so such packages are turned away first.
+/// Parts are streamed, not kept; the passes that need them decompress them again.
+pub(crate) fn validate_package_parts(package: &DocxPackage) -> Result<()> {
+    let mut archive = package.archive.clone();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        let name = entry.name().to_string();
+        let read_by_docx_rs = name.starts_with("word/")
+            || matches!(
+                name.as_str(),
+                "[Content_Types].xml" | "_rels/.rels" | "docProps/custom.xml"
+            );
+        if !read_by_docx_rs || entry.is_dir() {
+            continue;
+        }
+        let size = std::io::copy(
+            &mut (&mut entry).take(MAX_PART_SIZE + 1),
+            &mut std::io::sink(),
+        )
+        .with_context(|| format!("Invalid .docx file: {name} is damaged"))?;
+        if size > MAX_PART_SIZE {
+            bail!("Invalid .docx file: {name} is too large to open");
+        }
+        if size < 3 {
+            bail!("Invalid .docx file: {name} is empty");
+        }
+    }
+    Ok(())
+}
+
+/// Read `word/document.xml` out of the package as text
+///
+/// The XML is read once and shared by every pass that needs the raw markup
+/// (inline and display equation scanning), instead of each pass reopening the ZIP.
+pub(crate) fn read_document_xml(package: &DocxPackage) -> Result<&str> {
+    let data = package
+        .part("word/document.xml")?
+        .context("Invalid .docx file: missing word/document.xml")?;
+    Ok(std::str::from_utf8(data)?)
+}
+
+/// The media file each image relationship in `document.xml` points at
+///
+/// Maps relationship IDs (a picture's `r:embed`) to file names in
+/// `word/media/`, the names the image extractor keys its images by. A
+/// missing or unreadable relationships part gives an empty map.
+pub(crate) fn read_image_targets(package: &DocxPackage) -> HashMap<String, String> {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    let Ok(Some(rels)) = package.part("word/_rels/document.xml.rels") else {
+        return HashMap::new();
+    };
+    let mut reader = Reader::from_reader(rels);
+    let mut buf = Vec::new();
+    let mut targets = HashMap::new();
+    while let Ok(event) = reader.read_event_into(&mut buf) {
+        match event {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                let attr = |name: &[u8]| {
+                    e.attributes()
+                        .flatten()
+                        .find(|attr| attr.key.as_ref() == name)
+                        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+                };
+                if let (Some(id), Some(target)) = (attr(b"Id"), attr(b"Target")) {
+                    if let Some(name) = target.strip_prefix("media/") {
+                        targets.insert(id, name.to_string());
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    targets
+}
+
 /// Merge display equations into the element list at their correct paragraph positions
 ///
 /// This function handles the fact that docx-rs doesn't parse paragraphs containing only equations.
 /// We need to track paragraph indices from the XML and insert equations at the right positions.
+/// `section_ends` (element indices) are moved along with the elements.
 pub(crate) fn merge_display_equations(
     elements: Vec<DocumentElement>,
     display_equations_by_para: std::collections::HashMap<usize, Vec<DocumentElement>>,
+    section_ends: &mut [usize],
 ) -> Vec<DocumentElement> {
     if display_equations_by_para.is_empty() {
         return elements;
@@ -66,13 +412,16 @@ pub(crate) fn merge_display_equations(
     // Build a new element list with equations inserted at correct positions
     let mut result = Vec::new();
     let mut element_para_index = 0;
+    let len = elements.len();
 
-    for element in elements {
+    for (index, element) in elements.into_iter().enumerate() {
         // Increment paragraph counter for elements that correspond to paragraphs
         match &element {
             DocumentElement::Paragraph { .. }
             | DocumentElement::Heading { .. }
-            | DocumentElement::List { .. } => {
+            | DocumentElement::List { .. }
+            | DocumentElement::BlockQuote { .. }
+            | DocumentElement::CodeBlock { .. } => {
                 element_para_index += 1;
 
                 // Insert any display equations that come before this element
@@ -90,9 +439,16 @@ pub(crate) fn merge_display_equations(
             _ => {}
         }
 
+        for end in section_ends.iter_mut().filter(|end| **end == index) {
+            *end = result.len();
+        }
         result.push(element);
     }
 
+    for end in section_ends.iter_mut().filter(|end| **end == len) {
+        *end = result.len();
+    }
+
     // Add any remaining equations at the end
     for eq_idx in eq_para_indices {
         if let Some(eqs) = display_equations_by_para.get(&eq_idx) {
@@ -102,3 +458,52 @@ pub(crate) fn merge_display_equations(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_parts_are_decompressed_once() {
+        let package = DocxPackage::open(Path::new("tests/fixtures/minimal.docx")).unwrap();
+        validate_docx_package(&package).unwrap();
+
+        let first = package.part("word/document.xml").unwrap().unwrap();
+        let second = package.part("word/document.xml").unwrap().unwrap();
+        assert_eq!(first.as_ptr(), second.as_ptr());
+        assert!(read_document_xml(&package).unwrap().contains("<w:body"));
+
+        assert!(package.part("word/missing.xml").unwrap().is_none());
+        assert_eq!(
+            package.len(),
+            std::fs::metadata("tests/fixtures/minimal.docx")
+                .unwrap()
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_markup_package_leaves_out_media() {
+        let package = DocxPackage::open(Path::new("tests/fixtures/images.docx")).unwrap();
+        assert!(!package.part_names_with_prefix("word/media/").is_empty());
+
+        let markup = DocxPackage::from_bytes(
+            package
+                .markup_package(Some(b"<w:document/>".as_slice()))
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(markup.part_names_with_prefix("word/media/").is_empty());
+        assert!(markup.has_part("word/styles.xml"));
+        assert_eq!(read_document_xml(&markup).unwrap(), "<w:document/>");
+    }
+
+    #[test]
+    fn test_part_names_with_prefix_sorted() {
+        let package = DocxPackage::open(Path::new("tests/fixtures/minimal.docx")).unwrap();
+        let names = package.part_names_with_prefix("word/");
+        assert!(names.contains(&"word/document.xml"));
+        assert!(names.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(package.part_names_with_prefix("xl/").is_empty());
+    }
+}
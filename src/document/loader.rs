@@ -4,117 +4,559 @@
 //! the entire document parsing process, coordinating all the specialized parsing
 //! modules to transform a DOCX file into our internal Document representation.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
 // Import types from the models module
 use super::models::*;
+use super::progress::{NoProgress, Phase, Progress};
+use crate::image_extractor::VectorImage;
 // Import I/O functions
-use super::io::{merge_display_equations, validate_docx_file};
+use super::io::{
+    merge_display_equations, read_document_xml, read_image_targets, validate_docx_extension,
+    validate_docx_package, validate_package_parts, DocxPackage, PREALLOCATION_LIMIT,
+};
 // Import cleanup functions
 use super::cleanup::{clean_word_list_markers, estimate_page_count};
+use super::words::count_words;
 // Import numbering management
 use super::parsing::numbering::{
-    analyze_heading_structure, DocumentNumberingManager, HeadingNumberTracker, NumberingFormat,
+    analyze_heading_structure, scan_list_definitions, DocumentNumberingManager,
+    HeadingNumberTracker, NumberingFormat,
 };
 // Import list processing
 use super::parsing::list::group_list_items;
+// Import field code evaluation
+use super::parsing::field::{FieldContext, FieldReader};
+// Import language detection
+use super::parsing::language::{document_language, extend_sample};
+// Import run coalescing
+use super::parsing::runs::coalesce_runs;
+// Import partial loading
+use super::parsing::range::{relationship_ids, slice_body};
+// Import paragraph, table and tab styles, read in one pass
+use super::parsing::styles::StyleSheet;
+// Import symbol font mapping
+use super::parsing::symbol::{run_child_text, SymbolFont};
+// Import quote and code block detection
+use super::parsing::block::BlockKind;
+// Import editing restrictions
+use super::parsing::protection::read_protection;
+// Import signature lines and watermarks
+use super::parsing::annotation::{annotation_run, scan_watermarks};
+// Import section page setup
+use super::parsing::caption::{attach_captions, caption_kind};
+use super::parsing::section::{build_sections, scan_section_breaks};
+use super::parsing::skipped::scan_skipped_content;
+// Import text boxes and frames
+use super::parsing::floating::{
+    picture_placement, scan_picture_layouts, text_box_floats, Float, FloatPosition,
+    DEFAULT_PAGE_HEIGHT, EMUS_PER_PIXEL,
+};
 // Import formatting and text extraction
-use super::parsing::formatting::extract_run_formatting;
+use super::parsing::formatting::{
+    extract_paragraph_alignment, extract_paragraph_text, extract_run_formatting,
+    scan_raw_paragraph_properties,
+};
 // Import heading detection
-use super::parsing::heading::{detect_heading_from_text, detect_heading_with_numbering};
+use super::parsing::heading::{
+    baseline_font_size, detect_heading_from_size, detect_heading_from_text,
+    detect_heading_with_numbering, paragraph_font_size,
+};
+// Import theme colour resolution
+use super::parsing::theme::parse_theme_colors;
 // Import table extraction
 use super::parsing::table::extract_table_data;
+// Import table borders and styles
+use super::parsing::table_style::scan_table_properties;
 // Import equation processing
 use super::parsing::equation::{
-    extract_equations_from_docx, extract_inline_equation_positions, ParagraphContent,
+    extract_equations_from_xml, extract_inline_equation_positions, ParagraphContent,
 };
 
 /// Main document loading function that orchestrates the entire parsing process
 ///
 /// This function:
-/// 1. Validates the DOCX file
+/// 1. Opens the DOCX package once (memory-mapped for large files) and validates it,
+///    merging runs of plain text that share their properties
 /// 2. Extracts metadata (title, file size, etc.)
-/// 3. Optionally extracts images
+/// 3. Runs the independent extraction passes in parallel (docx-rs body parsing,
+///    optional image extraction, inline and display equation scanning)
 /// 4. Processes document structure (paragraphs, tables, headings, lists)
 /// 5. Integrates equations (both inline and display)
 /// 6. Post-processes elements (grouping lists, cleaning markers)
 /// 7. Returns a fully parsed Document
+///
+/// Loading is synchronous and needs no async runtime; with the `async` feature,
+/// `load_document_async` runs it on tokio's blocking pool.
+#[allow(dead_code)] // Library entry point; the viewer passes field options
 pub fn load_document(file_path: &Path, image_options: ImageOptions) -> Result<Document> {
+    load_document_with_fields(file_path, image_options, FieldOptions::default())
+}
+
+/// [`load_document`], with a choice of how field codes are shown
+pub fn load_document_with_fields(
+    file_path: &Path,
+    image_options: ImageOptions,
+    field_options: FieldOptions,
+) -> Result<Document> {
+    load_document_with_progress(file_path, image_options, field_options, &NoProgress)
+}
+
+/// [`load_document_with_fields`], reporting each phase of the load to `progress`
+pub fn load_document_with_progress(
+    file_path: &Path,
+    image_options: ImageOptions,
+    field_options: FieldOptions,
+    progress: &dyn Progress,
+) -> Result<Document> {
     // Validate file type before attempting to parse
-    validate_docx_file(file_path)?;
+    validate_docx_extension(file_path)?;
 
-    let file_size = std::fs::metadata(file_path)?.len();
+    // Open the package once; every pass below reads its parts from here
+    progress.start(Phase::Opening, None);
+    let package = DocxPackage::open(file_path)?;
+    load_package(
+        &package,
+        Some(file_path),
+        image_options,
+        field_options,
+        progress,
+    )
+}
 
-    // For now, create a simple implementation that reads the docx file
-    // This is a simplified version to get the project compiling
-    let file_data = std::fs::read(file_path)?;
-    let docx = docx_rs::read_docx(&file_data)?;
+/// [`load_document`] on tokio's blocking thread pool, for async callers
+///
+/// Loading is CPU-bound, blocking work; this only keeps it off the async runtime's
+/// worker threads.
+#[cfg(feature = "async")]
+#[allow(dead_code)] // Library entry point; the viewer also handles repair mode
+pub async fn load_document_async(
+    file_path: &Path,
+    image_options: ImageOptions,
+) -> Result<Document> {
+    let file_path = file_path.to_path_buf();
+    tokio::task::spawn_blocking(move || load_document(&file_path, image_options)).await?
+}
+
+impl Document {
+    /// Parse a .docx package held in memory, such as an upload
+    ///
+    /// Nothing is read from or written to disk, except that extracted images are
+    /// written to a temporary directory when `image_options` enables them. With
+    /// no file name the title is "Untitled Document"; set `title` afterwards if
+    /// you know better.
+    #[allow(dead_code)] // Library entry point; the binary loads from a path
+    pub fn from_bytes(bytes: &[u8], image_options: ImageOptions) -> Result<Self> {
+        let package = DocxPackage::from_bytes(bytes.to_vec())
+            .context("Not a .docx file: the data isn't a ZIP package")?;
+        load_package(
+            &package,
+            None,
+            image_options,
+            FieldOptions::default(),
+            &NoProgress,
+        )
+    }
+
+    /// Parse a .docx package read from `reader`, from its current position to the end
+    #[allow(dead_code)] // Library entry point; the binary loads from a path
+    pub fn from_reader(mut reader: impl Read + Seek, image_options: ImageOptions) -> Result<Self> {
+        let start = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+
+        // A reader can claim any length, so it only sizes the buffer up to a point
+        let len = end.saturating_sub(start).min(PREALLOCATION_LIMIT);
+        let mut bytes = Vec::with_capacity(len as usize);
+        reader.read_to_end(&mut bytes)?;
+        let package = DocxPackage::from_bytes(bytes)
+            .context("Not a .docx file: the data isn't a ZIP package")?;
+        load_package(
+            &package,
+            None,
+            image_options,
+            FieldOptions::default(),
+            &NoProgress,
+        )
+    }
+}
 
+/// Words in the body's paragraphs, before anything is evaluated
+fn count_body_words(document: &docx_rs::Document) -> usize {
+    document
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            docx_rs::DocumentChild::Paragraph(para) => Some(para),
+            _ => None,
+        })
+        .flat_map(|para| &para.children)
+        .filter_map(|child| match child {
+            docx_rs::ParagraphChild::Run(run) => Some(run),
+            _ => None,
+        })
+        .flat_map(|run| &run.children)
+        .map(|child| match child {
+            docx_rs::RunChild::Text(text) => count_words(&text.text),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Extract the package's images, or with a range only those it shows
+fn extract_images(
+    package: &DocxPackage,
+    document_xml: &str,
+    field_options: FieldOptions,
+    progress: &dyn Progress,
+) -> Result<crate::image_extractor::ImageExtractor> {
+    let mut extractor = crate::image_extractor::ImageExtractor::new()?;
+    // Only the images a range shows are worth extracting
+    let shown: Option<HashSet<String>> = field_options.range.map(|_| {
+        let ids = relationship_ids(document_xml);
+        read_image_targets(package)
+            .into_iter()
+            .filter(|(id, _)| ids.contains(id))
+            .map(|(_, name)| format!("word/media/{name}"))
+            .collect()
+    });
+    let mut media = Vec::new();
+    for name in package.part_names_with_prefix("word/media/") {
+        if shown.as_ref().is_some_and(|shown| !shown.contains(name)) {
+            continue;
+        }
+        if let Some(data) = package.part(name)? {
+            media.push((name, data));
+        }
+    }
+    progress.start(Phase::Images, Some(media.len() as u64));
+    extractor.extract_images_from_parts(media.into_iter().enumerate().map(|(done, part)| {
+        progress.advance(Phase::Images, done as u64);
+        part
+    }))?;
+    progress.finish(Phase::Images);
+    Ok(extractor)
+}
+
+/// Parse an opened package; `file_path`, if there is one, only names the document
+pub(crate) fn load_package(
+    package: &DocxPackage,
+    file_path: Option<&Path>,
+    image_options: ImageOptions,
+    field_options: FieldOptions,
+    progress: &dyn Progress,
+) -> Result<Document> {
+    validate_docx_package(package)?;
+    validate_package_parts(package)?;
+    progress.finish(Phase::Opening);
+
+    let file_size = package.len();
+    let document_xml = read_document_xml(package)?;
+
+    // Cut the body down to the range asked for before anything parses it
+    let slice = field_options
+        .range
+        .map(|range| slice_body(document_xml, range))
+        .transpose()?;
+    let (sliced_xml, pages_before) = slice.map(|slice| (slice.xml, slice.pages)).unzip();
+    let document_xml = sliced_xml.as_deref().unwrap_or(document_xml);
+
+    // Merge tiny runs before docx-rs (or anything else) builds one object per run
+    let coalesced_xml = coalesce_runs(document_xml);
+    let rewritten_xml = coalesced_xml.as_ref().or(sliced_xml.as_ref());
+    let markup_package = match rewritten_xml {
+        Some(xml) => Some(package.markup_package(Some(xml.as_bytes()))?),
+        // docx-rs must see the same entries validate_package_parts checked
+        None if package.has_several_end_records() => Some(package.markup_package(None)?),
+        None => None,
+    };
+    let document_xml = coalesced_xml.as_deref().unwrap_or(document_xml);
+    let docx_bytes = markup_package.as_deref().unwrap_or(package.as_bytes());
+
+    // Theme colours are optional; a missing or unreadable theme leaves `w:val` in charge
+    let theme_colors = package
+        .part("word/theme/theme1.xml")
+        .ok()
+        .flatten()
+        .and_then(|xml| parse_theme_colors(&String::from_utf8_lossy(xml)).ok())
+        .unwrap_or_default();
+
+    // The passes are independent of each other, so fan them out across cores
+    let styles_xml = package.part("word/styles.xml").ok().flatten();
+    let mut docx = None;
+    let mut image_extractor = Ok(None);
+    let mut inline_paragraphs = Default::default();
+    let mut equation_infos = Vec::new();
+    let mut raw_paragraphs = Default::default();
+    let mut section_breaks = Vec::new();
+    let mut table_properties = Vec::new();
+    let mut style_sheet = StyleSheet::default();
+    let mut list_definitions = Default::default();
+    progress.start(Phase::Parsing, None);
+    rayon::scope(|scope| {
+        scope.spawn(|_| docx = Some(docx_rs::read_docx(docx_bytes)));
+        if image_options.enabled {
+            scope.spawn(|_| {
+                image_extractor =
+                    extract_images(package, document_xml, field_options, progress).map(Some);
+            });
+        }
+        // Extract inline equations with their positions
+        scope.spawn(|_| {
+            inline_paragraphs = extract_inline_equation_positions(document_xml).unwrap_or_default()
+        });
+        // Extract all equations (both inline and display)
+        scope.spawn(|_| {
+            equation_infos = extract_equations_from_xml(document_xml).unwrap_or_default()
+        });
+        // Drop caps and small caps, which docx-rs doesn't parse
+        scope.spawn(|_| {
+            raw_paragraphs = scan_raw_paragraph_properties(document_xml).unwrap_or_default()
+        });
+        // Page setup, which docx-rs only reads for the last section
+        scope.spawn(|_| section_breaks = scan_section_breaks(document_xml).unwrap_or_default());
+        // Table looks, which docx-rs drops
+        scope.spawn(|_| table_properties = scan_table_properties(document_xml).unwrap_or_default());
+        // Every style, read once for headings, spacing, blocks, tabs and tables
+        scope.spawn(|_| {
+            style_sheet = styles_xml
+                .and_then(|xml| StyleSheet::parse(&String::from_utf8_lossy(xml)).ok())
+                .unwrap_or_default()
+        });
+        scope.spawn(|_| {
+            list_definitions = package
+                .part("word/numbering.xml")
+                .ok()
+                .flatten()
+                .and_then(|xml| scan_list_definitions(&String::from_utf8_lossy(xml)).ok())
+                .unwrap_or_default()
+        });
+    });
+    let docx = docx.expect("docx-rs runs in the scope above")?;
+    let image_extractor = image_extractor?;
+    progress.finish(Phase::Parsing);
+    let (image_targets, mut picture_layouts) = match image_extractor {
+        Some(_) => (
+            read_image_targets(package),
+            scan_picture_layouts(document_xml).unwrap_or_default(),
+        ),
+        None => Default::default(),
+    };
+
+    let file_name = file_path
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_default();
     let title = file_path
-        .file_stem()
+        .and_then(|path| path.file_stem())
         .and_then(|s| s.to_str())
         .unwrap_or("Untitled Document")
         .to_string();
 
+    // The declared language, or failing that one detected from the opening text
+    let mut sample = String::new();
+    for child in &docx.document.children {
+        if let docx_rs::DocumentChild::Paragraph(para) = child {
+            if !extend_sample(&mut sample, &extract_paragraph_text(para)) {
+                break;
+            }
+        }
+    }
+    let language = document_language(
+        document_xml,
+        styles_xml.map(String::from_utf8_lossy).as_deref(),
+        &sample,
+    );
+    // How table cells write numbers and dates, unless given with `--locale`
+    let locale = field_options
+        .locale
+        .unwrap_or_else(|| language.as_ref().map(CellLocale::from).unwrap_or_default());
+
+    // Paragraphs set well above the body text size may be headings
+    let baseline_size = baseline_font_size(&docx.document, &style_sheet);
+    // Paragraph spacing in lines is only kept with `--spacing preserve`
+    let spacing_styles =
+        (field_options.spacing == crate::SpacingMode::Preserve).then_some(&style_sheet);
+
     let mut elements = Vec::new();
     let mut word_count = 0;
-    let mut numbering_manager = DocumentNumberingManager::new();
+    let mut fidelity = Fidelity::default();
+    let mut numbering_manager = DocumentNumberingManager::new(list_definitions);
     let mut heading_tracker = HeadingNumberTracker::new();
+    let mut pages = pages_before.unwrap_or_default();
 
     // Analyze document structure to determine if auto-numbering should be enabled
-    let should_auto_number = analyze_heading_structure(&docx.document);
+    let should_auto_number = analyze_heading_structure(&docx.document, &style_sheet);
     if should_auto_number {
         heading_tracker.enable_auto_numbering();
     }
 
-    // Extract images if enabled
-    let image_extractor = if image_options.enabled {
-        let mut extractor = crate::image_extractor::ImageExtractor::new()?;
-        extractor.extract_images_from_docx(file_path)?;
-        Some(extractor)
-    } else {
-        None
-    };
+    // Fields are evaluated against the document's estimated length
+    let mut fields = FieldReader::new(
+        FieldContext::new(&file_name, count_body_words(&docx.document)),
+        field_options,
+    );
+
+    // Drop cap letters, waiting to be joined to the paragraph they start
+    let mut drop_cap_runs: Vec<FormattedRun> = Vec::new();
+    let visual_order = field_options.reading_order == crate::ReadingOrder::Visual;
+    // Frames read after the paragraph they're positioned against, waiting for it
+    let mut deferred_frames: Vec<DocumentElement> = Vec::new();
+    let mut body_paragraph_index = 0;
+    let mut body_table_index = 0;
+    // Text of the caption paragraphs, and what each labels
+    let mut captions = HashMap::new();
+    // Images placed so far, which number them and pair drawings with
+    // extracted files when their relationships don't
+    let mut image_count = 0;
+    // Element index each section ends at, for the sections that end on a paragraph
+    let mut section_ends = Vec::new();
+    let mut heading_bookmarks = BTreeMap::new();
+    let mut pending_breaks = section_breaks
+        .iter()
+        .filter_map(|section| section.paragraph)
+        .peekable();
 
     // Enhanced content extraction with style information
-    for child in &docx.document.children {
+    progress.start(Phase::Building, Some(docx.document.children.len() as u64));
+    for (done, child) in docx.document.children.iter().enumerate() {
+        progress.advance(Phase::Building, done as u64);
+        while pending_breaks
+            .next_if(|&paragraph| paragraph < body_paragraph_index)
+            .is_some()
+        {
+            elements.append(&mut deferred_frames);
+            section_ends.push(elements.len());
+        }
+
+        // Where this paragraph or table's elements start, to place floats around them
+        let block_start = elements.len();
+        let page_height = section_breaks
+            .iter()
+            .find(|section| {
+                section
+                    .paragraph
+                    .is_none_or(|end| end >= body_paragraph_index)
+            })
+            .and_then(|section| section.page.height)
+            .unwrap_or(DEFAULT_PAGE_HEIGHT);
         match child {
             docx_rs::DocumentChild::Paragraph(para) => {
+                let raw_properties = raw_paragraphs
+                    .get(body_paragraph_index)
+                    .cloned()
+                    .unwrap_or_default();
+                body_paragraph_index += 1;
+                fields.context.set_words_read(word_count);
+                fields.sequence = None;
+
+                let page_breaks = pages.paragraph(
+                    &raw_properties.page_marks,
+                    para.property.page_break_before.unwrap_or(false),
+                );
+                elements.extend(
+                    page_breaks
+                        .before
+                        .iter()
+                        .map(|&page| DocumentElement::PageBreak { page }),
+                );
+
                 // Check for heading with potential numbering first
-                let heading_info = detect_heading_with_numbering(para);
+                let heading_info = detect_heading_with_numbering(para, &style_sheet);
 
                 // Check for list numbering properties (Word's automatic lists)
                 let list_info = detect_list_from_paragraph_numbering(para);
 
-                // Check for images in this paragraph first
+                // Check for images in this paragraph first; floating ones are
+                // placed beside it with the text boxes
+                let mut floating_images = Vec::new();
                 for child in &para.children {
                     if let docx_rs::ParagraphChild::Run(run) = child {
                         for run_child in &run.children {
-                            if let docx_rs::RunChild::Drawing(_drawing) = run_child {
+                            if let docx_rs::RunChild::Drawing(drawing) = run_child {
+                                // Text boxes are read as text below, not skipped
+                                let text_box =
+                                    matches!(drawing.data, Some(docx_rs::DrawingData::TextBox(_)));
                                 // Create an Image element with consistent ordering
-                                if let Some(ref extractor) = image_extractor {
-                                    let images = extractor.get_extracted_images_sorted();
-                                    if !images.is_empty() {
-                                        // Count images processed so far to maintain document order
-                                        let image_count = elements
-                                            .iter()
-                                            .filter(|e| matches!(e, DocumentElement::Image { .. }))
-                                            .count();
-
-                                        // Only create Image element if we have an actual image file available
-                                        if image_count < images.len() {
-                                            let (_, image_path) = &images[image_count];
-
-                                            elements.push(DocumentElement::Image {
-                                                description: format!("Image {}", image_count + 1),
-                                                width: None,
-                                                height: None,
-                                                relationship_id: None,
-                                                image_path: Some(image_path.clone()),
-                                            });
+                                let Some(ref extractor) = image_extractor else {
+                                    // Charts and shapes have no picture to leave out
+                                    match drawing.data {
+                                        Some(docx_rs::DrawingData::Pic(_)) => {
+                                            fidelity.omitted_images += 1
+                                        }
+                                        None => fidelity.unsupported_drawings += 1,
+                                        Some(docx_rs::DrawingData::TextBox(_)) => {}
+                                    }
+                                    continue;
+                                };
+                                let images = extractor.get_extracted_images_sorted();
+                                let description = format!("Image {}", image_count + 1);
+
+                                // A picture names its media file through its relationship
+                                let pic = match &drawing.data {
+                                    Some(docx_rs::DrawingData::Pic(pic)) => Some(pic),
+                                    _ => None,
+                                };
+                                let placed = pic.and_then(|pic| {
+                                    let name = image_targets.get(&pic.id)?;
+                                    if let Some(path) = extractor.get_image_path(name) {
+                                        return Some((Some(path.clone()), description.clone()));
+                                    }
+                                    match extractor.get_vector_image(name)? {
+                                        VectorImage::Rasterized(path) => {
+                                            Some((Some(path.clone()), description.clone()))
+                                        }
+                                        VectorImage::Placeholder(what) => {
+                                            Some((None, format!("{description} ({what})")))
+                                        }
+                                    }
+                                });
+
+                                // Only create Image element if we have an actual image file available
+                                let by_relationship = placed.is_some();
+                                let (image_path, description) = match placed {
+                                    Some(placed) => placed,
+                                    None if image_count < images.len() => {
+                                        (Some(images[image_count].1.clone()), description)
+                                    }
+                                    None => {
+                                        if !text_box {
+                                            fidelity.unsupported_drawings += 1;
                                         }
+                                        continue;
                                     }
+                                };
+                                image_count += 1;
+
+                                let layout = pic
+                                    .and_then(|pic| picture_layouts.get_mut(&pic.id)?.pop_front())
+                                    .unwrap_or_default();
+                                let position =
+                                    pic.and_then(|pic| FloatPosition::of_picture(pic, &layout));
+                                let image = DocumentElement::Image {
+                                    description,
+                                    width: pic
+                                        .filter(|pic| pic.size.0 > 0)
+                                        .map(|pic| pic.size.0 / EMUS_PER_PIXEL),
+                                    height: pic
+                                        .filter(|pic| pic.size.1 > 0)
+                                        .map(|pic| pic.size.1 / EMUS_PER_PIXEL),
+                                    relationship_id: pic
+                                        .filter(|_| by_relationship)
+                                        .map(|pic| pic.id.clone()),
+                                    image_path,
+                                    caption: None,
+                                    placement: pic.map(|pic| picture_placement(pic, layout)),
+                                };
+                                match position {
+                                    Some(position) => floating_images.push(Float {
+                                        position: Some(position),
+                                        elements: vec![image],
+                                    }),
+                                    None => elements.push(image),
                                 }
                             }
                         }
@@ -124,38 +566,165 @@ pub fn load_document(file_path: &Path, image_options: ImageOptions) -> Result<Do
                 // Extract runs with individual formatting
                 let mut formatted_runs = Vec::new();
 
-                for child in &para.children {
-                    if let docx_rs::ParagraphChild::Run(run) = child {
-                        let run_formatting = extract_run_formatting(run);
-                        let mut run_text = String::new();
+                let runs = para.children.iter().filter_map(|child| match child {
+                    docx_rs::ParagraphChild::Run(run) => Some(run),
+                    _ => None,
+                });
+                for (run_index, run) in runs.enumerate() {
+                    let mut run_formatting = extract_run_formatting(run);
+                    let raw_run = raw_properties.run(run_index);
+                    run_formatting.small_caps = raw_run.small_caps;
+                    run_formatting.rtl = raw_run.rtl;
+                    if let Some(color) = raw_run
+                        .theme_color
+                        .as_ref()
+                        .and_then(|color| theme_colors.resolve(color))
+                    {
+                        run_formatting.color = Some(color);
+                    }
+                    let symbol_font = SymbolFont::of_run(run);
+                    let mut run_text = String::new();
+
+                    // Empty simple fields sit before this run; others hold it
+                    for field in raw_properties.simple_fields.iter() {
+                        if field.first_run == run_index && field.runs == 0 {
+                            run_text.extend(fields.simple(&field.instruction, ""));
+                        }
+                    }
+                    let simple_field = raw_properties.simple_fields.iter().find(|field| {
+                        (field.first_run..field.first_run + field.runs).contains(&run_index)
+                    });
+                    if let Some(field) = simple_field.filter(|field| field.first_run == run_index) {
+                        fields.begin();
+                        fields.instruction(&field.instruction);
+                        fields.separate();
+                    }
 
-                        for child in &run.children {
-                            if let docx_rs::RunChild::Text(text_elem) = child {
-                                run_text.push_str(&text_elem.text);
+                    for child in &run.children {
+                        match child {
+                            docx_rs::RunChild::Text(_)
+                            | docx_rs::RunChild::Sym(_)
+                            | docx_rs::RunChild::Tab(_) => {
+                                let text = match child {
+                                    docx_rs::RunChild::Tab(_) => Cow::Borrowed("\t"),
+                                    _ => match run_child_text(child, symbol_font) {
+                                        Some(text) => text,
+                                        None => continue,
+                                    },
+                                };
+                                if fields.text(&text) {
+                                    if run_text.is_empty() {
+                                        run_formatting.bookmark_link =
+                                            fields.reference().map(str::to_string);
+                                    }
+                                    run_text.push_str(&text);
+                                }
+                            }
+                            docx_rs::RunChild::FieldChar(field_char) => {
+                                match field_char.field_char_type {
+                                    docx_rs::FieldCharType::Begin => fields.begin(),
+                                    docx_rs::FieldCharType::Separate => fields.separate(),
+                                    docx_rs::FieldCharType::End => run_text.extend(fields.end()),
+                                    docx_rs::FieldCharType::Unsupported => {}
+                                }
+                            }
+                            docx_rs::RunChild::InstrTextString(instruction) => {
+                                fields.instruction(instruction);
                             }
+                            _ => {}
                         }
+                    }
+
+                    if simple_field
+                        .is_some_and(|field| field.first_run + field.runs == run_index + 1)
+                    {
+                        run_text.extend(fields.end());
+                    }
 
-                        if !run_text.is_empty() {
-                            formatted_runs.push(FormattedRun {
+                    if !run_text.is_empty() {
+                        FormattedRun::push_consolidated(
+                            &mut formatted_runs,
+                            FormattedRun {
                                 text: run_text,
                                 formatting: run_formatting,
-                            });
+                            },
+                        );
+                    }
+                }
+
+                // Empty simple fields after the last run
+                let run_count = raw_properties.runs.len();
+                for field in raw_properties.simple_fields.iter() {
+                    if field.first_run >= run_count && field.runs == 0 {
+                        if let Some(text) = fields.simple(&field.instruction, "") {
+                            FormattedRun::push_consolidated(
+                                &mut formatted_runs,
+                                FormattedRun {
+                                    text,
+                                    formatting: TextFormatting::default(),
+                                },
+                            );
                         }
                     }
                 }
 
+                // Signature lines have no text of their own; note them where they're drawn
+                for text in &raw_properties.signature_lines {
+                    if !formatted_runs.is_empty() {
+                        FormattedRun::push_consolidated(
+                            &mut formatted_runs,
+                            FormattedRun {
+                                text: " ".to_string(),
+                                formatting: TextFormatting::default(),
+                            },
+                        );
+                    }
+                    formatted_runs.push(annotation_run(text.clone()));
+                }
+
+                // A drop cap is its own framed paragraph; read it as part of the next one
+                if raw_properties.drop_cap {
+                    drop_cap_runs.append(&mut formatted_runs);
+                    elements.extend(floating_images.into_iter().flat_map(|float| float.elements));
+                    continue;
+                }
+                let mut floats = text_box_floats(para, locale, &mut fidelity);
+                floats.append(&mut floating_images);
+                let deferred = visual_order
+                    && raw_properties
+                        .frame
+                        .is_some_and(|frame| frame.reads_after(page_height, false));
+                if !formatted_runs.is_empty() && !drop_cap_runs.is_empty() {
+                    for run in formatted_runs.drain(..) {
+                        FormattedRun::push_consolidated(&mut drop_cap_runs, run);
+                    }
+                    formatted_runs = std::mem::take(&mut drop_cap_runs);
+                }
+
                 // Calculate total text for word count and processing
                 let total_text: String =
                     formatted_runs.iter().map(|run| run.text.as_str()).collect();
 
                 if !total_text.trim().is_empty() {
-                    word_count += total_text.split_whitespace().count();
+                    // The notes for signature lines aren't the document's words
+                    let note_words: usize = raw_properties
+                        .signature_lines
+                        .iter()
+                        .map(|text| count_words(text))
+                        .sum();
+                    word_count += count_words(&total_text).saturating_sub(note_words);
 
                     // Priority: list numbering > heading style > text heuristics
                     if let Some(list_info) = list_info {
                         // This is an automatic Word list item - format with proper indentation
                         let indent = "  ".repeat(list_info.level as usize);
-                        let prefix = if list_info.is_ordered {
+                        let is_ordered = list_info
+                            .num_id
+                            .and_then(|num_id| {
+                                numbering_manager.is_ordered(num_id, list_info.level)
+                            })
+                            .unwrap_or(list_info.is_ordered);
+                        let prefix = if is_ordered {
                             // Use the numbering manager for proper sequential numbering
                             if let Some(num_id) = list_info.num_id {
                                 let format = get_numbering_format(num_id, list_info.level);
@@ -182,7 +751,14 @@ pub fn load_document(file_path: &Path, image_options: ImageOptions) -> Result<Do
                             let mut updated_runs = vec![prefix_run];
                             updated_runs.extend(formatted_runs);
 
-                            elements.push(DocumentElement::Paragraph { runs: updated_runs });
+                            elements.push(DocumentElement::Paragraph {
+                                runs: updated_runs,
+                                alignment: TextAlignment::Left,
+                                shading: None,
+                                rtl: false,
+                                spacing: None,
+                                tab_stops: Vec::new(),
+                            });
                         } else {
                             // Fallback for empty runs
                             let list_text = format!("__WORD_LIST__{indent}{prefix}");
@@ -191,13 +767,21 @@ pub fn load_document(file_path: &Path, image_options: ImageOptions) -> Result<Do
                                     text: list_text,
                                     formatting: TextFormatting::default(),
                                 }],
+                                alignment: TextAlignment::Left,
+                                shading: None,
+                                rtl: false,
+                                spacing: None,
+                                tab_stops: Vec::new(),
                             });
                         }
                     } else {
                         // Check for headings (with or without numbering)
                         if let Some(heading_info) = heading_info {
-                            let heading_text =
-                                heading_info.clean_text.unwrap_or(total_text.clone());
+                            // A heading is one line, so its tabs are just spaces
+                            let heading_text = heading_info
+                                .clean_text
+                                .unwrap_or(total_text.clone())
+                                .replace('\t', " ");
 
                             let number = if heading_info.number.is_some() {
                                 heading_info.number
@@ -216,6 +800,22 @@ pub fn load_document(file_path: &Path, image_options: ImageOptions) -> Result<Do
                                 text: heading_text,
                                 number,
                             });
+                        } else if let Some(kind) = style_sheet.block_kind(para) {
+                            match (kind, elements.last_mut()) {
+                                (BlockKind::Quote, _) => {
+                                    elements.push(DocumentElement::BlockQuote {
+                                        runs: formatted_runs,
+                                    });
+                                }
+                                // Each paragraph of code is a line of the block
+                                (BlockKind::Code, Some(DocumentElement::CodeBlock { code })) => {
+                                    code.push('\n');
+                                    code.push_str(&total_text);
+                                }
+                                (BlockKind::Code, _) => {
+                                    elements.push(DocumentElement::CodeBlock { code: total_text });
+                                }
+                            }
                         } else {
                             // Fallback to text-based heading detection using first run's formatting
                             let first_formatting = if !formatted_runs.is_empty() {
@@ -224,47 +824,183 @@ pub fn load_document(file_path: &Path, image_options: ImageOptions) -> Result<Do
                                 &TextFormatting::default()
                             };
 
-                            let level = detect_heading_from_text(&total_text, first_formatting);
+                            // A caption is never a heading, however it's formatted
+                            let caption =
+                                caption_kind(para, fields.sequence.as_deref(), &total_text);
+                            if let Some(kind) = caption {
+                                captions.insert(total_text.trim().to_string(), kind);
+                            }
+                            let style_size = style_sheet
+                                .font_size(para.property.style.as_ref().map(|s| s.val.as_str()));
+                            let size = paragraph_font_size(&formatted_runs, style_size);
+                            // Fine print isn't a heading, however short
+                            let fine_print = !first_formatting.bold
+                                && size.is_some_and(|size| size < baseline_size);
+                            let level = size
+                                .and_then(|size| {
+                                    detect_heading_from_size(
+                                        &total_text,
+                                        size,
+                                        baseline_size,
+                                        first_formatting.bold,
+                                    )
+                                })
+                                .or_else(|| {
+                                    detect_heading_from_text(&total_text, first_formatting)
+                                        .filter(|_| !fine_print)
+                                })
+                                .filter(|_| caption.is_none());
                             if let Some(level) = level {
                                 elements.push(DocumentElement::Heading {
                                     level,
-                                    text: total_text,
+                                    text: total_text.replace('\t', " "),
                                     number: None,
                                 });
                             } else {
-                                // This is a regular paragraph - consolidate runs and preserve formatting
-                                let consolidated_runs =
-                                    FormattedRun::consolidate_runs(formatted_runs);
+                                // This is a regular paragraph, its runs consolidated as they were read
                                 elements.push(DocumentElement::Paragraph {
-                                    runs: consolidated_runs,
+                                    runs: formatted_runs,
+                                    alignment: extract_paragraph_alignment(
+                                        para,
+                                        raw_properties.bidi,
+                                    ),
+                                    shading: raw_properties.shading.clone(),
+                                    rtl: raw_properties.bidi,
+                                    spacing: spacing_styles
+                                        .map(|styles| styles.paragraph_spacing(para)),
+                                    tab_stops: style_sheet.tab_stops(para),
                                 });
                             }
                         }
                     }
+                } else if style_sheet.block_kind(para) == Some(BlockKind::Code) {
+                    // A blank line inside a code block
+                    if let Some(DocumentElement::CodeBlock { code }) = elements.last_mut() {
+                        code.push('\n');
+                    }
+                } else if let Some(styles) =
+                    spacing_styles.filter(|_| elements.len() == block_start)
+                {
+                    // An empty paragraph is a blank line of its own
+                    elements.push(DocumentElement::Paragraph {
+                        runs: Vec::new(),
+                        alignment: TextAlignment::Left,
+                        shading: None,
+                        rtl: false,
+                        spacing: Some(styles.paragraph_spacing(para)),
+                        tab_stops: Vec::new(),
+                    });
+                }
+
+                // Word draws one border under a run of paragraphs bordered alike
+                let border_continues = raw_paragraphs
+                    .get(body_paragraph_index)
+                    .is_some_and(|next| next.bottom_border && !next.boxed);
+                if raw_properties.horizontal_line
+                    || (raw_properties.bottom_border && !raw_properties.boxed && !border_continues)
+                {
+                    elements.push(DocumentElement::HorizontalRule);
+                }
+                elements.extend(
+                    page_breaks
+                        .after
+                        .iter()
+                        .map(|&page| DocumentElement::PageBreak { page }),
+                );
+
+                // Text boxes and floating images follow the paragraph they're
+                // anchored in, unless visual order puts them before it
+                if visual_order {
+                    floats.sort_by_key(|float| float.position.map(|at| (at.y, at.x)));
+                }
+                let mut before = Vec::new();
+                for float in floats {
+                    match float.position {
+                        Some(at) if visual_order && !at.reads_after(page_height, true) => {
+                            before.extend(float.elements)
+                        }
+                        _ => elements.extend(float.elements),
+                    }
+                }
+                elements.splice(block_start..block_start, before);
+
+                // Bookmarks on a heading are what cross-references to it name
+                let is_heading =
+                    |element: &DocumentElement| matches!(element, DocumentElement::Heading { .. });
+                let bookmarks = para.children.iter().filter_map(|child| match child {
+                    docx_rs::ParagraphChild::BookmarkStart(bookmark) => Some(&bookmark.name),
+                    _ => None,
+                });
+                let mut bookmarks = bookmarks.peekable();
+                if bookmarks.peek().is_some() {
+                    if let Some(at) = elements[block_start..].iter().position(is_heading) {
+                        let ordinal = elements[..block_start + at]
+                            .iter()
+                            .filter(|element| is_heading(element))
+                            .count();
+                        for name in bookmarks {
+                            heading_bookmarks.insert(name.clone(), ordinal);
+                        }
+                    }
+                }
+
+                if deferred {
+                    deferred_frames.extend(elements.drain(block_start..));
+                } else if elements.len() > block_start {
+                    elements.append(&mut deferred_frames);
                 }
             }
             docx_rs::DocumentChild::Table(table) => {
+                // A drop cap with no paragraph after it stands on its own
+                if !drop_cap_runs.is_empty() {
+                    elements.push(DocumentElement::Paragraph {
+                        runs: std::mem::take(&mut drop_cap_runs),
+                        alignment: TextAlignment::Left,
+                        shading: None,
+                        rtl: false,
+                        spacing: None,
+                        tab_stops: Vec::new(),
+                    });
+                }
+
                 // Extract table data
-                if let Some(table_element) = extract_table_data(table) {
+                let style = table_properties
+                    .get(body_table_index)
+                    .map(|properties| style_sheet.table_style(properties, &theme_colors))
+                    .unwrap_or_default();
+                body_table_index += 1;
+                if let Some(table_element) = extract_table_data(table, locale, style, &mut fidelity)
+                {
                     elements.push(table_element);
                 }
+                elements.append(&mut deferred_frames);
             }
             _ => {
                 // Handle other document elements (images, etc.) in future
             }
         }
     }
-
-    // Extract inline equations with their positions
-    let inline_paragraphs = extract_inline_equation_positions(file_path).unwrap_or_default();
-
-    // Extract all equations (both inline and display)
-    let equation_infos = extract_equations_from_docx(file_path).unwrap_or_default();
+    elements.append(&mut deferred_frames);
+    if !drop_cap_runs.is_empty() {
+        elements.push(DocumentElement::Paragraph {
+            runs: drop_cap_runs,
+            alignment: TextAlignment::Left,
+            shading: None,
+            rtl: false,
+            spacing: None,
+            tab_stops: Vec::new(),
+        });
+    }
+    section_ends.extend(pending_breaks.map(|_| elements.len()));
 
     // Create a map of paragraph index -> display equations
     let mut display_equations_by_para: std::collections::HashMap<usize, Vec<DocumentElement>> =
         std::collections::HashMap::new();
 
+    // Numbered equations in document order, and the bookmarks on them
+    let mut numbered_equations = std::collections::VecDeque::new();
+    let mut equation_bookmarks = BTreeMap::new();
+
     for eq in equation_infos.iter() {
         if !eq.is_inline {
             display_equations_by_para
@@ -273,19 +1009,50 @@ pub fn load_document(file_path: &Path, image_options: ImageOptions) -> Result<Do
                 .push(DocumentElement::Equation {
                     latex: eq.latex.clone(),
                     fallback: eq.fallback.clone(),
+                    number: eq.number.clone(),
                 });
+            if let Some(number) = &eq.number {
+                numbered_equations.push_back((eq.paragraph_index, number.trim().to_string()));
+                for name in &eq.bookmarks {
+                    equation_bookmarks.insert(name.clone(), number.clone());
+                }
+            }
         }
     }
 
     // Integrate inline equations into paragraphs and insert display equations at correct positions
     let mut elements_with_equations = Vec::new();
     let mut para_index = 0;
+    // Where each element's output starts, to carry the section ends over
+    let mut output_starts = Vec::with_capacity(elements.len() + 1);
 
     for element in elements {
+        output_starts.push(elements_with_equations.len());
         match element {
-            DocumentElement::Paragraph { runs } => {
+            DocumentElement::Paragraph {
+                runs,
+                alignment,
+                shading,
+                rtl,
+                spacing,
+                tab_stops,
+            } => {
                 para_index += 1;
 
+                // The number beside a display equation is all docx-rs reads of
+                // its paragraph, so the equation takes its place
+                let text: String = runs.iter().map(|run| run.text.as_str()).collect();
+                let numbered = numbered_equations
+                    .iter()
+                    .position(|(_, number)| *number == text.trim());
+                if let Some((paragraph, _)) = numbered.and_then(|at| numbered_equations.remove(at))
+                {
+                    if let Some(equations) = display_equations_by_para.remove(&paragraph) {
+                        elements_with_equations.extend(equations);
+                        continue;
+                    }
+                }
+
                 // Check if this paragraph has inline equations
                 if let Some(content_items) = inline_paragraphs.get(&para_index) {
                     // Check if there are actually any inline equations in this paragraph
@@ -329,10 +1096,24 @@ pub fn load_document(file_path: &Path, image_options: ImageOptions) -> Result<Do
                             });
                         }
 
-                        elements_with_equations.push(DocumentElement::Paragraph { runs: new_runs });
+                        elements_with_equations.push(DocumentElement::Paragraph {
+                            runs: new_runs,
+                            alignment,
+                            shading,
+                            rtl,
+                            spacing,
+                            tab_stops,
+                        });
                     } else {
                         // No actual equations, preserve original runs with formatting
-                        elements_with_equations.push(DocumentElement::Paragraph { runs });
+                        elements_with_equations.push(DocumentElement::Paragraph {
+                            runs,
+                            alignment,
+                            shading,
+                            rtl,
+                            spacing,
+                            tab_stops,
+                        });
                     }
                 } else {
                     // Check if this paragraph is actually a display equation
@@ -343,7 +1124,14 @@ pub fn load_document(file_path: &Path, image_options: ImageOptions) -> Result<Do
                         }
                     } else {
                         // Regular paragraph without equations
-                        elements_with_equations.push(DocumentElement::Paragraph { runs });
+                        elements_with_equations.push(DocumentElement::Paragraph {
+                            runs,
+                            alignment,
+                            shading,
+                            rtl,
+                            spacing,
+                            tab_stops,
+                        });
                     }
                 }
             }
@@ -353,31 +1141,126 @@ pub fn load_document(file_path: &Path, image_options: ImageOptions) -> Result<Do
         }
     }
 
-    // Post-process to group consecutive list items (only for text-based lists)
-    // Word numbering-based lists are already properly formatted
-    let elements = group_list_items(elements_with_equations);
+    output_starts.push(elements_with_equations.len());
+    for end in &mut section_ends {
+        *end = output_starts[*end];
+    }
 
-    // Clean up Word list markers
-    let elements = clean_word_list_markers(elements);
+    // Post-process each section on its own, so lists don't run across section breaks
+    let mut elements = Vec::new();
+    let mut remaining = elements_with_equations.into_iter();
+    let mut start = 0;
+    for index in 0..=section_ends.len() {
+        let end = section_ends.get(index).copied().unwrap_or(usize::MAX);
+        let chunk: Vec<DocumentElement> = remaining.by_ref().take(end - start).collect();
+        start = end;
+
+        // Group consecutive list items (only for text-based lists)
+        // Word numbering-based lists are already properly formatted
+        let chunk = group_list_items(chunk);
+
+        // Clean up Word list markers
+        elements.extend(clean_word_list_markers(chunk));
+        if let Some(end) = section_ends.get_mut(index) {
+            *end = elements.len();
+        }
+    }
 
     // Merge display equations into the final element list at correct positions
-    let elements = merge_display_equations(elements, display_equations_by_para);
+    let elements = merge_display_equations(elements, display_equations_by_para, &mut section_ends);
+    let mut elements = attach_captions(elements, &captions, &mut section_ends);
+
+    // A watermark is on every page; note it once, at the top
+    let mut watermarks: Vec<String> = Vec::new();
+    for name in package.part_names_with_prefix("word/header") {
+        let Some(xml) = package.part(name).ok().flatten() else {
+            continue;
+        };
+        for watermark in scan_watermarks(&String::from_utf8_lossy(xml)).unwrap_or_default() {
+            if !watermarks.contains(&watermark) {
+                watermarks.push(watermark);
+            }
+        }
+    }
+    elements.splice(
+        0..0,
+        watermarks.iter().map(|text| DocumentElement::Paragraph {
+            runs: vec![annotation_run(text.clone())],
+            alignment: TextAlignment::Center,
+            shading: None,
+            rtl: false,
+            spacing: None,
+            tab_stops: Vec::new(),
+        }),
+    );
+    for end in &mut section_ends {
+        *end += watermarks.len();
+    }
+    let sections = build_sections(
+        section_breaks
+            .into_iter()
+            .map(|section| section.page)
+            .zip(
+                section_ends
+                    .into_iter()
+                    .map(Some)
+                    .chain(std::iter::repeat(None)),
+            )
+            .map(|(page, end)| (end, page))
+            .collect(),
+        elements.len(),
+    );
+
+    fidelity.unevaluated_fields = fields.unevaluated;
+    // Charts and SmartArt are counted on their own rather than as drawings
+    let skipped = scan_skipped_content(document_xml).unwrap_or_default();
+    fidelity.charts = skipped.charts;
+    fidelity.diagrams = skipped.diagrams;
+    fidelity.embedded_objects = skipped.embedded_objects;
+    fidelity.unsupported_drawings = fidelity
+        .unsupported_drawings
+        .saturating_sub(skipped.charts + skipped.diagrams);
+    let index_entries = std::mem::take(&mut fields.entries);
+
+    // Protection is only reported; an unreadable setting leaves the document unprotected
+    let part_text = |name| {
+        package
+            .part(name)
+            .ok()
+            .flatten()
+            .map(String::from_utf8_lossy)
+    };
+    let protection = read_protection(
+        part_text("word/settings.xml").as_deref(),
+        part_text("docProps/custom.xml").as_deref(),
+    )
+    .unwrap_or_default();
 
     let metadata = DocumentMetadata {
-        file_path: file_path.to_string_lossy().to_string(),
+        file_path: file_name,
         file_size,
         word_count,
         page_count: estimate_page_count(word_count),
         created: None, // Simplified for now
         modified: None,
         author: None,
+        language,
+        locale: field_options.locale,
+        protection,
     };
 
+    progress.finish(Phase::Building);
     Ok(Document {
         title,
         metadata,
         elements,
+        sections,
         image_options,
+        fidelity,
+        index_entries,
+        heading_bookmarks,
+        equation_bookmarks,
+        volatile_fields: fields.volatile,
     })
 }
 
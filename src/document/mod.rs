@@ -5,16 +5,52 @@
 //!
 //! During refactoring: Incrementally extracting modules
 
+pub mod cache;
+pub mod chart;
 pub(crate) mod cleanup;
+pub mod concat;
+pub mod diff;
+pub mod forensic;
+pub mod forms;
+pub mod index;
+pub mod inspect;
 pub(crate) mod io;
 pub(crate) mod loader;
 pub mod models;
 pub(crate) mod parsing;
+pub mod progress;
 pub mod query;
+pub mod recovery;
+pub mod scan;
+#[cfg(feature = "spellcheck")]
+pub mod spelling;
+pub mod styles;
+pub mod table_query;
+pub mod words;
 
 // Re-export all models and query functions
 pub use models::*;
 pub use query::*;
+#[allow(unused_imports)]
+pub use table_query::{table_rows, table_with_rows, FilterOp, TableFilter};
+#[allow(unused_imports)]
+pub use words::{count_words, reading_minutes};
 
 // Re-export main document loading function
-pub use loader::load_document;
+#[allow(unused_imports)]
+pub use cleanup::{normalize_document, normalize_text};
+pub use concat::concat_documents;
+pub use forensic::extract_forensic_text;
+#[allow(unused_imports)]
+pub use inspect::SourceInspector;
+pub use io::SeekableInput;
+#[cfg(feature = "async")]
+#[allow(unused_imports)]
+pub use loader::load_document_async;
+#[allow(unused_imports)]
+pub use loader::{load_document, load_document_with_fields, load_document_with_progress};
+#[allow(unused_imports)]
+pub use progress::{NoProgress, Phase, Progress, ProgressEvent};
+#[allow(unused_imports)]
+pub use recovery::{load_document_lenient, load_document_lenient_with_fields};
+pub use styles::style_report;
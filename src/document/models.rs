@@ -4,6 +4,10 @@
 //! including elements, formatting, tables, and metadata.
 
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::{ReadingOrder, SpacingMode};
 
 // Type aliases for convenience
 pub type TableRows = Vec<Vec<TableCell>>;
@@ -16,6 +20,123 @@ pub struct ImageOptions {
     pub max_width: Option<u32>,
     pub max_height: Option<u32>,
     pub scale: Option<f32>,
+    /// Play animated GIFs where the terminal can show them (`--animate`)
+    pub animate: bool,
+    /// Width and height of a terminal cell in pixels, when the terminal reports it
+    pub cell_size: Option<(u16, u16)>,
+}
+
+/// Cell size assumed when the terminal doesn't report one
+pub const DEFAULT_CELL_SIZE: (u16, u16) = (8, 16);
+
+impl ImageOptions {
+    /// The columns and rows to draw an image of `pixels` (width, height) in
+    ///
+    /// The image fills as much of `--image-width` × `--image-height` cells
+    /// (by default, `terminal`) as it can, after `--image-scale`, without
+    /// changing shape: cells are usually twice as tall as they're wide, and
+    /// on HiDPI terminals hold many more pixels than the default suggests.
+    pub fn fit(&self, pixels: (u32, u32), terminal: (u32, u32)) -> (u32, u32) {
+        let scale = self.scale.unwrap_or(1.0).clamp(0.1, 2.0);
+        let columns = (self.max_width.unwrap_or(terminal.0) as f32 * scale).max(1.0);
+        let rows = (self.max_height.unwrap_or(terminal.1) as f32 * scale).max(1.0);
+        let (cell_width, cell_height) = self.cell_size.unwrap_or(DEFAULT_CELL_SIZE);
+        let (cell_width, cell_height) = (cell_width.max(1) as f32, cell_height.max(1) as f32);
+
+        let (width, height) = (pixels.0.max(1) as f32, pixels.1.max(1) as f32);
+        let zoom = (columns * cell_width / width).min(rows * cell_height / height);
+        (
+            ((width * zoom / cell_width).round() as u32).clamp(1, columns as u32),
+            ((height * zoom / cell_height).round() as u32).clamp(1, rows as u32),
+        )
+    }
+}
+
+/// Options for reading the body text: field codes, floating content, spacing
+/// and how much of it to read
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FieldOptions {
+    /// Follow the cached result of each field doxx can't evaluate with its
+    /// instruction in braces, e.g. `Section 2 {REF _Ref123 \h}`
+    pub show_unknown: bool,
+    /// Where text boxes and framed paragraphs are read
+    pub reading_order: ReadingOrder,
+    /// Whether paragraphs keep their spacing, and empty paragraphs are kept
+    pub spacing: SpacingMode,
+    /// How numbers and dates in table cells are read, instead of by the
+    /// document's language
+    pub locale: Option<CellLocale>,
+    /// Only the sections or pages to read, instead of the whole body
+    pub range: Option<DocumentRange>,
+}
+
+/// Sections or pages of a document, counting from 1 (`pages 10-20`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DocumentRange {
+    pub unit: RangeUnit,
+    pub first: usize,
+    /// The last one in the range, or `None` to read to the end
+    pub last: Option<usize>,
+}
+
+/// What a [`DocumentRange`] counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeUnit {
+    /// Sections, which end at each `w:sectPr`
+    Sections,
+    /// Pages, as Word last laid them out and broken by explicit page breaks
+    Pages,
+}
+
+impl DocumentRange {
+    /// Whether the range takes in any of `first..=last`
+    pub fn overlaps(&self, first: usize, last: usize) -> bool {
+        first <= self.last.unwrap_or(usize::MAX) && last >= self.first
+    }
+}
+
+impl std::str::FromStr for DocumentRange {
+    type Err = String;
+
+    /// Read `sections 2-4`, `section 3`, `pages 10-20` or `pages 10-`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let usage =
+            || format!("'{text}' isn't a range; expected e.g. 'sections 2-4' or 'pages 10-20'");
+        let (unit, span) = text
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(usage)?;
+        let unit = match unit.to_ascii_lowercase().as_str() {
+            "section" | "sections" => RangeUnit::Sections,
+            "page" | "pages" => RangeUnit::Pages,
+            _ => return Err(usage()),
+        };
+        let number = |text: &str| text.trim().parse::<usize>().ok().filter(|&n| n > 0);
+        let (first, last) = match span.split_once('-') {
+            Some((first, last)) if last.trim().is_empty() => (number(first), None),
+            Some((first, last)) => (number(first), Some(number(last).ok_or_else(usage)?)),
+            None => (number(span), number(span)),
+        };
+        let first = first.ok_or_else(usage)?;
+        if last.is_some_and(|last| last < first) {
+            return Err(format!("'{text}' ends before it starts"));
+        }
+        Ok(Self { unit, first, last })
+    }
+}
+
+impl std::fmt::Display for DocumentRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (one, many) = match self.unit {
+            RangeUnit::Sections => ("section", "sections"),
+            RangeUnit::Pages => ("page", "pages"),
+        };
+        match self.last {
+            Some(last) if last == self.first => write!(f, "{one} {last}"),
+            Some(last) => write!(f, "{many} {}-{last}", self.first),
+            None => write!(f, "{many} {}-", self.first),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,8 +144,170 @@ pub struct Document {
     pub title: String,
     pub metadata: DocumentMetadata,
     pub elements: Vec<DocumentElement>,
+    /// Page setup of each section, in order; empty if the document has none
+    #[serde(default)]
+    pub sections: Vec<Section>,
     #[serde(skip)]
     pub image_options: ImageOptions,
+    /// What the loader couldn't carry over as written
+    #[serde(default)]
+    pub fidelity: Fidelity,
+    /// Index entries (`XE` fields) and citations (`TA` fields) marked in the
+    /// text, in document order
+    #[serde(default)]
+    pub index_entries: Vec<IndexEntry>,
+    /// Bookmarks set on headings, by name, and which heading each is on,
+    /// counting the document's headings from 0; cross-references link to them
+    #[serde(default)]
+    pub heading_bookmarks: BTreeMap<String, usize>,
+    /// Bookmarks set on numbered equations, by name, and the number of the
+    /// equation each is on; cross-references link to them too
+    #[serde(default)]
+    pub equation_bookmarks: BTreeMap<String, String>,
+    /// `DATE`, `TIME` and `FILENAME` fields evaluated while loading, whose
+    /// values depend on when and under what name the file was opened
+    #[serde(default)]
+    pub volatile_fields: usize,
+}
+
+/// An index entry or a citation for the table of authorities
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// The entry and its subentries, as in `XE "Contracts:breach"`, or the
+    /// citation's long form
+    pub levels: Vec<String>,
+    /// The citation's category (`\c`), 1 for cases, 2 for statutes and so
+    /// on; `None` for an index entry
+    pub category: Option<u8>,
+    /// A cross-reference given in place of the page (`\t "See Torts"`)
+    pub see: Option<String>,
+    /// The page it's on, estimated as for `PAGE` fields
+    pub page: usize,
+}
+
+/// Content the loader skipped or approximated, for the fidelity summary after an
+/// export, the viewer's banner and `--strict`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fidelity {
+    /// Drawings with no picture behind them, such as shapes and WordArt
+    pub unsupported_drawings: usize,
+    /// Charts, whose data and drawing live in parts doxx doesn't read
+    #[serde(default)]
+    pub charts: usize,
+    /// SmartArt diagrams
+    #[serde(default)]
+    pub diagrams: usize,
+    /// Embedded (OLE) objects, such as spreadsheets and Equation Editor 3.0 equations
+    #[serde(default)]
+    pub embedded_objects: usize,
+    /// Pictures left out because image support was off
+    pub omitted_images: usize,
+    /// Tables inside table cells, flattened into the cell's text
+    pub flattened_tables: usize,
+    /// Fields showing the result Word cached, since doxx can't evaluate them
+    pub unevaluated_fields: usize,
+}
+
+impl Document {
+    /// Whether wrapped lines may break inside words, going by the document's language
+    pub fn hyphenates(&self) -> bool {
+        self.metadata
+            .language
+            .as_ref()
+            .is_none_or(DocumentLanguage::hyphenates)
+    }
+
+    /// How numbers in table cells are written, going by the document's language
+    pub fn number_style(&self) -> NumberStyle {
+        self.cell_locale().numbers
+    }
+
+    /// How numbers and dates in table cells are written: as given with
+    /// `--locale`, or else going by the document's language
+    pub fn cell_locale(&self) -> CellLocale {
+        self.metadata.locale.unwrap_or_else(|| {
+            self.metadata
+                .language
+                .as_ref()
+                .map(CellLocale::from)
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// A run of elements sharing one page setup (`w:sectPr`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    /// The section covers `elements[start..end]`
+    pub start: usize,
+    pub end: usize,
+    pub page: PageSetup,
+}
+
+impl Section {
+    /// Label for the boundary before this section, e.g. "Section 2: Landscape, 2 columns"
+    pub fn label(&self, number: usize) -> String {
+        format!("Section {number}: {}", self.page.describe())
+    }
+}
+
+/// Page size, margins and text columns of a section
+///
+/// Lengths are in twentieths of a point (twips), as Word stores them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageSetup {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub orientation: Orientation,
+    pub margins: Option<PageMargins>,
+    /// Number of text columns
+    pub columns: u16,
+    /// Space between text columns
+    pub column_gap: Option<u32>,
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        Self {
+            width: None,
+            height: None,
+            orientation: Orientation::Portrait,
+            margins: None,
+            columns: 1,
+            column_gap: None,
+        }
+    }
+}
+
+impl PageSetup {
+    /// Short summary, e.g. "Landscape, 2 columns"
+    pub fn describe(&self) -> String {
+        let orientation = match self.orientation {
+            Orientation::Portrait => "Portrait",
+            Orientation::Landscape => "Landscape",
+        };
+        if self.columns > 1 {
+            format!("{orientation}, {} columns", self.columns)
+        } else {
+            orientation.to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+/// Page margins in twips; top and bottom may be negative (text may overlap the header)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageMargins {
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub left: i32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +319,209 @@ pub struct DocumentMetadata {
     pub created: Option<String>,
     pub modified: Option<String>,
     pub author: Option<String>,
+    /// Main language of the text, if declared or detected
+    #[serde(default)]
+    pub language: Option<DocumentLanguage>,
+    /// How table cells were read, if set with `--locale` rather than by language
+    #[serde(default)]
+    pub locale: Option<CellLocale>,
+    /// Editing restrictions, if the document has any
+    #[serde(default)]
+    pub protection: Option<Protection>,
+}
+
+/// What `w:documentProtection` limits editing to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditRestriction {
+    /// No changes at all
+    ReadOnly,
+    /// Comments only
+    Comments,
+    /// Changes only as tracked changes
+    TrackedChanges,
+    /// Filling in form fields only
+    Forms,
+}
+
+/// How a document is protected from editing, from `settings.xml` and its
+/// custom properties
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Protection {
+    /// Editing restriction Word enforces
+    pub editing: Option<EditRestriction>,
+    /// Saving changes needs a password (`w:writeProtection` with a hash)
+    pub password_to_modify: bool,
+    /// Word suggests opening it read-only (`w:writeProtection w:recommended`)
+    pub read_only_recommended: bool,
+    /// Marked as final (the `_MarkAsFinal` custom property)
+    pub marked_final: bool,
+}
+
+impl Protection {
+    /// Each restriction in a few words, the strongest first
+    pub fn describe(&self) -> Vec<&'static str> {
+        let mut labels = Vec::new();
+        if self.marked_final {
+            labels.push("Marked as final");
+        }
+        labels.extend(self.editing.map(|editing| match editing {
+            EditRestriction::ReadOnly => "Read-only",
+            EditRestriction::Comments => "Comments only",
+            EditRestriction::TrackedChanges => "Tracked changes only",
+            EditRestriction::Forms => "Filling in forms only",
+        }));
+        if self.password_to_modify {
+            labels.push("Password to modify");
+        }
+        if self.read_only_recommended {
+            labels.push("Read-only recommended");
+        }
+        labels
+    }
+
+    /// The restrictions as one line, e.g. "Marked as final, Comments only"
+    pub fn summary(&self) -> String {
+        self.describe().join(", ")
+    }
+}
+
+/// The main language of a document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentLanguage {
+    /// BCP 47 tag as declared by `w:lang` (`de-DE`), or a bare language code
+    /// when detected (`de`)
+    pub tag: String,
+    /// Guessed from the text, because the document declares no language
+    pub detected: bool,
+}
+
+impl DocumentLanguage {
+    /// The language subtag, lowercased: `de` for `de-DE`
+    pub fn language(&self) -> String {
+        let primary = self.tag.split(['-', '_']).next().unwrap_or_default();
+        primary.to_ascii_lowercase()
+    }
+
+    /// How numbers are written in this language
+    pub fn number_style(&self) -> NumberStyle {
+        // Regions that write a decimal point although their language usually doesn't
+        const POINT_REGIONS: &[&str] = &["de-ch", "de-li", "it-ch", "es-mx", "es-us", "es-pr"];
+        const COMMA_LANGUAGES: &[&str] = &[
+            "af", "az", "be", "bg", "ca", "cs", "da", "de", "el", "es", "et", "fi", "fr", "hr",
+            "hu", "hy", "id", "it", "ka", "kk", "lt", "lv", "mk", "nb", "nl", "nn", "no", "pl",
+            "pt", "ro", "ru", "sk", "sl", "sq", "sr", "sv", "tr", "uk", "uz", "vi",
+        ];
+
+        let tag = self.tag.to_ascii_lowercase().replace('_', "-");
+        if POINT_REGIONS.iter().any(|region| tag.starts_with(region)) {
+            NumberStyle::DecimalPoint
+        } else if COMMA_LANGUAGES.contains(&self.language().as_str()) {
+            NumberStyle::DecimalComma
+        } else {
+            NumberStyle::DecimalPoint
+        }
+    }
+
+    /// The order dates written in numbers take in this language
+    pub fn date_order(&self) -> DateOrder {
+        // English outside these regions writes the day first
+        const MONTH_FIRST_REGIONS: &[&str] = &["en-us", "en-ph", "en-ca", "es-us"];
+        const YEAR_FIRST_LANGUAGES: &[&str] = &["zh", "ja", "ko", "hu", "lt", "mn", "sv"];
+
+        let tag = self.tag.to_ascii_lowercase().replace('_', "-");
+        let language = self.language();
+        if MONTH_FIRST_REGIONS
+            .iter()
+            .any(|region| tag.starts_with(region))
+            || tag == "en"
+        {
+            DateOrder::MonthDayYear
+        } else if YEAR_FIRST_LANGUAGES.contains(&language.as_str()) {
+            DateOrder::YearMonthDay
+        } else {
+            DateOrder::DayMonthYear
+        }
+    }
+
+    /// Whether wrapped lines may break inside words, after hyphens and at soft
+    /// hyphens; scripts written without hyphenation don't
+    pub fn hyphenates(&self) -> bool {
+        !matches!(
+            self.language().as_str(),
+            "zh" | "ja" | "ko" | "th" | "lo" | "km" | "my"
+        )
+    }
+}
+
+impl std::fmt::Display for DocumentLanguage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.detected {
+            write!(f, "{} (detected)", self.tag)
+        } else {
+            write!(f, "{}", self.tag)
+        }
+    }
+}
+
+/// Decimal separator used when reading numbers in table cells
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumberStyle {
+    /// `1,234.5`
+    #[default]
+    DecimalPoint,
+    /// `1.234,5` or `1 234,5`
+    DecimalComma,
+}
+
+/// Order of the day, month and year in dates written in numbers
+///
+/// A year of more than two digits is recognized wherever it is, and a first
+/// or second part above 12 can only be a day, whatever the order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateOrder {
+    /// `12/31/2024`
+    #[default]
+    MonthDayYear,
+    /// `31/12/2024` or `31.12.2024`
+    DayMonthYear,
+    /// `2024/12/31`
+    YearMonthDay,
+}
+
+/// How numbers and dates are written in table cells
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellLocale {
+    pub numbers: NumberStyle,
+    pub dates: DateOrder,
+}
+
+impl From<&DocumentLanguage> for CellLocale {
+    fn from(language: &DocumentLanguage) -> Self {
+        Self {
+            numbers: language.number_style(),
+            dates: language.date_order(),
+        }
+    }
+}
+
+impl std::str::FromStr for CellLocale {
+    type Err = String;
+
+    /// Read a language tag such as `de-DE`, `en_GB` or `fr`
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        let language = tag.split(['-', '_']).next().unwrap_or_default();
+        if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic())
+        {
+            return Err(format!(
+                "'{tag}' isn't a language tag; expected e.g. 'de-DE', 'en-GB' or 'fr'"
+            ));
+        }
+        Ok(Self::from(&DocumentLanguage {
+            tag: tag.to_string(),
+            detected: false,
+        }))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +533,20 @@ pub enum DocumentElement {
     },
     Paragraph {
         runs: Vec<FormattedRun>,
+        #[serde(default)]
+        alignment: TextAlignment,
+        /// Paragraph shading fill (`w:pPr/w:shd`) as a hex colour
+        #[serde(default)]
+        shading: Option<String>,
+        /// Right-to-left paragraph (`w:bidi`)
+        #[serde(default)]
+        rtl: bool,
+        /// Blank lines around the paragraph with `--spacing preserve`
+        #[serde(default)]
+        spacing: Option<ParagraphSpacing>,
+        /// Tab stops set by the paragraph and its style, in order
+        #[serde(default)]
+        tab_stops: Vec<TabStop>,
     },
     List {
         items: Vec<ListItem>,
@@ -61,12 +561,38 @@ pub enum DocumentElement {
         height: Option<u32>,
         relationship_id: Option<String>, // Link to DOCX relationship for image extraction
         image_path: Option<std::path::PathBuf>, // Path to extracted image file
+        /// The figure caption beside the image ("Figure 2: Revenue by region")
+        #[serde(default)]
+        caption: Option<String>,
+        /// Size in cells, and where a floating image was positioned
+        #[serde(default)]
+        placement: Option<ImagePlacement>,
     },
     Equation {
         latex: String,
         fallback: String,
+        /// The number set beside a display equation, as "(3.2)"
+        #[serde(default)]
+        number: Option<String>,
+    },
+    /// A quotation: a paragraph in a `Quote` or `Intense Quote` style
+    BlockQuote {
+        runs: Vec<FormattedRun>,
+    },
+    /// Consecutive paragraphs in a code style or monospace font, one line each
+    CodeBlock {
+        code: String,
+    },
+    /// An explicit page break, and the page it starts
+    PageBreak {
+        page: usize,
+    },
+    /// A paragraph's bottom border or a horizontal line
+    HorizontalRule,
+    /// Where the next file starts in documents merged with `--concat`
+    FileBoundary {
+        name: String,
     },
-    PageBreak,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
@@ -77,6 +603,225 @@ pub struct TextFormatting {
     pub strikethrough: bool,
     pub font_size: Option<f32>,
     pub color: Option<String>,
+    #[serde(default)]
+    pub vertical_align: VerticalAlign,
+    #[serde(default)]
+    pub all_caps: bool,
+    #[serde(default)]
+    pub small_caps: bool,
+    /// Word highlight colour name, e.g. `yellow` or `darkBlue`
+    #[serde(default)]
+    pub highlight: Option<String>,
+    /// Run shading fill (`w:rPr/w:shd`) as a hex colour
+    #[serde(default)]
+    pub background: Option<String>,
+    /// Right-to-left run (`w:rtl`)
+    #[serde(default)]
+    pub rtl: bool,
+    /// The bookmark a cross-reference (`REF`, `PAGEREF`) or an internal
+    /// hyperlink in this run points to
+    #[serde(default)]
+    pub bookmark_link: Option<String>,
+}
+
+/// Vertical position of a run relative to the baseline (`w:vertAlign`)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerticalAlign {
+    #[default]
+    Baseline,
+    Superscript,
+    Subscript,
+}
+
+impl TextFormatting {
+    /// Apply all caps and small caps, which terminals can only show as uppercase
+    ///
+    /// The mapping is one character for one character, so offsets into the
+    /// source text (search matches, for example) stay valid.
+    pub fn apply_caps<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !(self.all_caps || self.small_caps) {
+            return Cow::Borrowed(text);
+        }
+        Cow::Owned(
+            text.chars()
+                .map(|c| {
+                    let mut upper = c.to_uppercase();
+                    match (upper.next(), upper.next()) {
+                        (Some(u), None) => u,
+                        _ => c,
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Text as it should appear in a terminal: caps applied, and superscript or
+    /// subscript mapped to Unicode script characters
+    ///
+    /// Characters without a Unicode script form are left as they are.
+    pub fn display_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let text = self.apply_caps(text);
+        let script: fn(char) -> Option<char> = match self.vertical_align {
+            VerticalAlign::Baseline => return text,
+            VerticalAlign::Superscript => superscript_char,
+            VerticalAlign::Subscript => subscript_char,
+        };
+        Cow::Owned(text.chars().map(|c| script(c).unwrap_or(c)).collect())
+    }
+
+    /// Hex colour for the run's highlight, if it has one
+    pub fn highlight_hex(&self) -> Option<&'static str> {
+        let hex = match self.highlight.as_deref()? {
+            "black" => "#000000",
+            "blue" => "#0000FF",
+            "cyan" => "#00FFFF",
+            "green" => "#00FF00",
+            "magenta" => "#FF00FF",
+            "red" => "#FF0000",
+            "yellow" => "#FFFF00",
+            "white" => "#FFFFFF",
+            "darkBlue" => "#000080",
+            "darkCyan" => "#008080",
+            "darkGreen" => "#008000",
+            "darkMagenta" => "#800080",
+            "darkRed" => "#800000",
+            "darkYellow" => "#808000",
+            "darkGray" => "#808080",
+            "lightGray" => "#C0C0C0",
+            _ => return None,
+        };
+        Some(hex)
+    }
+
+    /// Background colour to draw behind the run: its highlight, which Word paints
+    /// over shading, or else its shading
+    pub fn background_hex(&self) -> Option<&str> {
+        self.highlight_hex().or(self.background.as_deref())
+    }
+}
+
+/// Black or white, whichever reads better on the given hex background
+///
+/// Used for text with a background but no colour of its own, which would
+/// otherwise take the terminal's default foreground.
+pub fn contrasting_text_color(background_hex: &str) -> &'static str {
+    let hex = background_hex.trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .map_or(0.0, f32::from)
+    };
+    // Rec. 601 luma
+    let luma = 0.299 * channel(0) + 0.587 * channel(2) + 0.114 * channel(4);
+    if luma > 140.0 {
+        "#000000"
+    } else {
+        "#FFFFFF"
+    }
+}
+
+fn superscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        'A' => 'ᴬ',
+        'B' => 'ᴮ',
+        'D' => 'ᴰ',
+        'E' => 'ᴱ',
+        'G' => 'ᴳ',
+        'H' => 'ᴴ',
+        'I' => 'ᴵ',
+        'J' => 'ᴶ',
+        'K' => 'ᴷ',
+        'L' => 'ᴸ',
+        'M' => 'ᴹ',
+        'N' => 'ᴺ',
+        'O' => 'ᴼ',
+        'P' => 'ᴾ',
+        'R' => 'ᴿ',
+        'T' => 'ᵀ',
+        'U' => 'ᵁ',
+        'V' => 'ⱽ',
+        'W' => 'ᵂ',
+        _ => return None,
+    })
+}
+
+fn subscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,27 +833,21 @@ pub struct FormattedRun {
 impl FormattedRun {
     /// Consolidate adjacent runs with identical formatting into single runs
     pub fn consolidate_runs(runs: Vec<FormattedRun>) -> Vec<FormattedRun> {
-        if runs.is_empty() {
-            return runs;
+        let mut consolidated = Vec::with_capacity(runs.len());
+        for run in runs {
+            Self::push_consolidated(&mut consolidated, run);
         }
+        consolidated
+    }
 
-        let mut consolidated = Vec::new();
-        let mut current_run = runs[0].clone();
-
-        for run in runs.into_iter().skip(1) {
-            if current_run.formatting == run.formatting {
-                // Same formatting - merge the text
-                current_run.text.push_str(&run.text);
-            } else {
-                // Different formatting - push current and start new
-                consolidated.push(current_run);
-                current_run = run;
-            }
+    /// Append a run, extending the last one instead if their formatting matches
+    ///
+    /// Building a paragraph this way keeps its runs consolidated as they are read.
+    pub fn push_consolidated(runs: &mut Vec<FormattedRun>, run: FormattedRun) {
+        match runs.last_mut() {
+            Some(last) if last.formatting == run.formatting => last.text.push_str(&run.text),
+            _ => runs.push(run),
         }
-
-        // last run
-        consolidated.push(current_run);
-        consolidated
     }
 }
 
@@ -127,10 +866,16 @@ pub struct TableData {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableCell {
+    /// The cell's plain text, which sorting, queries and CSV read
     pub content: String,
     pub alignment: TextAlignment,
+    /// Formatting of the cell's first run
     pub formatting: TextFormatting,
     pub data_type: CellDataType,
+    /// The cell's text as it's formatted, or empty for a cell of plain
+    /// `content` in `formatting`; see [`TableCell::formatted_runs`]
+    #[serde(default)]
+    pub runs: Vec<FormattedRun>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -141,6 +886,163 @@ pub struct TableMetadata {
     pub column_widths: Vec<usize>,
     pub column_alignments: Vec<TextAlignment>,
     pub title: Option<String>,
+    /// Lines and row shading, from the table's borders and table style
+    #[serde(default)]
+    pub style: TableStyle,
+}
+
+/// How a table is drawn: its lines and the shading of its rows
+///
+/// The default, for tables that set no borders and name no known style, is
+/// doxx's own layout: lines between columns and under the header row.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableStyle {
+    pub borders: TableBorders,
+    /// How the lines are drawn
+    pub line: BorderLine,
+    /// Header row fill (the style's `firstRow` shading) as a hex colour
+    pub header_fill: Option<String>,
+    /// Fill of the first, third, fifth... body rows (`band1Horz` shading)
+    pub band_fill: Option<String>,
+}
+
+/// Which of a table's lines are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableBorders {
+    pub top: bool,
+    pub bottom: bool,
+    pub left: bool,
+    pub right: bool,
+    /// Lines between rows
+    pub inside_horizontal: bool,
+    /// Lines between columns
+    pub inside_vertical: bool,
+    /// A line under the header row
+    pub header: bool,
+}
+
+impl Default for TableBorders {
+    fn default() -> Self {
+        Self {
+            top: false,
+            bottom: false,
+            left: false,
+            right: false,
+            inside_horizontal: false,
+            inside_vertical: true,
+            header: true,
+        }
+    }
+}
+
+impl TableBorders {
+    /// No lines at all
+    pub const NONE: Self = Self {
+        top: false,
+        bottom: false,
+        left: false,
+        right: false,
+        inside_horizontal: false,
+        inside_vertical: false,
+        header: false,
+    };
+}
+
+/// The box-drawing weight of a table's lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BorderLine {
+    #[default]
+    Light,
+    /// Borders of 1.5pt and up, and `thick` ones
+    Heavy,
+    Double,
+}
+
+/// Blank lines before and after a paragraph, from its spacing in the document
+///
+/// The default is doxx's compact layout: one blank line after each paragraph.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ParagraphSpacing {
+    pub before: usize,
+    pub after: usize,
+}
+
+impl Default for ParagraphSpacing {
+    fn default() -> Self {
+        Self {
+            before: 0,
+            after: 1,
+        }
+    }
+}
+
+/// Twentieths of a point (twips) in a column of fixed-width text, ten to the
+/// inch like a typewriter
+pub const TWIPS_PER_COLUMN: u32 = 144;
+
+/// A tab stop (`w:tabs/w:tab`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TabStop {
+    /// Distance from the left margin in twips
+    pub position: u32,
+    pub alignment: TabAlignment,
+    pub leader: TabLeader,
+}
+
+impl TabStop {
+    /// The column the stop falls on in fixed-width text
+    pub fn column(&self) -> usize {
+        ((self.position + TWIPS_PER_COLUMN / 2) / TWIPS_PER_COLUMN) as usize
+    }
+}
+
+/// How text after a tab lines up with its stop
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TabAlignment {
+    /// Text starts at the stop
+    #[default]
+    Left,
+    /// Text is centred on the stop
+    Center,
+    /// Text ends at the stop
+    Right,
+    /// The decimal point is at the stop
+    Decimal,
+}
+
+/// What fills the space up to a tab stop
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TabLeader {
+    #[default]
+    None,
+    /// `.....`, as in a table of contents
+    Dot,
+    /// `·····`
+    MiddleDot,
+    /// `-----`
+    Hyphen,
+    /// `_____`, as in a form to fill in
+    Underscore,
+}
+
+impl TabLeader {
+    /// The space before a stop, `width` columns wide; dots keep a space
+    /// from the text on either side
+    pub fn fill(self, width: usize) -> String {
+        let leader = match self {
+            Self::None => return " ".repeat(width),
+            Self::Dot => '.',
+            Self::MiddleDot => '·',
+            Self::Hyphen => '-',
+            Self::Underscore => '_',
+        };
+        match self {
+            Self::Dot | Self::MiddleDot if width >= 3 => {
+                format!(" {} ", leader.to_string().repeat(width - 2))
+            }
+            _ => leader.to_string().repeat(width),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
@@ -152,6 +1054,115 @@ pub enum TextAlignment {
     Justify,
 }
 
+/// How an image sits among the text: in line with it, or floating beside it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImagePlacement {
+    /// Size in terminal cells, from the size it's drawn at in the document
+    pub columns: u16,
+    pub rows: u16,
+    /// Where a floating image (`wp:anchor`) is positioned; `None` in line
+    pub anchor: Option<ImageAnchor>,
+}
+
+impl ImagePlacement {
+    /// Where a floating image was, e.g. "Floating at the right of the margin,
+    /// level with the paragraph; text wraps around it"; `None` in line
+    pub fn describe(&self) -> Option<String> {
+        let anchor = self.anchor.as_ref()?;
+        // "topMargin" reads as "top margin"
+        let against = |name: &str| {
+            let mut words = String::new();
+            for c in name.chars() {
+                if c.is_uppercase() {
+                    words.push(' ');
+                }
+                words.push(c.to_ascii_lowercase());
+            }
+            words
+        };
+        let (h, v) = (against(&anchor.relative_h), against(&anchor.relative_v));
+        let cells = |n: i32, unit: &str| match n.abs() {
+            1 => format!("1 {unit}"),
+            n => format!("{n} {unit}s"),
+        };
+        let across = match (&anchor.align_h, anchor.x) {
+            (Some(align), _) => format!("at the {align} of the {h}"),
+            (None, 0) => format!("at the left of the {h}"),
+            (None, x) if x > 0 => format!("{} in from the left of the {h}", cells(x, "column")),
+            (None, x) => format!("{} left of the {h}", cells(x, "column")),
+        };
+        let down = match (&anchor.align_v, anchor.y) {
+            (Some(align), _) => format!("at the {align} of the {v}"),
+            (None, 0) => format!("level with the {v}"),
+            (None, y) if y > 0 => format!("{} below the top of the {v}", cells(y, "row")),
+            (None, y) => format!("{} above the {v}", cells(y, "row")),
+        };
+        Some(format!(
+            "Floating {across}, {down}; {}",
+            anchor.wrap.describe()
+        ))
+    }
+
+    /// The side a floating image is aligned to, which text flows around
+    pub fn float_side(&self) -> Option<&str> {
+        let anchor = self.anchor.as_ref()?;
+        if !matches!(
+            anchor.wrap,
+            TextWrap::Square | TextWrap::Tight | TextWrap::Through
+        ) {
+            return None;
+        }
+        match anchor.align_h.as_deref()? {
+            side @ ("left" | "right") => Some(side),
+            "inside" => Some("left"),
+            "outside" => Some("right"),
+            _ => None,
+        }
+    }
+}
+
+/// Position of a floating image against its paragraph, the margins or the page
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImageAnchor {
+    /// What the image is positioned against across the page and down it:
+    /// "page", "margin", "column", "character", "paragraph", "line", …
+    pub relative_h: String,
+    pub relative_v: String,
+    /// Alignment ("left", "center", "right", "top", "bottom", …), for an
+    /// image aligned rather than offset
+    pub align_h: Option<String>,
+    pub align_v: Option<String>,
+    /// Offsets in terminal cells, for an image that isn't aligned
+    pub x: i32,
+    pub y: i32,
+    pub wrap: TextWrap,
+}
+
+/// How text flows around a floating image (`wp:wrapSquare`, `wp:wrapNone`, …)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextWrap {
+    #[default]
+    Square,
+    Tight,
+    Through,
+    TopAndBottom,
+    BehindText,
+    InFrontOfText,
+}
+
+impl TextWrap {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Square => "text wraps around it",
+            Self::Tight => "text wraps tightly around it",
+            Self::Through => "text wraps through it",
+            Self::TopAndBottom => "text above and below it",
+            Self::BehindText => "behind the text",
+            Self::InFrontOfText => "in front of the text",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
 pub enum CellDataType {
     #[default]
@@ -174,9 +1185,132 @@ pub struct SearchResult {
     pub end_pos: usize,
 }
 
+/// A word the spellchecker doesn't know
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Misspelling {
+    pub element_index: usize,
+    /// Which list item or table cell it's in, counting cells across the
+    /// header row and then row by row; 0 for other elements
+    pub item: usize,
+    /// Character offsets of the word in the item's text
+    pub start: usize,
+    pub end: usize,
+    pub word: String,
+}
+
+/// A piece of text search looks through: a heading, paragraph, list item,
+/// table title or cell, caption or equation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchableText {
+    pub element_index: usize,
+    pub text: String,
+}
+
+/// A paragraph or table cell matching `doxx grep`, with the text around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepMatch {
+    /// Where the text comes among everything searched, counting from 1
+    pub position: usize,
+    pub element_index: usize,
+    /// The headings it's under, outermost first
+    pub headings: Vec<String>,
+    pub text: String,
+    /// Byte ranges of each match in `text`
+    pub ranges: Vec<std::ops::Range<usize>>,
+    /// Up to the asked number of texts searched before and after it
+    pub before: Vec<SearchableText>,
+    pub after: Vec<SearchableText>,
+}
+
+/// How much `doxx grep` shows around each match and how many it finds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GrepOptions {
+    /// Texts shown before each match
+    pub before: usize,
+    /// Texts shown after each match
+    pub after: usize,
+    /// Stop after this many matching texts in a document
+    pub max_count: Option<usize>,
+}
+
+/// What a caption labels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaptionKind {
+    Table,
+    Figure,
+}
+
+impl CaptionKind {
+    /// The kind a caption label names, such as `Table` or `Figure` from a
+    /// `SEQ` field; labels for tables in other languages (`Tabelle`,
+    /// `Tableau`) start the same way
+    pub fn of_label(label: &str) -> Self {
+        if label.to_lowercase().starts_with("tab") {
+            Self::Table
+        } else {
+            Self::Figure
+        }
+    }
+}
+
+/// A captioned table or figure, for the lists of tables and figures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptionEntry {
+    pub kind: CaptionKind,
+    pub text: String,
+    pub element_index: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutlineItem {
     pub title: String,
     pub level: u8,
     pub element_index: usize,
 }
+
+/// A heading and the words under it, nested as the headings are
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineSection {
+    pub level: u8,
+    pub number: Option<String>,
+    pub text: String,
+    pub element_index: usize,
+    /// Words in the heading and up to the next heading
+    pub words: usize,
+    /// Words in the heading and everything under it, subsections included
+    pub total_words: usize,
+    pub children: Vec<OutlineSection>,
+}
+
+/// One screen of presenter mode: a section's heading and the elements under it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slide {
+    /// Element index of the heading, or `None` for content before the first one
+    pub heading: Option<usize>,
+    /// Elements shown below the heading
+    pub body: std::ops::Range<usize>,
+}
+
+/// Something in a document the command palette can jump to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavigationTarget {
+    pub kind: TargetKind,
+    pub title: String,
+    pub element_index: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    Heading,
+    Table,
+    Image,
+}
+
+/// An outline entry matched by a fuzzy heading query
+#[derive(Debug, Clone)]
+pub struct HeadingMatch {
+    pub item: OutlineItem,
+    pub score: i64,
+    /// Char indices into `item.title` that matched the query
+    pub positions: Vec<usize>,
+}
@@ -0,0 +1,105 @@
+//! Signature lines and watermarks
+//!
+//! Word draws both as VML shapes with nothing for docx-rs to read: a signature
+//! line is a `v:shape` holding an `o:signatureline` with the suggested signer,
+//! and a watermark is a shape in a header named `PowerPlusWaterMarkObject`
+//! (text along a `v:textpath`) or `WordPictureWatermark` (a picture). The
+//! loader shows them as bracketed notes so that they aren't lost in the
+//! terminal.
+
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::super::models::{FormattedRun, TextFormatting};
+
+/// The note shown for a signature line, naming the signer and their title
+pub(crate) fn signature_line_text(e: &BytesStart) -> String {
+    let signer: Vec<String> = [b"o:suggestedsigner".as_slice(), b"o:suggestedsigner2"]
+        .into_iter()
+        .filter_map(|name| attr(e, name))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect();
+    if signer.is_empty() {
+        "[Signature line]".to_string()
+    } else {
+        format!("[Signature line: {}]", signer.join(", "))
+    }
+}
+
+/// Whether an `o:signatureline` marks its shape as a signature line
+pub(crate) fn is_signature_line(e: &BytesStart) -> bool {
+    e.name().as_ref() == b"o:signatureline"
+        && matches!(attr(e, b"issignatureline").as_deref(), Some("t" | "true"))
+}
+
+/// Watermarks drawn in a header part, as notes such as `[Watermark: DRAFT]`
+pub(crate) fn scan_watermarks(header_xml: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(header_xml);
+    let mut watermarks = Vec::new();
+    // The watermark shape being read, if it's text
+    let mut text_shape = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"v:shape" => {
+                let id = attr(&e, b"id").unwrap_or_default();
+                text_shape = id.starts_with("PowerPlusWaterMarkObject");
+                if id.starts_with("WordPictureWatermark") {
+                    watermarks.push("[Watermark: picture]".to_string());
+                }
+            }
+            Event::End(e) if e.name().as_ref() == b"v:shape" => text_shape = false,
+            Event::Start(e) | Event::Empty(e)
+                if text_shape && e.name().as_ref() == b"v:textpath" =>
+            {
+                let text = attr(&e, b"string").unwrap_or_default();
+                watermarks.push(format!("[Watermark: {}]", text.trim()));
+                text_shape = false;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(watermarks)
+}
+
+/// A note as a run, in italics so that it reads as doxx's rather than the text's
+pub(crate) fn annotation_run(text: String) -> FormattedRun {
+    FormattedRun {
+        text,
+        formatting: TextFormatting {
+            italic: true,
+            ..TextFormatting::default()
+        },
+    }
+}
+
+fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == name)
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_and_picture_watermarks_are_found() {
+        let header = r##"<w:hdr><w:p><w:r><w:pict>
+            <v:shape id="PowerPlusWaterMarkObject357831064" type="#_x0000_t136">
+              <v:textpath style="font-family:&quot;Calibri&quot;" string="DRAFT &amp; CONFIDENTIAL"/>
+            </v:shape>
+            <v:shape id="WordPictureWatermark1"><v:imagedata r:id="rId1"/></v:shape>
+            <v:shape id="Other"><v:textpath string="Not a watermark"/></v:shape>
+        </w:pict></w:r></w:p></w:hdr>"##;
+        assert_eq!(
+            scan_watermarks(header).unwrap(),
+            ["[Watermark: DRAFT & CONFIDENTIAL]", "[Watermark: picture]"]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Quote and code block detection
+//!
+//! Paragraphs in Word's `Quote` and `Intense Quote` styles are block quotes,
+//! and paragraphs set in a monospace font, by their style or by every run,
+//! are code. Built-in styles are matched by their English names, which Word
+//! keeps whatever language the style IDs are in.
+
+use super::formatting::extract_run_text;
+use super::styles::StyleSheet;
+
+/// Fonts whose characters are all one width
+const MONOSPACE_FONTS: [&str; 14] = [
+    "consolas",
+    "courier",
+    "courier new",
+    "cascadia code",
+    "cascadia mono",
+    "dejavu sans mono",
+    "fira code",
+    "inconsolata",
+    "jetbrains mono",
+    "lucida console",
+    "menlo",
+    "monaco",
+    "source code pro",
+    "sf mono",
+];
+
+/// Names of built-in and common styles for quotations and code
+const QUOTE_STYLES: [&str; 3] = ["quote", "intense quote", "block text"];
+const CODE_STYLES: [&str; 4] = ["html preformatted", "code", "source code", "macro text"];
+
+/// What kind of block a paragraph belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BlockKind {
+    Quote,
+    Code,
+}
+
+/// Whether a font name is a monospace font, e.g. `Courier New` or `Consolas`
+pub(crate) fn is_monospace_font(name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    MONOSPACE_FONTS.contains(&name.as_str()) || name.ends_with(" mono")
+}
+
+impl StyleSheet {
+    /// The block a paragraph belongs to by its style, or by every run's font
+    pub fn block_kind(&self, para: &docx_rs::Paragraph) -> Option<BlockKind> {
+        if let Some(style) = &para.property.style {
+            let id = style.val.as_str();
+            let kind = self.style_block_kind(id).or(match id {
+                "Quote" | "IntenseQuote" => Some(BlockKind::Quote),
+                "Code" | "HTMLPreformatted" => Some(BlockKind::Code),
+                _ => None,
+            });
+            if kind.is_some() {
+                return kind;
+            }
+        }
+
+        let mut runs = para
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                docx_rs::ParagraphChild::Run(run) => Some(run),
+                _ => None,
+            })
+            .filter(|run| !extract_run_text(run).trim().is_empty())
+            .peekable();
+        runs.peek()?;
+        runs.all(|run| run_font(run).is_some_and(|font| is_monospace_font(&font)))
+            .then_some(BlockKind::Code)
+    }
+
+    /// The block kind a style has, from its name or font or those of a style it's based on
+    fn style_block_kind(&self, style_id: &str) -> Option<BlockKind> {
+        // The nearest font wins, so a proportional font undoes an inherited monospace one
+        let mut font_seen = false;
+        for (id, style) in self.chain(Some(style_id)) {
+            let name = style.name.as_deref().unwrap_or(id).to_lowercase();
+            if QUOTE_STYLES.contains(&name.as_str()) {
+                return Some(BlockKind::Quote);
+            }
+            if CODE_STYLES.contains(&name.as_str()) {
+                return Some(BlockKind::Code);
+            }
+            if let Some(font) = &style.font {
+                if !font_seen && is_monospace_font(font) {
+                    return Some(BlockKind::Code);
+                }
+                font_seen = true;
+            }
+        }
+        None
+    }
+}
+
+/// A run's direct ASCII font; docx-rs keeps it private, but serializes it
+fn run_font(run: &docx_rs::Run) -> Option<String> {
+    let fonts = serde_json::to_value(run.run_property.fonts.as_ref()?).ok()?;
+    fonts.get("ascii")?.as_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_styles_by_name_font_and_runs() {
+        let styles = StyleSheet::parse(
+            r#"<w:styles>
+            <w:style w:type="paragraph" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+            <w:style w:type="paragraph" w:styleId="Zitat"><w:name w:val="Quote"/><w:basedOn w:val="Normal"/></w:style>
+            <w:style w:type="paragraph" w:customStyle="1" w:styleId="PullQuote"><w:name w:val="Pull Quote"/><w:basedOn w:val="Zitat"/></w:style>
+            <w:style w:type="paragraph" w:customStyle="1" w:styleId="Listing"><w:name w:val="Listing"/><w:pPr><w:rPr><w:rFonts w:ascii="Arial"/></w:rPr></w:pPr><w:rPr><w:rFonts w:ascii="Consolas" w:hAnsi="Consolas"/></w:rPr></w:style>
+            <w:style w:type="paragraph" w:customStyle="1" w:styleId="ListingNote"><w:name w:val="Listing Note"/><w:basedOn w:val="Listing"/><w:rPr><w:rFonts w:ascii="Calibri"/></w:rPr></w:style>
+            </w:styles>"#,
+        )
+        .unwrap();
+        let para = |style: &str| docx_rs::Paragraph::new().style(style);
+        assert_eq!(styles.block_kind(&para("Zitat")), Some(BlockKind::Quote));
+        assert_eq!(
+            styles.block_kind(&para("PullQuote")),
+            Some(BlockKind::Quote)
+        );
+        assert_eq!(styles.block_kind(&para("Listing")), Some(BlockKind::Code));
+        assert_eq!(styles.block_kind(&para("ListingNote")), None);
+        assert_eq!(styles.block_kind(&para("Normal")), None);
+
+        let run = |text: &str, font: &str| {
+            docx_rs::Run::new()
+                .add_text(text)
+                .fonts(docx_rs::RunFonts::new().ascii(font))
+        };
+        let code = docx_rs::Paragraph::new()
+            .add_run(run("let x = 1;", "Courier New"))
+            .add_run(docx_rs::Run::new().add_text(" "));
+        assert_eq!(styles.block_kind(&code), Some(BlockKind::Code));
+        let mixed = code.add_run(run("// note", "Calibri"));
+        assert_eq!(styles.block_kind(&mixed), None);
+        assert_eq!(styles.block_kind(&docx_rs::Paragraph::new()), None);
+    }
+}
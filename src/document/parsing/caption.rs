@@ -0,0 +1,182 @@
+//! Table and figure captions
+//!
+//! Word writes a caption as its own paragraph in the Caption style, numbered
+//! by a `SEQ Table` or `SEQ Figure` field, above or below what it labels. The
+//! loader notes which paragraphs are captions; once the body is read, each one
+//! beside a table or image is moved onto it, as the table's title or the
+//! image's caption, so it travels with it through every view and export.
+
+use std::collections::HashMap;
+
+use super::super::models::*;
+
+/// Whether a paragraph is a caption, going by its style or a `SEQ` field in it
+///
+/// Returns what it labels, from the sequence name or else the first word of
+/// the caption ("Table 3: …").
+pub(crate) fn caption_kind(
+    para: &docx_rs::Paragraph,
+    sequence: Option<&str>,
+    text: &str,
+) -> Option<CaptionKind> {
+    let caption_style = para
+        .property
+        .style
+        .as_ref()
+        .is_some_and(|style| style.val.eq_ignore_ascii_case("caption"));
+    if !caption_style && sequence.is_none() {
+        return None;
+    }
+    let label = sequence.or_else(|| text.split_whitespace().next())?;
+    Some(CaptionKind::of_label(label))
+}
+
+/// Move each caption paragraph onto the table or image it labels
+///
+/// `captions` maps the text of each caption paragraph to what it labels. Table
+/// captions are looked for above the table first and figure captions below the
+/// image, as Word places them; a caption with nothing of its kind beside it
+/// stays a paragraph. Section ends are moved back past removed captions.
+pub(crate) fn attach_captions(
+    elements: Vec<DocumentElement>,
+    captions: &HashMap<String, CaptionKind>,
+    section_ends: &mut [usize],
+) -> Vec<DocumentElement> {
+    if captions.is_empty() {
+        return elements;
+    }
+
+    let caption_of = |element: &DocumentElement| match element {
+        DocumentElement::Paragraph { runs, .. } => {
+            let text: String = runs.iter().map(|run| run.text.as_str()).collect();
+            let text = text.trim();
+            captions.get(text).map(|&kind| (kind, text.to_string()))
+        }
+        _ => None,
+    };
+    let takes = |element: &DocumentElement, kind: CaptionKind| match (element, kind) {
+        (DocumentElement::Table { table }, CaptionKind::Table) => table.metadata.title.is_none(),
+        (DocumentElement::Image { caption, .. }, CaptionKind::Figure) => caption.is_none(),
+        _ => false,
+    };
+
+    let mut elements: Vec<Option<DocumentElement>> = elements.into_iter().map(Some).collect();
+    for index in 0..elements.len() {
+        let Some((kind, text)) = elements[index].as_ref().and_then(caption_of) else {
+            continue;
+        };
+        let before = index.checked_sub(1);
+        let after = Some(index + 1).filter(|&after| after < elements.len());
+        let order = match kind {
+            CaptionKind::Table => [after, before],
+            CaptionKind::Figure => [before, after],
+        };
+        let target = order.into_iter().flatten().find(|&target| {
+            elements[target]
+                .as_ref()
+                .is_some_and(|element| takes(element, kind))
+        });
+        let Some(target) = target else {
+            continue;
+        };
+        match elements[target].as_mut() {
+            Some(DocumentElement::Table { table }) => table.metadata.title = Some(text),
+            Some(DocumentElement::Image { caption, .. }) => *caption = Some(text),
+            _ => continue,
+        }
+        elements[index] = None;
+    }
+
+    // Where each element ends up, to carry the section ends over
+    let mut kept = 0;
+    let mut new_index = Vec::with_capacity(elements.len() + 1);
+    for element in &elements {
+        new_index.push(kept);
+        kept += usize::from(element.is_some());
+    }
+    new_index.push(kept);
+    for end in section_ends.iter_mut() {
+        *end = new_index[(*end).min(elements.len())];
+    }
+
+    elements.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paragraph(text: &str) -> DocumentElement {
+        DocumentElement::Paragraph {
+            runs: vec![FormattedRun {
+                text: text.to_string(),
+                formatting: TextFormatting::default(),
+            }],
+            alignment: TextAlignment::Left,
+            shading: None,
+            rtl: false,
+            spacing: None,
+            tab_stops: Vec::new(),
+        }
+    }
+
+    fn table() -> DocumentElement {
+        DocumentElement::Table {
+            table: TableData {
+                headers: Vec::new(),
+                rows: Vec::new(),
+                metadata: TableMetadata {
+                    column_count: 0,
+                    row_count: 0,
+                    has_headers: false,
+                    column_widths: Vec::new(),
+                    column_alignments: Vec::new(),
+                    title: None,
+                    style: TableStyle::default(),
+                },
+            },
+        }
+    }
+
+    fn image() -> DocumentElement {
+        DocumentElement::Image {
+            description: "Image 1".to_string(),
+            width: None,
+            height: None,
+            relationship_id: None,
+            image_path: None,
+            caption: None,
+            placement: None,
+        }
+    }
+
+    #[test]
+    fn test_captions_move_onto_what_they_label() {
+        let captions = HashMap::from([
+            ("Table 1: Sales".to_string(), CaptionKind::Table),
+            ("Figure 1: Growth".to_string(), CaptionKind::Figure),
+            ("Figure 2: Missing".to_string(), CaptionKind::Figure),
+        ]);
+        let elements = vec![
+            paragraph("Intro"),
+            paragraph("Table 1: Sales"),
+            table(),
+            image(),
+            paragraph("Figure 1: Growth"),
+            paragraph("Figure 2: Missing"),
+        ];
+        let mut section_ends = [3, 6];
+        let elements = attach_captions(elements, &captions, &mut section_ends);
+
+        assert_eq!(elements.len(), 4);
+        assert!(matches!(&elements[1], DocumentElement::Table { table }
+            if table.metadata.title.as_deref() == Some("Table 1: Sales")));
+        assert!(
+            matches!(&elements[2], DocumentElement::Image { caption, .. }
+            if caption.as_deref() == Some("Figure 1: Growth"))
+        );
+        // A caption with no figure beside it is left as it was
+        assert!(matches!(elements[3], DocumentElement::Paragraph { .. }));
+        assert_eq!(section_ends, [2, 4]);
+    }
+}
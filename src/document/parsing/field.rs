@@ -0,0 +1,698 @@
+//! Field codes (`w:fldSimple`, and `w:fldChar` with `w:instrText`)
+//!
+//! Word stores a field as an instruction such as `DATE \@ "d MMMM yyyy"` plus
+//! the result it last computed. Results go stale (the date a letter was typed,
+//! the page count before the last edit), so fields doxx can work out are
+//! evaluated again. Anything else falls back to the cached result.
+
+use std::collections::HashMap;
+use time::OffsetDateTime;
+
+use super::super::cleanup::estimate_page_count;
+use super::super::models::{FieldOptions, IndexEntry};
+
+/// A field instruction split into its name, arguments and switches
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct FieldInstruction {
+    /// Field name, upper-cased (`DATE`, `PAGE`, ...)
+    pub name: String,
+    pub args: Vec<String>,
+    /// Switches such as `\@ "yyyy-MM-dd"` or `\p`, with their argument if they take one
+    pub switches: Vec<(String, Option<String>)>,
+}
+
+impl FieldInstruction {
+    /// Parse an instruction; quoted arguments may contain spaces
+    pub fn parse(instruction: &str) -> Self {
+        let mut tokens = Vec::new();
+        let mut chars = instruction.trim().chars().peekable();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+            } else if c == '"' {
+                chars.next();
+                tokens.push((chars.by_ref().take_while(|&c| c != '"').collect(), true));
+            } else {
+                let mut token = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '"') {
+                    token.push(c);
+                }
+                tokens.push((token, false));
+            }
+        }
+
+        let mut field = Self::default();
+        let mut tokens = tokens.into_iter().peekable();
+        if let Some((name, _)) = tokens.next() {
+            field.name = name.to_uppercase();
+        }
+        while let Some((token, quoted)) = tokens.next() {
+            if quoted || !token.starts_with('\\') {
+                field.args.push(token);
+                continue;
+            }
+            // Format switches and `SEQ` resets take an argument; flags like
+            // `\p` or `\h` don't. Index entries and citations take text,
+            // a category or a bookmark, hyperlinks a bookmark, tooltip or frame
+            let takes_argument = match field.name.as_str() {
+                "XE" | "TA" => matches!(
+                    token.as_str(),
+                    "\\c" | "\\f" | "\\l" | "\\r" | "\\s" | "\\t" | "\\y"
+                ),
+                "HYPERLINK" => matches!(token.as_str(), "\\l" | "\\o" | "\\t"),
+                _ => matches!(token.as_str(), "\\@" | "\\*" | "\\#" | "\\r" | "\\s"),
+            };
+            let argument = takes_argument
+                .then(|| tokens.next().map(|(argument, _)| argument))
+                .flatten();
+            field.switches.push((token, argument));
+        }
+        field
+    }
+
+    fn switch(&self, name: &str) -> Option<&(String, Option<String>)> {
+        self.switches.iter().find(|(switch, _)| switch == name)
+    }
+
+    /// A switch's argument
+    fn argument(&self, name: &str) -> Option<&str> {
+        self.switch(name)?.1.as_deref()
+    }
+
+    /// The bookmark a cross-reference or a hyperlink within the document
+    /// goes to
+    fn reference(&self) -> Option<&str> {
+        match self.name.as_str() {
+            "REF" | "PAGEREF" => self.args.first().map(String::as_str),
+            "HYPERLINK" => self.argument("\\l"),
+            _ => None,
+        }
+    }
+
+    /// Whether this marks an index entry (`XE`) or a citation for the table
+    /// of authorities (`TA`), which show nothing in the text
+    fn is_mark(&self) -> bool {
+        matches!(self.name.as_str(), "XE" | "TA")
+    }
+}
+
+/// What fields are evaluated against
+#[derive(Debug, Clone)]
+pub(crate) struct FieldContext {
+    /// Document path, for `FILENAME`
+    pub file_path: String,
+    /// Current time, for `DATE` and `TIME`
+    pub now: OffsetDateTime,
+    /// Estimated page of the paragraph being read, for `PAGE`
+    pub page: usize,
+    /// Estimated length of the document in pages, for `NUMPAGES`
+    pub pages: usize,
+    /// Current number of each `SEQ` sequence, by upper-cased name
+    sequences: HashMap<String, usize>,
+}
+
+impl FieldContext {
+    /// Context for reading a document of about `words` words
+    ///
+    /// The time is UTC, or taken from `SOURCE_DATE_EPOCH` when that is set, so
+    /// output can be reproduced.
+    pub fn new(file_path: &str, words: usize) -> Self {
+        let now = std::env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|epoch| epoch.parse().ok())
+            .and_then(|epoch| OffsetDateTime::from_unix_timestamp(epoch).ok())
+            .unwrap_or_else(OffsetDateTime::now_utc);
+        Self {
+            file_path: file_path.to_string(),
+            now,
+            page: 1,
+            pages: estimate_page_count(words).max(1),
+            sequences: HashMap::new(),
+        }
+    }
+
+    /// Set the current page from the number of words read so far
+    pub fn set_words_read(&mut self, words: usize) {
+        self.page = words / 250 + 1;
+    }
+
+    /// Step a `SEQ` field's sequence, once per field and before evaluating it
+    ///
+    /// `\r n` resets the sequence to `n` and `\c` repeats its current number.
+    pub fn advance(&mut self, field: &FieldInstruction) {
+        let Some(name) = field.args.first().filter(|_| field.name == "SEQ") else {
+            return;
+        };
+        let number = self.sequences.entry(name.to_uppercase()).or_default();
+        match field
+            .switch("\\r")
+            .and_then(|(_, reset)| reset.as_deref()?.parse().ok())
+        {
+            Some(reset) => *number = reset,
+            None if field.switch("\\c").is_none() => *number += 1,
+            None => {}
+        }
+    }
+
+    /// The field's value, or `None` for fields doxx doesn't evaluate
+    pub fn evaluate(&self, field: &FieldInstruction) -> Option<String> {
+        let picture = field
+            .switch("\\@")
+            .and_then(|(_, picture)| picture.as_deref());
+        let value = match field.name.as_str() {
+            "DATE" => format_date(self.now, picture.unwrap_or("M/d/yyyy")),
+            "TIME" => format_date(self.now, picture.unwrap_or("h:mm AM/PM")),
+            "PAGE" => self.page.to_string(),
+            "NUMPAGES" => self.pages.to_string(),
+            // `\h` hides the number, for sequences only cross-referenced
+            "SEQ" if field.switch("\\h").is_some() => String::new(),
+            "SEQ" => {
+                let name = field.args.first()?.to_uppercase();
+                self.sequences.get(&name).copied().unwrap_or(0).to_string()
+            }
+            "FILENAME" => {
+                let path = std::path::Path::new(&self.file_path);
+                if field.switch("\\p").is_some() {
+                    self.file_path.clone()
+                } else {
+                    path.file_name()?.to_string_lossy().into_owned()
+                }
+            }
+            _ => return None,
+        };
+
+        Some(
+            match field
+                .switch("\\*")
+                .and_then(|(_, format)| format.as_deref())
+            {
+                Some(format) => apply_format(&value, format),
+                None => value,
+            },
+        )
+    }
+}
+
+/// Apply a `\*` general formatting switch
+fn apply_format(value: &str, format: &str) -> String {
+    let number = value.parse::<usize>().ok();
+    match (format, number) {
+        ("Upper", _) => value.to_uppercase(),
+        ("Lower", _) => value.to_lowercase(),
+        ("FirstCap", _) => {
+            let mut chars = value.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        ("Caps", _) => value
+            .split(' ')
+            .map(|word| apply_format(word, "FirstCap"))
+            .collect::<Vec<_>>()
+            .join(" "),
+        ("roman", Some(number)) => roman(number).to_lowercase(),
+        ("ROMAN", Some(number)) => roman(number),
+        ("alphabetic", Some(number)) => alphabetic(number),
+        ("ALPHABETIC", Some(number)) => alphabetic(number).to_uppercase(),
+        // MERGEFORMAT keeps the result's formatting, which is all doxx shows anyway
+        _ => value.to_string(),
+    }
+}
+
+/// 1 is `a`, 26 is `z`, 27 is `aa`
+fn alphabetic(number: usize) -> String {
+    if number == 0 {
+        return String::new();
+    }
+    let letter = (b'a' + ((number - 1) % 26) as u8) as char;
+    letter.to_string().repeat((number - 1) / 26 + 1)
+}
+
+fn roman(mut number: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut text = String::new();
+    for (value, numeral) in NUMERALS {
+        while number >= value {
+            text.push_str(numeral);
+            number -= value;
+        }
+    }
+    text
+}
+
+/// Format a time with a Word date-time picture such as `dddd, MMMM d, yyyy`
+///
+/// `M` is the month and `m` the minute; text in single quotes is copied as is.
+fn format_date(time: OffsetDateTime, picture: &str) -> String {
+    const DAYS: [&str; 7] = [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ];
+
+    let chars: Vec<char> = picture.chars().collect();
+    let mut output = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\'' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&c| c == '\'')
+                .map_or(chars.len(), |end| i + 1 + end);
+            output.extend(&chars[i + 1..end]);
+            i = end + 1;
+            continue;
+        }
+        let rest: String = chars[i..].iter().collect();
+        if let Some(marker) = ["AM/PM", "am/pm"]
+            .into_iter()
+            .find(|marker| rest.starts_with(marker))
+        {
+            let pm = time.hour() >= 12;
+            let text = if pm { "PM" } else { "AM" };
+            output.push_str(&if marker == "am/pm" {
+                text.to_lowercase()
+            } else {
+                text.to_string()
+            });
+            i += marker.len();
+            continue;
+        }
+
+        let run = chars[i..].iter().take_while(|&&next| next == c).count();
+        let hour12 = (time.hour() + 11) % 12 + 1;
+        let month = time.month();
+        match (c, run) {
+            ('d', 1) => output.push_str(&time.day().to_string()),
+            ('d', 2) => output.push_str(&format!("{:02}", time.day())),
+            ('d', 3) => {
+                output.push_str(&DAYS[time.weekday().number_days_from_monday() as usize][..3])
+            }
+            ('d', _) => output.push_str(DAYS[time.weekday().number_days_from_monday() as usize]),
+            ('M', 1) => output.push_str(&(month as u8).to_string()),
+            ('M', 2) => output.push_str(&format!("{:02}", month as u8)),
+            ('M', 3) => output.push_str(&month.to_string()[..3]),
+            ('M', _) => output.push_str(&month.to_string()),
+            ('y', 1 | 2) => output.push_str(&format!("{:02}", time.year() % 100)),
+            ('y', _) => output.push_str(&time.year().to_string()),
+            ('h', 1) => output.push_str(&hour12.to_string()),
+            ('h', _) => output.push_str(&format!("{hour12:02}")),
+            ('H', 1) => output.push_str(&time.hour().to_string()),
+            ('H', _) => output.push_str(&format!("{:02}", time.hour())),
+            ('m', 1) => output.push_str(&time.minute().to_string()),
+            ('m', _) => output.push_str(&format!("{:02}", time.minute())),
+            ('s', 1) => output.push_str(&time.second().to_string()),
+            ('s', _) => output.push_str(&format!("{:02}", time.second())),
+            _ => output.extend(&chars[i..i + run]),
+        }
+        i += run;
+    }
+    output
+}
+
+/// A field that has begun and not yet ended
+#[derive(Debug, Default)]
+struct OpenField {
+    instruction: String,
+    /// Past the separator, into the cached result
+    separated: bool,
+    /// The evaluated value, which replaces the cached result
+    value: Option<String>,
+    /// Bookmark the result refers to (`REF`, `PAGEREF`, `HYPERLINK \l`)
+    reference: Option<String>,
+}
+
+/// Follows fields through a paragraph's runs, deciding what text is shown
+///
+/// Fields can nest (`IF { PAGE } = 1 ...`) and, like a table of contents, span
+/// paragraphs, so the state lives as long as the document body. Cached results
+/// of fields that aren't evaluated pass straight through.
+#[derive(Debug)]
+pub(crate) struct FieldReader {
+    pub context: FieldContext,
+    /// Fields read so far that kept their cached result
+    pub unevaluated: usize,
+    /// Fields read so far whose value depends on when and under what name
+    /// the document was opened: `DATE`, `TIME` and `FILENAME`
+    pub volatile: usize,
+    /// Name of the last `SEQ` field read, as it was written; the loader
+    /// clears it at each paragraph to find the captions
+    pub sequence: Option<String>,
+    /// Index entries and citations marked so far
+    pub entries: Vec<IndexEntry>,
+    /// Each citation's long form and category, by its short form
+    authorities: HashMap<String, (String, u8)>,
+    options: FieldOptions,
+    open: Vec<OpenField>,
+}
+
+impl FieldReader {
+    pub fn new(context: FieldContext, options: FieldOptions) -> Self {
+        Self {
+            context,
+            unevaluated: 0,
+            volatile: 0,
+            sequence: None,
+            entries: Vec::new(),
+            authorities: HashMap::new(),
+            options,
+            open: Vec::new(),
+        }
+    }
+
+    /// `w:fldChar w:fldCharType="begin"`
+    pub fn begin(&mut self) {
+        self.open.push(OpenField::default());
+    }
+
+    /// `w:instrText`
+    pub fn instruction(&mut self, text: &str) {
+        if let Some(field) = self.open.last_mut() {
+            field.instruction.push_str(text);
+        }
+    }
+
+    /// `w:fldChar w:fldCharType="separate"`
+    pub fn separate(&mut self) {
+        if let Some(field) = self.open.last_mut() {
+            field.separated = true;
+            let instruction = FieldInstruction::parse(&field.instruction);
+            field.reference = instruction.reference().map(str::to_string);
+            field.value =
+                Self::first_evaluation(&mut self.context, &mut self.sequence, &instruction);
+        }
+    }
+
+    /// Evaluate a field for the first time, stepping it if it's a sequence
+    fn first_evaluation(
+        context: &mut FieldContext,
+        sequence: &mut Option<String>,
+        instruction: &FieldInstruction,
+    ) -> Option<String> {
+        if instruction.name == "SEQ" {
+            *sequence = instruction.args.first().cloned();
+        }
+        context.advance(instruction);
+        context.evaluate(instruction)
+    }
+
+    /// Text of a run; returns whether it is shown
+    pub fn text(&mut self, text: &str) -> bool {
+        match self.open.last_mut() {
+            Some(field) if !field.separated => {
+                field.instruction.push_str(text);
+                false
+            }
+            _ => self.visible(),
+        }
+    }
+
+    /// `w:fldChar w:fldCharType="end"`; returns text to show in the field's place
+    pub fn end(&mut self) -> Option<String> {
+        let field = self.open.pop()?;
+        let instruction = FieldInstruction::parse(&field.instruction);
+        let text = match field.value {
+            Some(value) => value,
+            // A field without a separator had no cached result
+            None if !field.separated => {
+                Self::first_evaluation(&mut self.context, &mut self.sequence, &instruction)
+                    .unwrap_or_default()
+            }
+            None => String::new(),
+        };
+        if instruction.is_mark() {
+            self.mark(&instruction);
+        }
+        let unknown = self.context.evaluate(&instruction).is_none() && !instruction.is_mark();
+        // A hyperlink's cached result is just its text, which can't go stale
+        if unknown && !matches!(instruction.name.as_str(), "" | "HYPERLINK") {
+            self.unevaluated += 1;
+        }
+        if !unknown && matches!(instruction.name.as_str(), "DATE" | "TIME" | "FILENAME") {
+            self.volatile += 1;
+        }
+        let text = if self.options.show_unknown && unknown {
+            format!("{text} {{{}}}", field.instruction.trim())
+        } else {
+            text
+        };
+
+        match self.open.last_mut() {
+            // A nested field's value is part of the outer instruction
+            Some(outer) if !outer.separated => {
+                outer.instruction.push_str(&text);
+                None
+            }
+            _ => (self.visible() && !text.is_empty()).then_some(text),
+        }
+    }
+
+    /// Record an `XE` or `TA` field's entry
+    ///
+    /// A citation given only by its short form (`TA \s "Smith"`) takes the
+    /// long form and category of the citation that named that short form.
+    fn mark(&mut self, instruction: &FieldInstruction) {
+        let mut entry = IndexEntry {
+            levels: Vec::new(),
+            category: None,
+            see: None,
+            page: self.context.page,
+        };
+        if instruction.name == "XE" {
+            let Some(text) = instruction.args.first() else {
+                return;
+            };
+            entry.levels = text
+                .split(':')
+                .map(|level| level.trim().to_string())
+                .collect();
+            entry.see = instruction.argument("\\t").map(str::to_string);
+        } else {
+            let short = instruction.argument("\\s");
+            let (citation, category) = match (instruction.argument("\\l"), short) {
+                (Some(long), _) => {
+                    let category = instruction
+                        .argument("\\c")
+                        .and_then(|category| category.parse().ok())
+                        .unwrap_or(1);
+                    if let Some(short) = short {
+                        self.authorities
+                            .insert(short.to_string(), (long.to_string(), category));
+                    }
+                    (long.to_string(), category)
+                }
+                (None, Some(short)) => self
+                    .authorities
+                    .get(short)
+                    .cloned()
+                    .unwrap_or_else(|| (short.to_string(), 1)),
+                (None, None) => return,
+            };
+            entry.levels = vec![citation];
+            entry.category = Some(category);
+        }
+        self.entries.push(entry);
+    }
+
+    /// Bookmark the text shown at this point refers to, from the innermost
+    /// field that refers to one
+    pub fn reference(&self) -> Option<&str> {
+        self.open
+            .iter()
+            .rev()
+            .find_map(|field| field.reference.as_deref())
+    }
+
+    /// Whether cached result text at this point is shown
+    fn visible(&self) -> bool {
+        self.open
+            .iter()
+            .all(|field| field.separated && field.value.is_none())
+    }
+
+    /// Read a whole `w:fldSimple`, whose cached result is `cached`
+    pub fn simple(&mut self, instruction: &str, cached: &str) -> Option<String> {
+        self.begin();
+        self.instruction(instruction);
+        self.separate();
+        let shown = self.text(cached).then(|| cached.to_string());
+        let value = self.end();
+        match (shown, value) {
+            (Some(cached), Some(value)) => Some(format!("{cached}{value}")),
+            (shown, value) => shown.or(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> FieldContext {
+        FieldContext {
+            file_path: "/home/me/report.docx".to_string(),
+            // Tuesday 5 March 2024, 14:07:09 UTC
+            now: OffsetDateTime::from_unix_timestamp(1_709_647_629).unwrap(),
+            page: 3,
+            pages: 12,
+            sequences: HashMap::new(),
+        }
+    }
+
+    fn evaluate(instruction: &str) -> Option<String> {
+        context().evaluate(&FieldInstruction::parse(instruction))
+    }
+
+    #[test]
+    fn test_instructions_are_parsed() {
+        let field = FieldInstruction::parse(r#" date \@ "d MMMM yyyy" \* MERGEFORMAT "#);
+        assert_eq!(field.name, "DATE");
+        assert_eq!(
+            field.switches,
+            vec![
+                ("\\@".to_string(), Some("d MMMM yyyy".to_string())),
+                ("\\*".to_string(), Some("MERGEFORMAT".to_string())),
+            ]
+        );
+
+        let field = FieldInstruction::parse(r#"REF _Ref123 \h"#);
+        assert_eq!(field.args, vec!["_Ref123"]);
+        assert_eq!(field.switches, vec![("\\h".to_string(), None)]);
+
+        // A citation's \c is its category, not SEQ's flag
+        let field = FieldInstruction::parse(r#"TA \l "Smith v. Jones" \s "Smith" \c 1"#);
+        assert_eq!(field.argument("\\l"), Some("Smith v. Jones"));
+        assert_eq!(field.argument("\\c"), Some("1"));
+        assert!(field.args.is_empty());
+    }
+
+    #[test]
+    fn test_known_fields_are_evaluated() {
+        assert_eq!(evaluate("DATE").as_deref(), Some("3/5/2024"));
+        assert_eq!(
+            evaluate(r#"DATE \@ "dddd, d MMMM yyyy""#).as_deref(),
+            Some("Tuesday, 5 March 2024")
+        );
+        assert_eq!(
+            evaluate(r#"DATE \@ "yyyy-MM-dd""#).as_deref(),
+            Some("2024-03-05")
+        );
+        assert_eq!(evaluate("TIME").as_deref(), Some("2:07 PM"));
+        assert_eq!(
+            evaluate(r#"TIME \@ "HH:mm:ss 'UTC'""#).as_deref(),
+            Some("14:07:09 UTC")
+        );
+        assert_eq!(evaluate("PAGE").as_deref(), Some("3"));
+        assert_eq!(evaluate(r#"PAGE \* ROMAN"#).as_deref(), Some("III"));
+        assert_eq!(evaluate("NUMPAGES").as_deref(), Some("12"));
+        assert_eq!(evaluate("FILENAME").as_deref(), Some("report.docx"));
+        assert_eq!(
+            evaluate(r#"FILENAME \p \* Upper"#).as_deref(),
+            Some("/HOME/ME/REPORT.DOCX")
+        );
+        assert_eq!(evaluate(r#"REF _Ref123 \h"#), None);
+    }
+
+    #[test]
+    fn test_sequences_count_each_name_on_its_own() {
+        let mut reader = FieldReader::new(context(), FieldOptions::default());
+        let numbers: Vec<_> = [
+            r#"SEQ Table \* ARABIC"#,
+            "SEQ Figure",
+            "SEQ Table",
+            r#"SEQ Table \c"#,
+            r#"SEQ Figure \r 5 \* ALPHABETIC"#,
+            r#"SEQ Table \h"#,
+        ]
+        .into_iter()
+        .map(|instruction| reader.simple(instruction, "9"))
+        .collect();
+        assert_eq!(
+            numbers,
+            [Some("1"), Some("1"), Some("2"), Some("2"), Some("E"), None]
+                .map(|n| n.map(String::from))
+        );
+        assert_eq!(reader.sequence.as_deref(), Some("Table"));
+        assert_eq!(reader.unevaluated, 0);
+    }
+
+    #[test]
+    fn test_reader_replaces_known_results_and_keeps_unknown_ones() {
+        let mut reader = FieldReader::new(context(), FieldOptions::default());
+        reader.begin();
+        reader.instruction(" PAGE ");
+        reader.separate();
+        assert!(!reader.text("1"));
+        assert_eq!(reader.end().as_deref(), Some("3"));
+
+        reader.begin();
+        reader.instruction(r#"REF _Ref123 \h"#);
+        reader.separate();
+        assert!(reader.text("Section 2"));
+        assert_eq!(reader.end(), None);
+        assert_eq!(
+            reader.simple("HYPERLINK \"https://example.com\"", "site"),
+            Some("site".into())
+        );
+        assert_eq!(reader.unevaluated, 1);
+        // Only fields that depend on when and where the file is read count
+        assert_eq!(reader.volatile, 0);
+        assert_eq!(
+            reader.simple("FILENAME", "draft.docx"),
+            Some("report.docx".into())
+        );
+        assert_eq!(reader.volatile, 1);
+
+        let mut reader = FieldReader::new(
+            context(),
+            FieldOptions {
+                show_unknown: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            reader.simple("AUTHOR", "Jane").as_deref(),
+            Some("Jane {AUTHOR}")
+        );
+        assert_eq!(reader.simple("NUMPAGES", "1").as_deref(), Some("12"));
+    }
+
+    #[test]
+    fn test_nested_fields_feed_the_outer_instruction() {
+        let mut reader = FieldReader::new(context(), FieldOptions::default());
+        reader.begin();
+        reader.instruction("IF ");
+        reader.begin();
+        reader.instruction("PAGE");
+        reader.separate();
+        reader.text("1");
+        assert_eq!(reader.end(), None);
+        reader.instruction(r#" = 3 "Third" "Other""#);
+        reader.separate();
+        // IF isn't evaluated, so its cached result is shown
+        assert!(reader.text("Third"));
+        assert_eq!(reader.end(), None);
+        assert!(reader.open.is_empty());
+        assert_eq!(reader.unevaluated, 1);
+    }
+}
@@ -0,0 +1,300 @@
+//! Text boxes, framed paragraphs and floating images
+//!
+//! All three float over the page rather than flowing with the text. Word stores
+//! a text box or floating image inside the paragraph it's anchored to, and a
+//! framed paragraph (`w:framePr`) just before the paragraph it's positioned
+//! against, so in XML order a pull quote or sidebar can land well away from
+//! where it's seen. Visual reading order moves each one before or after its
+//! anchor paragraph by where it's positioned; there's no page layout to place
+//! it more precisely.
+
+use anyhow::Result;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::{HashMap, VecDeque};
+
+use super::super::models::*;
+use super::formatting::{extract_paragraph_alignment, extract_run_formatting, extract_run_text};
+use super::table::extract_table_data;
+
+/// English Metric Units per twip: drawings are measured in EMUs, frames in twips
+pub(crate) const EMUS_PER_TWIP: i64 = 635;
+
+/// Height of a US Letter page in twips, for sections that don't give theirs
+pub(crate) const DEFAULT_PAGE_HEIGHT: u32 = 15840;
+
+/// EMUs across a terminal cell: a tenth of an inch, Word's 10-pitch character
+const EMUS_PER_COLUMN: i64 = 91440;
+
+/// EMUs down a terminal cell, which is about twice as tall as it's wide
+const EMUS_PER_ROW: i64 = 2 * EMUS_PER_COLUMN;
+
+/// EMUs per CSS pixel, at 96 to the inch
+pub(crate) const EMUS_PER_PIXEL: u32 = 9525;
+
+/// Where a floating object is positioned, as far as reading order cares
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct FloatPosition {
+    /// Positioned against the page or its margins, rather than the text
+    pub on_page: bool,
+    /// Vertical offset from what it's positioned against, in EMUs
+    pub y: i64,
+    /// Horizontal offset, in EMUs
+    pub x: i64,
+    /// Aligned to the bottom of the page or margin
+    pub bottom: bool,
+}
+
+impl FloatPosition {
+    /// The position of an anchored text box, or `None` for one in line with the text
+    pub fn of_text_box(text_box: &docx_rs::TextBox) -> Option<Self> {
+        if text_box.position_type != docx_rs::DrawingPositionType::Anchor {
+            return None;
+        }
+        Some(Self {
+            on_page: !matches!(
+                text_box.relative_from_v,
+                docx_rs::RelativeFromVType::Paragraph | docx_rs::RelativeFromVType::Line
+            ),
+            y: offset(&text_box.position_v),
+            x: offset(&text_box.position_h),
+            bottom: matches!(
+                text_box.position_v,
+                docx_rs::DrawingPosition::Align(docx_rs::PicAlign::Bottom)
+            ),
+        })
+    }
+
+    /// The position of a floating image; docx-rs drops alignments, so the
+    /// vertical one comes from the layout scanned from the XML
+    pub fn of_picture(pic: &docx_rs::Pic, layout: &PictureLayout) -> Option<Self> {
+        if pic.position_type != docx_rs::DrawingPositionType::Anchor {
+            return None;
+        }
+        Some(Self {
+            on_page: !matches!(
+                pic.relative_from_v,
+                docx_rs::RelativeFromVType::Paragraph | docx_rs::RelativeFromVType::Line
+            ),
+            y: offset(&pic.position_v),
+            x: offset(&pic.position_h),
+            bottom: layout.align_v.as_deref() == Some("bottom"),
+        })
+    }
+
+    /// Whether visual order reads the float after its anchor paragraph
+    ///
+    /// Floats on the page go before the anchor in the top half of the page and
+    /// after it in the bottom half. Floats positioned against the text go
+    /// before it when moved up and after it when moved down; one level with
+    /// its anchor stays where the XML has it, `stored_after`.
+    pub fn reads_after(&self, page_height: u32, stored_after: bool) -> bool {
+        if self.on_page {
+            self.bottom || self.y > page_height as i64 * EMUS_PER_TWIP / 2
+        } else {
+            match self.y.cmp(&0) {
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Equal => stored_after,
+            }
+        }
+    }
+}
+
+fn offset(position: &docx_rs::DrawingPosition) -> i64 {
+    match position {
+        docx_rs::DrawingPosition::Offset(offset) => *offset as i64,
+        docx_rs::DrawingPosition::Align(_) => 0,
+    }
+}
+
+/// What docx-rs doesn't read from a picture's `wp:inline` or `wp:anchor`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct PictureLayout {
+    pub wrap: TextWrap,
+    /// `wp:align` in `wp:positionH` and `wp:positionV`, for an aligned image
+    pub align_h: Option<String>,
+    pub align_v: Option<String>,
+}
+
+/// Picture layouts by the relationship ID of their image, in document order
+pub(crate) type PictureLayouts = HashMap<String, VecDeque<PictureLayout>>;
+
+/// Read the wrapping and alignment of every picture in `document.xml`
+///
+/// The same image can be drawn more than once, so each relationship ID keeps
+/// its layouts in order, to be taken as docx-rs hands out the pictures.
+pub(crate) fn scan_picture_layouts(document_xml: &str) -> Result<PictureLayouts> {
+    let mut reader = Reader::from_str(document_xml);
+    let mut layouts = PictureLayouts::new();
+    // The drawing being read and the image it shows, and the axis of the
+    // position being read
+    let mut current: Option<(PictureLayout, Option<String>)> = None;
+    let mut horizontal = true;
+    let mut in_align = false;
+    // `behindDoc` on the anchor: with no wrapping, behind the text or in front
+    let mut behind = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) => match e.name().as_ref() {
+                b"wp:inline" | b"wp:anchor" => {
+                    current = Some(Default::default());
+                    behind = e.attributes().flatten().any(|attr| {
+                        attr.key.as_ref() == b"behindDoc" && matches!(&*attr.value, b"1" | b"true")
+                    });
+                }
+                b"wp:positionH" => horizontal = true,
+                b"wp:positionV" => horizontal = false,
+                b"wp:align" => in_align = true,
+                name @ (b"wp:wrapSquare"
+                | b"wp:wrapTight"
+                | b"wp:wrapThrough"
+                | b"wp:wrapTopAndBottom"
+                | b"wp:wrapNone") => {
+                    if let Some((layout, _)) = &mut current {
+                        layout.wrap = match name {
+                            b"wp:wrapTight" => TextWrap::Tight,
+                            b"wp:wrapThrough" => TextWrap::Through,
+                            b"wp:wrapTopAndBottom" => TextWrap::TopAndBottom,
+                            b"wp:wrapNone" if behind => TextWrap::BehindText,
+                            b"wp:wrapNone" => TextWrap::InFrontOfText,
+                            _ => TextWrap::Square,
+                        };
+                    }
+                }
+                b"a:blip" => {
+                    if let Some((_, image @ None)) = &mut current {
+                        *image = e
+                            .attributes()
+                            .flatten()
+                            .find(|attr| attr.key.as_ref() == b"r:embed")
+                            .map(|attr| String::from_utf8_lossy(&attr.value).into_owned());
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(text) if in_align => {
+                if let Some((layout, _)) = &mut current {
+                    let align = Some(text.unescape()?.trim().to_string());
+                    match horizontal {
+                        true => layout.align_h = align,
+                        false => layout.align_v = align,
+                    }
+                }
+            }
+            Event::End(e) => match e.name().as_ref() {
+                b"wp:align" => in_align = false,
+                b"wp:inline" | b"wp:anchor" => {
+                    if let Some((layout, Some(image))) = current.take() {
+                        layouts.entry(image).or_default().push_back(layout);
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(layouts)
+}
+
+/// Size in cells of a picture, and its position if it floats
+pub(crate) fn picture_placement(pic: &docx_rs::Pic, layout: PictureLayout) -> ImagePlacement {
+    let cells = |emus: i64, per_cell: i64| (emus as f64 / per_cell as f64).round() as i64;
+    let anchor = (pic.position_type == docx_rs::DrawingPositionType::Anchor).then(|| ImageAnchor {
+        relative_h: pic.relative_from_h.to_string(),
+        relative_v: pic.relative_from_v.to_string(),
+        x: match layout.align_h {
+            Some(_) => 0,
+            None => cells(offset(&pic.position_h), EMUS_PER_COLUMN) as i32,
+        },
+        y: match layout.align_v {
+            Some(_) => 0,
+            None => cells(offset(&pic.position_v), EMUS_PER_ROW) as i32,
+        },
+        align_h: layout.align_h,
+        align_v: layout.align_v,
+        wrap: layout.wrap,
+    });
+    ImagePlacement {
+        columns: cells(pic.size.0 as i64, EMUS_PER_COLUMN).max(1) as u16,
+        rows: cells(pic.size.1 as i64, EMUS_PER_ROW).max(1) as u16,
+        anchor,
+    }
+}
+
+/// The content of a text box, and where it's positioned
+#[derive(Debug, Clone)]
+pub(crate) struct Float {
+    /// `None` for a text box in line with the text
+    pub position: Option<FloatPosition>,
+    pub elements: Vec<DocumentElement>,
+}
+
+/// The text boxes anchored in a paragraph, in the order their drawings appear
+pub(crate) fn text_box_floats(
+    para: &docx_rs::Paragraph,
+    locale: CellLocale,
+    fidelity: &mut Fidelity,
+) -> Vec<Float> {
+    let text_boxes = para
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            docx_rs::ParagraphChild::Run(run) => Some(run),
+            _ => None,
+        })
+        .flat_map(|run| &run.children)
+        .filter_map(|child| match child {
+            docx_rs::RunChild::Drawing(drawing) => match &drawing.data {
+                Some(docx_rs::DrawingData::TextBox(text_box)) => Some(text_box),
+                _ => None,
+            },
+            _ => None,
+        });
+
+    text_boxes
+        .map(|text_box| Float {
+            position: FloatPosition::of_text_box(text_box),
+            elements: text_box
+                .children
+                .iter()
+                .filter_map(|child| match child {
+                    docx_rs::TextBoxContentChild::Paragraph(para) => text_box_paragraph(para),
+                    docx_rs::TextBoxContentChild::Table(table) => {
+                        extract_table_data(table, locale, TableStyle::default(), fidelity)
+                    }
+                })
+                .collect(),
+        })
+        .filter(|float| !float.elements.is_empty())
+        .collect()
+}
+
+/// A paragraph in a text box, read as plain formatted runs
+fn text_box_paragraph(para: &docx_rs::Paragraph) -> Option<DocumentElement> {
+    let mut runs = Vec::new();
+    for child in &para.children {
+        if let docx_rs::ParagraphChild::Run(run) = child {
+            FormattedRun::push_consolidated(
+                &mut runs,
+                FormattedRun {
+                    text: extract_run_text(run),
+                    formatting: extract_run_formatting(run),
+                },
+            );
+        }
+    }
+    if runs.iter().all(|run| run.text.trim().is_empty()) {
+        return None;
+    }
+    Some(DocumentElement::Paragraph {
+        runs,
+        alignment: extract_paragraph_alignment(para, false),
+        shading: None,
+        rtl: false,
+        spacing: None,
+        tab_stops: Vec::new(),
+    })
+}
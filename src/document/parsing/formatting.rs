@@ -3,7 +3,14 @@
 //! This module handles extraction of text and formatting information
 //! from docx-rs paragraph and run elements.
 
+use anyhow::Result;
+
 use super::super::models::*;
+use super::annotation::{is_signature_line, signature_line_text};
+use super::floating::{FloatPosition, EMUS_PER_TWIP};
+use super::page::PageMark;
+use super::symbol::{run_child_text, SymbolFont};
+use super::theme::ThemeColorRef;
 
 /// Extract plain text from a paragraph, handling various child elements
 pub(crate) fn extract_paragraph_text(para: &docx_rs::Paragraph) -> String {
@@ -39,11 +46,12 @@ pub(crate) fn extract_paragraph_text(para: &docx_rs::Paragraph) -> String {
 /// Extract text from a run using docx-rs features
 pub(crate) fn extract_run_text(run: &docx_rs::Run) -> String {
     let mut text = String::new();
+    let symbol_font = SymbolFont::of_run(run);
 
     for child in &run.children {
         match child {
-            docx_rs::RunChild::Text(text_elem) => {
-                text.push_str(&text_elem.text);
+            docx_rs::RunChild::Text(_) | docx_rs::RunChild::Sym(_) => {
+                text.extend(run_child_text(child, symbol_font));
             }
             docx_rs::RunChild::Tab(_) => {
                 text.push('\t');
@@ -90,12 +98,316 @@ pub(crate) fn extract_run_formatting(run: &docx_rs::Run) -> TextFormatting {
         }
     }
 
-    // For now, skip font size extraction due to API complexity
-    // TODO: Add font size extraction when we understand the API better
+    // These fields are private too, but serialize to their raw values
+    if let Some(vert_align) = &props.vert_align {
+        formatting.vertical_align = match serde_json::to_value(vert_align) {
+            Ok(serde_json::Value::String(val)) if val == "superscript" => {
+                VerticalAlign::Superscript
+            }
+            Ok(serde_json::Value::String(val)) if val == "subscript" => VerticalAlign::Subscript,
+            _ => VerticalAlign::Baseline,
+        };
+    }
+    if let Some(caps) = &props.caps {
+        formatting.all_caps = serde_json::to_value(caps).is_ok_and(|val| val == true);
+    }
+    if let Some(highlight) = &props.highlight {
+        if let Ok(serde_json::Value::String(val)) = serde_json::to_value(highlight) {
+            if val != "none" {
+                formatting.highlight = Some(val);
+            }
+        }
+    }
+
+    if let Some(shading) = &props.shading {
+        if shading.shd_type != docx_rs::ShdType::Nil {
+            formatting.background = shading_fill(&shading.fill);
+        }
+    }
+
+    formatting.font_size = run_font_size(props);
 
     formatting
 }
 
+/// A run's direct font size in points; `w:sz` is in half-points
+pub(crate) fn run_font_size(props: &docx_rs::RunProperty) -> Option<f32> {
+    let half_points = serde_json::to_value(props.sz.as_ref()?).ok()?.as_f64()?;
+    Some(half_points as f32 / 2.0)
+}
+
+/// Paragraph and run properties that docx-rs doesn't parse
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RawParagraphProperties {
+    /// The paragraph is a drop cap frame (`w:framePr w:dropCap="drop|margin"`)
+    pub drop_cap: bool,
+    /// Where the paragraph floats if it's any other frame
+    pub frame: Option<FloatPosition>,
+    /// Properties of each direct `w:r` child, in order
+    pub runs: Vec<RawRunProperties>,
+    /// Paragraph shading fill (`w:pPr/w:shd`)
+    pub shading: Option<String>,
+    /// Right-to-left paragraph (`w:bidi`)
+    pub bidi: bool,
+    /// Simple fields (`w:fldSimple`), which docx-rs reads as plain runs
+    pub simple_fields: Vec<SimpleField>,
+    /// A bottom border (`w:pBdr/w:bottom`), the usual horizontal rule
+    pub bottom_border: bool,
+    /// A left, right or top border as well, boxing the paragraph in
+    pub boxed: bool,
+    /// A VML horizontal line (`o:hr="t"`), as pasted from a web page
+    pub horizontal_line: bool,
+    /// Notes for the signature lines drawn in the paragraph
+    pub signature_lines: Vec<String>,
+    /// Page breaks and the text around them
+    pub page_marks: Vec<PageMark>,
+}
+
+/// A `w:fldSimple` and the runs holding its cached result
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SimpleField {
+    /// `w:instr`
+    pub instruction: String,
+    /// Index of the field's first run among the paragraph's runs
+    pub first_run: usize,
+    pub runs: usize,
+}
+
+impl RawParagraphProperties {
+    pub fn run(&self, run_index: usize) -> RawRunProperties {
+        self.runs.get(run_index).cloned().unwrap_or_default()
+    }
+}
+
+/// Run properties that docx-rs doesn't parse
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RawRunProperties {
+    /// `w:smallCaps`
+    pub small_caps: bool,
+    /// `w:rtl`
+    pub rtl: bool,
+    /// `w:color w:themeColor`, which takes precedence over `w:val`
+    pub theme_color: Option<ThemeColorRef>,
+}
+
+/// Normalise a `w:shd` fill to a `#RRGGBB` colour; `auto` means no fill
+pub(crate) fn shading_fill(fill: &str) -> Option<String> {
+    (fill.len() == 6 && fill.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| format!("#{}", fill.to_ascii_uppercase()))
+}
+
+/// Scan `document.xml` for drop caps and frames, small caps, theme colours, paragraph shading,
+/// text direction, borders, page breaks and simple fields, one entry per top-level body paragraph in
+/// document order
+///
+/// The indices line up with the `Paragraph` children of the docx-rs document
+/// and the `Run` children of each paragraph, which include the runs inside
+/// `w:fldSimple`.
+pub(crate) fn scan_raw_paragraph_properties(
+    document_xml: &str,
+) -> Result<Vec<RawParagraphProperties>> {
+    use quick_xml::events::{BytesStart, Event};
+    use quick_xml::Reader;
+
+    fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+        e.attributes()
+            .flatten()
+            .find(|attr| attr.key.as_ref() == name)
+            .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+    }
+
+    // Anything but an explicit false turns an OOXML toggle property on
+    fn toggle_on(e: &BytesStart) -> bool {
+        e.attributes().flatten().all(|attr| {
+            attr.key.as_ref() != b"w:val"
+                || !matches!(attr.value.as_ref(), b"0" | b"false" | b"off")
+        })
+    }
+
+    let mut reader = Reader::from_str(document_xml);
+    let mut buf = Vec::new();
+    let mut paragraphs = Vec::new();
+    // Open element names, so we know which paragraphs and runs are direct children
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    let mut current: Option<RawParagraphProperties> = None;
+
+    loop {
+        let event = reader.read_event_into(&mut buf)?;
+        let (e, empty) = match &event {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(_) => {
+                if stack.pop().as_deref() == Some(b"w:p".as_slice())
+                    && stack.last().map(Vec::as_slice) == Some(b"w:body".as_slice())
+                {
+                    paragraphs.extend(current.take());
+                }
+                buf.clear();
+                continue;
+            }
+            Event::Eof => break,
+            _ => {
+                buf.clear();
+                continue;
+            }
+        };
+
+        let parent = stack.last().map(Vec::as_slice);
+        let grandparent = stack.len().checked_sub(2).map(|i| stack[i].as_slice());
+        // Runs of the paragraph, directly or inside a simple field
+        let in_simple_field = stack.get(3).map(Vec::as_slice) == Some(b"w:fldSimple");
+        let run_depth = if in_simple_field { 4 } else { 3 };
+        match e.name().as_ref() {
+            b"w:p" if parent == Some(b"w:body") => {
+                current = Some(RawParagraphProperties::default());
+                if empty {
+                    paragraphs.extend(current.take());
+                }
+            }
+            b"w:fldSimple" if parent == Some(b"w:p") && stack.len() == 3 => {
+                if let Some(paragraph) = current.as_mut() {
+                    paragraph.simple_fields.push(SimpleField {
+                        instruction: attr(e, b"w:instr").unwrap_or_default(),
+                        first_run: paragraph.runs.len(),
+                        runs: 0,
+                    });
+                }
+            }
+            b"w:r" if current.is_some() && stack.len() == run_depth => {
+                if let Some(paragraph) = current.as_mut() {
+                    paragraph.runs.push(RawRunProperties::default());
+                    if in_simple_field {
+                        if let Some(field) = paragraph.simple_fields.last_mut() {
+                            field.runs += 1;
+                        }
+                    }
+                }
+            }
+            b"w:smallCaps" | b"w:rtl" | b"w:color"
+                if grandparent == Some(b"w:r") && stack.len() == run_depth + 2 =>
+            {
+                if let Some(run) = current
+                    .as_mut()
+                    .and_then(|paragraph| paragraph.runs.last_mut())
+                {
+                    if e.name().as_ref() == b"w:smallCaps" {
+                        run.small_caps = toggle_on(e);
+                    } else if e.name().as_ref() == b"w:rtl" {
+                        run.rtl = toggle_on(e);
+                    } else if let Some(name) = attr(e, b"w:themeColor") {
+                        run.theme_color = Some(ThemeColorRef::new(
+                            name,
+                            attr(e, b"w:themeTint").as_deref(),
+                            attr(e, b"w:themeShade").as_deref(),
+                        ));
+                    }
+                }
+            }
+            b"w:shd" if parent == Some(b"w:pPr") && stack.len() == 4 => {
+                if let Some(paragraph) = current.as_mut() {
+                    if attr(e, b"w:val").as_deref() != Some("nil") {
+                        paragraph.shading = attr(e, b"w:fill").as_deref().and_then(shading_fill);
+                    }
+                }
+            }
+            b"w:top" | b"w:left" | b"w:right" | b"w:bottom" | b"w:start" | b"w:end"
+                if parent == Some(b"w:pBdr") && stack.len() == 5 =>
+            {
+                if let Some(paragraph) = current.as_mut() {
+                    if !matches!(attr(e, b"w:val").as_deref(), Some("nil" | "none")) {
+                        if e.name().as_ref() == b"w:bottom" {
+                            paragraph.bottom_border = true;
+                        } else {
+                            paragraph.boxed = true;
+                        }
+                    }
+                }
+            }
+            b"w:br" | b"w:lastRenderedPageBreak" | b"w:t"
+                if parent == Some(b"w:r") && !stack.iter().any(|name| name == b"w:txbxContent") =>
+            {
+                if let Some(paragraph) = current.as_mut() {
+                    let mark = match e.name().as_ref() {
+                        b"w:t" => Some(PageMark::Text),
+                        b"w:lastRenderedPageBreak" => Some(PageMark::RenderedBreak),
+                        _ => (attr(e, b"w:type").as_deref() == Some("page"))
+                            .then_some(PageMark::Break),
+                    };
+                    // Text counts once between breaks
+                    if let Some(mark) = mark {
+                        if mark != PageMark::Text || paragraph.page_marks.last() != Some(&mark) {
+                            paragraph.page_marks.push(mark);
+                        }
+                    }
+                }
+            }
+            b"o:signatureline" if is_signature_line(e) => {
+                if let Some(paragraph) = current.as_mut() {
+                    paragraph.signature_lines.push(signature_line_text(e));
+                }
+            }
+            _ if current.is_some() && attr(e, b"o:hr").as_deref() == Some("t") => {
+                if let Some(paragraph) = current.as_mut() {
+                    paragraph.horizontal_line = true;
+                }
+            }
+            b"w:bidi" if parent == Some(b"w:pPr") && stack.len() == 4 => {
+                if let Some(paragraph) = current.as_mut() {
+                    paragraph.bidi = toggle_on(e);
+                }
+            }
+            b"w:framePr" if parent == Some(b"w:pPr") && stack.len() == 4 => {
+                if let Some(paragraph) = current.as_mut() {
+                    paragraph.drop_cap =
+                        matches!(attr(e, b"w:dropCap").as_deref(), Some("drop" | "margin"));
+                    if !paragraph.drop_cap {
+                        // Offsets are in twips; without `w:vAnchor` they're from the margin
+                        let twips = |name| {
+                            attr(e, name)
+                                .and_then(|value| value.parse::<i64>().ok())
+                                .unwrap_or(0)
+                                * EMUS_PER_TWIP
+                        };
+                        paragraph.frame = Some(FloatPosition {
+                            on_page: attr(e, b"w:vAnchor").as_deref() != Some("text"),
+                            y: twips(b"w:y"),
+                            x: twips(b"w:x"),
+                            bottom: attr(e, b"w:yAlign").as_deref() == Some("bottom"),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if !empty {
+            stack.push(e.name().as_ref().to_vec());
+        }
+        buf.clear();
+    }
+
+    Ok(paragraphs)
+}
+
+/// Resolve a paragraph's horizontal alignment from `w:jc`, honouring `w:bidi`
+///
+/// In right-to-left paragraphs `start`/`left` refer to the leading (right) edge,
+/// and a bidi paragraph without an explicit `w:jc` is right-aligned. docx-rs
+/// doesn't read `w:bidi`, so the raw scan supplies it as `rtl`.
+pub(crate) fn extract_paragraph_alignment(para: &docx_rs::Paragraph, rtl: bool) -> TextAlignment {
+    let rtl = rtl || para.property.bidi.unwrap_or(false);
+    let jc = para.property.alignment.as_ref().map(|j| j.val.as_str());
+
+    match (jc, rtl) {
+        (Some("center"), _) => TextAlignment::Center,
+        (Some("both" | "distribute" | "thaiDistribute"), _) => TextAlignment::Justify,
+        (Some("right" | "end"), false) | (Some("left" | "start") | None, true) => {
+            TextAlignment::Right
+        }
+        _ => TextAlignment::Left,
+    }
+}
+
 /// Extract numbering information from docx-rs numbering properties
 pub(crate) fn extract_numbering_info(num_pr: &docx_rs::NumberingProperty) -> Option<NumberingInfo> {
     let num_id = num_pr.id.as_ref()?.id as i32;
@@ -3,19 +3,170 @@
 //! This module handles detection of headings from Word paragraphs,
 //! including style-based detection, text-based heuristics, and
 //! numbering extraction.
+//!
+//! A style is a heading style when it (or a style it's based on) sets an
+//! outline level, or is one of Word's built-in heading styles. Word localizes
+//! the IDs of built-in styles ("berschrift1", "Titre1") but always stores
+//! their English names ("heading 1"), so those are matched by name.
+//!
+//! Paragraphs without a heading style can still look like headings: short
+//! ones set noticeably larger than the document's body text are given a level
+//! by how much larger they are, before the text heuristics are tried.
+
+use std::collections::HashMap;
 
 use super::super::cleanup::is_likely_sentence;
 use super::super::models::*;
 use super::formatting::{
-    extract_numbering_info, extract_paragraph_text, reconstruct_heading_number,
+    extract_numbering_info, extract_paragraph_text, reconstruct_heading_number, run_font_size,
 };
 use super::list::is_likely_list_item;
 use super::numbering::{extract_heading_number_from_text, HeadingInfo};
+use super::styles::StyleSheet;
+
+/// The outline level `w:outlineLvl` sets for body text rather than a heading
+const BODY_TEXT_LEVEL: usize = 9;
+
+/// Word's font size, in points, when `w:docDefaults` doesn't set one
+const DEFAULT_FONT_SIZE: f32 = 10.0;
+
+impl StyleSheet {
+    /// The heading level of a paragraph style, or `None` if it's defined but
+    /// isn't a heading style; styles `styles.xml` doesn't define aren't known
+    ///
+    /// Outline levels of custom styles aren't inherited: numbered documents
+    /// often base their body paragraph styles on their custom heading style
+    /// without resetting its outline level. Built-in heading styles are.
+    fn heading_level(&self, style_id: &str) -> Option<Option<u8>> {
+        let own = self.style(style_id)?;
+        Some(match own.outline_level {
+            Some(level) if level < BODY_TEXT_LEVEL => Some(outline_heading_level(level)),
+            Some(_) => None,
+            None => self
+                .chain(Some(style_id))
+                .find_map(|(_, style)| style.name.as_deref().and_then(built_in_heading_level)),
+        })
+    }
+
+    /// The size, in points, of text in a paragraph of this style without a size of its own
+    pub fn font_size(&self, style_id: Option<&str>) -> f32 {
+        self.chain(style_id)
+            .find_map(|(_, style)| style.font_size)
+            .unwrap_or_else(|| self.body_font_size())
+    }
+
+    /// The size of text whose style doesn't set one (`w:docDefaults`)
+    fn body_font_size(&self) -> f32 {
+        self.default_font_size.unwrap_or(DEFAULT_FONT_SIZE)
+    }
+}
+
+/// The body text size of a document: the size most of its characters are set in
+///
+/// Paragraphs with a heading style are left out, so a document of short
+/// sections doesn't count its headings as body text.
+pub(crate) fn baseline_font_size(document: &docx_rs::Document, styles: &StyleSheet) -> f32 {
+    // Sizes in half-points, so they can be counted exactly
+    let mut characters: HashMap<u32, usize> = HashMap::new();
+    for child in &document.children {
+        let docx_rs::DocumentChild::Paragraph(para) = child else {
+            continue;
+        };
+        if detect_heading_from_paragraph_style(para, styles).is_some() {
+            continue;
+        }
+        let style_size = styles.font_size(para.property.style.as_ref().map(|s| s.val.as_str()));
+        for child in &para.children {
+            if let docx_rs::ParagraphChild::Run(run) = child {
+                let text = super::formatting::extract_run_text(run);
+                let count = text.chars().filter(|c| !c.is_whitespace()).count();
+                if count > 0 {
+                    let size = run_font_size(&run.run_property).unwrap_or(style_size);
+                    *characters.entry((size * 2.0).round() as u32).or_default() += count;
+                }
+            }
+        }
+    }
+    characters
+        .into_iter()
+        .max_by_key(|&(size, count)| (count, std::cmp::Reverse(size)))
+        .map(|(size, _)| size as f32 / 2.0)
+        .unwrap_or(styles.body_font_size())
+}
+
+/// The size of a paragraph's text: the smallest any of its words is set in
+pub(crate) fn paragraph_font_size(runs: &[FormattedRun], style_size: f32) -> Option<f32> {
+    runs.iter()
+        .filter(|run| !run.text.trim().is_empty())
+        .map(|run| run.formatting.font_size.unwrap_or(style_size))
+        .min_by(f32::total_cmp)
+}
+
+/// Detect a heading set in larger type than the body text
+///
+/// A paragraph at least a fifth larger than the baseline is a heading if its
+/// text reads like one; the larger it is, the higher its level. Bold text
+/// needs less of a lift, as bold a point up is a common minor heading.
+pub(crate) fn detect_heading_from_size(
+    text: &str,
+    size: f32,
+    baseline: f32,
+    bold: bool,
+) -> Option<u8> {
+    let text = text.trim();
+    if text.is_empty()
+        || text.chars().count() > 100
+        || text.contains('\n')
+        || is_likely_sentence(text)
+        || is_likely_list_item(text)
+        || text.ends_with(['.', ',', ';', ':'])
+    {
+        return None;
+    }
+    let ratio = size / baseline;
+    match ratio {
+        r if r >= 1.8 => Some(1),
+        r if r >= 1.45 => Some(2),
+        r if r >= 1.2 => Some(3),
+        r if r >= 1.05 && bold => Some(4),
+        _ => None,
+    }
+}
+
+/// The level of a built-in heading style by its (always English) name, e.g. "heading 2"
+fn built_in_heading_level(name: &str) -> Option<u8> {
+    let level = name
+        .to_lowercase()
+        .strip_prefix("heading ")?
+        .parse::<u8>()
+        .ok()?;
+    (1..=9).contains(&level).then(|| level.min(6))
+}
+
+/// Heading level for a `w:outlineLvl` value, which counts from 0
+fn outline_heading_level(level: usize) -> u8 {
+    (level + 1).min(6) as u8
+}
 
 /// Detect heading level from Word paragraph style
-pub(crate) fn detect_heading_from_paragraph_style(para: &docx_rs::Paragraph) -> Option<u8> {
+///
+/// An outline level set on the paragraph itself wins, then the heading level
+/// of its style in `styles.xml`. Styles the file doesn't define fall back to
+/// their ID, so `Heading2` is still a heading in documents without styles.
+pub(crate) fn detect_heading_from_paragraph_style(
+    para: &docx_rs::Paragraph,
+    styles: &StyleSheet,
+) -> Option<u8> {
+    if let Some(outline) = &para.property.outline_lvl {
+        return (outline.v < BODY_TEXT_LEVEL).then(|| outline_heading_level(outline.v));
+    }
+
     // Try to access paragraph properties and style
     if let Some(style) = &para.property.style {
+        if let Some(level) = styles.heading_level(&style.val) {
+            return level;
+        }
+
         // Check for heading styles (Heading1, Heading2, etc.)
         if style.val.starts_with("Heading") || style.val.starts_with("heading") {
             if let Some(level_char) = style.val.chars().last() {
@@ -32,9 +183,12 @@ pub(crate) fn detect_heading_from_paragraph_style(para: &docx_rs::Paragraph) ->
 }
 
 /// Detect heading with automatic or manual numbering
-pub(crate) fn detect_heading_with_numbering(para: &docx_rs::Paragraph) -> Option<HeadingInfo> {
+pub(crate) fn detect_heading_with_numbering(
+    para: &docx_rs::Paragraph,
+    styles: &StyleSheet,
+) -> Option<HeadingInfo> {
     // First check if this is a heading style
-    let heading_level = detect_heading_from_paragraph_style(para)?;
+    let heading_level = detect_heading_from_paragraph_style(para, styles)?;
 
     // Extract text using docx-rs proper text extraction
     let text = extract_paragraph_text(para);
@@ -85,6 +239,11 @@ pub(crate) fn detect_heading_from_text(text: &str, formatting: &TextFormatting)
             return None;
         }
 
+        // Lines set out with tabs, like contents entries and price lists
+        if text.contains('\t') {
+            return None;
+        }
+
         // Exclude if it contains typical sentence patterns
         if text.contains(" the ")
             || text.contains(" and ")
@@ -164,6 +323,53 @@ pub(crate) fn determine_heading_level_from_text(text: &str) -> u8 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_heading_styles_from_outline_levels_and_built_in_names() {
+        let styles = StyleSheet::parse(
+            r#"<w:styles>
+            <w:style w:type="paragraph" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+            <w:style w:type="paragraph" w:styleId="Titre2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:pPr><w:outlineLvl w:val="1"/></w:pPr></w:style>
+            <w:style w:type="paragraph" w:styleId="berschrift3"><w:name w:val="heading 3"/></w:style>
+            <w:style w:type="paragraph" w:customStyle="1" w:styleId="CorpChapter"><w:name w:val="Corp Chapter"/><w:pPr><w:outlineLvl w:val="0"/></w:pPr></w:style>
+            <w:style w:type="paragraph" w:customStyle="1" w:styleId="CorpClause"><w:name w:val="Corp Clause"/><w:basedOn w:val="CorpChapter"/></w:style>
+            <w:style w:type="paragraph" w:customStyle="1" w:styleId="CorpSection"><w:name w:val="Corp Section"/><w:basedOn w:val="Titre2"/></w:style>
+            <w:style w:type="paragraph" w:customStyle="1" w:styleId="HeadingNote"><w:name w:val="Heading Note"/><w:pPr><w:outlineLvl w:val="9"/></w:pPr></w:style>
+            </w:styles>"#,
+        )
+        .unwrap();
+        assert_eq!(styles.heading_level("Normal"), Some(None));
+        assert_eq!(styles.heading_level("Titre2"), Some(Some(2)));
+        assert_eq!(styles.heading_level("berschrift3"), Some(Some(3)));
+        assert_eq!(styles.heading_level("CorpChapter"), Some(Some(1)));
+        assert_eq!(styles.heading_level("CorpClause"), Some(None));
+        assert_eq!(styles.heading_level("CorpSection"), Some(Some(2)));
+        assert_eq!(styles.heading_level("HeadingNote"), Some(None));
+        assert_eq!(styles.heading_level("Heading1"), None);
+
+        let para = |style: &str| docx_rs::Paragraph::new().style(style);
+        assert_eq!(
+            detect_heading_from_paragraph_style(&para("Titre2"), &styles),
+            Some(2)
+        );
+        // Undefined styles still go by their ID
+        assert_eq!(
+            detect_heading_from_paragraph_style(&para("Heading4"), &styles),
+            Some(4)
+        );
+        assert_eq!(
+            detect_heading_from_paragraph_style(&para("HeadingNote"), &styles),
+            None
+        );
+        // The paragraph's own outline level wins
+        let body = para("Titre2").outline_lvl(9);
+        assert_eq!(detect_heading_from_paragraph_style(&body, &styles), None);
+        let chapter = para("Normal").outline_lvl(0);
+        assert_eq!(
+            detect_heading_from_paragraph_style(&chapter, &styles),
+            Some(1)
+        );
+    }
+
     #[test]
     fn test_heading_number_extraction() {
         // Test most common formats (decimal hierarchical)
@@ -0,0 +1,196 @@
+//! Document language
+//!
+//! Word records a language on runs (`w:rPr/w:lang`) and as a document default
+//! in `styles.xml`. Documents from other generators often record none, in
+//! which case the language is guessed from the text with whatlang.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+use super::super::models::DocumentLanguage;
+
+/// `w:val` of a `w:lang` element; `w:eastAsia` and `w:bidi` only name the
+/// language for those scripts
+static LANG_VALUE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<w:lang\b[^>]*?\sw:val="([^"]+)""#).unwrap());
+
+/// How much body text is enough to tell the language
+const SAMPLE_BYTES: usize = 4096;
+
+/// The language the document declares, or one detected from `sample` if it declares none
+///
+/// The most common language on runs wins over the default in `styles.xml`, since
+/// Word's templates set a default whatever language the text is written in.
+pub(crate) fn document_language(
+    document_xml: &str,
+    styles_xml: Option<&str>,
+    sample: &str,
+) -> Option<DocumentLanguage> {
+    let declared = most_common_lang(document_xml)
+        .or_else(|| styles_xml.and_then(|xml| most_common_lang(doc_defaults(xml))));
+    match declared {
+        Some(tag) => Some(DocumentLanguage {
+            tag,
+            detected: false,
+        }),
+        None => detect_language(sample).map(|tag| DocumentLanguage {
+            tag: tag.to_string(),
+            detected: true,
+        }),
+    }
+}
+
+/// Append paragraph text to a detection sample until it is long enough
+///
+/// Returns `false` once the sample is full.
+pub(crate) fn extend_sample(sample: &mut String, text: &str) -> bool {
+    if sample.len() >= SAMPLE_BYTES {
+        return false;
+    }
+    sample.push_str(text);
+    sample.push('\n');
+    sample.len() < SAMPLE_BYTES
+}
+
+fn most_common_lang(xml: &str) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for captures in LANG_VALUE.captures_iter(xml) {
+        let tag = captures.get(1).map_or("", |value| value.as_str());
+        *counts.entry(tag).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .filter(|(tag, _)| !tag.is_empty() && *tag != "x-none")
+        // Ties go to the alphabetically first tag, so the result is stable
+        .max_by(|(a_tag, a), (b_tag, b)| a.cmp(b).then(b_tag.cmp(a_tag)))
+        .map(|(tag, _)| tag.to_string())
+}
+
+/// The `w:docDefaults` element of `styles.xml`, or nothing if there is none
+fn doc_defaults(styles_xml: &str) -> &str {
+    let Some(start) = styles_xml.find("<w:docDefaults") else {
+        return "";
+    };
+    let end = styles_xml[start..]
+        .find("</w:docDefaults>")
+        .map_or(styles_xml.len(), |end| start + end);
+    &styles_xml[start..end]
+}
+
+/// ISO 639-1 code of the language `text` is most likely written in, if whatlang
+/// is confident
+fn detect_language(text: &str) -> Option<&'static str> {
+    let info = whatlang::detect(text).filter(|info| info.is_reliable())?;
+    Some(iso_639_1(info.lang()))
+}
+
+/// Two-letter code for a whatlang language, since BCP 47 prefers those to ISO 639-3
+fn iso_639_1(lang: whatlang::Lang) -> &'static str {
+    use whatlang::Lang::*;
+    match lang {
+        Afr => "af",
+        Aka => "ak",
+        Amh => "am",
+        Ara => "ar",
+        Aze => "az",
+        Bel => "be",
+        Ben => "bn",
+        Bul => "bg",
+        Cat => "ca",
+        Ces => "cs",
+        Cmn => "zh",
+        Dan => "da",
+        Deu => "de",
+        Ell => "el",
+        Eng => "en",
+        Epo => "eo",
+        Est => "et",
+        Fin => "fi",
+        Fra => "fr",
+        Guj => "gu",
+        Heb => "he",
+        Hin => "hi",
+        Hrv => "hr",
+        Hun => "hu",
+        Hye => "hy",
+        Ind => "id",
+        Ita => "it",
+        Jav => "jv",
+        Jpn => "ja",
+        Kan => "kn",
+        Kat => "ka",
+        Khm => "km",
+        Kor => "ko",
+        Lat => "la",
+        Lav => "lv",
+        Lit => "lt",
+        Mal => "ml",
+        Mar => "mr",
+        Mkd => "mk",
+        Mya => "my",
+        Nep => "ne",
+        Nld => "nl",
+        Nob => "nb",
+        Ori => "or",
+        Pan => "pa",
+        Pes => "fa",
+        Pol => "pl",
+        Por => "pt",
+        Ron => "ro",
+        Rus => "ru",
+        Sin => "si",
+        Slk => "sk",
+        Slv => "sl",
+        Sna => "sn",
+        Spa => "es",
+        Srp => "sr",
+        Swe => "sv",
+        Tam => "ta",
+        Tel => "te",
+        Tgl => "tl",
+        Tha => "th",
+        Tuk => "tk",
+        Tur => "tr",
+        Ukr => "uk",
+        Urd => "ur",
+        Uzb => "uz",
+        Vie => "vi",
+        Yid => "yi",
+        Zul => "zu",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_win_over_template_default() {
+        let document = r#"<w:r><w:rPr><w:lang w:val="de-DE"/></w:rPr></w:r>
+            <w:r><w:rPr><w:lang w:eastAsia="ja-JP" w:val="de-DE"/></w:rPr></w:r>
+            <w:r><w:rPr><w:lang w:val="en-US"/></w:rPr></w:r>"#;
+        let styles = r#"<w:docDefaults><w:rPrDefault><w:rPr>
+            <w:lang w:val="en-US" w:eastAsia="en-US" w:bidi="ar-SA"/>
+            </w:rPr></w:rPrDefault></w:docDefaults>"#;
+
+        let language = document_language(document, Some(styles), "").unwrap();
+        assert_eq!(language.tag, "de-DE");
+        assert!(!language.detected);
+
+        let language = document_language("<w:body/>", Some(styles), "").unwrap();
+        assert_eq!(language.tag, "en-US");
+    }
+
+    #[test]
+    fn test_undeclared_language_is_detected() {
+        let sample = "Der Bericht fasst die Ergebnisse des dritten Quartals zusammen. \
+            Alle Beträge sind in Euro angegeben und wurden von der Buchhaltung geprüft.";
+        let language = document_language("<w:body/>", None, sample).unwrap();
+        assert_eq!(language.tag, "de");
+        assert!(language.detected);
+
+        // Too little text to tell
+        assert_eq!(document_language("<w:body/>", None, "OK"), None);
+    }
+}
@@ -50,7 +50,7 @@ pub(crate) fn group_list_items(elements: Vec<DocumentElement>) -> Vec<DocumentEl
 
     for element in elements {
         match &element {
-            DocumentElement::Paragraph { runs } => {
+            DocumentElement::Paragraph { runs, .. } => {
                 // Get the combined text from all runs for list detection
                 let text: String = runs.iter().map(|run| run.text.as_str()).collect();
 
@@ -3,9 +3,27 @@
 //! This module contains specialized parsing functions for different
 //! document elements and features.
 
+pub(crate) mod annotation;
+pub(crate) mod block;
+pub(crate) mod caption;
 pub(crate) mod equation;
+pub(crate) mod field;
+pub(crate) mod floating;
 pub(crate) mod formatting;
 pub(crate) mod heading;
+pub(crate) mod language;
 pub(crate) mod list;
 pub(crate) mod numbering;
+pub(crate) mod page;
+pub(crate) mod protection;
+pub(crate) mod range;
+pub(crate) mod runs;
+pub(crate) mod section;
+pub(crate) mod skipped;
+pub(crate) mod spacing;
+pub(crate) mod styles;
+pub(crate) mod symbol;
 pub(crate) mod table;
+pub(crate) mod table_style;
+pub(crate) mod tabs;
+pub(crate) mod theme;
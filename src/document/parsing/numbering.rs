@@ -2,12 +2,237 @@
 //!
 //! This module handles stateful numbering tracking for both list items
 //! and heading auto-numbering, including hierarchical number generation.
-
+//!
+//! List numbers follow the definitions in `numbering.xml` when the document
+//! has them. Each list instance (`w:num`) points at an abstract definition
+//! (`w:abstractNum`) holding the format of every level. Instances that share
+//! a definition continue each other's count, as Word does, unless one
+//! overrides a level (`w:lvlOverride`), which is how Word restarts a list.
+//! docx-rs reads these definitions but keeps the start values, level text
+//! and restart rules private, so the XML is scanned directly.
+
+use anyhow::Result;
 use once_cell::sync::Lazy;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
 use regex::Regex;
+use std::collections::{HashMap, HashSet};
 
 /// Type alias for numbering counters to simplify complex HashMap type
-pub(crate) type NumberingCounters = std::collections::HashMap<(i32, u8), u32>;
+pub(crate) type NumberingCounters = HashMap<(ListKey, u8), u32>;
+
+/// Whose counters a list item advances
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ListKey {
+    /// A list instance counting on its own
+    Instance(i32),
+    /// Every instance of an abstract definition that doesn't override it
+    Abstract(u32),
+}
+
+/// One level of a list definition
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ListLevel {
+    /// `w:start`, the first number
+    pub start: u32,
+    /// `w:numFmt`: "decimal", "lowerRoman", "bullet", …
+    pub format: String,
+    /// `w:lvlText`, where `%1` to `%9` stand for the number at each level
+    pub text: String,
+    /// `w:lvlRestart`: restart only after a level up to this one (counting
+    /// from 1) is used, or never for 0; `None` restarts after any higher level
+    pub restart: Option<u8>,
+    /// `w:isLgl`: show the numbers of every level in the text as decimals
+    pub legal: bool,
+}
+
+impl Default for ListLevel {
+    fn default() -> Self {
+        Self {
+            // Omitting w:start starts the list at zero
+            start: 0,
+            format: "decimal".to_string(),
+            text: String::new(),
+            restart: None,
+            legal: false,
+        }
+    }
+}
+
+/// A list instance (`w:num`)
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ListInstance {
+    abstract_id: u32,
+    /// `w:startOverride` by level: the number the level restarts at where
+    /// this instance is first used
+    start_overrides: HashMap<u8, u32>,
+    /// Levels the instance redefines with a `w:lvl` of its own
+    levels: HashMap<u8, ListLevel>,
+}
+
+impl ListInstance {
+    fn overrides(&self) -> bool {
+        !self.start_overrides.is_empty() || !self.levels.is_empty()
+    }
+}
+
+/// The list definitions in `numbering.xml`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ListDefinitions {
+    abstracts: HashMap<u32, HashMap<u8, ListLevel>>,
+    instances: HashMap<i32, ListInstance>,
+}
+
+impl ListDefinitions {
+    /// The definition of a level of a list instance, if the document has one
+    pub(crate) fn level(&self, num_id: i32, level: u8) -> Option<&ListLevel> {
+        let instance = self.instances.get(&num_id)?;
+        instance.levels.get(&level).or_else(|| {
+            self.abstracts
+                .get(&instance.abstract_id)
+                .and_then(|levels| levels.get(&level))
+        })
+    }
+
+    fn key(&self, num_id: i32) -> ListKey {
+        match self.instances.get(&num_id) {
+            Some(instance) if !instance.overrides() => ListKey::Abstract(instance.abstract_id),
+            _ => ListKey::Instance(num_id),
+        }
+    }
+}
+
+/// Read the list definitions from `numbering.xml`
+pub(crate) fn scan_list_definitions(numbering_xml: &str) -> Result<ListDefinitions> {
+    fn attr<T: std::str::FromStr>(e: &BytesStart, name: &[u8]) -> Option<T> {
+        e.attributes()
+            .flatten()
+            .find(|attr| attr.key.as_ref() == name)
+            .and_then(|attr| String::from_utf8_lossy(&attr.value).parse().ok())
+    }
+
+    let mut reader = Reader::from_str(numbering_xml);
+    let mut buf = Vec::new();
+    let mut definitions = ListDefinitions::default();
+    // Abstract definitions that take their levels from a numbering style,
+    // and the abstract definition that defines each style
+    let mut style_uses = Vec::new();
+    let mut style_links = HashMap::new();
+    // What's being read: an abstract definition or an instance, and a level
+    let mut abstract_num: Option<(u32, HashMap<u8, ListLevel>)> = None;
+    let mut instance: Option<(i32, ListInstance)> = None;
+    let mut level: Option<(u8, ListLevel)> = None;
+    let mut override_level = None;
+
+    loop {
+        let event = reader.read_event_into(&mut buf)?;
+        let e = match &event {
+            Event::Start(e) | Event::Empty(e) => e,
+            Event::End(e) => {
+                match e.name().as_ref() {
+                    b"w:lvl" => {
+                        if let Some((ilvl, definition)) = level.take() {
+                            if let Some((_, levels)) = &mut abstract_num {
+                                levels.insert(ilvl, definition);
+                            } else if let Some((_, instance)) = &mut instance {
+                                instance.levels.insert(ilvl, definition);
+                            }
+                        }
+                    }
+                    b"w:abstractNum" => {
+                        if let Some((id, levels)) = abstract_num.take() {
+                            definitions.abstracts.insert(id, levels);
+                        }
+                    }
+                    b"w:num" => {
+                        if let Some((id, instance)) = instance.take() {
+                            definitions.instances.insert(id, instance);
+                        }
+                    }
+                    b"w:lvlOverride" => override_level = None,
+                    _ => {}
+                }
+                buf.clear();
+                continue;
+            }
+            Event::Eof => break,
+            _ => {
+                buf.clear();
+                continue;
+            }
+        };
+
+        match e.name().as_ref() {
+            b"w:abstractNum" => {
+                abstract_num = attr(e, b"w:abstractNumId").map(|id| (id, HashMap::new()))
+            }
+            b"w:num" => {
+                instance = attr(e, b"w:numId").map(|id| (id, ListInstance::default()));
+            }
+            b"w:lvlOverride" => override_level = attr::<u8>(e, b"w:ilvl"),
+            b"w:lvl" => level = attr(e, b"w:ilvl").map(|ilvl| (ilvl, ListLevel::default())),
+            b"w:styleLink" => {
+                if let (Some((id, _)), Some(style)) = (&abstract_num, attr::<String>(e, b"w:val")) {
+                    style_links.insert(style, *id);
+                }
+            }
+            b"w:numStyleLink" => {
+                if let (Some((id, _)), Some(style)) = (&abstract_num, attr::<String>(e, b"w:val")) {
+                    style_uses.push((*id, style));
+                }
+            }
+            b"w:abstractNumId" => {
+                if let (Some((_, instance)), Some(id)) = (&mut instance, attr(e, b"w:val")) {
+                    instance.abstract_id = id;
+                }
+            }
+            b"w:startOverride" => {
+                if let (Some((_, instance)), Some(ilvl), Some(start)) =
+                    (&mut instance, override_level, attr(e, b"w:val"))
+                {
+                    instance.start_overrides.insert(ilvl, start);
+                }
+            }
+            name => {
+                if let Some((_, definition)) = &mut level {
+                    match name {
+                        b"w:start" => definition.start = attr(e, b"w:val").unwrap_or(0),
+                        b"w:numFmt" => {
+                            definition.format =
+                                attr(e, b"w:val").unwrap_or_else(|| "decimal".to_string())
+                        }
+                        b"w:lvlText" => definition.text = attr(e, b"w:val").unwrap_or_default(),
+                        b"w:lvlRestart" => definition.restart = attr(e, b"w:val"),
+                        b"w:isLgl" => {
+                            definition.legal = !matches!(
+                                attr::<String>(e, b"w:val").as_deref(),
+                                Some("0" | "false" | "off")
+                            )
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        buf.clear();
+    }
+
+    // A list that uses a numbering style has the levels of the list defining it
+    for (id, style) in style_uses {
+        let levels = style_links
+            .get(&style)
+            .and_then(|source| definitions.abstracts.get(source))
+            .cloned();
+        if let Some(levels) = levels {
+            let target = definitions.abstracts.entry(id).or_default();
+            if target.is_empty() {
+                *target = levels;
+            }
+        }
+    }
+
+    Ok(definitions)
+}
 
 /// Type alias for heading number and cleaned text
 pub(crate) type HeadingNumberInfo = (String, String);
@@ -15,27 +240,46 @@ pub(crate) type HeadingNumberInfo = (String, String);
 /// Manages document-wide numbering state for proper sequential numbering
 #[derive(Debug)]
 pub(crate) struct DocumentNumberingManager {
-    /// Counters for each (numId, level) combination
-    /// Key: (numId, level), Value: current counter
+    /// Counters for each (list, level) combination
+    /// Key: (list, level), Value: current counter
     counters: NumberingCounters,
+    /// The document's list definitions, empty if it has none
+    definitions: ListDefinitions,
+    /// Levels of list instances already started, so start overrides apply once
+    started: HashSet<(i32, u8)>,
 }
 
 impl DocumentNumberingManager {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(definitions: ListDefinitions) -> Self {
         Self {
             counters: NumberingCounters::new(),
+            definitions,
+            started: HashSet::new(),
         }
     }
 
-    /// Generate the next number for a given numId and level
+    /// Whether a level of a list is numbered rather than bulleted, if the
+    /// document defines it
+    pub(crate) fn is_ordered(&self, num_id: i32, level: u8) -> Option<bool> {
+        self.definitions
+            .level(num_id, level)
+            .map(|definition| definition.format != "bullet")
+    }
+
+    /// Generate the next number for a given numId and level, in the format
+    /// the document defines or else the one given
     pub(crate) fn generate_number(
         &mut self,
         num_id: i32,
         level: u8,
         format: NumberingFormat,
     ) -> String {
+        if self.definitions.level(num_id, level).is_some() {
+            return self.generate_defined_number(num_id, level);
+        }
+
         // Get current counter for this (numId, level) combination
-        let key = (num_id, level);
+        let key = (ListKey::Instance(num_id), level);
         let counter_value = {
             let counter = self.counters.entry(key).or_insert(0);
             *counter += 1;
@@ -50,12 +294,97 @@ impl DocumentNumberingManager {
         self.format_hierarchical_number(num_id, level, counter_value, format)
     }
 
+    /// Next number of a level the document defines, laid out by its level text
+    fn generate_defined_number(&mut self, num_id: i32, level: u8) -> String {
+        let definitions = &self.definitions;
+        let key = definitions.key(num_id);
+        let Some(definition) = definitions.level(num_id, level) else {
+            return String::new();
+        };
+
+        let start_override = definitions
+            .instances
+            .get(&num_id)
+            .and_then(|instance| instance.start_overrides.get(&level))
+            .copied();
+        let counter = match start_override {
+            // The first item of a restarted list
+            Some(start) if self.started.insert((num_id, level)) => start,
+            _ => self
+                .counters
+                .get(&(key, level))
+                .map_or(definition.start, |counter| counter.saturating_add(1)),
+        };
+        let bullet = definition.format == "bullet";
+        self.counters.insert((key, level), counter);
+        self.reset_deeper_levels(num_id, level);
+
+        if bullet {
+            "* ".to_string()
+        } else {
+            format!("{} ", self.level_text(num_id, level))
+        }
+    }
+
+    /// The level text of a list item, with each `%N` replaced by the number
+    /// at that level
+    fn level_text(&self, num_id: i32, level: u8) -> String {
+        let key = self.definitions.key(num_id);
+        let Some(definition) = self.definitions.level(num_id, level) else {
+            return String::new();
+        };
+
+        let mut text = String::new();
+        let mut chars = definition.text.chars().peekable();
+        while let Some(c) = chars.next() {
+            let placeholder = chars.peek().and_then(|next| next.to_digit(10));
+            match placeholder {
+                Some(n @ 1..=9) if c == '%' => {
+                    chars.next();
+                    let shown = (n - 1) as u8;
+                    let Some(shown_definition) = self.definitions.level(num_id, shown) else {
+                        continue;
+                    };
+                    // A level not used yet shows its first number
+                    let counter = self
+                        .counters
+                        .get(&(key, shown))
+                        .copied()
+                        .unwrap_or(shown_definition.start);
+                    let format = if definition.legal {
+                        "decimal"
+                    } else {
+                        shown_definition.format.as_str()
+                    };
+                    text.push_str(&format_counter(counter, format));
+                }
+                _ => text.push(c),
+            }
+        }
+        text
+    }
+
     fn reset_deeper_levels(&mut self, num_id: i32, current_level: u8) {
-        // Reset all levels deeper than current_level for this numId
+        // Reset the levels deeper than current_level in this list that restart
+        // after it: by default all of them, or as their w:lvlRestart says
+        let list = match self.definitions.level(num_id, current_level) {
+            Some(_) => self.definitions.key(num_id),
+            None => ListKey::Instance(num_id),
+        };
         let keys_to_reset: Vec<_> = self
             .counters
             .keys()
-            .filter(|(id, level)| *id == num_id && *level > current_level)
+            .filter(|(key, level)| *key == list && *level > current_level)
+            .filter(|(_, level)| {
+                match self
+                    .definitions
+                    .level(num_id, *level)
+                    .and_then(|definition| definition.restart)
+                {
+                    None => true,
+                    Some(restart) => current_level < restart,
+                }
+            })
             .cloned()
             .collect();
 
@@ -137,7 +466,7 @@ impl DocumentNumberingManager {
             let mut parts = Vec::new();
 
             // Add parent level counter (level 0 for this numId)
-            if let Some(parent_counter) = self.counters.get(&(num_id, 0)) {
+            if let Some(parent_counter) = self.counters.get(&(ListKey::Instance(num_id), 0)) {
                 parts.push(parent_counter.to_string());
             }
 
@@ -153,6 +482,37 @@ impl DocumentNumberingManager {
     }
 }
 
+/// Largest number shown in letters; a start value of billions would otherwise
+/// repeat its letter millions of times
+const MAX_LETTER_COUNTER: u32 = 780;
+
+/// Largest number shown in Roman numerals, which have no digit above `M`
+const MAX_ROMAN_COUNTER: u32 = 3999;
+
+/// A number in one of Word's number formats (`w:numFmt`), falling back to
+/// decimal for those that aren't supported or numbers too large for them
+fn format_counter(counter: u32, format: &str) -> String {
+    let lettered = (1..=MAX_LETTER_COUNTER).contains(&counter);
+    let roman = (1..=MAX_ROMAN_COUNTER).contains(&counter);
+    match format {
+        "decimalZero" => format!("{counter:02}"),
+        "lowerLetter" | "upperLetter" if lettered => {
+            // Word goes on from z to aa, bb, …
+            let letter = (b'a' + ((counter - 1) % 26) as u8) as char;
+            let letters = letter.to_string().repeat(((counter - 1) / 26 + 1) as usize);
+            if format == "upperLetter" {
+                letters.to_uppercase()
+            } else {
+                letters
+            }
+        }
+        "lowerRoman" if roman => DocumentNumberingManager::to_roman(counter).to_lowercase(),
+        "upperRoman" if roman => DocumentNumberingManager::to_roman(counter),
+        "none" => String::new(),
+        _ => counter.to_string(),
+    }
+}
+
 /// Different numbering formats supported by Word
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum NumberingFormat {
@@ -219,7 +579,10 @@ impl HeadingNumberTracker {
 }
 
 /// Analyze document structure to determine if automatic numbering should be enabled
-pub(crate) fn analyze_heading_structure(document: &docx_rs::Document) -> bool {
+pub(crate) fn analyze_heading_structure(
+    document: &docx_rs::Document,
+    styles: &super::styles::StyleSheet,
+) -> bool {
     let mut heading_count = 0;
     let mut has_explicit_numbering = false;
     let mut level_counts = [0u32; 6]; // Count headings at each level
@@ -229,7 +592,9 @@ pub(crate) fn analyze_heading_structure(document: &docx_rs::Document) -> bool {
             // Note: detect_heading_from_paragraph_style and extract_paragraph_text
             // will be in the heading/formatting modules, but we use them here
             // This creates a circular dependency that we'll resolve in later phases
-            if let Some(heading_level) = super::heading::detect_heading_from_paragraph_style(para) {
+            if let Some(heading_level) =
+                super::heading::detect_heading_from_paragraph_style(para, styles)
+            {
                 let text = super::formatting::extract_paragraph_text(para);
 
                 // Check if this heading has explicit numbering in the text
@@ -300,3 +665,118 @@ pub(crate) fn extract_heading_number_from_text(text: &str) -> Option<HeadingNumb
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: &str = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main""#;
+
+    /// Four levels: `1.`, `a)`, `1.a.1` (`1.1.1` when legal) and `(i)`
+    fn definitions(instances: &str, legal: bool, restart: &str) -> ListDefinitions {
+        let legal = if legal { "<w:isLgl/>" } else { "" };
+        let xml = format!(
+            r#"<w:numbering {W}>
+            <w:abstractNum w:abstractNumId="0">
+              <w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%1."/></w:lvl>
+              <w:lvl w:ilvl="1"><w:start w:val="1"/><w:numFmt w:val="lowerLetter"/><w:lvlText w:val="%2)"/></w:lvl>
+              <w:lvl w:ilvl="2"><w:start w:val="1"/><w:numFmt w:val="decimal"/>{legal}<w:lvlText w:val="%1.%2.%3"/></w:lvl>
+              <w:lvl w:ilvl="3"><w:start w:val="1"/><w:numFmt w:val="lowerRoman"/>{restart}<w:lvlText w:val="(%4)"/></w:lvl>
+            </w:abstractNum>
+            {instances}
+            </w:numbering>"#
+        );
+        scan_list_definitions(&xml).unwrap()
+    }
+
+    fn numbers(manager: &mut DocumentNumberingManager, items: &[(i32, u8)]) -> Vec<String> {
+        items
+            .iter()
+            .map(|&(num_id, level)| {
+                manager
+                    .generate_number(num_id, level, NumberingFormat::Decimal)
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_instances_continue_their_definition_until_overridden() {
+        let mut manager = DocumentNumberingManager::new(definitions(
+            r#"<w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+            <w:num w:numId="2"><w:abstractNumId w:val="0"/></w:num>
+            <w:num w:numId="3"><w:abstractNumId w:val="0"/>
+              <w:lvlOverride w:ilvl="0"><w:startOverride w:val="1"/></w:lvlOverride></w:num>
+            <w:num w:numId="4"><w:abstractNumId w:val="0"/>
+              <w:lvlOverride w:ilvl="0"><w:startOverride w:val="5"/></w:lvlOverride></w:num>"#,
+            false,
+            "",
+        ));
+        assert_eq!(
+            numbers(&mut manager, &[(1, 0), (1, 1), (1, 1), (2, 0), (2, 1)]),
+            ["1.", "a)", "b)", "2.", "a)"]
+        );
+        // Overriding the start restarts the list, once
+        assert_eq!(
+            numbers(&mut manager, &[(3, 0), (3, 0), (4, 0), (4, 0)]),
+            ["1.", "2.", "5.", "6."]
+        );
+    }
+
+    #[test]
+    fn test_legal_numbering_shows_every_level_as_decimal() {
+        for (legal, expected) in [(false, "1.b.1"), (true, "1.2.1")] {
+            let mut manager = DocumentNumberingManager::new(definitions(
+                r#"<w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>"#,
+                legal,
+                "",
+            ));
+            let numbers = numbers(&mut manager, &[(1, 0), (1, 1), (1, 1), (1, 2)]);
+            assert_eq!(numbers[3], expected);
+        }
+    }
+
+    #[test]
+    fn test_levels_restart_after_the_level_they_name() {
+        let instances = r#"<w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>"#;
+        let items = [(1, 0), (1, 3), (1, 1), (1, 3), (1, 0), (1, 3)];
+
+        // By default any higher level restarts the count
+        let mut manager = DocumentNumberingManager::new(definitions(instances, false, ""));
+        assert_eq!(
+            numbers(&mut manager, &items),
+            ["1.", "(i)", "a)", "(i)", "2.", "(i)"]
+        );
+
+        // Restarting only after the first level carries on past the second
+        let restart = r#"<w:lvlRestart w:val="1"/>"#;
+        let mut manager = DocumentNumberingManager::new(definitions(instances, false, restart));
+        assert_eq!(
+            numbers(&mut manager, &items),
+            ["1.", "(i)", "a)", "(ii)", "2.", "(i)"]
+        );
+    }
+
+    #[test]
+    fn test_huge_start_values_fall_back_to_decimal() {
+        let mut manager = DocumentNumberingManager::new(definitions(
+            r#"<w:num w:numId="1"><w:abstractNumId w:val="0"/>
+              <w:lvlOverride w:ilvl="1"><w:startOverride w:val="4294967295"/></w:lvlOverride>
+              <w:lvlOverride w:ilvl="3"><w:startOverride w:val="4000"/></w:lvlOverride></w:num>"#,
+            false,
+            "",
+        ));
+        assert_eq!(
+            numbers(&mut manager, &[(1, 1), (1, 1), (1, 3)]),
+            ["4294967295)", "4294967295)", "(4000)"]
+        );
+    }
+
+    #[test]
+    fn test_undefined_lists_fall_back_to_their_numbering_id() {
+        let mut manager = DocumentNumberingManager::new(ListDefinitions::default());
+        assert_eq!(manager.is_ordered(1, 0), None);
+        assert_eq!(numbers(&mut manager, &[(7, 0), (7, 0)]), ["1.", "2."]);
+    }
+}
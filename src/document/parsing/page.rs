@@ -0,0 +1,115 @@
+//! Page breaks and page numbers
+//!
+//! Explicit breaks (`w:br w:type="page"` and `w:pageBreakBefore`) become
+//! page break elements. Word also marks where it last broke pages while laying
+//! the document out (`w:lastRenderedPageBreak`), which docx-rs drops; counting
+//! those too numbers the pages after an explicit break as Word showed them.
+
+/// A page break or some text in a paragraph, in document order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PageMark {
+    Text,
+    /// `w:br w:type="page"`
+    Break,
+    /// `w:lastRenderedPageBreak`
+    RenderedBreak,
+}
+
+/// Page numbers of the explicit breaks before and after a paragraph's text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ParagraphBreaks {
+    pub before: Vec<usize>,
+    pub after: Vec<usize>,
+}
+
+/// Counts pages through the body, one paragraph at a time
+#[derive(Debug, Clone)]
+pub(crate) struct PageCounter {
+    page: usize,
+    /// An explicit break has started this page and no text has followed yet;
+    /// Word marks the top of that page as a rendered break too
+    just_broke: bool,
+}
+
+impl Default for PageCounter {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            just_broke: false,
+        }
+    }
+}
+
+impl PageCounter {
+    /// The page the body has reached
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Count a paragraph's breaks, returning the page each explicit break starts
+    ///
+    /// A break with no text before it in the paragraph goes before the
+    /// paragraph, as does `w:pageBreakBefore`; any other goes after it.
+    pub fn paragraph(&mut self, marks: &[PageMark], break_before: bool) -> ParagraphBreaks {
+        let mut breaks = ParagraphBreaks::default();
+        if break_before {
+            breaks.before.push(self.explicit_break());
+        }
+        let mut seen_text = false;
+        for mark in marks {
+            match mark {
+                PageMark::Text => {
+                    seen_text = true;
+                    self.just_broke = false;
+                }
+                PageMark::Break => {
+                    let page = self.explicit_break();
+                    if seen_text {
+                        breaks.after.push(page);
+                    } else {
+                        breaks.before.push(page);
+                    }
+                }
+                PageMark::RenderedBreak => {
+                    if !self.just_broke {
+                        self.page += 1;
+                    }
+                    self.just_broke = false;
+                }
+            }
+        }
+        breaks
+    }
+
+    fn explicit_break(&mut self) -> usize {
+        self.page += 1;
+        self.just_broke = true;
+        self.page
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PageMark::*;
+
+    #[test]
+    fn test_rendered_breaks_after_explicit_ones_are_the_same_page() {
+        let mut counter = PageCounter::default();
+        // Two pages of text flow, then a hard break at the end of a paragraph
+        assert_eq!(
+            counter.paragraph(&[Text, RenderedBreak, Text], false),
+            ParagraphBreaks::default()
+        );
+        let breaks = counter.paragraph(&[Text, Break], false);
+        assert_eq!(breaks.after, [3]);
+        // Word marks the top of the new page as well
+        assert!(counter
+            .paragraph(&[RenderedBreak, Text], false)
+            .before
+            .is_empty());
+        let breaks = counter.paragraph(&[Text], true);
+        assert_eq!(breaks.before, [4]);
+        assert_eq!(counter.paragraph(&[Break, Text], false).before, [5]);
+    }
+}
@@ -0,0 +1,120 @@
+//! Editing restrictions
+//!
+//! `settings.xml` holds two of them: `w:documentProtection`, which limits
+//! editing to comments, tracked changes or form fields (or nothing) while
+//! `w:enforcement` is on, and `w:writeProtection`, a password to save changes
+//! or a recommendation to open the file read-only. Marking a document as
+//! final only sets the `_MarkAsFinal` custom property.
+
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::super::models::{EditRestriction, Protection};
+
+/// The document's protection, if it has any, from `settings.xml` and
+/// `docProps/custom.xml`
+pub(crate) fn read_protection(
+    settings_xml: Option<&str>,
+    custom_xml: Option<&str>,
+) -> Result<Option<Protection>> {
+    let mut protection = match settings_xml {
+        Some(xml) => scan_settings(xml)?,
+        None => Protection::default(),
+    };
+    if let Some(xml) = custom_xml {
+        protection.marked_final = marked_final(xml)?;
+    }
+    Ok((protection != Protection::default()).then_some(protection))
+}
+
+fn scan_settings(settings_xml: &str) -> Result<Protection> {
+    let mut reader = Reader::from_str(settings_xml);
+    let mut protection = Protection::default();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) => match e.name().as_ref() {
+                b"w:documentProtection" if is_on(&e, b"w:enforcement") => {
+                    protection.editing = match attr(&e, b"w:edit").as_deref() {
+                        Some("readOnly") => Some(EditRestriction::ReadOnly),
+                        Some("comments") => Some(EditRestriction::Comments),
+                        Some("trackedChanges") => Some(EditRestriction::TrackedChanges),
+                        Some("forms") => Some(EditRestriction::Forms),
+                        _ => None,
+                    };
+                }
+                b"w:writeProtection" => {
+                    protection.read_only_recommended = is_on(&e, b"w:recommended");
+                    // Older files keep the hash in `w:hash`, newer ones in `w:hashValue`
+                    protection.password_to_modify = attr(&e, b"w:hashValue")
+                        .or_else(|| attr(&e, b"w:hash"))
+                        .is_some();
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(protection)
+}
+
+/// Whether the custom properties mark the document as final
+fn marked_final(custom_xml: &str) -> Result<bool> {
+    let mut reader = Reader::from_str(custom_xml);
+    let mut in_property = false;
+    let mut in_value = false;
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.name().as_ref() == b"property" => {
+                in_property = attr(&e, b"name").as_deref() == Some("_MarkAsFinal");
+            }
+            Event::Start(e) if in_property && e.name().as_ref() == b"vt:bool" => in_value = true,
+            Event::Text(text) if in_value => {
+                return Ok(matches!(text.unescape()?.trim(), "true" | "1"));
+            }
+            Event::End(e) if e.name().as_ref() == b"property" => in_property = false,
+            Event::End(e) if e.name().as_ref() == b"vt:bool" => in_value = false,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(false)
+}
+
+fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == name)
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+/// Whether an on/off attribute such as `w:enforcement` is on
+fn is_on(e: &BytesStart, name: &[u8]) -> bool {
+    matches!(attr(e, name).as_deref(), Some("1" | "true" | "on"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protection_is_only_reported_when_enforced() {
+        let settings = r#"<w:settings><w:documentProtection w:edit="comments" w:enforcement="0"/></w:settings>"#;
+        assert_eq!(read_protection(Some(settings), None).unwrap(), None);
+
+        let settings = r#"<w:settings>
+            <w:writeProtection w:recommended="1" w:cryptProviderType="rsaAES" w:hashValue="abc=" w:saltValue="def="/>
+            <w:documentProtection w:edit="forms" w:enforcement="1"/>
+        </w:settings>"#;
+        let protection = read_protection(Some(settings), None).unwrap().unwrap();
+        assert_eq!(
+            protection.describe(),
+            [
+                "Filling in forms only",
+                "Password to modify",
+                "Read-only recommended"
+            ]
+        );
+    }
+}
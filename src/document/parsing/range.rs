@@ -0,0 +1,268 @@
+//! Loading part of a document (`--range "pages 10-20"`)
+//!
+//! The body XML is cut down to the top-level paragraphs and tables in the
+//! range before anything parses it, so docx-rs and every scanning pass only
+//! read the slice, and only the images it refers to are extracted. Pages are
+//! counted the way page break elements are numbered: by explicit breaks and
+//! where Word last broke pages while laying the document out.
+
+use anyhow::{bail, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashSet;
+
+use super::super::models::{DocumentRange, RangeUnit};
+use super::page::{PageCounter, PageMark};
+
+/// The body XML cut down to a range
+#[derive(Debug, Clone)]
+pub(crate) struct BodySlice {
+    pub xml: String,
+    /// Pages counted up to the start of the slice, to number its page breaks
+    pub pages: PageCounter,
+}
+
+/// A top-level element of the body and what it holds
+#[derive(Debug, Default)]
+struct BodyChild {
+    start: usize,
+    paragraph: bool,
+    break_before: bool,
+    page_marks: Vec<PageMark>,
+    /// Its `w:pPr` holds the properties of the section it ends
+    ends_section: bool,
+}
+
+/// The document XML with only the top-level body elements in `range`
+///
+/// An element is kept if any of its text is on a page in the range. Scanning
+/// stops at the first element past the range.
+pub(crate) fn slice_body(xml: &str, range: DocumentRange) -> Result<BodySlice> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    let mut body = None;
+    let mut child: Option<BodyChild> = None;
+    let mut kept = String::new();
+    let mut pages = PageCounter::default();
+    let mut pages_before = None;
+    let mut section = 1;
+    // The last section anything was kept from
+    let mut kept_section = None;
+    let in_range = |section: usize, first: usize, last: usize| match range.unit {
+        RangeUnit::Sections => range.overlaps(section, section),
+        RangeUnit::Pages => range.overlaps(first, last),
+    };
+
+    loop {
+        let before = reader.buffer_position() as usize;
+        let event = reader.read_event()?;
+        let after = reader.buffer_position() as usize;
+        let (e, empty) = match &event {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(_) => {
+                stack.pop();
+                if stack.len() == 2 {
+                    if let Some(child) = child.take() {
+                        let counted = pages.clone();
+                        let (first, last) = child_pages(&child, &mut pages);
+                        let past = match range.unit {
+                            RangeUnit::Sections => range.last.is_some_and(|last| section > last),
+                            RangeUnit::Pages => range.last.is_some_and(|last| first > last),
+                        };
+                        if past {
+                            break;
+                        }
+                        if in_range(section, first, last) {
+                            pages_before.get_or_insert(counted);
+                            kept.push_str(&xml[child.start..after]);
+                            kept_section = Some(section);
+                        }
+                        if child.ends_section {
+                            section += 1;
+                        }
+                    }
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let parent = stack.last().map(Vec::as_slice);
+        match e.name().as_ref() {
+            b"w:body" if stack.len() == 1 => body = Some(after),
+            // The last section's properties, kept with anything from it
+            b"w:sectPr" if parent == Some(b"w:body") => {
+                let end = if empty {
+                    after
+                } else {
+                    reader.read_to_end(e.name())?;
+                    reader.buffer_position() as usize
+                };
+                if kept_section == Some(section) {
+                    kept.push_str(&xml[before..end]);
+                }
+                continue;
+            }
+            name if parent == Some(b"w:body") => {
+                let top = BodyChild {
+                    start: before,
+                    paragraph: name == b"w:p",
+                    ..BodyChild::default()
+                };
+                if empty {
+                    // An empty paragraph or table takes no room of its own
+                    if in_range(section, pages.page(), pages.page()) {
+                        kept.push_str(&xml[before..after]);
+                    }
+                } else {
+                    child = Some(top);
+                }
+            }
+            name => {
+                if let Some(child) = child.as_mut().filter(|child| child.paragraph) {
+                    read_mark(child, name, e, &stack);
+                }
+            }
+        }
+
+        if !empty {
+            stack.push(e.name().as_ref().to_vec());
+        }
+    }
+
+    let (Some(body), Some(pages_before)) = (body, pages_before) else {
+        let (count, unit) = match range.unit {
+            RangeUnit::Sections => (section, "section"),
+            RangeUnit::Pages => (pages.page(), "page"),
+        };
+        let plural = if count == 1 { "" } else { "s" };
+        bail!("Nothing to read in {range}: the document has {count} {unit}{plural}");
+    };
+    let end = xml.rfind("</w:body>").unwrap_or(xml.len());
+    Ok(BodySlice {
+        xml: format!("{}{kept}{}", &xml[..body], &xml[end..]),
+        pages: pages_before,
+    })
+}
+
+/// Note a page break, page break before or section end in a body paragraph
+fn read_mark(child: &mut BodyChild, name: &[u8], e: &BytesStart, stack: &[Vec<u8>]) {
+    let parent = stack.last().map(Vec::as_slice);
+    let attr = |name: &[u8]| {
+        e.attributes()
+            .flatten()
+            .find(|attr| attr.key.as_ref() == name)
+            .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+    };
+    match name {
+        b"w:pageBreakBefore" if parent == Some(b"w:pPr") && stack.len() == 4 => {
+            child.break_before = !matches!(attr(b"w:val").as_deref(), Some("0" | "false"));
+        }
+        b"w:sectPr" if parent == Some(b"w:pPr") && stack.len() == 4 => child.ends_section = true,
+        b"w:br" | b"w:lastRenderedPageBreak" | b"w:t"
+            if parent == Some(b"w:r") && !stack.iter().any(|name| name == b"w:txbxContent") =>
+        {
+            let mark = match name {
+                b"w:t" => Some(PageMark::Text),
+                b"w:lastRenderedPageBreak" => Some(PageMark::RenderedBreak),
+                _ => (attr(b"w:type").as_deref() == Some("page")).then_some(PageMark::Break),
+            };
+            if let Some(mark) = mark {
+                if mark != PageMark::Text || child.page_marks.last() != Some(&mark) {
+                    child.page_marks.push(mark);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The first and last page an element's text is on, counting its breaks
+fn child_pages(child: &BodyChild, pages: &mut PageCounter) -> (usize, usize) {
+    let marks = &child.page_marks;
+    let Some(first_text) = marks.iter().position(|mark| *mark == PageMark::Text) else {
+        pages.paragraph(marks, child.break_before);
+        return (pages.page(), pages.page());
+    };
+    let last_text = marks
+        .iter()
+        .rposition(|mark| *mark == PageMark::Text)
+        .unwrap_or(first_text);
+    pages.paragraph(&marks[..first_text], child.break_before);
+    let first = pages.page();
+    pages.paragraph(&marks[first_text..=last_text], false);
+    let last = pages.page();
+    pages.paragraph(&marks[last_text + 1..], false);
+    (first, last)
+}
+
+/// Relationship IDs the XML refers to (`r:embed`, `r:id`, `r:link`), which
+/// name the images it shows
+pub(crate) fn relationship_ids(xml: &str) -> HashSet<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut ids = HashSet::new();
+    while let Ok(event) = reader.read_event() {
+        match event {
+            Event::Start(e) | Event::Empty(e) => ids.extend(
+                e.attributes()
+                    .flatten()
+                    .filter(|attr| attr.key.as_ref().starts_with(b"r:"))
+                    .map(|attr| String::from_utf8_lossy(&attr.value).into_owned()),
+            ),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = concat!(
+        r#"<w:document><w:body>"#,
+        r#"<w:p><w:r><w:t>One</w:t></w:r></w:p>"#,
+        r#"<w:p><w:r><w:t>Still one, </w:t></w:r><w:r><w:lastRenderedPageBreak/><w:t>now two</w:t></w:r></w:p>"#,
+        r#"<w:p><w:pPr><w:sectPr/></w:pPr><w:r><w:t>Two</w:t><w:br w:type="page"/></w:r></w:p>"#,
+        r#"<w:tbl><w:tr><w:tc><w:p><w:r><w:t>Three</w:t></w:r></w:p></w:tc></w:tr></w:tbl>"#,
+        r#"<w:p><w:pPr><w:pageBreakBefore/></w:pPr><w:r><w:t>Four</w:t></w:r></w:p>"#,
+        r#"<w:sectPr><w:pgSz w:w="12240"/></w:sectPr>"#,
+        r#"</w:body></w:document>"#,
+    );
+
+    fn sliced(range: &str) -> Result<BodySlice> {
+        slice_body(BODY, range.parse().unwrap())
+    }
+
+    #[test]
+    fn test_pages_keep_paragraphs_running_onto_them() {
+        let slice = sliced("page 2").unwrap();
+        assert_eq!(slice.pages.page(), 1);
+        assert!(slice.xml.contains("Still one"));
+        assert!(slice.xml.contains(">Two<"));
+        assert!(!slice.xml.contains(">One<") && !slice.xml.contains("Three"));
+        assert!(!slice.xml.contains("w:pgSz"));
+        assert!(slice.xml.ends_with("</w:body></w:document>"));
+
+        let slice = sliced("pages 3-").unwrap();
+        assert_eq!(slice.pages.page(), 3);
+        assert!(slice.xml.contains("Three") && slice.xml.contains("Four"));
+        assert!(slice.xml.contains("w:pgSz"));
+    }
+
+    #[test]
+    fn test_sections_end_at_paragraph_section_properties() {
+        let slice = sliced("section 2").unwrap();
+        assert!(!slice.xml.contains("Two"));
+        assert!(slice.xml.contains("Three") && slice.xml.contains("Four"));
+
+        let error = sliced("sections 3-4").unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "Nothing to read in sections 3-4: the document has 2 sections"
+        );
+    }
+}
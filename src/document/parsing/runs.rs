@@ -0,0 +1,173 @@
+//! Run coalescing
+//!
+//! Some generators write one `w:r` per character, every one with the same
+//! properties. docx-rs builds a full run for each, and so would every pass
+//! after it, so a few pages of such output can take seconds to load. Adjacent
+//! runs that hold nothing but text, with byte-identical properties, are merged
+//! in the XML before anything else reads it.
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+/// A run holding nothing but (optional) properties and text
+struct TextRun<'a> {
+    /// Byte range of the whole `w:r` element
+    start: usize,
+    end: usize,
+    /// The `w:rPr` element as written, or empty
+    properties: &'a str,
+    /// Escaped text of each `w:t`, in order
+    text: Vec<&'a str>,
+}
+
+/// The document XML with adjacent plain-text runs merged, or `None` if no runs
+/// could be merged (or the XML couldn't be read)
+///
+/// Runs are only merged when nothing but whitespace separates them, so they are
+/// always siblings and anything between them, such as a bookmark or a field
+/// character, keeps them apart.
+pub(crate) fn coalesce_runs(xml: &str) -> Option<String> {
+    let runs = text_runs(xml).ok()?;
+
+    let mut coalesced = String::new();
+    let mut copied = 0;
+    let mut start = 0;
+    while start < runs.len() {
+        let mut end = start + 1;
+        while end < runs.len()
+            && runs[end].properties == runs[start].properties
+            && xml[runs[end - 1].end..runs[end].start].trim().is_empty()
+        {
+            end += 1;
+        }
+
+        if end - start > 1 {
+            if coalesced.is_empty() {
+                coalesced.reserve(xml.len());
+            }
+            coalesced.push_str(&xml[copied..runs[start].start]);
+            coalesced.push_str("<w:r>");
+            coalesced.push_str(runs[start].properties);
+            coalesced.push_str(r#"<w:t xml:space="preserve">"#);
+            for run in &runs[start..end] {
+                coalesced.extend(run.text.iter().copied());
+            }
+            coalesced.push_str("</w:t></w:r>");
+            copied = runs[end - 1].end;
+        }
+        start = end;
+    }
+
+    if copied == 0 {
+        return None;
+    }
+    coalesced.push_str(&xml[copied..]);
+    Some(coalesced)
+}
+
+/// Every run in the document that holds only properties and text
+fn text_runs(xml: &str) -> quick_xml::Result<Vec<TextRun<'_>>> {
+    let mut reader = Reader::from_str(xml);
+    let mut runs = Vec::new();
+    // The run being read and whether it still holds only text
+    let mut current: Option<(TextRun, bool)> = None;
+
+    loop {
+        let before = reader.buffer_position() as usize;
+        let event = reader.read_event()?;
+        let after = reader.buffer_position() as usize;
+
+        let Some((run, plain)) = &mut current else {
+            match event {
+                Event::Start(e) if e.name().as_ref() == b"w:r" => {
+                    let run = TextRun {
+                        start: before,
+                        end: before,
+                        properties: "",
+                        text: Vec::new(),
+                    };
+                    current = Some((run, true));
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            continue;
+        };
+
+        match event {
+            Event::Start(e) => {
+                let name = e.name();
+                let span = reader.read_to_end(name)?;
+                let end = reader.buffer_position() as usize;
+                match name.as_ref() {
+                    b"w:rPr" if run.properties.is_empty() && run.text.is_empty() => {
+                        run.properties = &xml[before..end];
+                    }
+                    b"w:t" => run.text.push(&xml[span.start as usize..span.end as usize]),
+                    _ => *plain = false,
+                }
+            }
+            Event::Empty(e) => match e.name().as_ref() {
+                b"w:rPr" if run.properties.is_empty() && run.text.is_empty() => {
+                    run.properties = &xml[before..after];
+                }
+                b"w:t" => {}
+                _ => *plain = false,
+            },
+            Event::Text(text) if text.iter().all(u8::is_ascii_whitespace) => {}
+            Event::End(_) => {
+                let (mut run, plain) = current.take().expect("inside a run");
+                if plain {
+                    run.end = after;
+                    runs.push(run);
+                }
+            }
+            Event::Eof => break,
+            _ => *plain = false,
+        }
+    }
+
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOLD: &str = "<w:rPr><w:b/></w:rPr>";
+
+    fn run(properties: &str, text: &str) -> String {
+        format!(r#"<w:r w:rsidR="00A1">{properties}<w:t xml:space="preserve">{text}</w:t></w:r>"#)
+    }
+
+    #[test]
+    fn test_identical_runs_are_merged() {
+        let xml = format!(
+            "<w:p>{}{}\n{}{}</w:p>",
+            run(BOLD, "T"),
+            run(BOLD, "o"),
+            run(BOLD, "p &amp;"),
+            run("", " tail")
+        );
+        assert_eq!(
+            coalesce_runs(&xml).unwrap(),
+            format!(
+                r#"<w:p><w:r>{BOLD}<w:t xml:space="preserve">Top &amp;</w:t></w:r>{}</w:p>"#,
+                run("", " tail")
+            )
+        );
+    }
+
+    #[test]
+    fn test_runs_with_more_than_text_are_kept() {
+        let field = r#"<w:r><w:fldChar w:fldCharType="begin"/></w:r>"#;
+        let bookmark = r#"<w:bookmarkStart w:id="0" w:name="x"/>"#;
+        let xml = format!(
+            "<w:p>{}{field}{}{bookmark}{}</w:p>",
+            run("", "a"),
+            run("", "b"),
+            run("", "c")
+        );
+        assert_eq!(coalesce_runs(&xml), None);
+    }
+}
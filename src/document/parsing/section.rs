@@ -0,0 +1,169 @@
+//! Section page setup (`w:sectPr`)
+//!
+//! Each section's properties sit in the `w:pPr` of its last paragraph, except
+//! for the last section, whose `w:sectPr` is the final child of `w:body`.
+//! docx-rs only reads the final one, so the body XML is scanned directly.
+
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::super::models::*;
+
+/// The end of a section and its page setup
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SectionBreak {
+    /// Index among the top-level body paragraphs of the paragraph that ends the
+    /// section, or `None` for the last section
+    pub paragraph: Option<usize>,
+    pub page: PageSetup,
+}
+
+/// Scan `document.xml` for section properties, in document order
+pub(crate) fn scan_section_breaks(document_xml: &str) -> Result<Vec<SectionBreak>> {
+    fn attr<T: std::str::FromStr>(e: &BytesStart, name: &[u8]) -> Option<T> {
+        e.attributes()
+            .flatten()
+            .find(|attr| attr.key.as_ref() == name)
+            .and_then(|attr| String::from_utf8_lossy(&attr.value).parse().ok())
+    }
+
+    let mut reader = Reader::from_str(document_xml);
+    let mut buf = Vec::new();
+    let mut breaks = Vec::new();
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    let mut paragraphs = 0;
+    // The section being read, and the body paragraph it ends
+    let mut current: Option<SectionBreak> = None;
+
+    loop {
+        let event = reader.read_event_into(&mut buf)?;
+        let (e, empty) = match &event {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                if e.name().as_ref() == b"w:sectPr" {
+                    breaks.extend(current.take());
+                }
+                stack.pop();
+                buf.clear();
+                continue;
+            }
+            Event::Eof => break,
+            _ => {
+                buf.clear();
+                continue;
+            }
+        };
+
+        let parent = stack.last().map(Vec::as_slice);
+        match e.name().as_ref() {
+            b"w:p" if parent == Some(b"w:body") => paragraphs += 1,
+            b"w:sectPr"
+                if parent == Some(b"w:body") || (parent == Some(b"w:pPr") && stack.len() == 4) =>
+            {
+                let page = PageSetup::default();
+                let paragraph = (parent == Some(b"w:pPr")).then(|| paragraphs - 1);
+                current = Some(SectionBreak { paragraph, page });
+                if empty {
+                    breaks.extend(current.take());
+                }
+            }
+            name if parent == Some(b"w:sectPr") => {
+                if let Some(page) = current.as_mut().map(|section| &mut section.page) {
+                    match name {
+                        b"w:pgSz" => {
+                            page.width = attr(e, b"w:w");
+                            page.height = attr(e, b"w:h");
+                            let landscape = match attr::<String>(e, b"w:orient") {
+                                Some(orient) => orient == "landscape",
+                                None => page.width > page.height,
+                            };
+                            if landscape {
+                                page.orientation = Orientation::Landscape;
+                            }
+                        }
+                        b"w:pgMar" => {
+                            page.margins = Some(PageMargins {
+                                top: attr(e, b"w:top").unwrap_or_default(),
+                                right: attr(e, b"w:right").unwrap_or_default(),
+                                bottom: attr(e, b"w:bottom").unwrap_or_default(),
+                                left: attr(e, b"w:left").unwrap_or_default(),
+                            });
+                        }
+                        b"w:cols" => {
+                            page.columns = attr(e, b"w:num").unwrap_or(1).max(1);
+                            page.column_gap = attr(e, b"w:space");
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if !empty {
+            stack.push(e.name().as_ref().to_vec());
+        }
+        buf.clear();
+    }
+
+    Ok(breaks)
+}
+
+/// Split `len` elements into sections, given the element index each section
+/// break falls at (`None` for the end of the document)
+pub(crate) fn build_sections(breaks: Vec<(Option<usize>, PageSetup)>, len: usize) -> Vec<Section> {
+    let mut start = 0;
+    breaks
+        .into_iter()
+        .map(|(end, page)| {
+            let end = end.unwrap_or(len).clamp(start, len);
+            let section = Section { start, end, page };
+            start = end;
+            section
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: &str = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main""#;
+
+    #[test]
+    fn test_section_breaks_are_found() {
+        let xml = format!(
+            r#"<w:document {W}><w:body>
+            <w:p><w:r><w:t>One</w:t></w:r></w:p>
+            <w:p><w:pPr><w:sectPr><w:pgSz w:w="12240" w:h="15840"/>
+                <w:pgMar w:top="1440" w:right="1080" w:bottom="-720" w:left="1080"/></w:sectPr></w:pPr></w:p>
+            <w:tbl><w:tr><w:tc><w:p/></w:tc></w:tr></w:tbl>
+            <w:p/>
+            <w:sectPr><w:pgSz w:w="15840" w:h="12240"/><w:cols w:num="2" w:space="720"/></w:sectPr>
+            </w:body></w:document>"#
+        );
+        let breaks = scan_section_breaks(&xml).unwrap();
+        assert_eq!(breaks.len(), 2);
+
+        assert_eq!(breaks[0].paragraph, Some(1));
+        assert_eq!(breaks[0].page.orientation, Orientation::Portrait);
+        assert_eq!(breaks[0].page.margins.unwrap().bottom, -720);
+        assert_eq!(breaks[0].page.describe(), "Portrait");
+
+        // Landscape from the page size alone; the table's paragraph isn't counted
+        assert_eq!(breaks[1].paragraph, None);
+        assert_eq!(breaks[1].page.width, Some(15840));
+        assert_eq!(breaks[1].page.column_gap, Some(720));
+        assert_eq!(breaks[1].page.describe(), "Landscape, 2 columns");
+    }
+
+    #[test]
+    fn test_sections_cover_the_elements() {
+        let page = PageSetup::default();
+        let sections = build_sections(vec![(Some(2), page.clone()), (None, page)], 5);
+        assert_eq!((sections[0].start, sections[0].end), (0, 2));
+        assert_eq!((sections[1].start, sections[1].end), (2, 5));
+    }
+}
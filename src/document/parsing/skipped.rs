@@ -0,0 +1,66 @@
+//! Charts, SmartArt and embedded objects in the body
+//!
+//! docx-rs reads a chart or SmartArt drawing as a drawing with nothing in it,
+//! and an embedded (OLE) object such as a spreadsheet not at all, so the
+//! loader counts them from the XML to say what the view leaves out. Word
+//! writes a fallback picture for some of them in `mc:Fallback`; that's the
+//! same object again and isn't counted.
+
+use anyhow::Result;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+/// How many of each the body holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct SkippedContent {
+    pub charts: usize,
+    pub diagrams: usize,
+    pub embedded_objects: usize,
+}
+
+pub(crate) fn scan_skipped_content(document_xml: &str) -> Result<SkippedContent> {
+    let mut reader = Reader::from_str(document_xml);
+    let mut skipped = SkippedContent::default();
+    let mut fallback_depth = 0;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.name().as_ref() == b"mc:Fallback" => fallback_depth += 1,
+            Event::End(e) if e.name().as_ref() == b"mc:Fallback" => fallback_depth -= 1,
+            Event::Start(e) | Event::Empty(e) if fallback_depth == 0 => match e.name().as_ref() {
+                b"c:chart" => skipped.charts += 1,
+                b"dgm:relIds" => skipped.diagrams += 1,
+                b"w:object" => skipped.embedded_objects += 1,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_pictures_are_not_counted_twice() {
+        let body = r#"<w:body>
+            <w:p><w:r><mc:AlternateContent>
+              <mc:Choice Requires="cx1"><w:drawing><a:graphicData><c:chart r:id="rId5"/></a:graphicData></w:drawing></mc:Choice>
+              <mc:Fallback><w:object><v:shape/></w:object></mc:Fallback>
+            </mc:AlternateContent></w:r></w:p>
+            <w:p><w:r><w:drawing><a:graphicData><dgm:relIds r:dm="rId6"/></a:graphicData></w:drawing></w:r></w:p>
+            <w:p><w:r><w:object><o:OLEObject ProgID="Excel.Sheet.12"/></w:object></w:r></w:p>
+        </w:body>"#;
+        assert_eq!(
+            scan_skipped_content(body).unwrap(),
+            SkippedContent {
+                charts: 1,
+                diagrams: 1,
+                embedded_objects: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Paragraph spacing (`w:spacing`) in lines of text
+//!
+//! Spacing before and after a paragraph comes from its own properties, then
+//! its style and the styles that one is based on, then the document defaults.
+//! Word measures it in twentieths of a point (or hundredths of a line); doxx
+//! lays out a blank line for every 12pt, the height of a line of body text.
+
+use quick_xml::events::BytesStart;
+
+use super::super::models::ParagraphSpacing;
+use super::styles::StyleSheet;
+
+/// Twentieths of a point in a line of 12pt text
+const TWIPS_PER_LINE: u32 = 240;
+
+/// Spacing before and after, in twips, where it's set
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Spacing {
+    pub before: Option<u32>,
+    pub after: Option<u32>,
+}
+
+impl Spacing {
+    /// Read `w:spacing`; `w:beforeLines` and `w:afterLines` win over points
+    pub fn from_element(e: &BytesStart) -> Self {
+        let value = |name: &[u8]| {
+            e.attributes()
+                .flatten()
+                .find(|attr| attr.key.as_ref() == name)
+                .and_then(|attr| String::from_utf8_lossy(&attr.value).parse::<u32>().ok())
+        };
+        let lines = |name: &[u8]| value(name).map(|hundredths| hundredths * TWIPS_PER_LINE / 100);
+        Self {
+            before: lines(b"w:beforeLines").or_else(|| value(b"w:before")),
+            after: lines(b"w:afterLines").or_else(|| value(b"w:after")),
+        }
+    }
+
+    /// Fill in what isn't set from `other`
+    fn or(self, other: Self) -> Self {
+        Self {
+            before: self.before.or(other.before),
+            after: self.after.or(other.after),
+        }
+    }
+}
+
+impl StyleSheet {
+    /// Blank lines before and after a paragraph, rounded to the nearest line
+    pub fn paragraph_spacing(&self, para: &docx_rs::Paragraph) -> ParagraphSpacing {
+        let style = para
+            .property
+            .style
+            .as_ref()
+            .map(|style| style.val.as_str())
+            .or(self.default_paragraph.as_deref());
+        // Each attribute comes from the nearest style that sets it
+        let spacing = self
+            .chain(style)
+            .fold(direct_spacing(para), |spacing, (_, style)| {
+                spacing.or(style.spacing)
+            })
+            .or(self.default_spacing);
+        let lines = |twips: Option<u32>| {
+            ((twips.unwrap_or(0) + TWIPS_PER_LINE / 2) / TWIPS_PER_LINE) as usize
+        };
+        ParagraphSpacing {
+            before: lines(spacing.before),
+            after: lines(spacing.after),
+        }
+    }
+}
+
+/// The paragraph's own `w:spacing`; docx-rs keeps it private, but serializes it
+fn direct_spacing(para: &docx_rs::Paragraph) -> Spacing {
+    let Some(value) = para
+        .property
+        .line_spacing
+        .as_ref()
+        .and_then(|spacing| serde_json::to_value(spacing).ok())
+    else {
+        return Spacing::default();
+    };
+    let twips = |name: &str| value.get(name).and_then(|v| v.as_u64()).map(|v| v as u32);
+    let lines = |name: &str| twips(name).map(|hundredths| hundredths * TWIPS_PER_LINE / 100);
+    Spacing {
+        before: lines("beforeLines").or_else(|| twips("before")),
+        after: lines("afterLines").or_else(|| twips("after")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spacing_is_inherited_attribute_by_attribute() {
+        let styles = StyleSheet::parse(
+            r#"<w:styles>
+            <w:docDefaults><w:pPrDefault><w:pPr><w:spacing w:after="160"/></w:pPr></w:pPrDefault></w:docDefaults>
+            <w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+            <w:style w:type="paragraph" w:styleId="Tight"><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="0"/></w:pPr></w:style>
+            <w:style w:type="paragraph" w:styleId="Spaced"><w:basedOn w:val="Tight"/><w:pPr><w:spacing w:beforeLines="200"/></w:pPr></w:style>
+            </w:styles>"#,
+        )
+        .unwrap();
+        let spacing = |para: docx_rs::Paragraph| {
+            let spacing = styles.paragraph_spacing(&para);
+            (spacing.before, spacing.after)
+        };
+        assert_eq!(spacing(docx_rs::Paragraph::new()), (0, 1));
+        assert_eq!(spacing(docx_rs::Paragraph::new().style("Tight")), (0, 0));
+        assert_eq!(spacing(docx_rs::Paragraph::new().style("Spaced")), (2, 0));
+        let direct = docx_rs::Paragraph::new()
+            .style("Spaced")
+            .line_spacing(docx_rs::LineSpacing::new().before(480));
+        assert_eq!(spacing(direct), (2, 0));
+        let direct = docx_rs::Paragraph::new()
+            .style("Tight")
+            .line_spacing(docx_rs::LineSpacing::new().before(480).after(120));
+        assert_eq!(spacing(direct), (2, 1));
+    }
+}
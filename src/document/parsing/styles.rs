@@ -0,0 +1,262 @@
+//! Paragraph and table styles (`styles.xml`)
+//!
+//! Each `w:style` is read once into a [`Style`], as declared, with the
+//! properties headings, spacing, quote and code blocks, tab stops and table
+//! looks need. Those modules resolve what a paragraph or table inherits by
+//! walking [`StyleSheet::chain`], which follows `w:basedOn` from the nearest
+//! style outwards.
+
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+
+use super::spacing::Spacing;
+use super::table_style::{edge, Fill, TableDefinition};
+use super::tabs::TabSetting;
+
+/// An attribute's value, e.g. `w:val`
+pub(crate) fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == name)
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+/// One `w:style`, before `w:basedOn` is followed
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Style {
+    /// The name Word shows; built-in styles keep their English names
+    pub name: Option<String>,
+    pub based_on: Option<String>,
+    /// `w:outlineLvl`, counting from 0
+    pub outline_level: Option<usize>,
+    /// The list a paragraph style numbers its paragraphs in (`w:numPr`)
+    pub num_id: Option<i32>,
+    pub level: Option<u8>,
+    /// The text's size in points; a `w:sz` in `w:pPr` sizes the paragraph mark
+    pub font_size: Option<f32>,
+    /// The text's ASCII font (`w:rFonts`)
+    pub font: Option<String>,
+    pub spacing: Spacing,
+    pub tabs: Vec<TabSetting>,
+    pub table: TableDefinition,
+}
+
+/// Everything doxx reads from `styles.xml`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StyleSheet {
+    styles: HashMap<String, Style>,
+    /// The style of paragraphs that don't name one (`w:default="1"`)
+    pub default_paragraph: Option<String>,
+    /// The style of tables that don't name one
+    pub default_table: Option<String>,
+    /// The size of text whose style doesn't set one (`w:docDefaults`)
+    pub default_font_size: Option<f32>,
+    /// `w:docDefaults/w:pPrDefault`
+    pub default_spacing: Spacing,
+}
+
+impl Style {
+    /// Take in an element of the style, found at `path` below `w:style`
+    fn read(&mut self, path: &[&[u8]], e: &BytesStart, condition: Option<&str>) {
+        let val = || attr(e, b"w:val");
+        match path {
+            [b"w:name"] => self.name = val(),
+            [b"w:basedOn"] => self.based_on = val(),
+            [b"w:pPr", b"w:outlineLvl"] => self.outline_level = val().and_then(|v| v.parse().ok()),
+            [b"w:pPr", b"w:numPr", b"w:numId"] => self.num_id = val().and_then(|v| v.parse().ok()),
+            [b"w:pPr", b"w:numPr", b"w:ilvl"] => self.level = val().and_then(|v| v.parse().ok()),
+            [b"w:pPr", b"w:spacing"] => self.spacing = Spacing::from_element(e),
+            [b"w:pPr", b"w:tabs", b"w:tab"] => self.tabs.extend(TabSetting::from_element(e)),
+            [b"w:rPr", b"w:sz"] => self.font_size = font_size(e),
+            [b"w:rPr", b"w:rFonts"] => self.font = attr(e, b"w:ascii"),
+            [b"w:tblPr", b"w:tblBorders", side] => self.table.borders.set(side, edge(e)),
+            [b"w:tblStylePr", b"w:tcPr", b"w:tcBorders", b"w:bottom"]
+                if condition == Some("firstRow") =>
+            {
+                self.table.header_rule = Some(edge(e))
+            }
+            [b"w:tblStylePr", b"w:tcPr", b"w:shd"] => match condition {
+                Some("firstRow") => self.table.header_fill = Fill::parse(e),
+                Some("band1Horz") => self.table.band_fill = Fill::parse(e),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// A `w:sz` in points; Word stores half-points
+fn font_size(e: &BytesStart) -> Option<f32> {
+    Some(attr(e, b"w:val")?.parse::<f32>().ok()? / 2.0)
+}
+
+impl StyleSheet {
+    /// Read every style of `styles.xml` in one pass
+    pub fn parse(styles_xml: &str) -> Result<Self> {
+        let mut sheet = Self::default();
+        let mut reader = Reader::from_str(styles_xml);
+        // The open elements, so each property is read only where it applies
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        // The style being read, and how deep its `w:style` sits
+        let mut current: Option<(String, Style, usize)> = None;
+        // The `w:type` of the `w:tblStylePr` being read
+        let mut condition: Option<String> = None;
+
+        loop {
+            let event = reader.read_event()?;
+            match &event {
+                Event::Start(e) | Event::Empty(e) => {
+                    path.push(e.name().as_ref().to_vec());
+                    let names: Vec<&[u8]> = path.iter().map(Vec::as_slice).collect();
+                    match &mut current {
+                        Some((_, style, depth)) => {
+                            let within = &names[*depth + 1..];
+                            if within == [b"w:tblStylePr"] {
+                                condition = attr(e, b"w:type");
+                            }
+                            style.read(within, e, condition.as_deref());
+                        }
+                        None => match names.as_slice() {
+                            [.., b"w:style"] if matches!(event, Event::Start(_)) => {
+                                current = attr(e, b"w:styleId")
+                                    .map(|id| (id, Style::default(), names.len() - 1));
+                                if let Some((id, _, _)) = &current {
+                                    sheet.read_default(e, id);
+                                }
+                            }
+                            [.., b"w:rPrDefault", b"w:rPr", b"w:sz"] => {
+                                sheet.default_font_size = font_size(e)
+                            }
+                            [.., b"w:pPrDefault", b"w:pPr", b"w:spacing"] => {
+                                sheet.default_spacing = Spacing::from_element(e)
+                            }
+                            _ => {}
+                        },
+                    }
+                    if matches!(event, Event::Empty(_)) {
+                        path.pop();
+                    }
+                }
+                Event::End(e) => {
+                    path.pop();
+                    match e.name().as_ref() {
+                        b"w:tblStylePr" => condition = None,
+                        b"w:style" => {
+                            if let Some((id, style, _)) = current.take() {
+                                sheet.styles.insert(id, style);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(sheet)
+    }
+
+    /// Note a `w:style` marked `w:default="1"` as the default of its type
+    fn read_default(&mut self, e: &BytesStart, id: &str) {
+        if !attr(e, b"w:default").is_some_and(|value| value == "1" || value == "true") {
+            return;
+        }
+        match attr(e, b"w:type").as_deref() {
+            Some("paragraph") => self.default_paragraph = Some(id.to_string()),
+            Some("table") => self.default_table = Some(id.to_string()),
+            _ => {}
+        }
+    }
+
+    /// A style as declared, if `styles.xml` defines it
+    pub fn style(&self, style_id: &str) -> Option<&Style> {
+        self.styles.get(style_id)
+    }
+
+    /// A style and the styles it's based on, nearest first
+    ///
+    /// The chain ends at a style that isn't defined, or one already in it.
+    pub fn chain<'a>(&'a self, style_id: Option<&'a str>) -> StyleChain<'a> {
+        StyleChain {
+            sheet: self,
+            next: style_id,
+            seen: Vec::new(),
+        }
+    }
+}
+
+/// The styles a style inherits from, by [`StyleSheet::chain`]
+pub(crate) struct StyleChain<'a> {
+    sheet: &'a StyleSheet,
+    next: Option<&'a str>,
+    seen: Vec<&'a str>,
+}
+
+impl<'a> Iterator for StyleChain<'a> {
+    type Item = (&'a str, &'a Style);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.next.take()?;
+        // Guard against styles based on each other
+        if self.seen.contains(&id) {
+            return None;
+        }
+        self.seen.push(id);
+        let style = self.sheet.styles.get(id)?;
+        self.next = style.based_on.as_deref();
+        Some((id, style))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_style_is_read_once_for_every_pass() {
+        let sheet = StyleSheet::parse(
+            r#"<w:styles>
+            <w:docDefaults><w:rPrDefault><w:rPr><w:sz w:val="22"/></w:rPr></w:rPrDefault></w:docDefaults>
+            <w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+            <w:style w:type="paragraph" w:styleId="Code">
+                <w:name w:val="Code"/><w:basedOn w:val="Normal"/>
+                <w:pPr><w:spacing w:after="480"/><w:tabs><w:tab w:val="left" w:pos="720"/></w:tabs><w:rPr><w:sz w:val="8"/></w:rPr></w:pPr>
+                <w:rPr><w:rFonts w:ascii="Consolas"/><w:sz w:val="28"/></w:rPr>
+            </w:style>
+            <w:style w:type="table" w:default="1" w:styleId="Grid">
+                <w:tblPr><w:tblBorders><w:top w:val="single" w:sz="4"/></w:tblBorders></w:tblPr>
+            </w:style>
+        </w:styles>"#,
+        )
+        .unwrap();
+        assert_eq!(sheet.default_font_size, Some(11.0));
+        assert_eq!(sheet.default_paragraph.as_deref(), Some("Normal"));
+        assert_eq!(sheet.default_table.as_deref(), Some("Grid"));
+        let code = sheet.style("Code").unwrap();
+        assert_eq!(code.font_size, Some(14.0));
+        assert_eq!(code.font.as_deref(), Some("Consolas"));
+        assert_eq!(code.spacing.after, Some(480));
+        assert_eq!(code.tabs.len(), 1);
+        assert!(sheet.style("Grid").unwrap().table.borders.top.is_some());
+    }
+
+    #[test]
+    fn test_chain_follows_based_on_and_stops_at_a_cycle() {
+        let sheet = StyleSheet::parse(
+            r#"<w:styles>
+            <w:style w:type="paragraph" w:styleId="A"><w:basedOn w:val="B"/></w:style>
+            <w:style w:type="paragraph" w:styleId="B"><w:basedOn w:val="A"/></w:style>
+            <w:style w:type="paragraph" w:styleId="C"><w:basedOn w:val="Missing"/></w:style>
+        </w:styles>"#,
+        )
+        .unwrap();
+        let ids = |id| sheet.chain(Some(id)).map(|(id, _)| id).collect::<Vec<_>>();
+        assert_eq!(ids("A"), ["A", "B"]);
+        assert_eq!(ids("C"), ["C"]);
+        assert!(ids("Missing").is_empty());
+        assert_eq!(sheet.chain(None).count(), 0);
+    }
+}
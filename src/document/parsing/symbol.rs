@@ -0,0 +1,164 @@
+//! Symbol fonts
+//!
+//! Symbol and Wingdings draw pictures in place of letters, so a Wingdings
+//! smiley is stored as "J" and a Symbol alpha as "a". Outside Word the font is
+//! gone and only the letter is left. A run set in one of these fonts has its
+//! text mapped to the Unicode characters the font draws, using the classic
+//! tables for each. Word writes such text either as the letters themselves or
+//! moved into the private use area (U+F020 to U+F0FF), as it does for every
+//! `w:sym`; both are mapped.
+//!
+//! Only a run's own fonts count, not those of its style. Characters a font
+//! leaves undefined, and text in other symbol fonts such as Wingdings 2 or
+//! Webdings, are kept as written.
+
+use std::borrow::Cow;
+
+/// A font whose characters are mapped to Unicode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SymbolFont {
+    Symbol,
+    Wingdings,
+}
+
+impl SymbolFont {
+    /// The symbol font with this name, e.g. `Symbol` or `Wingdings`
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("symbol") {
+            Some(Self::Symbol)
+        } else if name.eq_ignore_ascii_case("wingdings") {
+            Some(Self::Wingdings)
+        } else {
+            None
+        }
+    }
+
+    /// The symbol font a run is set in, from its ASCII or high ANSI font
+    pub(crate) fn of_run(run: &docx_rs::Run) -> Option<Self> {
+        // docx-rs keeps the fonts private, but serializes them
+        let fonts = serde_json::to_value(run.run_property.fonts.as_ref()?).ok()?;
+        ["ascii", "hiAnsi"]
+            .into_iter()
+            .filter_map(|slot| fonts.get(slot)?.as_str())
+            .find_map(Self::from_name)
+    }
+
+    /// The Unicode character drawn for a character code, from 0x20 to 0xFF or
+    /// the same moved to U+F020 to U+F0FF
+    pub(crate) fn char(self, code: u32) -> Option<char> {
+        let code = match code {
+            0xF020..=0xF0FF => code - 0xF000,
+            0x20..=0xFF => code,
+            _ => return None,
+        };
+        let table = match self {
+            Self::Symbol => &SYMBOL,
+            Self::Wingdings => &WINGDINGS,
+        };
+        Some(table[(code - 0x20) as usize]).filter(|&c| c != UNDEFINED)
+    }
+
+    /// Text in this font, with every character the font defines mapped
+    pub(crate) fn decode(self, text: &str) -> String {
+        text.chars()
+            .map(|c| self.char(c as u32).unwrap_or(c))
+            .collect()
+    }
+}
+
+/// The text a run shows for one of its children, if any: its text, mapped if
+/// the run is set in a symbol font, or the character a `w:sym` draws
+pub(crate) fn run_child_text(
+    child: &docx_rs::RunChild,
+    font: Option<SymbolFont>,
+) -> Option<Cow<'_, str>> {
+    match child {
+        docx_rs::RunChild::Text(text) => Some(match font {
+            Some(font) => Cow::Owned(font.decode(&text.text)),
+            None => Cow::Borrowed(&text.text),
+        }),
+        docx_rs::RunChild::Sym(sym) => sym_char(sym).map(|c| Cow::Owned(c.to_string())),
+        _ => None,
+    }
+}
+
+/// The character a `w:sym` draws; one in a font without a table keeps its code
+/// if that's outside the private use area
+fn sym_char(sym: &docx_rs::Sym) -> Option<char> {
+    let code = u32::from_str_radix(sym.char.trim(), 16).ok()?;
+    match SymbolFont::from_name(&sym.font) {
+        Some(font) => font.char(code),
+        None => char::from_u32(code).filter(|c| !('\u{E000}'..='\u{F8FF}').contains(c)),
+    }
+}
+
+/// Codes a font leaves empty
+const UNDEFINED: char = '\0';
+const __: char = UNDEFINED;
+
+/// Symbol, from 0x20: Greek letters, mathematical operators and the pieces of
+/// large brackets
+#[rustfmt::skip]
+const SYMBOL: [char; 224] = [
+    ' ', '!', '∀', '#', '∃', '%', '&', '∋', '(', ')', '∗', '+', ',', '−', '.', '/',
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ':', ';', '<', '=', '>', '?',
+    '≅', 'Α', 'Β', 'Χ', 'Δ', 'Ε', 'Φ', 'Γ', 'Η', 'Ι', 'ϑ', 'Κ', 'Λ', 'Μ', 'Ν', 'Ο',
+    'Π', 'Θ', 'Ρ', 'Σ', 'Τ', 'Υ', 'ς', 'Ω', 'Ξ', 'Ψ', 'Ζ', '[', '∴', ']', '⊥', '_',
+    '‾', 'α', 'β', 'χ', 'δ', 'ε', 'φ', 'γ', 'η', 'ι', 'ϕ', 'κ', 'λ', 'μ', 'ν', 'ο',
+    'π', 'θ', 'ρ', 'σ', 'τ', 'υ', 'ϖ', 'ω', 'ξ', 'ψ', 'ζ', '{', '|', '}', '∼', __,
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __,
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __,
+    '€', 'ϒ', '′', '≤', '⁄', '∞', 'ƒ', '♣', '♦', '♥', '♠', '↔', '←', '↑', '→', '↓',
+    '°', '±', '″', '≥', '×', '∝', '∂', '•', '÷', '≠', '≡', '≈', '…', '⏐', '⎯', '↵',
+    'ℵ', 'ℑ', 'ℜ', '℘', '⊗', '⊕', '∅', '∩', '∪', '⊃', '⊇', '⊄', '⊂', '⊆', '∈', '∉',
+    '∠', '∇', '®', '©', '™', '∏', '√', '⋅', '¬', '∧', '∨', '⇔', '⇐', '⇑', '⇒', '⇓',
+    '◊', '〈', '®', '©', '™', '∑', '⎛', '⎜', '⎝', '⎡', '⎢', '⎣', '⎧', '⎨', '⎩', '⎪',
+    __, '〉', '∫', '⌠', '⎮', '⌡', '⎞', '⎟', '⎠', '⎤', '⎥', '⎦', '⎫', '⎬', '⎭', __,
+];
+
+/// Wingdings, from 0x20: office pictures, hands and faces, bullets, numbers
+/// in circles, clocks, arrows and check boxes
+#[rustfmt::skip]
+const WINGDINGS: [char; 224] = [
+    ' ', '🖉', '✂', '✁', '👓', '🕭', '🕮', '🕯', '☎', '✆', '✉', '🖃', '📪', '📫', '📬', '📭',
+    '📁', '📂', '📄', '🗏', '🗐', '🗄', '⌛', '🖮', '🖰', '🖲', '🖳', '🖴', '🖫', '🖬', '✇', '✍',
+    '🖎', '✌', '👌', '👍', '👎', '☜', '☞', '☝', '☟', '🖐', '☺', '😐', '☹', '💣', '☠', '🏳',
+    '🏱', '✈', '☼', '💧', '❄', '🕆', '✞', '🕈', '✠', '✡', '☪', '☯', 'ॐ', '☸', '♈', '♉',
+    '♊', '♋', '♌', '♍', '♎', '♏', '♐', '♑', '♒', '♓', '🙰', '🙵', '●', '🔾', '■', '□',
+    '🞐', '❑', '❒', '⬧', '⧫', '◆', '❖', '⬥', '⌧', '⮹', '⌘', '🏵', '🏶', '🙶', '🙷', __,
+    '⓪', '①', '②', '③', '④', '⑤', '⑥', '⑦', '⑧', '⑨', '⑩', '⓿', '❶', '❷', '❸', '❹',
+    '❺', '❻', '❼', '❽', '❾', '❿', '🙢', '🙠', '🙡', '🙣', '🙞', '🙜', '🙝', '🙟', '·', '•',
+    '▪', '⚪', '🞆', '🞈', '◉', '◎', '🔿', '▪', '☐', '🟂', '✦', '★', '✶', '✴', '✹', '✵',
+    '⯐', '⌖', '⟡', '⌑', '⯑', '✪', '✰', '🕐', '🕑', '🕒', '🕓', '🕔', '🕕', '🕖', '🕗', '🕘',
+    '🕙', '🕚', '🕛', '⮰', '⮱', '⮲', '⮳', '⮴', '⮵', '⮶', '⮷', '🙪', '🙫', '🙕', '🙔', '🙗',
+    '🙖', '🙐', '🙑', '🙒', '🙓', '⌫', '⌦', '⮘', '⮚', '⮙', '⮛', '⮈', '⮊', '⮉', '⮋', '🡨',
+    '🡪', '🡩', '🡫', '🡬', '🡭', '🡯', '🡮', '🡸', '🡺', '🡹', '🡻', '🡼', '🡽', '🡿', '🡾', '⇦',
+    '⇨', '⇧', '⇩', '⬄', '⇳', '⬀', '⬁', '⬃', '⬂', '▭', '▫', '✗', '✓', '☒', '☑', '⊞',
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_fonts_map_letters_and_private_use_codes() {
+        let wingdings = SymbolFont::from_name(" WINGDINGS ").unwrap();
+        assert_eq!(wingdings.decode("J K L"), "☺ 😐 ☹");
+        assert_eq!(wingdings.decode("\u{F0D8}\u{F0E8}\u{F0FC}§"), "⮚🡺✓▪");
+        assert_eq!(SymbolFont::Symbol.decode("a+b=p"), "α+β=π");
+        // Undefined codes and characters past the table are kept
+        assert_eq!(SymbolFont::Symbol.decode("\u{7F}→"), "\u{7F}→");
+        assert_eq!(SymbolFont::from_name("Wingdings 2"), None);
+    }
+
+    #[test]
+    fn test_sym_elements_map_through_their_font() {
+        let sym = |font: &str, code: &str| sym_char(&docx_rs::Sym::new(font, code));
+        assert_eq!(sym("Wingdings", "F04A"), Some('☺'));
+        assert_eq!(sym("Symbol", "F0B7"), Some('•'));
+        assert_eq!(sym("Symbol", "F0FF"), None);
+        assert_eq!(sym("Segoe UI Symbol", "2714"), Some('✔'));
+        assert_eq!(sym("Webdings", "F021"), None);
+    }
+}
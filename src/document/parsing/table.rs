@@ -5,10 +5,21 @@
 //! inference for proper alignment.
 
 use super::super::models::*;
+use super::super::table_query::parse_date;
 use super::formatting::extract_run_formatting;
-
-/// Extract table data from a docx-rs Table
-pub(crate) fn extract_table_data(table: &docx_rs::Table) -> Option<DocumentElement> {
+use super::symbol::{run_child_text, SymbolFont};
+use std::borrow::Cow;
+
+/// Extract table data from a docx-rs Table, reading numbers and dates in the
+/// document's style and drawing it in `style`
+///
+/// Tables nested in a cell are flattened into the cell's text and counted in `fidelity`.
+pub(crate) fn extract_table_data(
+    table: &docx_rs::Table,
+    locale: CellLocale,
+    style: TableStyle,
+    fidelity: &mut Fidelity,
+) -> Option<DocumentElement> {
     let mut header_cells = Vec::new();
     let mut data_rows = Vec::new();
 
@@ -23,40 +34,47 @@ pub(crate) fn extract_table_data(table: &docx_rs::Table) -> Option<DocumentEleme
 
         for row_child in &row.cells {
             let docx_rs::TableRowChild::TableCell(cell) = row_child;
-            let mut cell_text = String::new();
-            let mut cell_formatting = TextFormatting::default();
+            let mut runs = Vec::new();
 
-            // Extract text and formatting from all content in the cell
+            // Extract the formatted text of all content in the cell, with a
+            // space between paragraphs
             for content in &cell.children {
                 match content {
                     docx_rs::TableCellContent::Paragraph(para) => {
+                        separate_paragraph(&mut runs);
                         for para_child in &para.children {
                             if let docx_rs::ParagraphChild::Run(run) = para_child {
-                                // Extract formatting from the first run
-                                if !cell_formatting.bold && !cell_formatting.italic {
-                                    cell_formatting = extract_run_formatting(run);
-                                }
-
-                                for run_child in &run.children {
-                                    if let docx_rs::RunChild::Text(text_elem) = run_child {
-                                        if !cell_text.is_empty() && !cell_text.ends_with(' ') {
-                                            cell_text.push(' ');
-                                        }
-                                        cell_text.push_str(&text_elem.text);
-                                    }
+                                let formatting = extract_run_formatting(run);
+                                let symbol_font = SymbolFont::of_run(run);
+                                let text: String = run
+                                    .children
+                                    .iter()
+                                    .filter_map(|child| run_child_text(child, symbol_font))
+                                    .collect();
+                                if !text.is_empty() {
+                                    FormattedRun::push_consolidated(
+                                        &mut runs,
+                                        FormattedRun { text, formatting },
+                                    );
                                 }
                             }
                         }
                     }
-                    _ => {
-                        // Handle nested tables or other content if needed
+                    docx_rs::TableCellContent::Table(nested) => {
+                        separate_paragraph(&mut runs);
+                        FormattedRun::push_consolidated(
+                            &mut runs,
+                            FormattedRun {
+                                text: nested_table_text(nested, fidelity),
+                                formatting: TextFormatting::default(),
+                            },
+                        );
                     }
+                    _ => {}
                 }
             }
 
-            let table_cell =
-                TableCell::new(cell_text.trim().to_string()).with_formatting(cell_formatting);
-            row_cells.push(table_cell);
+            row_cells.push(TableCell::with_runs(runs, locale));
         }
 
         if !row_cells.is_empty() {
@@ -82,13 +100,55 @@ pub(crate) fn extract_table_data(table: &docx_rs::Table) -> Option<DocumentEleme
 
     // Return table only if it has content
     if !header_cells.is_empty() || !data_rows.is_empty() {
-        let table_data = TableData::new(header_cells, data_rows);
+        let mut table_data = TableData::new(header_cells, data_rows);
+        table_data.metadata.style = style;
         Some(DocumentElement::Table { table: table_data })
     } else {
         None
     }
 }
 
+/// End the cell's last paragraph with a space before another starts
+fn separate_paragraph(runs: &mut [FormattedRun]) {
+    if let Some(last) = runs.last_mut() {
+        if !last.text.ends_with(' ') {
+            last.text.push(' ');
+        }
+    }
+}
+
+/// Text of a nested table, row after row, as the recovery scanner flattens them
+fn nested_table_text(table: &docx_rs::Table, fidelity: &mut Fidelity) -> String {
+    fidelity.flattened_tables += 1;
+    let mut words = Vec::new();
+    for docx_rs::TableChild::TableRow(row) in &table.rows {
+        for docx_rs::TableRowChild::TableCell(cell) in &row.cells {
+            for content in &cell.children {
+                match content {
+                    docx_rs::TableCellContent::Paragraph(para) => {
+                        for child in &para.children {
+                            if let docx_rs::ParagraphChild::Run(run) = child {
+                                let symbol_font = SymbolFont::of_run(run);
+                                for run_child in &run.children {
+                                    if let Some(text) = run_child_text(run_child, symbol_font) {
+                                        words.push(text.trim().to_string());
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    docx_rs::TableCellContent::Table(nested) => {
+                        words.push(nested_table_text(nested, fidelity));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    words.retain(|word| !word.is_empty());
+    words.join(" ")
+}
+
 /// Detect if a row appears to be a header based on heuristics
 fn appears_to_be_header(row: &[String]) -> bool {
     // Heuristics to detect if a row is likely a header
@@ -157,6 +217,7 @@ impl TableData {
             column_widths,
             column_alignments,
             title: None,
+            style: TableStyle::default(),
         };
 
         Self {
@@ -185,7 +246,24 @@ impl TableData {
 
 impl TableCell {
     pub fn new(content: String) -> Self {
-        let data_type = detect_cell_data_type(&content);
+        Self::with_number_style(content, NumberStyle::default())
+    }
+
+    /// A cell whose numbers are written in the given style (`12,50 €` in German)
+    pub fn with_number_style(content: String, numbers: NumberStyle) -> Self {
+        Self::with_locale(
+            content,
+            CellLocale {
+                numbers,
+                ..CellLocale::default()
+            },
+        )
+    }
+
+    /// A cell whose numbers and dates are written as in the given locale
+    /// (`31.12.2024` in German, `31/12/2024` in British English)
+    pub fn with_locale(content: String, locale: CellLocale) -> Self {
+        let data_type = detect_cell_data_type(&content, locale);
         let alignment = default_alignment_for_type(data_type);
 
         Self {
@@ -193,7 +271,56 @@ impl TableCell {
             alignment,
             formatting: TextFormatting::default(),
             data_type,
+            runs: Vec::new(),
+        }
+    }
+
+    /// A cell of formatted runs, trimmed like a plain cell; the runs are kept
+    /// only when they aren't all formatted alike
+    pub fn with_runs(mut runs: Vec<FormattedRun>, locale: CellLocale) -> Self {
+        runs.retain(|run| !run.text.is_empty());
+        while runs.last().is_some_and(|run| run.text.trim().is_empty()) {
+            runs.pop();
+        }
+        let blank = runs
+            .iter()
+            .take_while(|run| run.text.trim().is_empty())
+            .count();
+        runs.drain(..blank);
+        if let Some(first) = runs.first_mut() {
+            first.text = first.text.trim_start().to_string();
+        }
+        if let Some(last) = runs.last_mut() {
+            last.text = last.text.trim_end().to_string();
+        }
+
+        let content: String = runs.iter().map(|run| run.text.as_str()).collect();
+        let mut cell = Self::with_locale(content, locale);
+        if let Some(first) = runs.first() {
+            cell.formatting = first.formatting.clone();
+        }
+        if runs.len() > 1 {
+            cell.runs = runs;
+        }
+        cell
+    }
+
+    /// The cell's text as formatted runs, for the viewer and the exports that
+    /// keep formatting
+    pub fn formatted_runs(&self) -> Cow<'_, [FormattedRun]> {
+        if !self.runs.is_empty() || self.content.is_empty() {
+            return Cow::Borrowed(&self.runs);
         }
+        Cow::Owned(vec![FormattedRun {
+            text: self.content.clone(),
+            formatting: self.formatting.clone(),
+        }])
+    }
+
+    /// Replace the cell's runs, keeping its plain `content` in step
+    pub fn set_runs(&mut self, runs: Vec<FormattedRun>) {
+        self.content = runs.iter().map(|run| run.text.as_str()).collect();
+        self.runs = runs;
     }
 
     pub fn _with_alignment(mut self, alignment: TextAlignment) -> Self {
@@ -206,9 +333,9 @@ impl TableCell {
         self
     }
 
+    /// Width of the content in terminal columns (CJK characters take two)
     pub fn display_width(&self) -> usize {
-        // Calculate display width considering unicode characters
-        unicode_segmentation::UnicodeSegmentation::graphemes(self.content.as_str(), true).count()
+        unicode_width::UnicodeWidthStr::width(self.content.as_str())
     }
 }
 
@@ -269,17 +396,21 @@ fn determine_column_alignments(headers: &[TableCell], rows: &TableRows) -> Vec<T
 }
 
 /// Detect the data type of a cell's content
-fn detect_cell_data_type(content: &str) -> CellDataType {
+fn detect_cell_data_type(content: &str, locale: CellLocale) -> CellDataType {
+    const CURRENCY_SYMBOLS: [char; 3] = ['$', '\u{20AC}', '\u{00A3}'];
+
+    let numbers = locale.numbers;
     let trimmed = content.trim();
 
     if trimmed.is_empty() {
         return CellDataType::Empty;
     }
 
-    // Check for currency
-    if trimmed.starts_with('$')
-        || trimmed.starts_with('\u{20AC}')
-        || trimmed.starts_with('\u{00A3}')
+    // Check for currency; decimal comma languages write the symbol after the amount
+    if trimmed.starts_with(CURRENCY_SYMBOLS)
+        || (numbers == NumberStyle::DecimalComma
+            && trimmed.ends_with(CURRENCY_SYMBOLS)
+            && parse_number(trimmed.trim_end_matches(CURRENCY_SYMBOLS).trim(), numbers).is_some())
     {
         return CellDataType::Currency;
     }
@@ -295,23 +426,47 @@ fn detect_cell_data_type(content: &str) -> CellDataType {
         return CellDataType::Boolean;
     }
 
-    // Check for number (including with commas)
-    let number_candidate = trimmed.replace(',', "");
-    if number_candidate.parse::<f64>().is_ok() {
+    // Check for number (including with digit grouping)
+    if parse_number(trimmed, numbers).is_some() {
         return CellDataType::Number;
     }
 
-    // Check for date patterns (basic)
-    if trimmed.contains('/') || trimmed.contains('-') {
-        let parts: Vec<&str> = trimmed.split(['/', '-']).collect();
-        if parts.len() == 3 && parts.iter().all(|p| p.parse::<u32>().is_ok()) {
-            return CellDataType::Date;
-        }
+    // Check for dates; decimal comma languages and those writing the day or
+    // year first also use dots
+    let dots = numbers == NumberStyle::DecimalComma || locale.dates != DateOrder::MonthDayYear;
+    let separators: &[char] = if dots { &['/', '-', '.'] } else { &['/', '-'] };
+    if trimmed.contains(separators)
+        && trimmed.split(separators).count() == 3
+        && parse_date(trimmed, locale.dates).is_some()
+    {
+        return CellDataType::Date;
     }
 
     CellDataType::Text
 }
 
+/// Parse a number written with digit grouping, in the given style
+pub(crate) fn parse_number(text: &str, numbers: NumberStyle) -> Option<f64> {
+    let (integer, fraction, separators): (&str, &str, &[char]) = match numbers {
+        NumberStyle::DecimalPoint => {
+            let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+            (integer, fraction, &[','])
+        }
+        NumberStyle::DecimalComma => {
+            let (integer, fraction) = text.split_once(',').unwrap_or((text, ""));
+            (integer, fraction, &['.', ' ', '\u{00A0}', '\u{202F}', '\''])
+        }
+    };
+    // Groups of three digits, so `31.12.2024` stays a date and `1.234,56`
+    // isn't taken for a number with a decimal point
+    let groups: Vec<&str> = integer.split(separators).collect();
+    let grouped = groups[1..].iter().all(|group| group.len() == 3);
+    if !grouped || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    format!("{}.{fraction}", groups.concat()).parse().ok()
+}
+
 /// Get default alignment for a data type
 fn default_alignment_for_type(data_type: CellDataType) -> TextAlignment {
     match data_type {
@@ -0,0 +1,364 @@
+//! Table borders and styles
+//!
+//! A table's lines are its own `w:tblBorders`, with any side it leaves out
+//! taken from its table style (`w:tblStyle`) or the styles that one is based
+//! on. A style can also shade the header row (`w:tblStylePr w:type="firstRow"`)
+//! and every other body row (`band1Horz`), and rule off the header; the
+//! table's `w:tblLook` says whether those apply. docx-rs doesn't read
+//! `w:tblLook`, so each top-level table's properties are scanned from
+//! `document.xml` here.
+//!
+//! Tables that set no borders and name no style in `styles.xml` keep doxx's
+//! own layout.
+
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::super::models::{BorderLine, TableBorders, TableStyle};
+use super::formatting::shading_fill;
+use super::styles::{attr, StyleSheet};
+use super::theme::{ThemeColorRef, ThemeColors};
+
+/// One side's border: a line, or none (`w:val="nil"` or `"none"`)
+type Edge = Option<BorderLine>;
+
+pub(crate) fn edge(e: &BytesStart) -> Edge {
+    let kind = attr(e, b"w:val")?;
+    // Eighths of a point
+    let size: u32 = attr(e, b"w:sz")
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(0);
+    match kind.as_str() {
+        "nil" | "none" => None,
+        "double" | "triple" => Some(BorderLine::Double),
+        // thinThickSmallGap and the like are drawn as two lines
+        kind if kind.contains("Thin") => Some(BorderLine::Double),
+        "thick" => Some(BorderLine::Heavy),
+        _ if size >= 12 => Some(BorderLine::Heavy),
+        _ => Some(BorderLine::Light),
+    }
+}
+
+/// The sides a `w:tblBorders` sets; `None` leaves a side to the style
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Borders {
+    pub top: Option<Edge>,
+    pub bottom: Option<Edge>,
+    pub left: Option<Edge>,
+    pub right: Option<Edge>,
+    pub inside_h: Option<Edge>,
+    pub inside_v: Option<Edge>,
+}
+
+impl Borders {
+    pub fn set(&mut self, side: &[u8], edge: Edge) {
+        let side = match side {
+            b"w:top" => &mut self.top,
+            b"w:bottom" => &mut self.bottom,
+            b"w:left" | b"w:start" => &mut self.left,
+            b"w:right" | b"w:end" => &mut self.right,
+            b"w:insideH" => &mut self.inside_h,
+            b"w:insideV" => &mut self.inside_v,
+            _ => return,
+        };
+        *side = Some(edge);
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These sides, with those left out taken from `inherited`
+    fn or(self, inherited: Self) -> Self {
+        Self {
+            top: self.top.or(inherited.top),
+            bottom: self.bottom.or(inherited.bottom),
+            left: self.left.or(inherited.left),
+            right: self.right.or(inherited.right),
+            inside_h: self.inside_h.or(inherited.inside_h),
+            inside_v: self.inside_v.or(inherited.inside_v),
+        }
+    }
+}
+
+/// A `w:shd` fill, literal or from the theme
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Fill {
+    hex: Option<String>,
+    theme: Option<ThemeColorRef>,
+}
+
+impl Fill {
+    pub fn parse(e: &BytesStart) -> Option<Self> {
+        if attr(e, b"w:val").as_deref() == Some("nil") {
+            return None;
+        }
+        let fill = Self {
+            hex: attr(e, b"w:fill").as_deref().and_then(shading_fill),
+            theme: attr(e, b"w:themeFill").map(|name| {
+                ThemeColorRef::new(
+                    name,
+                    attr(e, b"w:themeFillTint").as_deref(),
+                    attr(e, b"w:themeFillShade").as_deref(),
+                )
+            }),
+        };
+        (fill.hex.is_some() || fill.theme.is_some()).then_some(fill)
+    }
+
+    /// The fill as a `#RRGGBB` colour; a theme colour wins where the theme has it
+    fn resolve(&self, theme: &ThemeColors) -> Option<String> {
+        self.theme
+            .as_ref()
+            .and_then(|color| theme.resolve(color))
+            .map(|hex| format!("#{hex}"))
+            .or_else(|| self.hex.clone())
+    }
+}
+
+/// What a table style sets, before inheritance
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TableDefinition {
+    pub borders: Borders,
+    /// The header row's bottom border
+    pub header_rule: Option<Edge>,
+    pub header_fill: Option<Fill>,
+    pub band_fill: Option<Fill>,
+}
+
+/// Which of its style's row formats a table uses (`w:tblLook`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TableLook {
+    first_row: bool,
+    banded_rows: bool,
+}
+
+impl Default for TableLook {
+    fn default() -> Self {
+        Self {
+            first_row: true,
+            banded_rows: true,
+        }
+    }
+}
+
+impl TableLook {
+    /// Read from the separate attributes, or else the older `w:val` bit mask
+    fn parse(e: &BytesStart) -> Self {
+        let mask = attr(e, b"w:val")
+            .and_then(|val| u16::from_str_radix(&val, 16).ok())
+            .unwrap_or(0x0020);
+        let flag = |name: &[u8], bit: u16| match attr(e, name).as_deref() {
+            Some(value) => value == "1" || value == "true",
+            None => mask & bit != 0,
+        };
+        Self {
+            first_row: flag(b"w:firstRow", 0x0020),
+            banded_rows: !flag(b"w:noHBand", 0x0200),
+        }
+    }
+}
+
+/// What a top-level table's `w:tblPr` says about how it's drawn
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TableProperties {
+    style: Option<String>,
+    borders: Borders,
+    look: TableLook,
+}
+
+/// Scan `document.xml` for the properties of each top-level body table, in
+/// document order
+///
+/// The indices line up with the `Table` children of the docx-rs document.
+pub(crate) fn scan_table_properties(document_xml: &str) -> Result<Vec<TableProperties>> {
+    let mut reader = Reader::from_str(document_xml);
+    let mut tables = Vec::new();
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    // The table being read and how deep its `w:tbl` and `w:tblPr` sit
+    let mut current: Option<(TableProperties, usize)> = None;
+    let mut properties_depth: Option<usize> = None;
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(e) | Event::Empty(e) => {
+                let empty = matches!(event, Event::Empty(_));
+                let name = e.name().as_ref().to_vec();
+                let parent = stack.last().map(Vec::as_slice);
+                match (&mut current, name.as_slice()) {
+                    (None, b"w:tbl") if parent == Some(b"w:body") && !empty => {
+                        current = Some((TableProperties::default(), stack.len()));
+                    }
+                    (Some((_, depth)), b"w:tblPr") if stack.len() == *depth + 1 && !empty => {
+                        properties_depth = Some(stack.len());
+                    }
+                    (Some((table, _)), child)
+                        if properties_depth.map(|depth| depth + 1) == Some(stack.len()) =>
+                    {
+                        match child {
+                            b"w:tblStyle" => table.style = attr(e, b"w:val"),
+                            b"w:tblLook" => table.look = TableLook::parse(e),
+                            _ => {}
+                        }
+                    }
+                    (Some((table, _)), side)
+                        if properties_depth.map(|depth| depth + 2) == Some(stack.len())
+                            && parent == Some(b"w:tblBorders") =>
+                    {
+                        table.borders.set(side, edge(e));
+                    }
+                    _ => {}
+                }
+                if !empty {
+                    stack.push(name);
+                }
+            }
+            Event::End(_) => {
+                stack.pop();
+                if properties_depth == Some(stack.len()) {
+                    properties_depth = None;
+                }
+                if current
+                    .as_ref()
+                    .is_some_and(|(_, depth)| *depth == stack.len())
+                {
+                    tables.extend(current.take().map(|(table, _)| table));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(tables)
+}
+
+impl StyleSheet {
+    /// How a table with these properties is drawn
+    pub fn table_style(&self, table: &TableProperties, theme: &ThemeColors) -> TableStyle {
+        // The table's style and the styles it's based on, nearest first
+        let chain: Vec<&TableDefinition> = self
+            .chain(table.style.as_deref())
+            .map(|(_, style)| &style.table)
+            .collect();
+        if chain.is_empty() && table.borders.is_empty() {
+            return TableStyle::default();
+        }
+
+        let borders = chain
+            .iter()
+            .fold(table.borders, |borders, style| borders.or(style.borders));
+        let header_rule = chain
+            .iter()
+            .find_map(|style| style.header_rule)
+            .flatten()
+            .filter(|_| table.look.first_row);
+        let fill = |applies: bool, fill: fn(&TableDefinition) -> Option<&Fill>| {
+            chain
+                .iter()
+                .find_map(|style| fill(style))
+                .filter(|_| applies)
+                .and_then(|fill| fill.resolve(theme))
+        };
+
+        let sides = [
+            borders.top,
+            borders.left,
+            borders.bottom,
+            borders.right,
+            borders.inside_h,
+            borders.inside_v,
+        ]
+        .map(Option::flatten);
+        TableStyle {
+            borders: TableBorders {
+                top: sides[0].is_some(),
+                left: sides[1].is_some(),
+                bottom: sides[2].is_some(),
+                right: sides[3].is_some(),
+                inside_horizontal: sides[4].is_some(),
+                inside_vertical: sides[5].is_some(),
+                header: header_rule.is_some(),
+            },
+            line: sides
+                .into_iter()
+                .chain([header_rule])
+                .flatten()
+                .next()
+                .unwrap_or_default(),
+            header_fill: fill(table.look.first_row, |style| style.header_fill.as_ref()),
+            band_fill: fill(table.look.banded_rows, |style| style.band_fill.as_ref()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STYLES: &str = r#"<w:styles>
+        <w:style w:type="table" w:default="1" w:styleId="TableNormal"><w:name w:val="Normal Table"/></w:style>
+        <w:style w:type="table" w:styleId="PlainTable3"><w:name w:val="Plain Table 3"/><w:basedOn w:val="TableNormal"/>
+            <w:tblStylePr w:type="firstRow"><w:tcPr><w:tcBorders><w:bottom w:val="single" w:sz="4" w:color="7F7F7F"/></w:tcBorders></w:tcPr></w:tblStylePr>
+        </w:style>
+        <w:style w:type="table" w:styleId="GridTable4"><w:name w:val="Grid Table 4 Accent 1"/><w:basedOn w:val="TableNormal"/>
+            <w:tblPr><w:tblBorders><w:top w:val="single" w:sz="4"/><w:left w:val="single" w:sz="4"/><w:bottom w:val="single" w:sz="4"/><w:right w:val="single" w:sz="4"/><w:insideH w:val="single" w:sz="4"/><w:insideV w:val="single" w:sz="4"/></w:tblBorders></w:tblPr>
+            <w:tblStylePr w:type="firstRow"><w:tcPr><w:shd w:val="clear" w:color="auto" w:fill="4472C4" w:themeFill="accent1"/></w:tcPr></w:tblStylePr>
+            <w:tblStylePr w:type="band1Horz"><w:tcPr><w:shd w:val="clear" w:color="auto" w:fill="d9e2f3"/></w:tcPr></w:tblStylePr>
+        </w:style>
+        <w:style w:type="table" w:customStyle="1" w:styleId="Ledger"><w:basedOn w:val="GridTable4"/>
+            <w:tblPr><w:tblBorders><w:insideV w:val="nil"/><w:top w:val="double" w:sz="4"/></w:tblBorders></w:tblPr>
+        </w:style>
+    </w:styles>"#;
+
+    const DOCUMENT: &str = r#"<w:document><w:body>
+        <w:tbl><w:tblPr><w:tblStyle w:val="PlainTable3"/><w:tblLook w:val="04A0" w:firstRow="1" w:noHBand="0"/></w:tblPr><w:tr><w:tc><w:p/></w:tc></w:tr></w:tbl>
+        <w:tbl><w:tblPr><w:tblStyle w:val="Ledger"/><w:tblBorders><w:left w:val="nil"/><w:right w:val="nil"/></w:tblBorders><w:tblLook w:val="0620"/></w:tblPr>
+            <w:tr><w:tc><w:tbl><w:tblPr><w:tblStyle w:val="Nested"/></w:tblPr></w:tbl><w:p/></w:tc></w:tr></w:tbl>
+        <w:p/>
+        <w:tbl><w:tblPr><w:tblStyle w:val="Missing"/></w:tblPr></w:tbl>
+    </w:body></w:document>"#;
+
+    #[test]
+    fn test_tables_take_lines_and_shading_from_their_style() {
+        let styles = StyleSheet::parse(STYLES).unwrap();
+        let tables = scan_table_properties(DOCUMENT).unwrap();
+        assert_eq!(tables.len(), 3);
+        let theme = ThemeColors::default();
+
+        // Only a rule under the header
+        let plain = styles.table_style(&tables[0], &theme);
+        assert_eq!(
+            plain.borders,
+            TableBorders {
+                header: true,
+                ..TableBorders::NONE
+            }
+        );
+        assert_eq!(plain.header_fill, None);
+
+        // The table's borders and its style's win over those they're based on
+        let ledger = styles.table_style(&tables[1], &theme);
+        assert_eq!(
+            ledger.borders,
+            TableBorders {
+                top: true,
+                bottom: true,
+                inside_horizontal: true,
+                ..TableBorders::NONE
+            }
+        );
+        assert_eq!(ledger.line, BorderLine::Double);
+        // No theme, so the literal fill; banding is off in its look
+        assert_eq!(ledger.header_fill.as_deref(), Some("#4472C4"));
+        assert_eq!(ledger.band_fill, None);
+
+        // A style that isn't defined keeps doxx's layout
+        assert_eq!(
+            styles.table_style(&tables[2], &theme),
+            TableStyle::default()
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Tab stops (`w:tabs`)
+//!
+//! A paragraph's tab stops are those of its style, and the styles that one is
+//! based on, with the paragraph's own on top. Each `w:tab` adds a stop at its
+//! position or replaces the one already there; `w:val="clear"` removes an
+//! inherited stop instead. Bar tabs draw a line rather than stopping text, so
+//! they're left out.
+
+use quick_xml::events::BytesStart;
+use std::collections::BTreeMap;
+
+use super::super::models::{TabAlignment, TabLeader, TabStop};
+use super::styles::{attr, StyleSheet};
+
+/// One `w:tab`: a stop, or `None` to clear the stop at `position`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TabSetting {
+    position: u32,
+    stop: Option<TabStop>,
+}
+
+impl TabSetting {
+    fn new(position: u32, kind: &str, leader: Option<&str>) -> Option<Self> {
+        let alignment = match kind {
+            "clear" => {
+                return Some(Self {
+                    position,
+                    stop: None,
+                })
+            }
+            "left" | "start" | "num" => TabAlignment::Left,
+            "center" => TabAlignment::Center,
+            "right" | "end" => TabAlignment::Right,
+            "decimal" => TabAlignment::Decimal,
+            _ => return None,
+        };
+        let leader = match leader {
+            Some("dot") => TabLeader::Dot,
+            Some("middleDot") => TabLeader::MiddleDot,
+            Some("hyphen") => TabLeader::Hyphen,
+            Some("underscore" | "heavy") => TabLeader::Underscore,
+            _ => TabLeader::None,
+        };
+        Some(Self {
+            position,
+            stop: Some(TabStop {
+                position,
+                alignment,
+                leader,
+            }),
+        })
+    }
+
+    /// A style's `w:tab`
+    pub fn from_element(e: &BytesStart) -> Option<Self> {
+        let position = attr(e, b"w:pos")?.parse().ok()?;
+        Self::new(
+            position,
+            &attr(e, b"w:val")?,
+            attr(e, b"w:leader").as_deref(),
+        )
+    }
+
+    /// docx-rs reads the paragraph's own `w:tab`s
+    fn from_docx(tab: &docx_rs::Tab) -> Option<Self> {
+        let position = u32::try_from(tab.pos?).ok()?;
+        let kind = tab.val.as_ref()?.to_string();
+        let leader = tab.leader.as_ref().map(ToString::to_string);
+        Self::new(position, &kind, leader.as_deref())
+    }
+
+    fn apply(self, stops: &mut BTreeMap<u32, TabStop>) {
+        match self.stop {
+            Some(stop) => stops.insert(self.position, stop),
+            None => stops.remove(&self.position),
+        };
+    }
+}
+
+impl StyleSheet {
+    /// A paragraph's tab stops, left to right
+    pub fn tab_stops(&self, para: &docx_rs::Paragraph) -> Vec<TabStop> {
+        let style = para
+            .property
+            .style
+            .as_ref()
+            .map(|style| style.val.as_str())
+            .or(self.default_paragraph.as_deref());
+
+        // The furthest style first, so nearer ones override it
+        let chain: Vec<_> = self.chain(style).collect();
+        let mut stops = BTreeMap::new();
+        for (_, style) in chain.iter().rev() {
+            for setting in &style.tabs {
+                setting.apply(&mut stops);
+            }
+        }
+        for setting in para.property.tabs.iter().filter_map(TabSetting::from_docx) {
+            setting.apply(&mut stops);
+        }
+        stops.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraph_tabs_add_to_and_clear_style_tabs() {
+        let styles = StyleSheet::parse(
+            r#"<w:styles>
+            <w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+            <w:style w:type="paragraph" w:styleId="Form"><w:basedOn w:val="Normal"/><w:pPr><w:tabs><w:tab w:val="left" w:pos="1440"/><w:tab w:val="bar" w:pos="2880"/></w:tabs></w:pPr></w:style>
+            <w:style w:type="paragraph" w:styleId="TOC1"><w:basedOn w:val="Form"/><w:pPr><w:tabs><w:tab w:val="right" w:leader="dot" w:pos="9350"/></w:tabs></w:pPr></w:style>
+            </w:styles>"#,
+        )
+        .unwrap();
+        let para = docx_rs::Paragraph::new()
+            .style("TOC1")
+            .add_tab(
+                docx_rs::Tab::new()
+                    .val(docx_rs::TabValueType::Clear)
+                    .pos(1440),
+            )
+            .add_tab(
+                docx_rs::Tab::new()
+                    .val(docx_rs::TabValueType::Decimal)
+                    .leader(docx_rs::TabLeaderType::Hyphen)
+                    .pos(5040),
+            );
+        assert_eq!(
+            styles.tab_stops(&para),
+            [
+                TabStop {
+                    position: 5040,
+                    alignment: TabAlignment::Decimal,
+                    leader: TabLeader::Hyphen,
+                },
+                TabStop {
+                    position: 9350,
+                    alignment: TabAlignment::Right,
+                    leader: TabLeader::Dot,
+                },
+            ]
+        );
+        assert_eq!(
+            styles.tab_stops(&docx_rs::Paragraph::new()),
+            Vec::<TabStop>::new()
+        );
+    }
+}
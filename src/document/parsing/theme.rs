@@ -0,0 +1,249 @@
+//! Theme colour resolution
+//!
+//! Runs can name a colour from the document theme (`w:themeColor="accent1"`)
+//! instead of, or on top of, a literal `w:val`. This module reads the colour
+//! scheme from `word/theme/theme1.xml` and resolves those references, applying
+//! `w:themeTint` / `w:themeShade` to the colour's HSL luminance as Word does.
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// A run's reference to a theme colour
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ThemeColorRef {
+    /// `w:themeColor`, e.g. `accent1`, `text1` or `hyperlink`
+    pub name: String,
+    /// `w:themeTint`: 0 is white, 255 leaves the colour unchanged
+    pub tint: Option<u8>,
+    /// `w:themeShade`: 0 is black, 255 leaves the colour unchanged
+    pub shade: Option<u8>,
+}
+
+impl ThemeColorRef {
+    /// Build a reference from the raw `w:color` attribute values
+    pub fn new(name: String, tint: Option<&str>, shade: Option<&str>) -> Self {
+        let byte = |value: Option<&str>| value.and_then(|v| u8::from_str_radix(v, 16).ok());
+        Self {
+            name,
+            tint: byte(tint),
+            shade: byte(shade),
+        }
+    }
+}
+
+/// The colour scheme of a document theme, keyed by slot (`dk1`, `accent1`, ...)
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ThemeColors {
+    slots: HashMap<String, [u8; 3]>,
+}
+
+impl ThemeColors {
+    /// Resolve a theme colour reference to a hex colour (`RRGGBB`, as in `w:val`)
+    pub fn resolve(&self, color: &ThemeColorRef) -> Option<String> {
+        // WordprocessingML names map onto the DrawingML scheme slots
+        let slot = match color.name.as_str() {
+            "dark1" | "text1" => "dk1",
+            "light1" | "background1" => "lt1",
+            "dark2" | "text2" => "dk2",
+            "light2" | "background2" => "lt2",
+            "hyperlink" => "hlink",
+            "followedHyperlink" => "folHlink",
+            other => other,
+        };
+        let rgb = *self.slots.get(slot)?;
+
+        let (h, s, mut l) = rgb_to_hsl(rgb);
+        if let Some(shade) = color.shade {
+            l *= f64::from(shade) / 255.0;
+        }
+        if let Some(tint) = color.tint {
+            let tint = f64::from(tint) / 255.0;
+            l = l * tint + (1.0 - tint);
+        }
+        let [r, g, b] = hsl_to_rgb(h, s, l.clamp(0.0, 1.0));
+        Some(format!("{r:02X}{g:02X}{b:02X}"))
+    }
+}
+
+/// Read the colour scheme (`a:clrScheme`) from a theme part
+pub(crate) fn parse_theme_colors(theme_xml: &str) -> Result<ThemeColors> {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    let mut reader = Reader::from_str(theme_xml);
+    let mut buf = Vec::new();
+    let mut colors = ThemeColors::default();
+    let mut in_scheme = false;
+    let mut slot: Option<String> = None;
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) if e.name().as_ref() == b"a:clrScheme" => in_scheme = true,
+            Event::End(e) if e.name().as_ref() == b"a:clrScheme" => break,
+            Event::Start(e) if in_scheme && slot.is_none() => {
+                let name = e.name();
+                let name = name.as_ref();
+                let local = name.strip_prefix(b"a:").unwrap_or(name);
+                slot = Some(String::from_utf8_lossy(local).into_owned());
+            }
+            Event::End(e) if in_scheme && slot.is_some() => {
+                let name = e.name();
+                let name = name.as_ref();
+                let local = name.strip_prefix(b"a:").unwrap_or(name);
+                if slot.as_deref().map(str::as_bytes) == Some(local) {
+                    slot = None;
+                }
+            }
+            Event::Empty(e) if slot.is_some() => {
+                // Literal colours carry `val`; system colours their last known `lastClr`
+                let key: &[u8] = match e.name().as_ref() {
+                    b"a:srgbClr" => b"val",
+                    b"a:sysClr" => b"lastClr",
+                    _ => {
+                        buf.clear();
+                        continue;
+                    }
+                };
+                let rgb = e
+                    .attributes()
+                    .flatten()
+                    .find(|attr| attr.key.as_ref() == key)
+                    .and_then(|attr| parse_hex(&String::from_utf8_lossy(&attr.value)));
+                if let (Some(name), Some(rgb)) = (&slot, rgb) {
+                    colors.slots.insert(name.clone(), rgb);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(colors)
+}
+
+fn parse_hex(hex: &str) -> Option<[u8; 3]> {
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn rgb_to_hsl([r, g, b]: [u8; 3]) -> (f64, f64, f64) {
+    let (r, g, b) = (
+        f64::from(r) / 255.0,
+        f64::from(g) / 255.0,
+        f64::from(b) / 255.0,
+    );
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    if max == min {
+        return (0.0, 0.0, l);
+    }
+
+    let d = max - min;
+    let s = if l > 0.5 {
+        d / (2.0 - max - min)
+    } else {
+        d / (max + min)
+    };
+    let h = if max == r {
+        (g - b) / d + if g < b { 6.0 } else { 0.0 }
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    (h / 6.0, s, l)
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> [u8; 3] {
+    // Word truncates rather than rounds; the epsilon keeps exact channels exact
+    let to_byte = |v: f64| (v * 255.0 + 1e-9).floor().clamp(0.0, 255.0) as u8;
+    if s == 0.0 {
+        return [to_byte(l); 3];
+    }
+
+    let q = if l < 0.5 {
+        l * (1.0 + s)
+    } else {
+        l + s - l * s
+    };
+    let p = 2.0 * l - q;
+    let hue = |mut t: f64| {
+        if t < 0.0 {
+            t += 1.0;
+        }
+        if t > 1.0 {
+            t -= 1.0;
+        }
+        if t < 1.0 / 6.0 {
+            p + (q - p) * 6.0 * t
+        } else if t < 0.5 {
+            q
+        } else if t < 2.0 / 3.0 {
+            p + (q - p) * (2.0 / 3.0 - t) * 6.0
+        } else {
+            p
+        }
+    };
+    [
+        to_byte(hue(h + 1.0 / 3.0)),
+        to_byte(hue(h)),
+        to_byte(hue(h - 1.0 / 3.0)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THEME: &str = r#"<a:theme xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><a:themeElements><a:clrScheme name="Office"><a:dk1><a:sysClr val="windowText" lastClr="000000"/></a:dk1><a:lt1><a:sysClr val="window" lastClr="FFFFFF"/></a:lt1><a:accent1><a:srgbClr val="4472C4"/></a:accent1><a:hlink><a:srgbClr val="0563C1"/></a:hlink></a:clrScheme></a:themeElements></a:theme>"#;
+
+    fn resolve(name: &str, tint: Option<&str>, shade: Option<&str>) -> Option<String> {
+        parse_theme_colors(THEME)
+            .unwrap()
+            .resolve(&ThemeColorRef::new(name.to_string(), tint, shade))
+    }
+
+    #[test]
+    fn test_scheme_slots_and_aliases() {
+        assert_eq!(resolve("accent1", None, None).as_deref(), Some("4472C4"));
+        assert_eq!(resolve("text1", None, None).as_deref(), Some("000000"));
+        assert_eq!(
+            resolve("background1", None, None).as_deref(),
+            Some("FFFFFF")
+        );
+        assert_eq!(resolve("hyperlink", None, None).as_deref(), Some("0563C1"));
+        assert_eq!(resolve("accent6", None, None), None);
+    }
+
+    #[test]
+    fn test_tint_and_shade() {
+        // Word's "Blue, Accent 1, Darker 50%" and "Lighter 80%"
+        assert_eq!(
+            resolve("accent1", None, Some("80")).as_deref(),
+            Some("1F3864")
+        );
+        assert_eq!(
+            resolve("accent1", Some("33"), None).as_deref(),
+            Some("D9E2F3")
+        );
+        // "Darker 25%" and "Lighter 40%"
+        assert_eq!(
+            resolve("accent1", None, Some("BF")).as_deref(),
+            Some("2F5496")
+        );
+        assert_eq!(
+            resolve("accent1", Some("99"), None).as_deref(),
+            Some("8EAADB")
+        );
+        // "Black, Text 1, Lighter 50%"
+        assert_eq!(
+            resolve("text1", Some("80"), None).as_deref(),
+            Some("7F7F7F")
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Progress of loading and exporting a document
+//!
+//! A large document takes a while to open, so the loader reports each phase
+//! as it starts and finishes, with steps counted where it knows how many
+//! there are: media files extracted, body paragraphs and tables read. Callers
+//! pass anything implementing [`Progress`], a closure included, to
+//! [`load_document_with_progress`](super::loader::load_document_with_progress);
+//! the CLI draws a progress bar from the events.
+
+/// A stage of loading or exporting a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Opening and checking the package
+    Opening,
+    /// Parsing `document.xml` and the parts beside it
+    Parsing,
+    /// Extracting images, counted by media file
+    Images,
+    /// Turning the body into elements, counted by paragraph and table
+    Building,
+    /// Writing an export to a file, counted in bytes
+    Exporting,
+}
+
+impl Phase {
+    /// What the phase is doing, for a status line
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Opening => "Opening",
+            Self::Parsing => "Parsing",
+            Self::Images => "Extracting images",
+            Self::Building => "Reading the body",
+            Self::Exporting => "Exporting",
+        }
+    }
+}
+
+/// Something that happened in a [`Phase`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// The phase began; `total` is how many steps it has, if that's known
+    Started {
+        phase: Phase,
+        total: Option<u64>,
+    },
+    /// `done` of the phase's steps are done
+    Advanced {
+        phase: Phase,
+        done: u64,
+    },
+    Finished {
+        phase: Phase,
+    },
+}
+
+/// Receives progress events, possibly from several threads at once
+pub trait Progress: Sync {
+    fn event(&self, event: ProgressEvent);
+
+    fn start(&self, phase: Phase, total: Option<u64>) {
+        self.event(ProgressEvent::Started { phase, total });
+    }
+
+    fn advance(&self, phase: Phase, done: u64) {
+        self.event(ProgressEvent::Advanced { phase, done });
+    }
+
+    fn finish(&self, phase: Phase) {
+        self.event(ProgressEvent::Finished { phase });
+    }
+}
+
+impl<F: Fn(ProgressEvent) + Sync> Progress for F {
+    fn event(&self, event: ProgressEvent) {
+        self(event)
+    }
+}
+
+/// Progress that goes nowhere, for loading without reporting it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn event(&self, _event: ProgressEvent) {}
+}
@@ -1,91 +1,261 @@
 //! Document search and navigation operations
 //!
 //! This module provides read-only querying operations on parsed documents,
-//! including full-text search and outline generation.
+//! including full-text search, outline generation, and fuzzy heading lookup.
 
 use super::models::*;
+use super::words::{word_counts, WordCounts};
 
 pub fn search_document(document: &Document, query: &str) -> Vec<SearchResult> {
-    let mut results = Vec::new();
     // TODO: consider deferring search execution until Enter is pressed
     if query.is_empty() {
-        return results;
+        return Vec::new();
     }
     let query_lower = query.to_lowercase();
 
+    searchable_text(document)
+        .into_iter()
+        .filter_map(|searchable| {
+            let start_pos = searchable.text.to_lowercase().find(&query_lower)?;
+            Some(SearchResult {
+                element_index: searchable.element_index,
+                text: searchable.text,
+                start_pos,
+                end_pos: start_pos + query.len(),
+            })
+        })
+        .collect()
+}
+
+/// Everything search looks through, in document order: each list item and
+/// table cell on its own, and a table's title before its cells
+pub fn searchable_text(document: &Document) -> Vec<SearchableText> {
+    let mut texts = Vec::new();
     for (element_index, element) in document.elements.iter().enumerate() {
-        let text = match element {
-            DocumentElement::Heading { text, .. } => text,
-            DocumentElement::Paragraph { runs } => {
+        let mut push = |text: String| {
+            texts.push(SearchableText {
+                element_index,
+                text,
+            })
+        };
+        match element {
+            DocumentElement::Heading { text, .. } => push(text.clone()),
+            DocumentElement::Paragraph { runs, .. } | DocumentElement::BlockQuote { runs } => {
                 // Combine text from all runs for searching
-                &runs.iter().map(|run| run.text.as_str()).collect::<String>()
+                push(runs.iter().map(|run| run.text.as_str()).collect())
             }
             DocumentElement::List { items, .. } => {
-                // Search in list items
                 for item in items {
-                    let item_text: String = item.runs.iter().map(|run| run.text.as_str()).collect();
-                    let text_lower = item_text.to_lowercase();
-                    if let Some(start_pos) = text_lower.find(&query_lower) {
-                        results.push(SearchResult {
-                            element_index,
-                            text: item_text,
-                            start_pos,
-                            end_pos: start_pos + query.len(),
-                        });
-                    }
+                    push(item.runs.iter().map(|run| run.text.as_str()).collect());
                 }
-                continue;
             }
             DocumentElement::Table { table } => {
-                // Search in table content
-                for header in &table.headers {
-                    let text_lower = header.content.to_lowercase();
-                    if let Some(start_pos) = text_lower.find(&query_lower) {
-                        results.push(SearchResult {
-                            element_index,
-                            text: header.content.clone(),
-                            start_pos,
-                            end_pos: start_pos + query.len(),
-                        });
-                    }
+                if let Some(title) = &table.metadata.title {
+                    push(title.clone());
                 }
-                for row in &table.rows {
-                    for cell in row {
-                        let text_lower = cell.content.to_lowercase();
-                        if let Some(start_pos) = text_lower.find(&query_lower) {
-                            results.push(SearchResult {
-                                element_index,
-                                text: cell.content.clone(),
-                                start_pos,
-                                end_pos: start_pos + query.len(),
-                            });
-                        }
-                    }
+                for cell in table.headers.iter().chain(table.rows.iter().flatten()) {
+                    push(cell.content.clone());
                 }
-                continue;
             }
-            DocumentElement::Image { description, .. } => description,
-            DocumentElement::Equation { latex, .. } => latex,
-            DocumentElement::PageBreak => continue,
-        };
+            DocumentElement::Image {
+                description,
+                caption,
+                ..
+            } => push(caption.as_ref().unwrap_or(description).clone()),
+            DocumentElement::Equation { latex, .. } => push(latex.clone()),
+            DocumentElement::CodeBlock { code } => push(code.clone()),
+            DocumentElement::FileBoundary { name } => push(name.clone()),
+            DocumentElement::PageBreak { .. } | DocumentElement::HorizontalRule => {}
+        }
+    }
+    texts
+}
 
-        let text_lower = text.to_lowercase();
-        if let Some(start_pos) = text_lower.find(&query_lower) {
-            results.push(SearchResult {
-                element_index,
-                text: text.clone(),
-                start_pos,
-                end_pos: start_pos + query.len(),
-            });
+/// Texts matching `pattern`, each with the headings it's under and as many
+/// texts before and after it as `options` asks, for `doxx grep`
+pub fn grep_document(
+    document: &Document,
+    pattern: &regex::Regex,
+    options: &GrepOptions,
+) -> Vec<GrepMatch> {
+    let texts = searchable_text(document);
+    let mut matches = Vec::new();
+    // Headings above the element being searched: (level, title)
+    let mut headings: Vec<(u8, String)> = Vec::new();
+    let mut next_element = 0;
+    for (position, searchable) in texts.iter().enumerate() {
+        if options.max_count.is_some_and(|max| matches.len() >= max) {
+            break;
+        }
+        for element in &document.elements[next_element..searchable.element_index] {
+            if let DocumentElement::Heading { level, .. } = element {
+                headings.retain(|(open, _)| open < level);
+                headings.push((*level, heading_title(element)));
+            }
         }
+        next_element = next_element.max(searchable.element_index);
+
+        let ranges: Vec<_> = pattern
+            .find_iter(&searchable.text)
+            .filter(|found| !found.is_empty())
+            .map(|found| found.range())
+            .collect();
+        if ranges.is_empty() {
+            continue;
+        }
+        // A heading is under the headings above its level
+        let level = match &document.elements[searchable.element_index] {
+            DocumentElement::Heading { level, .. } => *level,
+            _ => u8::MAX,
+        };
+        matches.push(GrepMatch {
+            position: position + 1,
+            element_index: searchable.element_index,
+            headings: headings
+                .iter()
+                .filter(|(open, _)| *open < level)
+                .map(|(_, title)| title.clone())
+                .collect(),
+            text: searchable.text.clone(),
+            ranges,
+            before: texts[position.saturating_sub(options.before)..position].to_vec(),
+            after: texts[position + 1..(position + 1 + options.after).min(texts.len())].to_vec(),
+        });
     }
+    matches
+}
 
-    results
+/// A heading as the outline titles it, with its number
+fn heading_title(element: &DocumentElement) -> String {
+    match element {
+        DocumentElement::Heading {
+            text,
+            number: Some(number),
+            ..
+        } => format!("{number} {text}"),
+        DocumentElement::Heading { text, .. } => text.clone(),
+        _ => String::new(),
+    }
 }
 
+/// Headings in document order; in merged documents, the files they're in
+/// head the outline and their headings sit a level below
 pub fn generate_outline(document: &Document) -> Vec<OutlineItem> {
     let mut outline = Vec::new();
+    let mut depth = 0;
+
+    for (index, element) in document.elements.iter().enumerate() {
+        match element {
+            DocumentElement::Heading {
+                level,
+                text,
+                number,
+            } => {
+                let title = if let Some(number) = number {
+                    format!("{number} {text}")
+                } else {
+                    text.clone()
+                };
+                outline.push(OutlineItem {
+                    title,
+                    level: level + depth,
+                    element_index: index,
+                });
+            }
+            DocumentElement::FileBoundary { name } => {
+                depth = 1;
+                outline.push(OutlineItem {
+                    title: name.clone(),
+                    level: 1,
+                    element_index: index,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    outline
+}
+
+/// Every captioned table and figure, in document order
+pub fn caption_list(document: &Document) -> Vec<CaptionEntry> {
+    document
+        .elements
+        .iter()
+        .enumerate()
+        .filter_map(|(element_index, element)| {
+            let (kind, text) = match element {
+                DocumentElement::Table { table } => {
+                    (CaptionKind::Table, table.metadata.title.as_ref()?)
+                }
+                DocumentElement::Image { caption, .. } => (CaptionKind::Figure, caption.as_ref()?),
+                _ => return None,
+            };
+            Some(CaptionEntry {
+                kind,
+                text: text.clone(),
+                element_index,
+            })
+        })
+        .collect()
+}
+
+/// The List of Tables and List of Figures as outline entries
+///
+/// Each list is a top-level entry at its first table or figure, with the
+/// captions under it; a list with nothing in it is left out.
+pub fn caption_outline(document: &Document) -> Vec<OutlineItem> {
+    let captions = caption_list(document);
+    let mut outline = Vec::new();
+    for (kind, title) in [
+        (CaptionKind::Table, "List of Tables"),
+        (CaptionKind::Figure, "List of Figures"),
+    ] {
+        let mut entries = captions
+            .iter()
+            .filter(|entry| entry.kind == kind)
+            .peekable();
+        let Some(first) = entries.peek() else {
+            continue;
+        };
+        outline.push(OutlineItem {
+            title: title.to_string(),
+            level: 1,
+            element_index: first.element_index,
+        });
+        outline.extend(entries.map(|entry| OutlineItem {
+            title: entry.text.clone(),
+            level: 2,
+            element_index: entry.element_index,
+        }));
+    }
+    outline
+}
 
+/// The heading tree with word counts for each section
+///
+/// A heading nests under the nearest heading above it with a lower level, so
+/// skipped levels don't leave gaps. Words before the first heading belong to
+/// no section.
+pub fn section_outline(document: &Document) -> Vec<OutlineSection> {
+    // Move a finished section into its parent, or the top level
+    fn close(stack: &mut Vec<OutlineSection>, roots: &mut Vec<OutlineSection>) {
+        if let Some(mut section) = stack.pop() {
+            section.total_words = section.words
+                + section
+                    .children
+                    .iter()
+                    .map(|child| child.total_words)
+                    .sum::<usize>();
+            stack
+                .last_mut()
+                .map_or(&mut *roots, |parent| &mut parent.children)
+                .push(section);
+        }
+    }
+
+    let mut roots = Vec::new();
+    let mut stack: Vec<OutlineSection> = Vec::new();
     for (index, element) in document.elements.iter().enumerate() {
         if let DocumentElement::Heading {
             level,
@@ -93,18 +263,315 @@ pub fn generate_outline(document: &Document) -> Vec<OutlineItem> {
             number,
         } = element
         {
-            let title = if let Some(number) = number {
-                format!("{number} {text}")
-            } else {
-                text.clone()
-            };
-            outline.push(OutlineItem {
-                title,
+            while stack.last().is_some_and(|open| open.level >= *level) {
+                close(&mut stack, &mut roots);
+            }
+            stack.push(OutlineSection {
                 level: *level,
+                number: number.clone(),
+                text: text.clone(),
                 element_index: index,
+                words: element_words(element),
+                total_words: 0,
+                children: Vec::new(),
             });
+        } else if let Some(section) = stack.last_mut() {
+            section.words += element_words(element);
         }
     }
+    while !stack.is_empty() {
+        close(&mut stack, &mut roots);
+    }
+    roots
+}
 
-    outline
+/// Words of text in an element, table cells included
+pub fn element_words(element: &DocumentElement) -> usize {
+    element_word_counts(element).total()
+}
+
+/// Words of text in an element, table cells included, by how they're read
+pub fn element_word_counts(element: &DocumentElement) -> WordCounts {
+    // A word may be split across runs, so runs are counted together
+    let runs_counts = |runs: &[FormattedRun]| {
+        word_counts(&runs.iter().map(|run| run.text.as_str()).collect::<String>())
+    };
+    match element {
+        DocumentElement::Heading { text, .. } => word_counts(text),
+        DocumentElement::Paragraph { runs, .. } | DocumentElement::BlockQuote { runs } => {
+            runs_counts(runs)
+        }
+        DocumentElement::CodeBlock { code } => word_counts(code),
+        DocumentElement::List { items, .. } => {
+            items.iter().map(|item| runs_counts(&item.runs)).sum()
+        }
+        DocumentElement::Table { table } => table
+            .headers
+            .iter()
+            .chain(table.rows.iter().flatten())
+            .map(|cell| word_counts(&cell.content))
+            .sum(),
+        DocumentElement::Image { .. }
+        | DocumentElement::Equation { .. }
+        | DocumentElement::PageBreak { .. }
+        | DocumentElement::HorizontalRule
+        | DocumentElement::FileBoundary { .. } => WordCounts::default(),
+    }
+}
+
+/// The heading whose section holds an element: the element itself if it's a
+/// heading, or else the nearest heading before it
+pub fn section_heading(document: &Document, element_index: usize) -> Option<usize> {
+    let end = element_index.min(document.elements.len().checked_sub(1)?);
+    document.elements[..=end]
+        .iter()
+        .rposition(|element| matches!(element, DocumentElement::Heading { .. }))
+}
+
+/// Whether each element is hidden by a folded heading
+///
+/// Folding a heading hides everything up to the next heading at its level or
+/// above, deeper headings included; folds inside a hidden section are kept but
+/// have nothing more to hide.
+pub fn folded_elements(
+    document: &Document,
+    folds: &std::collections::BTreeSet<usize>,
+) -> Vec<bool> {
+    let mut hidden = Vec::with_capacity(document.elements.len());
+    // Level of the folded heading whose section we're in
+    let mut folded_level = None;
+    for (index, element) in document.elements.iter().enumerate() {
+        let level = match element {
+            DocumentElement::Heading { level, .. } => Some(*level),
+            _ => None,
+        };
+        if level.is_some_and(|level| folded_level.is_some_and(|folded| level <= folded)) {
+            folded_level = None;
+        }
+        hidden.push(folded_level.is_some());
+        if folded_level.is_none() && folds.contains(&index) {
+            folded_level = level;
+        }
+    }
+    hidden
+}
+
+/// Split a document into presenter-mode slides, one per section
+///
+/// A slide starts at every heading down to the first level with two headings
+/// at or above it, so a document under a single title heading is split at its
+/// chapters, with the title as a slide of its own. Anything before the first
+/// such heading is a slide if it has content; deeper headings stay in their
+/// slide.
+pub fn presentation_slides(document: &Document) -> Vec<Slide> {
+    let elements = &document.elements;
+    let mut levels: Vec<u8> = elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Heading { level, .. } => Some(*level),
+            _ => None,
+        })
+        .collect();
+    levels.sort_unstable();
+    let Some(&split_level) = levels.get(1).or(levels.first()) else {
+        return vec![Slide {
+            heading: None,
+            body: 0..elements.len(),
+        }];
+    };
+
+    let starts: Vec<usize> = elements
+        .iter()
+        .enumerate()
+        .filter(|(_, element)| {
+            matches!(element, DocumentElement::Heading { level, .. } if *level <= split_level)
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut slides = Vec::with_capacity(starts.len() + 1);
+    let first = starts[0];
+    if elements[..first].iter().any(has_content) {
+        slides.push(Slide {
+            heading: None,
+            body: 0..first,
+        });
+    }
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(elements.len());
+        slides.push(Slide {
+            heading: Some(start),
+            body: start + 1..end,
+        });
+    }
+    slides
+}
+
+/// Whether an element shows anything: not a page break or an empty paragraph
+fn has_content(element: &DocumentElement) -> bool {
+    match element {
+        DocumentElement::Paragraph { runs, .. } => {
+            runs.iter().any(|run| !run.text.trim().is_empty())
+        }
+        DocumentElement::PageBreak { .. }
+        | DocumentElement::HorizontalRule
+        | DocumentElement::FileBoundary { .. } => false,
+        _ => true,
+    }
+}
+
+/// Resolve a heading reference (as given to `--goto`) to its element index
+///
+/// An exact, case-insensitive title match wins (with or without the heading
+/// number); otherwise the best fuzzy match is used.
+pub fn resolve_heading(document: &Document, query: &str) -> Option<usize> {
+    let wanted = query.trim().to_lowercase();
+    let exact = document
+        .elements
+        .iter()
+        .enumerate()
+        .find_map(|(index, element)| match element {
+            DocumentElement::Heading { text, number, .. } => {
+                let numbered = number
+                    .as_ref()
+                    .map(|n| format!("{n} {text}").to_lowercase());
+                (text.to_lowercase() == wanted || numbered.as_deref() == Some(wanted.as_str()))
+                    .then_some(index)
+            }
+            _ => None,
+        });
+
+    exact.or_else(|| {
+        fuzzy_find_headings(document, query)
+            .first()
+            .map(|heading| heading.item.element_index)
+    })
+}
+
+/// Headings, tables and images, in document order, for the command palette
+///
+/// Headings are titled as in the outline. A table without a title is named by
+/// its position and header row, and an image without a description by its
+/// position.
+pub fn navigation_targets(document: &Document) -> Vec<NavigationTarget> {
+    let mut targets: Vec<NavigationTarget> = generate_outline(document)
+        .into_iter()
+        .map(|item| NavigationTarget {
+            kind: TargetKind::Heading,
+            title: item.title,
+            element_index: item.element_index,
+        })
+        .collect();
+
+    let (mut tables, mut images) = (0, 0);
+    for (element_index, element) in document.elements.iter().enumerate() {
+        let (kind, title) = match element {
+            DocumentElement::Table { table } => {
+                tables += 1;
+                let title = table.metadata.title.clone().unwrap_or_else(|| {
+                    let headers: Vec<&str> = table
+                        .headers
+                        .iter()
+                        .map(|cell| cell.content.trim())
+                        .collect();
+                    format!("Table {tables}: {}", headers.join(" | "))
+                });
+                (TargetKind::Table, title)
+            }
+            DocumentElement::Image {
+                description,
+                caption,
+                ..
+            } => {
+                images += 1;
+                let title = if let Some(caption) = caption {
+                    caption.trim().to_string()
+                } else if description.trim().is_empty() {
+                    format!("Image {images}")
+                } else {
+                    description.trim().to_string()
+                };
+                (TargetKind::Image, title)
+            }
+            _ => continue,
+        };
+        targets.push(NavigationTarget {
+            kind,
+            title,
+            element_index,
+        });
+    }
+
+    targets.sort_by_key(|target| target.element_index);
+    targets
+}
+
+/// Fuzzy-match one candidate against `query`, as [`fuzzy_find_headings`] does
+///
+/// Returns the score and the char indices of `candidate` that matched, or
+/// `None` if it doesn't match.
+pub fn fuzzy_match(candidate: &str, query: &str) -> Option<(i64, Vec<usize>)> {
+    let query_chars: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    fuzzy_score(candidate, &query_chars)
+}
+
+/// Fuzzy-match heading titles against `query`, best matches first
+///
+/// Query characters must appear in the title in order (case-insensitively), but
+/// not necessarily next to each other; whitespace in the query is ignored.
+/// Consecutive characters and matches at the start of a word score higher, so
+/// "exsum" ranks "Executive Summary" above "Example results summary". An empty
+/// query returns every heading in document order.
+pub fn fuzzy_find_headings(document: &Document, query: &str) -> Vec<HeadingMatch> {
+    let query_chars: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+
+    let mut matches: Vec<HeadingMatch> = generate_outline(document)
+        .into_iter()
+        .filter_map(|item| {
+            let (score, positions) = fuzzy_score(&item.title, &query_chars)?;
+            Some(HeadingMatch {
+                item,
+                score,
+                positions,
+            })
+        })
+        .collect();
+
+    // Stable sort keeps document order among equal scores
+    matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+    matches
+}
+
+/// Score a single candidate, returning `None` if the query is not a subsequence of it
+fn fuzzy_score(candidate: &str, query: &[char]) -> Option<(i64, Vec<usize>)> {
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut positions = Vec::with_capacity(query.len());
+    let mut score = 0i64;
+    let mut next = 0;
+
+    for &wanted in query {
+        let found = (next..chars.len()).find(|&i| chars_eq_ignore_case(chars[i], wanted))?;
+
+        score += 1;
+        if positions.last().is_some_and(|&last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !chars[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        score -= (found - next) as i64;
+
+        positions.push(found);
+        next = found + 1;
+    }
+
+    Some((score, positions))
+}
+
+fn chars_eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
 }
@@ -0,0 +1,729 @@
+//! Lenient loading for damaged or non-conforming packages
+//!
+//! Files from some exporters and broken pipelines are missing package parts
+//! (`[Content_Types].xml`, relationships), have a truncated ZIP, or contain
+//! malformed XML, and the normal loader rejects them outright. Repair mode
+//! tries the normal loader first and then works down a ladder of fallbacks:
+//!
+//! 1. Read every part that still decompresses, salvaging entries from their
+//!    local file headers if the ZIP central directory is gone
+//! 2. Drop malformed auxiliary parts and fill in missing package plumbing, then
+//!    run the normal loader on the rebuilt package
+//! 3. If the body XML itself is broken, scan it with quick-xml, keeping every
+//!    paragraph, heading, list item and table it can read
+//!
+//! Each step taken is recorded in a [`RecoveryReport`].
+
+use anyhow::{bail, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::cleanup::{clean_word_list_markers, estimate_page_count};
+use super::io::{validate_docx_extension, DocxPackage};
+use super::loader::{load_document_with_fields, load_package};
+use super::models::*;
+use super::parsing::heading::detect_heading_from_text;
+use super::parsing::language::{document_language, extend_sample};
+use super::parsing::list::group_list_items;
+use super::progress::NoProgress;
+use super::words::count_words;
+
+const MAIN_DOCUMENT: &str = "word/document.xml";
+
+/// What repair mode had to do to open a document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// One human-readable line per problem found or repair made, in order
+    pub notes: Vec<String>,
+}
+
+impl RecoveryReport {
+    /// Whether the document loaded normally, with nothing to repair
+    pub fn is_clean(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    fn note(&mut self, note: impl Into<String>) {
+        self.notes.push(note.into());
+    }
+}
+
+/// Load a document, recovering what can be read from a damaged file instead of failing
+///
+/// Files the normal loader accepts come back unchanged with an empty report.
+#[allow(dead_code)] // Library entry point; the viewer passes field options
+pub fn load_document_lenient(
+    file_path: &Path,
+    image_options: ImageOptions,
+) -> Result<(Document, RecoveryReport)> {
+    load_document_lenient_with_fields(file_path, image_options, FieldOptions::default())
+}
+
+/// [`load_document_lenient`], with a choice of how field codes are shown
+pub fn load_document_lenient_with_fields(
+    file_path: &Path,
+    image_options: ImageOptions,
+    field_options: FieldOptions,
+) -> Result<(Document, RecoveryReport)> {
+    validate_docx_extension(file_path)?;
+    let mut report = RecoveryReport::default();
+
+    match load_document_with_fields(file_path, image_options.clone(), field_options) {
+        Ok(document) => return Ok((document, report)),
+        Err(error) => report.note(format!("Normal loading failed: {error:#}")),
+    }
+
+    let bytes = std::fs::read(file_path)?;
+    let mut parts = read_parts(&bytes, &mut report)?;
+    if !parts.iter().any(|(name, _)| name == MAIN_DOCUMENT) {
+        if parts.iter().any(|(name, _)| name == "xl/workbook.xml") {
+            bail!("This appears to be an Excel file (.xlsx), which repair mode can't read");
+        }
+        bail!("Nothing to recover: the package has no readable {MAIN_DOCUMENT}");
+    }
+
+    // Parts docx-rs would choke on are dropped; the body is handled separately below
+    let mut body_error = None;
+    parts.retain(|(name, data)| {
+        let is_xml = name.ends_with(".xml") || name.ends_with(".rels");
+        let Some(error) = is_xml.then(|| xml_error(data)).flatten() else {
+            return true;
+        };
+        if name == MAIN_DOCUMENT {
+            body_error = Some(error);
+            return true;
+        }
+        let (error, position) = error;
+        report.note(format!(
+            "Dropped {name}: malformed XML ({error} at byte {position})"
+        ));
+        false
+    });
+    add_missing_plumbing(&mut parts, &mut report);
+
+    if body_error.is_none() {
+        let package = DocxPackage::from_bytes(write_package(&parts)?)?;
+        match load_package(
+            &package,
+            Some(file_path),
+            image_options.clone(),
+            field_options,
+            &NoProgress,
+        ) {
+            Ok(document) => {
+                report.note("Loaded the repaired package");
+                return Ok((document, report));
+            }
+            Err(error) => report.note(format!("The repaired package still failed: {error:#}")),
+        }
+    } else if let Some((error, position)) = &body_error {
+        report.note(format!(
+            "{MAIN_DOCUMENT} is malformed: {error} at byte {position}"
+        ));
+    }
+
+    let body = parts
+        .iter()
+        .find(|(name, _)| name == MAIN_DOCUMENT)
+        .map(|(_, data)| data.as_slice())
+        .unwrap_or_default();
+    let xml = String::from_utf8_lossy(body);
+    if matches!(xml, std::borrow::Cow::Owned(_)) {
+        report.note(format!(
+            "{MAIN_DOCUMENT} is not valid UTF-8; bad bytes were replaced"
+        ));
+    }
+    let reported = body_error.map(|(_, position)| position);
+    let elements = scan_body(&xml, reported, &mut report);
+    report.note(format!(
+        "Recovered {} elements by scanning the body XML; list numbering and formatting \
+        beyond bold, italic, underline, strikethrough and colour were not kept",
+        elements.len()
+    ));
+
+    // Counted like the normal loader, which leaves table cells out
+    let word_count = elements
+        .iter()
+        .filter(|element| !matches!(element, DocumentElement::Table { .. }))
+        .map(|element| count_words(&element_text(element)))
+        .sum();
+    let mut sample = String::new();
+    for element in &elements {
+        if !extend_sample(&mut sample, &element_text(element)) {
+            break;
+        }
+    }
+    let styles = parts
+        .iter()
+        .find(|(name, _)| name == "word/styles.xml")
+        .map(|(_, data)| String::from_utf8_lossy(data));
+    let language = document_language(&xml, styles.as_deref(), &sample);
+    let document = Document {
+        title: file_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Untitled Document")
+            .to_string(),
+        metadata: DocumentMetadata {
+            file_path: file_path.to_string_lossy().to_string(),
+            file_size: bytes.len() as u64,
+            word_count,
+            page_count: estimate_page_count(word_count),
+            created: None,
+            modified: None,
+            author: None,
+            language,
+            locale: field_options.locale,
+            protection: None,
+        },
+        elements,
+        sections: Vec::new(),
+        image_options,
+        fidelity: Fidelity::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
+    };
+    Ok((document, report))
+}
+
+/// Every part that can still be decompressed, in package order
+fn read_parts(bytes: &[u8], report: &mut RecoveryReport) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut parts = Vec::new();
+
+    match ZipArchive::new(Cursor::new(bytes)) {
+        Ok(mut archive) => {
+            for index in 0..archive.len() {
+                let mut entry = match archive.by_index(index) {
+                    Ok(entry) => entry,
+                    Err(error) => {
+                        report.note(format!("Dropped ZIP entry {index}: {error}"));
+                        continue;
+                    }
+                };
+                if entry.is_dir() {
+                    continue;
+                }
+                let name = entry.name().to_string();
+                let mut data = Vec::new();
+                let result = entry.read_to_end(&mut data);
+                keep_part(&mut parts, name, data, result, report);
+            }
+        }
+        Err(error) => {
+            report.note(format!(
+                "The ZIP directory is unreadable ({error}); salvaging entries from their local headers"
+            ));
+            let mut reader = Cursor::new(bytes);
+            loop {
+                let mut entry = match zip::read::read_zipfile_from_stream(&mut reader) {
+                    Ok(Some(entry)) => entry,
+                    Ok(None) => break,
+                    Err(error) => {
+                        report.note(format!(
+                            "Stopped salvaging where the file is cut short or damaged: {error}"
+                        ));
+                        break;
+                    }
+                };
+                if entry.is_dir() {
+                    continue;
+                }
+                let name = entry.name().to_string();
+                let mut data = Vec::new();
+                let result = entry.read_to_end(&mut data);
+                // The stream position is unknown after a damaged entry
+                let damaged = result.is_err();
+                keep_part(&mut parts, name, data, result, report);
+                if damaged {
+                    break;
+                }
+            }
+        }
+    }
+
+    if parts.is_empty() {
+        bail!("Nothing to recover: no part of the package could be read");
+    }
+    Ok(parts)
+}
+
+/// Keep a part that read cleanly; a damaged part is still worth scanning up to the damage
+fn keep_part(
+    parts: &mut Vec<(String, Vec<u8>)>,
+    name: String,
+    data: Vec<u8>,
+    result: std::io::Result<usize>,
+    report: &mut RecoveryReport,
+) {
+    match result {
+        Ok(_) => parts.push((name, data)),
+        Err(error) if !data.is_empty() => {
+            report.note(format!(
+                "{name} is damaged ({error}); kept its first {} bytes",
+                data.len()
+            ));
+            parts.push((name, data));
+        }
+        Err(error) => report.note(format!("Dropped {name}: {error}")),
+    }
+}
+
+/// The first well-formedness error in an XML part, if any, and where it is
+fn xml_error(data: &[u8]) -> Option<(String, u64)> {
+    let mut reader = Reader::from_reader(data);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Eof) => return None,
+            Ok(_) => buf.clear(),
+            Err(error) => return Some((error.to_string(), reader.error_position())),
+        }
+    }
+}
+
+/// Add the content types and relationships a Word package needs, where missing
+fn add_missing_plumbing(parts: &mut Vec<(String, Vec<u8>)>, report: &mut RecoveryReport) {
+    let has = |parts: &[(String, Vec<u8>)], name: &str| parts.iter().any(|(n, _)| n == name);
+
+    if !has(parts, "[Content_Types].xml") {
+        let mut types = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/>"#,
+        );
+        for (extension, content_type) in [
+            ("png", "image/png"),
+            ("jpeg", "image/jpeg"),
+            ("jpg", "image/jpeg"),
+            ("gif", "image/gif"),
+            ("bmp", "image/bmp"),
+            ("emf", "image/x-emf"),
+            ("wmf", "image/x-wmf"),
+        ] {
+            let suffix = format!(".{extension}");
+            if parts.iter().any(|(name, _)| name.ends_with(&suffix)) {
+                types.push_str(&format!(
+                    r#"<Default Extension="{extension}" ContentType="{content_type}"/>"#
+                ));
+            }
+        }
+        types.push_str(r#"<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#);
+        parts.push(("[Content_Types].xml".to_string(), types.into_bytes()));
+        report.note("Rebuilt the missing [Content_Types].xml");
+    }
+
+    if !has(parts, "_rels/.rels") {
+        let rels = relationships(&[("officeDocument", MAIN_DOCUMENT)]);
+        parts.push(("_rels/.rels".to_string(), rels.into_bytes()));
+        report.note("Rebuilt the missing _rels/.rels");
+    }
+
+    if !has(parts, "word/_rels/document.xml.rels") {
+        // Only the parts found by type can be relinked; images and headers are
+        // referenced by relationship ids that are lost with the file
+        let targets: Vec<(&str, &str)> = [
+            ("styles", "styles.xml"),
+            ("numbering", "numbering.xml"),
+            ("settings", "settings.xml"),
+            ("fontTable", "fontTable.xml"),
+            ("theme", "theme/theme1.xml"),
+            ("footnotes", "footnotes.xml"),
+            ("endnotes", "endnotes.xml"),
+            ("comments", "comments.xml"),
+        ]
+        .into_iter()
+        .filter(|(_, target)| has(parts, &format!("word/{target}")))
+        .collect();
+        let rels = relationships(&targets);
+        parts.push((
+            "word/_rels/document.xml.rels".to_string(),
+            rels.into_bytes(),
+        ));
+        report.note("Rebuilt the missing word/_rels/document.xml.rels");
+    }
+}
+
+/// A relationships part with one officeDocument relationship per `(type, target)`
+fn relationships(targets: &[(&str, &str)]) -> String {
+    let mut rels = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    );
+    for (i, (kind, target)) in targets.iter().enumerate() {
+        rels.push_str(&format!(
+            r#"<Relationship Id="rIdRepair{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/{kind}" Target="{target}"/>"#,
+            i + 1
+        ));
+    }
+    rels.push_str("</Relationships>");
+    rels
+}
+
+/// Write parts into a new, uncompressed ZIP package
+fn write_package(parts: &[(String, Vec<u8>)]) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for (name, data) in parts {
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(data)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .map(|attribute| String::from_utf8_lossy(&attribute.value).into_owned())
+}
+
+/// A run property toggle such as `<w:b/>` is on unless `w:val` turns it off
+fn toggle_is_on(element: &BytesStart) -> bool {
+    !matches!(
+        attribute(element, b"w:val").as_deref(),
+        Some("0" | "false" | "off" | "none")
+    )
+}
+
+/// A body paragraph being scanned
+#[derive(Default)]
+struct ScannedParagraph {
+    runs: Vec<FormattedRun>,
+    formatting: TextFormatting,
+    style: Option<String>,
+    list_level: Option<u8>,
+    alignment: TextAlignment,
+    rtl: bool,
+}
+
+impl ScannedParagraph {
+    fn text(&self) -> String {
+        self.runs.iter().map(|run| run.text.as_str()).collect()
+    }
+
+    fn into_element(self) -> Option<DocumentElement> {
+        let text = self.text();
+        if text.trim().is_empty() {
+            return None;
+        }
+        let heading_level = self.style.as_deref().and_then(|style| {
+            let lower = style.to_ascii_lowercase();
+            if lower == "title" {
+                return Some(1);
+            }
+            let level = lower.strip_prefix("heading")?;
+            Some(level.parse::<u8>().map_or(1, |level| level.clamp(1, 6)))
+        });
+
+        if let Some(level) = heading_level {
+            return Some(DocumentElement::Heading {
+                level,
+                text,
+                number: None,
+            });
+        }
+        if let Some(level) = self.list_level {
+            // Same marker the loader uses, so lists group the same way
+            let mut runs = vec![FormattedRun {
+                text: format!("__WORD_LIST__{}* ", "  ".repeat(level as usize)),
+                formatting: TextFormatting::default(),
+            }];
+            runs.extend(self.runs);
+            return Some(DocumentElement::Paragraph {
+                runs,
+                alignment: TextAlignment::Left,
+                shading: None,
+                rtl: false,
+                spacing: None,
+                tab_stops: Vec::new(),
+            });
+        }
+        // Without styles, fall back on the loader's text heuristics
+        let first_formatting = self
+            .runs
+            .first()
+            .map(|run| run.formatting.clone())
+            .unwrap_or_default();
+        if let Some(level) = detect_heading_from_text(&text, &first_formatting) {
+            return Some(DocumentElement::Heading {
+                level,
+                text,
+                number: None,
+            });
+        }
+        // `w:jc` names edges relative to the reading direction
+        let alignment = match (self.alignment, self.rtl) {
+            (TextAlignment::Left, true) => TextAlignment::Right,
+            (TextAlignment::Right, true) => TextAlignment::Left,
+            (alignment, _) => alignment,
+        };
+        Some(DocumentElement::Paragraph {
+            runs: self.runs,
+            alignment,
+            shading: None,
+            rtl: self.rtl,
+            spacing: None,
+            tab_stops: Vec::new(),
+        })
+    }
+}
+
+/// Recover elements from body XML that may be malformed or cut short
+///
+/// `reported` is the position of an error already in the report, so it isn't repeated.
+fn scan_body(
+    xml: &str,
+    reported: Option<u64>,
+    report: &mut RecoveryReport,
+) -> Vec<DocumentElement> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(false);
+    // Mismatched end tags are a common form of damage; match them up by name below
+    reader.config_mut().check_end_names = false;
+
+    let mut elements = Vec::new();
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    let mut paragraph: Option<ScannedParagraph> = None;
+    // Open tables (nested tables are flattened into their cell), their rows and cells
+    let mut tables: Vec<Vec<Vec<String>>> = Vec::new();
+    let mut images = 0;
+    let mut bad_text = false;
+    let mut last_error = None;
+
+    let finish_paragraph = |paragraph: Option<ScannedParagraph>,
+                            tables: &mut Vec<Vec<Vec<String>>>,
+                            elements: &mut Vec<DocumentElement>| {
+        let Some(paragraph) = paragraph else {
+            return;
+        };
+        match tables
+            .last_mut()
+            .and_then(|rows| rows.last_mut()?.last_mut())
+        {
+            Some(cell) => {
+                let text = paragraph.text();
+                if !cell.is_empty() && !text.trim().is_empty() {
+                    cell.push(' ');
+                }
+                cell.push_str(text.trim());
+            }
+            None => elements.extend(paragraph.into_element()),
+        }
+    };
+
+    loop {
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            Err(error) => {
+                let position = reader.error_position();
+                // Give up once the reader stops making progress
+                if last_error == Some(position) {
+                    break;
+                }
+                last_error = Some(position);
+                if reported != Some(position) {
+                    report.note(format!("Skipped damaged XML at byte {position}: {error}"));
+                }
+                continue;
+            }
+        };
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_start = matches!(event, Event::Start(_));
+                let name = e.name().as_ref().to_vec();
+                let in_run_properties = stack.last().is_some_and(|n| n == b"w:rPr");
+                let in_paragraph_properties = stack.last().is_some_and(|n| n == b"w:pPr");
+                match name.as_slice() {
+                    b"w:p" if is_start => {
+                        finish_paragraph(paragraph.take(), &mut tables, &mut elements);
+                        paragraph = Some(ScannedParagraph::default());
+                    }
+                    b"w:tbl" if is_start => {
+                        finish_paragraph(paragraph.take(), &mut tables, &mut elements);
+                        tables.push(Vec::new());
+                    }
+                    b"w:tr" if is_start => {
+                        if let Some(rows) = tables.last_mut() {
+                            rows.push(Vec::new());
+                        }
+                    }
+                    b"w:tc" => {
+                        if let Some(row) = tables.last_mut().and_then(|rows| rows.last_mut()) {
+                            row.push(String::new());
+                        }
+                    }
+                    b"w:r" if is_start => {
+                        if let Some(paragraph) = &mut paragraph {
+                            paragraph.formatting = TextFormatting::default();
+                        }
+                    }
+                    b"w:drawing" | b"w:pict" => images += 1,
+                    b"w:pStyle" if in_paragraph_properties => {
+                        if let Some(paragraph) = &mut paragraph {
+                            paragraph.style = attribute(e, b"w:val");
+                        }
+                    }
+                    b"w:ilvl" => {
+                        if let Some(paragraph) = &mut paragraph {
+                            let level = attribute(e, b"w:val").and_then(|v| v.parse().ok());
+                            paragraph.list_level = Some(level.unwrap_or(0));
+                        }
+                    }
+                    b"w:numPr" => {
+                        if let Some(paragraph) = &mut paragraph {
+                            paragraph.list_level.get_or_insert(0);
+                        }
+                    }
+                    b"w:jc" if in_paragraph_properties => {
+                        if let Some(paragraph) = &mut paragraph {
+                            paragraph.alignment = match attribute(e, b"w:val").as_deref() {
+                                Some("center") => TextAlignment::Center,
+                                Some("right" | "end") => TextAlignment::Right,
+                                Some("both" | "distribute") => TextAlignment::Justify,
+                                _ => TextAlignment::Left,
+                            };
+                        }
+                    }
+                    b"w:bidi" if in_paragraph_properties => {
+                        if let Some(paragraph) = &mut paragraph {
+                            paragraph.rtl = toggle_is_on(e);
+                        }
+                    }
+                    b"w:b" | b"w:i" | b"w:u" | b"w:strike" | b"w:rtl" | b"w:color"
+                        if in_run_properties =>
+                    {
+                        if let Some(paragraph) = &mut paragraph {
+                            let formatting = &mut paragraph.formatting;
+                            match name.as_slice() {
+                                b"w:b" => formatting.bold = toggle_is_on(e),
+                                b"w:i" => formatting.italic = toggle_is_on(e),
+                                b"w:u" => formatting.underline = toggle_is_on(e),
+                                b"w:strike" => formatting.strikethrough = toggle_is_on(e),
+                                b"w:rtl" => formatting.rtl = toggle_is_on(e),
+                                _ => {
+                                    formatting.color =
+                                        attribute(e, b"w:val").filter(|v| v != "auto")
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                if is_start {
+                    stack.push(name);
+                }
+            }
+            Event::Text(ref e) => {
+                if !stack.last().is_some_and(|n| n == b"w:t" || n == b"m:t") {
+                    continue;
+                }
+                let text = match e.unescape() {
+                    Ok(text) => text.into_owned(),
+                    Err(_) => {
+                        // A stray `&` and the like: keep the raw text
+                        bad_text = true;
+                        String::from_utf8_lossy(e).into_owned()
+                    }
+                };
+                if let Some(paragraph) = &mut paragraph {
+                    let formatting = paragraph.formatting.clone();
+                    FormattedRun::push_consolidated(
+                        &mut paragraph.runs,
+                        FormattedRun { text, formatting },
+                    );
+                }
+            }
+            Event::End(ref e) => {
+                let name = e.name().as_ref().to_vec();
+                // Close everything opened since the matching start tag, if there is one
+                let Some(depth) = stack.iter().rposition(|n| *n == name) else {
+                    continue;
+                };
+                stack.truncate(depth);
+                match name.as_slice() {
+                    b"w:p" => finish_paragraph(paragraph.take(), &mut tables, &mut elements),
+                    b"w:tbl" => {
+                        finish_paragraph(paragraph.take(), &mut tables, &mut elements);
+                        if let Some(rows) = tables.pop() {
+                            push_table(rows, &mut tables, &mut elements);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    // Whatever was open when the XML ran out
+    finish_paragraph(paragraph.take(), &mut tables, &mut elements);
+    while let Some(rows) = tables.pop() {
+        push_table(rows, &mut tables, &mut elements);
+    }
+
+    if bad_text {
+        report.note("Kept text with invalid character escapes as it was written");
+    }
+    if images > 0 {
+        report.note(format!(
+            "Skipped {images} images, which can't be placed without the normal loader"
+        ));
+    }
+
+    clean_word_list_markers(group_list_items(elements))
+}
+
+/// Add a scanned table as an element, or flatten it into the cell of an enclosing table
+fn push_table(
+    rows: Vec<Vec<String>>,
+    tables: &mut [Vec<Vec<String>>],
+    elements: &mut Vec<DocumentElement>,
+) {
+    let mut rows: Vec<Vec<String>> = rows.into_iter().filter(|row| !row.is_empty()).collect();
+    if rows.is_empty() {
+        return;
+    }
+
+    if let Some(cell) = tables
+        .last_mut()
+        .and_then(|rows| rows.last_mut()?.last_mut())
+    {
+        let text: Vec<String> = rows.iter().map(|row| row.join(" ")).collect();
+        if !cell.is_empty() {
+            cell.push(' ');
+        }
+        cell.push_str(&text.join(" "));
+        return;
+    }
+
+    let to_cells = |row: Vec<String>| row.into_iter().map(TableCell::new).collect::<Vec<_>>();
+    let headers = to_cells(rows.remove(0));
+    let rows = rows.into_iter().map(to_cells).collect();
+    elements.push(DocumentElement::Table {
+        table: TableData::new(headers, rows),
+    });
+}
+
+/// Plain text of an element, for the word count
+fn element_text(element: &DocumentElement) -> String {
+    match element {
+        DocumentElement::Heading { text, .. } => text.clone(),
+        DocumentElement::Paragraph { runs, .. } => {
+            runs.iter().map(|run| run.text.as_str()).collect()
+        }
+        DocumentElement::List { items, .. } => items
+            .iter()
+            .flat_map(|item| item.runs.iter().map(|run| run.text.as_str()))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => String::new(),
+    }
+}
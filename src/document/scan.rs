@@ -0,0 +1,669 @@
+//! Scanning for personal data and other sensitive text (`doxx scan`)
+//!
+//! Rules are regular expressions, some with a check of their own on what
+//! matches (card numbers pass the Luhn check, social security numbers aren't
+//! in the ranges never issued). They run over everything the viewer shows,
+//! table cells and captions included, and over the headers, footers,
+//! footnotes, endnotes and comments the body leaves out. Where rules overlap,
+//! the first to match a span keeps it, so a card number isn't also reported
+//! as a phone number.
+//!
+//! `--redact` masks what the same rules match in the document before the
+//! viewer or an export sees it.
+
+use anyhow::{bail, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use regex::Regex;
+use serde::Serialize;
+use std::ops::Range;
+use std::path::Path;
+
+use super::forensic::numbered_parts;
+use super::io::DocxPackage;
+use super::models::{CellDataType, Document, DocumentElement, FormattedRun};
+use super::query::searchable_text;
+use crate::Redaction;
+
+/// Characters of context shown either side of a match
+const CONTEXT_CHARS: usize = 30;
+
+/// The rules `pii` stands for, in the order they claim overlapping text
+pub const PII_RULES: &[&str] = &["credit-card", "ssn", "email", "phone"];
+
+/// Whether a match really is what a rule is after
+type Validator = fn(&str) -> bool;
+
+/// A pattern to look for, with an optional check of each match
+#[derive(Debug, Clone)]
+pub struct ScanRule {
+    pub name: String,
+    pub pattern: Regex,
+    validate: Option<Validator>,
+}
+
+impl ScanRule {
+    /// A rule from a regular expression, as configured or given on the
+    /// command line
+    pub fn custom(name: &str, pattern: &str) -> Result<Self> {
+        Ok(Self {
+            name: name.to_string(),
+            pattern: Regex::new(pattern)?,
+            validate: None,
+        })
+    }
+
+    /// One of the built-in rules: `email`, `ssn`, `credit-card` or `phone`
+    pub fn builtin(name: &str) -> Option<Self> {
+        let (pattern, validate): (&str, Option<Validator>) = match name {
+            "email" => (
+                r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b",
+                None,
+            ),
+            // US social security numbers, written with dashes or spaces
+            "ssn" => (r"\b\d{3}([- ])\d{2}([- ])\d{4}\b", Some(valid_ssn)),
+            "credit-card" => (r"\b\d(?:[ -]?\d){12,18}\b", Some(luhn)),
+            // North American numbers, and international ones written with a `+`
+            "phone" => (
+                r"(?:\+1[ .-]?)?(?:\(\d{3}\)|\b\d{3})[ .-]?\d{3}[ .-]\d{4}\b|\+\d{1,3}(?:[ .-]?\(?\d{1,4}\)?){2,5}\d\b",
+                Some(enough_phone_digits),
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            name: name.to_string(),
+            pattern: Regex::new(pattern).expect("built-in scan rules are valid"),
+            validate,
+        })
+    }
+
+    fn accepts(&self, found: &str) -> bool {
+        self.validate.is_none_or(|validate| validate(found))
+    }
+}
+
+/// The rules named, in order, expanding `pii` to the built-in rules; names
+/// that aren't built in are looked up in `custom`
+pub fn resolve_rules(names: &[String], custom: &[ScanRule]) -> Result<Vec<ScanRule>> {
+    let mut rules: Vec<ScanRule> = Vec::new();
+    let mut add = |rule: ScanRule| {
+        if !rules.iter().any(|known| known.name == rule.name) {
+            rules.push(rule);
+        }
+    };
+    for name in names {
+        if name == "pii" {
+            PII_RULES
+                .iter()
+                .filter_map(|name| ScanRule::builtin(name))
+                .for_each(&mut add);
+        } else if let Some(rule) = custom.iter().find(|rule| rule.name == *name) {
+            add(rule.clone());
+        } else if let Some(rule) = ScanRule::builtin(name) {
+            add(rule);
+        } else {
+            bail!(
+                "No scan rule named {name}: use pii, {}, or a rule from the config file",
+                PII_RULES.join(", ")
+            );
+        }
+    }
+    Ok(rules)
+}
+
+fn digits(text: &str) -> Vec<u32> {
+    text.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+/// A card number's check digit (the Luhn algorithm)
+fn luhn(text: &str) -> bool {
+    let digits = digits(text);
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| match i % 2 {
+            0 => digit,
+            _ if digit * 2 > 9 => digit * 2 - 9,
+            _ => digit * 2,
+        })
+        .sum();
+    (13..=19).contains(&digits.len()) && sum.is_multiple_of(10)
+}
+
+/// Not an area, group or serial number the SSA never issues
+fn valid_ssn(text: &str) -> bool {
+    let digits: String = text.chars().filter(char::is_ascii_digit).collect();
+    let (area, rest) = digits.split_at(3);
+    let (group, serial) = rest.split_at(2);
+    !matches!(area, "000" | "666") && !area.starts_with('9') && group != "00" && serial != "0000"
+}
+
+fn enough_phone_digits(text: &str) -> bool {
+    (10..=15).contains(&digits(text).len())
+}
+
+/// A piece of text to scan and where it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanText {
+    /// Package part, e.g. `word/document.xml` or `word/footer1.xml`
+    pub part: String,
+    /// The element in the body, as the viewer's inspector numbers them
+    pub element_index: Option<usize>,
+    /// The paragraph in a part other than the body, counting from 1
+    pub paragraph: Option<usize>,
+    pub text: String,
+}
+
+/// Something a rule matched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanFinding {
+    pub rule: String,
+    pub part: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paragraph: Option<usize>,
+    /// The text matched
+    #[serde(rename = "match")]
+    pub matched: String,
+    /// Byte range of the match in the paragraph, cell or other text
+    pub range: Range<usize>,
+    /// The match with some of the text either side
+    pub context: String,
+}
+
+impl ScanFinding {
+    /// Where it is: `#12` for a body element, `footer1 ¶2` elsewhere
+    pub fn location(&self) -> String {
+        match (self.element_index, self.paragraph) {
+            (Some(index), _) => format!("#{index}"),
+            (None, paragraph) => {
+                let part = self
+                    .part
+                    .trim_start_matches("word/")
+                    .trim_end_matches(".xml");
+                match paragraph {
+                    Some(paragraph) => format!("{part} ¶{paragraph}"),
+                    None => part.to_string(),
+                }
+            }
+        }
+    }
+}
+
+/// The document's text, body first, then the parts the body leaves out in
+/// the order a reader meets them: headers, footers, footnotes, endnotes and
+/// comments. Without `package_path` only the body is scanned.
+pub fn scan_texts(document: &Document, package_path: Option<&Path>) -> Result<Vec<ScanText>> {
+    let mut texts: Vec<ScanText> = searchable_text(document)
+        .into_iter()
+        .map(|searchable| ScanText {
+            part: "word/document.xml".to_string(),
+            element_index: Some(searchable.element_index),
+            paragraph: None,
+            text: searchable.text,
+        })
+        .collect();
+    let Some(path) = package_path else {
+        return Ok(texts);
+    };
+    let package = DocxPackage::open(path)?;
+    let mut names = numbered_parts(&package, "word/header");
+    names.extend(numbered_parts(&package, "word/footer"));
+    names.extend(
+        [
+            "word/footnotes.xml",
+            "word/endnotes.xml",
+            "word/comments.xml",
+        ]
+        .into_iter()
+        .filter(|name| package.has_part(name))
+        .map(str::to_string),
+    );
+    for name in names {
+        let Some(data) = package.part(&name)? else {
+            continue;
+        };
+        for (paragraph, text) in part_paragraphs(&String::from_utf8_lossy(data))? {
+            texts.push(ScanText {
+                part: name.clone(),
+                element_index: None,
+                paragraph: Some(paragraph),
+                text,
+            });
+        }
+    }
+    Ok(texts)
+}
+
+/// The text of each paragraph with any, numbered from 1, leaving out the
+/// separator lines above the notes
+fn part_paragraphs(xml: &str) -> Result<Vec<(usize, String)>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(false);
+    let mut paragraphs = Vec::new();
+    // Paragraphs being read, with their numbers; text boxes nest them
+    let mut open: Vec<(usize, String)> = Vec::new();
+    let mut count = 0;
+    let mut in_text = false;
+    let mut skip_depth: Option<usize> = None;
+    let mut depth = 0;
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(e) => {
+                depth += 1;
+                if skip_depth.is_some() {
+                    continue;
+                }
+                match e.name().as_ref() {
+                    b"mc:Fallback" => skip_depth = Some(depth),
+                    b"w:footnote" | b"w:endnote"
+                        if e.attributes().flatten().any(|attr| {
+                            attr.key.as_ref() == b"w:type" && attr.value.as_ref() != b"normal"
+                        }) =>
+                    {
+                        skip_depth = Some(depth)
+                    }
+                    b"w:p" => {
+                        count += 1;
+                        open.push((count, String::new()));
+                    }
+                    b"w:t" => in_text = true,
+                    _ => {}
+                }
+            }
+            Event::Empty(e) if skip_depth.is_none() => {
+                if let Some((_, text)) = open.last_mut() {
+                    match e.name().as_ref() {
+                        b"w:tab" => text.push('\t'),
+                        b"w:br" | b"w:cr" => text.push(' '),
+                        _ => {}
+                    }
+                }
+            }
+            Event::Text(e) if in_text && skip_depth.is_none() => {
+                if let Some((_, text)) = open.last_mut() {
+                    text.push_str(&e.unescape()?);
+                }
+            }
+            Event::End(e) => {
+                if skip_depth == Some(depth) {
+                    skip_depth = None;
+                } else if skip_depth.is_none() {
+                    match e.name().as_ref() {
+                        b"w:t" => in_text = false,
+                        b"w:p" => {
+                            if let Some((number, text)) = open.pop() {
+                                if !text.trim().is_empty() {
+                                    paragraphs.push((number, text));
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                depth -= 1;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    paragraphs.sort_by_key(|(number, _)| *number);
+    Ok(paragraphs)
+}
+
+/// The spans of `text` the rules match, in order, each claimed by the first
+/// rule to match it
+fn rule_matches<'r>(text: &str, rules: &'r [ScanRule]) -> Vec<(Range<usize>, &'r ScanRule)> {
+    let mut claimed: Vec<(Range<usize>, &ScanRule)> = Vec::new();
+    for rule in rules {
+        for found in rule.pattern.find_iter(text) {
+            let range = found.range();
+            let overlaps = claimed
+                .iter()
+                .any(|(other, _)| other.start < range.end && range.start < other.end);
+            if !found.is_empty() && !overlaps && rule.accepts(found.as_str()) {
+                claimed.push((range, rule));
+            }
+        }
+    }
+    claimed.sort_by_key(|(range, _)| range.start);
+    claimed
+}
+
+/// What the rules match in the texts, in reading order
+pub fn scan(texts: &[ScanText], rules: &[ScanRule]) -> Vec<ScanFinding> {
+    let mut findings = Vec::new();
+    for text in texts {
+        let claimed = rule_matches(&text.text, rules);
+        findings.extend(claimed.into_iter().map(|(range, rule)| ScanFinding {
+            rule: rule.name.clone(),
+            part: text.part.clone(),
+            element_index: text.element_index,
+            paragraph: text.paragraph,
+            matched: text.text[range.clone()].to_string(),
+            context: context(&text.text, range.clone()),
+            range,
+        }));
+    }
+    findings
+}
+
+/// The match with up to `CONTEXT_CHARS` characters either side, on one line
+fn context(text: &str, range: Range<usize>) -> String {
+    let before = &text[..range.start];
+    let after = &text[range.end..];
+    let start = before
+        .char_indices()
+        .rev()
+        .nth(CONTEXT_CHARS - 1)
+        .map_or(0, |(at, _)| at);
+    let end = after
+        .char_indices()
+        .nth(CONTEXT_CHARS)
+        .map_or(after.len(), |(at, _)| at);
+    let mut context = String::new();
+    if start > 0 {
+        context.push('…');
+    }
+    context.push_str(&text[start..range.end + end]);
+    if end < after.len() {
+        context.push('…');
+    }
+    context.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// What `--redact` puts in place of a match with `Redaction::Block`
+pub const REDACTED: &str = "████";
+
+/// Masks what the rules match, for `--redact`
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    pub rules: Vec<ScanRule>,
+    pub style: Redaction,
+}
+
+impl Redactor {
+    /// What a match of `rule` is replaced with: the same blocks whatever its
+    /// length, or the rule's name, as in `[EMAIL]`
+    fn mask(&self, rule: &ScanRule) -> String {
+        match self.style {
+            Redaction::Block => REDACTED.to_string(),
+            Redaction::Placeholder => format!("[{}]", rule.name.to_uppercase()),
+        }
+    }
+
+    /// `text` with each match masked, and how many there were
+    pub fn redact_text(&self, text: &str) -> (String, usize) {
+        let found = rule_matches(text, &self.rules);
+        let mut redacted = String::with_capacity(text.len());
+        let mut at = 0;
+        for (range, rule) in &found {
+            redacted.push_str(&text[at..range.start]);
+            redacted.push_str(&self.mask(rule));
+            at = range.end;
+        }
+        redacted.push_str(&text[at..]);
+        (redacted, found.len())
+    }
+
+    /// Mask matches in a paragraph's runs, which a match may run across: the
+    /// mask goes in the run the match starts in, and runs left empty are dropped
+    fn redact_runs(&self, runs: &mut Vec<FormattedRun>) -> usize {
+        let text: String = runs.iter().map(|run| run.text.as_str()).collect();
+        let found = rule_matches(&text, &self.rules);
+        if found.is_empty() {
+            return 0;
+        }
+        let mut start = 0;
+        let mut redacted = Vec::with_capacity(runs.len());
+        for run in runs.drain(..) {
+            let end = start + run.text.len();
+            let mut masked = String::new();
+            let mut at = start;
+            for (range, rule) in found
+                .iter()
+                .filter(|(range, _)| range.start < end && start < range.end)
+            {
+                if range.start >= start {
+                    masked.push_str(&text[at..range.start]);
+                    masked.push_str(&self.mask(rule));
+                }
+                at = range.end.min(end);
+            }
+            masked.push_str(&text[at..end]);
+            if !masked.is_empty() || run.text.is_empty() {
+                redacted.push(FormattedRun {
+                    text: masked,
+                    formatting: run.formatting,
+                });
+            }
+            start = end;
+        }
+        *runs = redacted;
+        found.len()
+    }
+
+    /// Mask every match in the document's text, which the viewer and every
+    /// export then show; returns how many there were
+    pub fn redact_document(&self, document: &mut Document) -> usize {
+        let redact = |text: &mut String| {
+            let (redacted, found) = self.redact_text(text);
+            if found > 0 {
+                *text = redacted;
+            }
+            found
+        };
+
+        let mut count = redact(&mut document.title);
+        for element in &mut document.elements {
+            match element {
+                DocumentElement::Heading { text, .. } => count += redact(text),
+                DocumentElement::Paragraph { runs, .. } | DocumentElement::BlockQuote { runs } => {
+                    count += self.redact_runs(runs)
+                }
+                DocumentElement::List { items, .. } => {
+                    for item in items {
+                        count += self.redact_runs(&mut item.runs);
+                    }
+                }
+                DocumentElement::Table { table } => {
+                    if let Some(title) = &mut table.metadata.title {
+                        count += redact(title);
+                    }
+                    for cell in table
+                        .headers
+                        .iter_mut()
+                        .chain(table.rows.iter_mut().flatten())
+                    {
+                        let found = if cell.runs.is_empty() {
+                            redact(&mut cell.content)
+                        } else {
+                            let mut runs = std::mem::take(&mut cell.runs);
+                            let found = self.redact_runs(&mut runs);
+                            cell.set_runs(runs);
+                            found
+                        };
+                        // A masked number no longer sorts or charts as one
+                        if found > 0 {
+                            cell.data_type = CellDataType::Text;
+                            count += found;
+                        }
+                    }
+                }
+                DocumentElement::Image {
+                    description,
+                    caption,
+                    ..
+                } => {
+                    count += redact(description);
+                    if let Some(caption) = caption {
+                        count += redact(caption);
+                    }
+                }
+                DocumentElement::Equation {
+                    latex, fallback, ..
+                } => {
+                    count += redact(latex);
+                    // The same matches again, as the equation reads in text
+                    redact(fallback);
+                }
+                DocumentElement::CodeBlock { code } => count += redact(code),
+                DocumentElement::PageBreak { .. }
+                | DocumentElement::HorizontalRule
+                | DocumentElement::FileBoundary { .. } => {}
+            }
+        }
+        count
+    }
+}
+
+/// Findings across a document, for `doxx scan`
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanReport {
+    pub source: String,
+    /// The rules run, in the order they claim overlapping text
+    pub rules: Vec<String>,
+    pub findings: Vec<ScanFinding>,
+}
+
+impl ScanReport {
+    /// A plain-text report: each finding's place, rule and match, with its
+    /// context below, then the count for each rule
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Scan of {} ({})",
+            self.source,
+            self.rules.join(", ")
+        )];
+        lines.push(String::new());
+        if self.findings.is_empty() {
+            lines.push("No findings".to_string());
+            return lines;
+        }
+        for finding in &self.findings {
+            lines.push(format!(
+                "{} {}: {}",
+                finding.location(),
+                finding.rule,
+                finding.matched
+            ));
+            lines.push(format!("    {}", finding.context));
+        }
+        lines.push(String::new());
+        let counts: Vec<String> = self
+            .rules
+            .iter()
+            .filter_map(|rule| {
+                let count = self
+                    .findings
+                    .iter()
+                    .filter(|finding| finding.rule == *rule)
+                    .count();
+                (count > 0).then(|| format!("{count} {rule}"))
+            })
+            .collect();
+        let noun = if self.findings.len() == 1 {
+            "finding"
+        } else {
+            "findings"
+        };
+        lines.push(format!(
+            "{} {noun}: {}",
+            self.findings.len(),
+            counts.join(", ")
+        ));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(rule: &str, text: &str) -> Vec<String> {
+        let rule = ScanRule::builtin(rule).unwrap();
+        rule.pattern
+            .find_iter(text)
+            .filter(|found| rule.accepts(found.as_str()))
+            .map(|found| found.as_str().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_rules_check_what_they_match() {
+        assert_eq!(
+            found(
+                "credit-card",
+                "Visa 4111 1111 1111 1111, not 4111 1111 1111 1112"
+            ),
+            ["4111 1111 1111 1111"]
+        );
+        assert_eq!(
+            found(
+                "ssn",
+                "SSN 123-45-6789; 000-12-3456 and 987-65-4321 are never issued"
+            ),
+            ["123-45-6789"]
+        );
+        assert_eq!(
+            found(
+                "phone",
+                "Call (555) 010-4477 or +44 20 7946 0958, not 2024-01-15"
+            ),
+            ["(555) 010-4477", "+44 20 7946 0958"]
+        );
+        assert_eq!(
+            found("email", "Write to jane.doe@example.co.uk."),
+            ["jane.doe@example.co.uk"]
+        );
+    }
+
+    #[test]
+    fn test_redaction_spans_runs() {
+        let run = |text: &str, bold| FormattedRun {
+            text: text.to_string(),
+            formatting: crate::document::TextFormatting {
+                bold,
+                ..Default::default()
+            },
+        };
+        let mut runs = vec![
+            run("Call (555) ", false),
+            run("010-4477", true),
+            run(" or write to ", false),
+            run("jo@example.com", true),
+        ];
+        let redactor = Redactor {
+            rules: resolve_rules(&["pii".to_string()], &[]).unwrap(),
+            style: Redaction::Placeholder,
+        };
+        assert_eq!(redactor.redact_runs(&mut runs), 2);
+        let texts: Vec<(&str, bool)> = runs
+            .iter()
+            .map(|run| (run.text.as_str(), run.formatting.bold))
+            .collect();
+        // The number starts in the first run, which takes its mask
+        assert_eq!(
+            texts,
+            [
+                ("Call [PHONE]", false),
+                (" or write to ", false),
+                ("[EMAIL]", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context_is_cut_at_characters() {
+        let text = "Please find my résumé attached — reach me at jane@example.com any time after lunch on Friday.";
+        let start = text.find("jane").unwrap();
+        let end = start + "jane@example.com".len();
+        assert_eq!(
+            context(text, start..end),
+            "…résumé attached — reach me at jane@example.com any time after lunch on Frida…"
+        );
+    }
+}
@@ -0,0 +1,817 @@
+//! Spellchecking against Hunspell dictionaries (`--spellcheck`, `doxx lint --spelling`)
+//!
+//! Reads the `.aff` and `.dic` pairs Hunspell, LibreOffice and most Linux
+//! distributions ship, and accepts a word that's in the dictionary or made
+//! from an entry by its prefix and suffix rules, two suffixes deep. Compound
+//! words, suggestions and morphology aren't supported, so a word the
+//! dictionary only builds by compounding is reported. A dictionary is read
+//! the first time its language is checked and kept from then on.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::models::*;
+
+/// Language checked when neither the document nor the configuration names one
+pub const DEFAULT_LANGUAGE: &str = "en-US";
+
+/// An affix flag, whichever way the dictionary writes them
+type Flag = u32;
+
+/// How flags are written after a word's slash (`FLAG`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum FlagType {
+    /// One character each
+    #[default]
+    Single,
+    /// Two characters each
+    Long,
+    /// Numbers separated by commas
+    Numeric,
+}
+
+/// One position of an affix condition
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Pattern {
+    Any,
+    Char(char),
+    Class { chars: Vec<char>, negated: bool },
+}
+
+impl Pattern {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Char(expected) => *expected == c,
+            Self::Class { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+/// A prefix or suffix rule, found by the text it adds
+#[derive(Debug, Clone)]
+struct AffixRule {
+    flag: Flag,
+    /// Whether it combines with affixes of the other kind
+    cross_product: bool,
+    /// Taken off the stem before the affix is added
+    strip: String,
+    /// What the stem must start (prefixes) or end (suffixes) with
+    condition: Vec<Pattern>,
+    /// Flags of further affixes allowed after this one
+    continuation: Vec<Flag>,
+}
+
+/// A Hunspell dictionary: its words and the affix rules that inflect them
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    /// Each word's flags, once for every entry of it
+    words: HashMap<String, Vec<Vec<Flag>>>,
+    prefixes: HashMap<String, Vec<AffixRule>>,
+    suffixes: HashMap<String, Vec<AffixRule>>,
+    /// Marks entries that are wrong however they're inflected
+    forbidden: Option<Flag>,
+    /// Marks entries and affixes that are only words with another affix
+    need_affix: Option<Flag>,
+    /// Characters left out before checking, such as Hebrew vowel points
+    ignore: Vec<char>,
+}
+
+impl Dictionary {
+    /// Read the dictionary at `path`, given with or without its extension:
+    /// `/usr/share/hunspell/en_US` reads `en_US.aff` and `en_US.dic`
+    pub fn load(path: &Path) -> Result<Self> {
+        let aff_path = path.with_extension("aff");
+        let dic_path = path.with_extension("dic");
+        let aff = std::fs::read(&aff_path)
+            .with_context(|| format!("Failed to read {}", aff_path.display()))?;
+        let dic = std::fs::read(&dic_path)
+            .with_context(|| format!("Failed to read {}", dic_path.display()))?;
+        let encoding = String::from_utf8_lossy(&aff)
+            .lines()
+            .find_map(|line| line.strip_prefix("SET "))
+            .map(|name| name.trim().to_ascii_uppercase())
+            .unwrap_or_else(|| "ISO8859-1".to_string());
+        let aff = decode(&aff, &encoding)
+            .with_context(|| format!("Can't read {}", aff_path.display()))?;
+        let dic = decode(&dic, &encoding)
+            .with_context(|| format!("Can't read {}", dic_path.display()))?;
+        Ok(Self::parse(&aff, &dic))
+    }
+
+    /// A dictionary from the text of its `.aff` and `.dic` files
+    pub fn parse(aff: &str, dic: &str) -> Self {
+        let mut dictionary = Self::default();
+        let directive = |name: &str| {
+            aff.lines().find_map(|line| {
+                let mut fields = line.split_whitespace();
+                (fields.next() == Some(name))
+                    .then(|| fields.next())
+                    .flatten()
+            })
+        };
+        let flag_type = match directive("FLAG") {
+            Some("long") => FlagType::Long,
+            Some("num") => FlagType::Numeric,
+            _ => FlagType::Single,
+        };
+        // Flag sets numbered from 1 (`AF`), which words and affixes refer to
+        let aliases: Vec<Vec<Flag>> = aff
+            .lines()
+            .filter_map(|line| line.strip_prefix("AF "))
+            .map(|rest| rest.split_whitespace().next().unwrap_or_default())
+            .skip(1)
+            .map(|flags| parse_flags(flags, flag_type))
+            .collect();
+        let flags = |text: &str| match aliases.is_empty() {
+            true => parse_flags(text, flag_type),
+            false => text
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| aliases.get(number.checked_sub(1)?))
+                .cloned()
+                .unwrap_or_default(),
+        };
+        let single_flag = |name: &str| directive(name).and_then(|text| first_flag(text, flag_type));
+        dictionary.forbidden = single_flag("FORBIDDENWORD");
+        dictionary.need_affix = single_flag("NEEDAFFIX").or_else(|| single_flag("PSEUDOROOT"));
+        dictionary.ignore = directive("IGNORE")
+            .map(|chars| chars.chars().collect())
+            .unwrap_or_default();
+
+        // Rules still to come under the last PFX or SFX header
+        let mut pending = 0;
+        let mut cross_product = false;
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (kind, flag) = match fields.as_slice() {
+                [kind @ ("PFX" | "SFX"), flag, ..] => (*kind, first_flag(flag, flag_type)),
+                _ => continue,
+            };
+            let Some(flag) = flag else { continue };
+            if pending == 0 {
+                // A header: `SFX A Y 3`
+                cross_product = fields.get(2) == Some(&"Y");
+                pending = fields.get(3).and_then(|n| n.parse().ok()).unwrap_or(0);
+                continue;
+            }
+            pending -= 1;
+            // A rule: `SFX A y ied [^aeiou]y`
+            let Some(&strip) = fields.get(2) else {
+                continue;
+            };
+            let Some(&add) = fields.get(3) else { continue };
+            let (add, continuation) = match add.split_once('/') {
+                Some((add, continuation)) => (add, flags(continuation)),
+                None => (add, Vec::new()),
+            };
+            let zero = |text: &str| {
+                if text == "0" {
+                    String::new()
+                } else {
+                    text.to_string()
+                }
+            };
+            let rule = AffixRule {
+                flag,
+                cross_product,
+                strip: zero(strip),
+                condition: parse_condition(fields.get(4).copied().unwrap_or(".")),
+                continuation,
+            };
+            let rules = match kind {
+                "PFX" => &mut dictionary.prefixes,
+                _ => &mut dictionary.suffixes,
+            };
+            rules.entry(zero(add)).or_default().push(rule);
+        }
+
+        for line in dic
+            .lines()
+            .skip_while(|line| line.trim().is_empty())
+            .skip(1)
+        {
+            // Morphological fields follow the word after a tab or space
+            let Some(entry) = line.split_whitespace().next() else {
+                continue;
+            };
+            let (word, word_flags) = split_entry(entry);
+            let word_flags = word_flags.map(flags).unwrap_or_default();
+            dictionary.words.entry(word).or_default().push(word_flags);
+        }
+        dictionary
+    }
+
+    /// Whether the dictionary accepts `word`
+    ///
+    /// A capitalised word is also accepted in lower case, as at the start of
+    /// a sentence, and a word in capitals in lower case or capitalised.
+    pub fn check(&self, word: &str) -> bool {
+        let word: String = word
+            .chars()
+            .filter(|c| !self.ignore.contains(c))
+            .map(|c| if c == '’' { '\'' } else { c })
+            .collect();
+        if self.check_exact(&word) {
+            return true;
+        }
+        let mut chars = word.chars();
+        let Some(first) = chars.next() else {
+            return true;
+        };
+        let rest = chars.as_str();
+        let lower = word.to_lowercase();
+        if !first.is_uppercase() || lower == word {
+            return false;
+        }
+        if !rest.chars().any(char::is_uppercase) {
+            return self.check_exact(&lower);
+        }
+        if !rest.chars().any(char::is_lowercase) {
+            let capitalized: String = first
+                .to_uppercase()
+                .chain(rest.to_lowercase().chars())
+                .collect();
+            return self.check_exact(&lower) || self.check_exact(&capitalized);
+        }
+        false
+    }
+
+    fn check_exact(&self, word: &str) -> bool {
+        if self.entries(word).any(|flags| self.is_forbidden(flags)) {
+            return false;
+        }
+        self.entries(word)
+            .any(|flags| self.need_affix.is_none_or(|flag| !flags.contains(&flag)))
+            || self.prefixed(word)
+            || self.suffixed(word)
+    }
+
+    fn entries<'a>(&'a self, word: &str) -> impl Iterator<Item = &'a Vec<Flag>> + Clone {
+        self.words.get(word).into_iter().flatten()
+    }
+
+    fn is_forbidden(&self, flags: &[Flag]) -> bool {
+        self.forbidden.is_some_and(|flag| flags.contains(&flag))
+    }
+
+    /// Whether an entry of `stem` carries all of `flags`
+    fn has_flags(&self, stem: &str, flags: &[Flag]) -> bool {
+        self.entries(stem)
+            .any(|entry| !self.is_forbidden(entry) && flags.iter().all(|flag| entry.contains(flag)))
+    }
+
+    /// Whether the affix leaves a word on its own, rather than needing another
+    fn completes(&self, rule: &AffixRule) -> bool {
+        self.need_affix
+            .is_none_or(|flag| !rule.continuation.contains(&flag))
+    }
+
+    fn prefixed(&self, word: &str) -> bool {
+        self.strip_prefixes(word, |stem, prefix| {
+            self.completes(prefix) && self.has_flags(stem, &[prefix.flag])
+        })
+    }
+
+    fn suffixed(&self, word: &str) -> bool {
+        self.strip_suffixes(word, |stem, suffix| {
+            self.completes(suffix) && self.has_flags(stem, &[suffix.flag])
+                // A prefix as well: `un` + `do` + `ing`
+                || suffix.cross_product
+                    && self.strip_prefixes(stem, |root, prefix| {
+                        prefix.cross_product && self.has_flags(root, &[suffix.flag, prefix.flag])
+                    })
+                // Another suffix before it, which allows this one: `-ize` + `-s`
+                || self.strip_suffixes(stem, |root, inner| {
+                    inner.continuation.contains(&suffix.flag) && self.has_flags(root, &[inner.flag])
+                })
+        })
+    }
+
+    /// Try each prefix rule `word` could have been made with, passing the
+    /// word it was made from to `accept`
+    fn strip_prefixes(&self, word: &str, accept: impl Fn(&str, &AffixRule) -> bool) -> bool {
+        boundaries(word).any(|at| {
+            let (add, rest) = word.split_at(at);
+            self.prefixes.get(add).into_iter().flatten().any(|rule| {
+                let stem = format!("{}{rest}", rule.strip);
+                !stem.is_empty() && starts_with(&stem, &rule.condition) && accept(&stem, rule)
+            })
+        })
+    }
+
+    /// Try each suffix rule `word` could have been made with, passing the
+    /// word it was made from to `accept`
+    fn strip_suffixes(&self, word: &str, accept: impl Fn(&str, &AffixRule) -> bool) -> bool {
+        boundaries(word).any(|at| {
+            let (rest, add) = word.split_at(at);
+            self.suffixes.get(add).into_iter().flatten().any(|rule| {
+                let stem = format!("{rest}{}", rule.strip);
+                !stem.is_empty() && ends_with(&stem, &rule.condition) && accept(&stem, rule)
+            })
+        })
+    }
+}
+
+/// Every char boundary in `text`, both ends included
+fn boundaries(text: &str) -> impl Iterator<Item = usize> + '_ {
+    text.char_indices()
+        .map(|(at, _)| at)
+        .chain(std::iter::once(text.len()))
+}
+
+fn starts_with(stem: &str, condition: &[Pattern]) -> bool {
+    let mut chars = stem.chars();
+    condition
+        .iter()
+        .all(|pattern| chars.next().is_some_and(|c| pattern.matches(c)))
+}
+
+fn ends_with(stem: &str, condition: &[Pattern]) -> bool {
+    let mut chars = stem.chars().rev();
+    condition
+        .iter()
+        .rev()
+        .all(|pattern| chars.next().is_some_and(|c| pattern.matches(c)))
+}
+
+/// An affix condition: characters, `.` for any, and `[…]` or `[^…]` classes;
+/// `.` on its own means no condition
+fn parse_condition(text: &str) -> Vec<Pattern> {
+    if text == "." {
+        return Vec::new();
+    }
+    let mut patterns = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        patterns.push(match c {
+            '.' => Pattern::Any,
+            '[' => {
+                let mut class: Vec<char> = chars.by_ref().take_while(|&c| c != ']').collect();
+                let negated = class.first() == Some(&'^');
+                if negated {
+                    class.remove(0);
+                }
+                Pattern::Class {
+                    chars: class,
+                    negated,
+                }
+            }
+            c => Pattern::Char(c),
+        });
+    }
+    patterns
+}
+
+fn parse_flags(text: &str, flag_type: FlagType) -> Vec<Flag> {
+    match flag_type {
+        FlagType::Single => text.chars().map(Flag::from).collect(),
+        FlagType::Long => {
+            let chars: Vec<char> = text.chars().collect();
+            chars
+                .chunks(2)
+                .map(|pair| pair.iter().fold(0, |flag, &c| (flag << 16) | Flag::from(c)))
+                .collect()
+        }
+        FlagType::Numeric => text
+            .split(',')
+            .filter_map(|number| number.trim().parse().ok())
+            .collect(),
+    }
+}
+
+fn first_flag(text: &str, flag_type: FlagType) -> Option<Flag> {
+    parse_flags(text, flag_type).first().copied()
+}
+
+/// A `.dic` entry split into the word and its flags, at the first slash
+/// that isn't escaped (`1\/2/A` is the word `1/2`)
+fn split_entry(entry: &str) -> (String, Option<&str>) {
+    let mut escaped = false;
+    for (at, c) in entry.char_indices() {
+        match c {
+            '/' if !escaped && at > 0 => {
+                return (entry[..at].replace("\\/", "/"), Some(&entry[at + 1..]));
+            }
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    (entry.replace("\\/", "/"), None)
+}
+
+/// Dictionary text in the encoding its `.aff` names (`SET`)
+fn decode(bytes: &[u8], encoding: &str) -> Result<String> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    Ok(match encoding {
+        "UTF-8" | "UTF8" => String::from_utf8_lossy(bytes).into_owned(),
+        // Latin-9 differs from Latin-1 in eight characters, the euro sign among them
+        "ISO8859-1" | "ISO-8859-1" | "ISO8859-15" | "ISO-8859-15" => bytes
+            .iter()
+            .map(|&byte| match (byte, encoding.ends_with("15")) {
+                (0xA4, true) => '€',
+                (0xA6, true) => 'Š',
+                (0xA8, true) => 'š',
+                (0xB4, true) => 'Ž',
+                (0xB8, true) => 'ž',
+                (0xBC, true) => 'Œ',
+                (0xBD, true) => 'œ',
+                (0xBE, true) => 'Ÿ',
+                (byte, _) => char::from(byte),
+            })
+            .collect(),
+        _ => bail!("dictionaries in {encoding} aren't supported; convert it to UTF-8"),
+    })
+}
+
+/// Where dictionaries are looked for: `$DICPATH`, doxx's own
+/// `dictionaries` directory, then where Hunspell and its users install them
+pub fn dictionary_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::env::var_os("DICPATH")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    if let Some(config) = dirs::config_dir() {
+        dirs.push(config.join("doxx").join("dictionaries"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join("Library").join("Spelling"));
+    }
+    dirs.extend(
+        [
+            "/usr/share/hunspell",
+            "/usr/local/share/hunspell",
+            "/usr/share/myspell",
+            "/usr/share/myspell/dicts",
+            "/Library/Spelling",
+        ]
+        .map(PathBuf::from),
+    );
+    dirs
+}
+
+/// Finds dictionaries by language and keeps each one it reads
+#[derive(Debug, Default)]
+pub struct SpellChecker {
+    /// Dictionaries chosen for a language (`en-GB` → `~/dicts/en_GB`)
+    dictionaries: BTreeMap<String, PathBuf>,
+    /// Directories searched for `en_US.aff` and `en_US.dic` style pairs
+    dirs: Vec<PathBuf>,
+    loaded: HashMap<PathBuf, Dictionary>,
+}
+
+impl SpellChecker {
+    /// A checker using the dictionaries chosen for languages, and otherwise
+    /// looking in `dirs` and then the usual places
+    pub fn new(dictionaries: BTreeMap<String, PathBuf>, dirs: Vec<PathBuf>) -> Self {
+        Self {
+            dictionaries,
+            dirs: dirs.into_iter().chain(dictionary_dirs()).collect(),
+            loaded: HashMap::new(),
+        }
+    }
+
+    /// The dictionary for a language tag: the one chosen for it or its
+    /// language, or else `en_US`, `en` or any `en_*` in the directories
+    pub fn find(&self, tag: &str) -> Option<PathBuf> {
+        let mut subtags = tag.split(['-', '_']);
+        let language = subtags.next().unwrap_or_default().to_ascii_lowercase();
+        let region = subtags.next().map(str::to_ascii_uppercase);
+        let normalize = |tag: &str| tag.replace('_', "-").to_ascii_lowercase();
+        for wanted in [normalize(tag), language.clone()] {
+            let chosen = self
+                .dictionaries
+                .iter()
+                .find(|(tag, _)| normalize(tag) == wanted);
+            if let Some((_, path)) = chosen {
+                return Some(path.clone());
+            }
+        }
+
+        let exists = |path: &Path| {
+            path.with_extension("dic").is_file() && path.with_extension("aff").is_file()
+        };
+        let names: Vec<String> = region
+            .map(|region| format!("{language}_{region}"))
+            .into_iter()
+            .chain([language.clone()])
+            .collect();
+        for dir in &self.dirs {
+            if let Some(path) = names
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| exists(path))
+            {
+                return Some(path);
+            }
+        }
+        // Any region of the language, first by name
+        let prefix = format!("{language}_");
+        self.dirs.iter().find_map(|dir| {
+            let mut found: Vec<PathBuf> = std::fs::read_dir(dir)
+                .ok()?
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension().is_some_and(|extension| extension == "dic")
+                        && path
+                            .file_stem()
+                            .and_then(|stem| stem.to_str())
+                            .is_some_and(|stem| stem.starts_with(&prefix))
+                        && exists(path)
+                })
+                .collect();
+            found.sort();
+            found.into_iter().next().map(|path| path.with_extension(""))
+        })
+    }
+
+    /// The dictionary for a language tag, read the first time it's asked for
+    pub fn dictionary(&mut self, tag: &str) -> Result<&Dictionary> {
+        let Some(path) = self.find(tag) else {
+            bail!(
+                "No {tag} dictionary found; install a Hunspell dictionary or put its \
+                 .aff and .dic files in {}",
+                dirs::config_dir()
+                    .map(|dir| dir.join("doxx").join("dictionaries"))
+                    .unwrap_or_else(|| PathBuf::from("a directory on DICPATH"))
+                    .display()
+            );
+        };
+        if !self.loaded.contains_key(&path) {
+            let dictionary = Dictionary::load(&path)?;
+            self.loaded.insert(path.clone(), dictionary);
+        }
+        Ok(&self.loaded[&path])
+    }
+}
+
+/// The language a document is checked in: the one it declares or is detected
+/// in, else `fallback`, else US English
+pub fn document_language(document: &Document, fallback: Option<&str>) -> String {
+    document
+        .metadata
+        .language
+        .as_ref()
+        .map(|language| language.tag.clone())
+        .or_else(|| fallback.map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string())
+}
+
+/// Whether a word is worth looking up: not a number, code, address,
+/// acronym or single letter, nor in a script read a character at a time
+fn worth_checking(word: &str) -> bool {
+    let mut letters = 0;
+    for c in word.chars() {
+        if c.is_numeric()
+            || matches!(c, '_' | '.' | '@' | ':')
+            || super::words::is_counted_by_character(c)
+        {
+            return false;
+        }
+        letters += usize::from(c.is_alphabetic());
+    }
+    let acronym = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .all(char::is_uppercase);
+    letters > 1 && !acronym
+}
+
+/// Words in `text` the dictionary doesn't know, as `(start, end, word)` with
+/// character offsets
+fn misspelled_words(
+    text: &str,
+    dictionary: &Dictionary,
+    verdicts: &mut HashMap<String, bool>,
+) -> Vec<(usize, usize, String)> {
+    let mut found = Vec::new();
+    let mut chars_before = 0;
+    let mut counted_to = 0;
+    for (at, word) in text.unicode_word_indices() {
+        chars_before += text[counted_to..at].chars().count();
+        counted_to = at;
+        if !worth_checking(word) {
+            continue;
+        }
+        let known = *verdicts
+            .entry(word.to_string())
+            .or_insert_with(|| dictionary.check(word));
+        if !known {
+            let start = chars_before;
+            found.push((start, start + word.chars().count(), word.to_string()));
+        }
+    }
+    found
+}
+
+/// Misspelled words in the document's headings, paragraphs, quotes, list
+/// items and table cells, in reading order; code is left alone
+pub fn check_document(document: &Document, dictionary: &Dictionary) -> Vec<Misspelling> {
+    let mut verdicts = HashMap::new();
+    let mut misspellings = Vec::new();
+    for (element_index, element) in document.elements.iter().enumerate() {
+        let mut check = |item: usize, text: &str| {
+            for (start, end, word) in misspelled_words(text, dictionary, &mut verdicts) {
+                misspellings.push(Misspelling {
+                    element_index,
+                    item,
+                    start,
+                    end,
+                    word,
+                });
+            }
+        };
+        let joined =
+            |runs: &[FormattedRun]| runs.iter().map(|run| run.text.as_str()).collect::<String>();
+        match element {
+            DocumentElement::Heading { text, .. } => check(0, text),
+            DocumentElement::Paragraph { runs, .. } | DocumentElement::BlockQuote { runs } => {
+                check(0, &joined(runs))
+            }
+            DocumentElement::List { items, .. } => {
+                for (item, list_item) in items.iter().enumerate() {
+                    check(item, &joined(&list_item.runs));
+                }
+            }
+            DocumentElement::Table { table } => {
+                for (item, cell) in table
+                    .headers
+                    .iter()
+                    .chain(table.rows.iter().flatten())
+                    .enumerate()
+                {
+                    check(item, &cell.content);
+                }
+            }
+            _ => {}
+        }
+    }
+    misspellings
+}
+
+/// Unknown words in a document, for `doxx lint --spelling`
+#[derive(Debug, Clone, Serialize)]
+pub struct SpellingReport {
+    pub source: String,
+    /// Language tag the document was checked in
+    pub language: String,
+    /// The `.dic` file used
+    pub dictionary: PathBuf,
+    pub misspellings: Vec<Misspelling>,
+}
+
+impl SpellingReport {
+    /// A plain-text report: each unknown word with how often it appears and
+    /// where, by element number as the viewer's inspector shows them
+    pub fn lines(&self, document: &Document) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Spelling in {} ({}, {})",
+            self.source,
+            self.language,
+            self.dictionary.with_extension("dic").display()
+        )];
+        if self.misspellings.is_empty() {
+            lines.push(String::new());
+            lines.push("No unknown words".to_string());
+            return lines;
+        }
+
+        // Each word and where it is, in the order the words first appear
+        let mut words: Vec<(&str, Vec<String>)> = Vec::new();
+        for misspelling in &self.misspellings {
+            let place = match document.elements.get(misspelling.element_index) {
+                Some(DocumentElement::List { .. }) => {
+                    format!(
+                        "#{} item {}",
+                        misspelling.element_index,
+                        misspelling.item + 1
+                    )
+                }
+                Some(DocumentElement::Table { .. }) => {
+                    format!(
+                        "#{} cell {}",
+                        misspelling.element_index,
+                        misspelling.item + 1
+                    )
+                }
+                _ => format!("#{}", misspelling.element_index),
+            };
+            match words.iter_mut().find(|(word, _)| *word == misspelling.word) {
+                Some((_, places)) => places.push(place),
+                None => words.push((&misspelling.word, vec![place])),
+            }
+        }
+        let width = words
+            .iter()
+            .map(|(word, _)| word.chars().count())
+            .max()
+            .unwrap_or(0);
+        lines.push(String::new());
+        for (word, places) in &words {
+            let padding = " ".repeat(width - word.chars().count());
+            lines.push(format!(
+                "  {word}{padding}  {:>3}  {}",
+                places.len(),
+                places.join(", ")
+            ));
+        }
+        lines.push(String::new());
+        let noun = if self.misspellings.len() == 1 {
+            "word"
+        } else {
+            "words"
+        };
+        lines.push(format!(
+            "{} unknown {noun}, {} different",
+            self.misspellings.len(),
+            words.len()
+        ));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "SET UTF-8
+TRY esianrtolcdugmphbyfvkwz
+NEEDAFFIX !
+FORBIDDENWORD *
+
+PFX U Y 1
+PFX U 0 un .
+
+SFX S Y 3
+SFX S y ies [^aeiou]y
+SFX S 0 s [aeiou]y
+SFX S 0 s [^y]
+
+SFX G Y 2
+SFX G e ing e
+SFX G 0 ing [^e]
+
+SFX Z Y 1
+SFX Z 0 ize/S .
+";
+
+    const DIC: &str = "6
+try/S
+do/GU
+make/G
+standard/Z
+colour/!S
+irregardless/*
+";
+
+    #[test]
+    fn test_words_are_made_with_affix_rules() {
+        let dictionary = Dictionary::parse(AFF, DIC);
+        assert_eq!(dictionary.words.len(), 6);
+        for word in [
+            "try",
+            "tries",
+            "doing",
+            "undo",
+            "undoing",
+            "making",
+            "standardizes",
+        ] {
+            assert!(dictionary.check(word), "{word}");
+        }
+        for word in ["trys", "makeing", "unmake", "standardsize", "makes"] {
+            assert!(!dictionary.check(word), "{word}");
+        }
+        // Entries that need an affix, and forbidden ones
+        assert!(!dictionary.check("colour") && dictionary.check("colours"));
+        assert!(!dictionary.check("irregardless"));
+    }
+
+    #[test]
+    fn test_capitals_are_checked_in_lower_case() {
+        let dictionary = Dictionary::parse(AFF, DIC);
+        assert!(dictionary.check("Tries"));
+        assert!(dictionary.check("UNDOING"));
+        assert!(!dictionary.check("tRies"));
+    }
+
+    #[test]
+    fn test_conditions_and_flags_parse() {
+        assert_eq!(
+            parse_condition("[^aeiou]y"),
+            [
+                Pattern::Class {
+                    chars: vec!['a', 'e', 'i', 'o', 'u'],
+                    negated: true
+                },
+                Pattern::Char('y')
+            ]
+        );
+        assert_eq!(parse_flags("AaBb", FlagType::Long).len(), 2);
+        assert_eq!(parse_flags("101,2", FlagType::Numeric), [101, 2]);
+        assert_eq!(split_entry("1\\/2/A"), ("1/2".to_string(), Some("A")));
+    }
+}
@@ -0,0 +1,629 @@
+//! Style usage report for `doxx styles`
+//!
+//! Counts how often each paragraph, character and table style is used in the
+//! body, lists the styles `styles.xml` defines that nothing uses, and flags
+//! paragraphs that are made to look like headings with direct formatting
+//! (bold or a large size) rather than a heading style. Template owners can use
+//! it to clean up documents, and the flagged paragraphs are the ones doxx has
+//! to guess at when it detects headings.
+
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::io::{read_document_xml, validate_docx_extension, validate_docx_package, DocxPackage};
+use super::models::TextFormatting;
+use super::parsing::heading::detect_heading_from_text;
+
+/// Direct run size, in half-points, from which a short paragraph reads as a heading
+const HEADING_SIZE: u32 = 28;
+
+/// What a style applies to (`w:style/@w:type`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StyleKind {
+    Paragraph,
+    Character,
+    Table,
+    Numbering,
+}
+
+impl StyleKind {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "paragraph" => Some(Self::Paragraph),
+            "character" => Some(Self::Character),
+            "table" => Some(Self::Table),
+            "numbering" => Some(Self::Numbering),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Paragraph => "Paragraph styles",
+            Self::Character => "Character styles",
+            Self::Table => "Table styles",
+            Self::Numbering => "Numbering styles",
+        }
+    }
+}
+
+/// One style and how often the body uses it
+#[derive(Debug, Clone, Serialize)]
+pub struct StyleUsage {
+    /// `w:styleId`, which paragraphs, runs and tables refer to
+    pub id: String,
+    /// The name Word shows, if the style is defined
+    pub name: Option<String>,
+    pub kind: StyleKind,
+    /// Paragraphs, runs or tables using it; unstyled paragraphs and tables
+    /// count towards the default style of their kind
+    pub uses: usize,
+    /// Whether `styles.xml` defines it; a reference to an undefined style
+    /// falls back to the default
+    pub defined: bool,
+    /// The default style of its kind
+    pub default: bool,
+    /// Added by the document's author rather than built into Word
+    pub custom: bool,
+}
+
+impl StyleUsage {
+    fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+/// A paragraph that looks like a heading only through direct formatting
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectHeading {
+    /// Position among the body's paragraphs, from 1
+    pub paragraph: usize,
+    pub text: String,
+    /// The paragraph's style, if it sets one
+    pub style: Option<String>,
+    /// The formatting that makes it look like a heading, e.g. "bold, 16pt"
+    pub formatting: String,
+}
+
+/// Style usage across a document's body
+#[derive(Debug, Clone, Serialize)]
+pub struct StyleReport {
+    pub source: String,
+    /// Styles the body uses, most used first within each kind
+    pub used: Vec<StyleUsage>,
+    /// Styles defined in `styles.xml` that the body never uses
+    pub unused: Vec<StyleUsage>,
+    pub direct_headings: Vec<DirectHeading>,
+}
+
+impl StyleReport {
+    /// A plain-text report
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("Styles in {}", self.source)];
+        let mut kind = None;
+        for style in &self.used {
+            if kind != Some(style.kind) {
+                kind = Some(style.kind);
+                lines.push(String::new());
+                lines.push(style.kind.label().to_string());
+            }
+            let mut notes = Vec::new();
+            if style.default {
+                notes.push("default");
+            }
+            if style.custom {
+                notes.push("custom");
+            }
+            if !style.defined {
+                notes.push("undefined");
+            }
+            let notes = match notes.is_empty() {
+                true => String::new(),
+                false => format!(" ({})", notes.join(", ")),
+            };
+            lines.push(format!(
+                "  {:>6}  {}{notes}",
+                style.uses,
+                style.display_name()
+            ));
+        }
+
+        lines.push(String::new());
+        match self.unused.is_empty() {
+            true => lines.push("Unused styles: none".to_string()),
+            false => {
+                let custom = self.unused.iter().filter(|style| style.custom).count();
+                lines.push(format!(
+                    "Unused styles ({}, {custom} custom)",
+                    self.unused.len()
+                ));
+                for style in &self.unused {
+                    let custom = if style.custom { " (custom)" } else { "" };
+                    lines.push(format!("  {}{custom}", style.display_name()));
+                }
+            }
+        }
+
+        lines.push(String::new());
+        match self.direct_headings.is_empty() {
+            true => lines.push("Direct formatting that looks like a heading: none".to_string()),
+            false => {
+                lines.push(format!(
+                    "Direct formatting that looks like a heading ({})",
+                    self.direct_headings.len()
+                ));
+                for heading in &self.direct_headings {
+                    let style = heading.style.as_deref().unwrap_or("no style");
+                    lines.push(format!(
+                        "  ¶{} \"{}\": {}, {style}",
+                        heading.paragraph, heading.text, heading.formatting
+                    ));
+                }
+            }
+        }
+        lines
+    }
+}
+
+/// A style as declared in `styles.xml`
+#[derive(Debug, Clone)]
+struct StyleDefinition {
+    kind: StyleKind,
+    name: Option<String>,
+    based_on: Option<String>,
+    default: bool,
+    custom: bool,
+    outline_level: bool,
+}
+
+/// What one body paragraph's XML says about its style and formatting
+#[derive(Debug, Default)]
+struct ParagraphScan {
+    style: Option<String>,
+    outline_level: bool,
+    in_table: bool,
+    text: String,
+    /// Runs with text, and how many of them are bold or large by direct formatting
+    runs: usize,
+    bold_runs: usize,
+    large_runs: usize,
+    largest: u32,
+}
+
+/// Count style usage in a .docx and flag headings made by direct formatting
+pub fn style_report(file_path: &Path) -> Result<StyleReport> {
+    validate_docx_extension(file_path)?;
+    let package = DocxPackage::open(file_path)?;
+    validate_docx_package(&package)?;
+    let definitions = match package.part("word/styles.xml")? {
+        Some(xml) => scan_styles(&String::from_utf8_lossy(xml))?,
+        None => HashMap::new(),
+    };
+    let (counts, paragraphs) = scan_body(read_document_xml(&package)?)?;
+    Ok(build_report(
+        file_path.display().to_string(),
+        &definitions,
+        counts,
+        &paragraphs,
+    ))
+}
+
+fn build_report(
+    source: String,
+    definitions: &HashMap<String, StyleDefinition>,
+    counts: HashMap<(StyleKind, Option<String>), usize>,
+    paragraphs: &[ParagraphScan],
+) -> StyleReport {
+    // Files that don't mark a default paragraph style still usually define Normal
+    let default_of = |kind: StyleKind| {
+        definitions
+            .iter()
+            .filter(|(_, style)| style.kind == kind && style.default)
+            .map(|(id, _)| id.clone())
+            .min()
+            .or_else(|| {
+                let normal = definitions.get("Normal")?;
+                (kind == StyleKind::Paragraph && normal.kind == kind).then(|| "Normal".to_string())
+            })
+    };
+
+    // Unstyled paragraphs and tables, and references to undefined styles, use the default
+    let mut uses: HashMap<(StyleKind, String), (usize, bool)> = HashMap::new();
+    for ((kind, id), count) in counts {
+        let (id, defined) = match id {
+            Some(id) if definitions.contains_key(&id) => (id, true),
+            Some(id) => (id, false),
+            None => match default_of(kind) {
+                Some(id) => (id, true),
+                None => continue,
+            },
+        };
+        uses.entry((kind, id)).or_insert((0, defined)).0 += count;
+    }
+
+    let usage = |kind: StyleKind, id: &str, uses: usize, defined: bool| {
+        let definition = definitions.get(id);
+        StyleUsage {
+            id: id.to_string(),
+            name: definition.and_then(|style| style.name.clone()),
+            kind,
+            uses,
+            defined,
+            default: definition.is_some_and(|style| style.default),
+            custom: definition.is_some_and(|style| style.custom),
+        }
+    };
+    let mut used: Vec<StyleUsage> = uses
+        .iter()
+        .map(|((kind, id), (uses, defined))| usage(*kind, id, *uses, *defined))
+        .collect();
+    used.sort_by(|a, b| {
+        (a.kind, std::cmp::Reverse(a.uses), a.display_name()).cmp(&(
+            b.kind,
+            std::cmp::Reverse(b.uses),
+            b.display_name(),
+        ))
+    });
+
+    // A style is in use if anything is based on a used style's chain too
+    let mut in_use: Vec<&str> = Vec::new();
+    for style in &used {
+        let mut next = Some(style.id.as_str());
+        while let Some(id) = next.take() {
+            if in_use.contains(&id) {
+                break;
+            }
+            in_use.push(id);
+            next = definitions
+                .get(id)
+                .and_then(|style| style.based_on.as_deref());
+        }
+    }
+    let mut unused: Vec<StyleUsage> = definitions
+        .iter()
+        .filter(|(id, _)| !in_use.contains(&id.as_str()))
+        .map(|(id, style)| usage(style.kind, id, 0, true))
+        .collect();
+    unused.sort_by(|a, b| (a.kind, a.display_name()).cmp(&(b.kind, b.display_name())));
+
+    let paragraph_default = default_of(StyleKind::Paragraph);
+    let direct_headings = paragraphs
+        .iter()
+        .enumerate()
+        .filter(|(_, paragraph)| {
+            // Bold header cells are the norm, not headings
+            let style = paragraph.style.as_ref().or(paragraph_default.as_ref());
+            !paragraph.in_table && !paragraph.outline_level && !is_heading_style(definitions, style)
+        })
+        .filter_map(|(i, paragraph)| direct_heading(i + 1, paragraph))
+        .collect();
+
+    StyleReport {
+        source,
+        used,
+        unused,
+        direct_headings,
+    }
+}
+
+/// Whether a paragraph style, or one it's based on, is a heading or title style
+fn is_heading_style(
+    definitions: &HashMap<String, StyleDefinition>,
+    style: Option<&String>,
+) -> bool {
+    let is_heading = |name: &str| {
+        let name = name.to_lowercase();
+        name.starts_with("heading") || name == "title"
+    };
+    let mut seen = Vec::new();
+    let mut next = style.cloned();
+    while let Some(id) = next.take() {
+        if seen.contains(&id) {
+            break;
+        }
+        let definition = definitions.get(&id);
+        if is_heading(&id)
+            || definition.is_some_and(|style| {
+                style.outline_level || style.name.as_deref().is_some_and(is_heading)
+            })
+        {
+            return true;
+        }
+        next = definition.and_then(|style| style.based_on.clone());
+        seen.push(id);
+    }
+    false
+}
+
+/// A paragraph whose every run is bold or large, if its text reads like a heading
+fn direct_heading(paragraph: usize, scan: &ParagraphScan) -> Option<DirectHeading> {
+    let bold = scan.runs > 0 && scan.bold_runs == scan.runs;
+    let large = scan.runs > 0 && scan.large_runs == scan.runs;
+    if !bold && !large {
+        return None;
+    }
+    let text = scan.text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let formatting = TextFormatting {
+        bold: true,
+        ..Default::default()
+    };
+    detect_heading_from_text(&text, &formatting)?;
+
+    let mut described = Vec::new();
+    if bold {
+        described.push("bold".to_string());
+    }
+    if large {
+        described.push(format!("{}pt", scan.largest as f32 / 2.0));
+    }
+    Some(DirectHeading {
+        paragraph,
+        text,
+        style: scan.style.clone(),
+        formatting: described.join(", "),
+    })
+}
+
+fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == name)
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+/// Whether an on/off property such as `w:b` is on
+fn is_on(e: &BytesStart) -> bool {
+    !matches!(attr(e, b"w:val").as_deref(), Some("0" | "false" | "off"))
+}
+
+/// Styles by ID
+fn scan_styles(styles_xml: &str) -> Result<HashMap<String, StyleDefinition>> {
+    let mut reader = Reader::from_str(styles_xml);
+    let mut styles = HashMap::new();
+    let mut current: Option<(String, StyleDefinition)> = None;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.name().as_ref() == b"w:style" => {
+                let kind = attr(&e, b"w:type").and_then(|kind| StyleKind::parse(&kind));
+                if let (Some(kind), Some(id)) = (kind, attr(&e, b"w:styleId")) {
+                    let definition = StyleDefinition {
+                        kind,
+                        name: None,
+                        based_on: None,
+                        default: attr(&e, b"w:default").as_deref() == Some("1"),
+                        custom: attr(&e, b"w:customStyle").as_deref() == Some("1"),
+                        outline_level: false,
+                    };
+                    current = Some((id, definition));
+                }
+            }
+            Event::Start(e) | Event::Empty(e) => {
+                if let Some((_, style)) = &mut current {
+                    match e.name().as_ref() {
+                        b"w:name" => style.name = attr(&e, b"w:val"),
+                        b"w:basedOn" => style.based_on = attr(&e, b"w:val"),
+                        // Level 9 is body text
+                        b"w:outlineLvl" => {
+                            style.outline_level = attr(&e, b"w:val").as_deref() != Some("9")
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Event::End(e) if e.name().as_ref() == b"w:style" => {
+                if let Some((id, style)) = current.take() {
+                    styles.insert(id, style);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(styles)
+}
+
+/// Style references counted by kind (`None` for unstyled paragraphs and
+/// tables), and what each body paragraph, including those in tables, looks like
+#[allow(clippy::type_complexity)]
+fn scan_body(
+    document_xml: &str,
+) -> Result<(
+    HashMap<(StyleKind, Option<String>), usize>,
+    Vec<ParagraphScan>,
+)> {
+    let mut reader = Reader::from_str(document_xml);
+    let mut counts: HashMap<(StyleKind, Option<String>), usize> = HashMap::new();
+    let mut paragraphs = Vec::new();
+    // Open paragraphs and tables; nested ones (in text boxes) sit above their parents
+    let mut open_paragraphs: Vec<ParagraphScan> = Vec::new();
+    let mut open_tables: Vec<Option<String>> = Vec::new();
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    // The run being read: whether it has text, is bold, and its size
+    let mut run: Option<(bool, bool, Option<u32>)> = None;
+    let mut in_text = false;
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(e) | Event::Empty(e) => {
+                let empty = matches!(event, Event::Empty(_));
+                let name = e.name().as_ref().to_vec();
+                let parent = stack.last().map(Vec::as_slice);
+                match name.as_slice() {
+                    b"w:p" if empty => {
+                        *counts.entry((StyleKind::Paragraph, None)).or_default() += 1;
+                        paragraphs.push(ParagraphScan::default());
+                    }
+                    b"w:p" => open_paragraphs.push(ParagraphScan {
+                        in_table: !open_tables.is_empty(),
+                        ..Default::default()
+                    }),
+                    b"w:tbl" if !empty => open_tables.push(None),
+                    b"w:tblStyle" if parent == Some(b"w:tblPr") => {
+                        if let Some(table) = open_tables.last_mut() {
+                            *table = attr(e, b"w:val");
+                        }
+                    }
+                    b"w:pStyle" if parent == Some(b"w:pPr") => {
+                        if let Some(paragraph) = open_paragraphs.last_mut() {
+                            paragraph.style = attr(e, b"w:val");
+                        }
+                    }
+                    b"w:outlineLvl" if parent == Some(b"w:pPr") => {
+                        if let Some(paragraph) = open_paragraphs.last_mut() {
+                            paragraph.outline_level = attr(e, b"w:val").as_deref() != Some("9");
+                        }
+                    }
+                    b"w:r" if !empty => run = Some((false, false, None)),
+                    b"w:rStyle" if parent == Some(b"w:rPr") => {
+                        if let Some(id) = attr(e, b"w:val") {
+                            *counts.entry((StyleKind::Character, Some(id))).or_default() += 1;
+                        }
+                    }
+                    b"w:b" if parent == Some(b"w:rPr") => {
+                        if let Some(run) = &mut run {
+                            run.1 = is_on(e);
+                        }
+                    }
+                    b"w:sz" if parent == Some(b"w:rPr") => {
+                        if let Some(run) = &mut run {
+                            run.2 = attr(e, b"w:val").and_then(|size| size.parse().ok());
+                        }
+                    }
+                    b"w:t" => in_text = !empty,
+                    b"w:tab" if run.is_some() => {
+                        if let Some(paragraph) = open_paragraphs.last_mut() {
+                            paragraph.text.push(' ');
+                        }
+                    }
+                    _ => {}
+                }
+                if !empty {
+                    stack.push(name);
+                }
+            }
+            Event::End(e) => {
+                stack.pop();
+                match e.name().as_ref() {
+                    b"w:t" => in_text = false,
+                    b"w:r" => {
+                        if let (Some((true, bold, size)), Some(paragraph)) =
+                            (run.take(), open_paragraphs.last_mut())
+                        {
+                            paragraph.runs += 1;
+                            paragraph.bold_runs += bold as usize;
+                            if let Some(size) = size {
+                                paragraph.largest = paragraph.largest.max(size);
+                                paragraph.large_runs += (size >= HEADING_SIZE) as usize;
+                            }
+                        }
+                    }
+                    b"w:p" => {
+                        if let Some(paragraph) = open_paragraphs.pop() {
+                            *counts
+                                .entry((StyleKind::Paragraph, paragraph.style.clone()))
+                                .or_default() += 1;
+                            paragraphs.push(paragraph);
+                        }
+                    }
+                    b"w:tbl" => {
+                        if let Some(style) = open_tables.pop() {
+                            *counts.entry((StyleKind::Table, style)).or_default() += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(text) if in_text => {
+                let text = text.unescape()?;
+                if let (Some(run), Some(paragraph)) = (&mut run, open_paragraphs.last_mut()) {
+                    run.0 |= !text.trim().is_empty();
+                    paragraph.text.push_str(&text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok((counts, paragraphs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STYLES: &str = r#"<w:styles>
+        <w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+        <w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/></w:style>
+        <w:style w:type="paragraph" w:customStyle="1" w:styleId="Chapter"><w:name w:val="Chapter"/><w:pPr><w:outlineLvl w:val="0"/></w:pPr></w:style>
+        <w:style w:type="paragraph" w:customStyle="1" w:styleId="OldQuote"><w:name w:val="Old Quote"/></w:style>
+        <w:style w:type="character" w:styleId="Strong"><w:name w:val="Strong"/></w:style>
+        <w:style w:type="table" w:default="1" w:styleId="TableNormal"><w:name w:val="Normal Table"/></w:style>
+    </w:styles>"#;
+
+    #[test]
+    fn test_usage_counts_defaults_and_direct_headings() {
+        let body = r#"<w:document><w:body>
+            <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:rPr><w:b/></w:rPr><w:t>Styled Heading</w:t></w:r></w:p>
+            <w:p><w:r><w:rPr><w:b/></w:rPr><w:t>Quarterly Results</w:t></w:r></w:p>
+            <w:p><w:r><w:rPr><w:sz w:val="32"/></w:rPr><w:t>Regional Summary</w:t></w:r></w:p>
+            <w:p><w:r><w:rPr><w:b w:val="0"/></w:rPr><w:t>Plain words here.</w:t></w:r><w:r><w:rPr><w:rStyle w:val="Strong"/></w:rPr><w:t>Strong</w:t></w:r></w:p>
+            <w:p><w:pPr><w:pStyle w:val="Chapter"/></w:pPr><w:r><w:rPr><w:b/></w:rPr><w:t>Chapter Heading</w:t></w:r></w:p>
+            <w:tbl><w:tr><w:tc><w:p><w:pPr><w:pStyle w:val="Missing"/></w:pPr></w:p></w:tc><w:tc><w:p><w:r><w:rPr><w:b/></w:rPr><w:t>Total Revenue</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+            <w:p/>
+        </w:body></w:document>"#;
+        let definitions = scan_styles(STYLES).unwrap();
+        let (counts, paragraphs) = scan_body(body).unwrap();
+        let report = build_report("test.docx".to_string(), &definitions, counts, &paragraphs);
+
+        let uses = |id: &str| {
+            report
+                .used
+                .iter()
+                .find(|style| style.id == id)
+                .map(|style| (style.uses, style.defined))
+        };
+        assert_eq!(uses("Normal"), Some((5, true)));
+        assert_eq!(uses("Heading1"), Some((1, true)));
+        assert_eq!(uses("Missing"), Some((1, false)));
+        assert_eq!(uses("Strong"), Some((1, true)));
+        assert_eq!(uses("TableNormal"), Some((1, true)));
+        assert_eq!(report.used[0].id, "Normal");
+
+        let unused: Vec<&str> = report
+            .unused
+            .iter()
+            .map(|style| style.id.as_str())
+            .collect();
+        assert_eq!(unused, ["OldQuote"]);
+        assert!(report.unused[0].custom);
+
+        // Neither the heading styles nor the bold table cell are flagged
+        let flagged: Vec<(usize, &str, &str)> = report
+            .direct_headings
+            .iter()
+            .map(|heading| {
+                (
+                    heading.paragraph,
+                    heading.text.as_str(),
+                    heading.formatting.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            flagged,
+            [
+                (2, "Quarterly Results", "bold"),
+                (3, "Regional Summary", "16pt")
+            ]
+        );
+    }
+}
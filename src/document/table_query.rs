@@ -0,0 +1,303 @@
+//! Sorting and filtering table rows
+//!
+//! The viewer's table mode orders rows by a column and narrows them down with
+//! a filter such as `Revenue >= 150000` or `Region contains north`. Cells are
+//! compared by their detected data type: numbers, amounts and percentages by
+//! value, dates by day, and anything else as text, ignoring case.
+
+use anyhow::{bail, Result};
+use std::cmp::Ordering;
+
+use super::models::*;
+use super::parsing::table::parse_number;
+
+/// How a filter compares a cell with its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOp {
+    Contains,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl FilterOp {
+    /// Operators as written; of two starting at the same place the longer
+    /// wins, so `>=` isn't read as `>`
+    const SYMBOLS: [(&'static str, FilterOp); 8] = [
+        (" contains ", FilterOp::Contains),
+        (">=", FilterOp::GreaterOrEqual),
+        ("<=", FilterOp::LessOrEqual),
+        ("!=", FilterOp::NotEqual),
+        ("~", FilterOp::Contains),
+        ("=", FilterOp::Equal),
+        (">", FilterOp::Greater),
+        ("<", FilterOp::Less),
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            FilterOp::Contains => "contains",
+            FilterOp::Equal => "=",
+            FilterOp::NotEqual => "!=",
+            FilterOp::Less => "<",
+            FilterOp::LessOrEqual => "<=",
+            FilterOp::Greater => ">",
+            FilterOp::GreaterOrEqual => ">=",
+        }
+    }
+}
+
+/// A condition on one column of a table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableFilter {
+    pub column: usize,
+    pub op: FilterOp,
+    pub value: String,
+}
+
+impl TableFilter {
+    /// Parse `<column> <operator> <value>`, where the column is a header (or
+    /// the start of one) or a number counting from 1, and the operator is one
+    /// of `contains` (or `~`), `=`, `!=`, `<`, `<=`, `>` and `>=`
+    pub fn parse(expression: &str, table: &TableData) -> Result<Self> {
+        let Some((at, symbol, op)) = FilterOp::SYMBOLS
+            .iter()
+            .filter_map(|&(symbol, op)| {
+                // The symbols are ASCII, so where one matches is a char boundary
+                let at = expression
+                    .as_bytes()
+                    .windows(symbol.len())
+                    .position(|window| window.eq_ignore_ascii_case(symbol.as_bytes()))?;
+                Some((at, symbol, op))
+            })
+            .min_by_key(|&(at, symbol, _)| (at, std::cmp::Reverse(symbol.len())))
+        else {
+            bail!("Expected <column> <operator> <value>, e.g. 'Revenue >= 1000'");
+        };
+        let column = expression[..at].trim();
+        let value = expression[at + symbol.len()..].trim();
+        if column.is_empty() || value.is_empty() {
+            bail!("Expected <column> <operator> <value>, e.g. 'Revenue >= 1000'");
+        }
+
+        Ok(Self {
+            column: find_column(table, column)?,
+            op,
+            value: value.to_string(),
+        })
+    }
+
+    /// Whether a row passes the filter
+    pub fn matches(&self, row: &[TableCell], locale: CellLocale) -> bool {
+        let Some(cell) = row.get(self.column) else {
+            return false;
+        };
+        if self.op == FilterOp::Contains {
+            return cell
+                .content
+                .to_lowercase()
+                .contains(&self.value.to_lowercase());
+        }
+
+        let ordering = match (sort_key(cell, locale), value_key(&self.value, locale)) {
+            (SortKey::Number(a), SortKey::Number(b)) => a.total_cmp(&b),
+            (SortKey::Date(a), SortKey::Date(b)) => a.cmp(&b),
+            _ => compare_text(&cell.content, &self.value),
+        };
+        match self.op {
+            FilterOp::Contains => unreachable!(),
+            FilterOp::Equal => ordering == Ordering::Equal,
+            FilterOp::NotEqual => ordering != Ordering::Equal,
+            FilterOp::Less => ordering == Ordering::Less,
+            FilterOp::LessOrEqual => ordering != Ordering::Greater,
+            FilterOp::Greater => ordering == Ordering::Greater,
+            FilterOp::GreaterOrEqual => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// The rows of `table` that pass `filter`, in the order of `sort` (a column
+/// and whether it's descending), as indices into `table.rows`
+///
+/// Empty cells sort last either way, and rows that compare equal keep their
+/// order in the document.
+pub fn table_rows(
+    table: &TableData,
+    sort: Option<(usize, bool)>,
+    filter: Option<&TableFilter>,
+    locale: CellLocale,
+) -> Vec<usize> {
+    let mut rows: Vec<usize> = (0..table.rows.len())
+        .filter(|&row| filter.is_none_or(|filter| filter.matches(&table.rows[row], locale)))
+        .collect();
+
+    if let Some((column, descending)) = sort {
+        let keys: Vec<SortKey> = table
+            .rows
+            .iter()
+            .map(|row| {
+                row.get(column)
+                    .map_or(SortKey::Empty, |cell| sort_key(cell, locale))
+            })
+            .collect();
+        rows.sort_by(|&a, &b| {
+            let ordering = keys[a].cmp_with(&keys[b]);
+            let empty = |row: usize| matches!(keys[row], SortKey::Empty);
+            if descending && !empty(a) && !empty(b) {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+    rows
+}
+
+/// A copy of `table` with only these rows, in this order
+pub fn table_with_rows(table: &TableData, rows: &[usize]) -> TableData {
+    let rows: Vec<Vec<TableCell>> = rows.iter().map(|&row| table.rows[row].clone()).collect();
+    TableData {
+        headers: table.headers.clone(),
+        metadata: TableMetadata {
+            row_count: rows.len(),
+            ..table.metadata.clone()
+        },
+        rows,
+    }
+}
+
+/// Find a column by number, header, or the start of a header
+fn find_column(table: &TableData, name: &str) -> Result<usize> {
+    let columns = table.headers.len();
+    if let Ok(number) = name.parse::<usize>() {
+        if (1..=columns).contains(&number) {
+            return Ok(number - 1);
+        }
+    }
+
+    let lower = name.to_lowercase();
+    let headers: Vec<String> = table
+        .headers
+        .iter()
+        .map(|cell| cell.content.trim().to_lowercase())
+        .collect();
+    if let Some(column) = headers.iter().position(|header| *header == lower) {
+        return Ok(column);
+    }
+    let prefixed: Vec<usize> = (0..columns)
+        .filter(|&column| headers[column].starts_with(&lower))
+        .collect();
+    match prefixed[..] {
+        [column] => Ok(column),
+        [] => bail!("No column named '{name}'"),
+        _ => bail!("More than one column starts with '{name}'"),
+    }
+}
+
+/// What a cell is compared by
+#[derive(Debug, Clone, PartialEq)]
+enum SortKey {
+    Number(f64),
+    /// Year, month and day
+    Date((i32, u32, u32)),
+    Text(String),
+    Empty,
+}
+
+impl SortKey {
+    /// Numbers before dates before text, and empty cells last
+    fn cmp_with(&self, other: &SortKey) -> Ordering {
+        fn rank(key: &SortKey) -> u8 {
+            match key {
+                SortKey::Number(_) => 0,
+                SortKey::Date(_) => 1,
+                SortKey::Text(_) => 2,
+                SortKey::Empty => 3,
+            }
+        }
+        match (self, other) {
+            (SortKey::Number(a), SortKey::Number(b)) => a.total_cmp(b),
+            (SortKey::Date(a), SortKey::Date(b)) => a.cmp(b),
+            (SortKey::Text(a), SortKey::Text(b)) => compare_text(a, b),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+fn sort_key(cell: &TableCell, locale: CellLocale) -> SortKey {
+    let text = cell.content.trim();
+    let key = match cell.data_type {
+        CellDataType::Empty => Some(SortKey::Empty),
+        CellDataType::Number | CellDataType::Currency | CellDataType::Percentage => {
+            parse_amount(text, locale.numbers).map(SortKey::Number)
+        }
+        CellDataType::Date => parse_date(text, locale.dates).map(SortKey::Date),
+        CellDataType::Text | CellDataType::Boolean => None,
+    };
+    key.unwrap_or_else(|| SortKey::Text(text.to_string()))
+}
+
+/// A filter's value, typed the way the cells are
+fn value_key(value: &str, locale: CellLocale) -> SortKey {
+    if let Some(number) = parse_amount(value, locale.numbers) {
+        SortKey::Number(number)
+    } else if let Some(date) = parse_date(value, locale.dates) {
+        SortKey::Date(date)
+    } else {
+        SortKey::Text(value.to_string())
+    }
+}
+
+/// The value of a number, amount or percentage, with `(…)` for negatives
+pub(crate) fn parse_amount(text: &str, numbers: NumberStyle) -> Option<f64> {
+    const SYMBOLS: [char; 4] = ['$', '\u{20AC}', '\u{00A3}', '%'];
+    let (text, sign) = match text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        Some(inner) => (inner, -1.0),
+        None => (text, 1.0),
+    };
+    let (text, sign) = match text.strip_prefix(['-', '\u{2212}']) {
+        Some(rest) => (rest, -sign),
+        None => (text, sign),
+    };
+    let digits = text.trim_matches(SYMBOLS).trim();
+    parse_number(digits, numbers).map(|number| sign * number)
+}
+
+/// Year, month and day of a date written `y-m-d`, or with the day and month
+/// in the order of `dates` (`m/d/y` in American English, `d.m.y` in German)
+///
+/// A part above 12 can only be a day, so `25/12/2024` and `12/25/2024` work
+/// either way.
+pub(crate) fn parse_date(text: &str, dates: DateOrder) -> Option<(i32, u32, u32)> {
+    let parts: Vec<u32> = text
+        .split(['/', '-', '.'])
+        .map(|part| part.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let &[a, b, c] = &parts[..] else {
+        return None;
+    };
+    let day_first = match dates {
+        DateOrder::DayMonthYear => b <= 12,
+        DateOrder::MonthDayYear | DateOrder::YearMonthDay => a > 12,
+    };
+    let (year, month, day) = if a > 31 || (dates == DateOrder::YearMonthDay && c <= 31) {
+        (a, b, c)
+    } else if day_first {
+        (c, b, a)
+    } else {
+        (c, a, b)
+    };
+    let year = match year {
+        0..=69 => year + 2000,
+        70..=99 => year + 1900,
+        _ => year,
+    };
+    ((1..=12).contains(&month) && (1..=31).contains(&day)).then_some((year as i32, month, day))
+}
+
+fn compare_text(a: &str, b: &str) -> Ordering {
+    a.trim().to_lowercase().cmp(&b.trim().to_lowercase())
+}
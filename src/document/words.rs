@@ -0,0 +1,146 @@
+//! Word counts and reading time
+//!
+//! Words are found by Unicode word segmentation (UAX #29) rather than by
+//! spaces, since Chinese and Japanese are written without them. As in Word's
+//! own count, each Chinese character and kana counts as a word; Korean, which
+//! puts spaces between words, is counted by the word like English.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::models::*;
+use super::query::element_word_counts;
+
+/// Words a minute in English and other languages read a word at a time
+/// (Brysbaert, 2019)
+const WORDS_PER_MINUTE: usize = 238;
+
+/// Characters a minute in Chinese and Japanese (Trauzettel-Klosinski et al., 2012)
+const CHINESE_CHARACTERS_PER_MINUTE: usize = 255;
+const JAPANESE_CHARACTERS_PER_MINUTE: usize = 357;
+
+/// Words in a text, split into those read a word at a time and those read a
+/// character at a time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WordCounts {
+    /// Words separated by spaces or punctuation
+    pub words: usize,
+    /// Chinese characters and kana, each a word of its own
+    pub characters: usize,
+    /// How many of `characters` are kana, which only Japanese writes
+    pub kana: usize,
+}
+
+impl WordCounts {
+    /// Words of either kind, as the document's word count gives them
+    pub fn total(&self) -> usize {
+        self.words + self.characters
+    }
+}
+
+impl std::ops::Add for WordCounts {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            words: self.words + other.words,
+            characters: self.characters + other.characters,
+            kana: self.kana + other.kana,
+        }
+    }
+}
+
+impl std::iter::Sum for WordCounts {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |sum, counts| sum + counts)
+    }
+}
+
+/// Whether a character is a Chinese character (a kanji or hanzi) or an
+/// iteration mark written with them
+fn is_ideograph(c: char) -> bool {
+    matches!(c,
+        '\u{3005}'..='\u{3007}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{3FFFF}')
+}
+
+/// Whether a character is hiragana or katakana, halfwidth forms included
+fn is_kana(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{31F0}'..='\u{31FF}'
+        | '\u{FF66}'..='\u{FF9F}')
+}
+
+#[cfg(feature = "spellcheck")]
+/// Whether a character is read on its own rather than as part of a word
+pub(crate) fn is_counted_by_character(c: char) -> bool {
+    is_ideograph(c) || is_kana(c)
+}
+
+/// Count the words in `text`
+pub fn word_counts(text: &str) -> WordCounts {
+    let mut counts = WordCounts::default();
+    for word in text.unicode_words() {
+        let kana = word.chars().filter(|&c| is_kana(c)).count();
+        let characters = kana + word.chars().filter(|&c| is_ideograph(c)).count();
+        if characters == 0 {
+            counts.words += 1;
+        } else {
+            counts.characters += characters;
+            counts.kana += kana;
+        }
+    }
+    counts
+}
+
+/// Words in `text`, each Chinese character and kana counted as one
+pub fn count_words(text: &str) -> usize {
+    word_counts(text).total()
+}
+
+/// Minutes it takes to read the document, rounded up
+///
+/// Chinese characters are read at Japanese speed in documents declared
+/// Japanese, or with kana among them if no language is declared, and at
+/// Chinese speed otherwise.
+pub fn reading_minutes(document: &Document) -> usize {
+    let counts: WordCounts = document.elements.iter().map(element_word_counts).sum();
+    let japanese = match &document.metadata.language {
+        Some(language) if !language.detected => language.language() == "ja",
+        _ => counts.kana > 0,
+    };
+    let characters_per_minute = match japanese {
+        true => JAPANESE_CHARACTERS_PER_MINUTE,
+        false => CHINESE_CHARACTERS_PER_MINUTE,
+    };
+    let minutes = counts.words as f64 / WORDS_PER_MINUTE as f64
+        + counts.characters as f64 / characters_per_minute as f64;
+    minutes.ceil() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chinese_and_japanese_count_each_character() {
+        assert_eq!(count_words("The quick brown fox."), 4);
+        assert_eq!(
+            word_counts("文档查看器支持中文。"),
+            WordCounts {
+                words: 0,
+                characters: 9,
+                kana: 0
+            }
+        );
+        // Katakana runs are one segment, but each character still counts
+        let counts = word_counts("日本語のテキストです");
+        assert_eq!((counts.characters, counts.kana), (10, 7));
+        assert_eq!(count_words("使用 doxx 查看文档"), 7);
+        assert_eq!(count_words("한국어 문서를 읽습니다"), 3);
+        assert_eq!(count_words("Revenue — $150,000 (up 20%)"), 4);
+    }
+}
@@ -1,533 +1,1227 @@
+//! Office Math (OMML) to LaTeX and Unicode
+//!
+//! Word keeps equations as OMML (Office Math Markup Language). The markup is
+//! read once, event by event, into a tree of nodes, matching elements by their
+//! local name so any namespace prefix will do and attributes may come in any
+//! order; the tree is then written out as LaTeX, as Unicode text for the
+//! terminal, or as its plain text. Structures the converter doesn't know keep
+//! their content, and ones nested deeper than [`MAX_NESTING`] keep their text.
+
 use anyhow::Result;
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use std::borrow::Cow;
 
 /// Represents a mathematical equation extracted from a DOCX file
 #[derive(Debug, Clone)]
 pub struct Equation {
     /// Raw OMML (Office Math Markup Language) XML
     pub omml: String,
+    /// The equation as LaTeX
+    pub latex: String,
     /// Parsed and rendered Unicode representation
     pub unicode: String,
     /// Plain text fallback
     pub fallback: String,
 }
 
-/// OMML element types we need to parse
-#[derive(Debug, Clone)]
-enum OmmlElement {
-    /// Superscript: base^exponent
-    Superscript {
-        base: Box<OmmlElement>,
-        sup: Box<OmmlElement>,
-    },
-    /// Subscript: base_sub
-    Subscript {
-        base: Box<OmmlElement>,
-        sub: Box<OmmlElement>,
-    },
-    /// Fraction: numerator/denominator
-    Fraction {
-        num: Box<OmmlElement>,
-        den: Box<OmmlElement>,
-    },
-    /// N-ary operator (sum, integral, etc.)
-    Nary {
-        operator: String,
-        sub: Option<Box<OmmlElement>>,
-        sup: Option<Box<OmmlElement>>,
-        base: Box<OmmlElement>,
-    },
-    /// Delimiter (parentheses, brackets, etc.)
-    Delimiter { content: Box<OmmlElement> },
-    /// Text run
-    Text(String),
-    /// Sequence of elements
-    Sequence(Vec<OmmlElement>),
-}
-
 impl Equation {
-    /// Parse OMML XML and convert to Unicode representation
+    /// Parse OMML XML and convert it to LaTeX and Unicode
     pub fn from_omml(omml: String) -> Result<Self> {
-        let unicode = parse_omml_to_unicode(&omml)?;
-        let fallback = extract_text_from_omml(&omml);
-
+        let nodes = parse_omml(&omml)?;
         Ok(Equation {
+            latex: to_latex(&nodes),
+            unicode: to_unicode(&nodes),
+            fallback: to_text(&nodes),
             omml,
-            unicode,
-            fallback,
         })
     }
 }
 
-/// Extract plain text from OMML (for fallback display)
-fn extract_text_from_omml(omml: &str) -> String {
-    let mut reader = Reader::from_str(omml);
-    reader.config_mut().trim_text(true);
-
-    let mut text = String::new();
-    let mut buf = Vec::new();
-
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) if e.name().as_ref() == b"m:t" => {
-                // Inside <m:t> tag, capture text
-                if let Ok(Event::Text(e)) = reader.read_event_into(&mut buf) {
-                    text.push_str(&e.unescape().unwrap_or_default());
-                }
-            }
-            Ok(Event::Eof) => break,
-            Err(e) => {
-                eprintln!("Error parsing OMML: {e}");
-                break;
+/// LaTeX and plain text for OMML, or its text twice where there's no LaTeX
+/// to be had; markup that isn't well-formed keeps what text can be read
+pub(crate) fn latex_and_text(omml: &str) -> (String, String) {
+    match parse_omml(omml) {
+        Ok(nodes) => {
+            let text = to_text(&nodes).trim().to_string();
+            match to_latex(&nodes).trim() {
+                "" => (text.clone(), text),
+                latex => (latex.to_string(), text),
             }
-            _ => {}
         }
-        buf.clear();
+        Err(_) => {
+            let text = readable_text(omml).trim().to_string();
+            (text.clone(), text)
+        }
     }
+}
 
-    text
+/// Deepest nesting of OMML structures converted; deeper ones keep their text
+const MAX_NESTING: usize = 64;
+
+/// A math argument: the content of an `m:e`, `m:num`, `m:sub` and the like
+type Arg = Vec<Node>;
+
+/// A node of parsed OMML
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    /// Text of math runs (`m:r`)
+    Text(String),
+    /// Superscript (`m:sSup`)
+    Sup { base: Arg, sup: Arg },
+    /// Subscript (`m:sSub`)
+    Sub { base: Arg, sub: Arg },
+    /// Subscript and superscript (`m:sSubSup`)
+    SubSup { base: Arg, sub: Arg, sup: Arg },
+    /// Scripts before the base (`m:sPre`)
+    PreScript { sub: Arg, sup: Arg, base: Arg },
+    /// Fraction (`m:f`)
+    Fraction {
+        kind: FractionKind,
+        num: Arg,
+        den: Arg,
+    },
+    /// Radical (`m:rad`); a square root has no degree
+    Radical { degree: Arg, base: Arg },
+    /// A big operator, its limits and its operand (`m:nary`)
+    Nary {
+        operator: char,
+        sub: Option<Arg>,
+        sup: Option<Arg>,
+        base: Arg,
+    },
+    /// Arguments between delimiters (`m:d`); `None` is no delimiter
+    Delimiter {
+        open: Option<char>,
+        close: Option<char>,
+        separator: char,
+        items: Vec<Arg>,
+    },
+    /// A function name applied to an argument (`m:func`)
+    Function { name: Arg, argument: Arg },
+    /// Matrix (`m:m`), row by row
+    Matrix { rows: Vec<Vec<Arg>> },
+    /// Equations stacked one above the other (`m:eqArr`), as in cases
+    EquationArray { rows: Vec<Arg> },
+    /// An accent over the base (`m:acc`)
+    Accent { accent: char, base: Arg },
+    /// A line over or under the base (`m:bar`)
+    Bar { over: bool, base: Arg },
+    /// A limit under or over the base (`m:limLow`, `m:limUpp`), as `lim`
+    /// has beneath it
+    Limit { over: bool, base: Arg, limit: Arg },
+}
+
+/// How a fraction is drawn (`m:type`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FractionKind {
+    Bar,
+    NoBar,
+    Linear,
+    Skewed,
 }
 
-/// Convert OMML XML to Unicode mathematical representation
-fn parse_omml_to_unicode(omml: &str) -> Result<String> {
-    let element = parse_omml_element(omml)?;
-    Ok(render_to_unicode(&element))
+/// The children of a structure: its arguments and its properties' values,
+/// by local name and in document order
+#[derive(Default)]
+struct Parts {
+    args: Vec<(Vec<u8>, Arg)>,
+    properties: Vec<(Vec<u8>, Option<String>)>,
 }
 
-/// Parse OMML XML into structured elements
-fn parse_omml_element(xml: &str) -> Result<OmmlElement> {
-    let mut reader = Reader::from_str(xml);
-    reader.config_mut().trim_text(true);
+impl Parts {
+    /// The first argument called `name`, or an empty one
+    fn arg(&mut self, name: &[u8]) -> Arg {
+        self.args
+            .iter_mut()
+            .find(|(arg, _)| arg == name)
+            .map(|(_, nodes)| std::mem::take(nodes))
+            .unwrap_or_default()
+    }
+
+    /// Every argument called `name`
+    fn args(&mut self, name: &[u8]) -> Vec<Arg> {
+        self.args
+            .iter_mut()
+            .filter(|(arg, _)| arg == name)
+            .map(|(_, nodes)| std::mem::take(nodes))
+            .collect()
+    }
+
+    /// A property's `m:val`: `None` when it isn't set, `Some(None)` when it's
+    /// set without a value
+    fn property(&self, name: &[u8]) -> Option<Option<&str>> {
+        self.properties
+            .iter()
+            .find(|(property, _)| property == name)
+            .map(|(_, value)| value.as_deref())
+    }
 
-    let mut buf = Vec::new();
-    let mut elements = Vec::new();
+    /// An on/off property such as `m:degHide`, off unless set
+    fn flag(&self, name: &[u8]) -> bool {
+        self.property(name)
+            .is_some_and(|value| !matches!(value, Some("0" | "off" | "false")))
+    }
+
+    /// A character property such as `m:chr`; set to nothing, it's `None`
+    fn character(&self, name: &[u8], default: char) -> Option<char> {
+        match self.property(name) {
+            None | Some(None) => Some(default),
+            Some(Some(value)) => value.chars().next(),
+        }
+    }
+}
+
+/// Parse OMML markup into its nodes
+fn parse_omml(omml: &str) -> Result<Arg> {
+    let mut reader = Reader::from_str(omml);
+    parse_arg(&mut reader, 0)
+}
 
+/// Nodes up to the end of the element being read, or of the input
+fn parse_arg(reader: &mut Reader<&[u8]>, depth: usize) -> Result<Arg> {
+    let mut nodes = Vec::new();
     loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                let name_ref = e.name();
-                let tag_name = std::str::from_utf8(name_ref.as_ref()).unwrap_or("");
-
-                match tag_name {
-                    "m:sSup" => {
-                        // Parse superscript structure
-                        elements.push(parse_superscript(&mut reader)?);
-                    }
-                    "m:sSub" => {
-                        elements.push(parse_subscript(&mut reader)?);
+        match reader.read_event()? {
+            Event::Start(start) => {
+                let name = start.local_name();
+                match name.as_ref() {
+                    b"t" => {
+                        let text = parse_text(reader)?;
+                        push_text(&mut nodes, &text);
                     }
-                    "m:f" => {
-                        elements.push(parse_fraction(&mut reader)?);
+                    name if name.ends_with(b"Pr") => {
+                        reader.read_to_end(start.name())?;
                     }
-                    "m:nary" => {
-                        elements.push(parse_nary(&mut reader)?);
+                    _ if depth >= MAX_NESTING => {
+                        let text = parse_flat_text(reader)?;
+                        push_text(&mut nodes, &text);
                     }
-                    "m:d" => {
-                        elements.push(parse_delimiter(&mut reader)?);
+                    b"sSup" | b"sSub" | b"sSubSup" | b"sPre" | b"f" | b"rad" | b"nary" | b"d"
+                    | b"func" | b"m" | b"eqArr" | b"acc" | b"bar" | b"limLow" | b"limUpp" => {
+                        let node = parse_structure(reader, &start, depth + 1)?;
+                        nodes.push(node);
                     }
-                    "m:r" => {
-                        elements.push(parse_run(&mut reader)?);
+                    // Runs, boxes and structures not converted keep their content
+                    _ => {
+                        for node in parse_arg(reader, depth + 1)? {
+                            match node {
+                                Node::Text(text) => push_text(&mut nodes, &text),
+                                node => nodes.push(node),
+                            }
+                        }
                     }
-                    _ => {}
                 }
             }
-            Ok(Event::Eof) => break,
-            Err(e) => anyhow::bail!("XML parsing error: {}", e),
+            Event::End(_) | Event::Eof => break,
             _ => {}
         }
-        buf.clear();
     }
+    Ok(nodes)
+}
 
-    if elements.len() == 1 {
-        Ok(elements.into_iter().next().unwrap())
-    } else {
-        Ok(OmmlElement::Sequence(elements))
+/// Text up to the end of an `m:t`
+fn parse_text(reader: &mut Reader<&[u8]>) -> Result<String> {
+    let mut text = String::new();
+    loop {
+        match reader.read_event()? {
+            Event::Text(content) => text.push_str(&content.unescape()?),
+            Event::CData(content) => text.push_str(&String::from_utf8_lossy(&content)),
+            Event::Start(start) => {
+                reader.read_to_end(start.name())?;
+            }
+            Event::End(_) | Event::Eof => break,
+            _ => {}
+        }
     }
+    Ok(text)
 }
 
-/// Parse superscript element
-fn parse_superscript(reader: &mut Reader<&[u8]>) -> Result<OmmlElement> {
-    let mut base = None;
-    let mut sup = None;
-    let mut buf = Vec::new();
-
+/// The text of `m:t` elements up to the end of the element being read,
+/// without descending into its children one call at a time
+fn parse_flat_text(reader: &mut Reader<&[u8]>) -> Result<String> {
+    let mut text = String::new();
+    let mut open = 1;
+    let mut in_text = false;
     loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                let name_ref = e.name();
-                let tag = std::str::from_utf8(name_ref.as_ref()).unwrap_or("");
-                match tag {
-                    "m:e" => {
-                        let content = read_element_content(reader, "m:e")?;
-                        if base.is_none() {
-                            base = Some(parse_omml_element(&content)?);
-                        }
-                    }
-                    "m:sup" => {
-                        let content = read_element_content(reader, "m:sup")?;
-                        sup = Some(parse_omml_element(&content)?);
-                    }
-                    _ => {}
+        match reader.read_event()? {
+            Event::Start(start) => {
+                open += 1;
+                in_text = start.local_name().as_ref() == b"t";
+            }
+            Event::End(_) => {
+                open -= 1;
+                in_text = false;
+                if open == 0 {
+                    break;
                 }
             }
-            Ok(Event::End(ref e)) if e.name().as_ref() == b"m:sSup" => break,
-            Ok(Event::Eof) => break,
-            Err(e) => anyhow::bail!("Superscript parse error: {}", e),
+            Event::Text(content) if in_text => text.push_str(&content.unescape()?),
+            Event::Eof => break,
             _ => {}
         }
-        buf.clear();
     }
+    Ok(text)
+}
 
-    Ok(OmmlElement::Superscript {
-        base: Box::new(base.unwrap_or(OmmlElement::Text(String::new()))),
-        sup: Box::new(sup.unwrap_or(OmmlElement::Text(String::new()))),
-    })
+/// Add text to the end of an argument, joining it to text already there
+fn push_text(nodes: &mut Arg, text: &str) {
+    match nodes.last_mut() {
+        Some(Node::Text(last)) => last.push_str(text),
+        _ if text.is_empty() => {}
+        _ => nodes.push(Node::Text(text.to_string())),
+    }
 }
 
-/// Parse subscript element
-fn parse_subscript(reader: &mut Reader<&[u8]>) -> Result<OmmlElement> {
-    let mut base = None;
-    let mut sub = None;
-    let mut buf = Vec::new();
+/// One of the structures the converter knows, from its start tag on
+fn parse_structure(reader: &mut Reader<&[u8]>, start: &BytesStart, depth: usize) -> Result<Node> {
+    if start.local_name().as_ref() == b"m" {
+        return Ok(Node::Matrix {
+            rows: parse_matrix_rows(reader, depth)?,
+        });
+    }
+    let mut parts = parse_parts(reader, depth)?;
+    let node = match start.local_name().as_ref() {
+        b"sSup" => Node::Sup {
+            base: parts.arg(b"e"),
+            sup: parts.arg(b"sup"),
+        },
+        b"sSub" => Node::Sub {
+            base: parts.arg(b"e"),
+            sub: parts.arg(b"sub"),
+        },
+        b"sSubSup" => Node::SubSup {
+            base: parts.arg(b"e"),
+            sub: parts.arg(b"sub"),
+            sup: parts.arg(b"sup"),
+        },
+        b"sPre" => Node::PreScript {
+            sub: parts.arg(b"sub"),
+            sup: parts.arg(b"sup"),
+            base: parts.arg(b"e"),
+        },
+        b"f" => Node::Fraction {
+            kind: match parts.property(b"type").flatten() {
+                Some("noBar") => FractionKind::NoBar,
+                Some("lin") => FractionKind::Linear,
+                Some("skw") => FractionKind::Skewed,
+                _ => FractionKind::Bar,
+            },
+            num: parts.arg(b"num"),
+            den: parts.arg(b"den"),
+        },
+        b"rad" => {
+            let degree = parts.arg(b"deg");
+            Node::Radical {
+                degree: if parts.flag(b"degHide") {
+                    Vec::new()
+                } else {
+                    degree
+                },
+                base: parts.arg(b"e"),
+            }
+        }
+        b"nary" => {
+            let (sub, sup) = (parts.arg(b"sub"), parts.arg(b"sup"));
+            let limit = |hidden: bool, limit: Arg| Some(limit).filter(|l| !hidden && !l.is_empty());
+            Node::Nary {
+                // Without `m:chr` the operator is an integral
+                operator: parts.character(b"chr", '∫').unwrap_or('∫'),
+                sub: limit(parts.flag(b"subHide"), sub),
+                sup: limit(parts.flag(b"supHide"), sup),
+                base: parts.arg(b"e"),
+            }
+        }
+        b"d" => Node::Delimiter {
+            open: parts.character(b"begChr", '('),
+            close: parts.character(b"endChr", ')'),
+            separator: parts.character(b"sepChr", '|').unwrap_or('|'),
+            items: parts.args(b"e"),
+        },
+        b"eqArr" => Node::EquationArray {
+            rows: parts.args(b"e"),
+        },
+        b"acc" => Node::Accent {
+            // Without `m:chr` the accent is a circumflex
+            accent: parts.character(b"chr", '\u{302}').unwrap_or('\u{302}'),
+            base: parts.arg(b"e"),
+        },
+        b"bar" => Node::Bar {
+            // Without `m:pos` the line is under the base
+            over: parts.property(b"pos").flatten() == Some("top"),
+            base: parts.arg(b"e"),
+        },
+        b"limLow" | b"limUpp" => Node::Limit {
+            over: start.local_name().as_ref() == b"limUpp",
+            base: parts.arg(b"e"),
+            limit: parts.arg(b"lim"),
+        },
+        _ => Node::Function {
+            name: parts.arg(b"fName"),
+            argument: parts.arg(b"e"),
+        },
+    };
+    Ok(node)
+}
 
+/// The cells of each row (`m:mr`) of a matrix, up to its end tag
+fn parse_matrix_rows(reader: &mut Reader<&[u8]>, depth: usize) -> Result<Vec<Vec<Arg>>> {
+    let mut rows = Vec::new();
     loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                let name_ref = e.name();
-                let tag = std::str::from_utf8(name_ref.as_ref()).unwrap_or("");
-                match tag {
-                    "m:e" => {
-                        let content = read_element_content(reader, "m:e")?;
-                        if base.is_none() {
-                            base = Some(parse_omml_element(&content)?);
-                        }
-                    }
-                    "m:sub" => {
-                        let content = read_element_content(reader, "m:sub")?;
-                        sub = Some(parse_omml_element(&content)?);
-                    }
-                    _ => {}
+        match reader.read_event()? {
+            Event::Start(child) if child.local_name().as_ref() == b"mr" => {
+                rows.push(parse_parts(reader, depth)?.args(b"e"));
+            }
+            Event::Start(child) => {
+                reader.read_to_end(child.name())?;
+            }
+            Event::End(_) | Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(rows)
+}
+
+/// A structure's arguments and properties, up to its end tag
+fn parse_parts(reader: &mut Reader<&[u8]>, depth: usize) -> Result<Parts> {
+    let mut parts = Parts::default();
+    loop {
+        match reader.read_event()? {
+            Event::Start(child) => {
+                let name = child.local_name().as_ref().to_vec();
+                if name.ends_with(b"Pr") {
+                    parse_properties(reader, &mut parts)?;
+                } else {
+                    let arg = parse_arg(reader, depth)?;
+                    parts.args.push((name, arg));
+                }
+            }
+            Event::Empty(child) => {
+                let name = child.local_name().as_ref().to_vec();
+                if !name.ends_with(b"Pr") {
+                    parts.args.push((name, Vec::new()));
                 }
             }
-            Ok(Event::End(ref e)) if e.name().as_ref() == b"m:sSub" => break,
-            Ok(Event::Eof) => break,
-            Err(e) => anyhow::bail!("Subscript parse error: {}", e),
+            Event::End(_) | Event::Eof => break,
             _ => {}
         }
-        buf.clear();
     }
+    Ok(parts)
+}
 
-    Ok(OmmlElement::Subscript {
-        base: Box::new(base.unwrap_or(OmmlElement::Text(String::new()))),
-        sub: Box::new(sub.unwrap_or(OmmlElement::Text(String::new()))),
-    })
+/// The values of a property element's children, such as `m:chr` in `m:naryPr`
+fn parse_properties(reader: &mut Reader<&[u8]>, parts: &mut Parts) -> Result<()> {
+    loop {
+        match reader.read_event()? {
+            Event::Empty(property) => {
+                parts.properties.push(property_value(&property)?);
+            }
+            Event::Start(property) => {
+                parts.properties.push(property_value(&property)?);
+                reader.read_to_end(property.name())?;
+            }
+            Event::End(_) | Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(())
 }
 
-/// Parse fraction element
-fn parse_fraction(reader: &mut Reader<&[u8]>) -> Result<OmmlElement> {
-    let mut num = None;
-    let mut den = None;
-    let mut buf = Vec::new();
+/// A property's local name and its `val` attribute, if it has one
+fn property_value(property: &BytesStart) -> Result<(Vec<u8>, Option<String>)> {
+    let mut value = None;
+    for attribute in property.attributes() {
+        let attribute = attribute?;
+        if attribute.key.local_name().as_ref() == b"val" {
+            value = Some(attribute.unescape_value()?.into_owned());
+        }
+    }
+    Ok((property.local_name().as_ref().to_vec(), value))
+}
 
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                let name_ref = e.name();
-                let tag = std::str::from_utf8(name_ref.as_ref()).unwrap_or("");
-                match tag {
-                    "m:num" => {
-                        let content = read_element_content(reader, "m:num")?;
-                        num = Some(parse_omml_element(&content)?);
-                    }
-                    "m:den" => {
-                        let content = read_element_content(reader, "m:den")?;
-                        den = Some(parse_omml_element(&content)?);
-                    }
-                    _ => {}
-                }
+/// The text of markup that didn't parse, as far as it could be read
+fn readable_text(omml: &str) -> String {
+    let mut reader = Reader::from_str(omml);
+    let mut text = String::new();
+    let mut in_text = false;
+    while let Ok(event) = reader.read_event() {
+        match event {
+            Event::Start(start) if start.local_name().as_ref() == b"t" => in_text = true,
+            Event::End(end) if end.local_name().as_ref() == b"t" => in_text = false,
+            Event::Text(content) if in_text => {
+                text.push_str(&content.unescape().unwrap_or_default());
             }
-            Ok(Event::End(ref e)) if e.name().as_ref() == b"m:f" => break,
-            Ok(Event::Eof) => break,
-            Err(e) => anyhow::bail!("Fraction parse error: {}", e),
+            Event::Eof => break,
             _ => {}
         }
-        buf.clear();
     }
+    text
+}
 
-    Ok(OmmlElement::Fraction {
-        num: Box::new(num.unwrap_or(OmmlElement::Text(String::new()))),
-        den: Box::new(den.unwrap_or(OmmlElement::Text(String::new()))),
-    })
+/// The plain text of nodes, as Word would search it
+fn to_text(nodes: &[Node]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(content) => text.push_str(content),
+            Node::Sup { base, sup } => {
+                text.push_str(&to_text(base));
+                text.push_str(&to_text(sup));
+            }
+            Node::Sub { base, sub } => {
+                text.push_str(&to_text(base));
+                text.push_str(&to_text(sub));
+            }
+            Node::SubSup { base, sub, sup } => {
+                for arg in [base, sub, sup] {
+                    text.push_str(&to_text(arg));
+                }
+            }
+            Node::PreScript { sub, sup, base } => {
+                for arg in [sub, sup, base] {
+                    text.push_str(&to_text(arg));
+                }
+            }
+            Node::Fraction { num, den, .. } => {
+                text.push_str(&to_text(num));
+                text.push_str(&to_text(den));
+            }
+            Node::Radical { degree, base } => {
+                text.push_str(&to_text(degree));
+                text.push_str(&to_text(base));
+            }
+            Node::Nary { sub, sup, base, .. } => {
+                for arg in [sub, sup].into_iter().flatten() {
+                    text.push_str(&to_text(arg));
+                }
+                text.push_str(&to_text(base));
+            }
+            Node::Delimiter { items, .. } => {
+                for item in items {
+                    text.push_str(&to_text(item));
+                }
+            }
+            Node::Function { name, argument } => {
+                text.push_str(&to_text(name));
+                text.push_str(&to_text(argument));
+            }
+            Node::Matrix { rows } => {
+                for cell in rows.iter().flatten() {
+                    text.push_str(&to_text(cell));
+                }
+            }
+            Node::EquationArray { rows } => {
+                for row in rows {
+                    text.push_str(&to_text(row));
+                }
+            }
+            Node::Accent { base, .. } | Node::Bar { base, .. } => {
+                text.push_str(&to_text(base));
+            }
+            Node::Limit { base, limit, .. } => {
+                text.push_str(&to_text(base));
+                text.push_str(&to_text(limit));
+            }
+        }
+    }
+    text
 }
 
-/// Parse n-ary operator (sum, integral, product, etc.)
-fn parse_nary(reader: &mut Reader<&[u8]>) -> Result<OmmlElement> {
-    let mut operator = String::from("∑"); // Default to summation
-    let mut sub = None;
-    let mut sup = None;
-    let mut base = None;
-    let mut buf = Vec::new();
+/// Nodes as LaTeX
+fn to_latex(nodes: &[Node]) -> String {
+    let mut latex = String::new();
+    for node in nodes {
+        push_latex(&mut latex, &node_latex(node));
+    }
+    latex
+}
 
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                let name_ref = e.name();
-                let tag = std::str::from_utf8(name_ref.as_ref()).unwrap_or("");
-                match tag {
-                    "m:chr" => {
-                        // Extract operator character from attribute
-                        if let Some(a) = e
-                            .attributes()
-                            .flatten()
-                            .find(|a| a.key.as_ref() == b"m:val")
-                        {
-                            operator = String::from_utf8_lossy(&a.value).to_string();
+/// Append LaTeX, with a space where a control word would otherwise run into
+/// the letters after it (`\pi r`, not `\pir`)
+fn push_latex(latex: &mut String, more: &str) {
+    // Control words are short, so only the end of the LaTeX need be looked at
+    const LONGEST_WORD: usize = 16;
+    let letters = latex
+        .bytes()
+        .rev()
+        .take(LONGEST_WORD + 1)
+        .take_while(u8::is_ascii_alphabetic)
+        .count();
+    let control_word =
+        (1..=LONGEST_WORD).contains(&letters) && latex[..latex.len() - letters].ends_with('\\');
+    if control_word && more.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        latex.push(' ');
+    }
+    latex.push_str(more);
+}
+
+fn node_latex(node: &Node) -> String {
+    match node {
+        Node::Text(text) => text_latex(text),
+        Node::Sup { base, sup } => format!("{}^{{{}}}", base_latex(base), to_latex(sup)),
+        Node::Sub { base, sub } => format!("{}_{{{}}}", base_latex(base), to_latex(sub)),
+        Node::SubSup { base, sub, sup } => format!(
+            "{}_{{{}}}^{{{}}}",
+            base_latex(base),
+            to_latex(sub),
+            to_latex(sup)
+        ),
+        Node::PreScript { sub, sup, base } => format!(
+            "{{}}_{{{}}}^{{{}}}{}",
+            to_latex(sub),
+            to_latex(sup),
+            base_latex(base)
+        ),
+        Node::Fraction { kind, num, den } => {
+            let (num, den) = (to_latex(num), to_latex(den));
+            match kind {
+                FractionKind::Bar => format!("\\frac{{{num}}}{{{den}}}"),
+                FractionKind::NoBar => format!("\\genfrac{{}}{{}}{{0pt}}{{}}{{{num}}}{{{den}}}"),
+                FractionKind::Linear => format!("{}/{}", group(&num), group(&den)),
+                FractionKind::Skewed => format!("{{}}^{{{num}}}/_{{{den}}}"),
+            }
+        }
+        Node::Radical { degree, base } => match to_latex(degree) {
+            degree if degree.is_empty() || degree == "2" => format!("\\sqrt{{{}}}", to_latex(base)),
+            degree => format!("\\sqrt[{degree}]{{{}}}", to_latex(base)),
+        },
+        Node::Nary {
+            operator,
+            sub,
+            sup,
+            base,
+        } => {
+            let mut latex = operator_latex(*operator).into_owned();
+            if let Some(sub) = sub {
+                latex.push_str(&format!("_{{{}}}", to_latex(sub)));
+            }
+            if let Some(sup) = sup {
+                latex.push_str(&format!("^{{{}}}", to_latex(sup)));
+            }
+            let base = to_latex(base);
+            if !base.is_empty() {
+                latex.push(' ');
+                latex.push_str(&base);
+            }
+            latex
+        }
+        Node::Delimiter {
+            open,
+            close,
+            separator,
+            items,
+        } => {
+            // A stack without a bar in parentheses is a binomial coefficient
+            if let ([item], Some('('), Some(')')) = (items.as_slice(), open, close) {
+                if let [Node::Fraction {
+                    kind: FractionKind::NoBar,
+                    num,
+                    den,
+                }] = item.as_slice()
+                {
+                    return format!("\\binom{{{}}}{{{}}}", to_latex(num), to_latex(den));
+                }
+            }
+            // A matrix in brackets is written with the environment for them,
+            // and equations after a lone brace are cases
+            if let [item] = items.as_slice() {
+                match (item.as_slice(), open, close) {
+                    ([Node::Matrix { rows }], _, _) => {
+                        if let Some(environment) = matrix_environment(*open, *close) {
+                            return matrix_latex(rows, environment);
                         }
                     }
-                    "m:sub" => {
-                        let content = read_element_content(reader, "m:sub")?;
-                        sub = Some(parse_omml_element(&content)?);
-                    }
-                    "m:sup" => {
-                        let content = read_element_content(reader, "m:sup")?;
-                        sup = Some(parse_omml_element(&content)?);
-                    }
-                    "m:e" => {
-                        let content = read_element_content(reader, "m:e")?;
-                        base = Some(parse_omml_element(&content)?);
+                    ([Node::EquationArray { rows }], Some('{'), None) => {
+                        return equation_array_latex(rows, "cases");
                     }
                     _ => {}
                 }
             }
-            Ok(Event::End(ref e)) if e.name().as_ref() == b"m:nary" => break,
-            Ok(Event::Eof) => break,
-            Err(e) => anyhow::bail!("Nary parse error: {}", e),
-            _ => {}
+            let mut latex = format!("\\left{}", delimiter_latex(*open));
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    push_latex(&mut latex, &text_latex(&separator.to_string()));
+                }
+                push_latex(&mut latex, &to_latex(item));
+            }
+            push_latex(&mut latex, &format!("\\right{}", delimiter_latex(*close)));
+            latex
+        }
+        Node::Function { name, argument } => {
+            let mut latex = function_name_latex(name);
+            let argument = to_latex(argument);
+            if !argument.is_empty() {
+                latex.push(' ');
+                latex.push_str(&argument);
+            }
+            latex
+        }
+        Node::Matrix { rows } => matrix_latex(rows, "matrix"),
+        Node::EquationArray { rows } => equation_array_latex(rows, "aligned"),
+        Node::Accent { accent, base } => {
+            let base_latex = to_latex(base);
+            let wide = group(&base_latex).starts_with('{');
+            match accent_latex(*accent, wide) {
+                Some(command) => format!("{command}{{{base_latex}}}"),
+                None => format!(
+                    "\\overset{{{}}}{{{base_latex}}}",
+                    text_latex(&accent.to_string())
+                ),
+            }
+        }
+        Node::Bar { over, base } => {
+            let command = if *over { "overline" } else { "underline" };
+            format!("\\{command}{{{}}}", to_latex(base))
+        }
+        Node::Limit { over, base, limit } => {
+            let limit = to_latex(limit);
+            match limit_operator(base) {
+                Some(operator) if *over => format!("{operator}^{{{limit}}}"),
+                Some(operator) => format!("{operator}_{{{limit}}}"),
+                None => {
+                    let command = if *over { "overset" } else { "underset" };
+                    format!("\\{command}{{{limit}}}{{{}}}", to_latex(base))
+                }
+            }
         }
-        buf.clear();
     }
+}
 
-    Ok(OmmlElement::Nary {
-        operator,
-        sub: sub.map(Box::new),
-        sup: sup.map(Box::new),
-        base: Box::new(base.unwrap_or(OmmlElement::Text(String::new()))),
+/// The environment for a matrix between these delimiters, if LaTeX has one
+fn matrix_environment(open: Option<char>, close: Option<char>) -> Option<&'static str> {
+    Some(match (open, close) {
+        (Some('('), Some(')')) => "pmatrix",
+        (Some('['), Some(']')) => "bmatrix",
+        (Some('{'), Some('}')) => "Bmatrix",
+        (Some('|'), Some('|')) => "vmatrix",
+        (Some('‖'), Some('‖')) => "Vmatrix",
+        (None, None) => "matrix",
+        _ => return None,
     })
 }
 
-/// Parse delimiter (parentheses, brackets, etc.)
-fn parse_delimiter(reader: &mut Reader<&[u8]>) -> Result<OmmlElement> {
-    let mut content = None;
-    let mut buf = Vec::new();
+/// A matrix in an environment, cells split by `&` and rows by `\\`
+fn matrix_latex(rows: &[Vec<Arg>], environment: &str) -> String {
+    let rows: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let cells: Vec<String> = row.iter().map(|cell| to_latex(cell)).collect();
+            cells.join(" & ")
+        })
+        .collect();
+    format!(
+        "\\begin{{{environment}}}{}\\end{{{environment}}}",
+        rows.join(" \\\\ ")
+    )
+}
 
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                let name_ref = e.name();
-                let tag = std::str::from_utf8(name_ref.as_ref()).unwrap_or("");
-                if tag == "m:e" {
-                    let xml_content = read_element_content(reader, "m:e")?;
-                    content = Some(parse_omml_element(&xml_content)?);
-                }
-            }
-            Ok(Event::End(ref e)) if e.name().as_ref() == b"m:d" => break,
-            Ok(Event::Eof) => break,
-            Err(e) => anyhow::bail!("Delimiter parse error: {}", e),
-            _ => {}
+/// Stacked equations in an environment, one per row; an `&` in them is the
+/// point Word aligns them at, as it is in LaTeX
+fn equation_array_latex(rows: &[Arg], environment: &str) -> String {
+    let rows: Vec<String> = rows
+        .iter()
+        .map(|row| to_latex(row).replace("\\&", "&"))
+        .collect();
+    format!(
+        "\\begin{{{environment}}}{}\\end{{{environment}}}",
+        rows.join(" \\\\ ")
+    )
+}
+
+/// The LaTeX command for an accent, given as a combining character or its
+/// spacing form; `wide` accents cover more than one character
+fn accent_latex(accent: char, wide: bool) -> Option<&'static str> {
+    Some(match accent {
+        '\u{300}' | '`' => "\\grave",
+        '\u{301}' | '´' => "\\acute",
+        '\u{302}' | '^' | 'ˆ' if wide => "\\widehat",
+        '\u{302}' | '^' | 'ˆ' => "\\hat",
+        '\u{303}' | '~' | '˜' if wide => "\\widetilde",
+        '\u{303}' | '~' | '˜' => "\\tilde",
+        '\u{304}' | '\u{305}' | '¯' | '‾' if wide => "\\overline",
+        '\u{304}' | '\u{305}' | '¯' | '‾' => "\\bar",
+        '\u{306}' | '˘' => "\\breve",
+        '\u{307}' | '˙' => "\\dot",
+        '\u{308}' | '¨' => "\\ddot",
+        '\u{20DB}' => "\\dddot",
+        '\u{30C}' | 'ˇ' => "\\check",
+        '\u{20D7}' | '→' if wide => "\\overrightarrow",
+        '\u{20D7}' | '→' => "\\vec",
+        '\u{20D6}' | '←' => "\\overleftarrow",
+        '\u{20E1}' | '↔' => "\\overleftrightarrow",
+        _ => return None,
+    })
+}
+
+/// The operator a limit belongs to, as `\lim`, when the base is the name of
+/// one LaTeX has a command for
+fn limit_operator(base: &Arg) -> Option<String> {
+    match base.as_slice() {
+        [Node::Text(name)] if LATEX_FUNCTIONS.contains(&name.trim()) => {
+            Some(format!("\\{}", name.trim()))
         }
-        buf.clear();
+        _ => None,
     }
+}
 
-    Ok(OmmlElement::Delimiter {
-        content: Box::new(content.unwrap_or(OmmlElement::Text(String::new()))),
-    })
+/// LaTeX for the base of a script, grouped unless it's a single symbol or
+/// structure
+fn base_latex(base: &Arg) -> String {
+    let latex = to_latex(base);
+    match base.as_slice() {
+        [Node::Text(_)] => group(&latex),
+        [_] => latex,
+        _ => group(&latex),
+    }
 }
 
-/// Parse text run
-fn parse_run(reader: &mut Reader<&[u8]>) -> Result<OmmlElement> {
-    let mut text = String::new();
-    let mut buf = Vec::new();
+/// LaTeX in braces, unless it's one character or one control word
+fn group(latex: &str) -> String {
+    let single = latex.chars().count() == 1
+        || latex
+            .strip_prefix('\\')
+            .is_some_and(|word| !word.is_empty() && word.chars().all(|c| c.is_ascii_alphabetic()));
+    if single {
+        latex.to_string()
+    } else {
+        format!("{{{latex}}}")
+    }
+}
 
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) if e.name().as_ref() == b"m:t" => {
-                if let Ok(Event::Text(e)) = reader.read_event_into(&mut buf) {
-                    text.push_str(&e.unescape().unwrap_or_default());
+/// Math text as LaTeX, with symbols as their commands and LaTeX's special
+/// characters escaped
+fn text_latex(text: &str) -> String {
+    let mut latex = String::new();
+    for c in text.chars() {
+        match symbol_latex(c) {
+            Some(command) => push_latex(&mut latex, command),
+            None => match c {
+                '#' | '$' | '%' | '&' | '_' | '{' | '}' => {
+                    latex.push('\\');
+                    latex.push(c);
                 }
-            }
-            Ok(Event::End(ref e)) if e.name().as_ref() == b"m:r" => break,
-            Ok(Event::Eof) => break,
-            Err(e) => anyhow::bail!("Run parse error: {}", e),
-            _ => {}
+                '\\' => latex.push_str("\\backslash"),
+                // Word writes a true minus sign
+                '−' => latex.push('-'),
+                _ => push_latex(&mut latex, c.encode_utf8(&mut [0; 4])),
+            },
         }
-        buf.clear();
     }
+    latex
+}
 
-    Ok(OmmlElement::Text(text))
+/// The LaTeX command for a symbol
+fn symbol_latex(c: char) -> Option<&'static str> {
+    Some(match c {
+        'α' => "\\alpha",
+        'β' => "\\beta",
+        'γ' => "\\gamma",
+        'δ' => "\\delta",
+        'ε' => "\\varepsilon",
+        'ϵ' => "\\epsilon",
+        'ζ' => "\\zeta",
+        'η' => "\\eta",
+        'θ' => "\\theta",
+        'ϑ' => "\\vartheta",
+        'ι' => "\\iota",
+        'κ' => "\\kappa",
+        'λ' => "\\lambda",
+        'μ' => "\\mu",
+        'ν' => "\\nu",
+        'ξ' => "\\xi",
+        'π' => "\\pi",
+        'ϖ' => "\\varpi",
+        'ρ' => "\\rho",
+        'ϱ' => "\\varrho",
+        'σ' => "\\sigma",
+        'ς' => "\\varsigma",
+        'τ' => "\\tau",
+        'υ' => "\\upsilon",
+        'φ' => "\\varphi",
+        'ϕ' => "\\phi",
+        'χ' => "\\chi",
+        'ψ' => "\\psi",
+        'ω' => "\\omega",
+        'Γ' => "\\Gamma",
+        'Δ' => "\\Delta",
+        'Θ' => "\\Theta",
+        'Λ' => "\\Lambda",
+        'Ξ' => "\\Xi",
+        'Π' => "\\Pi",
+        'Σ' => "\\Sigma",
+        'Υ' => "\\Upsilon",
+        'Φ' => "\\Phi",
+        'Ψ' => "\\Psi",
+        'Ω' => "\\Omega",
+        '±' => "\\pm",
+        '∓' => "\\mp",
+        '×' => "\\times",
+        '÷' => "\\div",
+        '·' | '⋅' => "\\cdot",
+        '∗' => "\\ast",
+        '∘' => "\\circ",
+        '•' => "\\bullet",
+        '≤' => "\\leq",
+        '≥' => "\\geq",
+        '≠' => "\\neq",
+        '≈' => "\\approx",
+        '≡' => "\\equiv",
+        '∼' => "\\sim",
+        '≃' => "\\simeq",
+        '≅' => "\\cong",
+        '∝' => "\\propto",
+        '≪' => "\\ll",
+        '≫' => "\\gg",
+        '∞' => "\\infty",
+        '∂' => "\\partial",
+        '∇' => "\\nabla",
+        '∈' => "\\in",
+        '∉' => "\\notin",
+        '∋' => "\\ni",
+        '⊂' => "\\subset",
+        '⊃' => "\\supset",
+        '⊆' => "\\subseteq",
+        '⊇' => "\\supseteq",
+        '∪' => "\\cup",
+        '∩' => "\\cap",
+        '∅' => "\\emptyset",
+        '∖' => "\\setminus",
+        '→' => "\\rightarrow",
+        '←' => "\\leftarrow",
+        '↔' => "\\leftrightarrow",
+        '⇒' => "\\Rightarrow",
+        '⇐' => "\\Leftarrow",
+        '⇔' => "\\Leftrightarrow",
+        '↦' => "\\mapsto",
+        '∀' => "\\forall",
+        '∃' => "\\exists",
+        '∄' => "\\nexists",
+        '¬' => "\\neg",
+        '∧' => "\\wedge",
+        '∨' => "\\vee",
+        '⊕' => "\\oplus",
+        '⊗' => "\\otimes",
+        '⊥' => "\\perp",
+        '∥' => "\\parallel",
+        '∣' => "\\mid",
+        '∠' => "\\angle",
+        '…' => "\\ldots",
+        '⋯' => "\\cdots",
+        '⋮' => "\\vdots",
+        '⋱' => "\\ddots",
+        '′' => "\\prime",
+        '°' => "^{\\circ}",
+        '¹' => "^{1}",
+        '²' => "^{2}",
+        '³' => "^{3}",
+        'ℏ' => "\\hbar",
+        'ℓ' => "\\ell",
+        'ℜ' => "\\Re",
+        'ℑ' => "\\Im",
+        'ℵ' => "\\aleph",
+        'ℝ' => "\\mathbb{R}",
+        'ℕ' => "\\mathbb{N}",
+        'ℤ' => "\\mathbb{Z}",
+        'ℚ' => "\\mathbb{Q}",
+        'ℂ' => "\\mathbb{C}",
+        '√' => "\\surd",
+        _ => return None,
+    })
 }
 
-/// Read content of an XML element as a string
-fn read_element_content(reader: &mut Reader<&[u8]>, end_tag: &str) -> Result<String> {
-    let mut content = String::new();
-    let mut buf = Vec::new();
-    let mut depth = 1;
-    let end_tag_bytes = end_tag.as_bytes();
+/// The LaTeX command for an n-ary operator, or the operator itself
+fn operator_latex(operator: char) -> Cow<'static, str> {
+    Cow::Borrowed(match operator {
+        '∑' => "\\sum",
+        '∏' => "\\prod",
+        '∐' => "\\coprod",
+        '∫' => "\\int",
+        '∬' => "\\iint",
+        '∭' => "\\iiint",
+        '∮' => "\\oint",
+        '⋃' => "\\bigcup",
+        '⋂' => "\\bigcap",
+        '⋁' => "\\bigvee",
+        '⋀' => "\\bigwedge",
+        '⨁' => "\\bigoplus",
+        '⨂' => "\\bigotimes",
+        '⨀' => "\\bigodot",
+        _ => return Cow::Owned(text_latex(&operator.to_string())),
+    })
+}
 
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                content.push('<');
-                content.push_str(std::str::from_utf8(e.name().as_ref()).unwrap_or(""));
-                for a in e.attributes().flatten() {
-                    content.push(' ');
-                    content.push_str(std::str::from_utf8(a.key.as_ref()).unwrap_or(""));
-                    content.push_str("=\"");
-                    content.push_str(&String::from_utf8_lossy(&a.value));
-                    content.push('"');
-                }
-                content.push('>');
-                depth += 1;
+/// A delimiter after `\left` or `\right`; `.` is none
+fn delimiter_latex(delimiter: Option<char>) -> Cow<'static, str> {
+    Cow::Borrowed(match delimiter {
+        None => ".",
+        Some('{') => "\\{",
+        Some('}') => "\\}",
+        Some('⟨' | '〈') => "\\langle",
+        Some('⟩' | '〉') => "\\rangle",
+        Some('‖') => "\\|",
+        Some('⌊') => "\\lfloor",
+        Some('⌋') => "\\rfloor",
+        Some('⌈') => "\\lceil",
+        Some('⌉') => "\\rceil",
+        Some(c) => return Cow::Owned(c.to_string()),
+    })
+}
+
+/// Functions LaTeX has a command for
+const LATEX_FUNCTIONS: [&str; 29] = [
+    "arccos", "arcsin", "arctan", "arg", "cos", "cosh", "cot", "coth", "csc", "deg", "det", "dim",
+    "exp", "gcd", "hom", "inf", "ker", "lg", "lim", "liminf", "limsup", "ln", "log", "max", "min",
+    "sec", "sin", "sinh", "tan",
+];
+
+/// A function's name as LaTeX: `\sin`, or `\operatorname{name}` for one
+/// LaTeX lacks; a name with scripts (`sin²`) keeps them
+fn function_name_latex(name: &Arg) -> String {
+    let mut latex = String::new();
+    for node in name {
+        let part = match node {
+            Node::Text(text) if LATEX_FUNCTIONS.contains(&text.trim()) => {
+                format!("\\{}", text.trim())
             }
-            Ok(Event::End(ref e)) => {
-                depth -= 1;
-                if depth == 0 && e.name().as_ref() == end_tag_bytes {
-                    break;
-                }
-                content.push_str("</");
-                content.push_str(std::str::from_utf8(e.name().as_ref()).unwrap_or(""));
-                content.push('>');
+            Node::Text(text) if text.trim().chars().all(|c| c.is_ascii_alphabetic()) => {
+                format!("\\operatorname{{{}}}", text.trim())
             }
-            Ok(Event::Text(ref e)) => {
-                content.push_str(&e.unescape().unwrap_or_default());
+            Node::Sup { base, sup } => {
+                format!("{}^{{{}}}", function_name_latex(base), to_latex(sup))
             }
-            Ok(Event::Eof) => break,
-            Err(e) => anyhow::bail!("Element content read error: {}", e),
-            _ => {}
-        }
-        buf.clear();
+            Node::Sub { base, sub } => {
+                format!("{}_{{{}}}", function_name_latex(base), to_latex(sub))
+            }
+            node => node_latex(node),
+        };
+        push_latex(&mut latex, &part);
     }
+    latex
+}
 
-    Ok(content)
+/// Nodes as Unicode text for the terminal
+fn to_unicode(nodes: &[Node]) -> String {
+    nodes.iter().map(node_unicode).collect()
 }
 
-/// Render parsed OMML element to Unicode string
-fn render_to_unicode(element: &OmmlElement) -> String {
-    match element {
-        OmmlElement::Text(s) => s.clone(),
-        OmmlElement::Sequence(elements) => elements.iter().map(render_to_unicode).collect(),
-        OmmlElement::Superscript { base, sup } => {
-            let base_str = render_to_unicode(base);
-            let sup_str = render_to_unicode(sup);
-            format!("{}{}", base_str, to_superscript(&sup_str))
+fn node_unicode(node: &Node) -> String {
+    match node {
+        Node::Text(text) => text.clone(),
+        Node::Sup { base, sup } => to_unicode(base) + &superscript(&to_unicode(sup)),
+        Node::Sub { base, sub } => to_unicode(base) + &subscript(&to_unicode(sub)),
+        Node::SubSup { base, sub, sup } => {
+            to_unicode(base) + &subscript(&to_unicode(sub)) + &superscript(&to_unicode(sup))
         }
-        OmmlElement::Subscript { base, sub } => {
-            let base_str = render_to_unicode(base);
-            let sub_str = render_to_unicode(sub);
-            format!("{}{}", base_str, to_subscript(&sub_str))
+        Node::PreScript { sub, sup, base } => {
+            subscript(&to_unicode(sub)) + &superscript(&to_unicode(sup)) + &to_unicode(base)
         }
-        OmmlElement::Fraction { num, den } => {
-            let num_str = render_to_unicode(num);
-            let den_str = render_to_unicode(den);
-            // For simple single-char fractions, use Unicode fractions
-            match (num_str.as_str(), den_str.as_str()) {
-                ("1", "2") => "½".to_string(),
-                ("1", "4") => "¼".to_string(),
-                ("3", "4") => "¾".to_string(),
-                ("1", "3") => "⅓".to_string(),
-                ("2", "3") => "⅔".to_string(),
-                ("1", "5") => "⅕".to_string(),
-                ("1", "8") => "⅛".to_string(),
-                _ => format!("({num_str}⁄{den_str})"),
+        Node::Fraction { kind, num, den } => {
+            let (num, den) = (to_unicode(num), to_unicode(den));
+            match (num.as_str(), den.as_str(), kind) {
+                (_, _, FractionKind::NoBar) => format!("{num}¦{den}"),
+                ("1", "2", _) => "½".to_string(),
+                ("1", "4", _) => "¼".to_string(),
+                ("3", "4", _) => "¾".to_string(),
+                ("1", "3", _) => "⅓".to_string(),
+                ("2", "3", _) => "⅔".to_string(),
+                ("1", "5", _) => "⅕".to_string(),
+                ("1", "8", _) => "⅛".to_string(),
+                _ => format!("({num}⁄{den})"),
             }
         }
-        OmmlElement::Nary {
+        Node::Radical { degree, base } => {
+            let sign = match to_unicode(degree).as_str() {
+                "" | "2" => "√".to_string(),
+                "3" => "∛".to_string(),
+                "4" => "∜".to_string(),
+                degree => superscript(degree) + "√",
+            };
+            let base = to_unicode(base);
+            if base.chars().count() == 1 {
+                sign + &base
+            } else {
+                format!("{sign}({base})")
+            }
+        }
+        Node::Nary {
             operator,
             sub,
             sup,
             base,
         } => {
-            let mut result = operator.clone();
-            if let Some(s) = sub {
-                result.push_str(&to_subscript(&render_to_unicode(s)));
-            }
-            if let Some(s) = sup {
-                result.push_str(&to_superscript(&render_to_unicode(s)));
-            }
-            result.push_str(&render_to_unicode(base));
-            result
-        }
-        OmmlElement::Delimiter { content } => {
-            format!("({})", render_to_unicode(content))
-        }
-    }
-}
-
-/// Convert ASCII text to Unicode superscript
-fn to_superscript(text: &str) -> String {
-    text.chars()
-        .map(|c| match c {
-            '0' => '⁰',
-            '1' => '¹',
-            '2' => '²',
-            '3' => '³',
-            '4' => '⁴',
-            '5' => '⁵',
-            '6' => '⁶',
-            '7' => '⁷',
-            '8' => '⁸',
-            '9' => '⁹',
-            '+' => '⁺',
-            '-' => '⁻',
-            '=' => '⁼',
-            '(' => '⁽',
-            ')' => '⁾',
-            'n' => 'ⁿ',
-            'i' => 'ⁱ',
-            _ => c, // Keep other characters as-is
-        })
-        .collect()
-}
-
-/// Convert ASCII text to Unicode subscript
-fn to_subscript(text: &str) -> String {
-    text.chars()
-        .map(|c| match c {
-            '0' => '₀',
-            '1' => '₁',
-            '2' => '₂',
-            '3' => '₃',
-            '4' => '₄',
-            '5' => '₅',
-            '6' => '₆',
-            '7' => '₇',
-            '8' => '₈',
-            '9' => '₉',
-            '+' => '₊',
-            '-' => '₋',
-            '=' => '₌',
-            '(' => '₍',
-            ')' => '₎',
-            'a' => 'ₐ',
-            'e' => 'ₑ',
-            'h' => 'ₕ',
-            'i' => 'ᵢ',
-            'j' => 'ⱼ',
-            'k' => 'ₖ',
-            'l' => 'ₗ',
-            'm' => 'ₘ',
-            'n' => 'ₙ',
-            'o' => 'ₒ',
-            'p' => 'ₚ',
-            'r' => 'ᵣ',
-            's' => 'ₛ',
-            't' => 'ₜ',
-            'u' => 'ᵤ',
-            'v' => 'ᵥ',
-            'x' => 'ₓ',
-            _ => c, // Keep other characters as-is
-        })
-        .collect()
+            let mut unicode = operator.to_string();
+            if let Some(sub) = sub {
+                unicode.push_str(&subscript(&to_unicode(sub)));
+            }
+            if let Some(sup) = sup {
+                unicode.push_str(&superscript(&to_unicode(sup)));
+            }
+            unicode.push_str(&to_unicode(base));
+            unicode
+        }
+        Node::Delimiter {
+            open,
+            close,
+            separator,
+            items,
+        } => {
+            let items: Vec<String> = items.iter().map(|item| to_unicode(item)).collect();
+            format!(
+                "{}{}{}",
+                open.map(String::from).unwrap_or_default(),
+                items.join(&separator.to_string()),
+                close.map(String::from).unwrap_or_default()
+            )
+        }
+        Node::Function { name, argument } => {
+            let (name, argument) = (to_unicode(name), to_unicode(argument));
+            if argument.starts_with('(') {
+                name + &argument
+            } else {
+                format!("{name} {argument}")
+            }
+        }
+        Node::Matrix { rows } => {
+            let rows: Vec<String> = rows
+                .iter()
+                .map(|row| {
+                    let cells: Vec<String> = row.iter().map(|cell| to_unicode(cell)).collect();
+                    cells.join(" ")
+                })
+                .collect();
+            rows.join("; ")
+        }
+        Node::EquationArray { rows } => {
+            let rows: Vec<String> = rows
+                .iter()
+                .map(|row| to_unicode(row).replace('&', ""))
+                .collect();
+            rows.join("; ")
+        }
+        Node::Accent { accent, base } => {
+            let mut unicode = to_unicode(base);
+            unicode.push(combining_accent(*accent));
+            unicode
+        }
+        Node::Bar { over, base } => {
+            let line = if *over { '\u{305}' } else { '\u{332}' };
+            to_unicode(base).chars().flat_map(|c| [c, line]).collect()
+        }
+        Node::Limit { over, base, limit } => {
+            let limit = to_unicode(limit);
+            let limit = if *over {
+                superscript(&limit)
+            } else {
+                subscript(&limit)
+            };
+            to_unicode(base) + &limit
+        }
+    }
+}
+
+/// An accent as the combining character that sets it over the character
+/// before it
+fn combining_accent(accent: char) -> char {
+    match accent {
+        '`' => '\u{300}',
+        '´' => '\u{301}',
+        '^' | 'ˆ' => '\u{302}',
+        '~' | '˜' => '\u{303}',
+        '¯' => '\u{304}',
+        '‾' => '\u{305}',
+        '˘' => '\u{306}',
+        '˙' => '\u{307}',
+        '¨' => '\u{308}',
+        'ˇ' => '\u{30C}',
+        '→' => '\u{20D7}',
+        '←' => '\u{20D6}',
+        '↔' => '\u{20E1}',
+        accent => accent,
+    }
+}
+
+/// Text as superscript characters, or `^(text)` when some have none
+fn superscript(text: &str) -> String {
+    scripted(text, superscript_char, '^')
+}
+
+/// Text as subscript characters, or `_(text)` when some have none
+fn subscript(text: &str) -> String {
+    scripted(text, subscript_char, '_')
+}
+
+fn scripted(text: &str, script: fn(char) -> Option<char>, marker: char) -> String {
+    match text.chars().map(script).collect::<Option<String>>() {
+        Some(scripted) => scripted,
+        None if text.chars().count() == 1 => format!("{marker}{text}"),
+        None => format!("{marker}({text})"),
+    }
+}
+
+fn superscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'n' => 'ⁿ',
+        'i' => 'ⁱ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'k' => 'ᵏ',
+        'T' => 'ᵀ',
+        '′' | '*' | '°' => c,
+        _ => return None,
+    })
+}
+
+fn subscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
 }
 
 #[cfg(test)]
@@ -536,16 +1230,17 @@ mod tests {
 
     #[test]
     fn test_superscript_conversion() {
-        assert_eq!(to_superscript("2"), "²");
-        assert_eq!(to_superscript("n"), "ⁿ");
-        assert_eq!(to_superscript("10"), "¹⁰");
+        assert_eq!(superscript("2"), "²");
+        assert_eq!(superscript("n"), "ⁿ");
+        assert_eq!(superscript("10"), "¹⁰");
+        assert_eq!(superscript("ab"), "^(ab)");
     }
 
     #[test]
     fn test_subscript_conversion() {
-        assert_eq!(to_subscript("0"), "₀");
-        assert_eq!(to_subscript("k"), "ₖ");
-        assert_eq!(to_subscript("n-k"), "ₙ₋ₖ");
+        assert_eq!(subscript("0"), "₀");
+        assert_eq!(subscript("k"), "ₖ");
+        assert_eq!(subscript("n-k"), "ₙ₋ₖ");
     }
 
     #[test]
@@ -553,5 +1248,19 @@ mod tests {
         let omml = r#"<m:f><m:num><m:r><m:t>1</m:t></m:r></m:num><m:den><m:r><m:t>2</m:t></m:r></m:den></m:f>"#;
         let eq = Equation::from_omml(omml.to_string()).unwrap();
         assert_eq!(eq.unicode, "½");
+        assert_eq!(eq.latex, "\\frac{1}{2}");
+    }
+
+    #[test]
+    fn test_control_words_are_kept_apart() {
+        let mut latex = "\\pi".to_string();
+        push_latex(&mut latex, "r");
+        assert_eq!(latex, "\\pi r");
+        push_latex(&mut latex, "^{2}");
+        assert_eq!(latex, "\\pi r^{2}");
+        // An escaped character isn't a control word
+        let mut latex = "\\{".to_string();
+        push_latex(&mut latex, "x");
+        assert_eq!(latex, "\\{x");
     }
 }
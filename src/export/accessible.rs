@@ -0,0 +1,369 @@
+//! Accessible plain text export, for screen readers and text-to-speech
+//!
+//! Structure that sighted readers get from layout is spoken instead: headings
+//! and their levels, list and table sizes, each table cell with its column
+//! name. Nothing is drawn with box-drawing characters or rules, and list
+//! markers such as `iv.` or `(b)` are expanded so they aren't spelled out.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::document::*;
+
+/// A list marker at the start of a paragraph or list item, as the loader writes
+/// Word's numbering: indentation, then `2.1.`, `(a)`, `iv.` or a bullet
+static LIST_MARKER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^( *)(?:\(([0-9]+|[A-Za-z]+)\)\s*|(\d+(?:\.\d+)*)[.)]\s+|([A-Za-z]+)[.)]\s+|([•◦▪‣·])\s*|(\*)\s+)",
+    )
+    .unwrap()
+});
+
+/// Render the whole document as speakable plain text
+pub fn render_accessible_text(document: &Document) -> String {
+    let mut text = String::new();
+
+    text.push_str(&format!("Document: {}\n", document.title));
+    let pages = document.metadata.page_count;
+    let words = document.metadata.word_count;
+    text.push_str(&format!(
+        "About {pages} {}, {words} {}\n\n",
+        plural(pages, "page"),
+        plural(words, "word")
+    ));
+
+    for element in &document.elements {
+        let spoken = element_to_speech(element);
+        if !spoken.is_empty() {
+            text.push_str(&spoken);
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+/// Speakable text for one element, one line per paragraph, list item or table row
+fn element_to_speech(element: &DocumentElement) -> String {
+    match element {
+        DocumentElement::Heading {
+            level,
+            text,
+            number,
+        } => match number.as_deref().map(spoken_heading_number) {
+            Some(number) if !number.is_empty() => {
+                format!("Heading level {level}, section {number}: {text}\n")
+            }
+            _ => format!("Heading level {level}: {text}\n"),
+        },
+        DocumentElement::Paragraph { runs, .. } => {
+            let text = runs_to_speech(runs);
+            if text.trim().is_empty() {
+                return String::new();
+            }
+            // Word's numbered paragraphs keep their marker in the text
+            match split_list_marker(&text, false) {
+                Some((level, marker, rest)) => format!("{}\n", list_item(level, &marker, rest)),
+                None => format!("{}\n", text.trim()),
+            }
+        }
+        DocumentElement::List { items, ordered } => {
+            let kind = if *ordered { "Numbered list" } else { "List" };
+            let mut speech = format!(
+                "{kind} with {} {}\n",
+                items.len(),
+                plural(items.len(), "item")
+            );
+            // Item numbers by level, for items without a marker of their own
+            let mut counters: Vec<usize> = Vec::new();
+            for item in items {
+                let level = item.level as usize;
+                counters.resize(level + 1, 0);
+                counters[level] += 1;
+
+                let text = runs_to_speech(&item.runs);
+                let (marker, rest) = match split_list_marker(&text, true) {
+                    Some((_, marker, rest)) => (marker, rest),
+                    None if *ordered => (Marker::Item(counters[level].to_string()), text.trim()),
+                    None => (Marker::Bullet, text.trim()),
+                };
+                speech.push_str(&list_item(level, &marker, rest));
+                speech.push('\n');
+            }
+            speech.push_str("End of list\n");
+            speech
+        }
+        DocumentElement::BlockQuote { runs } => {
+            format!("Quote: {}\nEnd of quote\n", runs_to_speech(runs).trim())
+        }
+        DocumentElement::CodeBlock { code } => {
+            let lines = code.lines().count();
+            format!(
+                "Code block with {lines} {}\n{code}\nEnd of code\n",
+                plural(lines, "line")
+            )
+        }
+        DocumentElement::Table { table } => table_to_speech(table),
+        DocumentElement::Image {
+            description,
+            caption,
+            placement,
+            ..
+        } => {
+            let label = caption.as_ref().unwrap_or(description);
+            match placement.as_ref().and_then(ImagePlacement::describe) {
+                Some(note) => format!("Image: {label}. {note}.\n"),
+                None => format!("Image: {label}\n"),
+            }
+        }
+        DocumentElement::Equation {
+            latex,
+            fallback,
+            number,
+        } => {
+            let equation = if latex.trim().is_empty() {
+                fallback
+            } else {
+                latex
+            };
+            match number {
+                Some(number) => format!("Equation {number}: {}\n", equation.trim()),
+                None => format!("Equation: {}\n", equation.trim()),
+            }
+        }
+        DocumentElement::PageBreak { page } => format!("Page break, page {page}\n"),
+        DocumentElement::HorizontalRule => "Horizontal rule\n".to_string(),
+        DocumentElement::FileBoundary { name } => format!("File {name}\n"),
+    }
+}
+
+/// Plain text of runs, with inline equations unwrapped from their `$` delimiters
+///
+/// Capitalisation effects are left off, since speech engines spell out words in
+/// capitals.
+fn runs_to_speech(runs: &[FormattedRun]) -> String {
+    runs.iter()
+        .map(|run| {
+            run.text
+                .strip_prefix('$')
+                .and_then(|text| text.strip_suffix('$'))
+                .filter(|latex| !latex.is_empty())
+                .unwrap_or(&run.text)
+                .replace('\n', " ")
+        })
+        .collect()
+}
+
+/// How a list item is announced
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Marker {
+    /// A numbered or lettered item, with its number as spoken
+    Item(String),
+    Bullet,
+}
+
+fn list_item(level: usize, marker: &Marker, text: &str) -> String {
+    let announcement = match marker {
+        Marker::Item(number) => format!("item {number}"),
+        Marker::Bullet => "bullet".to_string(),
+    };
+    if level == 0 {
+        let mut chars = announcement.chars();
+        let first = chars.next().map(|c| c.to_ascii_uppercase());
+        format!("{}{}: {text}", first.unwrap_or_default(), chars.as_str())
+    } else {
+        format!("Level {} {announcement}: {text}", level + 1)
+    }
+}
+
+/// Split a list marker off the front of `text`, returning the nesting level
+/// (two spaces of indentation a level), the marker and the rest of the text
+///
+/// Outside lists, a lone letter or numeral followed by a full stop ("I. Scope",
+/// "A. Smith") only counts as a marker in an indented paragraph.
+fn split_list_marker(text: &str, in_list: bool) -> Option<(usize, Marker, &str)> {
+    let captures = LIST_MARKER.captures(text)?;
+    let indent = captures.get(1).map_or(0, |m| m.as_str().len());
+    let rest = text[captures.get(0)?.end()..].trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    let marker = if let Some(label) = captures.get(2) {
+        Marker::Item(spoken_label(label.as_str())?)
+    } else if let Some(number) = captures.get(3) {
+        Marker::Item(number.as_str().to_string())
+    } else if let Some(label) = captures.get(4) {
+        if indent == 0 && !in_list {
+            return None;
+        }
+        Marker::Item(spoken_label(label.as_str())?)
+    } else {
+        Marker::Bullet
+    };
+    Some((indent / 2, marker, rest))
+}
+
+/// A letter or roman numeral as it should be spoken: "iv" is 4, "b" is B
+///
+/// A lone "i" is read as a numeral, as Word's lower-roman lists start with it.
+fn spoken_label(label: &str) -> Option<String> {
+    if label.chars().all(|c| c.is_ascii_digit()) {
+        return Some(label.to_string());
+    }
+    if label.len() > 1 || label.eq_ignore_ascii_case("i") {
+        return roman_to_number(label).map(|n| n.to_string());
+    }
+    Some(label.to_ascii_uppercase())
+}
+
+/// A heading number with roman numerals expanded and trailing full stops dropped
+fn spoken_heading_number(number: &str) -> String {
+    number
+        .trim()
+        .trim_end_matches('.')
+        .split('.')
+        .map(|part| match roman_to_number(part) {
+            Some(n) if part.len() > 1 || part.eq_ignore_ascii_case("i") => n.to_string(),
+            _ => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Value of a well-formed roman numeral, in either case
+fn roman_to_number(numeral: &str) -> Option<usize> {
+    let value = |c: char| match c.to_ascii_lowercase() {
+        'i' => Some(1),
+        'v' => Some(5),
+        'x' => Some(10),
+        'l' => Some(50),
+        'c' => Some(100),
+        'd' => Some(500),
+        'm' => Some(1000),
+        _ => None,
+    };
+    let values = numeral.chars().map(value).collect::<Option<Vec<usize>>>()?;
+    let mut total = 0;
+    for (i, &v) in values.iter().enumerate() {
+        if values.get(i + 1).is_some_and(|&next| next > v) {
+            total -= v as isize;
+        } else {
+            total += v as isize;
+        }
+    }
+    // Only accept the canonical spelling, so "ic" or "iiii" isn't a numeral
+    let total = usize::try_from(total).ok().filter(|&n| n > 0)?;
+    (to_roman(total).eq_ignore_ascii_case(numeral)).then_some(total)
+}
+
+fn to_roman(mut n: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut roman = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            roman.push_str(numeral);
+            n -= value;
+        }
+    }
+    roman
+}
+
+/// A table announced with its size, then each row with cells named by their column
+fn table_to_speech(table: &TableData) -> String {
+    let columns = table
+        .headers
+        .len()
+        .max(table.rows.iter().map(Vec::len).max().unwrap_or(0));
+    let rows = table.rows.len();
+    let name = match &table.metadata.title {
+        Some(title) => format!("Table: {title}, with"),
+        None => "Table with".to_string(),
+    };
+    let mut speech = format!(
+        "{name} {columns} {} and {rows} {}\n",
+        plural(columns, "column"),
+        plural(rows, "row")
+    );
+
+    let labels: Vec<&str> = table
+        .headers
+        .iter()
+        .map(|cell| cell.content.trim())
+        .collect();
+    if labels.iter().any(|label| !label.is_empty()) {
+        speech.push_str(&format!("Columns: {}\n", labels.join("; ")));
+    }
+    for (index, row) in table.rows.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| {
+                let content = cell.content.replace('\n', " ");
+                let content = if content.trim().is_empty() {
+                    "blank"
+                } else {
+                    content.trim()
+                };
+                match labels.get(column).filter(|label| !label.is_empty()) {
+                    Some(label) => format!("{label}: {content}"),
+                    None => content.to_string(),
+                }
+            })
+            .collect();
+        speech.push_str(&format!("Row {}: {}\n", index + 1, cells.join("; ")));
+    }
+    speech.push_str("End of table\n");
+    speech
+}
+
+/// `noun`, or its plural for any count but one
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        noun.to_string()
+    } else {
+        format!("{noun}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_markers_are_expanded() {
+        let item = |text| split_list_marker(text, false).map(|(level, marker, _)| (level, marker));
+        assert_eq!(item("2. Second"), Some((0, Marker::Item("2".into()))));
+        assert_eq!(item("  2.1. Nested"), Some((1, Marker::Item("2.1".into()))));
+        assert_eq!(item("    iv. Deeper"), Some((2, Marker::Item("4".into()))));
+        assert_eq!(item("    (b)Lettered"), Some((2, Marker::Item("B".into()))));
+        assert_eq!(item("  • Bullet"), Some((1, Marker::Bullet)));
+
+        // Not list markers
+        assert_eq!(item("3.5 percent"), None);
+        assert_eq!(item("A. Smith wrote"), None);
+        assert_eq!(item("2024 was a good year"), None);
+    }
+
+    #[test]
+    fn test_roman_numerals() {
+        assert_eq!(roman_to_number("xiv"), Some(14));
+        assert_eq!(roman_to_number("MCMXC"), Some(1990));
+        assert_eq!(roman_to_number("iiii"), None);
+        assert_eq!(spoken_heading_number("IV.2."), "4.2");
+    }
+}
@@ -0,0 +1,398 @@
+//! AsciiDoc export
+//!
+//! Targets Asciidoctor (and so Antora). Unlike Markdown, AsciiDoc tables carry
+//! per-column and per-cell alignment, so tables keep their layout. Paragraphs
+//! that open with a label such as "Note:" or "Warning:" become admonitions, and
+//! equations are written as `latexmath` passthroughs.
+
+use crate::document::*;
+
+use super::{cell_runs, heading_display_text, tagged_latex};
+
+/// Labels that turn a paragraph into an admonition, as AsciiDoc spells them
+const ADMONITIONS: [&str; 5] = ["NOTE", "TIP", "IMPORTANT", "WARNING", "CAUTION"];
+
+/// Characters that AsciiDoc would read as inline markup
+const MARKUP_CHARS: &[char] = &['*', '_', '`', '#', '^', '~', '{', '[', '\\'];
+
+/// Render the whole document as one AsciiDoc string
+pub fn render_asciidoc(document: &Document) -> String {
+    let mut asciidoc = String::new();
+
+    // Document header: title and attributes, then a blank line to end it
+    asciidoc.push_str(&format!("= {}\n", document.title));
+    if let Some(author) = &document.metadata.author {
+        asciidoc.push_str(&format!(":author: {author}\n"));
+    }
+    asciidoc.push_str(":stem: latexmath\n\n");
+
+    let mut previous: Option<&DocumentElement> = None;
+    for element in &document.elements {
+        // Adjacent lists would otherwise merge into one
+        if matches!(previous, Some(DocumentElement::List { .. }))
+            && matches!(element, DocumentElement::List { .. })
+        {
+            asciidoc.push_str("//-\n\n");
+        }
+        asciidoc.push_str(&element_to_asciidoc(element));
+        previous = Some(element);
+    }
+
+    asciidoc
+}
+
+/// AsciiDoc for a single document element, including its trailing blank line
+fn element_to_asciidoc(element: &DocumentElement) -> String {
+    let mut asciidoc = String::new();
+    match element {
+        DocumentElement::Heading { level, .. } => {
+            // The document title is level 0; AsciiDoc sections stop at level 5
+            let prefix = "=".repeat((*level as usize).clamp(1, 5) + 1);
+            let heading_text = heading_display_text(element).unwrap_or_default();
+            asciidoc.push_str(&format!("{prefix} {heading_text}\n\n"));
+        }
+        DocumentElement::Paragraph {
+            runs, alignment, ..
+        } => {
+            let (admonition, runs) = split_admonition(runs);
+            let text = runs_to_asciidoc(&runs);
+            if text.trim().is_empty() {
+                return asciidoc;
+            }
+
+            match alignment {
+                TextAlignment::Center => asciidoc.push_str("[.text-center]\n"),
+                TextAlignment::Right => asciidoc.push_str("[.text-right]\n"),
+                TextAlignment::Left | TextAlignment::Justify => {}
+            }
+            match admonition {
+                Some(label) => asciidoc.push_str(&format!("{label}: {text}\n\n")),
+                None => asciidoc.push_str(&format!("{}\n\n", escape_block_start(&text))),
+            }
+        }
+        DocumentElement::List { items, ordered } => {
+            let marker = if *ordered { "." } else { "*" };
+            for item in items {
+                let depth = (item.level as usize).min(4) + 1;
+                let text = runs_to_asciidoc(&item.runs);
+                asciidoc.push_str(&format!("{} {text}\n", marker.repeat(depth)));
+            }
+            asciidoc.push('\n');
+        }
+        DocumentElement::BlockQuote { runs } => {
+            let text = runs_to_asciidoc(runs);
+            asciidoc.push_str(&format!("____\n{}\n____\n\n", text.trim()));
+        }
+        DocumentElement::CodeBlock { code } => {
+            // A delimiter longer than any line of dashes in the code
+            let longest = code
+                .lines()
+                .filter(|line| !line.is_empty() && line.chars().all(|c| c == '-'))
+                .map(str::len)
+                .max()
+                .unwrap_or(0);
+            let delimiter = "-".repeat(longest.max(3) + 1);
+            asciidoc.push_str(&format!("[source]\n{delimiter}\n{code}\n{delimiter}\n\n"));
+        }
+        DocumentElement::Table { table } => {
+            asciidoc.push_str(&asciidoc_table(table));
+            asciidoc.push('\n');
+        }
+        DocumentElement::Image {
+            description,
+            width,
+            height,
+            image_path,
+            caption,
+            placement,
+            ..
+        } => {
+            let Some(path) = image_path else {
+                // Nothing to point at, such as a metafile that couldn't be converted
+                asciidoc.push_str(&format!("[Image: {description}]\n\n"));
+                return asciidoc;
+            };
+            let target = path.to_string_lossy();
+            // Where a floating image was, as a comment
+            if let Some(note) = placement.as_ref().and_then(ImagePlacement::describe) {
+                asciidoc.push_str(&format!("// {note}\n"));
+            }
+            if let Some(caption) = caption {
+                asciidoc.push_str(&format!(".{caption}\n"));
+            }
+            let mut attributes = vec![format!("\"{}\"", description.replace('"', "\\\""))];
+            if let (Some(w), Some(h)) = (width, height) {
+                attributes.push(w.to_string());
+                attributes.push(h.to_string());
+            }
+            if let Some(side) = placement.as_ref().and_then(ImagePlacement::float_side) {
+                attributes.push(format!("float={side}"));
+            }
+            asciidoc.push_str(&format!("image::{target}[{}]\n\n", attributes.join(",")));
+        }
+        DocumentElement::Equation { latex, number, .. } => {
+            let latex = tagged_latex(latex, number.as_deref());
+            asciidoc.push_str(&format!("[latexmath]\n++++\n{latex}\n++++\n\n"));
+        }
+        DocumentElement::PageBreak { .. } => {
+            asciidoc.push_str("<<<\n\n");
+        }
+        DocumentElement::HorizontalRule => {
+            asciidoc.push_str("'''\n\n");
+        }
+        DocumentElement::FileBoundary { name } => {
+            asciidoc.push_str(&format!("// file: {name}\n'''\n\n"));
+        }
+    }
+    asciidoc
+}
+
+/// Split an admonition label ("Note:", "WARNING -", ...) off the front of a paragraph
+///
+/// Returns the AsciiDoc label and the runs that follow it, or no label and the
+/// runs unchanged.
+fn split_admonition(runs: &[FormattedRun]) -> (Option<&'static str>, Vec<FormattedRun>) {
+    let text: String = runs.iter().map(|run| run.text.as_str()).collect();
+    let trimmed = text.trim_start();
+
+    for label in ADMONITIONS {
+        let Some(head) = trimmed.get(..label.len()) else {
+            continue;
+        };
+        if !head.eq_ignore_ascii_case(label) {
+            continue;
+        }
+        let rest = &trimmed[label.len()..];
+        let Some(after) = rest
+            .strip_prefix(':')
+            .or_else(|| rest.strip_prefix(" -"))
+            .or_else(|| rest.strip_prefix(" –"))
+        else {
+            continue;
+        };
+        let body = after.trim_start();
+        if body.is_empty() {
+            continue;
+        }
+        let skip = text.len() - body.len();
+        return (Some(label), drop_leading_bytes(runs, skip));
+    }
+
+    (None, runs.to_vec())
+}
+
+/// Remove the first `count` bytes of text from a run sequence
+fn drop_leading_bytes(runs: &[FormattedRun], mut count: usize) -> Vec<FormattedRun> {
+    let mut remaining = Vec::new();
+    for run in runs {
+        if count >= run.text.len() {
+            count -= run.text.len();
+            continue;
+        }
+        let mut run = run.clone();
+        run.text = run.text[count..].to_string();
+        count = 0;
+        remaining.push(run);
+    }
+    remaining
+}
+
+fn runs_to_asciidoc(runs: &[FormattedRun]) -> String {
+    runs.iter().map(run_to_asciidoc).collect()
+}
+
+/// AsciiDoc for a single run
+///
+/// Formatting uses the unconstrained (doubled) marks, which also work in the
+/// middle of a word, since Word runs often split one.
+fn run_to_asciidoc(run: &FormattedRun) -> String {
+    if let Some(latex) = inline_equation(&run.text) {
+        return format!("latexmath:[{}]", latex.replace(']', "\\]"));
+    }
+
+    let text = run.formatting.apply_caps(&run.text);
+    if text.trim().is_empty() {
+        return text.replace('\n', " +\n");
+    }
+    // Keep surrounding spaces outside the marks, where AsciiDoc expects them
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    let mut formatted = escape_inline(text.trim());
+
+    match run.formatting.vertical_align {
+        VerticalAlign::Superscript => formatted = format!("^{formatted}^"),
+        VerticalAlign::Subscript => formatted = format!("~{formatted}~"),
+        VerticalAlign::Baseline => {}
+    }
+    let mut roles = String::new();
+    if run.formatting.underline {
+        roles.push_str(".underline");
+    }
+    if run.formatting.strikethrough {
+        roles.push_str(".line-through");
+    }
+    if !roles.is_empty() {
+        formatted = format!("[{roles}]##{formatted}##");
+    } else if run.formatting.background_hex().is_some() {
+        formatted = format!("##{formatted}##");
+    }
+    if run.formatting.bold {
+        formatted = format!("**{formatted}**");
+    }
+    if run.formatting.italic {
+        formatted = format!("__{formatted}__");
+    }
+
+    format!("{leading}{formatted}{trailing}").replace('\n', " +\n")
+}
+
+/// The LaTeX of an inline equation run, which the loader writes as `$...$`
+fn inline_equation(text: &str) -> Option<&str> {
+    text.strip_prefix('$')?
+        .strip_suffix('$')
+        .filter(|latex| !latex.is_empty())
+}
+
+/// Pass text through unchanged if AsciiDoc would read any of it as markup
+fn escape_inline(text: &str) -> String {
+    let is_markup = text.contains(MARKUP_CHARS) || text.contains("<<");
+    if is_markup && !text.contains("++") {
+        format!("++{text}++")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Keep a paragraph from being read as a list, heading, block or comment
+fn escape_block_start(text: &str) -> String {
+    let block_starts = ["* ", "- ", ". ", "= ", "//", "|===", "<<<", "'''", "...."];
+    let is_numbered = text.split_once(". ").is_some_and(|(number, _)| {
+        !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+    });
+    if block_starts.iter().any(|start| text.starts_with(start))
+        || is_numbered
+        || ADMONITIONS
+            .iter()
+            .any(|label| text.starts_with(&format!("{label}:")))
+    {
+        format!("{{empty}}{text}")
+    } else {
+        text.to_string()
+    }
+}
+
+/// A table as an AsciiDoc table, with its title and column alignments
+fn asciidoc_table(table: &TableData) -> String {
+    let mut asciidoc = String::new();
+
+    if let Some(title) = &table.metadata.title {
+        asciidoc.push_str(&format!(".{title}\n"));
+    }
+
+    let alignments = &table.metadata.column_alignments;
+    let columns = table
+        .headers
+        .len()
+        .max(table.rows.iter().map(Vec::len).max().unwrap_or(0))
+        .max(1);
+    let cols: Vec<String> = (0..columns)
+        .map(|i| {
+            let align = alignments.get(i).copied().unwrap_or_default();
+            let width = table
+                .metadata
+                .column_widths
+                .get(i)
+                .copied()
+                .unwrap_or(1)
+                .max(1);
+            format!("{}{width}", alignment_specifier(align))
+        })
+        .collect();
+    let header_option = if table.headers.is_empty() {
+        ""
+    } else {
+        "%header,"
+    };
+    asciidoc.push_str(&format!("[{header_option}cols=\"{}\"]\n", cols.join(",")));
+    asciidoc.push_str("|===\n");
+
+    // Header cells follow their column; in the body, only cells that differ
+    // from their column need their own specifier
+    let row_line = |cells: &[TableCell], is_header: bool| {
+        let cells: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let column = alignments.get(i).copied().unwrap_or_default();
+                let specifier = if is_header
+                    || asciidoc_alignment(cell.alignment) == asciidoc_alignment(column)
+                {
+                    ""
+                } else {
+                    alignment_specifier(cell.alignment)
+                };
+                let text = runs_to_asciidoc(&cell_runs(cell, is_header));
+                format!("{specifier}| {}", text.replace('|', "\\|"))
+            })
+            .collect();
+        format!("{}\n", cells.join(" "))
+    };
+
+    if !table.headers.is_empty() {
+        asciidoc.push_str(&row_line(&table.headers, true));
+        asciidoc.push('\n');
+    }
+    for row in &table.rows {
+        asciidoc.push_str(&row_line(row, false));
+    }
+    asciidoc.push_str("|===\n");
+    asciidoc
+}
+
+/// Alignments AsciiDoc can express; justified text is left aligned
+fn asciidoc_alignment(alignment: TextAlignment) -> TextAlignment {
+    match alignment {
+        TextAlignment::Justify => TextAlignment::Left,
+        other => other,
+    }
+}
+
+/// Horizontal alignment operator for a column or cell specifier
+fn alignment_specifier(alignment: TextAlignment) -> &'static str {
+    match asciidoc_alignment(alignment) {
+        TextAlignment::Center => "^",
+        TextAlignment::Right => ">",
+        _ => "<",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str) -> FormattedRun {
+        FormattedRun {
+            text: text.to_string(),
+            formatting: TextFormatting::default(),
+        }
+    }
+
+    #[test]
+    fn test_admonition_label_is_split_across_runs() {
+        let mut label = run("Note: ");
+        label.formatting.bold = true;
+        let (admonition, runs) = split_admonition(&[label, run("back up first.")]);
+        assert_eq!(admonition, Some("NOTE"));
+        assert_eq!(runs_to_asciidoc(&runs), "back up first.");
+
+        let (admonition, _) = split_admonition(&[run("Notebooks: three")]);
+        assert_eq!(admonition, None);
+    }
+
+    #[test]
+    fn test_paragraphs_never_start_a_block() {
+        assert_eq!(escape_block_start("* not a list"), "{empty}* not a list");
+        assert_eq!(escape_block_start("2024. A year"), "{empty}2024. A year");
+        assert_eq!(escape_block_start("3.5 percent"), "3.5 percent");
+    }
+}
@@ -0,0 +1,186 @@
+//! CSV export
+//!
+//! Only the document's tables are exported. Cells are written by their
+//! detected type, so spreadsheets and scripts read them as values: numbers,
+//! amounts and percentages without currency symbols, digit grouping or `%`,
+//! and dates in ISO 8601 (`2024-09-30`). Spreadsheets set to a language that
+//! writes a decimal comma want that comma, with semicolons between fields.
+
+use anyhow::Result;
+use std::io::Write;
+
+use crate::{
+    document::{table_query, *},
+    CsvDelimiter, DecimalSeparator,
+};
+
+/// Options for CSV export
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvOptions {
+    /// Field separator; unset, a semicolon with a decimal comma and a comma otherwise
+    pub delimiter: Option<CsvDelimiter>,
+    /// Decimal separator for numbers
+    pub decimal: DecimalSeparator,
+    /// Follow the header row with a row giving each column's type
+    pub types: bool,
+    /// Keep cells as written instead of by type
+    pub as_written: bool,
+}
+
+impl CsvOptions {
+    /// Cells exactly as the document has them, comma-separated
+    pub fn as_written() -> Self {
+        Self {
+            as_written: true,
+            ..Self::default()
+        }
+    }
+
+    fn delimiter(&self) -> char {
+        match (self.delimiter, self.decimal) {
+            (Some(CsvDelimiter::Comma), _) => ',',
+            (Some(CsvDelimiter::Semicolon), _) => ';',
+            (Some(CsvDelimiter::Tab), _) => '\t',
+            (None, DecimalSeparator::Comma) => ';',
+            (None, DecimalSeparator::Point) => ',',
+        }
+    }
+}
+
+/// Write the tables of a document as CSV
+pub fn write_csv(out: &mut impl Write, document: &Document, options: &CsvOptions) -> Result<()> {
+    let locale = document.cell_locale();
+    let mut csv_output = Vec::new();
+
+    // Find all tables in the document
+    for (table_index, element) in document.elements.iter().enumerate() {
+        if let DocumentElement::Table { table } = element {
+            if table_index > 0 {
+                csv_output.push(String::new()); // Empty line between tables
+                csv_output.push(format!("# Table {}", table_index + 1));
+            }
+
+            // Add table title as comment if present
+            if let Some(title) = &table.metadata.title {
+                csv_output.push(format!("# {title}"));
+            }
+
+            csv_output.extend(csv_table_lines(table, locale, options));
+        }
+    }
+
+    if csv_output.is_empty() {
+        writeln!(out, "No tables found in document")?;
+    } else {
+        for line in csv_output {
+            writeln!(out, "{line}")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Header and data lines of a table as CSV; `locale` is how the document
+/// writes its numbers and dates
+pub(crate) fn csv_table_lines(
+    table: &TableData,
+    locale: CellLocale,
+    options: &CsvOptions,
+) -> Vec<String> {
+    let delimiter = options.delimiter();
+    let line = |fields: Vec<String>| {
+        fields
+            .iter()
+            .map(|field| escape_field(field, delimiter))
+            .collect::<Vec<_>>()
+            .join(&delimiter.to_string())
+    };
+    let header = table
+        .headers
+        .iter()
+        .map(|cell| cell.content.clone())
+        .collect();
+    let types = (0..table.headers.len())
+        .map(|column| type_name(column_type(table, column)).to_string())
+        .collect();
+    let rows = table.rows.iter().map(|row| {
+        row.iter()
+            .map(|cell| {
+                if options.as_written {
+                    cell.content.clone()
+                } else {
+                    cell_value(cell, locale, options.decimal)
+                }
+            })
+            .collect()
+    });
+
+    std::iter::once(header)
+        .chain(options.types.then_some(types))
+        .chain(rows)
+        .map(line)
+        .collect()
+}
+
+/// A cell's value as its type is written in CSV; text, booleans and cells
+/// that only looked like numbers or dates stay as written
+fn cell_value(cell: &TableCell, locale: CellLocale, decimal: DecimalSeparator) -> String {
+    let text = cell.content.trim();
+    let value = match cell.data_type {
+        CellDataType::Number | CellDataType::Currency | CellDataType::Percentage => {
+            table_query::parse_amount(text, locale.numbers).map(|number| match decimal {
+                DecimalSeparator::Point => number.to_string(),
+                DecimalSeparator::Comma => number.to_string().replace('.', ","),
+            })
+        }
+        CellDataType::Date => table_query::parse_date(text, locale.dates)
+            .map(|(year, month, day)| format!("{year:04}-{month:02}-{day:02}")),
+        _ => None,
+    };
+    value.unwrap_or_else(|| cell.content.clone())
+}
+
+/// The type most of a column's filled cells have, text if they're mixed evenly
+fn column_type(table: &TableData, column: usize) -> CellDataType {
+    let mut counts: Vec<(CellDataType, usize)> = Vec::new();
+    for cell in table.rows.iter().filter_map(|row| row.get(column)) {
+        if cell.data_type == CellDataType::Empty {
+            continue;
+        }
+        match counts
+            .iter_mut()
+            .find(|(data_type, _)| *data_type == cell.data_type)
+        {
+            Some((_, count)) => *count += 1,
+            None => counts.push((cell.data_type, 1)),
+        }
+    }
+    counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    match &counts[..] {
+        [] => CellDataType::Empty,
+        [(data_type, _)] => *data_type,
+        [(first, most), (_, next), ..] if most > next => *first,
+        _ => CellDataType::Text,
+    }
+}
+
+/// Type names as in JSON export
+fn type_name(data_type: CellDataType) -> &'static str {
+    match data_type {
+        CellDataType::Text => "text",
+        CellDataType::Number => "number",
+        CellDataType::Currency => "currency",
+        CellDataType::Percentage => "percentage",
+        CellDataType::Date => "date",
+        CellDataType::Boolean => "boolean",
+        CellDataType::Empty => "empty",
+    }
+}
+
+pub(crate) fn escape_field(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,126 @@
+//! Conversion fidelity summary
+//!
+//! After an export, doxx says how much of the document made it across: what
+//! the loader skipped or approximated, plus whatever the chosen format itself
+//! can't hold (CSV has no paragraphs, text and ANSI have no pictures).
+
+use std::fmt;
+
+use crate::{document::*, ExportFormat};
+
+/// How faithfully a document was exported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FidelitySummary {
+    /// Elements written to the export
+    pub exported: usize,
+    /// One entry per kind of loss, such as "2 unsupported drawings skipped"
+    pub losses: Vec<String>,
+}
+
+impl fmt::Display for FidelitySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} exported", count(self.exported, "element"))?;
+        for loss in &self.losses {
+            write!(f, ", {loss}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Summarise an export of `document` as `format`
+///
+/// `inline_images` matches the export: text export to a terminal draws
+/// pictures rather than writing placeholders.
+pub fn fidelity_summary(
+    document: &Document,
+    format: &ExportFormat,
+    inline_images: bool,
+) -> FidelitySummary {
+    let mut losses = load_losses(&document.fidelity);
+    let mut report = |n: usize, noun: &str, what: &str| {
+        if n > 0 {
+            losses.push(format!("{} {what}", count(n, noun)));
+        }
+    };
+
+    let elements = &document.elements;
+    let images = |with_path_drawn: bool| {
+        elements
+            .iter()
+            .filter(|element| match element {
+                DocumentElement::Image { image_path, .. } => {
+                    !(with_path_drawn && image_path.is_some())
+                }
+                _ => false,
+            })
+            .count()
+    };
+    let equations = elements
+        .iter()
+        .filter(|element| matches!(element, DocumentElement::Equation { .. }))
+        .count();
+
+    let mut exported = elements.len();
+    match format {
+        ExportFormat::Csv => {
+            let tables = elements
+                .iter()
+                .filter(|element| matches!(element, DocumentElement::Table { .. }))
+                .count();
+            report(elements.len() - tables, "non-table element", "left out");
+            exported = tables;
+        }
+        ExportFormat::Text => {
+            report(images(inline_images), "image", "written as text");
+            report(equations, "equation", "left as LaTeX");
+        }
+        ExportFormat::Ansi => {
+            report(images(false), "image", "written as text");
+            report(equations, "equation", "left as LaTeX");
+        }
+        ExportFormat::Markdown
+        | ExportFormat::Json
+        | ExportFormat::Ndjson
+        | ExportFormat::Asciidoc
+        | ExportFormat::Rst
+        | ExportFormat::Html
+        | ExportFormat::Notes => {}
+    }
+
+    FidelitySummary { exported, losses }
+}
+
+/// What the loader skipped or approximated, whatever the document is shown
+/// as, such as "1 chart skipped"; empty when nothing was lost
+pub fn load_losses(fidelity: &Fidelity) -> Vec<String> {
+    [
+        (fidelity.charts, "chart", "skipped"),
+        (fidelity.diagrams, "SmartArt diagram", "skipped"),
+        (fidelity.embedded_objects, "embedded object", "skipped"),
+        (
+            fidelity.unsupported_drawings,
+            "unsupported drawing",
+            "skipped",
+        ),
+        (
+            fidelity.omitted_images,
+            "image",
+            "left out without --images",
+        ),
+        (fidelity.flattened_tables, "nested table", "flattened"),
+        (fidelity.unevaluated_fields, "field", "unevaluated"),
+    ]
+    .into_iter()
+    .filter(|&(n, _, _)| n > 0)
+    .map(|(n, noun, what)| format!("{} {what}", count(n, noun)))
+    .collect()
+}
+
+/// "1 element", "2 elements"
+fn count(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{n} {noun}s")
+    }
+}
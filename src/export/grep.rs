@@ -0,0 +1,507 @@
+//! Searching documents for `doxx grep` and printing what matched
+//!
+//! In one document, matches print as ripgrep prints lines: under the headings
+//! they're in, each prefixed with its element number, `:` for a match and `-`
+//! for context, with `--` between matches whose context doesn't meet. Across
+//! several documents each match is one `file:headings:snippet` line, followed
+//! by a count of the matches and files, in path order or, with
+//! `--sort relevance`, the documents with the most occurrences first. Markdown
+//! quotes each match and its context under a heading path, and JSON gives the
+//! match ranges.
+
+use anyhow::{bail, Result};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::{document::*, GrepFormat};
+
+const MATCH_STYLE: &str = "\x1b[1;31m";
+const HEADING_STYLE: &str = "\x1b[1;35m";
+const NUMBER_STYLE: &str = "\x1b[32m";
+const FILE_STYLE: &str = "\x1b[35m";
+const RESET: &str = "\x1b[0m";
+
+/// Bytes of text a snippet shows before the first match in it
+const SNIPPET_LEAD: usize = 30;
+/// Longest snippet, in bytes, before it's cut short
+const SNIPPET_LENGTH: usize = 120;
+
+/// The matches found in one document
+#[derive(Debug, Clone)]
+pub struct GrepFile {
+    pub path: String,
+    pub matches: Vec<GrepMatch>,
+}
+
+/// The documents `paths` name: files as given, `.docx` files in directories
+/// with `recursive`, and files matching `*` and `?` in the last part of a path
+///
+/// Hidden files and directories and Word's `~$` lock files are skipped.
+pub fn find_documents(paths: &[PathBuf], recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut documents = Vec::new();
+    for path in paths {
+        let name = path.file_name().map(|name| name.to_string_lossy());
+        if let Some(pattern) = name.filter(|name| name.contains(['*', '?'])) {
+            let dir = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            let mut matched: Vec<PathBuf> = std::fs::read_dir(dir)?
+                .flatten()
+                .filter(|entry| glob_match(&pattern, &entry.file_name().to_string_lossy()))
+                .map(|entry| entry.path())
+                .collect();
+            if matched.is_empty() {
+                bail!("No files match {}", path.display());
+            }
+            matched.sort();
+            for path in matched {
+                if path.is_dir() {
+                    if recursive {
+                        walk(&path, &mut documents)?;
+                    }
+                } else {
+                    documents.push(path);
+                }
+            }
+        } else if path.is_dir() {
+            if !recursive {
+                bail!(
+                    "{} is a directory; use --recursive to search the documents in it",
+                    path.display()
+                );
+            }
+            walk(path, &mut documents)?;
+        } else {
+            documents.push(path.clone());
+        }
+    }
+    Ok(documents)
+}
+
+/// Add the `.docx` files under `dir` to `documents`, in name order
+fn walk(dir: &Path, documents: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.flatten().collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') || name.starts_with("~$") {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            walk(&path, documents)?;
+        } else if name.to_ascii_lowercase().ends_with(".docx") {
+            documents.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Whether `name` matches a pattern where `*` stands for any text and `?`
+/// for any one character
+fn glob_match(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|skip| matches(rest, &name[skip..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+/// Search the documents in parallel on `threads` threads, or one per CPU
+/// for 0, keeping them in order
+pub fn grep_files(
+    paths: &[PathBuf],
+    pattern: &Regex,
+    options: &GrepOptions,
+    threads: usize,
+) -> Result<Vec<(PathBuf, Result<Vec<GrepMatch>>)>> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()?;
+    Ok(pool.install(|| {
+        paths
+            .par_iter()
+            .map(|path| {
+                let found = SeekableInput::open(path).and_then(|input| {
+                    let document = load_document(input.path(), ImageOptions::default())?;
+                    Ok(grep_document(&document, pattern, options))
+                });
+                (path.clone(), found)
+            })
+            .collect()
+    }))
+}
+
+/// Put the documents with the most occurrences of the pattern first, those
+/// with as many staying in the order they were found
+pub fn sort_by_relevance(files: &mut [GrepFile]) {
+    files.sort_by_key(|file| {
+        let occurrences: usize = file.matches.iter().map(|found| found.ranges.len()).sum();
+        std::cmp::Reverse(occurrences)
+    });
+}
+
+#[derive(Serialize)]
+struct JsonGrep<'a> {
+    file: &'a str,
+    pattern: &'a str,
+    matches: Vec<JsonMatch<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonGrepFiles<'a> {
+    pattern: &'a str,
+    files: Vec<JsonFile<'a>>,
+    summary: JsonSummary,
+}
+
+#[derive(Serialize)]
+struct JsonFile<'a> {
+    file: &'a str,
+    matches: Vec<JsonMatch<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonSummary {
+    files_searched: usize,
+    files_with_matches: usize,
+    matches: usize,
+}
+
+#[derive(Serialize)]
+struct JsonMatch<'a> {
+    element_index: usize,
+    headings: &'a [String],
+    text: &'a str,
+    /// Byte offsets of each match in `text`
+    matches: Vec<JsonRange>,
+    before: Vec<JsonContext<'a>>,
+    after: Vec<JsonContext<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonRange {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Serialize)]
+struct JsonContext<'a> {
+    element_index: usize,
+    text: &'a str,
+}
+
+/// Render the matches found in `file` in the given format
+pub fn render_grep(
+    file: &str,
+    pattern: &str,
+    matches: &[GrepMatch],
+    format: GrepFormat,
+    color: bool,
+) -> Result<String> {
+    Ok(match format {
+        GrepFormat::Text => render_text(matches, color),
+        GrepFormat::Markdown => render_markdown(pattern, matches),
+        GrepFormat::Json => {
+            let grep = JsonGrep {
+                file,
+                pattern,
+                matches: matches.iter().map(json_match).collect(),
+            };
+            serde_json::to_string_pretty(&grep)? + "\n"
+        }
+    })
+}
+
+/// Render the matches found across several documents, out of `searched`
+/// documents searched, with a count of the matches and the files they're in
+pub fn render_grep_files(
+    pattern: &str,
+    files: &[GrepFile],
+    searched: usize,
+    format: GrepFormat,
+    color: bool,
+) -> Result<String> {
+    let files: Vec<&GrepFile> = files
+        .iter()
+        .filter(|file| !file.matches.is_empty())
+        .collect();
+    let summary = JsonSummary {
+        files_searched: searched,
+        files_with_matches: files.len(),
+        matches: files.iter().map(|file| file.matches.len()).sum(),
+    };
+    let counted = format!(
+        "{} in {} of {} searched",
+        plural(summary.matches, "match", "matches"),
+        summary.files_with_matches,
+        plural(searched, "file", "files"),
+    );
+    Ok(match format {
+        GrepFormat::Text => {
+            let mut out = String::new();
+            for file in &files {
+                out.push_str(&render_file_lines(file, color));
+            }
+            if !files.is_empty() {
+                out.push('\n');
+            }
+            out + &counted + "\n"
+        }
+        GrepFormat::Markdown => {
+            let mut markdown = format!("# Matches for `{pattern}`\n\n");
+            for file in &files {
+                markdown.push_str(&format!("## {}\n\n", file.path));
+                markdown.push_str(&markdown_matches(&file.matches, "###"));
+            }
+            markdown + "_" + &counted + "_\n"
+        }
+        GrepFormat::Json => {
+            let grep = JsonGrepFiles {
+                pattern,
+                files: files
+                    .iter()
+                    .map(|file| JsonFile {
+                        file: &file.path,
+                        matches: file.matches.iter().map(json_match).collect(),
+                    })
+                    .collect(),
+                summary,
+            };
+            serde_json::to_string_pretty(&grep)? + "\n"
+        }
+    })
+}
+
+/// One `file:headings:snippet` line per match, and `file-headings-text` for context
+fn render_file_lines(file: &GrepFile, color: bool) -> String {
+    let paint = |style: &str, text: &str| match color {
+        true => format!("{style}{text}{RESET}"),
+        false => text.to_string(),
+    };
+    let line = |headings: &[String], separator: char, text: &str| {
+        format!(
+            "{}{separator}{}{separator}{text}\n",
+            paint(FILE_STYLE, &file.path),
+            paint(NUMBER_STYLE, &headings.join(" › "))
+        )
+    };
+    let mut out = String::new();
+    let mut printed = 0;
+    let context = file
+        .matches
+        .iter()
+        .any(|found| !found.before.is_empty() || !found.after.is_empty());
+    for (i, found) in file.matches.iter().enumerate() {
+        let first = found.position - found.before.len();
+        if context && printed > 0 && first > printed + 1 {
+            out.push_str("--\n");
+        }
+        for (position, context) in (first..).zip(&found.before) {
+            if position > printed {
+                out.push_str(&line(&found.headings, '-', &context.text));
+            }
+        }
+        let (text, ranges) = snippet(&found.text, &found.ranges);
+        let text = highlight(&text, &ranges, |text| paint(MATCH_STYLE, text));
+        out.push_str(&line(&found.headings, ':', &text));
+        printed = found.position;
+        let next = file
+            .matches
+            .get(i + 1)
+            .map_or(usize::MAX, |next| next.position);
+        for (position, context) in (found.position + 1..next).zip(&found.after) {
+            out.push_str(&line(&found.headings, '-', &context.text));
+            printed = position;
+        }
+    }
+    out
+}
+
+/// Up to [`SNIPPET_LENGTH`] bytes of `text` from a little before its first
+/// match, with `…` where it's cut, and the match ranges within it
+fn snippet(text: &str, ranges: &[std::ops::Range<usize>]) -> (String, Vec<std::ops::Range<usize>>) {
+    let floor = |mut index: usize| {
+        while !text.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    };
+    let first = ranges.first().map_or(0, |range| range.start);
+    let start = floor(first.saturating_sub(SNIPPET_LEAD));
+    let end = floor((start + SNIPPET_LENGTH).min(text.len()));
+    let lead = if start > 0 { "…" } else { "" };
+    let tail = if end < text.len() { "…" } else { "" };
+    let shown = ranges
+        .iter()
+        .filter(|range| range.start >= start && range.end <= end)
+        .map(|range| range.start - start + lead.len()..range.end - start + lead.len())
+        .collect();
+    (format!("{lead}{}{tail}", &text[start..end]), shown)
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    match count {
+        1 => format!("1 {one}"),
+        n => format!("{n} {many}"),
+    }
+}
+
+fn render_text(matches: &[GrepMatch], color: bool) -> String {
+    let paint = |style: &str, text: &str| match color {
+        true => format!("{style}{text}{RESET}"),
+        false => text.to_string(),
+    };
+    let mut out = String::new();
+    let mut headings: Option<&[String]> = None;
+    // Position of the last text printed, so overlapping context prints once
+    let mut printed = 0;
+    // As in grep, `--` only separates matches shown with context
+    let context = matches
+        .iter()
+        .any(|found| !found.before.is_empty() || !found.after.is_empty());
+    for (i, found) in matches.iter().enumerate() {
+        let first = found.position - found.before.len();
+        if context && printed > 0 && first > printed + 1 {
+            out.push_str("--\n");
+        }
+        if headings != Some(found.headings.as_slice()) {
+            if !found.headings.is_empty() {
+                out.push_str(&paint(HEADING_STYLE, &found.headings.join(" › ")));
+                out.push('\n');
+            }
+            headings = Some(&found.headings);
+        }
+        let line = |number: usize, separator: char, text: &str| {
+            format!(
+                "{}{separator}{text}\n",
+                paint(NUMBER_STYLE, &number.to_string())
+            )
+        };
+        for (position, context) in (first..).zip(&found.before) {
+            if position > printed {
+                out.push_str(&line(context.element_index, '-', &context.text));
+            }
+        }
+        let text = highlight(&found.text, &found.ranges, |text| paint(MATCH_STYLE, text));
+        out.push_str(&line(found.element_index, ':', &text));
+        printed = found.position;
+        // Context running into the next match is printed with it
+        let next = matches.get(i + 1).map_or(usize::MAX, |next| next.position);
+        for (position, context) in (found.position + 1..next).zip(&found.after) {
+            out.push_str(&line(context.element_index, '-', &context.text));
+            printed = position;
+        }
+    }
+    out
+}
+
+fn render_markdown(pattern: &str, matches: &[GrepMatch]) -> String {
+    let mut markdown = format!("# Matches for `{pattern}`\n\n");
+    if matches.is_empty() {
+        markdown.push_str("No matches found.\n");
+    }
+    markdown + &markdown_matches(matches, "##")
+}
+
+/// Each match and its context quoted under its heading path
+fn markdown_matches(matches: &[GrepMatch], heading: &str) -> String {
+    let mut markdown = String::new();
+    for found in matches {
+        let path = match found.headings.is_empty() {
+            true => "Before the first heading".to_string(),
+            false => found.headings.join(" › "),
+        };
+        markdown.push_str(&format!("{heading} {path}\n\n"));
+        let text = highlight(&found.text, &found.ranges, |text| format!("**{text}**"));
+        let quoted: Vec<&str> = found
+            .before
+            .iter()
+            .map(|context| context.text.as_str())
+            .chain([text.as_str()])
+            .chain(found.after.iter().map(|context| context.text.as_str()))
+            .collect();
+        markdown.push_str(&format!("> {}\n\n", quoted.join("\n>\n> ")));
+    }
+    markdown
+}
+
+/// The text with each match passed through `mark`
+fn highlight(
+    text: &str,
+    ranges: &[std::ops::Range<usize>],
+    mark: impl Fn(&str) -> String,
+) -> String {
+    let mut out = String::new();
+    let mut end = 0;
+    for range in ranges {
+        out.push_str(&text[end..range.start]);
+        out.push_str(&mark(&text[range.clone()]));
+        end = range.end;
+    }
+    out.push_str(&text[end..]);
+    out
+}
+
+fn json_match(found: &GrepMatch) -> JsonMatch<'_> {
+    JsonMatch {
+        element_index: found.element_index,
+        headings: &found.headings,
+        text: &found.text,
+        matches: found
+            .ranges
+            .iter()
+            .map(|range| JsonRange {
+                start: range.start,
+                end: range.end,
+            })
+            .collect(),
+        before: json_context(&found.before),
+        after: json_context(&found.after),
+    }
+}
+
+fn json_context(texts: &[SearchableText]) -> Vec<JsonContext<'_>> {
+    texts
+        .iter()
+        .map(|context| JsonContext {
+            element_index: context.element_index,
+            text: &context.text,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_stars_and_question_marks() {
+        assert!(glob_match("*.docx", "notes.docx"));
+        assert!(glob_match("2024-??-*.docx", "2024-03-sync.docx"));
+        assert!(!glob_match("2024-??-*.docx", "2024-3-sync.docx"));
+        assert!(!glob_match("*.docx", "notes.docx.bak"));
+    }
+
+    #[test]
+    fn test_snippets_start_a_little_before_the_match() {
+        let text = format!("{}needle{}", "é".repeat(40), "x".repeat(200));
+        let start = text.find("needle").unwrap();
+        let needle = std::ops::Range {
+            start,
+            end: start + 6,
+        };
+        let (snippet, ranges) = snippet(&text, &[needle]);
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert_eq!(&snippet[ranges[0].clone()], "needle");
+    }
+}
@@ -0,0 +1,430 @@
+//! HTML export
+//!
+//! A self-contained HTML5 page with no stylesheet or scripts, so it can be
+//! dropped into a preview pane or styled by the page that embeds it. As in
+//! Markdown export, the document title is the `h1` and Word headings start at
+//! `h2`. Equations stay as LaTeX between `\(` `\)` or `\[` `\]`, the
+//! delimiters MathJax and KaTeX look for.
+
+use std::collections::HashMap;
+
+use crate::document::*;
+
+use super::{
+    bookmark_anchors, cell_runs, equation_anchor, equation_anchors, heading_display_text,
+    table_of_contents_with, tagged_latex, ExportOptions, CONTENTS_HEADING,
+};
+
+/// Render the whole document as one HTML page
+pub fn render_html(document: &Document) -> String {
+    render_html_with_options(document, &ExportOptions::default())
+}
+
+/// Render the whole document as one HTML page, with a table of contents
+/// linking to ids on the headings if `options.toc` is set, and links to the
+/// enclosing sections above each nested heading if `options.breadcrumbs` is
+///
+/// Headings also get ids when cross-references in the text link to them.
+pub fn render_html_with_options(document: &Document, options: &ExportOptions) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n");
+    match &document.metadata.language {
+        Some(language) => html.push_str(&format!("<html lang=\"{}\">\n", escape(&language.tag))),
+        None => html.push_str("<html>\n"),
+    }
+    html.push_str("<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(&document.title)));
+    if let Some(author) = &document.metadata.author {
+        html.push_str(&format!(
+            "<meta name=\"author\" content=\"{}\">\n",
+            escape(author)
+        ));
+    }
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape(&document.title)));
+
+    let headings = table_of_contents_with(document, options.toc, options.anchors);
+    let equations = equation_anchors(document);
+    let links = bookmark_anchors(document, &headings, &equations);
+    if options.toc && !headings.is_empty() {
+        html.push_str(&contents_to_html(&headings));
+    }
+    let mut anchors = headings.iter();
+    // Open sections above the current heading: (level, title, anchor)
+    let mut section_stack: Vec<(u8, String, &str)> = Vec::new();
+    let with_ids = options.toc || options.breadcrumbs || !links.is_empty();
+    for element in &document.elements {
+        match (element, with_ids) {
+            (DocumentElement::Heading { level, .. }, true) => {
+                let tag = heading_tag(*level);
+                let text = heading_display_text(element).unwrap_or_default();
+                let id = anchors.next().map(|entry| entry.anchor.as_str());
+                // As in Markdown export, a heading closes any open section at
+                // its own level or deeper
+                section_stack.retain(|(open_level, _, _)| open_level < level);
+                if options.breadcrumbs && !section_stack.is_empty() {
+                    html.push_str(&breadcrumbs_to_html(&section_stack));
+                }
+                section_stack.push((*level, text.clone(), id.unwrap_or_default()));
+                html.push_str(&format!(
+                    "<{tag} id=\"{}\">{}</{tag}>\n",
+                    escape(id.unwrap_or_default()),
+                    escape(&text)
+                ));
+            }
+            _ => html.push_str(&element_to_html(element, &links)),
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// HTML for a single element, ending with a newline
+fn element_to_html(element: &DocumentElement, links: &HashMap<&str, &str>) -> String {
+    match element {
+        DocumentElement::Heading { level, .. } => {
+            let tag = heading_tag(*level);
+            let text = heading_display_text(element).unwrap_or_default();
+            format!("<{tag}>{}</{tag}>\n", escape(&text))
+        }
+        DocumentElement::Paragraph {
+            runs,
+            alignment,
+            shading,
+            rtl,
+            ..
+        } => {
+            let text = runs_to_html(runs, links);
+            if text.trim().is_empty() {
+                return String::new();
+            }
+            let mut styles = Vec::new();
+            if let Some(align) = css_alignment(*alignment) {
+                styles.push(format!("text-align: {align}"));
+            }
+            if let Some(fill) = shading.as_deref().and_then(css_color) {
+                styles.push(format!("background-color: {fill}"));
+            }
+            let dir = if *rtl { " dir=\"rtl\"" } else { "" };
+            format!("<p{dir}{}>{text}</p>\n", style_attribute(&styles))
+        }
+        DocumentElement::BlockQuote { runs } => {
+            format!(
+                "<blockquote>\n<p>{}</p>\n</blockquote>\n",
+                runs_to_html(runs, links)
+            )
+        }
+        DocumentElement::CodeBlock { code } => {
+            format!("<pre><code>{}</code></pre>\n", escape(code))
+        }
+        DocumentElement::List { items, ordered } => list_to_html(items, *ordered, links),
+        DocumentElement::Table { table } => table_to_html(table, links),
+        DocumentElement::Image {
+            description,
+            width,
+            height,
+            image_path,
+            caption,
+            placement,
+            ..
+        } => match image_path {
+            Some(path) => {
+                let mut attributes = format!(
+                    "src=\"{}\" alt=\"{}\"",
+                    escape(&path.to_string_lossy()),
+                    escape(description)
+                );
+                if let (Some(w), Some(h)) = (width, height) {
+                    attributes.push_str(&format!(" width=\"{w}\" height=\"{h}\""));
+                }
+                // Where a floating image was, and the side text flowed around
+                if let Some(note) = placement.as_ref().and_then(ImagePlacement::describe) {
+                    attributes.push_str(&format!(" title=\"{}\"", escape(&note)));
+                }
+                let float: Vec<String> = placement
+                    .as_ref()
+                    .and_then(ImagePlacement::float_side)
+                    .map(|side| format!("float: {side}"))
+                    .into_iter()
+                    .collect();
+                let style = style_attribute(&float);
+                match caption {
+                    Some(caption) => format!(
+                        "<figure{style}>\n<img {attributes}>\n<figcaption>{}</figcaption>\n</figure>\n",
+                        escape(caption)
+                    ),
+                    None => format!("<p{style}><img {attributes}></p>\n"),
+                }
+            }
+            // Nothing was extracted to point at
+            None => format!(
+                "<p>[Image: {}]</p>\n",
+                escape(caption.as_ref().unwrap_or(description))
+            ),
+        },
+        DocumentElement::Equation {
+            latex,
+            fallback,
+            number,
+        } => {
+            let id = number
+                .as_deref()
+                .map(|number| format!(" id=\"{}\"", equation_anchor(number)))
+                .unwrap_or_default();
+            if latex.trim().is_empty() {
+                if fallback.trim().is_empty() {
+                    return String::new();
+                }
+                let number = number
+                    .as_deref()
+                    .map(|number| format!(" {}", escape(number)))
+                    .unwrap_or_default();
+                return format!("<p{id}>{}{number}</p>\n", escape(fallback.trim()));
+            }
+            let latex = tagged_latex(latex.trim(), number.as_deref());
+            format!("<div class=\"math\"{id}>\\[{}\\]</div>\n", escape(&latex))
+        }
+        DocumentElement::PageBreak { page } => {
+            format!("<hr class=\"page-break\" aria-label=\"Page {page}\">\n")
+        }
+        DocumentElement::HorizontalRule => "<hr>\n".to_string(),
+        DocumentElement::FileBoundary { name } => {
+            format!(
+                "<hr class=\"file-boundary\" aria-label=\"{}\">\n",
+                escape(name)
+            )
+        }
+    }
+}
+
+/// The tag for a heading level; the document title is the `h1`
+fn heading_tag(level: u8) -> String {
+    format!("h{}", (level as usize + 1).clamp(2, 6))
+}
+
+/// Links to the sections a heading is nested in, outermost first, in a `nav`
+fn breadcrumbs_to_html(sections: &[(u8, String, &str)]) -> String {
+    let trail: Vec<String> = sections
+        .iter()
+        .map(|(_, title, anchor)| format!("<a href=\"#{}\">{}</a>", escape(anchor), escape(title)))
+        .collect();
+    format!("<nav class=\"breadcrumbs\">{}</nav>\n", trail.join(" › "))
+}
+
+/// The table of contents as nested lists of links, in a `nav`
+fn contents_to_html(headings: &[super::TocEntry]) -> String {
+    let mut html = format!("<nav class=\"toc\">\n<h2>{CONTENTS_HEADING}</h2>\n");
+    // Levels of the lists open around the current entry
+    let mut open: Vec<u8> = Vec::new();
+    for entry in headings {
+        while open.last().is_some_and(|&level| level > entry.level) {
+            open.pop();
+            html.push_str("</li>\n</ul>\n");
+        }
+        match open.last() {
+            Some(&level) if level == entry.level => html.push_str("</li>\n"),
+            _ => {
+                open.push(entry.level);
+                html.push_str("<ul>\n");
+            }
+        }
+        html.push_str(&format!(
+            "<li><a href=\"#{}\">{}</a>",
+            escape(&entry.anchor),
+            escape(&entry.title)
+        ));
+    }
+    for _ in open {
+        html.push_str("</li>\n</ul>\n");
+    }
+    html.push_str("</nav>\n");
+    html
+}
+
+fn runs_to_html(runs: &[FormattedRun], links: &HashMap<&str, &str>) -> String {
+    runs.iter().map(|run| run_to_html(run, links)).collect()
+}
+
+/// HTML for a single run, innermost tag first: colour, highlight, vertical
+/// position, emphasis, then a link to the heading a cross-reference names
+fn run_to_html(run: &FormattedRun, links: &HashMap<&str, &str>) -> String {
+    if let Some(latex) = run
+        .text
+        .strip_prefix('$')
+        .and_then(|text| text.strip_suffix('$'))
+        .filter(|latex| !latex.is_empty())
+    {
+        return format!("<span class=\"math\">\\({}\\)</span>", escape(latex));
+    }
+
+    let formatting = &run.formatting;
+    let mut html = escape(&formatting.apply_caps(&run.text)).replace('\n', "<br>\n");
+    if html.is_empty() {
+        return html;
+    }
+
+    if let Some(color) = formatting.color.as_deref().and_then(css_color) {
+        html = format!("<span style=\"color: {color}\">{html}</span>");
+    }
+    if let Some(background) = formatting.background_hex().and_then(css_color) {
+        html = format!("<mark style=\"background-color: {background}\">{html}</mark>");
+    }
+    match formatting.vertical_align {
+        VerticalAlign::Superscript => html = format!("<sup>{html}</sup>"),
+        VerticalAlign::Subscript => html = format!("<sub>{html}</sub>"),
+        VerticalAlign::Baseline => {}
+    }
+    for (on, tag) in [
+        (formatting.underline, "u"),
+        (formatting.strikethrough, "s"),
+        (formatting.italic, "em"),
+        (formatting.bold, "strong"),
+    ] {
+        if on {
+            html = format!("<{tag}>{html}</{tag}>");
+        }
+    }
+    if let Some(anchor) = formatting
+        .bookmark_link
+        .as_deref()
+        .and_then(|name| links.get(name))
+    {
+        html = format!("<a href=\"#{}\">{html}</a>", escape(anchor));
+    }
+    if formatting.rtl {
+        html = format!("<span dir=\"rtl\">{html}</span>");
+    }
+    html
+}
+
+/// Nested `ul` or `ol` elements, each sublist inside the item before it
+///
+/// An item may only go one level deeper than the one before it, since there's
+/// no item to hang a deeper list from.
+fn list_to_html(items: &[ListItem], ordered: bool, links: &HashMap<&str, &str>) -> String {
+    let tag = if ordered { "ol" } else { "ul" };
+    let mut html = String::new();
+    // Lists currently open; the innermost item of each is open too
+    let mut depth = 0;
+    for item in items {
+        let level = (item.level as usize + 1).min(depth + 1);
+        if level > depth {
+            html.push_str(&format!("<{tag}>\n"));
+            depth = level;
+        } else {
+            html.push_str("</li>\n");
+            while depth > level {
+                html.push_str(&format!("</{tag}>\n</li>\n"));
+                depth -= 1;
+            }
+        }
+        html.push_str(&format!("<li>{}", runs_to_html(&item.runs, links)));
+    }
+    while depth > 0 {
+        html.push_str(&format!("</li>\n</{tag}>\n"));
+        depth -= 1;
+    }
+    html
+}
+
+fn table_to_html(table: &TableData, links: &HashMap<&str, &str>) -> String {
+    let cell = |tag: &str, cell: &TableCell| {
+        let styles: Vec<String> = css_alignment(cell.alignment)
+            .map(|align| format!("text-align: {align}"))
+            .into_iter()
+            .collect();
+        let content = runs_to_html(&cell_runs(cell, tag == "th"), links);
+        format!("<{tag}{}>{content}</{tag}>", style_attribute(&styles))
+    };
+
+    let mut html = String::from("<table>\n");
+    if let Some(title) = &table.metadata.title {
+        html.push_str(&format!("<caption>{}</caption>\n", escape(title)));
+    }
+    if !table.headers.is_empty() {
+        let cells: String = table.headers.iter().map(|c| cell("th", c)).collect();
+        html.push_str(&format!("<thead>\n<tr>{cells}</tr>\n</thead>\n"));
+    }
+    html.push_str("<tbody>\n");
+    for row in &table.rows {
+        let cells: String = row.iter().map(|c| cell("td", c)).collect();
+        html.push_str(&format!("<tr>{cells}</tr>\n"));
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+/// CSS `text-align` value, or `None` for the default left alignment
+fn css_alignment(alignment: TextAlignment) -> Option<&'static str> {
+    match alignment {
+        TextAlignment::Left => None,
+        TextAlignment::Center => Some("center"),
+        TextAlignment::Right => Some("right"),
+        TextAlignment::Justify => Some("justify"),
+    }
+}
+
+fn style_attribute(styles: &[String]) -> String {
+    if styles.is_empty() {
+        String::new()
+    } else {
+        format!(" style=\"{}\"", styles.join("; "))
+    }
+}
+
+/// A Word colour as CSS, or `None` for `auto` and anything else that isn't hex
+fn css_color(color: &str) -> Option<String> {
+    let hex = color.trim_start_matches('#');
+    (hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| format!("#{hex}"))
+}
+
+/// Escape text for use in element content and quoted attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str, level: u8) -> ListItem {
+        ListItem {
+            runs: vec![FormattedRun {
+                text: text.to_string(),
+                formatting: TextFormatting::default(),
+            }],
+            level,
+        }
+    }
+
+    #[test]
+    fn test_nested_lists_hang_from_their_parent_item() {
+        let items = [item("a", 0), item("b", 1), item("c", 0)];
+        assert_eq!(
+            list_to_html(&items, false, &HashMap::new()),
+            "<ul>\n<li>a<ul>\n<li>b</li>\n</ul>\n</li>\n<li>c</li>\n</ul>\n"
+        );
+
+        // A jump of two levels only goes one deeper
+        let items = [item("a", 0), item("b", 2)];
+        assert_eq!(
+            list_to_html(&items, true, &HashMap::new()),
+            "<ol>\n<li>a<ol>\n<li>b</li>\n</ol>\n</li>\n</ol>\n"
+        );
+    }
+
+    #[test]
+    fn test_text_is_escaped() {
+        assert_eq!(escape("a < b & \"c\""), "a &lt; b &amp; &quot;c&quot;");
+    }
+}
@@ -0,0 +1,101 @@
+//! Images in exported documents
+//!
+//! Images are extracted to a temporary directory when the document is loaded.
+//! Markdown, HTML, reStructuredText and AsciiDoc exports can point at them
+//! there, copy them into an assets directory beside the output, or carry them
+//! inline as base64 `data:` URIs so the export stands on its own.
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use crate::{document::*, image_extractor::ImageFormat, ImageExportMode};
+
+/// The document with its image paths set to what the export should reference
+///
+/// `output` is the file being written, if any, and `chunk_dir` the directory
+/// Markdown chunks are written to; copied images go beside whichever it is.
+pub fn place_images<'a>(
+    document: &'a Document,
+    mode: ImageExportMode,
+    output: Option<&Path>,
+    chunk_dir: Option<&Path>,
+) -> Result<Cow<'a, Document>> {
+    let extracted = document.elements.iter().any(|element| {
+        matches!(
+            element,
+            DocumentElement::Image {
+                image_path: Some(_),
+                ..
+            }
+        )
+    });
+    if mode == ImageExportMode::Link || !extracted {
+        return Ok(Cow::Borrowed(document));
+    }
+
+    let (assets, reference) = assets_location(output, chunk_dir, &document.title);
+    if mode == ImageExportMode::Copy {
+        std::fs::create_dir_all(&assets)
+            .with_context(|| format!("Failed to create {}", assets.display()))?;
+    }
+
+    let mut document = document.clone();
+    for element in &mut document.elements {
+        let DocumentElement::Image {
+            image_path: Some(path),
+            ..
+        } = element
+        else {
+            continue;
+        };
+        *path = match mode {
+            ImageExportMode::Copy => {
+                let name = path
+                    .file_name()
+                    .with_context(|| format!("Image path {} has no file name", path.display()))?;
+                std::fs::copy(&path, assets.join(name))
+                    .with_context(|| format!("Failed to copy {}", path.display()))?;
+                reference.join(name)
+            }
+            ImageExportMode::Embed => PathBuf::from(data_uri(path)?),
+            ImageExportMode::Link => continue,
+        };
+    }
+    Ok(Cow::Owned(document))
+}
+
+/// Where copied images go, and the relative path the export refers to them by
+///
+/// Beside an output file they go in `<stem>_assets`; with Markdown chunks, in
+/// `assets` among the chunks; on stdout, in `<title>_assets` in the current
+/// directory.
+fn assets_location(
+    output: Option<&Path>,
+    chunk_dir: Option<&Path>,
+    title: &str,
+) -> (PathBuf, PathBuf) {
+    match (output, chunk_dir) {
+        (Some(output), _) => {
+            let stem = output
+                .file_stem()
+                .map_or(Cow::Borrowed(title), |stem| stem.to_string_lossy());
+            let name = format!("{stem}_assets");
+            (output.with_file_name(&name), PathBuf::from(name))
+        }
+        (None, Some(chunk_dir)) => (chunk_dir.join("assets"), PathBuf::from("assets")),
+        (None, None) => {
+            let name = format!("{title}_assets");
+            (PathBuf::from(&name), PathBuf::from(name))
+        }
+    }
+}
+
+/// An image file as a base64 `data:` URI
+fn data_uri(path: &Path) -> Result<String> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mime = ImageFormat::from_filename(&path.to_string_lossy())
+        .map_or("application/octet-stream", |format| format.mime_type());
+    Ok(format!("data:{mime};base64,{}", STANDARD.encode(data)))
+}
@@ -0,0 +1,568 @@
+//! Stable, versioned JSON export
+//!
+//! `--export json` writes this schema instead of serializing the internal
+//! document model, so the model can change without breaking downstream tools.
+//! Incompatible changes bump [`SCHEMA_VERSION`]; new fields may be added within
+//! a version, so consumers should ignore keys they don't know.
+//!
+//! The top level holds `schema_version`, `title`, `metadata` and `elements`.
+//! Every element carries a `type` tag: `heading`, `paragraph`, `list`,
+//! `block_quote`, `code_block`, `table`, `image`, `equation`, `page_break` or
+//! `horizontal_rule`; a page break's `page` is the page it starts. Enum values are always snake_case:
+//! alignments are `left`, `center`, `right` or `justify`, paragraph directions
+//! are `ltr` or `rtl`, run vertical
+//! alignments are `baseline`, `superscript` or `subscript`, and table cell data
+//! types are `text`, `number`, `currency`, `percentage`, `date`, `boolean` or
+//! `empty`. A numbered equation has its `number`, as "(3.2)". A cell's formatting is that of its first run; a cell whose
+//! formatting changes part way through also lists its `runs`.
+//! A floating image's `placement.anchor` gives what it's positioned
+//! against as the OOXML names in snake_case (`page`, `margin`, `paragraph`,
+//! `left_margin`, …) and its `wrap` as `square`, `tight`, `through`,
+//! `top_and_bottom`, `behind_text` or `in_front_of_text`. Object keys are snake_case, or camelCase with [`JsonCase::Camel`].
+//!
+//! `--export ndjson` writes the same schema a line at a time, for pipelines:
+//! see [`write_ndjson`].
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::Write;
+
+use crate::{document::*, JsonCase};
+
+/// Version of the JSON export schema
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonDocument<'a> {
+    schema_version: u32,
+    title: &'a str,
+    metadata: JsonMetadata<'a>,
+    elements: Vec<JsonElement<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonMetadata<'a> {
+    file_path: &'a str,
+    file_size: u64,
+    word_count: usize,
+    page_count: usize,
+    created: Option<&'a str>,
+    modified: Option<&'a str>,
+    author: Option<&'a str>,
+    language: Option<JsonLanguage<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protection: Option<&'a Protection>,
+}
+
+#[derive(Serialize)]
+struct JsonLanguage<'a> {
+    tag: &'a str,
+    detected: bool,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonElement<'a> {
+    Heading {
+        level: u8,
+        text: &'a str,
+        number: Option<&'a str>,
+    },
+    Paragraph {
+        alignment: JsonAlignment,
+        direction: JsonDirection,
+        shading: Option<&'a str>,
+        runs: Vec<JsonRun<'a>>,
+    },
+    List {
+        ordered: bool,
+        items: Vec<JsonListItem<'a>>,
+    },
+    BlockQuote {
+        runs: Vec<JsonRun<'a>>,
+    },
+    CodeBlock {
+        code: &'a str,
+    },
+    Table {
+        title: Option<&'a str>,
+        column_alignments: Vec<JsonAlignment>,
+        headers: Vec<JsonCell<'a>>,
+        rows: Vec<Vec<JsonCell<'a>>>,
+    },
+    Image {
+        description: &'a str,
+        caption: Option<&'a str>,
+        width: Option<u32>,
+        height: Option<u32>,
+        relationship_id: Option<&'a str>,
+        path: Option<String>,
+        placement: Option<JsonPlacement<'a>>,
+    },
+    Equation {
+        latex: &'a str,
+        fallback: &'a str,
+        /// Only for numbered equations
+        #[serde(skip_serializing_if = "Option::is_none")]
+        number: Option<&'a str>,
+    },
+    PageBreak {
+        page: usize,
+    },
+    HorizontalRule,
+    FileBoundary {
+        name: &'a str,
+    },
+}
+
+#[derive(Serialize)]
+struct JsonRun<'a> {
+    text: &'a str,
+    #[serde(flatten)]
+    formatting: JsonFormatting<'a>,
+}
+
+#[derive(Serialize)]
+struct JsonFormatting<'a> {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+    font_size: Option<f32>,
+    color: Option<&'a str>,
+    vertical_align: JsonVerticalAlign,
+    all_caps: bool,
+    small_caps: bool,
+    highlight: Option<&'a str>,
+    background: Option<&'a str>,
+    rtl: bool,
+}
+
+#[derive(Serialize)]
+struct JsonListItem<'a> {
+    level: u8,
+    runs: Vec<JsonRun<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonCell<'a> {
+    text: &'a str,
+    alignment: JsonAlignment,
+    data_type: JsonDataType,
+    #[serde(flatten)]
+    formatting: JsonFormatting<'a>,
+    /// Only for cells whose formatting changes part way through
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    runs: Vec<JsonRun<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonPlacement<'a> {
+    columns: u16,
+    rows: u16,
+    anchor: Option<JsonAnchor<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonAnchor<'a> {
+    relative_h: String,
+    relative_v: String,
+    align_h: Option<&'a str>,
+    align_v: Option<&'a str>,
+    x: i32,
+    y: i32,
+    wrap: JsonWrap,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum JsonWrap {
+    Square,
+    Tight,
+    Through,
+    TopAndBottom,
+    BehindText,
+    InFrontOfText,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum JsonAlignment {
+    Left,
+    Center,
+    Right,
+    Justify,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum JsonDirection {
+    Ltr,
+    Rtl,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum JsonVerticalAlign {
+    Baseline,
+    Superscript,
+    Subscript,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum JsonDataType {
+    Text,
+    Number,
+    Currency,
+    Percentage,
+    Date,
+    Boolean,
+    Empty,
+}
+
+impl<'a> From<&'a ImagePlacement> for JsonPlacement<'a> {
+    fn from(placement: &'a ImagePlacement) -> Self {
+        // "leftMargin" → "left_margin"
+        let snake_case = |name: &str| {
+            let mut snake = String::new();
+            for c in name.chars() {
+                if c.is_uppercase() {
+                    snake.push('_');
+                }
+                snake.push(c.to_ascii_lowercase());
+            }
+            snake
+        };
+        Self {
+            columns: placement.columns,
+            rows: placement.rows,
+            anchor: placement.anchor.as_ref().map(|anchor| JsonAnchor {
+                relative_h: snake_case(&anchor.relative_h),
+                relative_v: snake_case(&anchor.relative_v),
+                align_h: anchor.align_h.as_deref(),
+                align_v: anchor.align_v.as_deref(),
+                x: anchor.x,
+                y: anchor.y,
+                wrap: match anchor.wrap {
+                    TextWrap::Square => JsonWrap::Square,
+                    TextWrap::Tight => JsonWrap::Tight,
+                    TextWrap::Through => JsonWrap::Through,
+                    TextWrap::TopAndBottom => JsonWrap::TopAndBottom,
+                    TextWrap::BehindText => JsonWrap::BehindText,
+                    TextWrap::InFrontOfText => JsonWrap::InFrontOfText,
+                },
+            }),
+        }
+    }
+}
+
+impl From<TextAlignment> for JsonAlignment {
+    fn from(alignment: TextAlignment) -> Self {
+        match alignment {
+            TextAlignment::Left => Self::Left,
+            TextAlignment::Center => Self::Center,
+            TextAlignment::Right => Self::Right,
+            TextAlignment::Justify => Self::Justify,
+        }
+    }
+}
+
+impl From<VerticalAlign> for JsonVerticalAlign {
+    fn from(vertical_align: VerticalAlign) -> Self {
+        match vertical_align {
+            VerticalAlign::Baseline => Self::Baseline,
+            VerticalAlign::Superscript => Self::Superscript,
+            VerticalAlign::Subscript => Self::Subscript,
+        }
+    }
+}
+
+impl From<CellDataType> for JsonDataType {
+    fn from(data_type: CellDataType) -> Self {
+        match data_type {
+            CellDataType::Text => Self::Text,
+            CellDataType::Number => Self::Number,
+            CellDataType::Currency => Self::Currency,
+            CellDataType::Percentage => Self::Percentage,
+            CellDataType::Date => Self::Date,
+            CellDataType::Boolean => Self::Boolean,
+            CellDataType::Empty => Self::Empty,
+        }
+    }
+}
+
+impl<'a> From<&'a TextFormatting> for JsonFormatting<'a> {
+    fn from(formatting: &'a TextFormatting) -> Self {
+        Self {
+            bold: formatting.bold,
+            italic: formatting.italic,
+            underline: formatting.underline,
+            strikethrough: formatting.strikethrough,
+            font_size: formatting.font_size,
+            color: formatting.color.as_deref(),
+            vertical_align: formatting.vertical_align.into(),
+            all_caps: formatting.all_caps,
+            small_caps: formatting.small_caps,
+            highlight: formatting.highlight.as_deref(),
+            background: formatting.background.as_deref(),
+            rtl: formatting.rtl,
+        }
+    }
+}
+
+fn runs(runs: &[FormattedRun]) -> Vec<JsonRun<'_>> {
+    runs.iter()
+        .map(|run| JsonRun {
+            text: &run.text,
+            formatting: (&run.formatting).into(),
+        })
+        .collect()
+}
+
+fn cell(cell: &TableCell) -> JsonCell<'_> {
+    JsonCell {
+        text: &cell.content,
+        alignment: cell.alignment.into(),
+        data_type: cell.data_type.into(),
+        formatting: (&cell.formatting).into(),
+        runs: runs(&cell.runs),
+    }
+}
+
+fn element(element: &DocumentElement) -> JsonElement<'_> {
+    match element {
+        DocumentElement::Heading {
+            level,
+            text,
+            number,
+        } => JsonElement::Heading {
+            level: *level,
+            text,
+            number: number.as_deref(),
+        },
+        DocumentElement::Paragraph {
+            runs: paragraph_runs,
+            alignment,
+            shading,
+            rtl,
+            ..
+        } => JsonElement::Paragraph {
+            alignment: (*alignment).into(),
+            direction: if *rtl {
+                JsonDirection::Rtl
+            } else {
+                JsonDirection::Ltr
+            },
+            shading: shading.as_deref(),
+            runs: runs(paragraph_runs),
+        },
+        DocumentElement::List { items, ordered } => JsonElement::List {
+            ordered: *ordered,
+            items: items
+                .iter()
+                .map(|item| JsonListItem {
+                    level: item.level,
+                    runs: runs(&item.runs),
+                })
+                .collect(),
+        },
+        DocumentElement::BlockQuote { runs: quote_runs } => JsonElement::BlockQuote {
+            runs: runs(quote_runs),
+        },
+        DocumentElement::CodeBlock { code } => JsonElement::CodeBlock { code },
+        DocumentElement::Table { table } => JsonElement::Table {
+            title: table.metadata.title.as_deref(),
+            column_alignments: table
+                .metadata
+                .column_alignments
+                .iter()
+                .map(|&alignment| alignment.into())
+                .collect(),
+            headers: table.headers.iter().map(cell).collect(),
+            rows: table
+                .rows
+                .iter()
+                .map(|row| row.iter().map(cell).collect())
+                .collect(),
+        },
+        DocumentElement::Image {
+            description,
+            width,
+            height,
+            relationship_id,
+            image_path,
+            caption,
+            placement,
+        } => JsonElement::Image {
+            description,
+            caption: caption.as_deref(),
+            width: *width,
+            height: *height,
+            relationship_id: relationship_id.as_deref(),
+            path: image_path
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned()),
+            placement: placement.as_ref().map(JsonPlacement::from),
+        },
+        DocumentElement::Equation {
+            latex,
+            fallback,
+            number,
+        } => JsonElement::Equation {
+            latex,
+            fallback,
+            number: number.as_deref(),
+        },
+        DocumentElement::PageBreak { page } => JsonElement::PageBreak { page: *page },
+        DocumentElement::FileBoundary { name } => JsonElement::FileBoundary { name },
+        DocumentElement::HorizontalRule => JsonElement::HorizontalRule,
+    }
+}
+
+fn metadata(metadata: &DocumentMetadata) -> JsonMetadata<'_> {
+    JsonMetadata {
+        file_path: &metadata.file_path,
+        file_size: metadata.file_size,
+        word_count: metadata.word_count,
+        page_count: metadata.page_count,
+        created: metadata.created.as_deref(),
+        modified: metadata.modified.as_deref(),
+        author: metadata.author.as_deref(),
+        language: metadata.language.as_ref().map(|language| JsonLanguage {
+            tag: &language.tag,
+            detected: language.detected,
+        }),
+        protection: metadata.protection.as_ref(),
+    }
+}
+
+/// Build the export schema for a document as a JSON value
+pub fn to_json_value(document: &Document, case: JsonCase) -> Result<Value> {
+    let export = JsonDocument {
+        schema_version: SCHEMA_VERSION,
+        title: &document.title,
+        metadata: metadata(&document.metadata),
+        elements: document.elements.iter().map(element).collect(),
+    };
+
+    let value = serde_json::to_value(export)?;
+    Ok(match case {
+        JsonCase::Snake => value,
+        JsonCase::Camel => camel_case_keys(value),
+    })
+}
+
+/// The first line of NDJSON export
+#[derive(Serialize)]
+#[serde(tag = "type", rename = "document")]
+struct NdjsonHeader<'a> {
+    schema_version: u32,
+    title: &'a str,
+    metadata: JsonMetadata<'a>,
+}
+
+/// A line of NDJSON export after the first
+#[derive(Serialize)]
+struct NdjsonElement<'a> {
+    index: usize,
+    #[serde(flatten)]
+    element: JsonElement<'a>,
+}
+
+/// NDJSON export: a `document` line with the schema version, title and
+/// metadata, then one line per element with its `index`
+///
+/// Each line is the same object [`to_json_value`] puts in `elements`. The
+/// document is loaded in full first; only the output is line-delimited, each
+/// line written as it's serialized rather than built up as one string.
+/// Elements can't be written as they're parsed: docx-rs reads the whole body
+/// before the loader sees any of it, and lists, captions and display equations
+/// are settled over the whole element list.
+pub fn write_ndjson(out: &mut impl Write, document: &Document, case: JsonCase) -> Result<()> {
+    let header = NdjsonHeader {
+        schema_version: SCHEMA_VERSION,
+        title: &document.title,
+        metadata: metadata(&document.metadata),
+    };
+    write_line(out, &header, case)?;
+    for (index, item) in document.elements.iter().enumerate() {
+        let line = NdjsonElement {
+            index,
+            element: element(item),
+        };
+        write_line(out, &line, case)?;
+    }
+    Ok(())
+}
+
+/// Write one line; a failed write stays an I/O error, so that a reader that
+/// has stopped reading can be told apart
+fn write_line(out: &mut impl Write, value: &impl Serialize, case: JsonCase) -> Result<()> {
+    let written = match case {
+        JsonCase::Snake => serde_json::to_writer(&mut *out, value),
+        JsonCase::Camel => {
+            serde_json::to_writer(&mut *out, &camel_case_keys(serde_json::to_value(value)?))
+        }
+    };
+    written.map_err(std::io::Error::from)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Pretty-printed JSON export of a document
+pub fn render_json(document: &Document, case: JsonCase) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&to_json_value(
+        document, case,
+    )?)?)
+}
+
+/// Rename object keys (never values) from snake_case to camelCase, recursively
+fn camel_case_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (camel_case(&key), camel_case_keys(value)))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(camel_case_keys).collect()),
+        other => other,
+    }
+}
+
+fn camel_case(key: &str) -> String {
+    let mut camel = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camel_case_renames_keys_only() {
+        let value = serde_json::json!({
+            "schema_version": 1,
+            "elements": [{ "type": "page_break", "data_type": "text" }],
+        });
+        assert_eq!(
+            camel_case_keys(value),
+            serde_json::json!({
+                "schemaVersion": 1,
+                "elements": [{ "type": "page_break", "dataType": "text" }],
+            })
+        );
+    }
+}
@@ -0,0 +1,1634 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use unicode_width::UnicodeWidthStr;
+
+#[cfg(feature = "terminal")]
+use crate::ansi::{export_to_ansi_with_options, AnsiOptions};
+use crate::{
+    document::*,
+    wrap::{
+        align_line, default_width, expand_tabs, labeled_rule, number_line, pad_to_width,
+        wrap_text_with, BreakOptions, WrapMode,
+    },
+    AnchorStyle, ChartStyle, ChunkBy, ColorDepth, ExportFormat, ImageExportMode, JsonCase,
+    LineBreaking, TableCopyFormat,
+};
+use slug::Slugger;
+
+pub mod accessible;
+pub mod asciidoc;
+pub mod csv;
+pub mod fidelity;
+pub mod grep;
+pub mod html;
+pub mod images;
+pub mod json;
+pub mod notes;
+pub mod outline;
+pub mod print;
+pub mod rst;
+pub mod slug;
+pub mod template;
+pub mod verify;
+
+/// Options for the structured text export formats
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Prepend each nested section with a line linking to its ancestor sections (Markdown)
+    pub breadcrumbs: bool,
+    /// Put a table of contents at the top (Markdown, text and HTML)
+    pub toc: bool,
+    /// Split Markdown output into chunks instead of printing one document
+    pub chunk_by: Option<ChunkBy>,
+    /// Upper bound on the estimated token count of each chunk
+    pub max_tokens: Option<usize>,
+    /// Write chunks as numbered Markdown files here rather than JSONL on stdout
+    pub chunk_dir: Option<PathBuf>,
+    /// Wrap width for text and ANSI export (default: $COLUMNS or 80)
+    pub terminal_width: Option<usize>,
+    /// Color depth for ANSI export
+    pub color_depth: ColorDepth,
+    /// Chart the numeric columns below each table in ANSI export
+    pub charts: Option<ChartStyle>,
+    /// Key naming convention for JSON export
+    pub json_case: JsonCase,
+    /// Line wrapping for text and Markdown export; unset keeps each format's default
+    pub wrap: Option<WrapMode>,
+    /// How wrapped lines are broken in text, Markdown and ANSI export
+    pub line_breaking: LineBreaking,
+    /// Text export that speaks structure aloud, for screen readers
+    pub accessible: bool,
+    /// Delimiter, decimal separator and typing of CSV export
+    pub csv: csv::CsvOptions,
+    /// Whether Markdown, HTML, reStructuredText, AsciiDoc and JSON link, copy
+    /// or embed extracted images
+    pub images: ImageExportMode,
+    /// Whose rules the heading anchors Markdown and HTML link to follow
+    pub anchors: AnchorStyle,
+}
+
+impl ExportOptions {
+    /// Wrap width for text and ANSI export
+    fn width(&self) -> usize {
+        self.terminal_width.unwrap_or_else(default_width)
+    }
+
+    #[cfg(feature = "terminal")]
+    fn ansi_options(&self) -> AnsiOptions {
+        let defaults = AnsiOptions::default();
+        AnsiOptions {
+            terminal_width: self.width(),
+            color_depth: self.color_depth.clone(),
+            line_breaking: self.line_breaking,
+            hyphenate: defaults.hyphenate,
+            charts: self.charts,
+            locale: defaults.locale,
+        }
+    }
+}
+
+/// Pick an export format from an output file's extension
+pub fn infer_export_format(path: &Path) -> Option<ExportFormat> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "md" | "markdown" => Some(ExportFormat::Markdown),
+        "adoc" | "asciidoc" => Some(ExportFormat::Asciidoc),
+        "rst" | "rest" => Some(ExportFormat::Rst),
+        "html" | "htm" => Some(ExportFormat::Html),
+        "txt" | "text" => Some(ExportFormat::Text),
+        "csv" => Some(ExportFormat::Csv),
+        "json" => Some(ExportFormat::Json),
+        "ndjson" | "jsonl" => Some(ExportFormat::Ndjson),
+        "ans" | "ansi" => Some(ExportFormat::Ansi),
+        _ => None,
+    }
+}
+
+pub fn export_document(document: &Document, format: &ExportFormat) -> Result<()> {
+    export_document_with_options(document, format, &ExportOptions::default())
+}
+
+pub fn export_document_with_options(
+    document: &Document,
+    format: &ExportFormat,
+    options: &ExportOptions,
+) -> Result<()> {
+    write_export(
+        &mut std::io::stdout(),
+        document,
+        format,
+        options,
+        None,
+        true,
+    )
+}
+
+/// Export to a file instead of stdout
+///
+/// Images in text export are written as `[Image: ...]` placeholders, since they
+/// can only be drawn inline on a terminal.
+pub fn export_document_to_file(
+    document: &Document,
+    format: &ExportFormat,
+    options: &ExportOptions,
+    path: &Path,
+) -> Result<()> {
+    export_document_to_file_with_progress(document, format, options, path, &NoProgress)
+}
+
+/// [`export_document_to_file`], reporting the bytes written to `progress`
+pub fn export_document_to_file_with_progress(
+    document: &Document,
+    format: &ExportFormat,
+    options: &ExportOptions,
+    path: &Path,
+    progress: &dyn Progress,
+) -> Result<()> {
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    progress.start(Phase::Exporting, None);
+    let mut out = std::io::BufWriter::new(ProgressWriter {
+        inner: file,
+        progress,
+        written: 0,
+    });
+    write_export(&mut out, document, format, options, Some(path), false)?;
+    out.flush()?;
+    progress.finish(Phase::Exporting);
+    Ok(())
+}
+
+/// A writer that reports how many bytes have gone through it
+struct ProgressWriter<'a, W> {
+    inner: W,
+    progress: &'a dyn Progress,
+    written: u64,
+}
+
+impl<W: Write> Write for ProgressWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        self.progress.advance(Phase::Exporting, self.written);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Export to a string, as it would be written to a file
+pub fn export_document_to_string(
+    document: &Document,
+    format: &ExportFormat,
+    options: &ExportOptions,
+) -> Result<String> {
+    let mut out = Vec::new();
+    write_export(&mut out, document, format, options, None, false)?;
+    Ok(String::from_utf8(out)?)
+}
+
+fn write_export(
+    out: &mut impl Write,
+    document: &Document,
+    format: &ExportFormat,
+    options: &ExportOptions,
+    output: Option<&Path>,
+    // Images are only drawn inline when writing to the terminal
+    inline_images: bool,
+) -> Result<()> {
+    let placed = match format {
+        ExportFormat::Markdown
+        | ExportFormat::Html
+        | ExportFormat::Rst
+        | ExportFormat::Asciidoc
+        | ExportFormat::Json
+        | ExportFormat::Ndjson => images::place_images(
+            document,
+            options.images,
+            output,
+            options.chunk_dir.as_deref(),
+        )?,
+        _ => std::borrow::Cow::Borrowed(document),
+    };
+    let document = placed.as_ref();
+    match format {
+        ExportFormat::Markdown if options.chunk_by.is_some() => {
+            write_markdown_chunks(out, document, options)?;
+        }
+        ExportFormat::Markdown => write!(out, "{}", render_markdown(document, options))?,
+        ExportFormat::Text if options.accessible => {
+            write!(out, "{}", accessible::render_accessible_text(document))?;
+        }
+        ExportFormat::Text => {
+            write_text(
+                out,
+                document,
+                options.width(),
+                options.wrap,
+                options.line_breaking,
+                options.toc,
+                inline_images,
+            )?;
+        }
+        ExportFormat::Csv => csv::write_csv(out, document, &options.csv)?,
+        ExportFormat::Json => writeln!(out, "{}", json::render_json(document, options.json_case)?)?,
+        ExportFormat::Ndjson => json::write_ndjson(out, document, options.json_case)?,
+        #[cfg(feature = "terminal")]
+        ExportFormat::Ansi => write!(
+            out,
+            "{}",
+            export_to_ansi_with_options(document, &options.ansi_options())?
+        )?,
+        #[cfg(not(feature = "terminal"))]
+        ExportFormat::Ansi => anyhow::bail!("ANSI export needs doxx's `terminal` feature"),
+        ExportFormat::Asciidoc => write!(out, "{}", asciidoc::render_asciidoc(document))?,
+        ExportFormat::Rst => write!(out, "{}", rst::render_rst(document))?,
+        ExportFormat::Html => write!(out, "{}", html::render_html_with_options(document, options))?,
+        ExportFormat::Notes => {
+            let saved =
+                crate::notes::Notes::load(Path::new(&document.metadata.file_path), document)?;
+            write!(out, "{}", notes::render_notes(document, &saved))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn export_to_markdown(document: &Document) -> Result<()> {
+    export_to_markdown_with_options(document, &ExportOptions::default())
+}
+
+pub fn export_to_markdown_with_options(document: &Document, options: &ExportOptions) -> Result<()> {
+    export_document_with_options(document, &ExportFormat::Markdown, options)
+}
+
+/// Render the whole document as one Markdown string
+pub fn render_markdown(document: &Document, options: &ExportOptions) -> String {
+    let mut markdown = String::new();
+    let headings = table_of_contents_with(document, options.toc, options.anchors);
+    let equations = equation_anchors(document);
+    let links = bookmark_anchors(document, &headings, &equations);
+    let mut anchors = headings.iter().map(|entry| &entry.anchor);
+    // Open sections above the current heading: (level, title, anchor)
+    let mut section_stack: Vec<(u8, String, String)> = Vec::new();
+
+    // Add document title
+    markdown.push_str(&format!("# {}\n\n", document.title));
+
+    // Add metadata
+    markdown.push_str("## Document Information\n\n");
+    markdown.push_str(&format!("- **File**: {}\n", document.metadata.file_path));
+    markdown.push_str(&format!("- **Pages**: {}\n", document.metadata.page_count));
+    markdown.push_str(&format!("- **Words**: {}\n", document.metadata.word_count));
+    if let Some(author) = &document.metadata.author {
+        markdown.push_str(&format!("- **Author**: {author}\n"));
+    }
+    if let Some(language) = &document.metadata.language {
+        markdown.push_str(&format!("- **Language**: {language}\n"));
+    }
+    if let Some(protection) = &document.metadata.protection {
+        markdown.push_str(&format!("- **Protection**: {}\n", protection.summary()));
+    }
+    markdown.push_str("\n---\n\n");
+
+    if options.toc && !headings.is_empty() {
+        markdown.push_str(&format!("## {CONTENTS_HEADING}\n\n"));
+        for entry in &headings {
+            let indent = "  ".repeat(entry.depth);
+            markdown.push_str(&format!("{indent}- [{}](#{})\n", entry.title, entry.anchor));
+        }
+        markdown.push_str("\n---\n\n");
+    }
+
+    // Convert document content
+    for element in &document.elements {
+        if let DocumentElement::Heading { level, .. } = element {
+            let heading_text = heading_display_text(element).unwrap_or_default();
+
+            // Same nesting rule as the outline: a heading closes any open section
+            // at its own level or deeper
+            section_stack.retain(|(open_level, _, _)| open_level < level);
+            if options.breadcrumbs && !section_stack.is_empty() {
+                let trail: Vec<String> = section_stack
+                    .iter()
+                    .map(|(_, title, anchor)| format!("[{title}](#{anchor})"))
+                    .collect();
+                markdown.push_str(&format!("_{}_\n\n", trail.join(" › ")));
+            }
+
+            let anchor = anchors.next().cloned().unwrap_or_default();
+            section_stack.push((*level, heading_text, anchor));
+        }
+        markdown.push_str(&element_to_markdown(
+            element,
+            options.wrap,
+            options.line_breaking,
+            &links,
+        ));
+    }
+
+    markdown
+}
+
+/// Heading text as displayed, including its number if it has one
+fn heading_display_text(element: &DocumentElement) -> Option<String> {
+    match element {
+        DocumentElement::Heading { text, number, .. } => Some(match number {
+            Some(number) => format!("{number} {text}"),
+            None => text.clone(),
+        }),
+        _ => None,
+    }
+}
+
+/// The anchor of the heading or equation each bookmark is on, for linking
+/// cross-references; `equations` are from [`equation_anchors`]
+fn bookmark_anchors<'a>(
+    document: &'a Document,
+    headings: &'a [TocEntry],
+    equations: &'a [(&'a str, String)],
+) -> HashMap<&'a str, &'a str> {
+    document
+        .heading_bookmarks
+        .iter()
+        .filter_map(|(name, &heading)| {
+            Some((name.as_str(), headings.get(heading)?.anchor.as_str()))
+        })
+        .chain(
+            equations
+                .iter()
+                .map(|(name, anchor)| (*name, anchor.as_str())),
+        )
+        .collect()
+}
+
+/// The anchor of the numbered equation each bookmark is on
+fn equation_anchors(document: &Document) -> Vec<(&str, String)> {
+    document
+        .equation_bookmarks
+        .iter()
+        .map(|(name, number)| (name.as_str(), equation_anchor(number)))
+        .collect()
+}
+
+/// The anchor of a numbered equation, `eq-3-2` for "(3.2)"
+fn equation_anchor(number: &str) -> String {
+    let label: String = number
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let words: Vec<&str> = label.split('-').filter(|word| !word.is_empty()).collect();
+    format!("eq-{}", words.join("-"))
+}
+
+/// An equation's LaTeX with its number as a `\tag`, which MathJax and KaTeX
+/// set at the right margin; brackets other than parentheses are kept
+fn tagged_latex(latex: &str, number: Option<&str>) -> String {
+    let Some(number) = number else {
+        return latex.to_string();
+    };
+    match number
+        .strip_prefix('(')
+        .and_then(|label| label.strip_suffix(')'))
+    {
+        Some(label) => format!("{latex} \\tag{{{label}}}"),
+        None => format!("{latex} \\tag*{{{number}}}"),
+    }
+}
+
+/// Markdown for a single run, with emphasis and HTML highlight and super/subscript
+/// tags, and a link to the heading a cross-reference names
+fn run_to_markdown(run: &FormattedRun, links: &HashMap<&str, &str>) -> String {
+    let mut formatted_text = run.formatting.apply_caps(&run.text).into_owned();
+
+    if run.formatting.background_hex().is_some() {
+        formatted_text = format!("<mark>{formatted_text}</mark>");
+    }
+    match run.formatting.vertical_align {
+        VerticalAlign::Superscript => formatted_text = format!("<sup>{formatted_text}</sup>"),
+        VerticalAlign::Subscript => formatted_text = format!("<sub>{formatted_text}</sub>"),
+        VerticalAlign::Baseline => {}
+    }
+    if run.formatting.bold {
+        formatted_text = format!("**{formatted_text}**");
+    }
+    if run.formatting.italic {
+        formatted_text = format!("*{formatted_text}*");
+    }
+    if run.formatting.strikethrough {
+        formatted_text = format!("~~{formatted_text}~~");
+    }
+    if let Some(anchor) = run
+        .formatting
+        .bookmark_link
+        .as_deref()
+        .and_then(|name| links.get(name))
+    {
+        formatted_text = format!("[{formatted_text}](#{anchor})");
+    }
+
+    formatted_text
+}
+
+/// Markdown for a single document element, including its trailing blank line
+fn element_to_markdown(
+    element: &DocumentElement,
+    wrap: Option<WrapMode>,
+    breaking: LineBreaking,
+    links: &HashMap<&str, &str>,
+) -> String {
+    let mut markdown = String::new();
+    match element {
+        DocumentElement::Heading { level, .. } => {
+            let prefix = "#".repeat(*level as usize + 1); // +1 because title is h1
+            let heading_text = heading_display_text(element).unwrap_or_default();
+            markdown.push_str(&format!("{prefix} {heading_text}\n\n"));
+        }
+        DocumentElement::Paragraph { runs, .. } => {
+            let mut paragraph_text = String::new();
+
+            for run in runs {
+                paragraph_text.push_str(&run_to_markdown(run, links));
+            }
+
+            markdown.push_str(&wrap_markdown(&paragraph_text, wrap, breaking, 0));
+            markdown.push_str("\n\n");
+        }
+        DocumentElement::List { items, ordered } => {
+            for (i, item) in items.iter().enumerate() {
+                let indent = "  ".repeat(item.level as usize);
+                let bullet = if *ordered {
+                    format!("{}. ", i + 1)
+                } else {
+                    "- ".to_string()
+                };
+
+                let mut item_text = String::new();
+                for run in &item.runs {
+                    item_text.push_str(&run_to_markdown(run, links));
+                }
+
+                let hang = indent.len() + bullet.len();
+                let item_text = wrap_markdown(&item_text, wrap, breaking, hang);
+                markdown.push_str(&format!("{indent}{bullet}{item_text}\n"));
+            }
+            markdown.push('\n');
+        }
+        DocumentElement::Table { table } => {
+            // Add table title if present
+            if let Some(title) = &table.metadata.title {
+                markdown.push_str(&format!("### {title}\n\n"));
+            }
+
+            markdown.push_str(&markdown_table(table, links));
+            markdown.push('\n');
+        }
+        DocumentElement::Image {
+            description,
+            width,
+            height,
+            image_path,
+            caption,
+            placement,
+            ..
+        } => {
+            let alt = caption.as_ref().unwrap_or(description);
+            let dimensions = match (width, height) {
+                (Some(w), Some(h)) => format!(" <!-- {w}x{h} -->"),
+                _ => String::new(),
+            };
+            match image_path {
+                Some(path) => markdown.push_str(&format!(
+                    "![{alt}]({}){dimensions}\n\n",
+                    path.to_string_lossy()
+                )),
+                // Nothing to link to, such as a metafile that couldn't be converted
+                None => markdown.push_str(&format!("*[Image: {description}]*\n\n")),
+            }
+            if let Some(caption) = caption {
+                markdown.push_str(&format!("*{caption}*\n\n"));
+            }
+            if let Some(note) = placement.as_ref().and_then(ImagePlacement::describe) {
+                markdown.push_str(&format!("*({note})*\n\n"));
+            }
+        }
+        DocumentElement::BlockQuote { runs } => {
+            let quote_text: String = runs.iter().map(|run| run_to_markdown(run, links)).collect();
+            let quote_text = wrap_markdown(&quote_text, wrap, breaking, 0);
+            for line in quote_text.lines() {
+                markdown.push_str(&format!("> {line}\n"));
+            }
+            markdown.push('\n');
+        }
+        DocumentElement::CodeBlock { code } => {
+            let fence = code_fence(code);
+            markdown.push_str(&format!("{fence}\n{code}\n{fence}\n\n"));
+        }
+        DocumentElement::Equation { latex, number, .. } => {
+            if let Some(number) = number {
+                markdown.push_str(&format!("<a id=\"{}\"></a>\n\n", equation_anchor(number)));
+            }
+            let latex = tagged_latex(latex, number.as_deref());
+            markdown.push_str(&format!("$${latex}$$\n\n"));
+        }
+        DocumentElement::PageBreak { page } => {
+            markdown.push_str(&format!("\n---\n<!-- page {page} -->\n\n"));
+        }
+        DocumentElement::HorizontalRule => {
+            markdown.push_str("\n---\n\n");
+        }
+        DocumentElement::FileBoundary { name } => {
+            markdown.push_str(&format!("\n---\n<!-- file: {name} -->\n\n"));
+        }
+    }
+    markdown
+}
+
+/// A backtick fence longer than any run of backticks inside the code
+fn code_fence(code: &str) -> String {
+    let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// Wrap Markdown inline text, indenting continuation lines by `hang` columns
+///
+/// Continuation lines that would otherwise start a new block (a heading, list
+/// item, quote or rule) have their leading marker escaped. Lines never break
+/// inside a hyphenated word or between CJK characters, since Markdown renders
+/// the line break as a space.
+fn wrap_markdown(
+    text: &str,
+    wrap: Option<WrapMode>,
+    breaking: LineBreaking,
+    hang: usize,
+) -> String {
+    let mode = match wrap {
+        None | Some(WrapMode::None) => return text.to_string(),
+        Some(WrapMode::Columns(cols)) => WrapMode::Columns(cols.saturating_sub(hang).max(1)),
+        Some(mode) => mode,
+    };
+
+    let options = BreakOptions {
+        breaking,
+        ..Default::default()
+    };
+    wrap_text_with(text, mode, options)
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                line.clone()
+            } else {
+                format!("{}{}", " ".repeat(hang), escape_block_start(line))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Backslash-escape a line start that Markdown would read as block syntax
+fn escape_block_start(line: &str) -> String {
+    let marker_then_space = |marker: char| {
+        let rest = line.trim_start_matches(marker);
+        rest.len() < line.len() && (rest.is_empty() || rest.starts_with(' '))
+    };
+    if ['#', '>', '-', '+', '*', '=']
+        .into_iter()
+        .any(marker_then_space)
+    {
+        return format!("\\{line}");
+    }
+
+    // Ordered list markers: "1. " or "1) "
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = &line[digits..];
+    if digits > 0 && rest.starts_with(['.', ')']) && (rest.len() == 1 || rest[1..].starts_with(' '))
+    {
+        return format!("{}\\{}", &line[..digits], rest);
+    }
+
+    line.to_string()
+}
+
+/// Title of the table of contents `--toc` adds
+const CONTENTS_HEADING: &str = "Contents";
+
+/// A heading listed in the table of contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocEntry {
+    pub level: u8,
+    /// How many headings it's listed under: those before it at a higher level
+    pub depth: usize,
+    /// The heading as displayed, with its number
+    pub title: String,
+    /// Anchor, GitHub-style unless asked otherwise, and the `id` HTML export
+    /// gives the heading
+    pub anchor: String,
+    pub element_index: usize,
+}
+
+/// The document's headings and the anchors Markdown export links them by
+///
+/// Anchors are numbered past the document title and information headings
+/// Markdown export writes first, and the contents heading if there is one, so
+/// a heading links to the same anchor in Markdown and HTML.
+pub fn table_of_contents(document: &Document, with_contents: bool) -> Vec<TocEntry> {
+    table_of_contents_with(document, with_contents, AnchorStyle::default())
+}
+
+/// [`table_of_contents`], with anchors made by another site's rules
+pub fn table_of_contents_with(
+    document: &Document,
+    with_contents: bool,
+    anchors: AnchorStyle,
+) -> Vec<TocEntry> {
+    let mut slugs = Slugger::new(anchors);
+    slugs.slug(&document.title);
+    slugs.slug("Document Information");
+    if with_contents {
+        slugs.slug(CONTENTS_HEADING);
+    }
+    // Levels of the headings the next one may be listed under
+    let mut open: Vec<u8> = Vec::new();
+    document
+        .elements
+        .iter()
+        .enumerate()
+        .filter_map(|(element_index, element)| {
+            let DocumentElement::Heading { level, .. } = element else {
+                return None;
+            };
+            let title = heading_display_text(element)?;
+            open.retain(|&open_level| open_level < *level);
+            open.push(*level);
+            Some(TocEntry {
+                level: *level,
+                depth: open.len() - 1,
+                anchor: slugs.slug(&title),
+                title,
+                element_index,
+            })
+        })
+        .collect()
+}
+
+/// A self-contained slice of the document's Markdown, for feeding to LLM pipelines
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarkdownChunk {
+    /// Titles of the enclosing headings, outermost first (empty before the first heading)
+    pub heading_path: Vec<String>,
+    pub text: String,
+    /// Estimated token count of `text`
+    pub tokens: usize,
+}
+
+/// Rough token estimate: about four characters per token for English prose
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Split the document into one Markdown chunk per section
+///
+/// Each heading starts a new chunk. With `max_tokens`, sections that would exceed
+/// the limit are split between blocks (paragraphs, lists, tables, ...); continuation
+/// chunks repeat the section heading so each chunk stands on its own, and the
+/// heading's tokens count against the limit. A single block that is larger than
+/// the limit is split between lines, then between sentences, and only a sentence
+/// too long for a chunk on its own between words.
+pub fn chunk_markdown_by_heading(
+    document: &Document,
+    options: &ExportOptions,
+) -> Vec<MarkdownChunk> {
+    let mut chunks = Vec::new();
+    let mut section_stack: Vec<(u8, String)> = Vec::new();
+    // The section's heading, at the top of each of its chunks
+    let mut heading_line = String::new();
+    let mut body = String::new();
+    // Whether the section has a chunk yet, so a heading alone still gets one
+    let mut started = false;
+
+    let mut flush =
+        |stack: &[(u8, String)], heading_line: &str, body: &mut String, started: &mut bool| {
+            if body.trim().is_empty() && (*started || heading_line.trim().is_empty()) {
+                return;
+            }
+            let text = format!("{heading_line}{}", std::mem::take(body));
+            chunks.push(MarkdownChunk {
+                heading_path: stack.iter().map(|(_, title)| title.clone()).collect(),
+                tokens: estimate_tokens(&text),
+                text,
+            });
+            *started = true;
+        };
+
+    for element in &document.elements {
+        // A chunk stands on its own, with no headings elsewhere to link to
+        let block = element_to_markdown(
+            element,
+            options.wrap,
+            options.line_breaking,
+            &HashMap::new(),
+        );
+
+        if let DocumentElement::Heading { level, .. } = element {
+            flush(&section_stack, &heading_line, &mut body, &mut started);
+            section_stack.retain(|(open_level, _)| open_level < level);
+            section_stack.push((*level, heading_display_text(element).unwrap_or_default()));
+            heading_line = block;
+            started = false;
+            continue;
+        }
+
+        let Some(limit) = options.max_tokens else {
+            body.push_str(&block);
+            continue;
+        };
+
+        // What's left of the limit once the heading is repeated
+        let budget = limit.saturating_sub(estimate_tokens(&heading_line)).max(1);
+        for piece in split_block(&block, budget) {
+            if !body.is_empty() && estimate_tokens(&body) + estimate_tokens(&piece) > budget {
+                flush(&section_stack, &heading_line, &mut body, &mut started);
+            }
+            body.push_str(&piece);
+        }
+    }
+    flush(&section_stack, &heading_line, &mut body, &mut started);
+
+    chunks
+}
+
+/// Break a Markdown block into pieces of at most `limit` tokens where possible
+///
+/// A block within the limit is one piece. Otherwise its lines, or the sentences
+/// of lines too long for a piece, are gathered into pieces; a sentence that's
+/// too long by itself is the only thing cut between words.
+fn split_block(block: &str, limit: usize) -> Vec<String> {
+    if estimate_tokens(block) <= limit {
+        return vec![block.to_string()];
+    }
+
+    let mut parts = Vec::new();
+    for line in block.split_inclusive('\n') {
+        if estimate_tokens(line) <= limit {
+            parts.push(line);
+            continue;
+        }
+        for sentence in sentences(line) {
+            match estimate_tokens(sentence) <= limit {
+                true => parts.push(sentence),
+                false => parts.extend(sentence.split_inclusive(' ')),
+            }
+        }
+    }
+
+    // A piece cut mid-line ends its chunk, so it's closed off like a paragraph
+    let close = |piece: &str| match piece.ends_with('\n') {
+        true => piece.to_string(),
+        false => format!("{piece}\n\n"),
+    };
+    let mut pieces = Vec::new();
+    let mut piece = String::new();
+    for part in parts {
+        if !piece.is_empty() && estimate_tokens(&close(&format!("{piece}{part}"))) > limit {
+            pieces.push(close(&std::mem::take(&mut piece)));
+        }
+        piece.push_str(part);
+    }
+    if !piece.is_empty() {
+        pieces.push(close(&piece));
+    }
+    pieces
+}
+
+/// A line's sentences, each with the space after it
+///
+/// A sentence ends at `.`, `?` or `!` followed by a space and a word that
+/// doesn't start in lower case, so "e.g. this" stays in one sentence.
+fn sentences(line: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for (end, _) in line.match_indices(['.', '?', '!']) {
+        let rest = &line[end + 1..];
+        let Some(next) = rest.strip_prefix(' ') else {
+            continue;
+        };
+        if next.starts_with(|c: char| c.is_lowercase() || c.is_whitespace()) || next.is_empty() {
+            continue;
+        }
+        sentences.push(&line[start..end + 2]);
+        start = end + 2;
+    }
+    if start < line.len() {
+        sentences.push(&line[start..]);
+    }
+    sentences
+}
+
+fn write_markdown_chunks(
+    out: &mut impl Write,
+    document: &Document,
+    options: &ExportOptions,
+) -> Result<()> {
+    let chunks = match options.chunk_by {
+        Some(ChunkBy::Heading) | None => chunk_markdown_by_heading(document, options),
+    };
+
+    let Some(dir) = &options.chunk_dir else {
+        for chunk in &chunks {
+            writeln!(out, "{}", serde_json::to_string(chunk)?)?;
+        }
+        return Ok(());
+    };
+
+    std::fs::create_dir_all(dir)?;
+    let mut slugs = Slugger::new(AnchorStyle::Github);
+    let digits = chunks.len().to_string().len().max(3);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let title = chunk.heading_path.last().map_or("preamble", String::as_str);
+        let mut slug = slugs.slug(title);
+        if slug.is_empty() {
+            slug = "section".to_string();
+        }
+        let path = dir.join(format!("{:0digits$}-{slug}.md", i + 1));
+        std::fs::write(&path, &chunk.text)?;
+        writeln!(out, "Wrote: {}", path.display())?;
+    }
+    Ok(())
+}
+
+pub fn format_as_text(document: &Document) -> String {
+    let mut text = String::new();
+
+    // Add document title
+    text.push_str(&format!("{}\n", document.title));
+    text.push_str(&"=".repeat(document.title.width()));
+    text.push_str("\n\n");
+
+    // Convert document content
+    for element in &document.elements {
+        match element {
+            DocumentElement::Heading {
+                level,
+                text: heading_text,
+                ..
+            } => {
+                let underline = match level {
+                    1 => "=",
+                    2 => "-",
+                    _ => "~",
+                };
+                text.push_str(&format!("{heading_text}\n"));
+                text.push_str(&underline.repeat(heading_text.width()));
+                text.push_str("\n\n");
+            }
+            DocumentElement::Paragraph {
+                runs,
+                spacing,
+                tab_stops,
+                ..
+            } => {
+                let segments: Vec<&str> = runs.iter().map(|run| run.text.as_str()).collect();
+                let para_text = expand_tabs(&segments, tab_stops, default_width()).concat();
+                let spacing = spacing.unwrap_or_default();
+                text.push_str(&"\n".repeat(spacing.before));
+                text.push_str(&format!("{para_text}\n"));
+                text.push_str(&"\n".repeat(spacing.after));
+            }
+            DocumentElement::List { items, ordered } => {
+                for (i, item) in items.iter().enumerate() {
+                    let bullet = if *ordered {
+                        format!("{}. ", i + 1)
+                    } else {
+                        "* ".to_string()
+                    };
+
+                    let indent = "  ".repeat(item.level as usize);
+                    let item_text: String = item.runs.iter().map(|run| run.text.as_str()).collect();
+                    text.push_str(&format!("{indent}{bullet}{item_text}\n"));
+                }
+                text.push('\n');
+            }
+            DocumentElement::Table { table } => {
+                // Add table title if present
+                if let Some(title) = &table.metadata.title {
+                    text.push_str(&format!("{title}\n"));
+                    text.push_str(&"=".repeat(title.width()));
+                    text.push_str("\n\n");
+                }
+
+                // Use the calculated column widths from metadata
+                let col_widths = &table.metadata.column_widths;
+
+                // Top border
+                let top_border = generate_text_table_border(col_widths, "┌", "┬", "┐", "─");
+                text.push_str(&format!("{top_border}\n"));
+
+                // Header with proper alignment
+                let header_line = render_text_table_row(&table.headers, col_widths, true);
+                text.push_str(&format!("{header_line}\n"));
+
+                // Header separator
+                let separator = generate_text_table_border(col_widths, "├", "┼", "┤", "─");
+                text.push_str(&format!("{separator}\n"));
+
+                // Data rows
+                for row in &table.rows {
+                    let row_line = render_text_table_row(row, col_widths, false);
+                    text.push_str(&format!("{row_line}\n"));
+                }
+
+                // Bottom border
+                let bottom_border = generate_text_table_border(col_widths, "└", "┴", "┘", "─");
+                text.push_str(&format!("{bottom_border}\n"));
+
+                text.push('\n');
+            }
+            DocumentElement::PageBreak { page } => {
+                text.push_str(&labeled_rule(&format!("page {page}"), '─', 50));
+                text.push_str("\n\n");
+            }
+            DocumentElement::HorizontalRule => {
+                text.push_str(&"─".repeat(50));
+                text.push_str("\n\n");
+            }
+            DocumentElement::FileBoundary { name } => {
+                text.push_str(&labeled_rule(name, '═', 50));
+                text.push_str("\n\n");
+            }
+            DocumentElement::Image {
+                description,
+                image_path,
+                caption,
+                placement,
+                ..
+            } => {
+                // Try to render the image inline if available
+                if let Some(path) = image_path {
+                    if draw_image(document, path, description) {
+                        // Image displayed successfully, add spacing
+                        text.push('\n');
+                    } else {
+                        // Fallback to text description
+                        text.push_str(&format!("[Image: {description}]\n\n"));
+                    }
+                } else {
+                    text.push_str(&format!("[Image: {description}]\n\n"));
+                }
+                if let Some(caption) = caption {
+                    text.push_str(&format!("{caption}\n\n"));
+                }
+                if let Some(note) = placement.as_ref().and_then(ImagePlacement::describe) {
+                    text.push_str(&format!("({note})\n\n"));
+                }
+            }
+            DocumentElement::BlockQuote { runs } => {
+                let quote_text: String = runs.iter().map(|run| run.text.as_str()).collect();
+                for line in quote_text.lines() {
+                    text.push_str(&format!("> {line}\n"));
+                }
+                text.push('\n');
+            }
+            DocumentElement::CodeBlock { code } => {
+                for line in code.lines() {
+                    let indent = if line.is_empty() { "" } else { "    " };
+                    text.push_str(&format!("{indent}{line}\n"));
+                }
+                text.push('\n');
+            }
+            DocumentElement::Equation { latex, number, .. } => {
+                let line = format!("Equation: {latex}");
+                let line = match number {
+                    Some(number) => number_line(&line, number, 50),
+                    None => line,
+                };
+                text.push_str(&format!("{line}\n\n"));
+            }
+        }
+    }
+
+    text
+}
+
+pub fn export_to_text(document: &Document) -> Result<()> {
+    export_document(document, &ExportFormat::Text)
+}
+
+pub fn export_to_text_with_cli_options(
+    document: &Document,
+    terminal_width: Option<usize>,
+) -> Result<()> {
+    let options = ExportOptions {
+        terminal_width,
+        ..Default::default()
+    };
+    export_document_with_options(document, &ExportFormat::Text, &options)
+}
+
+fn write_text(
+    out: &mut impl Write,
+    document: &Document,
+    width: usize,
+    wrap: Option<WrapMode>,
+    breaking: LineBreaking,
+    toc: bool,
+    inline_images: bool,
+) -> Result<()> {
+    // Print title
+    writeln!(out, "{}\n", document.title)?;
+
+    // Print metadata
+    writeln!(out, "Document Information:")?;
+    writeln!(out, "- File: {}", document.metadata.file_path)?;
+    writeln!(out, "- Pages: {}", document.metadata.page_count)?;
+    writeln!(out, "- Words: {}", document.metadata.word_count)?;
+    if let Some(author) = &document.metadata.author {
+        writeln!(out, "- Author: {author}")?;
+    }
+    if let Some(language) = &document.metadata.language {
+        writeln!(out, "- Language: {language}")?;
+    }
+    if let Some(protection) = &document.metadata.protection {
+        writeln!(out, "- Protection: {}", protection.summary())?;
+    }
+    writeln!(out, "\n{}\n", "=".repeat(50))?;
+
+    let headings = if toc {
+        table_of_contents(document, false)
+    } else {
+        Vec::new()
+    };
+    if !headings.is_empty() {
+        writeln!(out, "{CONTENTS_HEADING}:")?;
+        for entry in &headings {
+            let indent = "  ".repeat(entry.depth);
+            writeln!(out, "{indent}- {}", entry.title)?;
+        }
+        writeln!(out, "\n{}\n", "=".repeat(50))?;
+    }
+
+    write_text_elements(
+        out,
+        document,
+        &document.elements,
+        width,
+        wrap,
+        breaking,
+        inline_images,
+    )
+}
+
+/// Write elements as text export lays them out, without the title and metadata
+fn write_text_elements(
+    out: &mut impl Write,
+    document: &Document,
+    elements: &[DocumentElement],
+    width: usize,
+    wrap: Option<WrapMode>,
+    breaking: LineBreaking,
+    inline_images: bool,
+) -> Result<()> {
+    // Process elements in order, printing immediately
+    for element in elements {
+        match element {
+            DocumentElement::Heading {
+                level,
+                text,
+                number,
+            } => {
+                let prefix = "#".repeat(*level as usize);
+                let heading_text = if let Some(number) = number {
+                    format!("{number} {text}")
+                } else {
+                    text.clone()
+                };
+                writeln!(out, "{prefix} {heading_text}\n")?;
+            }
+            DocumentElement::Paragraph {
+                runs,
+                alignment,
+                spacing,
+                tab_stops,
+                ..
+            } => {
+                let mut paragraph_text = String::new();
+
+                // Tabs are laid out before the emphasis markers go in
+                let texts: Vec<_> = runs
+                    .iter()
+                    .map(|run| run.formatting.display_text(&run.text))
+                    .collect();
+                let segments: Vec<&str> = texts.iter().map(|text| text.as_ref()).collect();
+                let expanded = expand_tabs(&segments, tab_stops, width);
+                for (run, mut formatted_text) in runs.iter().zip(expanded) {
+                    if run.formatting.bold {
+                        formatted_text = format!("**{formatted_text}**");
+                    }
+                    if run.formatting.italic {
+                        formatted_text = format!("*{formatted_text}*");
+                    }
+                    if run.formatting.underline {
+                        formatted_text = format!("_{formatted_text}_");
+                    }
+                    if run.formatting.strikethrough {
+                        formatted_text = format!("~~{formatted_text}~~");
+                    }
+
+                    paragraph_text.push_str(&formatted_text);
+                }
+
+                let spacing = spacing.unwrap_or_default();
+                write!(out, "{}", "\n".repeat(spacing.before))?;
+                writeln!(
+                    out,
+                    "{}",
+                    align_text_paragraph(
+                        &paragraph_text,
+                        *alignment,
+                        width,
+                        wrap,
+                        breaking,
+                        document.hyphenates(),
+                    )
+                )?;
+                write!(out, "{}", "\n".repeat(spacing.after))?;
+            }
+            DocumentElement::List { items, .. } => {
+                for item in items {
+                    let item_text: String = item
+                        .runs
+                        .iter()
+                        .map(|run| run.formatting.display_text(&run.text))
+                        .collect();
+                    let break_options = BreakOptions {
+                        breaking,
+                        hyphenate: document.hyphenates(),
+                        justify: false,
+                        ideographic: true,
+                    };
+                    let lines = match wrap {
+                        Some(WrapMode::Columns(cols)) => wrap_text_with(
+                            &item_text,
+                            WrapMode::Columns(cols.saturating_sub(2).max(1)),
+                            break_options,
+                        ),
+                        Some(WrapMode::Semantic) => {
+                            wrap_text_with(&item_text, WrapMode::Semantic, break_options)
+                        }
+                        _ => vec![item_text],
+                    };
+                    writeln!(out, "- {}", lines.join("\n  "))?;
+                }
+                writeln!(out)?;
+            }
+            DocumentElement::Table { table } => {
+                if let Some(title) = &table.metadata.title {
+                    writeln!(out, "{title}")?;
+                }
+                // Simple table rendering for text export
+                for row in &table.rows {
+                    let row_content: Vec<String> =
+                        row.iter().map(|cell| cell.content.clone()).collect();
+                    writeln!(out, "| {} |", row_content.join(" | "))?;
+                }
+                writeln!(out)?;
+            }
+            DocumentElement::Image {
+                description,
+                image_path,
+                caption,
+                placement,
+                ..
+            } => {
+                // Render image immediately in the correct position
+                if let Some(path) = image_path.as_ref().filter(|_| inline_images) {
+                    out.flush()?;
+                    if draw_image(document, path, description) {
+                        // Image displayed successfully, add spacing
+                        writeln!(out)?;
+                    } else {
+                        // Fallback to text description
+                        writeln!(out, "[Image: {description}]\n")?;
+                    }
+                } else {
+                    writeln!(out, "[Image: {description}]\n")?;
+                }
+                if let Some(caption) = caption {
+                    writeln!(out, "{caption}\n")?;
+                }
+                if let Some(note) = placement.as_ref().and_then(ImagePlacement::describe) {
+                    writeln!(out, "({note})\n")?;
+                }
+            }
+            DocumentElement::BlockQuote { runs } => {
+                let quote_text: String = runs
+                    .iter()
+                    .map(|run| run.formatting.display_text(&run.text))
+                    .collect();
+                let break_options = BreakOptions {
+                    breaking,
+                    hyphenate: document.hyphenates(),
+                    justify: false,
+                    ideographic: true,
+                };
+                let lines = match wrap {
+                    Some(WrapMode::Columns(cols)) => wrap_text_with(
+                        &quote_text,
+                        WrapMode::Columns(cols.saturating_sub(2).max(1)),
+                        break_options,
+                    ),
+                    Some(WrapMode::Semantic) => {
+                        wrap_text_with(&quote_text, WrapMode::Semantic, break_options)
+                    }
+                    _ => quote_text.lines().map(str::to_string).collect(),
+                };
+                for line in lines {
+                    writeln!(out, "> {line}")?;
+                }
+                writeln!(out)?;
+            }
+            DocumentElement::CodeBlock { code } => {
+                // Code keeps its lines and spacing, however narrow the terminal
+                for line in code.lines() {
+                    let indent = if line.is_empty() { "" } else { "    " };
+                    writeln!(out, "{indent}{line}")?;
+                }
+                writeln!(out)?;
+            }
+            DocumentElement::Equation { latex, number, .. } => {
+                let line = format!("Equation: {latex}");
+                match number {
+                    Some(number) => writeln!(out, "{}\n", number_line(&line, number, width))?,
+                    None => writeln!(out, "{line}\n")?,
+                }
+            }
+            DocumentElement::PageBreak { page } => {
+                writeln!(out, "{}\n", labeled_rule(&format!("page {page}"), '-', 50))?;
+            }
+            DocumentElement::HorizontalRule => {
+                writeln!(out, "{}\n", "-".repeat(50))?;
+            }
+            DocumentElement::FileBoundary { name } => {
+                writeln!(out, "{}\n", labeled_rule(name, '=', 50))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw an image on the terminal, returning whether it was drawn
+#[cfg(feature = "terminal")]
+fn draw_image(document: &Document, path: &Path, description: &str) -> bool {
+    crate::terminal_image::TerminalImageRenderer::for_image_options(&document.image_options)
+        .render_image_from_path(path, description)
+        .is_ok()
+}
+
+/// Without a terminal, images are always written as placeholders
+#[cfg(not(feature = "terminal"))]
+fn draw_image(_document: &Document, _path: &Path, _description: &str) -> bool {
+    false
+}
+
+pub fn export_to_csv(document: &Document) -> Result<()> {
+    export_document(document, &ExportFormat::Csv)
+}
+
+/// A table cell's runs to export, without the bold a header row is set in already
+fn cell_runs(cell: &TableCell, header: bool) -> Vec<FormattedRun> {
+    let mut runs = cell.formatted_runs().into_owned();
+    if header {
+        runs.iter_mut().for_each(|run| run.formatting.bold = false);
+    }
+    runs
+}
+
+/// Markdown for a table cell
+fn cell_to_markdown(cell: &TableCell, header: bool, links: &HashMap<&str, &str>) -> String {
+    cell_runs(cell, header)
+        .iter()
+        .map(|run| run_to_markdown(run, links))
+        .collect()
+}
+
+/// A table as a Markdown pipe table, without its title
+fn markdown_table(table: &TableData, links: &HashMap<&str, &str>) -> String {
+    let mut markdown = String::new();
+
+    // Markdown table header
+    let header_content: Vec<String> = table
+        .headers
+        .iter()
+        .map(|h| cell_to_markdown(h, true, links))
+        .collect();
+    markdown.push_str(&format!("| {} |\n", header_content.join(" | ")));
+
+    // Generate alignment indicators
+    let alignment_row: Vec<String> = table
+        .metadata
+        .column_alignments
+        .iter()
+        .map(|align| match align {
+            TextAlignment::Left => ":---".to_string(),
+            TextAlignment::Right => "---:".to_string(),
+            TextAlignment::Center => ":---:".to_string(),
+            TextAlignment::Justify => ":---".to_string(),
+        })
+        .collect();
+    markdown.push_str(&format!("| {} |\n", alignment_row.join(" | ")));
+
+    // Table rows
+    for row in &table.rows {
+        let row_content: Vec<String> = row
+            .iter()
+            .map(|cell| cell_to_markdown(cell, false, links))
+            .collect();
+        markdown.push_str(&format!("| {} |\n", row_content.join(" | ")));
+    }
+    markdown
+}
+
+/// A table as text for the clipboard, header row first
+///
+/// TSV is the default because spreadsheets split pasted tab-separated text
+/// into cells; tabs and line breaks inside cells become spaces so they can't
+/// shift the columns.
+pub fn format_table(table: &TableData, format: TableCopyFormat) -> String {
+    match format {
+        TableCopyFormat::Tsv => std::iter::once(&table.headers)
+            .chain(&table.rows)
+            .map(|row| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cell| cell.content.replace(['\t', '\n', '\r'], " "))
+                    .collect();
+                cells.join("\t") + "\n"
+            })
+            .collect(),
+        TableCopyFormat::Markdown => markdown_table(table, &HashMap::new()),
+        TableCopyFormat::Csv => {
+            csv::csv_table_lines(table, CellLocale::default(), &csv::CsvOptions::as_written())
+                .into_iter()
+                .map(|line| line + "\n")
+                .collect()
+        }
+    }
+}
+
+pub fn export_to_json(document: &Document) -> Result<()> {
+    export_document(document, &ExportFormat::Json)
+}
+
+#[allow(dead_code)]
+pub fn extract_citations(document: &Document) -> Result<Vec<Citation>> {
+    let mut citations = Vec::new();
+
+    // Simple citation extraction - look for common citation patterns
+    for (index, element) in document.elements.iter().enumerate() {
+        let text = match element {
+            DocumentElement::Heading { text, .. } => text,
+            DocumentElement::Paragraph { runs, .. } => {
+                &runs.iter().map(|run| run.text.as_str()).collect::<String>()
+            }
+            _ => continue,
+        };
+
+        // Look for citation patterns like (Author, Year) or [1]
+        let citation_patterns = [
+            r"\([A-Z][a-z]+,\s*\d{4}\)",             // (Author, 2024)
+            r"\[[0-9]+\]",                           // [1]
+            r"\([A-Z][a-z]+\s+et\s+al\.,\s*\d{4}\)", // (Author et al., 2024)
+        ];
+
+        for pattern in &citation_patterns {
+            if let Ok(regex) = regex::Regex::new(pattern) {
+                for mat in regex.find_iter(text) {
+                    citations.push(Citation {
+                        text: mat.as_str().to_string(),
+                        element_index: index,
+                        citation_type: CitationType::InText,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(citations)
+}
+
+#[allow(dead_code)]
+pub fn extract_bibliography(document: &Document) -> Result<Vec<Citation>> {
+    let mut bibliography = Vec::new();
+
+    // Look for bibliography or references section
+    for (index, element) in document.elements.iter().enumerate() {
+        if let DocumentElement::Heading { text, .. } = element {
+            if text.to_lowercase().contains("reference")
+                || text.to_lowercase().contains("bibliography")
+                || text.to_lowercase().contains("works cited")
+            {
+                // Process following elements as bibliography entries
+                for (bib_index, bib_element) in document.elements[index + 1..].iter().enumerate() {
+                    match bib_element {
+                        DocumentElement::Paragraph { runs, .. } => {
+                            let text: String = runs.iter().map(|run| run.text.as_str()).collect();
+                            if !text.trim().is_empty() {
+                                bibliography.push(Citation {
+                                    text: text.clone(),
+                                    element_index: index + bib_index + 1,
+                                    citation_type: CitationType::Bibliography,
+                                });
+                            }
+                        }
+                        DocumentElement::List { items, .. } => {
+                            for item in items {
+                                let text: String =
+                                    item.runs.iter().map(|run| run.text.as_str()).collect();
+                                bibliography.push(Citation {
+                                    text,
+                                    element_index: index + bib_index + 1,
+                                    citation_type: CitationType::Bibliography,
+                                });
+                            }
+                        }
+                        DocumentElement::Heading { .. } => break, // Next section
+                        _ => {}
+                    }
+                }
+                break;
+            }
+        }
+    }
+
+    Ok(bibliography)
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct Citation {
+    pub text: String,
+    pub element_index: usize,
+    pub citation_type: CitationType,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub enum CitationType {
+    InText,
+    Bibliography,
+}
+
+// Helper functions for text table rendering
+fn generate_text_table_border(
+    column_widths: &[usize],
+    left: &str,
+    middle: &str,
+    right: &str,
+    fill: &str,
+) -> String {
+    let mut border = String::new();
+    border.push_str(left);
+
+    for (i, &width) in column_widths.iter().enumerate() {
+        border.push_str(&fill.repeat(width + 2)); // +2 for padding
+        if i < column_widths.len() - 1 {
+            border.push_str(middle);
+        }
+    }
+
+    border.push_str(right);
+    border
+}
+
+fn render_text_table_row(cells: &[TableCell], column_widths: &[usize], _is_header: bool) -> String {
+    let mut row = String::new();
+    row.push('│');
+
+    for (i, cell) in cells.iter().enumerate() {
+        let width = column_widths.get(i).copied().unwrap_or(10);
+        let aligned_content = align_text_cell_content(&cell.content, cell.alignment, width);
+
+        row.push(' ');
+        row.push_str(&aligned_content);
+        row.push(' ');
+        row.push('│');
+    }
+
+    row
+}
+
+/// Wrap and align a text-export paragraph
+///
+/// Without an explicit wrap mode, left-aligned paragraphs stay on one line and
+/// the others are wrapped to `width` so they can be positioned.
+fn align_text_paragraph(
+    text: &str,
+    alignment: TextAlignment,
+    width: usize,
+    wrap: Option<WrapMode>,
+    breaking: LineBreaking,
+    hyphenate: bool,
+) -> String {
+    let options = BreakOptions {
+        breaking,
+        hyphenate,
+        justify: alignment == TextAlignment::Justify,
+        ideographic: true,
+    };
+    let (lines, width) = match wrap {
+        None if alignment == TextAlignment::Left => return text.to_string(),
+        None => (
+            wrap_text_with(text, WrapMode::Columns(width), options),
+            width,
+        ),
+        Some(WrapMode::None) => return text.to_string(),
+        Some(WrapMode::Semantic) => {
+            return wrap_text_with(text, WrapMode::Semantic, options).join("\n")
+        }
+        Some(WrapMode::Columns(cols)) => {
+            (wrap_text_with(text, WrapMode::Columns(cols), options), cols)
+        }
+    };
+
+    let last_line = lines.len().saturating_sub(1);
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| align_line(line, alignment, width, i == last_line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn align_text_cell_content(content: &str, alignment: TextAlignment, width: usize) -> String {
+    pad_to_width(content.trim(), alignment, width)
+}
+
+pub fn export_to_ansi(document: &Document) -> Result<()> {
+    export_document(document, &ExportFormat::Ansi)
+}
+
+pub fn export_to_ansi_with_cli_options(
+    document: &Document,
+    terminal_width: Option<usize>,
+    color_depth: &ColorDepth,
+) -> Result<()> {
+    let options = ExportOptions {
+        terminal_width,
+        color_depth: color_depth.clone(),
+        ..Default::default()
+    };
+    export_document_with_options(document, &ExportFormat::Ansi, &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapped_markdown_never_starts_a_block() {
+        assert_eq!(escape_block_start("- not a list"), "\\- not a list");
+        assert_eq!(escape_block_start("# not a heading"), "\\# not a heading");
+        assert_eq!(escape_block_start("---"), "\\---");
+        assert_eq!(escape_block_start("2024. A year"), "2024\\. A year");
+        assert_eq!(escape_block_start("**bold** text"), "**bold** text");
+        assert_eq!(escape_block_start("3.5 percent"), "3.5 percent");
+
+        let wrapped = wrap_markdown(
+            "costs fell by - 5 points",
+            Some(WrapMode::Columns(15)),
+            LineBreaking::Greedy,
+            2,
+        );
+        assert_eq!(wrapped, "costs fell by\n  \\- 5 points");
+    }
+
+    #[test]
+    fn test_oversized_blocks_split_between_sentences() {
+        let paragraph = "The first quarter closed ahead of plan. Margins held, e.g. in services. \
+                         Did hiring keep pace? It did not, and the backlog grew.\n\n";
+        let pieces = split_block(paragraph, 15);
+        assert_eq!(
+            pieces,
+            [
+                "The first quarter closed ahead of plan. \n\n",
+                "Margins held, e.g. in services. Did hiring keep pace? \n\n",
+                "It did not, and the backlog grew.\n\n",
+            ]
+        );
+        assert!(pieces.iter().all(|piece| estimate_tokens(piece) <= 15));
+        assert_eq!(split_block(paragraph, 100), [paragraph]);
+        // Only a sentence too long for a piece is cut between words
+        assert_eq!(split_block("Far too long a sentence.", 3).len(), 3);
+    }
+}
@@ -0,0 +1,55 @@
+//! Bookmark and note export
+//!
+//! The annotations made in the viewer, as a Markdown review: one section per
+//! document heading that has any, each bookmarked passage quoted with its
+//! note underneath.
+
+use crate::{
+    document::*,
+    notes::{element_text, Notes},
+};
+
+use super::heading_display_text;
+
+/// Render the annotations on a document as Markdown
+pub fn render_notes(document: &Document, notes: &Notes) -> String {
+    let mut markdown = format!("# Notes on {}\n\n", document.title);
+    if notes.is_empty() {
+        markdown.push_str("No bookmarks or notes yet.\n");
+        return markdown;
+    }
+
+    let mut section = None;
+    for annotation in notes.annotations() {
+        let Some(element) = document.elements.get(annotation.element) else {
+            continue;
+        };
+        // The heading the bookmark falls under, if any
+        let heading = document.elements[..=annotation.element]
+            .iter()
+            .rposition(|element| matches!(element, DocumentElement::Heading { .. }));
+        if heading != section {
+            section = heading;
+            let title = heading
+                .and_then(|index| heading_display_text(&document.elements[index]))
+                .unwrap_or_else(|| "Before the first heading".to_string());
+            markdown.push_str(&format!("## {title}\n\n"));
+        }
+
+        // A bookmarked heading is the section title already
+        if !matches!(element, DocumentElement::Heading { .. }) {
+            markdown.push_str(&format!("> {}\n\n", element_text(element)));
+        }
+        match &annotation.note {
+            Some(note) => markdown.push_str(&format!("{note}\n\n")),
+            None if matches!(element, DocumentElement::Heading { .. }) => {
+                markdown.push_str("*Bookmarked*\n\n")
+            }
+            None => {}
+        }
+    }
+
+    markdown.truncate(markdown.trim_end().len());
+    markdown.push('\n');
+    markdown
+}
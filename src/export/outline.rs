@@ -0,0 +1,158 @@
+//! Outline export for `doxx outline`
+//!
+//! The heading tree of a document, each heading with its number, level and
+//! the words in its section, counting subsections and table cells, followed
+//! by the lists of tables and figures when any are captioned.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{document::*, OutlineFormat};
+
+#[derive(Serialize)]
+struct JsonOutline<'a> {
+    title: &'a str,
+    words: usize,
+    sections: Vec<JsonSection<'a>>,
+    tables: Vec<JsonCaption<'a>>,
+    figures: Vec<JsonCaption<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonCaption<'a> {
+    text: &'a str,
+    element_index: usize,
+}
+
+#[derive(Serialize)]
+struct JsonSection<'a> {
+    level: u8,
+    number: Option<&'a str>,
+    text: &'a str,
+    element_index: usize,
+    /// Words up to the next heading
+    words: usize,
+    /// Words including subsections
+    total_words: usize,
+    sections: Vec<JsonSection<'a>>,
+}
+
+impl<'a> From<&'a OutlineSection> for JsonSection<'a> {
+    fn from(section: &'a OutlineSection) -> Self {
+        Self {
+            level: section.level,
+            number: section.number.as_deref(),
+            text: &section.text,
+            element_index: section.element_index,
+            words: section.words,
+            total_words: section.total_words,
+            sections: section.children.iter().map(JsonSection::from).collect(),
+        }
+    }
+}
+
+/// Render a document's outline in the given format
+pub fn render_outline(document: &Document, format: OutlineFormat) -> Result<String> {
+    let sections = section_outline(document);
+    let words: usize = document.elements.iter().map(element_words).sum();
+    let captions = caption_list(document);
+    let captioned = |kind| -> Vec<&CaptionEntry> {
+        captions.iter().filter(|entry| entry.kind == kind).collect()
+    };
+    let lists = [
+        ("List of Tables", captioned(CaptionKind::Table)),
+        ("List of Figures", captioned(CaptionKind::Figure)),
+    ];
+
+    Ok(match format {
+        OutlineFormat::Tree => {
+            let mut tree = format!("{}  ({})\n", document.title, word_count(words));
+            write_tree(&mut tree, &sections, "");
+            for (title, entries) in lists.iter().filter(|(_, entries)| !entries.is_empty()) {
+                tree.push_str(&format!("\n{title}\n"));
+                for entry in entries {
+                    tree.push_str(&format!("  {}\n", entry.text));
+                }
+            }
+            tree
+        }
+        OutlineFormat::Markdown => {
+            let mut markdown = format!("# Outline of {}\n\n", document.title);
+            if sections.is_empty() {
+                markdown.push_str("No headings found.\n");
+            }
+            write_list(&mut markdown, &sections, 0);
+            for (title, entries) in lists.iter().filter(|(_, entries)| !entries.is_empty()) {
+                markdown.push_str(&format!("\n## {title}\n\n"));
+                for entry in entries {
+                    markdown.push_str(&format!("- {}\n", entry.text));
+                }
+            }
+            markdown
+        }
+        OutlineFormat::Json => {
+            let outline = JsonOutline {
+                title: &document.title,
+                words,
+                sections: sections.iter().map(JsonSection::from).collect(),
+                tables: json_captions(&lists[0].1),
+                figures: json_captions(&lists[1].1),
+            };
+            serde_json::to_string_pretty(&outline)? + "\n"
+        }
+    })
+}
+
+fn json_captions<'a>(entries: &[&'a CaptionEntry]) -> Vec<JsonCaption<'a>> {
+    entries
+        .iter()
+        .map(|entry| JsonCaption {
+            text: &entry.text,
+            element_index: entry.element_index,
+        })
+        .collect()
+}
+
+fn write_tree(out: &mut String, sections: &[OutlineSection], indent: &str) {
+    for (i, section) in sections.iter().enumerate() {
+        let last = i + 1 == sections.len();
+        let (branch, continuation) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        out.push_str(&format!(
+            "{indent}{branch}{}  ({})\n",
+            heading_title(section),
+            word_count(section.total_words)
+        ));
+        write_tree(out, &section.children, &format!("{indent}{continuation}"));
+    }
+}
+
+fn write_list(out: &mut String, sections: &[OutlineSection], depth: usize) {
+    for section in sections {
+        out.push_str(&format!(
+            "{}- {} ({})\n",
+            "  ".repeat(depth),
+            heading_title(section),
+            word_count(section.total_words)
+        ));
+        write_list(out, &section.children, depth + 1);
+    }
+}
+
+/// A heading's text after its number, if it has one
+fn heading_title(section: &OutlineSection) -> String {
+    match &section.number {
+        Some(number) => format!("{number} {}", section.text),
+        None => section.text.clone(),
+    }
+}
+
+fn word_count(words: usize) -> String {
+    match words {
+        1 => "1 word".to_string(),
+        n => format!("{n} words"),
+    }
+}
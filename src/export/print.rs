@@ -0,0 +1,317 @@
+//! Paginated PostScript for `doxx print`
+//!
+//! The document is laid out as text export lays it out, in Courier at the
+//! chosen size, so that it fits between the margins. A page ends at each page
+//! break and wherever the lines fill it. Each page carries the title at the
+//! top and its number at the bottom, and the whole is PostScript, which CUPS
+//! prints as it is.
+
+use anyhow::{bail, Result};
+use unicode_width::UnicodeWidthChar;
+
+use super::write_text_elements;
+use crate::{document::*, wrap::WrapMode, LineBreaking, Normalization, Paper};
+
+/// Courier's advance width, as a share of the font size
+const CHARACTER_WIDTH: f32 = 0.6;
+
+/// Baseline to baseline, as a share of the font size
+const LEADING: f32 = 1.2;
+
+/// A point is a twentieth of a twip
+const TWIPS_PER_POINT: f32 = 20.0;
+
+/// Page size, margins and type size to print at, in points
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintOptions {
+    pub width: f32,
+    pub height: f32,
+    /// Top, right, bottom and left
+    pub margins: [f32; 4],
+    pub font_size: f32,
+}
+
+impl Default for PrintOptions {
+    /// Letter with inch margins, as Word starts a document
+    fn default() -> Self {
+        let (width, height) = Paper::Letter.size();
+        Self {
+            width,
+            height,
+            margins: [72.0; 4],
+            font_size: 10.0,
+        }
+    }
+}
+
+impl PrintOptions {
+    /// The page size and margins of the document's first section, where it
+    /// sets them
+    pub fn for_document(document: &Document) -> Self {
+        let mut options = Self::default();
+        let Some(page) = document.sections.first().map(|section| &section.page) else {
+            return options;
+        };
+        if let (Some(width), Some(height)) = (page.width, page.height) {
+            options.width = width as f32 / TWIPS_PER_POINT;
+            options.height = height as f32 / TWIPS_PER_POINT;
+        }
+        if let Some(margins) = page.margins {
+            options.margins = [margins.top, margins.right, margins.bottom, margins.left]
+                .map(|margin| margin.unsigned_abs() as f32 / TWIPS_PER_POINT);
+        }
+        options
+    }
+
+    /// Print on this paper, turned on its side for `landscape`
+    pub fn paper(&mut self, paper: Paper, landscape: bool) {
+        let (width, height) = paper.size();
+        (self.width, self.height) = (width, height);
+        if landscape {
+            self.landscape();
+        }
+    }
+
+    /// Make the page wider than it's tall
+    pub fn landscape(&mut self) {
+        if self.width < self.height {
+            (self.width, self.height) = (self.height, self.width);
+        }
+    }
+
+    /// Characters that fit across the page between the margins
+    pub fn columns(&self) -> usize {
+        let [_, right, _, left] = self.margins;
+        ((self.width - left - right) / (self.font_size * CHARACTER_WIDTH)).floor() as usize
+    }
+
+    /// Lines that fit down the page between the margins
+    pub fn lines_per_page(&self) -> usize {
+        let [top, _, bottom, _] = self.margins;
+        ((self.height - top - bottom) / (self.font_size * LEADING)).floor() as usize
+    }
+}
+
+/// Parse a length such as 2.5cm, 20mm, 1in or 72pt into points; a bare
+/// number is points
+pub fn parse_length(text: &str) -> Result<f32, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let points_per_unit = match unit {
+        "" | "pt" => 1.0,
+        "in" => 72.0,
+        "cm" => 72.0 / 2.54,
+        "mm" => 72.0 / 25.4,
+        _ => return Err(format!("unknown unit {unit}; use pt, in, cm or mm")),
+    };
+    match number.trim().parse::<f32>() {
+        Ok(number) if number >= 0.0 => Ok(number * points_per_unit),
+        _ => Err(format!("'{text}' isn't a length such as 2cm or 1in")),
+    }
+}
+
+/// Lay the document out in pages of lines
+///
+/// Lines too long for the page, such as wide table rows, continue on the next.
+pub fn paginate(document: &Document, options: &PrintOptions) -> Result<Vec<Vec<String>>> {
+    let (columns, per_page) = (options.columns(), options.lines_per_page());
+    if options.font_size <= 0.0 {
+        bail!("The type size must be above 0pt");
+    }
+    if columns == 0 || per_page == 0 {
+        bail!(
+            "The margins leave no room for text at {}pt",
+            options.font_size
+        );
+    }
+    let mut pages = Vec::new();
+    let mut page: Vec<String> = Vec::new();
+    for elements in document
+        .elements
+        .split(|element| matches!(element, DocumentElement::PageBreak { .. }))
+    {
+        let mut text = Vec::new();
+        write_text_elements(
+            &mut text,
+            document,
+            elements,
+            columns,
+            Some(WrapMode::Columns(columns)),
+            LineBreaking::default(),
+            false,
+        )?;
+        let text = String::from_utf8_lossy(&text).replace('\t', "    ");
+        for line in text.trim_end().lines().flat_map(|line| fold(line, columns)) {
+            if page.len() == per_page {
+                pages.push(std::mem::take(&mut page));
+            }
+            // Pages don't start with a gap
+            if page.is_empty() && line.trim().is_empty() {
+                continue;
+            }
+            page.push(line);
+        }
+        if !page.is_empty() {
+            pages.push(std::mem::take(&mut page));
+        }
+    }
+    if pages.is_empty() {
+        pages.push(Vec::new());
+    }
+    Ok(pages)
+}
+
+/// Break a line at `columns`, wherever that falls
+fn fold(line: &str, columns: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    let mut width = 0;
+    for c in line.trim_end().chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width > columns {
+            lines.push(String::new());
+            width = 0;
+        }
+        lines.last_mut().unwrap().push(c);
+        width += char_width;
+    }
+    lines
+}
+
+/// Write the pages as a PostScript document
+pub fn render_postscript(
+    document: &Document,
+    pages: &[Vec<String>],
+    options: &PrintOptions,
+) -> String {
+    let [top, _, bottom, left] = options.margins;
+    let size = options.font_size;
+    let columns = options.columns();
+    let mut ps = String::new();
+    ps.push_str("%!PS-Adobe-3.0\n");
+    ps.push_str(&format!(
+        "%%Title: ({})\n",
+        postscript_text(&document.title)
+    ));
+    ps.push_str("%%Creator: doxx\n");
+    ps.push_str(&format!("%%Pages: {}\n", pages.len()));
+    ps.push_str(&format!(
+        "%%BoundingBox: 0 0 {} {}\n",
+        options.width.round(),
+        options.height.round()
+    ));
+    ps.push_str("%%DocumentNeededResources: font Courier\n");
+    ps.push_str("%%EndComments\n");
+    // Courier with Latin-1 in place of the standard encoding, for accented letters
+    ps.push_str("%%BeginProlog\n");
+    ps.push_str("/Courier findfont dup length dict begin\n");
+    ps.push_str("  { 1 index /FID ne { def } { pop pop } ifelse } forall\n");
+    ps.push_str("  /Encoding ISOLatin1Encoding def\n");
+    ps.push_str("currentdict end /Courier-Latin1 exch definefont pop\n");
+    ps.push_str("%%EndProlog\n");
+    ps.push_str("%%BeginSetup\n");
+    ps.push_str(&format!(
+        "<< /PageSize [{} {}] >> setpagedevice\n",
+        number(options.width),
+        number(options.height)
+    ));
+    ps.push_str("%%EndSetup\n");
+
+    let title: String = fold(&document.title, columns).swap_remove(0);
+    let leading = size * LEADING;
+    for (index, lines) in pages.iter().enumerate() {
+        let number_text = format!("Page {} of {}", index + 1, pages.len());
+        ps.push_str(&format!("%%Page: {0} {0}\n", index + 1));
+        ps.push_str(&format!(
+            "/Courier-Latin1 findfont {} scalefont setfont\n",
+            number(size)
+        ));
+        let mut show = |x: f32, y: f32, text: &str| {
+            ps.push_str(&format!(
+                "{} {} moveto ({}) show\n",
+                number(x),
+                number(y),
+                postscript_text(text)
+            ));
+        };
+        // The title above the text and the page number below it, in the margins
+        show(left, options.height - (top + size) / 2.0, &title);
+        for (row, line) in lines.iter().enumerate() {
+            if !line.trim().is_empty() {
+                show(
+                    left,
+                    options.height - top - size - row as f32 * leading,
+                    line,
+                );
+            }
+        }
+        let number_width = number_text.len() as f32 * size * CHARACTER_WIDTH;
+        show(
+            (options.width - number_width) / 2.0,
+            (bottom - size) / 2.0,
+            &number_text,
+        );
+        ps.push_str("showpage\n");
+    }
+    ps.push_str("%%Trailer\n%%EOF\n");
+    ps
+}
+
+/// A number as PostScript reads it, to two places
+fn number(value: f32) -> String {
+    let text = format!("{value:.2}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Text for a PostScript string in Latin-1, with what Latin-1 lacks spelled
+/// in ASCII or shown as ? so that columns still line up
+fn postscript_text(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in normalize_text(text, Normalization::Ascii).chars() {
+        let c = match c {
+            '─' | '━' | '═' => '-',
+            '│' | '┃' | '║' => '|',
+            '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' => '+',
+            '•' | '◦' | '▪' => '*',
+            '→' => '>',
+            '←' => '<',
+            c => c,
+        };
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            '\u{A0}'..='\u{FF}' => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            c => escaped.push_str(&"?".repeat(c.width().unwrap_or(0))),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_is_escaped_for_latin_1() {
+        assert_eq!(postscript_text("f(x) = a\\b"), "f\\(x\\) = a\\\\b");
+        assert_eq!(
+            postscript_text("Café “menu” – 5€"),
+            "Caf\\351 \"menu\" - 5?"
+        );
+        assert_eq!(postscript_text("┌─┐ 日本"), "+-+ ????");
+    }
+
+    #[test]
+    fn test_lengths_are_read_in_points() {
+        assert_eq!(parse_length("1in"), Ok(72.0));
+        assert_eq!(parse_length("36"), Ok(36.0));
+        assert!((parse_length("2.54cm").unwrap() - 72.0).abs() < 0.01);
+        assert!(parse_length("3em").is_err());
+        assert!(parse_length("-1in").is_err());
+    }
+}
@@ -0,0 +1,376 @@
+//! reStructuredText export
+//!
+//! Written for docutils and Sphinx. Section adornments follow the usual Python
+//! documentation order, tables are grid tables, and images and equations use
+//! the `image` and `math` directives.
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::document::*;
+
+use super::{cell_runs, heading_display_text, tagged_latex};
+
+/// Underline characters for section levels, outermost first; the title is
+/// over- and underlined with `=`
+const ADORNMENTS: [char; 6] = ['=', '-', '~', '^', '"', '\''];
+
+/// Render the whole document as one reStructuredText string
+pub fn render_rst(document: &Document) -> String {
+    let mut rst = String::new();
+
+    let title = escape_inline(document.title.trim());
+    if !title.is_empty() {
+        let rule = "=".repeat(title.width());
+        rst.push_str(&format!("{rule}\n{title}\n{rule}\n\n"));
+    }
+    if let Some(author) = &document.metadata.author {
+        rst.push_str(&format!(":Author: {author}\n\n"));
+    }
+
+    // docutils ranks adornments in the order it meets them, so a heading may only
+    // go one level deeper than the one before it
+    let mut depth = 0;
+    let mut previous: Option<&DocumentElement> = None;
+    for element in &document.elements {
+        if let DocumentElement::Heading { level, .. } = element {
+            depth = (*level as usize).clamp(1, depth + 1).min(ADORNMENTS.len());
+        }
+        // Adjacent lists would otherwise merge into one, and an indented
+        // quote would continue the list's last item
+        if matches!(previous, Some(DocumentElement::List { .. }))
+            && matches!(
+                element,
+                DocumentElement::List { .. } | DocumentElement::BlockQuote { .. }
+            )
+        {
+            rst.push_str("..\n\n");
+        }
+        rst.push_str(&element_to_rst(element, depth));
+        previous = Some(element);
+    }
+
+    rst
+}
+
+/// reStructuredText for a single element, including its trailing blank line
+fn element_to_rst(element: &DocumentElement, depth: usize) -> String {
+    let mut rst = String::new();
+    match element {
+        DocumentElement::Heading { .. } => {
+            let text = escape_inline(&heading_display_text(element).unwrap_or_default());
+            let underline = ADORNMENTS[depth.saturating_sub(1)]
+                .to_string()
+                .repeat(text.width().max(1));
+            rst.push_str(&format!("{text}\n{underline}\n\n"));
+        }
+        DocumentElement::Paragraph { runs, .. } => {
+            let text = runs_to_rst(runs);
+            if text.trim().is_empty() {
+                return rst;
+            }
+            if text.contains('\n') {
+                // Keep Word's line breaks as a line block
+                for line in text.lines() {
+                    rst.push_str(&format!("| {line}\n"));
+                }
+            } else {
+                rst.push_str(&escape_block_start(&text));
+                rst.push('\n');
+            }
+            rst.push('\n');
+        }
+        DocumentElement::List { items, ordered } => {
+            let marker = if *ordered { "#. " } else { "- " };
+            let mut previous_level = None;
+            for item in items {
+                // Nested lists need a blank line on either side
+                if previous_level.is_some_and(|level| level != item.level) {
+                    rst.push('\n');
+                }
+                let indent = " ".repeat(item.level as usize * marker.len());
+                let text = runs_to_rst(&item.runs).replace('\n', " ");
+                rst.push_str(&format!("{indent}{marker}{text}\n"));
+                previous_level = Some(item.level);
+            }
+            rst.push('\n');
+        }
+        DocumentElement::BlockQuote { runs } => {
+            let text = runs_to_rst(runs);
+            for line in text.trim().lines() {
+                rst.push_str(&format!("   {line}\n"));
+            }
+            rst.push('\n');
+        }
+        DocumentElement::CodeBlock { code } => {
+            rst.push_str("::\n\n");
+            for line in code.lines() {
+                if line.is_empty() {
+                    rst.push('\n');
+                } else {
+                    rst.push_str(&format!("   {line}\n"));
+                }
+            }
+            rst.push('\n');
+        }
+        DocumentElement::Table { table } => {
+            rst.push_str(&rst_table(table));
+            rst.push('\n');
+        }
+        DocumentElement::Image {
+            description,
+            width,
+            height,
+            image_path,
+            caption,
+            placement,
+            ..
+        } => {
+            let Some(path) = image_path else {
+                // Nothing to point at, such as a metafile that couldn't be converted
+                rst.push_str(&format!(
+                    "{}\n\n",
+                    escape_inline(&format!("[Image: {description}]"))
+                ));
+                return rst;
+            };
+            let target = path.to_string_lossy();
+            // Where a floating image was, as a comment
+            if let Some(note) = placement.as_ref().and_then(ImagePlacement::describe) {
+                rst.push_str(&format!(".. {note}\n\n"));
+            }
+            // A figure is an image followed by its caption
+            let directive = if caption.is_some() { "figure" } else { "image" };
+            rst.push_str(&format!(
+                ".. {directive}:: {target}\n   :alt: {description}\n"
+            ));
+            if let (Some(w), Some(h)) = (width, height) {
+                rst.push_str(&format!("   :width: {w}px\n   :height: {h}px\n"));
+            }
+            if let Some(side) = placement.as_ref().and_then(ImagePlacement::float_side) {
+                rst.push_str(&format!("   :align: {side}\n"));
+            }
+            if let Some(caption) = caption {
+                rst.push_str(&format!("\n   {}\n", escape_inline(caption)));
+            }
+            rst.push('\n');
+        }
+        DocumentElement::Equation {
+            latex,
+            fallback,
+            number,
+        } => {
+            if latex.trim().is_empty() {
+                if !fallback.trim().is_empty() {
+                    rst.push_str(&format!("{}\n\n", escape_inline(fallback.trim())));
+                }
+                return rst;
+            }
+            rst.push_str(".. math::\n\n");
+            for line in tagged_latex(latex, number.as_deref()).lines() {
+                rst.push_str(&format!("   {line}\n"));
+            }
+            rst.push('\n');
+        }
+        DocumentElement::PageBreak { page } => {
+            // Only the LaTeX builder has pages; other builders skip this
+            rst.push_str(&format!(
+                ".. page {page}\n\n.. raw:: latex\n\n   \\newpage\n\n"
+            ));
+        }
+        DocumentElement::HorizontalRule => {
+            rst.push_str("----\n\n");
+        }
+        DocumentElement::FileBoundary { name } => {
+            rst.push_str(&format!(".. file: {name}\n\n----\n\n"));
+        }
+    }
+    rst
+}
+
+fn runs_to_rst(runs: &[FormattedRun]) -> String {
+    let mut rst = String::new();
+    for (index, run) in runs.iter().enumerate() {
+        let (formatted, is_markup) = run_to_rst(run);
+        // Inline markup must start and end at a word boundary; an escaped space
+        // is one that renders as nothing
+        if is_markup
+            && !formatted.starts_with(char::is_whitespace)
+            && rst.chars().last().is_some_and(|c| !c.is_whitespace())
+        {
+            rst.push_str("\\ ");
+        }
+        rst.push_str(&formatted);
+        let next = runs.get(index + 1).and_then(|run| run.text.chars().next());
+        if is_markup
+            && !formatted.ends_with(char::is_whitespace)
+            && next.is_some_and(char::is_alphanumeric)
+        {
+            rst.push_str("\\ ");
+        }
+    }
+    rst
+}
+
+/// reStructuredText for a single run, and whether it is inline markup
+///
+/// Markup can't nest, so bold wins over italic, and both over super- and subscript.
+fn run_to_rst(run: &FormattedRun) -> (String, bool) {
+    if let Some(latex) = run
+        .text
+        .strip_prefix('$')
+        .and_then(|text| text.strip_suffix('$'))
+        .filter(|latex| !latex.is_empty())
+    {
+        return (format!(":math:`{latex}`"), true);
+    }
+
+    let text = run.formatting.apply_caps(&run.text);
+    let trimmed = text.trim();
+    let (open, close) = if run.formatting.bold {
+        ("**", "**")
+    } else if run.formatting.italic {
+        ("*", "*")
+    } else {
+        match run.formatting.vertical_align {
+            VerticalAlign::Superscript => (":sup:`", "`"),
+            VerticalAlign::Subscript => (":sub:`", "`"),
+            VerticalAlign::Baseline => ("", ""),
+        }
+    };
+    if trimmed.is_empty() || open.is_empty() {
+        return (escape_inline(&text), false);
+    }
+
+    // Markup can't start or end with a space, so keep those outside it
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    let escaped = escape_inline(trimmed);
+    (format!("{leading}{open}{escaped}{close}{trailing}"), true)
+}
+
+/// Backslash-escape characters that could start inline markup or references
+fn escape_inline(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '`' | '_' | '|') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Keep a paragraph from being read as a list item, directive or comment
+fn escape_block_start(text: &str) -> String {
+    let is_bullet = ["- ", "+ ", "#. ", ".. "]
+        .iter()
+        .any(|start| text.starts_with(start));
+    let is_enumerated = text.split_once(". ").is_some_and(|(number, _)| {
+        !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+    });
+    if is_bullet || is_enumerated {
+        format!("\\{text}")
+    } else {
+        text.to_string()
+    }
+}
+
+/// A table as a grid table, inside a `table` directive when it has a title
+fn rst_table(table: &TableData) -> String {
+    let columns = table
+        .headers
+        .len()
+        .max(table.rows.iter().map(Vec::len).max().unwrap_or(0));
+    if columns == 0 {
+        return String::new();
+    }
+
+    let row_texts = |cells: &[TableCell], header: bool| -> Vec<String> {
+        cells
+            .iter()
+            .map(|cell| runs_to_rst(&cell_runs(cell, header)).replace('\n', " "))
+            .collect()
+    };
+    let header_texts = row_texts(&table.headers, true);
+    let body_texts: Vec<Vec<String>> = table.rows.iter().map(|row| row_texts(row, false)).collect();
+    let mut widths = vec![1; columns];
+    for texts in std::iter::once(&header_texts).chain(&body_texts) {
+        for (i, text) in texts.iter().enumerate() {
+            widths[i] = widths[i].max(text.width());
+        }
+    }
+
+    let border = |fill: &str| {
+        let segments: Vec<String> = widths.iter().map(|w| fill.repeat(w + 2)).collect();
+        format!("+{}+\n", segments.join("+"))
+    };
+    let line = |cells: &[TableCell], texts: &[String]| {
+        let padded: Vec<String> = (0..columns)
+            .map(|i| {
+                let text = texts.get(i).map(String::as_str).unwrap_or_default();
+                let padding = widths[i] - text.width();
+                // Grid tables have no alignment; padding at least lines the text up
+                match cells.get(i).map(|cell| cell.alignment) {
+                    Some(TextAlignment::Right) => format!(" {}{text} ", " ".repeat(padding)),
+                    Some(TextAlignment::Center) => format!(
+                        " {}{text}{} ",
+                        " ".repeat(padding / 2),
+                        " ".repeat(padding - padding / 2)
+                    ),
+                    _ => format!(" {text}{} ", " ".repeat(padding)),
+                }
+            })
+            .collect();
+        format!("|{}|\n", padded.join("|"))
+    };
+
+    let mut grid = border("-");
+    if !table.headers.is_empty() {
+        grid.push_str(&line(&table.headers, &header_texts));
+        grid.push_str(&border("="));
+    }
+    for (row, texts) in table.rows.iter().zip(&body_texts) {
+        grid.push_str(&line(row, texts));
+        grid.push_str(&border("-"));
+    }
+
+    match &table.metadata.title {
+        Some(title) => {
+            let indented: String = grid.lines().map(|line| format!("   {line}\n")).collect();
+            format!(".. table:: {}\n\n{indented}", escape_inline(title))
+        }
+        None => grid,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str, bold: bool) -> FormattedRun {
+        FormattedRun {
+            text: text.to_string(),
+            formatting: TextFormatting {
+                bold,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_markup_inside_a_word_is_set_off_with_escaped_spaces() {
+        let runs = [run("un", false), run("believ", true), run("able", false)];
+        assert_eq!(runs_to_rst(&runs), "un\\ **believ**\\ able");
+
+        let runs = [run("a ", false), run("bold ", true), run("word", false)];
+        assert_eq!(runs_to_rst(&runs), "a **bold** word");
+    }
+
+    #[test]
+    fn test_paragraphs_never_start_a_block() {
+        assert_eq!(escape_block_start("- not a list"), "\\- not a list");
+        assert_eq!(escape_block_start("2024. A year"), "\\2024. A year");
+        assert_eq!(escape_block_start("3.5 percent"), "3.5 percent");
+        assert_eq!(escape_inline("snake_case *x*"), "snake\\_case \\*x\\*");
+    }
+}
@@ -0,0 +1,121 @@
+//! Heading anchors
+//!
+//! A heading's anchor is made from its text the way the site the export will
+//! be published on makes it, so that links into the document keep working
+//! when GitHub, GitLab or pandoc renders the headings itself. A heading whose
+//! anchor is taken gets the next free number after it: `intro`, `intro-1`,
+//! and an "Intro 1" heading after them `intro-1-1`.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::AnchorStyle;
+
+/// Hands out anchors for a document's headings, in order
+#[derive(Debug, Default)]
+pub struct Slugger {
+    style: AnchorStyle,
+    used: HashSet<String>,
+    /// The last number given to repeats of each anchor
+    repeats: HashMap<String, usize>,
+}
+
+impl Slugger {
+    pub fn new(style: AnchorStyle) -> Self {
+        Self {
+            style,
+            ..Self::default()
+        }
+    }
+
+    /// The anchor for the next heading, numbered if it's already taken
+    pub fn slug(&mut self, heading: &str) -> String {
+        let base = slugify(heading, self.style);
+        let mut slug = base.clone();
+        while self.used.contains(&slug) {
+            let repeat = self.repeats.entry(base.clone()).or_insert(0);
+            *repeat += 1;
+            slug = format!("{base}-{repeat}");
+        }
+        self.used.insert(slug.clone());
+        slug
+    }
+}
+
+/// A heading's anchor before repeats are numbered
+pub fn slugify(heading: &str, style: AnchorStyle) -> String {
+    let heading = heading.trim().to_lowercase();
+    match style {
+        AnchorStyle::Github => heading
+            .chars()
+            .filter_map(|c| match c {
+                ' ' => Some('-'),
+                c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+                _ => None,
+            })
+            .collect(),
+        AnchorStyle::Gitlab => {
+            let mut slug = String::new();
+            for c in heading.chars() {
+                let c = match c {
+                    ' ' => '-',
+                    c if c.is_alphanumeric() || c == '-' || c == '_' => c,
+                    _ => continue,
+                };
+                if !(c == '-' && slug.ends_with('-')) {
+                    slug.push(c);
+                }
+            }
+            slug
+        }
+        AnchorStyle::Pandoc => {
+            let words: Vec<String> = heading
+                .split_whitespace()
+                .map(|word| {
+                    word.chars()
+                        .filter(|&c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+                        .collect()
+                })
+                .collect();
+            let slug = words.join("-");
+            let slug = slug.trim_start_matches(|c: char| !c.is_alphabetic());
+            match slug {
+                "" => "section".to_string(),
+                slug => slug.to_string(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_styles_differ_on_punctuation_and_numbers() {
+        let all = |heading| {
+            [
+                AnchorStyle::Github,
+                AnchorStyle::Gitlab,
+                AnchorStyle::Pandoc,
+            ]
+            .map(|style| slugify(heading, style))
+        };
+        assert_eq!(
+            all("What's New & FAQ"),
+            ["whats-new--faq", "whats-new-faq", "whats-new--faq"]
+        );
+        assert_eq!(all("2.1 Results"), ["21-results", "21-results", "results"]);
+        assert_eq!(all("Café au lait"), ["café-au-lait"; 3]);
+        assert_eq!(all("42"), ["42", "42", "section"]);
+    }
+
+    #[test]
+    fn test_repeats_skip_anchors_already_taken() {
+        let mut slugger = Slugger::new(AnchorStyle::Github);
+        let anchors: Vec<String> = ["Intro", "Intro", "Intro 1", "Intro"]
+            .into_iter()
+            .map(|heading| slugger.slug(heading))
+            .collect();
+        assert_eq!(anchors, ["intro", "intro-1", "intro-1-1", "intro-2"]);
+    }
+}
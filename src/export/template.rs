@@ -0,0 +1,115 @@
+//! Export through a template of the user's own (`--template report.html.j2`)
+//!
+//! The template is rendered with [minijinja](https://docs.rs/minijinja), which
+//! reads Jinja syntax. It sees the document as [JSON export](super::json)
+//! describes it, with snake_case keys: `schema_version`, `title`, `metadata`
+//! and `elements`, and beside them `outline`, the headings in order, each with
+//! its `level`, `depth`, `title`, `anchor` and `element_index`. The `text`
+//! filter turns an element or a list of runs into its plain text.
+//!
+//! Templates named `.html`, `.htm` or `.xml` (before any `.j2`) have their
+//! output escaped for HTML; others, such as Markdown or wiki markup, don't.
+//! `{% include %}` and `{% extends %}` find templates beside the first one.
+
+use anyhow::{Context, Result};
+use minijinja::{path_loader, value::ValueKind, Environment, Value};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::{document::*, JsonCase};
+
+use super::{json::to_json_value, table_of_contents_with, ExportOptions};
+
+#[derive(Serialize)]
+struct OutlineEntry<'a> {
+    level: u8,
+    depth: usize,
+    title: &'a str,
+    anchor: &'a str,
+    element_index: usize,
+}
+
+/// Render a document through the template at `path`
+pub fn render_template(
+    document: &Document,
+    path: &Path,
+    options: &ExportOptions,
+) -> Result<String> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("Not a template file: {}", path.display()))?;
+    if !path.is_file() {
+        anyhow::bail!("Template not found: {}", path.display());
+    }
+    let mut environment = Environment::new();
+    environment.set_loader(path_loader(path.parent().unwrap_or(Path::new("."))));
+    environment.add_filter("text", text);
+    let template = environment
+        .get_template(name)
+        .with_context(|| format!("Failed to read template {}", path.display()))?;
+
+    let headings = table_of_contents_with(document, false, options.anchors);
+    let outline: Vec<OutlineEntry> = headings
+        .iter()
+        .map(|entry| OutlineEntry {
+            level: entry.level,
+            depth: entry.depth,
+            title: &entry.title,
+            anchor: &entry.anchor,
+            element_index: entry.element_index,
+        })
+        .collect();
+    let mut context = to_json_value(document, JsonCase::Snake)?;
+    context["outline"] = serde_json::to_value(outline)?;
+
+    template
+        .render(context)
+        .with_context(|| format!("Failed to render template {}", path.display()))
+}
+
+/// The plain text of an element, a run or a list of either; a list's items
+/// go on lines of their own
+fn text(value: Value) -> String {
+    match value.kind() {
+        ValueKind::String => value.as_str().unwrap_or_default().to_string(),
+        ValueKind::Seq => {
+            let parts: Vec<Value> = value.try_iter().into_iter().flatten().collect();
+            let separator = match parts.first().map(|part| part.get_attr("runs")) {
+                Some(Ok(runs)) if !runs.is_undefined() => "\n",
+                _ => "",
+            };
+            parts
+                .into_iter()
+                .map(text)
+                .collect::<Vec<_>>()
+                .join(separator)
+        }
+        ValueKind::Map => ["runs", "items", "text", "code", "latex", "caption"]
+            .into_iter()
+            .filter_map(|key| value.get_attr(key).ok())
+            .find(|inner| !inner.is_undefined() && !inner.is_none())
+            .map(text)
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_joins_runs_and_list_items() {
+        let run = |text: &str| serde_json::json!({ "text": text, "bold": false });
+        let paragraph =
+            serde_json::json!({ "type": "paragraph", "runs": [run("Hello, "), run("world")] });
+        assert_eq!(text(Value::from_serialize(&paragraph)), "Hello, world");
+
+        let list = serde_json::json!({
+            "type": "list",
+            "items": [{ "runs": [run("one")] }, { "runs": [run("two")] }],
+        });
+        assert_eq!(text(Value::from_serialize(&list)), "one\ntwo");
+    }
+}
@@ -0,0 +1,555 @@
+//! Round-trip checks for `doxx verify`
+//!
+//! The document is exported to Markdown again and both exports are read back
+//! as blocks: headings, tables and runs of text. Formatting, wrapping, image
+//! paths and the metadata and contents sections doxx adds are left out, so
+//! only changes in what the document says are reported. Blocks are lined up by
+//! their longest common subsequence, and a removal next to an insertion of the
+//! same kind is reported as one change.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::{export_document_to_string, ExportOptions, CONTENTS_HEADING};
+use crate::{document::Document, ExportFormat};
+
+/// A heading, table or piece of text read back from Markdown
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Heading { level: usize, text: String },
+    Table { rows: Vec<Vec<String>> },
+    Text(String),
+}
+
+impl Block {
+    fn kind(&self) -> &'static str {
+        match self {
+            Block::Heading { .. } => "heading",
+            Block::Table { .. } => "table",
+            Block::Text(_) => "text",
+        }
+    }
+
+    /// One-line summary, e.g. "## Costs" or "table of 4 rows: Metric | Q4 2024"
+    fn describe(&self) -> String {
+        match self {
+            Block::Heading { level, text } => format!("{} {text}", "#".repeat(*level)),
+            Block::Table { rows } => format!(
+                "table of {} rows: {}",
+                rows.len(),
+                rows.first().map(|row| row.join(" | ")).unwrap_or_default()
+            ),
+            Block::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// A block and the line of the Markdown it starts on
+#[derive(Debug, Clone)]
+struct Located {
+    block: Block,
+    line: usize,
+}
+
+/// What differs between the document and its export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DifferenceKind {
+    /// A heading the document has that the export lacks
+    MissingHeading,
+    /// A heading the export has that the document no longer does
+    ExtraHeading,
+    ChangedHeading,
+    /// A table the export dropped
+    MissingTable,
+    ExtraTable,
+    ChangedTable,
+    /// Text the document has that the export lacks
+    MissingText,
+    ExtraText,
+    ChangedText,
+}
+
+impl DifferenceKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::MissingHeading => "missing heading",
+            Self::ExtraHeading => "extra heading",
+            Self::ChangedHeading => "changed heading",
+            Self::MissingTable => "missing table",
+            Self::ExtraTable => "extra table",
+            Self::ChangedTable => "changed table",
+            Self::MissingText => "missing text",
+            Self::ExtraText => "extra text",
+            Self::ChangedText => "changed text",
+        }
+    }
+}
+
+/// One difference, with the export's line it's at or would go before
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Difference {
+    pub kind: DifferenceKind,
+    pub line: usize,
+    /// What the document has here
+    pub document: Option<String>,
+    /// What the export has here
+    pub export: Option<String>,
+    /// The first cell that differs, for changed tables
+    pub detail: Option<String>,
+}
+
+/// Differences between a document and a Markdown export of it
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub source: String,
+    pub export: String,
+    pub differences: Vec<Difference>,
+}
+
+impl VerifyReport {
+    /// The report as `doxx verify` prints it
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("Verifying {} against {}", self.export, self.source)];
+        if self.differences.is_empty() {
+            lines.push(format!("No differences: {} is up to date", self.export));
+            return lines;
+        }
+        lines.push(String::new());
+        for difference in &self.differences {
+            lines.push(format!("{}: {}", difference.line, difference.kind.name()));
+            let (document, export) = match (&difference.document, &difference.export) {
+                (Some(document), Some(export)) => {
+                    let (document, export) = from_first_change(document, export);
+                    (Some(document), Some(export))
+                }
+                (document, export) => (document.clone(), export.clone()),
+            };
+            if let Some(document) = document {
+                lines.push(format!("    document: {}", shorten(&document)));
+            }
+            if let Some(export) = export {
+                lines.push(format!("    export:   {}", shorten(&export)));
+            }
+            if let Some(detail) = &difference.detail {
+                lines.push(format!("    {detail}"));
+            }
+        }
+        let mut counts: Vec<(DifferenceKind, usize)> = Vec::new();
+        for difference in &self.differences {
+            match counts.iter_mut().find(|(kind, _)| *kind == difference.kind) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((difference.kind, 1)),
+            }
+        }
+        counts.sort_by_key(|(kind, _)| *kind as usize);
+        let counts: Vec<String> = counts
+            .iter()
+            .map(|(kind, count)| format!("{count} {}", kind.name()))
+            .collect();
+        let total = self.differences.len();
+        lines.push(String::new());
+        lines.push(format!(
+            "{total} difference{}: {}",
+            if total == 1 { "" } else { "s" },
+            counts.join(", ")
+        ));
+        lines
+    }
+}
+
+/// Two texts from a little before where they first differ
+fn from_first_change(a: &str, b: &str) -> (String, String) {
+    const LEAD: usize = 20;
+    let same = a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count();
+    if same <= LEAD {
+        return (a.to_string(), b.to_string());
+    }
+    let from = |text: &str| format!("…{}", text.chars().skip(same - LEAD).collect::<String>());
+    (from(a), from(b))
+}
+
+/// Text cut to a line's length
+fn shorten(text: &str) -> String {
+    const LIMIT: usize = 72;
+    match text.char_indices().nth(LIMIT) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Compare a Markdown export made earlier with the document as it is now
+pub fn verify_export(document: &Document, export: &str) -> Result<Vec<Difference>> {
+    let markdown =
+        export_document_to_string(document, &ExportFormat::Markdown, &ExportOptions::default())?;
+    Ok(compare_markdown(&markdown, export))
+}
+
+/// Compare Markdown exported earlier (`export`) with Markdown exported now
+/// (`document`)
+pub fn compare_markdown(document: &str, export: &str) -> Vec<Difference> {
+    let expected = read_blocks(document);
+    let found = read_blocks(export);
+    let end_line = export.lines().count() + 1;
+    let line_at = |index: usize| found.get(index).map_or(end_line, |block| block.line);
+
+    let mut differences = Vec::new();
+    let (mut missing, mut extra): (Vec<&Block>, Vec<&Located>) = (Vec::new(), Vec::new());
+    let mut flush = |missing: &mut Vec<&Block>, extra: &mut Vec<&Located>, next: usize| {
+        // Pair each removal with the next insertion of the same kind
+        for block in missing.drain(..) {
+            let paired = extra
+                .iter()
+                .position(|located| located.block.kind() == block.kind());
+            match paired.map(|index| extra.remove(index)) {
+                Some(located) => differences.push(changed(block, located)),
+                None => differences.push(Difference {
+                    kind: match block {
+                        Block::Heading { .. } => DifferenceKind::MissingHeading,
+                        Block::Table { .. } => DifferenceKind::MissingTable,
+                        Block::Text(_) => DifferenceKind::MissingText,
+                    },
+                    line: next,
+                    document: Some(block.describe()),
+                    export: None,
+                    detail: None,
+                }),
+            }
+        }
+        for located in extra.drain(..) {
+            differences.push(Difference {
+                kind: match located.block {
+                    Block::Heading { .. } => DifferenceKind::ExtraHeading,
+                    Block::Table { .. } => DifferenceKind::ExtraTable,
+                    Block::Text(_) => DifferenceKind::ExtraText,
+                },
+                line: located.line,
+                document: None,
+                export: Some(located.block.describe()),
+                detail: None,
+            });
+        }
+    };
+    for step in align(&expected, &found) {
+        match step {
+            Step::Same(index) => flush(&mut missing, &mut extra, line_at(index)),
+            Step::Missing(index) => missing.push(&expected[index].block),
+            Step::Extra(index) => extra.push(&found[index]),
+        }
+    }
+    flush(&mut missing, &mut extra, end_line);
+    differences.sort_by_key(|difference| difference.line);
+    differences
+}
+
+/// A block of the document paired with the block of the export in its place
+fn changed(block: &Block, located: &Located) -> Difference {
+    let (kind, detail) = match (block, &located.block) {
+        (Block::Heading { .. }, _) => (DifferenceKind::ChangedHeading, None),
+        (Block::Table { rows: expected }, Block::Table { rows: found }) => (
+            DifferenceKind::ChangedTable,
+            Some(table_change(expected, found)),
+        ),
+        _ => (DifferenceKind::ChangedText, None),
+    };
+    Difference {
+        kind,
+        line: located.line,
+        document: Some(block.describe()),
+        export: Some(located.block.describe()),
+        detail,
+    }
+}
+
+/// Where two tables first differ
+fn table_change(expected: &[Vec<String>], found: &[Vec<String>]) -> String {
+    for (row, (expected_row, found_row)) in expected.iter().zip(found).enumerate() {
+        let columns = expected_row.len().max(found_row.len());
+        for column in 0..columns {
+            let cell = |cells: &[String]| cells.get(column).cloned().unwrap_or_default();
+            let (was, now) = (cell(expected_row), cell(found_row));
+            if was != now {
+                return format!(
+                    "row {}, column {}: document has \"{was}\", export has \"{now}\"",
+                    row + 1,
+                    column + 1
+                );
+            }
+        }
+    }
+    format!(
+        "the document has {} rows, the export {}",
+        expected.len(),
+        found.len()
+    )
+}
+
+enum Step {
+    /// A block found in both, by its index in the export
+    Same(usize),
+    Missing(usize),
+    Extra(usize),
+}
+
+/// Line up two block lists by their longest common subsequence
+fn align(expected: &[Located], found: &[Located]) -> Vec<Step> {
+    let same = |i: usize, j: usize| expected[i].block == found[j].block;
+    // What's the same at either end needs no table
+    let mut start = 0;
+    while start < expected.len() && start < found.len() && same(start, start) {
+        start += 1;
+    }
+    let mut end = 0;
+    while end < expected.len() - start
+        && end < found.len() - start
+        && same(expected.len() - 1 - end, found.len() - 1 - end)
+    {
+        end += 1;
+    }
+    let (rows, columns) = (expected.len() - start - end, found.len() - start - end);
+
+    // lengths[i][j]: common subsequence of the middles from i and j on
+    let width = columns + 1;
+    let mut lengths = vec![0u32; (rows + 1) * width];
+    for i in (0..rows).rev() {
+        for j in (0..columns).rev() {
+            lengths[i * width + j] = if same(start + i, start + j) {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut steps: Vec<Step> = (0..start).map(Step::Same).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < rows || j < columns {
+        if i < rows && j < columns && same(start + i, start + j) {
+            steps.push(Step::Same(start + j));
+            (i, j) = (i + 1, j + 1);
+        } else if j == columns
+            || (i < rows && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            steps.push(Step::Missing(start + i));
+            i += 1;
+        } else {
+            steps.push(Step::Extra(start + j));
+            j += 1;
+        }
+    }
+    steps.extend((0..end).map(|k| Step::Same(start + columns + k)));
+    steps
+}
+
+/// Read Markdown back as blocks
+///
+/// Continuation lines are joined, so exports wrapped at any width read alike.
+fn read_blocks(markdown: &str) -> Vec<Located> {
+    let mut blocks = Vec::new();
+    let mut text: Option<Located> = None;
+    let mut table: Option<Located> = None;
+    let mut fence: Option<(String, Located)> = None;
+    // Inside the metadata or contents section doxx puts at the top
+    let mut skipping = false;
+
+    let finish = |blocks: &mut Vec<Located>, pending: &mut Option<Located>| {
+        if let Some(located) = pending.take() {
+            blocks.push(located);
+        }
+    };
+
+    for (index, raw) in markdown.lines().enumerate() {
+        let line_number = index + 1;
+        if let Some((marker, located)) = &mut fence {
+            if raw.trim() == marker {
+                let (_, located) = fence.take().unwrap();
+                blocks.push(located);
+            } else if let Block::Text(code) = &mut located.block {
+                if !code.is_empty() {
+                    code.push('\n');
+                }
+                code.push_str(raw);
+            }
+            continue;
+        }
+        let line = raw.trim();
+        if !line.starts_with('|') {
+            finish(&mut blocks, &mut table);
+        }
+        if skipping {
+            skipping = line != "---";
+            continue;
+        }
+
+        if line.is_empty() || line == "---" || (line.starts_with("<!--") && line.ends_with("-->")) {
+            finish(&mut blocks, &mut text);
+        } else if line.starts_with("```") || line.starts_with("~~~") {
+            finish(&mut blocks, &mut text);
+            let marker: String = line
+                .chars()
+                .take_while(|&c| c == line.as_bytes()[0] as char)
+                .collect();
+            fence = Some((
+                marker,
+                Located {
+                    block: Block::Text(String::new()),
+                    line: line_number,
+                },
+            ));
+        } else if let Some(level) = heading_level(line) {
+            finish(&mut blocks, &mut text);
+            let heading = inline_text(line[level..].trim());
+            if level == 2 && (heading == "Document Information" || heading == CONTENTS_HEADING) {
+                skipping = true;
+                continue;
+            }
+            blocks.push(Located {
+                block: Block::Heading {
+                    level,
+                    text: heading,
+                },
+                line: line_number,
+            });
+        } else if line.starts_with('|') {
+            finish(&mut blocks, &mut text);
+            let cells: Vec<String> = line
+                .trim_matches('|')
+                .split('|')
+                .map(|cell| inline_text(cell.trim()))
+                .collect();
+            // The alignment row
+            if cells
+                .iter()
+                .all(|cell| !cell.is_empty() && cell.chars().all(|c| matches!(c, ':' | '-')))
+            {
+                continue;
+            }
+            let located = table.get_or_insert(Located {
+                block: Block::Table { rows: Vec::new() },
+                line: line_number,
+            });
+            if let Block::Table { rows } = &mut located.block {
+                rows.push(cells);
+            }
+        } else if let Some(item) = list_item(line) {
+            // Each item is a block of its own
+            finish(&mut blocks, &mut text);
+            text = Some(Located {
+                block: Block::Text(inline_text(item)),
+                line: line_number,
+            });
+        } else if is_breadcrumb(line) {
+            finish(&mut blocks, &mut text);
+        } else {
+            let line = inline_text(line.trim_start_matches("> "));
+            match &mut text {
+                Some(Located {
+                    block: Block::Text(paragraph),
+                    ..
+                }) => {
+                    paragraph.push(' ');
+                    paragraph.push_str(&line);
+                }
+                _ => {
+                    text = Some(Located {
+                        block: Block::Text(line),
+                        line: line_number,
+                    })
+                }
+            }
+        }
+    }
+    finish(&mut blocks, &mut table);
+    finish(&mut blocks, &mut text);
+    if let Some((_, located)) = fence {
+        blocks.push(located);
+    }
+    blocks
+}
+
+/// The level of an ATX heading ("## Costs" is 2)
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.len() - line.trim_start_matches('#').len();
+    let rest = &line[level..];
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' '))).then_some(level)
+}
+
+/// The text of a list item, without its bullet or number
+fn list_item(line: &str) -> Option<&str> {
+    if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(item);
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = &line[digits..];
+    (digits > 0 && (rest.starts_with(". ") || rest.starts_with(") "))).then(|| &rest[2..])
+}
+
+/// The trail of links above a section with `--breadcrumbs`
+fn is_breadcrumb(line: &str) -> bool {
+    line.starts_with("_[") && line.ends_with(")_") && line.contains("](#")
+}
+
+/// Inline Markdown as plain text: without emphasis, HTML tags, escapes or
+/// image paths, and with runs of spaces made one
+fn inline_text(markdown: &str) -> String {
+    let mut text = String::with_capacity(markdown.len());
+    let mut chars = markdown.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    text.push(escaped);
+                }
+            }
+            '*' | '~' => {}
+            // Emphasis with underscores, but not snake_case
+            '_' if text.is_empty()
+                || text.ends_with(' ')
+                || chars.peek().is_none_or(|next| *next == ' ') => {}
+            '<' => {
+                let tag: String = chars.clone().take_while(|&c| c != '>').collect();
+                let name = tag.trim_start_matches('/');
+                if tag.starts_with("!--") {
+                    // A comment, such as an image's size
+                    chars.by_ref().take_while(|&c| c != '>').for_each(drop);
+                } else if matches!(name, "mark" | "sup" | "sub" | "u" | "br" | "br/" | "br /") {
+                    chars.nth(tag.chars().count());
+                } else {
+                    text.push(c);
+                }
+            }
+            '!' if chars.peek() == Some(&'[') => {
+                // ![alt](path): the alt text only, since image paths change
+                chars.next();
+                let alt: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                if chars.peek() == Some(&'(') {
+                    chars.by_ref().take_while(|&c| c != ')').for_each(drop);
+                }
+                text.push_str(&format!("[Image: {alt}]"));
+            }
+            _ => text.push(c),
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formatting_and_wrapping_are_not_differences() {
+        let document = "# Report\n\n**Revenue** rose by *20%* this <mark>quarter</mark>.\n\n![Chart](/tmp/a/image1.png)\n";
+        let export = "# Report\n\nRevenue rose by 20%\nthis quarter.\n\n![Chart](images/image1.png) <!-- 10x10 -->\n";
+        assert_eq!(compare_markdown(document, export), []);
+    }
+
+    #[test]
+    fn test_snake_case_keeps_its_underscores() {
+        assert_eq!(
+            inline_text("set _max_retries_ to 3"),
+            "set max_retries to 3"
+        );
+        assert_eq!(inline_text(r"1\. not a list"), "1. not a list");
+    }
+}
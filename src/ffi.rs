@@ -0,0 +1,127 @@
+//! C bindings
+//!
+//! A small `extern "C"` API over the parser, for Python (ctypes, cffi), Node
+//! (ffi-napi, koffi) and anything else that can load a shared library. Every
+//! function takes the raw bytes of a .docx and returns a NUL-terminated UTF-8
+//! string that the caller owns and must release with [`doxx_string_free`].
+//!
+//! On failure a function returns NULL, and [`doxx_last_error`] describes what
+//! went wrong. The message is kept per thread until that thread's next call.
+//! The declarations are in `include/doxx.h`.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use anyhow::{bail, Result};
+
+use crate::{
+    document::{Document, ImageOptions},
+    export::{json, render_markdown, ExportOptions},
+    JsonCase,
+};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Parse a .docx and return the document as doxx's JSON export
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes, or be NULL when `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn doxx_parse(bytes: *const u8, len: usize) -> *mut c_char {
+    // SAFETY: upheld by the caller, as documented above
+    let data = unsafe { input(bytes, len) };
+    call(|| json::render_json(&load(data?)?, JsonCase::Snake))
+}
+
+/// Parse a .docx and return it as Markdown, as `--export markdown` writes it
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes, or be NULL when `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn doxx_export_markdown(bytes: *const u8, len: usize) -> *mut c_char {
+    // SAFETY: upheld by the caller, as documented above
+    let data = unsafe { input(bytes, len) };
+    call(|| Ok(render_markdown(&load(data?)?, &ExportOptions::default())))
+}
+
+/// Release a string returned by any other `doxx_` function; NULL is ignored
+///
+/// # Safety
+///
+/// `string` must be NULL or a pointer returned by this library that hasn't
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn doxx_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        // SAFETY: the pointer came from `CString::into_raw` in `call`
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+/// Why the last call on this thread returned NULL, or NULL if it succeeded
+///
+/// The string belongs to the library and stays valid until the thread's next
+/// `doxx_` call; don't free it.
+#[no_mangle]
+pub extern "C" fn doxx_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// The library version, such as `0.1.2`; a static string, not to be freed
+#[no_mangle]
+pub extern "C" fn doxx_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Borrow the caller's buffer
+///
+/// # Safety
+///
+/// As for [`doxx_parse`].
+unsafe fn input<'a>(bytes: *const u8, len: usize) -> Result<&'a [u8]> {
+    if bytes.is_null() {
+        if len == 0 {
+            return Ok(&[]);
+        }
+        bail!("bytes is NULL but len is {len}");
+    }
+    // SAFETY: the caller promises `len` readable bytes at `bytes`
+    Ok(unsafe { std::slice::from_raw_parts(bytes, len) })
+}
+
+fn load(data: &[u8]) -> Result<Document> {
+    Document::from_bytes(data, ImageOptions::default())
+}
+
+/// Run `f`, handing its string to the caller or recording its error
+///
+/// Panics are caught here: unwinding into C is undefined behaviour.
+fn call(f: impl FnOnce() -> Result<String>) -> *mut c_char {
+    let outcome = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(output)) => CString::new(output).map_err(|_| "output contains a NUL byte".into()),
+        Ok(Err(error)) => Err(format!("{error:#}")),
+        Err(_) => Err("doxx panicked while reading the document".to_string()),
+    };
+    match outcome {
+        Ok(output) => {
+            set_last_error(None);
+            output.into_raw()
+        }
+        Err(message) => {
+            set_last_error(Some(message));
+            ptr::null_mut()
+        }
+    }
+}
+
+fn set_last_error(message: Option<String>) {
+    // Interior NULs can't be represented; drop them rather than lose the message
+    let message = message.map(|m| CString::new(m.replace('\0', "")).unwrap_or_default());
+    LAST_ERROR.with(|error| *error.borrow_mut() = message);
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use std::collections::HashMap;
 use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::io::{Cursor, Read, Write};
 use std::path::{Path, PathBuf};
 use zip::ZipArchive;
 
@@ -9,11 +9,27 @@ use zip::ZipArchive;
 type ImageList<'a> = Vec<(&'a String, &'a PathBuf)>;
 type ExtractedImages = Vec<(String, PathBuf)>;
 
+/// Largest side, in pixels, of a rasterized SVG
+#[cfg(feature = "svg")]
+const SVG_RASTER_SIZE: f32 = 1024.0;
+
 /// Manages extraction of images from DOCX files
 #[derive(Debug)]
 pub struct ImageExtractor {
     temp_dir: PathBuf,
     extracted_images: HashMap<String, PathBuf>, // relationship_id -> temp_file_path
+    /// Vector images, by media file name like `extracted_images`
+    vector_images: HashMap<String, VectorImage>,
+}
+
+/// What became of a vector image the terminal can't draw as it is
+#[derive(Debug, Clone, PartialEq)]
+pub enum VectorImage {
+    /// An SVG rendered to a PNG at this path
+    Rasterized(PathBuf),
+    /// An EMF or WMF (or an SVG that couldn't be rendered), described by its
+    /// format and size, such as "EMF drawing, 120 × 80 mm"
+    Placeholder(String),
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +39,11 @@ pub enum ImageFormat {
     Gif,
     Bmp,
     Tiff,
+    Svg,
+    /// Enhanced Metafile, Windows' vector format
+    Emf,
+    /// Windows Metafile, EMF's 16-bit predecessor
+    Wmf,
 }
 
 impl ImageFormat {
@@ -35,10 +56,32 @@ impl ImageFormat {
             "gif" => Some(Self::Gif),
             "bmp" => Some(Self::Bmp),
             "tiff" | "tif" => Some(Self::Tiff),
+            "svg" => Some(Self::Svg),
+            "emf" => Some(Self::Emf),
+            "wmf" => Some(Self::Wmf),
             _ => None,
         }
     }
 
+    /// The media type, for `data:` URIs
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Gif => "image/gif",
+            Self::Bmp => "image/bmp",
+            Self::Tiff => "image/tiff",
+            Self::Svg => "image/svg+xml",
+            Self::Emf => "image/emf",
+            Self::Wmf => "image/wmf",
+        }
+    }
+
+    /// Whether the image is drawn from vector instructions rather than pixels
+    pub fn is_vector(&self) -> bool {
+        matches!(self, Self::Svg | Self::Emf | Self::Wmf)
+    }
+
     pub fn to_extension(&self) -> &'static str {
         match self {
             Self::Png => "png",
@@ -46,6 +89,9 @@ impl ImageFormat {
             Self::Gif => "gif",
             Self::Bmp => "bmp",
             Self::Tiff => "tiff",
+            Self::Svg => "svg",
+            Self::Emf => "emf",
+            Self::Wmf => "wmf",
         }
     }
 }
@@ -59,36 +105,70 @@ impl ImageExtractor {
         Ok(Self {
             temp_dir,
             extracted_images: HashMap::new(),
+            vector_images: HashMap::new(),
         })
     }
 
     /// Extract all images from a DOCX file
     pub fn extract_images_from_docx(&mut self, docx_path: &Path) -> Result<()> {
-        let file = File::open(docx_path)?;
-        let mut archive = ZipArchive::new(file)?;
+        let data = fs::read(docx_path)?;
+        self.extract_images_from_bytes(&data)
+    }
+
+    /// Extract all images from an in-memory DOCX package
+    pub fn extract_images_from_bytes(&mut self, data: &[u8]) -> Result<()> {
+        let mut archive = ZipArchive::new(Cursor::new(data))?;
 
         // Look for images in the word/media/ folder
+        let mut media = Vec::new();
         for i in 0..archive.len() {
             let mut file = archive.by_index(i)?;
-            let outpath = file.name().to_string(); // Clone the name to avoid borrow issues
+            if file.name().starts_with("word/media/") {
+                let name = file.name().to_string();
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer)?;
+                media.push((name, buffer));
+            }
+        }
 
+        self.extract_images_from_parts(
+            media
+                .iter()
+                .map(|(name, data)| (name.as_str(), data.as_slice())),
+        )
+    }
+
+    /// Extract images from already-decompressed package parts given as (part name, bytes)
+    ///
+    /// Parts outside `word/media/` or with a non-image extension are skipped.
+    /// SVGs are rasterized to PNG and EMF and WMF drawings described; see
+    /// [`get_vector_image`](Self::get_vector_image).
+    pub fn extract_images_from_parts<'a>(
+        &mut self,
+        parts: impl IntoIterator<Item = (&'a str, &'a [u8])>,
+    ) -> Result<()> {
+        for (part_name, data) in parts {
             // Check if this is an image file in the media folder
-            if outpath.starts_with("word/media/") && self.is_image_file(&outpath) {
-                let filename = Path::new(&outpath)
+            if part_name.starts_with("word/media/") && self.is_image_file(part_name) {
+                let filename = Path::new(part_name)
                     .file_name()
                     .and_then(|n| n.to_str())
                     .unwrap_or("unknown");
 
+                if let Some(format) =
+                    ImageFormat::from_filename(filename).filter(ImageFormat::is_vector)
+                {
+                    let image = self.convert_vector_image(filename, &format, data);
+                    self.vector_images.insert(filename.to_string(), image);
+                    continue;
+                }
+
                 // Create a unique temp file path
                 let temp_file_path = self.temp_dir.join(filename);
 
-                // Read the image data
-                let mut buffer = Vec::new();
-                file.read_to_end(&mut buffer)?;
-
                 // Write to temp file
                 let mut temp_file = File::create(&temp_file_path)?;
-                temp_file.write_all(&buffer)?;
+                temp_file.write_all(data)?;
 
                 // Store the mapping (we'll enhance this with proper relationship parsing later)
                 let rel_id = filename.to_string(); // Simplified for now
@@ -96,11 +176,6 @@ impl ImageExtractor {
             }
         }
 
-        println!(
-            "Extracted {} images to {}",
-            self.extracted_images.len(),
-            self.temp_dir.display()
-        );
         Ok(())
     }
 
@@ -118,6 +193,39 @@ impl ImageExtractor {
         self.extracted_images.get(rel_id)
     }
 
+    /// What became of a vector image (SVG, EMF or WMF), by media file name
+    pub fn get_vector_image(&self, name: &str) -> Option<&VectorImage> {
+        self.vector_images.get(name)
+    }
+
+    /// Rasterize an SVG, or describe a drawing that can't be
+    #[cfg_attr(not(feature = "svg"), allow(unused_variables))]
+    fn convert_vector_image(
+        &self,
+        filename: &str,
+        format: &ImageFormat,
+        data: &[u8],
+    ) -> VectorImage {
+        #[cfg(feature = "svg")]
+        if matches!(format, ImageFormat::Svg) {
+            let target = self.temp_dir.join(format!("{filename}.png"));
+            if rasterize_svg(data)
+                .and_then(|png| Ok(fs::write(&target, png)?))
+                .is_ok()
+            {
+                return VectorImage::Rasterized(target);
+            }
+        }
+
+        let name = format.to_extension().to_uppercase();
+        match vector_size_mm(format, data) {
+            Some((width, height)) => {
+                VectorImage::Placeholder(format!("{name} drawing, {width:.0} × {height:.0} mm"))
+            }
+            None => VectorImage::Placeholder(format!("{name} drawing")),
+        }
+    }
+
     /// List all extracted images
     pub fn list_images(&self) -> ImageList<'_> {
         self.extracted_images.iter().collect()
@@ -158,6 +266,69 @@ impl ImageExtractor {
     }
 }
 
+/// Render an SVG to PNG, scaled so its longer side is [`SVG_RASTER_SIZE`]
+#[cfg(feature = "svg")]
+fn rasterize_svg(data: &[u8]) -> Result<Vec<u8>> {
+    use resvg::{tiny_skia, usvg};
+
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_data(data, &options)?;
+    let size = tree.size();
+    let scale = SVG_RASTER_SIZE / size.width().max(size.height());
+    let mut pixmap = tiny_skia::Pixmap::new(
+        (size.width() * scale).ceil() as u32,
+        (size.height() * scale).ceil() as u32,
+    )
+    .ok_or_else(|| anyhow::anyhow!("SVG has no area to draw"))?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    Ok(pixmap.encode_png()?)
+}
+
+/// The size of an EMF or WMF drawing in millimetres, from its header
+///
+/// An EMF header gives its frame in hundredths of a millimetre. A WMF only
+/// has a size when it starts with a placeable header, which gives a bounding
+/// box in units of so many to the inch.
+fn vector_size_mm(format: &ImageFormat, data: &[u8]) -> Option<(f32, f32)> {
+    let i32_at = |offset: usize| {
+        Some(i32::from_le_bytes(
+            data.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    let i16_at = |offset: usize| {
+        Some(i16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?) as f32)
+    };
+    let size = match format {
+        ImageFormat::Emf => {
+            // EMR_HEADER, with " EMF" at offset 40
+            if i32_at(0)? != 1 || data.get(40..44)? != b" EMF" {
+                return None;
+            }
+            let (left, top, right, bottom) = (i32_at(24)?, i32_at(28)?, i32_at(32)?, i32_at(36)?);
+            ((right - left) as f32 / 100.0, (bottom - top) as f32 / 100.0)
+        }
+        ImageFormat::Wmf => {
+            if data.get(0..4)? != [0xD7, 0xCD, 0xC6, 0x9A] {
+                return None;
+            }
+            let (left, top, right, bottom) = (i16_at(6)?, i16_at(8)?, i16_at(10)?, i16_at(12)?);
+            let per_inch = u16::from_le_bytes(data.get(14..16)?.try_into().ok()?) as f32;
+            if per_inch == 0.0 {
+                return None;
+            }
+            let mm = |units: f32| units / per_inch * 25.4;
+            (mm(right - left), mm(bottom - top))
+        }
+        _ => return None,
+    };
+    (size.0 > 0.0 && size.1 > 0.0).then_some(size)
+}
+
 impl Drop for ImageExtractor {
     fn drop(&mut self) {
         // Don't automatically clean up temp files - let them persist
@@ -198,6 +369,29 @@ mod tests {
             Some(ImageFormat::Tiff)
         ));
         assert!(ImageFormat::from_filename("document.txt").is_none());
+        assert!(ImageFormat::from_filename("chart.SVG").is_some_and(|format| format.is_vector()));
+    }
+
+    #[test]
+    fn test_metafile_sizes_come_from_their_headers() {
+        // EMF frame of 120 × 80 mm, in hundredths of a millimetre
+        let mut emf = vec![0u8; 108];
+        emf[0] = 1;
+        emf[32..36].copy_from_slice(&12000i32.to_le_bytes());
+        emf[36..40].copy_from_slice(&8000i32.to_le_bytes());
+        emf[40..44].copy_from_slice(b" EMF");
+        assert_eq!(vector_size_mm(&ImageFormat::Emf, &emf), Some((120.0, 80.0)));
+
+        // Placeable WMF of 2 × 1 inches at 1440 units to the inch
+        let mut wmf = vec![0u8; 22];
+        wmf[0..4].copy_from_slice(&[0xD7, 0xCD, 0xC6, 0x9A]);
+        wmf[10..12].copy_from_slice(&2880i16.to_le_bytes());
+        wmf[12..14].copy_from_slice(&1440i16.to_le_bytes());
+        wmf[14..16].copy_from_slice(&1440u16.to_le_bytes());
+        assert_eq!(vector_size_mm(&ImageFormat::Wmf, &wmf), Some((50.8, 25.4)));
+
+        // Without a placeable header there's nothing to go on
+        assert_eq!(vector_size_mm(&ImageFormat::Wmf, &[1, 0, 9, 0]), None);
     }
 
     #[test]
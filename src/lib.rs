@@ -3,13 +3,27 @@
 //! This library provides functionality for parsing Microsoft Word documents
 //! and displaying them in terminal environments with rich formatting support.
 
+#[cfg(feature = "terminal")]
 pub mod ansi;
+pub mod bidi;
+#[cfg(feature = "terminal")]
+pub mod clipboard;
 pub mod document;
 pub mod equation;
 pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod image_extractor;
+pub mod notes;
+#[cfg(feature = "terminal")]
 pub mod terminal_image;
+#[cfg(feature = "test-support")]
+pub mod testing;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "terminal")]
 pub mod widgets;
+pub mod wrap;
 
 /// Export format options
 #[derive(clap::ValueEnum, Clone)]
@@ -18,13 +32,333 @@ pub enum ExportFormat {
     Text,
     Csv,
     Json,
+    /// One JSON object per line: the document, then each element
+    Ndjson,
     Ansi,
+    Asciidoc,
+    Rst,
+    Html,
+    Notes,
+}
+
+/// How `--export markdown` splits a document into chunks
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkBy {
+    /// One chunk per section, starting at each heading
+    Heading,
+}
+
+/// How `doxx grep` prints its matches
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GrepFormat {
+    /// Matches under their headings, numbered by element, as ripgrep prints lines
+    #[default]
+    Text,
+    /// Each match quoted under its heading path
+    #[value(name = "md", alias = "markdown")]
+    Markdown,
+    /// Matches with their headings, byte ranges and context
+    Json,
+}
+
+/// The order `doxx grep` prints the documents it searched in
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GrepSort {
+    /// By path, as the documents were given or found
+    #[default]
+    Path,
+    /// The documents with the most occurrences of the pattern first
+    Relevance,
+}
+
+/// How `doxx styles` prints its report
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StyleReportFormat {
+    /// Style counts, unused styles and suspect paragraphs as plain text
+    #[default]
+    Text,
+    /// The same report as JSON
+    Json,
+}
+
+/// How `doxx outline` prints the heading tree
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutlineFormat {
+    /// An indented tree drawn with box characters
+    #[default]
+    Tree,
+    /// A nested Markdown list
+    Markdown,
+    /// Nested JSON sections
+    Json,
+}
+
+/// How `doxx lint` prints its findings
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LintFormat {
+    /// Each finding with where it is, as plain text
+    #[default]
+    Text,
+    /// The findings as JSON
+    Json,
+}
+
+/// How `doxx scan` prints its findings
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScanFormat {
+    /// Each finding with where it is and the text around it
+    #[default]
+    Text,
+    /// The findings with their byte ranges, as JSON
+    Json,
+}
+
+/// How `doxx form` prints the form fields
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FormFormat {
+    /// Each field with its kind and value
+    #[default]
+    Text,
+    /// The fields with their options, as JSON
+    Json,
+    /// A row to a field, for spreadsheets
+    Csv,
+}
+
+/// How `doxx verify` prints its report
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VerifyFormat {
+    /// Each difference with its line in the export
+    #[default]
+    Text,
+    /// The differences as JSON
+    Json,
+}
+
+/// Whose rules heading anchors follow, for the links Markdown and HTML export write
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnchorStyle {
+    /// As GitHub makes them: `whats-new--faq` for "What's New & FAQ" and
+    /// `21-results` for "2.1 Results"
+    #[default]
+    Github,
+    /// As GitLab makes them: runs of hyphens become one, `whats-new-faq`
+    Gitlab,
+    /// As pandoc makes them: periods are kept and anything before the first
+    /// letter is dropped, `results` for "2.1 Results"
+    Pandoc,
+}
+
+/// Key naming convention for JSON export
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsonCase {
+    /// snake_case keys (`schema_version`)
+    #[default]
+    Snake,
+    /// camelCase keys (`schemaVersion`)
+    Camel,
+}
+
+/// Where text boxes and framed paragraphs are read, which float over the page
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadingOrder {
+    /// As the document stores them: a text box after the paragraph it's anchored in
+    #[default]
+    Xml,
+    /// Before or after that paragraph, by where they're positioned on the page
+    Visual,
+}
+
+/// How `--charts` draws a table's numeric columns
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChartStyle {
+    /// A line of ▁▂▃▅▇ per column, with its lowest and highest value
+    #[default]
+    Sparkline,
+    /// A horizontal bar per row, grouped by column
+    Bars,
+}
+
+/// How paragraph spacing and empty paragraphs are laid out
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpacingMode {
+    /// One blank line after every paragraph; empty paragraphs are dropped
+    #[default]
+    Compact,
+    /// Blank lines from each paragraph's spacing before and after, and a line
+    /// for each empty paragraph
+    Preserve,
+}
+
+/// Field separator for CSV export
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvDelimiter {
+    Comma,
+    Semicolon,
+    Tab,
+}
+
+/// Decimal separator for numbers in CSV export
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecimalSeparator {
+    /// `1234.5`
+    #[default]
+    Point,
+    /// `1234,5`, as spreadsheets in most of Europe expect
+    Comma,
+}
+
+/// Clipboard format for tables copied in the viewer
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TableCopyFormat {
+    /// Tab-separated values, which spreadsheets split into cells on paste
+    #[default]
+    Tsv,
+    /// A Markdown pipe table
+    Markdown,
+    /// Comma-separated values
+    Csv,
+}
+
+impl TableCopyFormat {
+    /// The format after this one, for cycling through them in the viewer
+    pub fn next(self) -> Self {
+        match self {
+            Self::Tsv => Self::Markdown,
+            Self::Markdown => Self::Csv,
+            Self::Csv => Self::Tsv,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tsv => "TSV",
+            Self::Markdown => "Markdown",
+            Self::Csv => "CSV",
+        }
+    }
+}
+
+/// Where the viewer copies to
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClipboardMode {
+    /// The system clipboard, or OSC 52 over SSH or when there is none
+    #[default]
+    Auto,
+    /// Only the system clipboard
+    System,
+    /// The terminal's clipboard, through OSC 52 escape sequences
+    Osc52,
+    /// A file in the temporary directory
+    File,
+}
+
+/// Where Markdown, HTML, reStructuredText and AsciiDoc exports find their images
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImageExportMode {
+    /// Reference the images where they were extracted, in the temporary directory
+    #[default]
+    Link,
+    /// Copy the images into an assets directory beside the output
+    Copy,
+    /// Inline each image as a base64 `data:` URI
+    Embed,
+}
+
+/// How wrapped text chooses where to break lines
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineBreaking {
+    /// Fill each line as far as it goes
+    #[default]
+    Greedy,
+    /// Balance line lengths across the whole paragraph, breaking at hyphens where it helps
+    Optimal,
+}
+
+/// How `--normalize` cleans up the text Word leaves behind
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Normalization {
+    /// Keep the text as written
+    #[default]
+    None,
+    /// Unicode clean-up, then straight quotes and ASCII dashes and ellipses
+    Ascii,
+    /// Plain spaces for non-breaking and other wide spaces, and no soft
+    /// hyphens, zero-width spaces or control characters
+    UnicodeClean,
+}
+
+/// What `--redact` puts in place of the text it masks
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Redaction {
+    /// Solid blocks (████), the same whatever the length of the text
+    #[default]
+    Block,
+    /// The name of the rule that matched, as in [EMAIL] or [SSN]
+    Placeholder,
+}
+
+/// Paper sizes for `doxx print`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Paper {
+    /// 210 × 297 mm
+    A4,
+    /// 148 × 210 mm
+    A5,
+    /// 8.5 × 11 in
+    Letter,
+    /// 8.5 × 14 in
+    Legal,
+}
+
+impl Paper {
+    /// Width and height in points, upright
+    pub fn size(self) -> (f32, f32) {
+        match self {
+            Self::A4 => (595.28, 841.89),
+            Self::A5 => (419.53, 595.28),
+            Self::Letter => (612.0, 792.0),
+            Self::Legal => (612.0, 1008.0),
+        }
+    }
+}
+
+/// When to colour output (`--color`)
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colour output to a terminal, unless `NO_COLOR` or `CLICOLOR=0` says
+    /// otherwise; `CLICOLOR_FORCE` colours it anywhere
+    #[default]
+    Auto,
+    /// Always colour, even when piped
+    Always,
+    /// Never colour
+    Never,
+}
+
+impl ColorMode {
+    /// Whether to colour output, given whether it goes to a terminal
+    ///
+    /// `NO_COLOR` wins over `CLICOLOR_FORCE`; both follow the usual convention
+    /// that an empty value is unset.
+    pub fn enabled(self, terminal: bool) -> bool {
+        let var = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto if var("NO_COLOR").is_some() => false,
+            Self::Auto if var("CLICOLOR_FORCE").is_some_and(|value| value != "0") => true,
+            Self::Auto if var("CLICOLOR").as_deref() == Some("0") => false,
+            Self::Auto => terminal,
+        }
+    }
 }
 
 /// Color depth options for ANSI export
-#[derive(clap::ValueEnum, Clone, Debug)]
+#[derive(clap::ValueEnum, Clone, Debug, Default)]
 pub enum ColorDepth {
     /// Auto-detect terminal color capabilities
+    #[default]
     Auto,
     /// Monochrome (no colors)
     #[value(name = "1")]
@@ -44,4 +378,5 @@ pub enum ColorDepth {
 pub use document::{Document, DocumentElement};
 pub use equation::Equation;
 pub use image_extractor::ImageExtractor;
+#[cfg(feature = "terminal")]
 pub use terminal_image::{TerminalImageRenderer, TerminalImageSupport};
@@ -1,17 +1,30 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
-use doxx::{ColorDepth, ExportFormat};
+use doxx::{
+    AnchorStyle, ChartStyle, ChunkBy, ClipboardMode, ColorDepth, ColorMode, CsvDelimiter,
+    DecimalSeparator, ExportFormat, FormFormat, GrepFormat, GrepSort, ImageExportMode, JsonCase,
+    LineBreaking, LintFormat, Normalization, OutlineFormat, Paper, ReadingOrder, Redaction,
+    ScanFormat, SpacingMode, StyleReportFormat, TableCopyFormat, VerifyFormat,
+};
 
 mod ansi;
+pub mod bidi;
+pub mod clipboard;
+mod config;
 mod document;
-mod export;
+pub mod equation;
+pub mod export;
 pub mod image_extractor;
+mod notes;
+mod progress_bar;
 mod state;
 pub mod terminal_image;
 mod ui;
 mod widgets;
+pub mod wrap;
 
 #[derive(Parser)]
 #[command(
@@ -25,6 +38,10 @@ struct Cli {
     #[arg(value_name = "FILE")]
     file: Option<PathBuf>,
 
+    /// Documents to read after FILE with --concat
+    #[arg(value_name = "MORE", requires = "concat")]
+    more: Vec<PathBuf>,
+
     /// Start with outline view
     #[arg(short, long)]
     outline: bool,
@@ -37,11 +54,173 @@ struct Cli {
     #[arg(short, long)]
     search: Option<String>,
 
-    /// Export format
+    /// Open at the first heading matching this text (exact match first, then fuzzy)
+    #[arg(short, long, value_name = "HEADING")]
+    goto: Option<String>,
+
+    /// Export format; `notes` writes the bookmarks and notes made in the viewer
     #[arg(long, value_enum)]
     export: Option<ExportFormat>,
 
-    /// Terminal width for ANSI export (default: $COLUMNS or 80)
+    /// Write the export to this file, inferring the format from its extension
+    /// (.md, .txt, .csv, .json, .ndjson, .ans, .adoc, .rst, .html) unless --export is given
+    #[arg(long, value_name = "FILE", conflicts_with = "chunk_dir")]
+    output: Option<PathBuf>,
+
+    /// Export through this Jinja template instead of a built-in format; it's
+    /// given the document as JSON export describes it, plus the outline
+    #[arg(long, value_name = "FILE", conflicts_with = "export")]
+    template: Option<PathBuf>,
+
+    /// Text export that keeps hidden text, tracked changes, comments, notes,
+    /// headers and footers, each labelled with where it came from
+    #[arg(long)]
+    forensic: bool,
+
+    /// Text export for screen readers and text-to-speech: structure is announced
+    /// ("Heading level 2: ...", "Table with 4 columns and 6 rows") rather than drawn
+    #[arg(long, conflicts_with = "forensic")]
+    accessible: bool,
+
+    /// Open damaged or non-conforming files by recovering what can be read,
+    /// and report what was repaired or skipped
+    #[arg(long, visible_alias = "lenient")]
+    repair: bool,
+
+    /// Fail, without showing or exporting anything, if the document has
+    /// content doxx would skip or approximate: charts, SmartArt, embedded
+    /// objects, unsupported drawings, images without --images, nested tables
+    /// or fields it can't evaluate
+    #[arg(long)]
+    strict: bool,
+
+    /// Keep the parsed document on disk and reuse it while the file is
+    /// unchanged (set `cache.enabled` in the config to always do so)
+    #[arg(long, conflicts_with = "repair")]
+    cache: bool,
+
+    /// Don't show a progress bar while a long document loads or exports
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Follow the cached result of each field doxx can't evaluate (anything
+    /// but DATE, TIME, PAGE, NUMPAGES and FILENAME) with its field code
+    #[arg(long)]
+    show_field_codes: bool,
+
+    /// Where text boxes and framed paragraphs are read: "xml" as the document
+    /// stores them, "visual" before or after the paragraph they're anchored to
+    /// by where they sit on the page
+    #[arg(long, value_enum, default_value = "xml", value_name = "ORDER")]
+    reading_order: ReadingOrder,
+
+    /// Paragraph spacing in the viewer and text export: "compact" puts one
+    /// blank line after each paragraph and drops empty ones, "preserve" keeps
+    /// the document's spacing before and after paragraphs and its empty
+    /// paragraphs
+    #[arg(long, value_enum, default_value = "compact", value_name = "MODE")]
+    spacing: SpacingMode,
+
+    /// Language tag whose way of writing numbers and dates table cells are
+    /// read in, e.g. "de-DE" for 1.234,56 and 31.12.2024 or "en-GB" for
+    /// 31/12/2024; by default the `locale` in the config file, or else the
+    /// document's language
+    #[arg(long, value_name = "TAG")]
+    locale: Option<document::CellLocale>,
+
+    /// Only read part of the document, e.g. "sections 2-4", "pages 10-20" or
+    /// "pages 50-"; pages are counted as Word last laid the document out
+    #[arg(long, value_name = "RANGE")]
+    range: Option<document::DocumentRange>,
+
+    /// Read FILE and the documents after it as one, such as a report split
+    /// into chapter files; each file starts behind a rule with its name, and
+    /// the outline and search cover them all
+    #[arg(
+        long,
+        requires = "more",
+        conflicts_with_all = ["watch", "forensic", "extract_images"]
+    )]
+    concat: bool,
+
+    /// Clean up the text: "unicode-clean" makes non-breaking and other special
+    /// spaces plain and drops soft hyphens, zero-width spaces and control
+    /// characters; "ascii" also straightens quotes and dashes
+    #[arg(long, value_enum, default_value = "none", value_name = "MODE")]
+    normalize: Normalization,
+
+    /// Line wrapping for text and Markdown export: a column count, "none", or
+    /// "semantic" (one sentence per line)
+    #[arg(long, value_name = "COLS|none|semantic")]
+    wrap: Option<wrap::WrapMode>,
+
+    /// How wrapped lines are broken in text, Markdown and ANSI export: "greedy"
+    /// fills each line in turn, "optimal" balances the whole paragraph
+    #[arg(long, value_enum, default_value = "greedy", value_name = "MODE")]
+    line_breaking: LineBreaking,
+
+    /// Key naming for JSON export
+    #[arg(long, value_enum, default_value = "snake", value_name = "CASE")]
+    json_case: JsonCase,
+
+    /// Whose rules heading anchors follow in Markdown and HTML export, so that
+    /// the contents and cross-references link to the headings the site
+    /// publishing the export renders
+    #[arg(long, value_enum, default_value = "github", value_name = "STYLE")]
+    anchors: AnchorStyle,
+
+    /// Field separator for CSV export (default: semicolon with a decimal comma,
+    /// otherwise comma)
+    #[arg(long, value_enum, value_name = "SEP")]
+    csv_delimiter: Option<CsvDelimiter>,
+
+    /// Decimal separator for numbers in CSV export
+    #[arg(long, value_enum, default_value = "point", value_name = "SEP")]
+    decimal_separator: DecimalSeparator,
+
+    /// Follow the CSV header row with a row of column types (number, date, ...)
+    #[arg(long)]
+    csv_types: bool,
+
+    /// Keep CSV cells as written rather than as plain numbers and ISO dates
+    #[arg(long)]
+    csv_raw: bool,
+
+    /// Prepend nested sections with breadcrumb links to their ancestors (Markdown and HTML export)
+    #[arg(long)]
+    breadcrumbs: bool,
+
+    /// Put a table of contents linking to each heading at the top of
+    /// Markdown, text and HTML export, with the headings' GitHub-style
+    /// anchors as their HTML ids
+    #[arg(long)]
+    toc: bool,
+
+    /// Add a table of authorities and an index, listing what the document's
+    /// TA and XE fields mark with their pages, at the end
+    #[arg(long)]
+    index: bool,
+
+    /// Split Markdown export into chunks, written as JSONL records to stdout
+    /// or a .jsonl --output file
+    #[arg(long, value_enum, value_name = "UNIT")]
+    chunk_by: Option<ChunkBy>,
+
+    /// Split chunks larger than this many (estimated) tokens at block
+    /// boundaries, or between the sentences of a block too large for one
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "chunk_by",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    max_tokens: Option<usize>,
+
+    /// Write each chunk to its own Markdown file in this directory instead of JSONL
+    #[arg(long, value_name = "DIR", requires = "chunk_by")]
+    chunk_dir: Option<PathBuf>,
+
+    /// Terminal width for ANSI and text export (default: $COLUMNS or 80)
     #[arg(short = 'w', long, value_name = "COLS")]
     terminal_width: Option<usize>,
 
@@ -49,13 +228,92 @@ struct Cli {
     #[arg(long, value_enum, default_value = "auto")]
     color_depth: ColorDepth,
 
+    /// Chart the numeric columns below each table, in the viewer and ANSI
+    /// export: "sparkline" (the default) or "bars"; `v` in the viewer cycles
+    /// through them
+    #[arg(
+        long,
+        value_enum,
+        value_name = "STYLE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "sparkline"
+    )]
+    charts: Option<ChartStyle>,
+
+    /// Underline words that aren't in the Hunspell dictionary for the
+    /// document's language (toggle from the command palette)
+    #[arg(long)]
+    spellcheck: bool,
+
+    /// Mask what scan rules find, in the viewer and every export: "pii" (all
+    /// of email, ssn, credit-card and phone) unless rules are given, separated
+    /// by commas, as `doxx scan --rules` takes them
+    #[arg(
+        long,
+        value_name = "RULES",
+        value_delimiter = ',',
+        num_args = 0..,
+        require_equals = true,
+        default_missing_value = "pii",
+        conflicts_with_all = ["forensic", "extract_images"]
+    )]
+    redact: Option<Vec<String>>,
+
+    /// What --redact puts in place of a match: "block" (████) or
+    /// "placeholder", the rule's name as in [EMAIL]
+    #[arg(
+        long,
+        value_enum,
+        default_value = "block",
+        value_name = "STYLE",
+        requires = "redact"
+    )]
+    redact_with: Redaction,
+
     /// Force interactive UI mode (bypass TTY detection)
     #[arg(long)]
     force_ui: bool,
 
-    /// Enable color support for text rendering
+    /// Print the document into the terminal's scrollback and exit, as bat
+    /// does, rather than opening the viewer: styled to the terminal's width,
+    /// between a header and a closing rule, with images drawn given --images
+    #[arg(
+        long,
+        conflicts_with_all = ["export", "output", "watch", "present", "force_ui", "spellcheck"]
+    )]
+    inline: bool,
+
+    /// When to use colour: "auto" colours output to a terminal, following
+    /// NO_COLOR, CLICOLOR and CLICOLOR_FORCE; `--color` alone means "always"
+    #[arg(
+        long,
+        value_enum,
+        default_value = "auto",
+        value_name = "WHEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "always"
+    )]
+    color: ColorMode,
+
+    /// Where the viewer copies to: "auto" uses the system clipboard, or the
+    /// terminal's (OSC 52) over SSH; "file" saves to a temporary file
+    #[arg(long, value_enum, default_value = "auto", value_name = "MODE")]
+    clipboard: ClipboardMode,
+
+    /// Largest copy sent through OSC 52, in bytes of base64; larger copies are
+    /// saved to a temporary file instead
+    #[arg(long, value_name = "BYTES", default_value_t = clipboard::DEFAULT_OSC52_LIMIT)]
+    osc52_limit: usize,
+
+    /// Format for tables copied with `y` in the viewer
+    #[arg(long, value_enum, default_value = "tsv", value_name = "FORMAT")]
+    table_format: TableCopyFormat,
+
+    /// Reload the document in the viewer whenever the file changes
     #[arg(long)]
-    color: bool,
+    watch: bool,
 
     /// Restore last saved scroll position for this document
     #[arg(short = 'r', long)]
@@ -73,6 +331,11 @@ struct Cli {
     #[arg(long)]
     extract_images: Option<PathBuf>,
 
+    /// How exports reference images: link to the extracted files, copy them
+    /// into an assets directory beside the output, or embed them as data URIs
+    #[arg(long, value_enum, value_name = "MODE")]
+    export_images_mode: Option<ImageExportMode>,
+
     /// Maximum image width in terminal columns (default: auto-detect)
     #[arg(long, value_name = "COLS")]
     image_width: Option<u32>,
@@ -85,28 +348,224 @@ struct Cli {
     #[arg(long, value_name = "SCALE")]
     image_scale: Option<f32>,
 
+    /// Play animated GIFs in Kitty and iTerm2 terminals; otherwise (and in
+    /// exports) they show their first frame
+    #[arg(long)]
+    animate: bool,
+
+    /// Present the document one section per screen under large titles;
+    /// space and the arrow keys move between sections
+    #[arg(long)]
+    present: bool,
+
+    /// Show a timer in presenter mode, against a talk length in MINUTES if given
+    #[arg(
+        long,
+        value_name = "MINUTES",
+        num_args = 0..=1,
+        default_missing_value = "0",
+        requires = "present"
+    )]
+    timer: Option<u32>,
+
     /// Test terminal image capabilities
     #[arg(long)]
     debug_terminal: bool,
 
-    /// Configuration commands
+    /// Configuration and packaging commands
     #[command(subcommand)]
-    config: Option<ConfigCommands>,
+    command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
-enum ConfigCommands {
+enum Commands {
     /// Set configuration value
     Set { key: String, value: String },
     /// Get configuration value
     Get { key: String },
     /// Initialize configuration
     Init,
+    /// Print the paragraphs and table cells matching a regular expression,
+    /// under the headings they're in
+    Grep {
+        /// Regular expression to search for
+        pattern: String,
+        /// .docx files, directories with --recursive, or patterns such as
+        /// "notes/2024-*.docx"
+        #[arg(required = true, value_name = "PATH")]
+        paths: Vec<PathBuf>,
+        /// Search the .docx files in directories and their subdirectories
+        #[arg(short, long)]
+        recursive: bool,
+        /// Show N paragraphs or cells after each match
+        #[arg(short = 'A', long, value_name = "N")]
+        after_context: Option<usize>,
+        /// Show N paragraphs or cells before each match
+        #[arg(short = 'B', long, value_name = "N")]
+        before_context: Option<usize>,
+        /// Show N paragraphs or cells before and after each match; -A and -B
+        /// override it on their side
+        #[arg(short = 'C', long, default_value_t = 0, value_name = "N")]
+        context: usize,
+        /// Stop after N matching paragraphs or cells in each document
+        #[arg(short = 'm', long, value_name = "N")]
+        max_count: Option<usize>,
+        /// Search on N threads; 0 uses one per CPU
+        #[arg(short = 'j', long, default_value_t = 0, value_name = "N")]
+        threads: usize,
+        /// Order documents by path or, with relevance, most occurrences first
+        #[arg(long, value_enum, default_value = "path")]
+        sort: GrepSort,
+        /// Match regardless of case
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Search for the pattern as plain text rather than a regular expression
+        #[arg(short = 'F', long)]
+        fixed_strings: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: GrepFormat,
+    },
+    /// Count how often each style is used, list unused styles and flag
+    /// paragraphs formatted by hand to look like headings
+    Styles {
+        /// Path to the .docx file
+        file: PathBuf,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: StyleReportFormat,
+    },
+    /// Print a tab-completion script for a shell
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Print the man page (roff) to install as doxx.1
+    Manpage,
+    /// Manage the parse cache that --cache keeps
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Print a document's heading tree with each section's word count
+    Outline {
+        /// Path to the .docx file
+        file: PathBuf,
+        /// Output format
+        #[arg(long, value_enum, default_value = "tree")]
+        format: OutlineFormat,
+    },
+    /// Print the stored XML, style chain and numbering behind document elements
+    Inspect {
+        /// Path to the .docx file
+        file: PathBuf,
+        /// Element numbers to inspect, as the viewer's inspector (`X`) shows them
+        elements: Vec<usize>,
+        /// Inspect the elements containing this text
+        #[arg(long)]
+        find: Option<String>,
+    },
+    /// Check a document for problems, as chosen by the flags below
+    Lint {
+        /// Path to the .docx file
+        file: PathBuf,
+        /// Report words that aren't in the Hunspell dictionary for the
+        /// document's language
+        #[arg(long)]
+        spelling: bool,
+        /// Check spelling in this language rather than the document's (en-GB)
+        #[arg(long, value_name = "TAG")]
+        language: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: LintFormat,
+    },
+    /// Find personal data (email addresses, social security, card and phone
+    /// numbers) in the body, tables, headers, footers, notes and comments
+    Scan {
+        /// Path to the .docx file
+        file: PathBuf,
+        /// Rules to run, separated by commas: pii for all of email, ssn,
+        /// credit-card and phone, any of those, or rules from the config file
+        #[arg(long, value_delimiter = ',', default_value = "pii")]
+        rules: Vec<String>,
+        /// Also run a rule of your own, a regular expression
+        #[arg(long = "rule", value_name = "NAME=REGEX")]
+        extra_rules: Vec<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: ScanFormat,
+    },
+    /// List the form fields (legacy fields and content controls) with their
+    /// names, options and current values
+    Form {
+        /// Path to the .docx file
+        file: PathBuf,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: FormFormat,
+    },
+    /// Check a Markdown export against the document as it is now, reporting
+    /// missing headings, dropped tables and changed text
+    Verify {
+        /// Path to the .docx file
+        file: PathBuf,
+        /// Markdown exported from it earlier
+        export: PathBuf,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: VerifyFormat,
+    },
+    /// Print a document with lp (CUPS), laid out as text export lays it out,
+    /// or write the pages as PostScript
+    Print {
+        /// Path to the .docx file
+        file: PathBuf,
+        /// Printer to send it to (default: the system's default printer)
+        #[arg(short = 'd', long, value_name = "NAME")]
+        printer: Option<String>,
+        /// Number of copies
+        #[arg(short = 'n', long, default_value_t = 1, value_name = "N")]
+        copies: u32,
+        /// Write PostScript to this file instead of printing (- for stdout)
+        #[arg(short, long, value_name = "FILE", conflicts_with = "printer")]
+        output: Option<PathBuf>,
+        /// Paper size (default: the document's page size)
+        #[arg(long, value_enum)]
+        paper: Option<Paper>,
+        /// Turn the page on its side
+        #[arg(long)]
+        landscape: bool,
+        /// Margin on every side, such as 2cm, 20mm, 1in or 72pt (default: the
+        /// document's margins)
+        #[arg(long, value_name = "LENGTH", value_parser = export::print::parse_length)]
+        margin: Option<f32>,
+        /// Type size in points
+        #[arg(long, default_value_t = 10.0, value_name = "POINTS")]
+        font_size: f32,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Show where the cache is and how much it holds
+    Info,
+    /// Remove every cached document
+    Clear,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+
+    if cli.locale.is_none() {
+        if let Some(tag) = config::Config::load().ok().and_then(|config| config.locale) {
+            let locale = tag
+                .parse()
+                .map_err(|error: String| anyhow::anyhow!("In the config file: {error}"))?;
+            cli.locale = Some(locale);
+        }
+    }
 
     // Handle debug terminal command
     if cli.debug_terminal {
@@ -116,22 +575,365 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    match &cli.config {
-        Some(ConfigCommands::Init) => {
+    match &cli.command {
+        Some(Commands::Completions { shell }) => {
+            clap_complete::generate(*shell, &mut Cli::command(), "doxx", &mut std::io::stdout());
+            return Ok(());
+        }
+        Some(Commands::Manpage) => {
+            clap_mangen::Man::new(Cli::command()).render(&mut std::io::stdout())?;
+            return Ok(());
+        }
+        Some(Commands::Outline { file, format }) => {
+            let input = document::SeekableInput::open(file)?;
+            let document =
+                document::load_document(input.path(), document::ImageOptions::default())?;
+            print!("{}", export::outline::render_outline(&document, *format)?);
+            return Ok(());
+        }
+        Some(Commands::Inspect {
+            file,
+            elements,
+            find,
+        }) => {
+            let input = document::SeekableInput::open(file)?;
+            let document =
+                document::load_document(input.path(), document::ImageOptions::default())?;
+            let inspector = document::SourceInspector::open(input.path())?;
+            let mut indices = elements.clone();
+            if let Some(query) = find {
+                indices.extend(
+                    document::search_document(&document, query)
+                        .iter()
+                        .map(|result| result.element_index),
+                );
+            }
+            if elements.is_empty() && find.is_none() {
+                indices.extend(0..document.elements.len());
+            }
+            // Each element once, in the order asked for
+            let mut seen = std::collections::HashSet::new();
+            indices.retain(|index| seen.insert(*index));
+            for (i, index) in indices.into_iter().enumerate() {
+                let Some(inspection) = inspector.inspect(&document, index) else {
+                    anyhow::bail!(
+                        "No element {index}: the document has {}",
+                        document.elements.len()
+                    );
+                };
+                if i > 0 {
+                    println!();
+                }
+                for line in inspection.lines() {
+                    println!("{line}");
+                }
+            }
+            return Ok(());
+        }
+        Some(Commands::Init) => {
             println!("Initializing doxx configuration...");
             // TODO: Initialize config file
             return Ok(());
         }
-        Some(ConfigCommands::Set { key, value }) => {
+        Some(Commands::Cache { action }) => {
+            let cache = config::Config::load()?.cache.parse_cache()?;
+            match action {
+                CacheAction::Info => {
+                    let usage = cache.usage()?;
+                    println!("Cache: {}", cache.dir.display());
+                    println!(
+                        "{} documents, {:.1} MB of {} MB",
+                        usage.entries,
+                        usage.bytes as f64 / (1024.0 * 1024.0),
+                        cache.max_bytes / (1024 * 1024)
+                    );
+                }
+                CacheAction::Clear => {
+                    let removed = cache.clear()?;
+                    println!(
+                        "Removed {} cached documents ({:.1} MB)",
+                        removed.entries,
+                        removed.bytes as f64 / (1024.0 * 1024.0)
+                    );
+                }
+            }
+            return Ok(());
+        }
+        Some(Commands::Set { key, value }) => {
             println!("Setting {key} = {value}");
             // TODO: Set config value
             return Ok(());
         }
-        Some(ConfigCommands::Get { key }) => {
+        Some(Commands::Get { key }) => {
             println!("Getting {key}");
             // TODO: Get config value
             return Ok(());
         }
+        Some(Commands::Grep {
+            pattern,
+            paths,
+            recursive,
+            after_context,
+            before_context,
+            context,
+            max_count,
+            threads,
+            sort,
+            ignore_case,
+            fixed_strings,
+            format,
+        }) => {
+            let source = match fixed_strings {
+                true => regex::escape(pattern),
+                false => pattern.clone(),
+            };
+            let regex = regex::RegexBuilder::new(&source)
+                .case_insensitive(*ignore_case)
+                .build()?;
+            let color = cli.color.enabled(std::io::stdout().is_terminal());
+            let documents = export::grep::find_documents(paths, *recursive)?;
+            let options = document::GrepOptions {
+                before: before_context.unwrap_or(*context),
+                after: after_context.unwrap_or(*context),
+                max_count: *max_count,
+            };
+            let mut files = Vec::new();
+            for (path, found) in export::grep::grep_files(&documents, &regex, &options, *threads)? {
+                match found {
+                    Ok(matches) => files.push(export::grep::GrepFile {
+                        path: path.display().to_string(),
+                        matches,
+                    }),
+                    Err(error) if documents.len() == 1 => return Err(error),
+                    Err(error) => eprintln!("{}: {error:#}", path.display()),
+                }
+            }
+            if *sort == GrepSort::Relevance {
+                export::grep::sort_by_relevance(&mut files);
+            }
+            let output = match files.as_slice() {
+                [file] if !*recursive && documents.len() == 1 => {
+                    export::grep::render_grep(&file.path, pattern, &file.matches, *format, color)?
+                }
+                _ => export::grep::render_grep_files(
+                    pattern,
+                    &files,
+                    documents.len(),
+                    *format,
+                    color,
+                )?,
+            };
+            print!("{output}");
+            // As grep does, exit with 1 when nothing matched
+            if files.iter().all(|file| file.matches.is_empty()) {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Commands::Lint {
+            file,
+            spelling,
+            language,
+            format,
+        }) => {
+            if !spelling {
+                anyhow::bail!("Nothing to check: choose a check such as --spelling");
+            }
+            #[cfg(not(feature = "spellcheck"))]
+            {
+                let _ = (file, language, format);
+                anyhow::bail!("This doxx was built without the spellcheck feature");
+            }
+            #[cfg(feature = "spellcheck")]
+            {
+                let input = document::SeekableInput::open(file)?;
+                let document =
+                    document::load_document(input.path(), document::ImageOptions::default())?;
+                let spelling = config::Config::load().unwrap_or_default().spelling;
+                let mut checker = spelling.spell_checker();
+                let language = language.clone().unwrap_or_else(|| {
+                    document::spelling::document_language(&document, spelling.language.as_deref())
+                });
+                let dictionary = checker.find(&language).unwrap_or_default();
+                let report = document::spelling::SpellingReport {
+                    source: file.display().to_string(),
+                    misspellings: document::spelling::check_document(
+                        &document,
+                        checker.dictionary(&language)?,
+                    ),
+                    language,
+                    dictionary,
+                };
+                match format {
+                    LintFormat::Text => {
+                        for line in report.lines(&document) {
+                            println!("{line}");
+                        }
+                    }
+                    LintFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+                }
+                // As linters do, exit with 1 when there's something to fix
+                if !report.misspellings.is_empty() {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+        }
+        Some(Commands::Scan {
+            file,
+            rules,
+            extra_rules,
+            format,
+        }) => {
+            let mut custom = config::Config::load()
+                .unwrap_or_default()
+                .scan
+                .scan_rules()?;
+            let mut names = rules.clone();
+            for rule in extra_rules {
+                let Some((name, pattern)) = rule.split_once('=') else {
+                    anyhow::bail!("--rule takes NAME=REGEX, not {rule}");
+                };
+                custom.push(document::scan::ScanRule::custom(name, pattern)?);
+                names.push(name.to_string());
+            }
+            let rules = document::scan::resolve_rules(&names, &custom)?;
+            let input = document::SeekableInput::open(file)?;
+            let document =
+                document::load_document(input.path(), document::ImageOptions::default())?;
+            let texts = document::scan::scan_texts(&document, Some(input.path()))?;
+            let report = document::scan::ScanReport {
+                source: file.display().to_string(),
+                rules: rules.iter().map(|rule| rule.name.clone()).collect(),
+                findings: document::scan::scan(&texts, &rules),
+            };
+            match format {
+                ScanFormat::Text => {
+                    for line in report.lines() {
+                        println!("{line}");
+                    }
+                }
+                ScanFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            // As linters do, exit with 1 when there's something to look at
+            if !report.findings.is_empty() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Commands::Form { file, format }) => {
+            let input = document::SeekableInput::open(file)?;
+            let mut report = document::forms::form_report(input.path())?;
+            report.source = file.display().to_string();
+            let lines = match format {
+                FormFormat::Text => report.lines(),
+                FormFormat::Json => vec![serde_json::to_string_pretty(&report)?],
+                FormFormat::Csv => report.csv_lines(),
+            };
+            for line in lines {
+                println!("{line}");
+            }
+            return Ok(());
+        }
+        Some(Commands::Verify {
+            file,
+            export,
+            format,
+        }) => {
+            let markdown = std::fs::read_to_string(export)
+                .map_err(|error| anyhow::anyhow!("Couldn't read {}: {error}", export.display()))?;
+            let input = document::SeekableInput::open(file)?;
+            let document =
+                document::load_document(input.path(), document::ImageOptions::default())?;
+            let report = export::verify::VerifyReport {
+                source: file.display().to_string(),
+                export: export.display().to_string(),
+                differences: export::verify::verify_export(&document, &markdown)?,
+            };
+            match format {
+                VerifyFormat::Text => {
+                    for line in report.lines() {
+                        println!("{line}");
+                    }
+                }
+                VerifyFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            // As diff does, exit with 1 when the two differ
+            if !report.differences.is_empty() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Commands::Print {
+            file,
+            printer,
+            copies,
+            output,
+            paper,
+            landscape,
+            margin,
+            font_size,
+        }) => {
+            let input = document::SeekableInput::open(file)?;
+            let document =
+                document::load_document(input.path(), document::ImageOptions::default())?;
+            let mut options = export::print::PrintOptions::for_document(&document);
+            match paper {
+                Some(paper) => options.paper(*paper, *landscape),
+                None if *landscape => options.landscape(),
+                None => {}
+            }
+            if let Some(margin) = margin {
+                options.margins = [*margin; 4];
+            }
+            options.font_size = *font_size;
+            let pages = export::print::paginate(&document, &options)?;
+            let postscript = export::print::render_postscript(&document, &pages, &options);
+            match output {
+                Some(path) if path.as_os_str() == "-" => print!("{postscript}"),
+                Some(path) => {
+                    std::fs::write(path, &postscript)?;
+                    eprintln!("Wrote {} pages to {}", pages.len(), path.display());
+                }
+                None => {
+                    use anyhow::Context;
+                    use std::io::Write;
+                    let mut lp = std::process::Command::new("lp");
+                    lp.arg("-t").arg(&document.title);
+                    lp.arg("-n").arg(copies.to_string());
+                    if let Some(printer) = printer {
+                        lp.arg("-d").arg(printer);
+                    }
+                    let mut child = lp.stdin(std::process::Stdio::piped()).spawn().context(
+                        "Couldn't run lp: is CUPS installed? --output writes PostScript instead",
+                    )?;
+                    child
+                        .stdin
+                        .take()
+                        .expect("lp's stdin is piped")
+                        .write_all(postscript.as_bytes())?;
+                    let status = child.wait()?;
+                    if !status.success() {
+                        anyhow::bail!("lp failed ({status})");
+                    }
+                }
+            }
+            return Ok(());
+        }
+        Some(Commands::Styles { file, format }) => {
+            let input = document::SeekableInput::open(file)?;
+            let mut report = document::style_report(input.path())?;
+            report.source = file.display().to_string();
+            match format {
+                StyleReportFormat::Text => {
+                    for line in report.lines() {
+                        println!("{line}");
+                    }
+                }
+                StyleReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            return Ok(());
+        }
         None => {}
     }
 
@@ -144,19 +946,213 @@ async fn main() -> Result<()> {
         anyhow::bail!("File not found: {}", file_path.display());
     }
 
+    // Pipes can't seek, which reading a ZIP needs, so their contents are copied to a temp file
+    let input = document::SeekableInput::open(&file_path)?;
+    if input.is_buffered() && cli.watch {
+        anyhow::bail!(
+            "--watch needs a file to watch, but {} is a pipe",
+            file_path.display()
+        );
+    }
+    if input.is_buffered() && matches!(cli.export, Some(ExportFormat::Notes)) {
+        anyhow::bail!(
+            "Notes are kept beside the document file, but {} is a pipe",
+            file_path.display()
+        );
+    }
+    let (file_path, input_name) = (input.path().to_path_buf(), file_path);
+
+    // The documents read after it with --concat
+    let mut more_inputs = Vec::new();
+    for path in &cli.more {
+        if !path.exists() {
+            anyhow::bail!("File not found: {}", path.display());
+        }
+        more_inputs.push((document::SeekableInput::open(path)?, path.clone()));
+    }
+    if cli.concat && matches!(cli.export, Some(ExportFormat::Notes)) {
+        anyhow::bail!("Notes are kept beside one document file, so --concat can't export them");
+    }
+
+    // An explicit --export wins over the output file's extension
+    let export_format = match (&cli.export, &cli.output) {
+        (Some(format), _) => Some(format.clone()),
+        // A template is a format of its own, whatever the file's extension
+        (None, _) if cli.template.is_some() => None,
+        (None, Some(output)) => Some(export::infer_export_format(output).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cannot infer an export format from '{}'; use --export with one of: markdown, text, csv, json, ndjson, ansi, asciidoc, rst, html, notes",
+                output.display()
+            )
+        })?),
+        (None, None) => None,
+    };
+
+    if cli.chunk_by.is_some() && !matches!(export_format, Some(ExportFormat::Markdown)) {
+        anyhow::bail!("--chunk-by requires --export markdown");
+    }
+
+    // Chunks are JSONL records, which don't belong in a .md file
+    if let Some(output) = cli.output.as_ref().filter(|_| cli.chunk_by.is_some()) {
+        let extension = output.extension().and_then(|ext| ext.to_str());
+        if !matches!(extension, Some("jsonl" | "ndjson")) {
+            anyhow::bail!(
+                "--chunk-by writes JSONL records, so --output needs a .jsonl file, not '{}'; use --chunk-dir for Markdown files",
+                output.display()
+            );
+        }
+    }
+
+    if cli.accessible && !matches!(export_format, Some(ExportFormat::Text)) {
+        anyhow::bail!("--accessible requires --export text");
+    }
+
+    if cli.toc
+        && !matches!(
+            export_format,
+            Some(ExportFormat::Markdown | ExportFormat::Text | ExportFormat::Html)
+        )
+    {
+        anyhow::bail!("--toc requires --export markdown, text or html");
+    }
+
+    if cli.charts.is_some()
+        && export_format
+            .as_ref()
+            .is_some_and(|format| !matches!(format, ExportFormat::Ansi))
+    {
+        anyhow::bail!("--charts only draws in the viewer and --export ansi");
+    }
+
+    if cli.spellcheck && export_format.is_some() {
+        anyhow::bail!(
+            "--spellcheck only underlines in the viewer; use doxx lint --spelling for a report"
+        );
+    }
+
+    // Rules are checked before the document is read
+    let redactor = match &cli.redact {
+        Some(rules) => Some(
+            config::Config::load()
+                .unwrap_or_default()
+                .scan
+                .redactor(rules, cli.redact_with)?,
+        ),
+        None => None,
+    };
+    if redactor.is_some() && matches!(export_format, Some(ExportFormat::Notes)) {
+        anyhow::bail!("Notes quote the document as it was bookmarked, so --redact can't mask them");
+    }
+
+    if cli.forensic {
+        if !matches!(export_format, Some(ExportFormat::Text)) {
+            anyhow::bail!("--forensic requires --export text");
+        }
+        let report = document::extract_forensic_text(&file_path)?.to_text();
+        match &cli.output {
+            Some(output) => std::fs::write(output, report)?,
+            None => print!("{report}"),
+        }
+        return Ok(());
+    }
+
     let image_options = document::ImageOptions {
-        enabled: cli.images,
+        // An export asked for images needs them extracted
+        enabled: cli.images || (export_format.is_some() && cli.export_images_mode.is_some()),
         max_width: cli.image_width,
         max_height: cli.image_height,
         scale: cli.image_scale,
+        animate: cli.animate,
+        // Measured before the viewer or export takes over the terminal
+        cell_size: cli.images.then(terminal_image::cell_size).flatten(),
     };
 
     // Run CPU-intensive document loading on a blocking thread
-    let file_path_clone = file_path.clone();
-    let document = tokio::task::spawn_blocking(move || {
-        document::load_document(&file_path_clone, image_options)
-    })
-    .await??;
+    let paths: Vec<PathBuf> = std::iter::once(file_path.clone())
+        .chain(
+            more_inputs
+                .iter()
+                .map(|(input, _)| input.path().to_path_buf()),
+        )
+        .collect();
+    let repair = cli.repair;
+    let field_options = document::FieldOptions {
+        show_unknown: cli.show_field_codes,
+        reading_order: cli.reading_order,
+        spacing: cli.spacing,
+        locale: cli.locale,
+        range: cli.range,
+    };
+    let cache_config = config::Config::load().unwrap_or_default().cache;
+    let cache = match cli.cache || cache_config.enabled {
+        true => Some(cache_config.parse_cache()?),
+        false => None,
+    };
+    let progress = progress_bar::LoadProgress::new(cli.quiet);
+    let load_progress = progress.clone();
+    let load = move |path: &PathBuf| {
+        if let Some(cache) = cache.as_ref().filter(|_| !repair) {
+            return cache.load_with_progress(
+                path,
+                image_options.clone(),
+                field_options,
+                &load_progress,
+            );
+        }
+        if !repair {
+            return document::load_document_with_progress(
+                path,
+                image_options.clone(),
+                field_options,
+                &load_progress,
+            );
+        }
+        let (document, report) = document::load_document_lenient_with_fields(
+            path,
+            image_options.clone(),
+            field_options,
+        )?;
+        if !report.is_clean() {
+            eprintln!("Repaired {}:", path.display());
+            for note in &report.notes {
+                eprintln!("  - {note}");
+            }
+        }
+        Ok(document)
+    };
+    let mut documents =
+        tokio::task::spawn_blocking(move || paths.iter().map(load).collect::<Result<Vec<_>>>())
+            .await??;
+    progress.clear();
+    let inputs = std::iter::once((&input, &input_name))
+        .chain(more_inputs.iter().map(|(input, name)| (input, name)));
+    for (document, (input, name)) in documents.iter_mut().zip(inputs) {
+        if input.is_buffered() {
+            document.metadata.file_path = name.to_string_lossy().to_string();
+        }
+    }
+    let mut document = match cli.concat {
+        true => document::concat_documents(documents),
+        false => documents.pop(),
+    }
+    .expect("FILE is always loaded");
+    document::normalize_document(&mut document, cli.normalize);
+    if cli.index && document::index::append_index(&mut document) == 0 {
+        eprintln!("No index entries or citations: the document has no XE or TA fields");
+    }
+    if let Some(redactor) = &redactor {
+        redactor.redact_document(&mut document);
+    }
+    if cli.strict {
+        let losses = export::fidelity::load_losses(&document.fidelity);
+        if !losses.is_empty() {
+            anyhow::bail!(
+                "--strict: {} can't be shown as written: {}",
+                file_path.display(),
+                losses.join(", ")
+            );
+        }
+    }
 
     // Handle image extraction flag
     if let Some(extract_dir) = &cli.extract_images {
@@ -181,22 +1177,119 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    if let Some(export_format) = &cli.export {
-        match export_format {
-            ExportFormat::Ansi => {
-                export::export_to_ansi_with_cli_options(
+    if let Some(template) = &cli.template {
+        // Of the export options, only the outline's anchors reach a template
+        let options = export::ExportOptions {
+            anchors: cli.anchors,
+            ..Default::default()
+        };
+        let rendered = export::template::render_template(&document, template, &options)?;
+        match &cli.output {
+            Some(output) => std::fs::write(output, rendered)?,
+            None => print!("{rendered}"),
+        }
+        return Ok(());
+    }
+
+    if let Some(export_format) = &export_format {
+        let options = export::ExportOptions {
+            breadcrumbs: cli.breadcrumbs,
+            toc: cli.toc,
+            chunk_by: cli.chunk_by,
+            max_tokens: cli.max_tokens,
+            chunk_dir: cli.chunk_dir.clone(),
+            terminal_width: cli.terminal_width,
+            // ANSI export is asked for by name, so piping it keeps its colours;
+            // only --color=never or the environment turn them off
+            color_depth: match cli.color.enabled(true) {
+                true => cli.color_depth.clone(),
+                false => ColorDepth::Monochrome,
+            },
+            charts: cli.charts,
+            json_case: cli.json_case,
+            anchors: cli.anchors,
+            wrap: cli.wrap,
+            line_breaking: cli.line_breaking,
+            accessible: cli.accessible,
+            csv: export::csv::CsvOptions {
+                delimiter: cli.csv_delimiter,
+                decimal: cli.decimal_separator,
+                types: cli.csv_types,
+                as_written: cli.csv_raw,
+            },
+            images: cli.export_images_mode.unwrap_or_default(),
+        };
+        match &cli.output {
+            Some(output) => {
+                export::export_document_to_file_with_progress(
                     &document,
-                    cli.terminal_width,
-                    &cli.color_depth,
+                    export_format,
+                    &options,
+                    output,
+                    &progress,
                 )?;
+                progress.clear();
             }
-            _ => {
-                export::export_document(&document, export_format)?;
+            None => {
+                let written =
+                    export::export_document_with_options(&document, export_format, &options);
+                // A pipeline reading NDJSON may stop once it has what it needs
+                let stopped_reading = |error: &anyhow::Error| {
+                    error
+                        .downcast_ref::<std::io::Error>()
+                        .is_some_and(|error| error.kind() == std::io::ErrorKind::BrokenPipe)
+                };
+                match written {
+                    Err(error)
+                        if matches!(export_format, ExportFormat::Ndjson)
+                            && stopped_reading(&error) =>
+                    {
+                        return Ok(())
+                    }
+                    written => written?,
+                }
             }
         }
+        // Kept off stderr when it isn't a terminal, so scripts and logs stay clean.
+        // A notes export isn't a copy of the document, so there's nothing to report.
+        if std::io::stderr().is_terminal() && !matches!(export_format, ExportFormat::Notes) {
+            let summary =
+                export::fidelity::fidelity_summary(&document, export_format, cli.output.is_none());
+            eprintln!("{summary}");
+        }
         return Ok(());
     }
 
+    if cli.inline {
+        let stdout = std::io::stdout();
+        let terminal = stdout.is_terminal();
+        let color = cli.color.enabled(terminal);
+        // The terminal's own width, which $COLUMNS often isn't exported to give
+        let width = cli.terminal_width.unwrap_or_else(|| {
+            terminal
+                .then(|| crossterm::terminal::size().ok())
+                .flatten()
+                .map_or_else(wrap::default_width, |(columns, _)| columns.into())
+        });
+        let options = ansi::AnsiOptions {
+            terminal_width: width,
+            color_depth: match color {
+                true => cli.color_depth.clone(),
+                false => ColorDepth::Monochrome,
+            },
+            line_breaking: cli.line_breaking,
+            charts: cli.charts,
+            locale: cli.locale,
+            ..Default::default()
+        };
+        let images = (cli.images && terminal)
+            .then(|| {
+                terminal_image::TerminalImageRenderer::for_image_options(&document.image_options)
+            })
+            .filter(terminal_image::TerminalImageRenderer::can_display_images);
+        return ansi::print_inline(&document, &options, images.as_ref(), color);
+    }
+
     // Start terminal UI
     ui::run_viewer(document, &cli).await?;
 
@@ -0,0 +1,302 @@
+//! Bookmarks and notes kept beside a document
+//!
+//! The viewer's `B` key bookmarks the element at the top of the screen and `a`
+//! attaches a note to it. They're saved to a `.doxx-notes.json` file in the
+//! document's directory, keyed by a hash of the document's contents, so one
+//! notes file serves every document in a folder and survives renames.
+//!
+//! Each annotation remembers the start of its element's text. When the
+//! document is edited its hash changes: the notes saved under the same file
+//! name are picked up instead, and moved to the element that still has that
+//! text.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::document::{Document, DocumentElement};
+
+/// Name of the notes file, in the same directory as the documents it covers
+pub const NOTES_FILE: &str = ".doxx-notes.json";
+
+/// Characters of an element's text kept to find it again
+const EXCERPT_CHARS: usize = 80;
+
+/// A bookmarked element, and the note on it if there is one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Index of the element in the document
+    pub element: usize,
+    /// The start of the element's text when it was bookmarked
+    pub excerpt: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Annotations on one version of a document
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DocumentNotes {
+    /// File name of the document, to recognise it once it has been edited
+    file: String,
+    annotations: Vec<Annotation>,
+}
+
+/// Contents of a notes file: annotations by document hash
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct NotesFile {
+    documents: BTreeMap<String, DocumentNotes>,
+}
+
+/// The annotations on a document, and where they're saved
+#[derive(Debug)]
+pub struct Notes {
+    /// The notes file
+    path: PathBuf,
+    /// File name of the document
+    file: String,
+    /// Hash of the document as it is now
+    key: String,
+    /// Sorted by element, at most one per element
+    annotations: Vec<Annotation>,
+}
+
+impl Notes {
+    /// The annotations saved for the document at `document_path`, which has
+    /// been loaded as `document`
+    pub fn load(document_path: &Path, document: &Document) -> Result<Self> {
+        let contents = fs::read(document_path)
+            .with_context(|| format!("Failed to read {}", document_path.display()))?;
+        let directory = document_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let path = directory.join(NOTES_FILE);
+        let file = document_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let key = document_key(&contents);
+
+        let mut notes_file = read_notes_file(&path)?;
+        let (edited, annotations) = match notes_file.documents.remove(&key) {
+            Some(saved) => (false, saved.annotations),
+            // An edited document: the notes saved under its file name
+            None => match notes_file
+                .documents
+                .into_values()
+                .find(|saved| saved.file == file)
+            {
+                Some(saved) => (true, saved.annotations),
+                None => (false, Vec::new()),
+            },
+        };
+
+        let mut notes = Self {
+            path,
+            file,
+            key,
+            annotations,
+        };
+        if edited {
+            notes.reanchor(document);
+        }
+        Ok(notes)
+    }
+
+    /// Where the notes are saved
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// The annotation on an element
+    pub fn get(&self, element: usize) -> Option<&Annotation> {
+        self.position(element).ok().map(|i| &self.annotations[i])
+    }
+
+    /// Bookmark an element, or remove its bookmark and note; returns whether
+    /// the element is bookmarked now
+    pub fn toggle_bookmark(&mut self, document: &Document, element: usize) -> bool {
+        match self.position(element) {
+            Ok(i) => {
+                self.annotations.remove(i);
+                false
+            }
+            Err(i) => {
+                self.annotations
+                    .insert(i, annotation(document, element, None));
+                true
+            }
+        }
+    }
+
+    /// Set the note on an element, bookmarking it if it isn't; an empty note
+    /// leaves a plain bookmark
+    pub fn set_note(&mut self, document: &Document, element: usize, note: &str) {
+        let note = Some(note.trim().to_string()).filter(|note| !note.is_empty());
+        match self.position(element) {
+            Ok(i) => self.annotations[i].note = note,
+            Err(i) => self
+                .annotations
+                .insert(i, annotation(document, element, note)),
+        }
+    }
+
+    /// The first bookmarked element after `element`
+    pub fn next_after(&self, element: usize) -> Option<usize> {
+        self.annotations
+            .iter()
+            .map(|annotation| annotation.element)
+            .find(|&bookmarked| bookmarked > element)
+    }
+
+    /// The last bookmarked element before `element`
+    pub fn prev_before(&self, element: usize) -> Option<usize> {
+        self.annotations
+            .iter()
+            .map(|annotation| annotation.element)
+            .rfind(|&bookmarked| bookmarked < element)
+    }
+
+    /// Follow the document to a new version after it changed on disk
+    pub fn reload(&mut self, contents: &[u8], document: &Document) {
+        let key = document_key(contents);
+        if key != self.key {
+            self.key = key;
+            self.reanchor(document);
+        }
+    }
+
+    /// Write the annotations to the notes file, keeping other documents'
+    ///
+    /// A file name is unique within the directory, so the notes saved under
+    /// this one for earlier versions of the document are replaced.
+    pub fn save(&self) -> Result<()> {
+        let mut notes_file = read_notes_file(&self.path)?;
+        notes_file
+            .documents
+            .retain(|key, saved| *key != self.key && saved.file != self.file);
+        if !self.annotations.is_empty() {
+            notes_file.documents.insert(
+                self.key.clone(),
+                DocumentNotes {
+                    file: self.file.clone(),
+                    annotations: self.annotations.clone(),
+                },
+            );
+        }
+
+        // Don't leave an empty notes file behind
+        if notes_file.documents.is_empty() {
+            if self.path.exists() {
+                fs::remove_file(&self.path)
+                    .with_context(|| format!("Failed to remove {}", self.path.display()))?;
+            }
+            return Ok(());
+        }
+        let contents =
+            serde_json::to_string_pretty(&notes_file).context("Failed to serialize notes")?;
+        fs::write(&self.path, contents + "\n")
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    /// Where an element's annotation is, or would be inserted
+    fn position(&self, element: usize) -> Result<usize, usize> {
+        self.annotations
+            .binary_search_by_key(&element, |annotation| annotation.element)
+    }
+
+    /// Move each annotation to the element with its excerpt nearest where it
+    /// was, leaving it in place if the text is gone
+    fn reanchor(&mut self, document: &Document) {
+        let excerpts: Vec<String> = document.elements.iter().map(excerpt).collect();
+        let last = document.elements.len().saturating_sub(1);
+        for annotation in &mut self.annotations {
+            annotation.element = excerpts
+                .iter()
+                .enumerate()
+                .filter(|(_, excerpt)| **excerpt == annotation.excerpt)
+                .map(|(index, _)| index)
+                .min_by_key(|index| index.abs_diff(annotation.element))
+                .unwrap_or(annotation.element.min(last));
+        }
+        self.annotations
+            .sort_by_key(|annotation| annotation.element);
+        self.annotations
+            .dedup_by_key(|annotation| annotation.element);
+    }
+}
+
+fn annotation(document: &Document, element: usize, note: Option<String>) -> Annotation {
+    Annotation {
+        element,
+        excerpt: document
+            .elements
+            .get(element)
+            .map(excerpt)
+            .unwrap_or_default(),
+        note,
+    }
+}
+
+fn read_notes_file(path: &Path) -> Result<NotesFile> {
+    if !path.exists() {
+        return Ok(NotesFile::default());
+    }
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Identifies a version of a document: its size and CRC-32
+fn document_key(contents: &[u8]) -> String {
+    format!("{:x}-{:08x}", contents.len(), crc32fast::hash(contents))
+}
+
+/// The start of an element's text
+pub fn excerpt(element: &DocumentElement) -> String {
+    element_text(element).chars().take(EXCERPT_CHARS).collect()
+}
+
+/// An element's text on one line: a table's header row, an image's description
+pub fn element_text(element: &DocumentElement) -> String {
+    let text = match element {
+        DocumentElement::Heading { text, .. } => text.clone(),
+        DocumentElement::Paragraph { runs, .. } | DocumentElement::BlockQuote { runs } => {
+            runs.iter().map(|run| run.text.as_str()).collect()
+        }
+        DocumentElement::CodeBlock { code } => code.clone(),
+        DocumentElement::List { items, .. } => items
+            .iter()
+            .flat_map(|item| item.runs.iter().map(|run| run.text.as_str()))
+            .collect::<Vec<_>>()
+            .join(" "),
+        DocumentElement::Table { table } => table
+            .headers
+            .iter()
+            .map(|cell| cell.content.as_str())
+            .collect::<Vec<_>>()
+            .join(" | "),
+        DocumentElement::Image {
+            description,
+            caption,
+            ..
+        } => caption.as_ref().unwrap_or(description).clone(),
+        DocumentElement::Equation { latex, .. } => latex.clone(),
+        DocumentElement::PageBreak { .. }
+        | DocumentElement::HorizontalRule
+        | DocumentElement::FileBoundary { .. } => String::new(),
+    };
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
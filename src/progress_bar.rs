@@ -0,0 +1,84 @@
+//! The progress bar drawn on stderr while a document loads or exports
+//!
+//! Most documents open in a blink, so the bar stays hidden until a load has
+//! run for a moment; it's never drawn when stderr isn't a terminal or with
+//! `--quiet`.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::document::progress::{Phase, Progress, ProgressEvent};
+
+/// How long an operation runs before the bar appears
+const DELAY: Duration = Duration::from_millis(250);
+
+/// Draws [`ProgressEvent`]s as a bar, or a spinner for phases of unknown length
+#[derive(Clone)]
+pub struct LoadProgress {
+    bar: ProgressBar,
+    started: Instant,
+    enabled: bool,
+    shown: Arc<AtomicBool>,
+}
+
+impl LoadProgress {
+    pub fn new(quiet: bool) -> Self {
+        Self {
+            bar: ProgressBar::with_draw_target(None, ProgressDrawTarget::hidden()),
+            started: Instant::now(),
+            enabled: !quiet && std::io::stderr().is_terminal(),
+            shown: Default::default(),
+        }
+    }
+
+    /// Remove the bar, so that what's printed next starts on a clean line; a
+    /// later phase that's slow draws it again
+    pub fn clear(&self) {
+        if self.shown.swap(false, Ordering::Relaxed) {
+            self.bar.disable_steady_tick();
+            self.bar.finish_and_clear();
+            self.bar.set_draw_target(ProgressDrawTarget::hidden());
+            self.bar.reset();
+        }
+    }
+
+    fn show_once_slow(&self) {
+        if self.enabled
+            && self.started.elapsed() >= DELAY
+            && !self.shown.swap(true, Ordering::Relaxed)
+        {
+            self.bar.set_draw_target(ProgressDrawTarget::stderr());
+            self.bar.enable_steady_tick(Duration::from_millis(100));
+        }
+    }
+}
+
+impl Progress for LoadProgress {
+    fn event(&self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::Started { phase, total } => {
+                self.bar.set_length(total.unwrap_or(0));
+                self.bar.set_position(0);
+                let template = match (total, phase) {
+                    (Some(_), _) => "{spinner} {msg} [{bar:30}] {pos}/{len}",
+                    (None, Phase::Exporting) => "{spinner} {msg} {bytes}",
+                    (None, _) => "{spinner} {msg}",
+                };
+                if let Ok(style) = ProgressStyle::with_template(template) {
+                    self.bar.set_style(style.progress_chars("=> "));
+                }
+                self.bar.set_message(phase.label());
+            }
+            ProgressEvent::Advanced { done, .. } => self.bar.set_position(done),
+            ProgressEvent::Finished { .. } => {
+                if let Some(total) = self.bar.length() {
+                    self.bar.set_position(total);
+                }
+            }
+        }
+        self.show_once_slow();
+    }
+}
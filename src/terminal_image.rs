@@ -1,5 +1,21 @@
 use anyhow::Result;
+use image::AnimationDecoder;
+use std::io::BufReader;
 use std::path::Path;
+use std::time::Duration;
+
+use crate::document::ImageOptions;
+
+/// Most frames read from an animated GIF
+const MAX_ANIMATION_FRAMES: usize = 500;
+
+/// Shortest time a frame is shown; browsers treat shorter GIF delays,
+/// including none at all, as a tenth of a second, and so does doxx
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// How long a terminal gets to answer the cell size query
+const QUERY_TIMEOUT: Duration = Duration::from_millis(500);
 
 /// Terminal image display capabilities
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -17,6 +33,128 @@ pub struct TerminalImageRenderer {
     support: TerminalImageSupport,
     max_width: u32,
     max_height: u32,
+    /// Let the terminal play animated GIFs (`--animate`)
+    animate: bool,
+    /// Pixel size of a cell, which images are fitted to keep their shape
+    cell_size: Option<(u16, u16)>,
+}
+
+/// One frame of an animated image, and how long it's shown
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    pub image: image::DynamicImage,
+    pub delay: Duration,
+}
+
+/// The frames of an animated GIF, or `None` for a still image, a GIF with a
+/// single frame, or anything that isn't a GIF
+pub fn animation_frames(path: &Path) -> Option<Vec<AnimationFrame>> {
+    let decoder = gif_decoder(path)?;
+    let frames: Vec<_> = decoder
+        .into_frames()
+        .take(MAX_ANIMATION_FRAMES)
+        .map_while(|frame| frame.ok())
+        .map(|frame| {
+            let delay = Duration::from(frame.delay());
+            AnimationFrame {
+                delay: if delay < MIN_FRAME_DELAY {
+                    DEFAULT_FRAME_DELAY
+                } else {
+                    delay
+                },
+                image: image::DynamicImage::ImageRgba8(frame.into_buffer()),
+            }
+        })
+        .collect();
+    (frames.len() > 1).then_some(frames)
+}
+
+/// Whether an image is a GIF with more than one frame
+pub fn is_animated(path: &Path) -> bool {
+    gif_decoder(path).is_some_and(|decoder| decoder.into_frames().take(2).count() > 1)
+}
+
+fn gif_decoder(path: &Path) -> Option<image::codecs::gif::GifDecoder<BufReader<std::fs::File>>> {
+    let is_gif = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gif"));
+    if !is_gif {
+        return None;
+    }
+    let file = std::fs::File::open(path).ok()?;
+    image::codecs::gif::GifDecoder::new(BufReader::new(file)).ok()
+}
+
+/// The pixel size (width, height) of a terminal cell, if the terminal says
+///
+/// The window's size in pixels (`TIOCGWINSZ` on Unix) over its cells is
+/// tried first. Terminals that leave that zero, and Windows consoles, which
+/// have no such call, are asked with `CSI 16 t` instead.
+pub fn cell_size() -> Option<(u16, u16)> {
+    window_cell_size().or_else(query_cell_size)
+}
+
+fn window_cell_size() -> Option<(u16, u16)> {
+    let size = crossterm::terminal::window_size().ok()?;
+    if size.columns == 0 || size.rows == 0 {
+        return None;
+    }
+    let cell = (size.width / size.columns, size.height / size.rows);
+    (cell.0 > 0 && cell.1 > 0).then_some(cell)
+}
+
+/// Ask the terminal for its cell size, which it reports as `CSI 6 ; height ;
+/// width t`
+///
+/// A device status request (`CSI 5 n`) follows the query: every terminal
+/// answers it, so one that ignores the query doesn't keep us waiting.
+fn query_cell_size() -> Option<(u16, u16)> {
+    use std::io::{IsTerminal, Read, Write};
+
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let was_raw = crossterm::terminal::is_raw_mode_enabled().unwrap_or(false);
+    if !was_raw {
+        crossterm::terminal::enable_raw_mode().ok()?;
+    }
+
+    let mut stdout = std::io::stdout();
+    let sent = stdout
+        .write_all(b"\x1b[16t\x1b[5n")
+        .and_then(|_| stdout.flush());
+    let (sender, receiver) = std::sync::mpsc::channel();
+    if sent.is_ok() {
+        // The read blocks until the terminal answers, so it's left on its own
+        // thread and abandoned if the answer is too slow
+        std::thread::spawn(move || {
+            let mut reply = Vec::new();
+            for byte in std::io::stdin().lock().bytes() {
+                let Ok(byte) = byte else { break };
+                reply.push(byte);
+                if byte == b'n' && reply.windows(2).any(|pair| pair == b"\x1b[") {
+                    break;
+                }
+            }
+            let _ = sender.send(reply);
+        });
+    }
+    let reply = receiver.recv_timeout(QUERY_TIMEOUT).ok();
+
+    if !was_raw {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+    parse_cell_size_reply(&reply?)
+}
+
+/// The cell size in a terminal's `CSI 6 ; height ; width t` reply
+fn parse_cell_size_reply(reply: &[u8]) -> Option<(u16, u16)> {
+    let reply = String::from_utf8_lossy(reply);
+    let start = reply.find("\x1b[6;")? + 4;
+    let end = start + reply[start..].find('t')?;
+    let (height, width) = reply[start..end].split_once(';')?;
+    let cell: (u16, u16) = (width.parse().ok()?, height.parse().ok()?);
+    (cell.0 > 0 && cell.1 > 0).then_some(cell)
 }
 
 impl TerminalImageRenderer {
@@ -29,6 +167,8 @@ impl TerminalImageRenderer {
             support,
             max_width,
             max_height,
+            animate: false,
+            cell_size: None,
         }
     }
 
@@ -41,6 +181,8 @@ impl TerminalImageRenderer {
             support,
             max_width: max_width.unwrap_or(default_width),
             max_height: max_height.unwrap_or(default_height),
+            animate: false,
+            cell_size: None,
         }
     }
 
@@ -62,9 +204,26 @@ impl TerminalImageRenderer {
             support,
             max_width: ((scaled_width as f32) * scale_factor) as u32,
             max_height: ((scaled_height as f32) * scale_factor) as u32,
+            animate: false,
+            cell_size: None,
         }
     }
 
+    /// A renderer for the document's `--image-*` options and cell size
+    pub fn for_image_options(options: &ImageOptions) -> Self {
+        let mut renderer = Self::with_options(options.max_width, options.max_height, options.scale)
+            .with_animation(options.animate);
+        renderer.cell_size = options.cell_size;
+        renderer
+    }
+
+    /// Let terminals that play GIFs themselves (iTerm2) animate them;
+    /// otherwise an animated GIF is drawn as its first frame
+    pub fn with_animation(mut self, animate: bool) -> Self {
+        self.animate = animate;
+        self
+    }
+
     /// Create a renderer with specific capabilities (for testing)
     pub fn with_support(support: TerminalImageSupport) -> Self {
         let (max_width, max_height) = Self::get_terminal_size();
@@ -73,6 +232,8 @@ impl TerminalImageRenderer {
             support,
             max_width,
             max_height,
+            animate: false,
+            cell_size: None,
         }
     }
 
@@ -126,15 +287,26 @@ impl TerminalImageRenderer {
                 println!("📷 Image: {description}");
                 Ok(())
             }
+            // iTerm2 plays a GIF it's sent, so a still is sent in its place
+            // unless animation was asked for; the others only ever draw one frame
+            _ if !(self.animate && self.support == TerminalImageSupport::ITerm2)
+                && is_animated(image_path) =>
+            {
+                let first_frame = image::open(image_path)?;
+                let mut png = std::io::Cursor::new(Vec::new());
+                first_frame.write_to(&mut png, image::ImageFormat::Png)?;
+                self.render_image_from_bytes(png.get_ref(), description)
+            }
             _ => {
                 let display_path = image_path.to_path_buf();
 
                 // Use viuer to display the image with appropriate protocol
+                let (width, height) = self.image_cells(image::image_dimensions(image_path).ok());
                 let mut conf = viuer::Config {
                     transparent: true,
                     absolute_offset: false,
-                    width: Some(self.max_width.min(80)), // Limit width to 80 columns
-                    height: Some(self.max_height.min(24)), // Limit height to 24 rows
+                    width,
+                    height,
                     ..Default::default()
                 };
 
@@ -177,11 +349,16 @@ impl TerminalImageRenderer {
                 Ok(())
             }
             _ => {
+                let pixels = image::ImageReader::new(std::io::Cursor::new(image_data))
+                    .with_guessed_format()
+                    .ok()
+                    .and_then(|reader| reader.into_dimensions().ok());
+                let (width, height) = self.image_cells(pixels);
                 let mut conf = viuer::Config {
                     transparent: true,
                     absolute_offset: false,
-                    width: Some(self.max_width.min(80)),
-                    height: Some(self.max_height.min(24)),
+                    width,
+                    height,
                     ..Default::default()
                 };
 
@@ -220,6 +397,26 @@ impl TerminalImageRenderer {
         }
     }
 
+    /// The columns and rows to draw an image in, at most 80 by 24
+    ///
+    /// Both are given so the image keeps its shape on the cells it's drawn
+    /// on; viuer alone assumes cells twice as tall as wide. An image whose
+    /// size can't be read is fitted to the width and viuer picks its height.
+    fn image_cells(&self, pixels: Option<(u32, u32)>) -> (Option<u32>, Option<u32>) {
+        let (columns, rows) = (self.max_width.min(80), self.max_height.min(24));
+        let Some(pixels) = pixels else {
+            return (Some(columns), None);
+        };
+        let options = ImageOptions {
+            max_width: Some(columns),
+            max_height: Some(rows),
+            cell_size: self.cell_size,
+            ..Default::default()
+        };
+        let (width, height) = options.fit(pixels, (columns, rows));
+        (Some(width), Some(height))
+    }
+
     /// Get terminal size for image scaling
     fn get_terminal_size() -> (u32, u32) {
         // Try to get terminal size from crossterm
@@ -236,6 +433,10 @@ impl TerminalImageRenderer {
         println!("=== Terminal Image Debug Information ===");
         println!("Detected support: {:?}", self.support);
         println!("Max dimensions: {}x{}", self.max_width, self.max_height);
+        match cell_size() {
+            Some((width, height)) => println!("Cell size: {width}x{height} pixels"),
+            None => println!("Cell size: not reported"),
+        }
         println!("Can display images: {}", self.can_display_images());
 
         // Environment variables
@@ -298,6 +499,76 @@ mod tests {
         assert!(renderer.max_height > 0);
     }
 
+    /// Write a GIF of solid frames, each shown for the given milliseconds
+    fn write_gif(name: &str, delays: &[u32]) -> std::path::PathBuf {
+        use image::codecs::gif::GifEncoder;
+        use image::{Delay, Frame, Rgba, RgbaImage};
+
+        let path = std::env::temp_dir().join(name);
+        let mut encoder = GifEncoder::new(std::fs::File::create(&path).unwrap());
+        for (shade, &delay) in delays.iter().enumerate() {
+            let pixels = RgbaImage::from_pixel(4, 4, Rgba([shade as u8 * 80, 0, 0, 255]));
+            let delay = Delay::from_numer_denom_ms(delay, 1);
+            encoder
+                .encode_frame(Frame::from_parts(pixels, 0, 0, delay))
+                .unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn test_animated_gif_frames() {
+        let path = write_gif("doxx_test_animated.gif", &[0, 50, 500]);
+        assert!(is_animated(&path));
+        let delays: Vec<_> = animation_frames(&path)
+            .unwrap()
+            .iter()
+            .map(|frame| frame.delay)
+            .collect();
+        // No delay at all plays at the usual ten frames a second
+        assert_eq!(delays, [100, 50, 500].map(Duration::from_millis).to_vec());
+
+        // A single frame is a still image
+        let path = write_gif("doxx_test_still.gif", &[0]);
+        assert!(!is_animated(&path));
+        assert!(animation_frames(&path).is_none());
+    }
+
+    #[test]
+    fn test_cell_size_reply() {
+        assert_eq!(
+            parse_cell_size_reply(b"\x1b[6;36;17t\x1b[0n"),
+            Some((17, 36))
+        );
+        // A terminal that ignores the query only answers the status request
+        assert_eq!(parse_cell_size_reply(b"\x1b[0n"), None);
+        assert_eq!(parse_cell_size_reply(b"\x1b[6;0;0t\x1b[0n"), None);
+    }
+
+    #[test]
+    fn test_images_keep_their_shape() {
+        let hidpi = ImageOptions {
+            cell_size: Some((18, 36)),
+            ..Default::default()
+        };
+        // A square image is as many rows as columns over two
+        assert_eq!(hidpi.fit((1000, 1000), (80, 24)), (48, 24));
+        // A wide one fills the width and keeps its height in proportion
+        assert_eq!(hidpi.fit((1600, 400), (80, 24)), (80, 10));
+
+        // The limits and scale bound the box it's fitted to
+        let limited = ImageOptions {
+            max_width: Some(40),
+            scale: Some(0.5),
+            cell_size: Some((10, 20)),
+            ..Default::default()
+        };
+        assert_eq!(limited.fit((1600, 400), (80, 24)), (20, 3));
+
+        // Without a cell size, the default is assumed
+        assert_eq!(ImageOptions::default().fit((800, 800), (80, 24)), (48, 24));
+    }
+
     #[test]
     fn test_can_display_images() {
         let renderer = TerminalImageRenderer::with_support(TerminalImageSupport::Kitty);
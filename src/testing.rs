@@ -0,0 +1,234 @@
+//! Golden-file snapshots, for catching regressions in the exporters
+//!
+//! A fixture is loaded and rendered to an export format with fixed options
+//! (80 columns, true colour), and the result is compared with a golden file
+//! in `tests/golden`. A mismatch fails with a line diff of what changed.
+//! Other output, such as the JSON schema's cases and equation conversions,
+//! is checked the same way with [`assert_golden`]. After an intended change,
+//! run the tests with `DOXX_BLESS=1` to write the new output over the golden
+//! files, and review them in the commit.
+//!
+//! ```no_run
+//! use doxx::{testing::assert_export_snapshot, ExportFormat};
+//! use std::path::Path;
+//!
+//! assert_export_snapshot(
+//!     Path::new("tests/fixtures/example.docx"),
+//!     &ExportFormat::Markdown,
+//!     Path::new("tests/golden"),
+//! );
+//! ```
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    document::{load_document, ImageOptions},
+    export::{export_document_to_string, ExportOptions},
+    ColorDepth, ExportFormat,
+};
+
+/// Set to write the current output as the golden files instead of comparing
+pub const BLESS_VAR: &str = "DOXX_BLESS";
+
+/// Lines of unchanged context shown around each change in a diff
+const DIFF_CONTEXT: usize = 3;
+
+/// The formats a fixture can be snapshotted in: every export but notes,
+/// which come from the user's saved annotations
+pub fn snapshot_formats() -> Vec<ExportFormat> {
+    let mut formats = vec![
+        ExportFormat::Markdown,
+        ExportFormat::Text,
+        ExportFormat::Csv,
+        ExportFormat::Json,
+        ExportFormat::Ndjson,
+        ExportFormat::Asciidoc,
+        ExportFormat::Rst,
+        ExportFormat::Html,
+    ];
+    if cfg!(feature = "terminal") {
+        formats.push(ExportFormat::Ansi);
+    }
+    formats
+}
+
+/// The golden file for a fixture in a format: `<golden_dir>/<stem>.<extension>`
+pub fn golden_path(golden_dir: &Path, fixture: &Path, format: &ExportFormat) -> PathBuf {
+    let stem = fixture.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = match format {
+        ExportFormat::Markdown => "md",
+        ExportFormat::Text => "txt",
+        ExportFormat::Csv => "csv",
+        ExportFormat::Json => "json",
+        ExportFormat::Ndjson => "ndjson",
+        ExportFormat::Ansi => "ansi",
+        ExportFormat::Asciidoc => "adoc",
+        ExportFormat::Rst => "rst",
+        ExportFormat::Html => "html",
+        ExportFormat::Notes => "notes.md",
+    };
+    golden_dir.join(format!("{stem}.{extension}"))
+}
+
+/// A fixture exported with the options snapshots are taken with
+///
+/// The width and colour depth are fixed so the output doesn't depend on the
+/// terminal the tests run in.
+pub fn render_export(fixture: &Path, format: &ExportFormat) -> Result<String> {
+    let document = load_document(fixture, ImageOptions::default())
+        .with_context(|| format!("Failed to load {}", fixture.display()))?;
+    let options = ExportOptions {
+        terminal_width: Some(80),
+        color_depth: ColorDepth::TrueColor,
+        ..Default::default()
+    };
+    export_document_to_string(&document, format, &options)
+}
+
+/// Compare text with its golden file
+///
+/// Returns the diff when they differ, or when the golden file is missing.
+/// With `DOXX_BLESS` set, writes the golden file instead and returns `None`.
+pub fn check_golden(golden: &Path, actual: &str) -> Result<Option<String>> {
+    if std::env::var_os(BLESS_VAR).is_some_and(|value| !value.is_empty()) {
+        if let Some(dir) = golden.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(golden, actual)
+            .with_context(|| format!("Failed to write {}", golden.display()))?;
+        return Ok(None);
+    }
+
+    let Ok(expected) = std::fs::read_to_string(golden) else {
+        return Ok(Some(format!(
+            "{} is missing; run with {BLESS_VAR}=1 to create it",
+            golden.display()
+        )));
+    };
+    Ok((expected != actual).then(|| {
+        format!(
+            "{} no longer matches:\n{}",
+            golden.display(),
+            diff_lines(&expected, actual)
+        )
+    }))
+}
+
+/// Panic with a readable diff unless text matches its golden file
+pub fn assert_golden(golden: &Path, actual: &str) {
+    match check_golden(golden, actual) {
+        Ok(None) => {}
+        Ok(Some(diff)) => panic!("{diff}\nIf the change is intended, rerun with {BLESS_VAR}=1"),
+        Err(error) => panic!("{error:#}"),
+    }
+}
+
+/// Compare a fixture's export with its golden file, as [`check_golden`] does
+pub fn check_export_snapshot(
+    fixture: &Path,
+    format: &ExportFormat,
+    golden_dir: &Path,
+) -> Result<Option<String>> {
+    let actual = render_export(fixture, format)?;
+    let golden = golden_path(golden_dir, fixture, format);
+    Ok(check_golden(&golden, &actual)?
+        .map(|diff| format!("Export of {}: {diff}", fixture.display())))
+}
+
+/// Panic with a readable diff unless a fixture's export matches its golden file
+pub fn assert_export_snapshot(fixture: &Path, format: &ExportFormat, golden_dir: &Path) {
+    match check_export_snapshot(fixture, format, golden_dir) {
+        Ok(None) => {}
+        Ok(Some(diff)) => panic!("{diff}\nIf the change is intended, rerun with {BLESS_VAR}=1"),
+        Err(error) => panic!("{error:#}"),
+    }
+}
+
+/// A unified diff of two texts, line by line, with a few lines of context
+///
+/// Escape characters are shown as `␛` so ANSI output diffs legibly.
+pub fn diff_lines(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence, filled from the end
+    let mut common = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = match old[i] == new[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    // Each line as (index in the old text, index in the new, marker, text)
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((i, j, ' ', old[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push((i, j, '-', old[i]));
+            i += 1;
+        } else {
+            lines.push((i, j, '+', new[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = (0..lines.len())
+        .filter(|&index| lines[index].2 != ' ')
+        .collect();
+    let mut out = String::new();
+    let mut index = 0;
+    while let Some(&first) = changed.iter().find(|&&line| line >= index) {
+        // A hunk runs from the change's context to the end of the context of
+        // the last change that's near enough to join it
+        let start = first.saturating_sub(DIFF_CONTEXT).max(index);
+        let mut end = first;
+        while let Some(&next) = changed.iter().find(|&&line| line > end) {
+            if next > end + 2 * DIFF_CONTEXT {
+                break;
+            }
+            end = next;
+        }
+        let end = (end + DIFF_CONTEXT + 1).min(lines.len());
+        let (old_start, new_start, _, _) = lines[start];
+        out.push_str(&format!("@@ -{} +{} @@\n", old_start + 1, new_start + 1));
+        for &(_, _, marker, text) in &lines[start..end] {
+            out.push(marker);
+            out.push_str(&text.replace('\x1b', "␛"));
+            out.push('\n');
+        }
+        index = end;
+    }
+    if out.is_empty() && expected != actual {
+        out.push_str("(the texts differ only in line endings)\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_shows_changes_with_context() {
+        let expected = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n";
+        let actual = "one\ntwo\nTHREE\nfour\nfive\nsix\nseven\neight\nnine\nten\neleven\n";
+        assert_eq!(
+            diff_lines(expected, actual),
+            "@@ -1 +1 @@\n one\n two\n-three\n+THREE\n four\n five\n six\n\
+             @@ -8 +8 @@\n eight\n nine\n ten\n+eleven\n"
+        );
+        assert_eq!(diff_lines("same\n", "same\n"), "");
+        assert_eq!(
+            diff_lines("\x1b[1mbold", "bold"),
+            "@@ -1 +1 @@\n-␛[1mbold\n+bold\n"
+        );
+    }
+}
@@ -1,51 +1,401 @@
 use anyhow::Result;
-use arboard::Clipboard;
 use crossterm::{
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseEventKind,
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseEventKind,
     },
     execute,
+    style::{Attribute, SetAttribute},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     tty::IsTty,
 };
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout, Margin, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{
-        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
-        ScrollbarOrientation, ScrollbarState, Wrap,
+        Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Table, Wrap,
     },
     Frame, Terminal,
 };
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::{
+    clipboard::{Copier, CopyTarget},
+    config::{Config, LayoutConfig, SpellingConfig},
     document::*,
+    notes::{self, Notes},
     state::StateManager,
-    widgets::{DocumentWidget, LayoutCache},
-    Cli,
+    terminal_image::{animation_frames, AnimationFrame},
+    widgets::{banner, DocumentWidget, FrameStats, LayoutCache, BANNER_HEIGHT},
+    ChartStyle, Cli, ExportFormat, Redaction, TableCopyFormat,
 };
-use ratatui_image::{picker::Picker, protocol::StatefulProtocol};
+use ratatui_image::{
+    picker::{Picker, ProtocolType},
+    protocol::StatefulProtocol,
+    StatefulImage,
+};
+use unicode_width::UnicodeWidthStr;
 
 type ImageProtocols = Vec<StatefulProtocol>;
 
+/// How often `--watch` checks the document for changes
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Widest a presenter-mode slide gets, so lines stay readable on large screens
+const SLIDE_WIDTH: u16 = 100;
+
+/// Presenter mode: the document one section per screen
+pub struct Presenter {
+    pub slides: Vec<Slide>,
+    pub current: usize,
+    /// Elements of the current slide scrolled off the top
+    pub scroll: usize,
+    /// When the timer was started, while it is shown
+    pub timer_started: Option<Instant>,
+    /// Layout of the slide column, which is narrower than the document view
+    pub layout_cache: LayoutCache,
+}
+
+impl Presenter {
+    fn slide(&self) -> &Slide {
+        &self.slides[self.current]
+    }
+
+    fn go_to(&mut self, slide: usize) {
+        self.current = slide.min(self.slides.len() - 1);
+        self.scroll = 0;
+    }
+}
+
+/// Table mode: one table on its own, its rows sorted and filtered
+pub struct TableMode {
+    /// Index of the table element
+    pub element: usize,
+    /// Which table it is in the document, counting from 1
+    pub number: usize,
+    /// Column the arrow keys have selected
+    pub column: usize,
+    /// First column on screen, for tables wider than it
+    pub first_column: usize,
+    /// Sort column, and whether it's descending
+    pub sort: Option<(usize, bool)>,
+    pub filter: Option<TableFilter>,
+    /// Rows shown, as indices into the table's rows
+    pub rows: Vec<usize>,
+    /// Rows scrolled off the top
+    pub scroll: usize,
+    /// Rows that fit on screen in the last frame
+    pub page: usize,
+    /// The filter being typed, while its prompt is open
+    pub filter_input: Option<String>,
+}
+
+/// The file `--watch` reloads, and how it looked when last loaded
+pub struct FileWatch {
+    path: PathBuf,
+    /// Load leniently, as `--repair` does
+    repair: bool,
+    fields: FieldOptions,
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl FileWatch {
+    fn new(path: PathBuf, repair: bool, fields: FieldOptions) -> Self {
+        let stamp = Self::stamp(&path);
+        Self {
+            path,
+            repair,
+            fields,
+            stamp,
+        }
+    }
+
+    fn stamp(path: &std::path::Path) -> Option<(SystemTime, u64)> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+
+    /// Whether the file was modified since the last call
+    fn changed(&mut self) -> bool {
+        let stamp = Self::stamp(&self.path);
+        let changed = stamp.is_some() && stamp != self.stamp;
+        self.stamp = stamp;
+        changed
+    }
+
+    fn load(&self, image_options: ImageOptions) -> Result<Document> {
+        if self.repair {
+            Ok(crate::document::load_document_lenient_with_fields(
+                &self.path,
+                image_options,
+                self.fields,
+            )?
+            .0)
+        } else {
+            crate::document::load_document_with_fields(&self.path, image_options, self.fields)
+        }
+    }
+}
+
+/// What choosing a command palette entry does
+#[derive(Clone)]
+enum PaletteAction {
+    /// Show the element at this index
+    Jump(usize),
+    Run(PaletteCommand),
+}
+
+/// Commands the palette runs, besides jumping
+#[derive(Clone)]
+enum PaletteCommand {
+    /// Write the document beside it, with this file extension
+    Export(ExportFormat, &'static str),
+    ToggleImages,
+    ToggleColor,
+    CycleCharts,
+    ToggleSpellcheck,
+    TableMode,
+    ToggleOutlinePane,
+    TogglePreviewPane,
+    Outline,
+    Search,
+    Present,
+    Bookmark,
+    Note,
+    CopyDocument,
+    CopyViewCommand,
+    CopyTable,
+    Debug,
+    Inspect,
+    Help,
+    Quit,
+}
+
+/// An entry in the command palette
+struct PaletteEntry {
+    icon: &'static str,
+    label: String,
+    action: PaletteAction,
+}
+
+/// A palette entry matched by the query
+struct PaletteMatch {
+    /// Index into the palette's entries
+    entry: usize,
+    score: i64,
+    /// Char indices of the label that matched
+    positions: Vec<usize>,
+}
+
+/// A move through the document, which a count typed before its key repeats
+/// or, for `g`, `G` and `%`, aims
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    LineDown,
+    LineUp,
+    HalfPageDown,
+    HalfPageUp,
+    PageDown,
+    PageUp,
+    /// The start, or row N with a count (`g`, Home)
+    Top,
+    /// The end, or row N with a count (`G`, End)
+    Bottom,
+    /// N percent of the way through (`N%`)
+    Percent,
+    NextMatch,
+    PrevMatch,
+    NextBookmark,
+    PrevBookmark,
+}
+
+impl Motion {
+    /// The motion a key in the document view stands for, as in less
+    fn for_key(key: KeyEvent, searching: bool) -> Option<Self> {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        Some(match key.code {
+            KeyCode::Down | KeyCode::Char('j') if !control => Self::LineDown,
+            KeyCode::Up | KeyCode::Char('k') => Self::LineUp,
+            KeyCode::Char('d') if control => Self::HalfPageDown,
+            KeyCode::Char('u') if control => Self::HalfPageUp,
+            KeyCode::PageDown => Self::PageDown,
+            KeyCode::PageUp => Self::PageUp,
+            KeyCode::Home | KeyCode::Char('g') => Self::Top,
+            KeyCode::End | KeyCode::Char('G') => Self::Bottom,
+            KeyCode::Char('%') => Self::Percent,
+            KeyCode::Char('n') if searching => Self::NextMatch,
+            KeyCode::Char('p') if searching && !control => Self::PrevMatch,
+            KeyCode::Char(']') => Self::NextBookmark,
+            KeyCode::Char('[') => Self::PrevBookmark,
+            _ => return None,
+        })
+    }
+}
+
+/// What the next key does after `m` or `'`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkKey {
+    /// `m`: put a mark at the top of the view
+    Set,
+    /// `'`: go to a mark
+    Jump,
+}
+
+/// An animated GIF playing in the viewer
+struct Animation {
+    /// Its entry in `image_protocols`
+    slot: usize,
+    frames: Vec<AnimationFrame>,
+    current: usize,
+    /// When the frame after `current` is due
+    next_frame: Instant,
+}
+
+/// How many recent frame times the debug overlay summarises
+const FRAME_HISTORY: usize = 60;
+
+/// Recent frame times, for the debug overlay
+#[derive(Debug, Default)]
+pub struct FrameTimer {
+    times: VecDeque<Duration>,
+}
+
+impl FrameTimer {
+    pub fn record(&mut self, time: Duration) {
+        if self.times.len() == FRAME_HISTORY {
+            self.times.pop_front();
+        }
+        self.times.push_back(time);
+    }
+
+    pub fn last(&self) -> Option<Duration> {
+        self.times.back().copied()
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        let total: Duration = self.times.iter().sum();
+        (!self.times.is_empty()).then(|| total / self.times.len() as u32)
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.times.iter().max().copied()
+    }
+}
+
 pub struct App {
     pub document: Document,
     pub current_view: ViewMode,
+    /// Index of the element at the top of the viewport
     pub scroll_offset: usize,
+    /// Rows of that element scrolled off the top
+    pub scroll_line_offset: usize,
+    /// Height of the document viewport from the last frame
+    pub viewport_height: usize,
     pub search_query: String,
     pub search_results: Vec<SearchResult>,
     pub backup_search_results: Vec<SearchResult>,
     pub current_search_index: usize,
     pub outline_state: ListState,
+    pub heading_query: String,
+    pub heading_matches: Vec<HeadingMatch>,
+    pub heading_selected: usize,
     pub show_help: bool,
-    pub clipboard: Option<Clipboard>,
+    pub clipboard: Copier,
+    /// Format used by `y` to copy the table in view
+    pub table_copy_format: TableCopyFormat,
     pub status_message: Option<String>,
     pub color_enabled: bool,
     pub image_picker: Option<Picker>,
     pub image_protocols: ImageProtocols,
+    /// Image elements with a file to decode
+    pub image_count: usize,
+    /// Time spent decoding images at startup
+    pub image_decode_time: Duration,
     pub layout_cache: LayoutCache,
+    /// Show the performance overlay
+    pub show_debug: bool,
+    /// Show the source of the element at the top of the view (`X`)
+    pub show_inspector: bool,
+    /// Read from the file the first time the inspector is shown
+    pub inspector: Option<SourceInspector>,
+    /// The last inspection, and the element it was made for
+    inspection: Option<(usize, Vec<String>)>,
+    pub frame_timer: FrameTimer,
+    /// Set with `--watch`
+    pub watch: Option<FileWatch>,
+    /// [`fingerprint`](crate::document::diff::fingerprint) of each element as
+    /// loaded, to diff against on reload
+    pub fingerprints: Vec<u64>,
+    /// Fingerprint of the image element behind each entry of `image_protocols`
+    image_keys: Vec<u64>,
+    /// Animated GIFs playing in `image_protocols` (`--animate`)
+    animations: Vec<Animation>,
+    /// Set while in presenter mode
+    pub presenter: Option<Presenter>,
+    /// Talk length from `--timer`, counted down in presenter mode's footer
+    pub talk_length: Option<Duration>,
+    /// Show the timer as soon as presenter mode starts (`--timer`)
+    pub start_timer: bool,
+    /// Bookmarks and notes, or why they can't be kept for this document
+    pub notes: std::result::Result<Notes, String>,
+    /// The note being written in the note prompt
+    pub note_input: String,
+    /// Panes shown beside the document, from the config file
+    pub layout: LayoutConfig,
+    /// Keys go to the outline pane rather than the document
+    pub outline_focus: bool,
+    /// Selection in the outline pane while it has focus
+    pub outline_pane_state: ListState,
+    /// The image shown in the preview pane, by element index
+    preview_image: Option<(usize, StatefulProtocol)>,
+    /// Draw images in the document; the palette can hide them
+    show_images: bool,
+    /// Chart the numeric columns below each table (`--charts`, `v`)
+    pub charts: Option<ChartStyle>,
+    /// Underline words the dictionary doesn't know (`--spellcheck`, palette)
+    pub spellcheck: bool,
+    /// Misspelled words found while spellchecking is on
+    pub misspellings: Vec<Misspelling>,
+    /// Dictionaries and language from the config file
+    spelling: SpellingConfig,
+    /// Reads each dictionary the first time spellchecking needs it
+    #[cfg(feature = "spellcheck")]
+    spell_checker: crate::document::spelling::SpellChecker,
+    /// Masks the document, and every reload of it, with `--redact`
+    redactor: Option<crate::document::scan::Redactor>,
+    /// Adds the index and table of authorities to every reload, with `--index`
+    index: bool,
+    palette_query: String,
+    /// Everything the command palette lists, gathered when it opens
+    palette_entries: Vec<PaletteEntry>,
+    palette_matches: Vec<PaletteMatch>,
+    palette_selected: usize,
+    /// Set while in table mode
+    pub table_mode: Option<TableMode>,
+    /// Headings whose sections are folded, by element index
+    folds: BTreeSet<usize>,
+    /// Whether each element is hidden by a fold; empty when nothing is folded
+    hidden: Vec<bool>,
+    /// `z` was pressed and the next key is a fold command
+    fold_prefix: bool,
+    /// Digits typed before a navigation key
+    count: Option<usize>,
+    /// `m` or `'` was pressed and the next key names a mark
+    mark_prefix: Option<MarkKey>,
+    /// Marks set with `m{a-z}`: the element at the top of the view and how
+    /// far into it the view was
+    marks: BTreeMap<char, (usize, usize)>,
+    /// Where the view was before the last jump, for `''`
+    previous_position: Option<(usize, usize)>,
+    /// What the loader skipped or approximated, shown over the document until
+    /// Esc dismisses it
+    pub load_losses: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
@@ -54,8 +404,13 @@ pub enum ViewMode {
     Document,
     Outline,
     Search,
+    HeadingJump,
     #[allow(dead_code)]
     Help,
+    Present,
+    Annotate,
+    Palette,
+    Table,
 }
 
 impl App {
@@ -84,200 +439,1708 @@ impl App {
             (0, String::new(), ViewMode::Document)
         };
 
+        let config = Config::load().unwrap_or_default();
+        // The rules were checked before the document was read
+        let redactor = cli
+            .redact
+            .as_ref()
+            .and_then(|rules| config.scan.redactor(rules, cli.redact_with).ok());
+        let load_losses = crate::export::fidelity::load_losses(&document.fidelity);
         let mut app = Self {
             document,
             current_view: initial_view,
             scroll_offset: initial_scroll,
+            scroll_line_offset: 0,
+            viewport_height: 0,
             search_query: initial_search.clone(),
             search_results: Vec::new(),
             backup_search_results: Vec::new(),
             current_search_index: 0,
             outline_state: ListState::default(),
+            heading_query: String::new(),
+            heading_matches: Vec::new(),
+            heading_selected: 0,
             show_help: false,
-            clipboard: Clipboard::new().ok(),
+            clipboard: Copier::new(cli.clipboard, cli.osc52_limit),
+            table_copy_format: cli.table_format,
             status_message: None,
-            color_enabled: cli.color,
+            color_enabled: cli.color.enabled(IsTty::is_tty(&io::stdout())),
             image_picker: None,
             image_protocols: Vec::new(),
+            animations: Vec::new(),
+            image_count: 0,
+            image_decode_time: Duration::ZERO,
             layout_cache: LayoutCache::new(),
+            show_debug: false,
+            show_inspector: false,
+            inspector: None,
+            inspection: None,
+            frame_timer: FrameTimer::default(),
+            watch: None,
+            fingerprints: Vec::new(),
+            image_keys: Vec::new(),
+            presenter: None,
+            talk_length: cli
+                .timer
+                .filter(|&minutes| minutes > 0)
+                .map(|minutes| Duration::from_secs(u64::from(minutes) * 60)),
+            start_timer: cli.timer.is_some(),
+            notes: Err(String::new()),
+            note_input: String::new(),
+            layout: config.layout,
+            outline_focus: false,
+            outline_pane_state: ListState::default(),
+            preview_image: None,
+            show_images: true,
+            charts: cli.charts,
+            spellcheck: false,
+            misspellings: Vec::new(),
+            #[cfg(feature = "spellcheck")]
+            spell_checker: config.spelling.spell_checker(),
+            spelling: config.spelling,
+            redactor,
+            index: cli.index,
+            palette_query: String::new(),
+            palette_entries: Vec::new(),
+            palette_matches: Vec::new(),
+            palette_selected: 0,
+            table_mode: None,
+            folds: BTreeSet::new(),
+            hidden: Vec::new(),
+            fold_prefix: false,
+            count: None,
+            mark_prefix: None,
+            marks: BTreeMap::new(),
+            previous_position: None,
+            load_losses,
         };
 
+        let document_path = PathBuf::from(&app.document.metadata.file_path);
+        app.notes = Notes::load(&document_path, &app.document).map_err(|e| format!("{e:#}"));
+
+        if cli.watch {
+            app.fingerprints = fingerprints(&app.document);
+            app.watch = Some(FileWatch::new(
+                PathBuf::from(&app.document.metadata.file_path),
+                cli.repair,
+                FieldOptions {
+                    show_unknown: cli.show_field_codes,
+                    reading_order: cli.reading_order,
+                    spacing: cli.spacing,
+                    locale: cli.locale,
+                    range: cli.range,
+                },
+            ));
+        }
+
         // Restore search results if we had a saved search
         if !initial_search.is_empty() {
             app.search_results = crate::document::search_document(&app.document, &initial_search);
         }
 
-        // CLI options override saved state
-        if cli.outline {
-            app.current_view = ViewMode::Outline;
+        // CLI options override saved state
+        if cli.outline {
+            app.current_view = ViewMode::Outline;
+        }
+
+        if let Some(search) = &cli.search {
+            app.search_query = search.clone();
+            app.search_results = crate::document::search_document(&app.document, search);
+            app.current_view = ViewMode::Search;
+        }
+
+        if let Some(page) = cli.page {
+            // Rough estimate of elements per page
+            let elements_per_page = 10;
+            app.scroll_offset = (page.saturating_sub(1)) * elements_per_page;
+        }
+
+        // --goto lands in the document at that heading; a --search alongside it
+        // still highlights matches but doesn't take over the view
+        if let Some(heading) = &cli.goto {
+            match crate::document::resolve_heading(&app.document, heading) {
+                Some(element_index) => {
+                    app.jump_to_element(element_index);
+                    app.current_view = ViewMode::Document;
+                }
+                None => app.status_message = Some(format!("No heading matches '{heading}'")),
+            }
+        }
+
+        // Present from the section --goto or --page landed in
+        if cli.present {
+            app.start_presenting();
+        }
+
+        // Initialize image support if images are enabled
+        if cli.images {
+            app.init_image_support();
+        }
+
+        if cli.spellcheck {
+            app.toggle_spellcheck();
+        }
+
+        app
+    }
+
+    fn init_image_support(&mut self) {
+        // The query finds the graphics protocol (and the kitty protocol) on
+        // every platform, but guesses the cell size when the terminal won't
+        // answer `CSI 16 t`; the size measured at startup is used over a guess
+        let queried = Picker::from_query_stdio().ok();
+        let picker = match (queried, self.document.image_options.cell_size) {
+            (queried, Some(cell_size)) => {
+                let mut picker = Picker::from_fontsize(cell_size);
+                if let Some(queried) = queried {
+                    picker.set_protocol_type(queried.protocol_type());
+                }
+                picker
+            }
+            (Some(queried), None) => queried,
+            (None, None) => Picker::from_fontsize(crate::document::DEFAULT_CELL_SIZE),
+        };
+
+        self.image_picker = Some(picker);
+        self.load_images(Vec::new());
+    }
+
+    /// Decode the document's images, reusing the `(fingerprint, protocol)`
+    /// pairs of a previous load for images that haven't changed
+    fn load_images(&mut self, mut previous: Vec<(u64, StatefulProtocol)>) {
+        let Some(picker) = &self.image_picker else {
+            return;
+        };
+
+        let started = Instant::now();
+        self.image_count = 0;
+        self.image_protocols.clear();
+        self.image_keys.clear();
+        self.animations.clear();
+        // Kitty and iTerm2 take a whole new image quickly enough to play one
+        let animate = self.document.image_options.animate
+            && matches!(
+                picker.protocol_type(),
+                ProtocolType::Kitty | ProtocolType::Iterm2
+            );
+        for (index, element) in self.document.elements.iter().enumerate() {
+            if let DocumentElement::Image {
+                image_path: Some(path),
+                ..
+            } = element
+            {
+                self.image_count += 1;
+                let key = self.fingerprints.get(index).copied().unwrap_or_default();
+                if let Some(frames) = animate.then(|| animation_frames(path)).flatten() {
+                    self.image_protocols
+                        .push(picker.new_resize_protocol(frames[0].image.clone()));
+                    self.image_keys.push(key);
+                    self.animations.push(Animation {
+                        slot: self.image_protocols.len() - 1,
+                        next_frame: Instant::now() + frames[0].delay,
+                        current: 0,
+                        frames,
+                    });
+                    continue;
+                }
+                // An unchanged image keeps its protocol, so it isn't sent to the terminal again
+                if let Some(reused) = previous.iter().position(|(old, _)| *old == key) {
+                    self.image_protocols.push(previous.swap_remove(reused).1);
+                    self.image_keys.push(key);
+                    continue;
+                }
+                // Try to load and create protocol for each image
+                if let Ok(img) = image::ImageReader::open(path) {
+                    if let Ok(dyn_img) = img.decode() {
+                        let protocol = picker.new_resize_protocol(dyn_img);
+                        self.image_protocols.push(protocol);
+                        self.image_keys.push(key);
+                    }
+                }
+            }
+        }
+
+        self.image_decode_time = started.elapsed();
+    }
+
+    /// Reload the document if `--watch` saw the file change; returns whether it did
+    pub fn reload_if_changed(&mut self) -> bool {
+        let Some(watch) = &mut self.watch else {
+            return false;
+        };
+        if !watch.changed() {
+            return false;
+        }
+        // A save in progress can leave a partial file; the next change retries
+        match watch.load(self.document.image_options.clone()) {
+            Ok(mut document) => {
+                if self.index {
+                    crate::document::index::append_index(&mut document);
+                }
+                if let Some(redactor) = &self.redactor {
+                    redactor.redact_document(&mut document);
+                }
+                self.reload(document)
+            }
+            Err(error) => self.status_message = Some(format!("Reload failed: {error}")),
+        }
+        true
+    }
+
+    /// Switch to a new version of the document, keeping the scroll position,
+    /// wrapped lines and decoded images of every element that didn't change
+    pub fn reload(&mut self, document: Document) {
+        let fingerprints = fingerprints(&document);
+        let matches = crate::document::diff::match_elements(&self.fingerprints, &fingerprints);
+        let kept = matches.iter().flatten().count();
+        let changed = matches.len() - kept;
+        let removed = self.fingerprints.len() - kept;
+
+        // Stay on the element at the top of the view, or near where it was
+        match matches
+            .iter()
+            .position(|old| *old == Some(self.scroll_offset))
+        {
+            Some(top) => self.scroll_offset = top,
+            None => {
+                self.scroll_offset = self
+                    .scroll_offset
+                    .min(document.elements.len().saturating_sub(1));
+                self.scroll_line_offset = 0;
+            }
+        }
+
+        self.layout_cache.remap(&matches);
+        self.preview_image = None;
+        self.document = document;
+        self.fingerprints = fingerprints;
+        // Carry folds over to headings that are still there
+        let folds = self
+            .folds
+            .iter()
+            .filter_map(|&heading| matches.iter().position(|old| *old == Some(heading)))
+            .collect();
+        self.set_folds(folds);
+        // Marks stay on their elements, and go with them
+        let moved = |(element, line_offset): (usize, usize)| {
+            let element = matches.iter().position(|old| *old == Some(element))?;
+            Some((element, line_offset))
+        };
+        self.marks = std::mem::take(&mut self.marks)
+            .into_iter()
+            .filter_map(|(letter, mark)| Some((letter, moved(mark)?)))
+            .collect();
+        self.previous_position = self.previous_position.and_then(moved);
+        self.reveal(self.scroll_offset);
+        // The source changed under the inspector
+        self.inspector = None;
+        self.inspection = None;
+        if self.show_inspector {
+            self.show_inspector = false;
+            self.toggle_inspector();
+        }
+
+        let previous = self
+            .image_keys
+            .drain(..)
+            .zip(self.image_protocols.drain(..))
+            .collect();
+        self.load_images(previous);
+
+        if !self.search_query.is_empty() {
+            self.search_results =
+                crate::document::search_document(&self.document, &self.search_query);
+            self.current_search_index = self
+                .current_search_index
+                .min(self.search_results.len().saturating_sub(1));
+        }
+
+        if self.spellcheck {
+            self.check_spelling();
+        }
+
+        if let Ok(notes) = &mut self.notes {
+            if let Ok(contents) = std::fs::read(&self.document.metadata.file_path) {
+                notes.reload(&contents, &self.document);
+            }
+        }
+
+        if let Some(presenter) = &mut self.presenter {
+            presenter.slides = crate::document::presentation_slides(&self.document);
+            presenter.layout_cache = LayoutCache::new();
+            let (current, scroll) = (presenter.current, presenter.scroll);
+            presenter.go_to(current);
+            presenter.scroll = scroll.min(presenter.slide().body.len().saturating_sub(1));
+        }
+
+        self.status_message = Some(format!(
+            "Reloaded: {changed} changed, {removed} removed, {kept} unchanged."
+        ));
+    }
+
+    pub fn next_search_result(&mut self) {
+        if !self.search_results.is_empty() {
+            self.current_search_index = (self.current_search_index + 1) % self.search_results.len();
+            if let Some(element_index) = self
+                .search_results
+                .get(self.current_search_index)
+                .map(|result| result.element_index)
+            {
+                self.reveal(element_index);
+                self.scroll_offset = element_index;
+            }
+        }
+    }
+
+    pub fn prev_search_result(&mut self) {
+        if !self.search_results.is_empty() {
+            self.current_search_index = if self.current_search_index == 0 {
+                self.search_results.len() - 1
+            } else {
+                self.current_search_index - 1
+            };
+            if let Some(element_index) = self
+                .search_results
+                .get(self.current_search_index)
+                .map(|result| result.element_index)
+            {
+                self.reveal(element_index);
+                self.scroll_offset = element_index;
+            }
+        }
+    }
+
+    /// Put the start of an element at the top of the viewport
+    pub fn jump_to_element(&mut self, element_index: usize) {
+        self.reveal(element_index);
+        self.scroll_offset = element_index;
+        self.scroll_line_offset = 0;
+    }
+
+    fn is_hidden(&self, element_index: usize) -> bool {
+        self.hidden.get(element_index).copied().unwrap_or(false)
+    }
+
+    /// Handle the key after `z`: `a` toggles the fold of the section in view,
+    /// `o` opens it, `c` closes it, `M` folds every section and `R` opens them all
+    pub fn fold_command(&mut self, key: KeyCode) {
+        self.fold_prefix = false;
+        let heading = crate::document::section_heading(&self.document, self.scroll_offset);
+        match (key, heading) {
+            (KeyCode::Char('a'), Some(heading)) if self.folds.contains(&heading) => {
+                self.open_fold(heading)
+            }
+            (KeyCode::Char('a') | KeyCode::Char('c'), Some(heading)) => self.close_fold(heading),
+            (KeyCode::Char('o'), Some(heading)) => self.open_fold(heading),
+            (KeyCode::Char('a') | KeyCode::Char('c') | KeyCode::Char('o'), None) => {
+                self.status_message = Some("No heading to fold.".to_string())
+            }
+            (KeyCode::Char('M'), _) => {
+                let folds = (0..self.document.elements.len())
+                    .filter(|&index| {
+                        matches!(
+                            self.document.elements[index],
+                            DocumentElement::Heading { .. }
+                        ) && self.section_end(index) > index + 1
+                    })
+                    .collect();
+                self.set_folds(folds);
+                if let Some(heading) = heading {
+                    self.jump_to_element(heading);
+                }
+            }
+            (KeyCode::Char('R'), _) => self.set_folds(BTreeSet::new()),
+            _ => {}
+        }
+    }
+
+    /// Handle a key in the document view that moves through it as in less:
+    /// digits make a count for the motion after them, and `m` and `'` take the
+    /// letter of a mark. Returns whether the key was one of these.
+    pub fn navigation_key(&mut self, key: KeyEvent) -> bool {
+        if let Some(mark) = self.mark_prefix.take() {
+            self.count = None;
+            match (mark, key.code) {
+                (MarkKey::Set, KeyCode::Char(letter @ 'a'..='z')) => self.set_mark(letter),
+                (MarkKey::Jump, KeyCode::Char(letter @ 'a'..='z')) => self.jump_to_mark(letter),
+                (MarkKey::Jump, KeyCode::Char('\'')) => self.jump_back(),
+                _ => {}
+            }
+            return true;
+        }
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char(digit @ '0'..='9') if !control => {
+                let digit = digit.to_digit(10).unwrap_or(0) as usize;
+                let count = self.count.unwrap_or(0);
+                self.count = Some(count.saturating_mul(10).saturating_add(digit));
+                return true;
+            }
+            KeyCode::Char('m') if !control => {
+                self.mark_prefix = Some(MarkKey::Set);
+                return true;
+            }
+            KeyCode::Char('\'') => {
+                self.mark_prefix = Some(MarkKey::Jump);
+                return true;
+            }
+            KeyCode::Esc if self.count.is_some() => {
+                self.count = None;
+                return true;
+            }
+            _ => {}
+        }
+        // Any other key drops the count
+        let count = self.count.take();
+        match Motion::for_key(key, !self.search_results.is_empty()) {
+            Some(motion) => {
+                self.navigate(motion, count);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Make a move: `count` times over, or for `g`, `G` and `%` to where
+    /// `count` says
+    pub fn navigate(&mut self, motion: Motion, count: Option<usize>) {
+        let times = count.unwrap_or(1).max(1);
+        match motion {
+            Motion::LineDown => (0..times).for_each(|_| self.scroll_down()),
+            Motion::LineUp => (0..times).for_each(|_| self.scroll_up()),
+            Motion::HalfPageDown => self.page_down(times.saturating_mul(self.half_page())),
+            Motion::HalfPageUp => self.page_up(times.saturating_mul(self.half_page())),
+            Motion::PageDown => self.page_down(times.saturating_mul(self.page_size())),
+            Motion::PageUp => self.page_up(times.saturating_mul(self.page_size())),
+            Motion::Top | Motion::Bottom | Motion::Percent => {
+                self.previous_position = Some(self.position());
+                match (motion, count) {
+                    (Motion::Percent, percent) => self.go_to_percent(percent.unwrap_or(0)),
+                    // Rows are counted from 1, as less counts lines
+                    (_, Some(row)) => self.go_to_row(row.saturating_sub(1)),
+                    (Motion::Top, None) => self.jump_to_element(0),
+                    (_, None) => {
+                        let last = (0..self.document.elements.len())
+                            .rev()
+                            .find(|&index| !self.is_hidden(index))
+                            .unwrap_or(0);
+                        self.jump_to_element(last)
+                    }
+                }
+            }
+            Motion::NextMatch => (0..times).for_each(|_| self.next_search_result()),
+            Motion::PrevMatch => (0..times).for_each(|_| self.prev_search_result()),
+            Motion::NextBookmark => (0..times).for_each(|_| self.next_bookmark()),
+            Motion::PrevBookmark => (0..times).for_each(|_| self.prev_bookmark()),
+        }
+    }
+
+    /// Rows to move for Ctrl-D and Ctrl-U
+    fn half_page(&self) -> usize {
+        (self.viewport_height / 2).max(1)
+    }
+
+    /// Put a document row at the top of the view, counting rows as laid out
+    /// on screen, or elements before the first frame
+    fn go_to_row(&mut self, row: usize) {
+        let elements = self.document.elements.len();
+        if self.layout_cache.has_layout(elements) {
+            let last = self.layout_cache.total_lines().saturating_sub(1);
+            self.scroll_to_line(row.min(last));
+        } else {
+            self.jump_to_element(row.min(elements.saturating_sub(1)));
+        }
+    }
+
+    fn go_to_percent(&mut self, percent: usize) {
+        let elements = self.document.elements.len();
+        let total = match self.layout_cache.has_layout(elements) {
+            true => self.layout_cache.total_lines(),
+            false => elements,
+        };
+        self.go_to_row(total * percent.min(100) / 100);
+    }
+
+    /// The element at the top of the view and the row of it shown first
+    fn position(&self) -> (usize, usize) {
+        (self.scroll_offset, self.scroll_line_offset)
+    }
+
+    fn set_position(&mut self, (element, line_offset): (usize, usize)) {
+        let element = element.min(self.document.elements.len().saturating_sub(1));
+        self.reveal(element);
+        self.scroll_offset = element;
+        self.scroll_line_offset = line_offset;
+    }
+
+    fn set_mark(&mut self, letter: char) {
+        self.marks.insert(letter, self.position());
+        self.status_message = Some(format!("Mark {letter} set"));
+    }
+
+    fn jump_to_mark(&mut self, letter: char) {
+        match self.marks.get(&letter).copied() {
+            Some(mark) => {
+                self.previous_position = Some(self.position());
+                self.set_position(mark);
+            }
+            None => self.status_message = Some(format!("No mark {letter}")),
+        }
+    }
+
+    /// Go back to where the view was before the last jump (`''`); again to return
+    fn jump_back(&mut self) {
+        match self.previous_position {
+            Some(previous) => {
+                self.previous_position = Some(self.position());
+                self.set_position(previous);
+            }
+            None => self.status_message = Some("No jump to go back from".to_string()),
+        }
+    }
+
+    /// Fold a section away, moving up to its heading if the view was inside it
+    fn close_fold(&mut self, heading: usize) {
+        if self.section_end(heading) == heading + 1 {
+            self.status_message = Some("Nothing under this heading to fold.".to_string());
+            return;
+        }
+        let mut folds = self.folds.clone();
+        folds.insert(heading);
+        self.set_folds(folds);
+        if self.scroll_offset != heading {
+            self.jump_to_element(heading);
+        }
+    }
+
+    fn open_fold(&mut self, heading: usize) {
+        let mut folds = self.folds.clone();
+        folds.remove(&heading);
+        self.set_folds(folds);
+    }
+
+    /// Index just past the last element in a heading's section
+    fn section_end(&self, heading: usize) -> usize {
+        let elements = &self.document.elements;
+        let DocumentElement::Heading { level, .. } = elements[heading] else {
+            return heading + 1;
+        };
+        (heading + 1..elements.len())
+            .find(|&index| {
+                matches!(elements[index], DocumentElement::Heading { level: next, .. } if next <= level)
+            })
+            .unwrap_or(elements.len())
+    }
+
+    /// Open every fold that hides an element, so jumping to it shows it
+    fn reveal(&mut self, element_index: usize) {
+        if !self.is_hidden(element_index) {
+            return;
+        }
+        let folds = self
+            .folds
+            .iter()
+            .copied()
+            .filter(|&heading| {
+                !(heading < element_index && element_index < self.section_end(heading))
+            })
+            .collect();
+        self.set_folds(folds);
+    }
+
+    fn set_folds(&mut self, folds: BTreeSet<usize>) {
+        self.folds = folds;
+        self.hidden = if self.folds.is_empty() {
+            Vec::new()
+        } else {
+            crate::document::folded_elements(&self.document, &self.folds)
+        };
+    }
+
+    /// Document row at the top of the viewport, once a layout exists
+    fn current_line(&self) -> Option<usize> {
+        self.layout_cache
+            .has_layout(self.document.elements.len())
+            .then(|| {
+                self.layout_cache.line_of_element(self.scroll_offset) + self.scroll_line_offset
+            })
+    }
+
+    /// Scroll so the given document row is at the top of the viewport
+    fn scroll_to_line(&mut self, line: usize) {
+        let (element_index, line_offset) = self.layout_cache.element_at_line(line);
+        self.scroll_offset = element_index;
+        self.scroll_line_offset = line_offset;
+    }
+
+    pub fn scroll_up(&mut self) {
+        // A resize can leave the offset past the end of the (now shorter) element
+        if let Some(height) = self.layout_cache.height(self.scroll_offset) {
+            self.scroll_line_offset = self.scroll_line_offset.min(height.saturating_sub(1));
+        }
+        if self.scroll_line_offset > 0 {
+            self.scroll_line_offset -= 1;
+        } else if let Some(previous) = (0..self.scroll_offset)
+            .rev()
+            .find(|&index| !self.is_hidden(index))
+        {
+            self.scroll_offset = previous;
+            self.scroll_line_offset = self
+                .layout_cache
+                .height(self.scroll_offset)
+                .map_or(0, |height| height.saturating_sub(1));
+        }
+    }
+
+    pub fn scroll_down(&mut self) {
+        let height = self.layout_cache.height(self.scroll_offset).unwrap_or(1);
+        if self.scroll_line_offset + 1 < height {
+            self.scroll_line_offset += 1;
+        } else if let Some(next) = (self.scroll_offset + 1..self.document.elements.len())
+            .find(|&index| !self.is_hidden(index))
+        {
+            self.jump_to_element(next);
+        }
+    }
+
+    pub fn page_up(&mut self, page_size: usize) {
+        match self.current_line() {
+            Some(line) => self.scroll_to_line(line.saturating_sub(page_size)),
+            None => self.jump_to_element(self.scroll_offset.saturating_sub(page_size)),
+        }
+    }
+
+    pub fn page_down(&mut self, page_size: usize) {
+        match self.current_line() {
+            Some(line) => self.scroll_to_line(line + page_size),
+            None => {
+                let max_offset = self.document.elements.len().saturating_sub(1);
+                self.jump_to_element(std::cmp::min(self.scroll_offset + page_size, max_offset));
+            }
+        }
+    }
+
+    /// Rows to move for Page Up/Page Down: one viewport, keeping a line of context
+    fn page_size(&self) -> usize {
+        if self.viewport_height > 1 {
+            self.viewport_height - 1
+        } else {
+            10
+        }
+    }
+
+    /// Copy `content` and report where it went in the status line
+    fn copy_text(&mut self, content: &str, what: &str) {
+        self.status_message = Some(match self.clipboard.copy(content, &mut io::stdout()) {
+            Ok(target) => target.describe(what, self.clipboard.limit),
+            Err(error) => format!("{error}."),
+        });
+    }
+
+    pub fn copy_content(&mut self) {
+        let (content, what) = match self.current_view {
+            ViewMode::Document => {
+                // Copy the full document as text
+                (crate::export::format_as_text(&self.document), "document")
+            }
+            ViewMode::Search => {
+                // Copy search results
+                if self.search_results.is_empty() {
+                    self.status_message = Some("No search results to copy.".to_string());
+                    return;
+                }
+                let mut content = format!("Search results for '{}':\n\n", self.search_query);
+                for (i, result) in self.search_results.iter().enumerate() {
+                    content.push_str(&format!("{}. {}\n", i + 1, result.text.trim()));
+                }
+                (content, "search results")
+            }
+            ViewMode::Outline => {
+                // Copy document outline
+                let outline = crate::document::generate_outline(&self.document);
+                let mut content = String::from("Document Outline:\n\n");
+                for item in outline {
+                    let indent = "  ".repeat((item.level as usize).saturating_sub(1));
+                    content.push_str(&format!("{}{}\n", indent, item.title));
+                }
+                (content, "outline")
+            }
+            _ => {
+                self.status_message =
+                    Some("Content not available for copying in this view.".to_string());
+                return;
+            }
+        };
+
+        self.copy_text(&content, what);
+    }
+
+    /// The first table whose rows are on screen
+    fn table_in_view(&self) -> Option<&TableData> {
+        match &self.document.elements[self.table_index_in_view()?] {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        }
+    }
+
+    /// Element index of the first table whose rows are on screen
+    fn table_index_in_view(&self) -> Option<usize> {
+        let top = self.current_line()?;
+        let bottom = top + self.viewport_height.max(1);
+        (self.scroll_offset..self.document.elements.len())
+            .take_while(|&index| self.layout_cache.line_of_element(index) < bottom)
+            .filter(|&index| !self.is_hidden(index))
+            .find(|&index| matches!(self.document.elements[index], DocumentElement::Table { .. }))
+    }
+
+    /// Copy the table in view, keeping its cell boundaries
+    pub fn copy_table(&mut self) {
+        let Some(table) = self.table_in_view() else {
+            self.status_message = Some("No table in view.".to_string());
+            return;
+        };
+        let format = self.table_copy_format;
+        let content = crate::export::format_table(table, format);
+        let rows = table.rows.len();
+
+        self.copy_text(
+            &content,
+            &format!("table ({rows} rows) as {}", format.name()),
+        );
+    }
+
+    /// Switch the format `y` copies tables in: TSV, Markdown, then CSV
+    pub fn cycle_table_copy_format(&mut self) {
+        self.table_copy_format = self.table_copy_format.next();
+        self.status_message = Some(format!("Tables copy as {}.", self.table_copy_format.name()));
+    }
+
+    pub fn open_heading_jump(&mut self) {
+        self.heading_query.clear();
+        self.update_heading_matches();
+        self.current_view = ViewMode::HeadingJump;
+    }
+
+    pub fn update_heading_matches(&mut self) {
+        self.heading_matches =
+            crate::document::fuzzy_find_headings(&self.document, &self.heading_query);
+        self.heading_selected = 0;
+    }
+
+    pub fn next_heading_match(&mut self) {
+        if self.heading_selected + 1 < self.heading_matches.len() {
+            self.heading_selected += 1;
+        }
+    }
+
+    pub fn prev_heading_match(&mut self) {
+        self.heading_selected = self.heading_selected.saturating_sub(1);
+    }
+
+    pub fn jump_to_selected_heading(&mut self) {
+        if let Some(heading) = self.heading_matches.get(self.heading_selected) {
+            self.jump_to_element(heading.item.element_index);
+        }
+        self.current_view = ViewMode::Document;
+    }
+
+    /// Heading of the section currently at the top of the viewport
+    fn current_heading(&self) -> Option<OutlineItem> {
+        crate::document::generate_outline(&self.document)
+            .into_iter()
+            .take_while(|item| item.element_index <= self.scroll_offset)
+            .last()
+    }
+
+    /// A `doxx` invocation that reopens this document in the current view
+    ///
+    /// `None` when the document was piped in, as from standard input or
+    /// `<(curl …)`: the pipe is gone, so no command could reopen it.
+    pub fn view_command(&self) -> Option<String> {
+        if !Path::new(&self.document.metadata.file_path).is_file() {
+            return None;
+        }
+        let mut args = vec![
+            "doxx".to_string(),
+            shell_quote(&self.document.metadata.file_path),
+        ];
+
+        match self.current_view {
+            ViewMode::Outline => args.push("--outline".to_string()),
+            // --search on its own reopens the search view
+            ViewMode::Search => {}
+            ViewMode::Present => {
+                args.push("--present".to_string());
+                let heading = self.presenter.as_ref().and_then(|p| p.slide().heading);
+                if let Some(DocumentElement::Heading { text, number, .. }) =
+                    heading.map(|index| &self.document.elements[index])
+                {
+                    let title = match number {
+                        Some(number) => format!("{number} {text}"),
+                        None => text.clone(),
+                    };
+                    args.push("--goto".to_string());
+                    args.push(shell_quote(&title));
+                }
+            }
+            _ => {
+                if let Some(heading) = self.current_heading() {
+                    args.push("--goto".to_string());
+                    args.push(shell_quote(&heading.title));
+                }
+            }
+        }
+
+        if !self.search_query.is_empty() {
+            args.push("--search".to_string());
+            args.push(shell_quote(&self.search_query));
+        }
+        if !self.color_enabled {
+            args.push("--color=never".to_string());
+        }
+        if self.image_picker.is_some() && self.show_images {
+            args.push("--images".to_string());
+        }
+        match self.charts {
+            Some(ChartStyle::Sparkline) => args.push("--charts".to_string()),
+            Some(ChartStyle::Bars) => args.push("--charts=bars".to_string()),
+            None => {}
+        }
+        if self.spellcheck {
+            args.push("--spellcheck".to_string());
+        }
+        if self.index {
+            args.push("--index".to_string());
+        }
+        if let Some(redactor) = &self.redactor {
+            let rules: Vec<&str> = redactor
+                .rules
+                .iter()
+                .map(|rule| rule.name.as_str())
+                .collect();
+            args.push(shell_quote(&format!("--redact={}", rules.join(","))));
+            if redactor.style == Redaction::Placeholder {
+                args.push("--redact-with=placeholder".to_string());
+            }
+        }
+
+        Some(args.join(" "))
+    }
+
+    pub fn copy_view_command(&mut self) {
+        let Some(command) = self.view_command() else {
+            self.status_message = Some(
+                "This document was piped in from standard input, so no command can reopen it."
+                    .to_string(),
+            );
+            return;
+        };
+        self.status_message = Some(match self.clipboard.copy(&command, &mut io::stdout()) {
+            Ok(CopyTarget::File { .. }) | Err(_) => {
+                format!("Clipboard not available. Command: {command}")
+            }
+            Ok(_) => format!("Copied command: {command}"),
+        });
+    }
+
+    pub fn clear_status_message(&mut self) {
+        self.status_message = None;
+    }
+
+    pub fn toggle_search_state(&mut self) {
+        if self.search_query.is_empty() {
+            return;
+        }
+        // Toggles search state: clears results if active, restores backup if inactive.
+        if !self.search_results.is_empty() {
+            self.backup_search_results = self.search_results.clone();
+            self.search_results.clear();
+        } else if !self.backup_search_results.is_empty() {
+            self.search_results = self.backup_search_results.clone();
+            self.backup_search_results.clear();
+        }
+    }
+
+    /// Bookmark the element at the top of the view, or remove its bookmark
+    pub fn toggle_bookmark(&mut self) {
+        let element = self.scroll_offset;
+        let notes = match &mut self.notes {
+            Ok(notes) => notes,
+            Err(e) => {
+                self.status_message = Some(format!("❌ Bookmarks unavailable: {e}"));
+                return;
+            }
+        };
+        let had_note = notes.get(element).is_some_and(|a| a.note.is_some());
+        let message = if notes.toggle_bookmark(&self.document, element) {
+            "🔖 Bookmarked"
+        } else if had_note {
+            "Bookmark and note removed"
+        } else {
+            "Bookmark removed"
+        };
+        self.save_notes(message);
+    }
+
+    /// Open the prompt for a note on the element at the top of the view
+    pub fn open_note_prompt(&mut self) {
+        match &self.notes {
+            Ok(notes) => {
+                self.note_input = notes
+                    .get(self.scroll_offset)
+                    .and_then(|annotation| annotation.note.clone())
+                    .unwrap_or_default();
+                self.current_view = ViewMode::Annotate;
+            }
+            Err(e) => self.status_message = Some(format!("❌ Notes unavailable: {e}")),
+        }
+    }
+
+    /// Attach the note from the prompt
+    pub fn save_note(&mut self) {
+        self.current_view = ViewMode::Document;
+        if let Ok(notes) = &mut self.notes {
+            notes.set_note(&self.document, self.scroll_offset, &self.note_input);
+            self.save_notes("📝 Note saved");
+        }
+    }
+
+    /// Write the notes file, reporting `message` or the failure
+    fn save_notes(&mut self, message: &str) {
+        if let Ok(notes) = &self.notes {
+            self.status_message = Some(match notes.save() {
+                Ok(()) => message.to_string(),
+                Err(e) => format!("❌ Failed to save notes: {e:#}"),
+            });
+        }
+    }
+
+    pub fn next_bookmark(&mut self) {
+        let next = self
+            .notes
+            .as_ref()
+            .ok()
+            .and_then(|notes| notes.next_after(self.scroll_offset));
+        match next {
+            Some(element) => self.jump_to_element(element),
+            None => self.status_message = Some("No bookmarks below".to_string()),
+        }
+    }
+
+    pub fn prev_bookmark(&mut self) {
+        // A bookmark partly scrolled past is the one to go back to
+        let from = self.scroll_offset + usize::from(self.scroll_line_offset > 0);
+        let prev = self
+            .notes
+            .as_ref()
+            .ok()
+            .and_then(|notes| notes.prev_before(from));
+        match prev {
+            Some(element) => self.jump_to_element(element),
+            None => self.status_message = Some("No bookmarks above".to_string()),
+        }
+    }
+
+    /// The bookmark on the element at the top of the view
+    fn annotation_in_view(&self) -> Option<&notes::Annotation> {
+        self.notes.as_ref().ok()?.get(self.scroll_offset)
+    }
+
+    /// The table row at the top of the view and the table's row count, when the
+    /// view starts in a table
+    fn table_row_in_view(&self) -> Option<(usize, usize)> {
+        DocumentWidget::new(&self.document.elements)
+            .sections(&self.document.sections)
+            .table_row_in_view(self.scroll_offset, self.scroll_line_offset)
+    }
+
+    /// Show or hide the XML, style chain and numbering behind the element in view
+    pub fn toggle_inspector(&mut self) {
+        if self.show_inspector {
+            self.show_inspector = false;
+            return;
+        }
+        if self.redactor.is_some() {
+            self.status_message = Some("The source isn't shown with --redact".to_string());
+            return;
+        }
+        if self.inspector.is_none() {
+            let path = std::path::Path::new(&self.document.metadata.file_path);
+            match SourceInspector::open(path) {
+                Ok(inspector) => self.inspector = Some(inspector),
+                Err(error) => {
+                    self.status_message = Some(format!("Can't read the source: {error}"));
+                    return;
+                }
+            }
+        }
+        self.show_inspector = true;
+    }
+
+    /// The inspector's report on the element at the top of the view
+    fn inspection_lines(&mut self) -> &[String] {
+        let element = self.scroll_offset;
+        if self.inspection.as_ref().map(|(index, _)| *index) != Some(element) {
+            let lines = self
+                .inspector
+                .as_ref()
+                .and_then(|inspector| inspector.inspect(&self.document, element))
+                .map(|inspection| inspection.lines())
+                .unwrap_or_default();
+            self.inspection = Some((element, lines));
+        }
+        self.inspection.as_ref().map_or(&[], |(_, lines)| lines)
+    }
+
+    /// Show or hide the outline pane
+    pub fn toggle_outline_pane(&mut self) {
+        self.layout.outline = !self.layout.outline;
+        self.outline_focus = false;
+        self.save_layout();
+    }
+
+    /// Show or hide the preview pane
+    pub fn toggle_preview_pane(&mut self) {
+        self.layout.preview = !self.layout.preview;
+        self.save_layout();
+    }
+
+    /// Move keyboard focus between the document and the outline pane
+    pub fn toggle_outline_focus(&mut self) {
+        if !self.layout.outline {
+            return;
+        }
+        self.outline_focus = !self.outline_focus;
+        if self.outline_focus {
+            let outline = crate::document::generate_outline(&self.document);
+            self.outline_pane_state
+                .select(current_outline_item(&outline, self.scroll_offset));
+        }
+    }
+
+    /// Move the outline pane's selection
+    pub fn select_in_outline_pane(&mut self, down: bool) {
+        let len = crate::document::generate_outline(&self.document).len();
+        let selected = self.outline_pane_state.selected().unwrap_or(0);
+        let selected = if down {
+            (selected + 1).min(len.saturating_sub(1))
+        } else {
+            selected.saturating_sub(1)
+        };
+        self.outline_pane_state.select(Some(selected));
+    }
+
+    /// Show the heading selected in the outline pane
+    pub fn open_outline_pane_selection(&mut self) {
+        let outline = crate::document::generate_outline(&self.document);
+        if let Some(item) = self
+            .outline_pane_state
+            .selected()
+            .and_then(|selected| outline.get(selected))
+        {
+            self.jump_to_element(item.element_index);
+        }
+    }
+
+    /// Apply a layout key (pane toggles and resizing); returns whether it was one
+    fn handle_layout_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('O') => self.toggle_outline_pane(),
+            KeyCode::Char('I') => self.toggle_preview_pane(),
+            KeyCode::Tab => self.toggle_outline_focus(),
+            KeyCode::Char('<' | '>') if self.layout.outline => {
+                self.layout.resize_outline(code == KeyCode::Char('>'));
+                self.save_layout();
+            }
+            KeyCode::Char('{' | '}') if self.layout.preview => {
+                // The preview pane is on the right, so `{` moves its edge left
+                self.layout.resize_preview(code == KeyCode::Char('{'));
+                self.save_layout();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Keep the pane layout for next time
+    fn save_layout(&mut self) {
+        let saved = Config::load().and_then(|mut config| {
+            config.layout = self.layout;
+            config.save()
+        });
+        if let Err(e) = saved {
+            self.status_message = Some(format!("❌ Failed to save layout: {e:#}"));
+        }
+    }
+
+    /// The first image at least partly on screen
+    fn image_in_view(&self) -> Option<usize> {
+        let elements = &self.document.elements;
+        let last = match self.current_line() {
+            Some(line) => {
+                let (element, _) = self
+                    .layout_cache
+                    .element_at_line(line + self.viewport_height.saturating_sub(1));
+                element
+            }
+            None => self.scroll_offset + self.viewport_height,
+        };
+        (self.scroll_offset..=last.min(elements.len().saturating_sub(1)))
+            .filter(|&index| !self.is_hidden(index))
+            .find(|&index| matches!(elements[index], DocumentElement::Image { .. }))
+    }
+
+    /// Open the table in view on its own, to sort and filter it
+    pub fn open_table_mode(&mut self) {
+        let Some(element) = self.table_index_in_view() else {
+            self.status_message = Some("No table in view.".to_string());
+            return;
+        };
+        let number = self.document.elements[..=element]
+            .iter()
+            .filter(|element| matches!(element, DocumentElement::Table { .. }))
+            .count();
+        self.table_mode = Some(TableMode {
+            element,
+            number,
+            column: 0,
+            first_column: 0,
+            sort: None,
+            filter: None,
+            rows: Vec::new(),
+            scroll: 0,
+            page: 0,
+            filter_input: None,
+        });
+        self.refresh_table_rows();
+        self.current_view = ViewMode::Table;
+    }
+
+    /// The table shown in table mode
+    fn table_mode_table(&self) -> Option<&TableData> {
+        let mode = self.table_mode.as_ref()?;
+        match self.document.elements.get(mode.element)? {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        }
+    }
+
+    /// Sort and filter the rows again
+    fn refresh_table_rows(&mut self) {
+        let locale = self.document.cell_locale();
+        let Some(mode) = &mut self.table_mode else {
+            return;
+        };
+        let Some(DocumentElement::Table { table }) = self.document.elements.get(mode.element)
+        else {
+            return;
+        };
+        mode.rows = crate::document::table_rows(table, mode.sort, mode.filter.as_ref(), locale);
+        mode.scroll = mode.scroll.min(mode.rows.len().saturating_sub(1));
+    }
+
+    pub fn select_table_column(&mut self, right: bool) {
+        let columns = self
+            .table_mode_table()
+            .map_or(0, |table| table.headers.len());
+        if let Some(mode) = &mut self.table_mode {
+            mode.column = if right {
+                (mode.column + 1).min(columns.saturating_sub(1))
+            } else {
+                mode.column.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Sort by the selected column: ascending, then descending, then unsorted
+    pub fn cycle_table_sort(&mut self) {
+        if let Some(mode) = &mut self.table_mode {
+            mode.sort = match mode.sort {
+                Some((column, false)) if column == mode.column => Some((column, true)),
+                Some((column, true)) if column == mode.column => None,
+                _ => Some((mode.column, false)),
+            };
+        }
+        self.refresh_table_rows();
+    }
+
+    /// Open the filter prompt, starting from the selected column
+    pub fn open_table_filter(&mut self) {
+        let (Some(mode), Some(table)) = (&self.table_mode, self.table_mode_table()) else {
+            return;
+        };
+        // A header the filter couldn't be read back with is written as its number
+        let name = |column: usize| match table.headers.get(column) {
+            Some(cell)
+                if !cell.content.trim().is_empty()
+                    && !cell.content.contains(['<', '>', '=', '~', '!']) =>
+            {
+                cell.content.trim().to_string()
+            }
+            _ => (column + 1).to_string(),
+        };
+        let input = match &mode.filter {
+            Some(filter) => format!(
+                "{} {} {}",
+                name(filter.column),
+                filter.op.symbol(),
+                filter.value
+            ),
+            None => format!("{} contains ", name(mode.column)),
+        };
+        if let Some(mode) = &mut self.table_mode {
+            mode.filter_input = Some(input);
+        }
+    }
+
+    /// Apply the filter typed in the prompt; a mistake leaves the prompt open
+    pub fn apply_table_filter(&mut self) {
+        let Some(table) = self.table_mode_table() else {
+            return;
+        };
+        let Some(input) = self
+            .table_mode
+            .as_ref()
+            .and_then(|mode| mode.filter_input.clone())
+        else {
+            return;
+        };
+        let parsed = if input.trim().is_empty() {
+            Ok(None)
+        } else {
+            TableFilter::parse(&input, table).map(Some)
+        };
+        match parsed {
+            Ok(filter) => {
+                if let Some(mode) = &mut self.table_mode {
+                    mode.filter = filter;
+                    mode.filter_input = None;
+                    mode.scroll = 0;
+                }
+                self.refresh_table_rows();
+            }
+            Err(e) => self.status_message = Some(format!("❌ {e}")),
+        }
+    }
+
+    pub fn clear_table_filter(&mut self) {
+        if let Some(mode) = &mut self.table_mode {
+            mode.filter = None;
+        }
+        self.refresh_table_rows();
+    }
+
+    fn table_page(&self) -> usize {
+        self.table_mode
+            .as_ref()
+            .map_or(1, |mode| mode.page.saturating_sub(1).max(1))
+    }
+
+    pub fn scroll_table(&mut self, rows: isize) {
+        if let Some(mode) = &mut self.table_mode {
+            let last = mode.rows.len().saturating_sub(mode.page.max(1));
+            mode.scroll = mode.scroll.saturating_add_signed(rows).min(last);
+        }
+    }
+
+    /// The table as it's shown: only the rows that pass the filter, in order
+    fn table_mode_view(&self) -> Option<TableData> {
+        let mode = self.table_mode.as_ref()?;
+        Some(crate::document::table_with_rows(
+            self.table_mode_table()?,
+            &mode.rows,
+        ))
+    }
+
+    /// Copy the sorted and filtered table
+    pub fn copy_table_view(&mut self) {
+        let Some(table) = self.table_mode_view() else {
+            return;
+        };
+        let format = self.table_copy_format;
+        let content = crate::export::format_table(&table, format);
+        self.copy_text(
+            &content,
+            &format!("table ({} rows) as {}", table.rows.len(), format.name()),
+        );
+    }
+
+    /// Save the sorted and filtered table as CSV beside the document
+    pub fn save_table_view(&mut self) {
+        let (Some(table), Some(mode)) = (self.table_mode_view(), &self.table_mode) else {
+            return;
+        };
+        let source = PathBuf::from(&self.document.metadata.file_path);
+        let stem = source
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "table".to_string());
+        let path = source.with_file_name(format!("{stem}-table{}.csv", mode.number));
+        if path.exists() {
+            self.status_message = Some(format!("❌ {} already exists", path.display()));
+            return;
+        }
+        let csv = crate::export::format_table(&table, TableCopyFormat::Csv);
+        self.status_message = Some(match std::fs::write(&path, csv) {
+            Ok(()) => format!("💾 Saved {} rows to {}", table.rows.len(), path.display()),
+            Err(e) => format!("❌ Failed to write {}: {e}", path.display()),
+        });
+    }
+
+    /// Back to the document, at the table
+    pub fn close_table_mode(&mut self) {
+        if let Some(mode) = self.table_mode.take() {
+            if self.scroll_offset > mode.element {
+                self.jump_to_element(mode.element);
+            }
+        }
+        self.current_view = ViewMode::Document;
+    }
+
+    /// Open the command palette, listing what it can jump to and do
+    pub fn open_palette(&mut self) {
+        self.palette_entries = palette_entries(&self.document, self.notes.as_ref().ok());
+        self.palette_query.clear();
+        self.update_palette_matches();
+        self.current_view = ViewMode::Palette;
+    }
+
+    pub fn update_palette_matches(&mut self) {
+        let mut matches: Vec<PaletteMatch> = self
+            .palette_entries
+            .iter()
+            .enumerate()
+            .filter_map(|(entry, palette_entry)| {
+                let (score, positions) =
+                    crate::document::fuzzy_match(&palette_entry.label, &self.palette_query)?;
+                Some(PaletteMatch {
+                    entry,
+                    score,
+                    positions,
+                })
+            })
+            .collect();
+        // Stable sort keeps the listed order among equal scores
+        matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+        self.palette_matches = matches;
+        self.palette_selected = 0;
+    }
+
+    pub fn next_palette_match(&mut self) {
+        if self.palette_selected + 1 < self.palette_matches.len() {
+            self.palette_selected += 1;
+        }
+    }
+
+    pub fn prev_palette_match(&mut self) {
+        self.palette_selected = self.palette_selected.saturating_sub(1);
+    }
+
+    /// Jump to or run the selected entry; returns whether it was the quit command
+    pub fn run_palette_selection(&mut self) -> bool {
+        self.current_view = ViewMode::Document;
+        let Some(selected) = self.palette_matches.get(self.palette_selected) else {
+            return false;
+        };
+        match self.palette_entries[selected.entry].action.clone() {
+            PaletteAction::Jump(element) => self.jump_to_element(element),
+            PaletteAction::Run(command) => return self.run_command(command),
+        }
+        false
+    }
+
+    /// Run a palette command; returns whether it was the quit command
+    fn run_command(&mut self, command: PaletteCommand) -> bool {
+        match command {
+            PaletteCommand::Export(format, extension) => self.export_beside(format, extension),
+            PaletteCommand::ToggleImages => self.toggle_images(),
+            PaletteCommand::CycleCharts => self.cycle_charts(),
+            PaletteCommand::ToggleSpellcheck => self.toggle_spellcheck(),
+            PaletteCommand::ToggleColor => {
+                self.color_enabled = !self.color_enabled;
+                self.status_message = Some(
+                    if self.color_enabled {
+                        "Colors on"
+                    } else {
+                        "Colors off"
+                    }
+                    .to_string(),
+                );
+            }
+            PaletteCommand::TableMode => self.open_table_mode(),
+            PaletteCommand::ToggleOutlinePane => self.toggle_outline_pane(),
+            PaletteCommand::TogglePreviewPane => self.toggle_preview_pane(),
+            PaletteCommand::Outline => self.current_view = ViewMode::Outline,
+            PaletteCommand::Search => self.current_view = ViewMode::Search,
+            PaletteCommand::Present => self.start_presenting(),
+            PaletteCommand::Bookmark => self.toggle_bookmark(),
+            PaletteCommand::Note => self.open_note_prompt(),
+            PaletteCommand::CopyDocument => self.copy_content(),
+            PaletteCommand::CopyViewCommand => self.copy_view_command(),
+            PaletteCommand::CopyTable => self.copy_table(),
+            PaletteCommand::Debug => self.show_debug = !self.show_debug,
+            PaletteCommand::Inspect => self.toggle_inspector(),
+            PaletteCommand::Help => self.show_help = true,
+            PaletteCommand::Quit => return true,
+        }
+        false
+    }
+
+    /// Export the document to a file next to it, named after it
+    ///
+    /// An existing file is left alone rather than overwritten.
+    fn export_beside(&mut self, format: ExportFormat, extension: &str) {
+        let source = PathBuf::from(&self.document.metadata.file_path);
+        if !source.is_file() {
+            self.status_message = Some("❌ Export needs the document's file".to_string());
+            return;
         }
-
-        if let Some(search) = &cli.search {
-            app.search_query = search.clone();
-            app.search_results = crate::document::search_document(&app.document, search);
-            app.current_view = ViewMode::Search;
+        let path = source.with_extension(extension);
+        if path.exists() {
+            self.status_message = Some(format!("❌ {} already exists", path.display()));
+            return;
         }
+        let exported = crate::export::export_document_to_file(
+            &self.document,
+            &format,
+            &crate::export::ExportOptions::default(),
+            &path,
+        );
+        self.status_message = Some(match exported {
+            Ok(()) => format!("💾 Exported to {}", path.display()),
+            Err(e) => format!("❌ Export failed: {e:#}"),
+        });
+    }
 
-        if let Some(page) = cli.page {
-            // Rough estimate of elements per page
-            let elements_per_page = 10;
-            app.scroll_offset = (page.saturating_sub(1)) * elements_per_page;
+    /// Show or hide the document's images
+    pub fn toggle_images(&mut self) {
+        if self.image_picker.is_none() {
+            self.status_message =
+                Some("Images aren't loaded; start doxx with --images".to_string());
+            return;
         }
-
-        // Initialize image support if images are enabled
-        if cli.images {
-            app.init_image_support();
+        self.show_images = !self.show_images;
+        // Images take rows of their own, so the layout changes
+        self.layout_cache = LayoutCache::new();
+        self.scroll_line_offset = 0;
+        if let Some(presenter) = &mut self.presenter {
+            presenter.layout_cache = LayoutCache::new();
         }
-
-        app
+        self.status_message = Some(
+            if self.show_images {
+                "Images shown"
+            } else {
+                "Images hidden"
+            }
+            .to_string(),
+        );
     }
 
-    fn init_image_support(&mut self) {
-        // Try to initialize picker from terminal query on Unix, use font size on Windows
-        #[cfg(unix)]
-        let picker = if let Ok(p) = Picker::from_query_stdio() {
-            p
-        } else {
-            // Fallback to manual font size
-            Picker::from_fontsize((8, 16))
+    /// Cycle the table charts from none to sparklines to bars and back
+    pub fn cycle_charts(&mut self) {
+        self.charts = match self.charts {
+            None => Some(ChartStyle::Sparkline),
+            Some(ChartStyle::Sparkline) => Some(ChartStyle::Bars),
+            Some(ChartStyle::Bars) => None,
         };
+        // Charts take rows of their own, so the layout changes
+        self.layout_cache = LayoutCache::new();
+        self.scroll_line_offset = 0;
+        if let Some(presenter) = &mut self.presenter {
+            presenter.layout_cache = LayoutCache::new();
+        }
+        self.status_message = Some(
+            match self.charts {
+                None => "Table charts hidden",
+                Some(ChartStyle::Sparkline) => "Table charts: sparklines",
+                Some(ChartStyle::Bars) => "Table charts: bars",
+            }
+            .to_string(),
+        );
+    }
 
-        #[cfg(not(unix))]
-        let picker = Picker::from_fontsize((8, 16));
+    /// Turn underlining misspelled words on or off
+    pub fn toggle_spellcheck(&mut self) {
+        self.spellcheck = !self.spellcheck;
+        if self.spellcheck {
+            self.check_spelling();
+        } else {
+            self.misspellings.clear();
+            self.status_message = Some("Spellcheck off".to_string());
+        }
+    }
 
-        // Process all images in the document
-        for element in &self.document.elements {
-            if let DocumentElement::Image {
-                image_path: Some(path),
-                ..
-            } = element
-            {
-                // Try to load and create protocol for each image
-                if let Ok(img) = image::ImageReader::open(path) {
-                    if let Ok(dyn_img) = img.decode() {
-                        let protocol = picker.new_resize_protocol(dyn_img);
-                        self.image_protocols.push(protocol);
-                    }
-                }
+    /// Find the misspelled words, reading the dictionary for the document's
+    /// language if it hasn't been yet
+    #[cfg(feature = "spellcheck")]
+    fn check_spelling(&mut self) {
+        use crate::document::spelling;
+        let language =
+            spelling::document_language(&self.document, self.spelling.language.as_deref());
+        match self.spell_checker.dictionary(&language) {
+            Ok(dictionary) => {
+                self.misspellings = spelling::check_document(&self.document, dictionary);
+                let count = self.misspellings.len();
+                let noun = if count == 1 { "word" } else { "words" };
+                self.status_message =
+                    Some(format!("Spellcheck ({language}): {count} unknown {noun}"));
+            }
+            Err(error) => {
+                self.spellcheck = false;
+                self.misspellings.clear();
+                self.status_message = Some(format!("Spellcheck unavailable: {error:#}"));
             }
         }
+    }
 
-        self.image_picker = Some(picker);
+    #[cfg(not(feature = "spellcheck"))]
+    fn check_spelling(&mut self) {
+        let _ = &self.spelling;
+        self.spellcheck = false;
+        self.status_message =
+            Some("This doxx was built without the spellcheck feature".to_string());
     }
 
-    pub fn next_search_result(&mut self) {
-        if !self.search_results.is_empty() {
-            self.current_search_index = (self.current_search_index + 1) % self.search_results.len();
-            if let Some(result) = self.search_results.get(self.current_search_index) {
-                self.scroll_offset = result.element_index;
-            }
+    /// Enter presenter mode at the section in view
+    pub fn start_presenting(&mut self) {
+        let slides = crate::document::presentation_slides(&self.document);
+        let current = slides
+            .iter()
+            .rposition(|slide| slide.heading.unwrap_or(slide.body.start) <= self.scroll_offset)
+            .unwrap_or(0);
+        let timer_started = match &self.presenter {
+            Some(presenter) => presenter.timer_started,
+            None => self.start_timer.then(Instant::now),
+        };
+        self.presenter = Some(Presenter {
+            slides,
+            current,
+            scroll: 0,
+            timer_started,
+            layout_cache: LayoutCache::new(),
+        });
+        self.current_view = ViewMode::Present;
+    }
+
+    /// Leave presenter mode, showing the document at the current section
+    pub fn stop_presenting(&mut self) {
+        if let Some(presenter) = &self.presenter {
+            let slide = presenter.slide();
+            let element = slide.heading.unwrap_or(slide.body.start) + presenter.scroll;
+            self.jump_to_element(element.min(self.document.elements.len().saturating_sub(1)));
         }
+        self.current_view = ViewMode::Document;
     }
 
-    pub fn prev_search_result(&mut self) {
-        if !self.search_results.is_empty() {
-            self.current_search_index = if self.current_search_index == 0 {
-                self.search_results.len() - 1
-            } else {
-                self.current_search_index - 1
-            };
-            if let Some(result) = self.search_results.get(self.current_search_index) {
-                self.scroll_offset = result.element_index;
-            }
+    pub fn next_slide(&mut self) {
+        if let Some(presenter) = &mut self.presenter {
+            presenter.go_to(presenter.current + 1);
         }
     }
 
-    pub fn scroll_up(&mut self) {
-        self.scroll_offset = self.scroll_offset.saturating_sub(1);
+    pub fn prev_slide(&mut self) {
+        if let Some(presenter) = &mut self.presenter {
+            presenter.go_to(presenter.current.saturating_sub(1));
+        }
     }
 
-    pub fn scroll_down(&mut self) {
-        if self.scroll_offset + 1 < self.document.elements.len() {
-            self.scroll_offset += 1;
+    pub fn first_slide(&mut self) {
+        if let Some(presenter) = &mut self.presenter {
+            presenter.go_to(0);
         }
     }
 
-    pub fn page_up(&mut self, page_size: usize) {
-        self.scroll_offset = self.scroll_offset.saturating_sub(page_size);
+    pub fn last_slide(&mut self) {
+        if let Some(presenter) = &mut self.presenter {
+            presenter.go_to(presenter.slides.len() - 1);
+        }
     }
 
-    pub fn page_down(&mut self, page_size: usize) {
-        let max_offset = self.document.elements.len().saturating_sub(1);
-        self.scroll_offset = std::cmp::min(self.scroll_offset + page_size, max_offset);
+    /// Scroll a slide too long for the screen, an element at a time
+    pub fn scroll_slide(&mut self, down: bool) {
+        if let Some(presenter) = &mut self.presenter {
+            let last = presenter.slide().body.len().saturating_sub(1);
+            presenter.scroll = if down {
+                (presenter.scroll + 1).min(last)
+            } else {
+                presenter.scroll.saturating_sub(1)
+            };
+        }
     }
 
-    pub fn copy_content(&mut self) {
-        if let Some(clipboard) = &mut self.clipboard {
-            let content = match self.current_view {
-                ViewMode::Document => {
-                    // Copy the full document as text
-                    crate::export::format_as_text(&self.document)
-                }
-                ViewMode::Search => {
-                    // Copy search results
-                    if self.search_results.is_empty() {
-                        "No search results to copy.".to_string()
-                    } else {
-                        let mut content =
-                            format!("Search results for '{}':\n\n", self.search_query);
-                        for (i, result) in self.search_results.iter().enumerate() {
-                            content.push_str(&format!("{}. {}\n", i + 1, result.text.trim()));
-                        }
-                        content
-                    }
-                }
-                ViewMode::Outline => {
-                    // Copy document outline
-                    let outline = crate::document::generate_outline(&self.document);
-                    let mut content = String::from("Document Outline:\n\n");
-                    for item in outline {
-                        let indent = "  ".repeat((item.level as usize).saturating_sub(1));
-                        content.push_str(&format!("{}{}\n", indent, item.title));
-                    }
-                    content
-                }
-                _ => "Content not available for copying in this view.".to_string(),
+    /// Show the presenter timer from zero, or hide it
+    pub fn toggle_timer(&mut self) {
+        if let Some(presenter) = &mut self.presenter {
+            presenter.timer_started = match presenter.timer_started {
+                Some(_) => None,
+                None => Some(Instant::now()),
             };
+        }
+    }
 
-            match clipboard.set_text(content) {
-                Ok(_) => {
-                    self.status_message = Some("Copied to clipboard!".to_string());
-                }
-                Err(_) => {
-                    self.status_message = Some("Failed to copy to clipboard.".to_string());
-                }
+    /// Whether the screen has a running clock to keep up to date
+    /// Show the next frame of each animation that's due; returns whether any was
+    fn advance_animations(&mut self) -> bool {
+        let Some(picker) = &self.image_picker else {
+            return false;
+        };
+        if !self.show_images {
+            return false;
+        }
+        let now = Instant::now();
+        let mut advanced = false;
+        for animation in &mut self.animations {
+            if now < animation.next_frame {
+                continue;
+            }
+            animation.current = (animation.current + 1) % animation.frames.len();
+            let frame = &animation.frames[animation.current];
+            animation.next_frame = now + frame.delay;
+            if let Some(protocol) = self.image_protocols.get_mut(animation.slot) {
+                *protocol = picker.new_resize_protocol(frame.image.clone());
+                advanced = true;
             }
-        } else {
-            self.status_message = Some("Clipboard not available.".to_string());
         }
+        advanced
     }
 
-    pub fn clear_status_message(&mut self) {
-        self.status_message = None;
+    /// Time until an animation next changes frame, if any are playing
+    fn next_frame_in(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.animations
+            .iter()
+            .map(|animation| animation.next_frame.saturating_duration_since(now))
+            .min()
     }
 
-    pub fn toggle_search_state(&mut self) {
-        if self.search_query.is_empty() {
-            return;
-        }
-        // Toggles search state: clears results if active, restores backup if inactive.
-        if !self.search_results.is_empty() {
-            self.backup_search_results = self.search_results.clone();
-            self.search_results.clear();
-        } else if !self.backup_search_results.is_empty() {
-            self.search_results = self.backup_search_results.clone();
-            self.backup_search_results.clear();
-        }
+    fn timer_running(&self) -> bool {
+        matches!(self.current_view, ViewMode::Present)
+            && self
+                .presenter
+                .as_ref()
+                .is_some_and(|presenter| presenter.timer_started.is_some())
     }
 }
 
 async fn run_non_interactive(document: Document, cli: &Cli) -> Result<()> {
     let app = App::new(document, cli);
+    // Only coloured when piped if --color=always or CLICOLOR_FORCE asks for it
+    let preview_runs = |runs: &[FormattedRun]| match app.color_enabled {
+        true => crate::ansi::format_runs(runs, &cli.color_depth),
+        false => runs
+            .iter()
+            .map(|run| run.formatting.display_text(&run.text))
+            .collect(),
+    };
+
+    if let Some(message) = &app.status_message {
+        eprintln!("{message}");
+    }
 
     match app.current_view {
         ViewMode::Outline => {
             // Show outline
-            let outline = crate::document::generate_outline(&app.document);
+            let outline = outline_view(&app.document);
             println!("Document Outline:");
             println!("================");
             for item in outline {
@@ -302,13 +2165,21 @@ async fn run_non_interactive(document: Document, cli: &Cli) -> Result<()> {
             println!("Document: {}", app.document.title);
             println!("Pages: {}", app.document.metadata.page_count);
             println!("Words: {}", app.document.metadata.word_count);
+            println!("Reading time: about {} min", reading_minutes(&app.document));
+            if let Some(protection) = &app.document.metadata.protection {
+                println!("⚠ Protected: {}", protection.summary());
+            }
+            if !app.load_losses.is_empty() {
+                println!("⚠ Not shown as written: {}", app.load_losses.join(", "));
+            }
             println!();
             println!("Content Preview:");
             println!("================");
 
-            // Show first few elements with proper formatting
-            let preview_count = std::cmp::min(app.document.elements.len(), 20);
-            for element in &app.document.elements[0..preview_count] {
+            // Show a few elements with proper formatting, starting from --goto/--page
+            let start = std::cmp::min(app.scroll_offset, app.document.elements.len());
+            let end = std::cmp::min(start + 20, app.document.elements.len());
+            for element in &app.document.elements[start..end] {
                 match element {
                     DocumentElement::Heading {
                         level,
@@ -325,12 +2196,18 @@ async fn run_non_interactive(document: Document, cli: &Cli) -> Result<()> {
                         } else {
                             text.clone()
                         };
-                        println!("{prefix}{heading_text}");
+                        match app.color_enabled {
+                            true => println!(
+                                "{}{prefix}{heading_text}{}",
+                                SetAttribute(Attribute::Bold),
+                                SetAttribute(Attribute::Reset)
+                            ),
+                            false => println!("{prefix}{heading_text}"),
+                        }
                         println!();
                     }
-                    DocumentElement::Paragraph { runs } => {
-                        let text: String = runs.iter().map(|run| run.text.as_str()).collect();
-                        println!("{text}");
+                    DocumentElement::Paragraph { runs, .. } => {
+                        println!("{}", preview_runs(runs));
                         println!();
                     }
                     DocumentElement::List { items, ordered } => {
@@ -341,12 +2218,21 @@ async fn run_non_interactive(document: Document, cli: &Cli) -> Result<()> {
                                 "• ".to_string()
                             };
                             let indent = "  ".repeat(item.level as usize);
-                            let item_text: String =
-                                item.runs.iter().map(|run| run.text.as_str()).collect();
+                            let item_text = preview_runs(&item.runs);
                             println!("{indent}{bullet}{item_text}");
                         }
                         println!();
                     }
+                    DocumentElement::BlockQuote { runs } => {
+                        println!("│ {}", preview_runs(runs));
+                        println!();
+                    }
+                    DocumentElement::CodeBlock { code } => {
+                        for line in code.lines() {
+                            println!("    {line}");
+                        }
+                        println!();
+                    }
                     DocumentElement::Table { .. } => {
                         println!("[Table content - use --export csv to view]");
                         println!();
@@ -354,14 +2240,13 @@ async fn run_non_interactive(document: Document, cli: &Cli) -> Result<()> {
                     DocumentElement::Image {
                         description,
                         image_path,
+                        placement,
                         ..
                     } => {
                         if let Some(path) = image_path {
                             // Try to display the image inline using terminal protocols
-                            match crate::terminal_image::TerminalImageRenderer::with_options(
-                                app.document.image_options.max_width,
-                                app.document.image_options.max_height,
-                                app.document.image_options.scale,
+                            match crate::terminal_image::TerminalImageRenderer::for_image_options(
+                                &app.document.image_options,
                             )
                             .render_image_from_path(path, description)
                             {
@@ -379,22 +2264,34 @@ async fn run_non_interactive(document: Document, cli: &Cli) -> Result<()> {
                             println!("📷 [Image: {description}]");
                             println!();
                         }
+                        if let Some(note) = placement.as_ref().and_then(ImagePlacement::describe) {
+                            println!("({note})");
+                            println!();
+                        }
                     }
                     DocumentElement::Equation { latex, .. } => {
                         println!("📐 Equation: {latex}");
                         println!();
                     }
-                    DocumentElement::PageBreak => {
+                    DocumentElement::PageBreak { page } => {
+                        println!("--- page {page} ---");
+                        println!();
+                    }
+                    DocumentElement::HorizontalRule => {
                         println!("---");
                         println!();
                     }
+                    DocumentElement::FileBoundary { name } => {
+                        println!("=== {name} ===");
+                        println!();
+                    }
                 }
             }
 
-            if app.document.elements.len() > preview_count {
+            if app.document.elements.len() > end {
                 println!(
                     "... and {} more elements",
-                    app.document.elements.len() - preview_count
+                    app.document.elements.len() - end
                 );
                 println!();
             }
@@ -421,7 +2318,15 @@ fn save_app_state(app: &App) {
     let state = DocumentState {
         scroll_offset: app.scroll_offset,
         last_search: app.search_query.clone(),
-        view_mode: app.current_view.clone(),
+        view_mode: match app.current_view {
+            // Prompts and presenter mode are transient; reopen in the document
+            ViewMode::HeadingJump
+            | ViewMode::Present
+            | ViewMode::Annotate
+            | ViewMode::Palette
+            | ViewMode::Table => ViewMode::Document,
+            ref view => view.clone(),
+        },
         last_accessed: std::time::SystemTime::now(),
     };
 
@@ -472,109 +2377,263 @@ pub async fn run_viewer(document: Document, cli: &Cli) -> Result<()> {
 }
 
 async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+    let mut redraw = true;
     loop {
-        terminal.draw(|f| ui(f, app))?;
+        if redraw {
+            let started = Instant::now();
+            terminal.draw(|f| ui(f, app))?;
+            app.frame_timer.record(started.elapsed());
+        }
+        redraw = true;
+
+        // While watching, wait for input in short steps and check the file in between.
+        // Only the cells that changed are redrawn, so a reload doesn't flash the screen.
+        // The presenter timer ticks over on the same steps.
+        // Animated GIFs wake the loop in time for their next frame.
+        let ticking = app.timer_running();
+        let next_frame = app.next_frame_in();
+        let wait = next_frame.map_or(WATCH_INTERVAL, |next| next.min(WATCH_INTERVAL));
+        if (app.watch.is_some() || ticking || next_frame.is_some()) && !event::poll(wait)? {
+            let reloaded = app.reload_if_changed();
+            redraw = app.advance_animations() || reloaded || ticking;
+            continue;
+        }
 
         match event::read()? {
-            Event::Key(key) => {
-                if key.kind == KeyEventKind::Press {
-                    // Clear status message on any key press (except the copy key)
-                    if app.status_message.is_some()
-                        && key.code != KeyCode::Char('c')
-                        && key.code != KeyCode::F(2)
-                    {
-                        app.clear_status_message();
-                    }
-                    match app.current_view {
-                        ViewMode::Document => match key.code {
-                            KeyCode::Char('q') => break,
-                            KeyCode::Char('o') => app.current_view = ViewMode::Outline,
-                            KeyCode::Char('s') => app.current_view = ViewMode::Search,
-                            KeyCode::Char('S') => app.toggle_search_state(),
-                            KeyCode::Char('c') => app.copy_content(),
-                            KeyCode::Char('h') | KeyCode::F(1) => app.show_help = !app.show_help,
-                            KeyCode::Up | KeyCode::Char('k') => app.scroll_up(),
-                            KeyCode::Down | KeyCode::Char('j') => app.scroll_down(),
-                            KeyCode::PageUp => app.page_up(10),
-                            KeyCode::PageDown => app.page_down(10),
-                            KeyCode::Home => app.scroll_offset = 0,
-                            KeyCode::End => {
-                                app.scroll_offset = app.document.elements.len().saturating_sub(1)
-                            }
-                            KeyCode::Char('n') if !app.search_results.is_empty() => {
-                                app.next_search_result()
-                            }
-                            KeyCode::Char('p') if !app.search_results.is_empty() => {
-                                app.prev_search_result()
-                            }
-                            _ => {}
-                        },
-                        ViewMode::Outline => match key.code {
-                            KeyCode::Char('q') | KeyCode::Esc => {
-                                app.current_view = ViewMode::Document
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                // Clear status message on any key press (except the copy key)
+                if app.status_message.is_some()
+                    && key.code != KeyCode::Char('c')
+                    && key.code != KeyCode::Char('C')
+                    && key.code != KeyCode::Char('y')
+                    && key.code != KeyCode::Char('Y')
+                    && key.code != KeyCode::F(2)
+                {
+                    app.clear_status_message();
+                }
+                let document_view =
+                    matches!(app.current_view, ViewMode::Document) && !app.outline_focus;
+                if document_view && !app.fold_prefix && app.navigation_key(key) {
+                    continue;
+                }
+                if matches!(app.current_view, ViewMode::Document) && app.handle_layout_key(key.code)
+                {
+                    continue;
+                }
+                match app.current_view {
+                    ViewMode::Document if app.outline_focus => match key.code {
+                        KeyCode::Char('q') => break,
+                        KeyCode::Esc => app.outline_focus = false,
+                        KeyCode::Up | KeyCode::Char('k') => app.select_in_outline_pane(false),
+                        KeyCode::Down | KeyCode::Char('j') => app.select_in_outline_pane(true),
+                        KeyCode::Enter => app.open_outline_pane_selection(),
+                        KeyCode::Char('h') | KeyCode::F(1) => app.show_help = !app.show_help,
+                        _ => {}
+                    },
+                    ViewMode::Document => match key.code {
+                        code if app.fold_prefix => app.fold_command(code),
+                        KeyCode::Char('z') => app.fold_prefix = true,
+                        KeyCode::Char('q') => break,
+                        KeyCode::Char('o') => app.current_view = ViewMode::Outline,
+                        KeyCode::Char('s') => app.current_view = ViewMode::Search,
+                        KeyCode::Char('S') => app.toggle_search_state(),
+                        KeyCode::Char('t') => app.open_heading_jump(),
+                        KeyCode::Char('j') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.open_heading_jump()
+                        }
+                        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.open_palette()
+                        }
+                        KeyCode::Char('P') => app.start_presenting(),
+                        KeyCode::Char('T') => app.open_table_mode(),
+                        KeyCode::Char('B') => app.toggle_bookmark(),
+                        KeyCode::Char('a') => app.open_note_prompt(),
+                        KeyCode::Char('c') => app.copy_content(),
+                        KeyCode::Char('C') => app.copy_view_command(),
+                        KeyCode::Char('y') => app.copy_table(),
+                        KeyCode::Char('Y') => app.cycle_table_copy_format(),
+                        KeyCode::Char('v') => app.cycle_charts(),
+                        KeyCode::Char('h') | KeyCode::F(1) => app.show_help = !app.show_help,
+                        KeyCode::Char('D') => app.show_debug = !app.show_debug,
+                        KeyCode::Char('X') => app.toggle_inspector(),
+                        KeyCode::Esc => app.load_losses.clear(),
+                        _ => {}
+                    },
+                    ViewMode::Outline => match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => app.current_view = ViewMode::Document,
+                        KeyCode::Char('c') => app.copy_content(),
+                        KeyCode::Char('C') => app.copy_view_command(),
+                        KeyCode::Up | KeyCode::Char('k') => {
+                            let selected = app.outline_state.selected().unwrap_or(0);
+                            if selected > 0 {
+                                app.outline_state.select(Some(selected - 1));
                             }
-                            KeyCode::Char('c') => app.copy_content(),
-                            KeyCode::Up | KeyCode::Char('k') => {
-                                let selected = app.outline_state.selected().unwrap_or(0);
-                                if selected > 0 {
-                                    app.outline_state.select(Some(selected - 1));
-                                }
+                        }
+                        KeyCode::Down | KeyCode::Char('j') => {
+                            let selected = app.outline_state.selected().unwrap_or(0);
+                            if selected + 1 < outline_view(&app.document).len() {
+                                app.outline_state.select(Some(selected + 1));
                             }
-                            KeyCode::Down | KeyCode::Char('j') => {
-                                let selected = app.outline_state.selected().unwrap_or(0);
-                                if selected + 1
-                                    < crate::document::generate_outline(&app.document).len()
+                        }
+                        KeyCode::Enter => {
+                            if let Some(selected) = app.outline_state.selected() {
+                                if let Some(outline_item) =
+                                    outline_view(&app.document).get(selected)
                                 {
-                                    app.outline_state.select(Some(selected + 1));
+                                    app.jump_to_element(outline_item.element_index);
+                                    app.current_view = ViewMode::Document;
                                 }
                             }
-                            KeyCode::Enter => {
-                                if let Some(selected) = app.outline_state.selected() {
-                                    if let Some(outline_item) =
-                                        crate::document::generate_outline(&app.document)
-                                            .get(selected)
-                                    {
-                                        app.scroll_offset = outline_item.element_index;
-                                        app.current_view = ViewMode::Document;
-                                    }
+                        }
+                        _ => {}
+                    },
+                    ViewMode::Search => match key.code {
+                        KeyCode::Esc => app.current_view = ViewMode::Document,
+                        KeyCode::F(2) => app.copy_content(), // Use F2 for copy in search mode to avoid conflicts
+                        KeyCode::Char(c) => {
+                            app.search_query.push(c);
+                            app.search_results =
+                                crate::document::search_document(&app.document, &app.search_query);
+                            app.current_search_index = 0;
+                        }
+                        KeyCode::Backspace => {
+                            app.search_query.pop();
+                            app.search_results =
+                                crate::document::search_document(&app.document, &app.search_query);
+                            app.current_search_index = 0;
+                        }
+                        KeyCode::Enter | KeyCode::Down => app.next_search_result(),
+                        KeyCode::Up => app.prev_search_result(),
+                        _ => {}
+                    },
+                    ViewMode::HeadingJump => match key.code {
+                        KeyCode::Esc => app.current_view = ViewMode::Document,
+                        KeyCode::Enter => app.jump_to_selected_heading(),
+                        KeyCode::Down => app.next_heading_match(),
+                        KeyCode::Up => app.prev_heading_match(),
+                        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.next_heading_match()
+                        }
+                        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.prev_heading_match()
+                        }
+                        KeyCode::Char(c) => {
+                            app.heading_query.push(c);
+                            app.update_heading_matches();
+                        }
+                        KeyCode::Backspace => {
+                            app.heading_query.pop();
+                            app.update_heading_matches();
+                        }
+                        _ => {}
+                    },
+                    ViewMode::Table
+                        if app
+                            .table_mode
+                            .as_ref()
+                            .is_some_and(|mode| mode.filter_input.is_some()) =>
+                    {
+                        let input = app
+                            .table_mode
+                            .as_mut()
+                            .and_then(|mode| mode.filter_input.as_mut());
+                        match (key.code, input) {
+                            (KeyCode::Esc, _) => {
+                                if let Some(mode) = &mut app.table_mode {
+                                    mode.filter_input = None;
                                 }
                             }
-                            _ => {}
-                        },
-                        ViewMode::Search => match key.code {
-                            KeyCode::Esc => app.current_view = ViewMode::Document,
-                            KeyCode::F(2) => app.copy_content(), // Use F2 for copy in search mode to avoid conflicts
-                            KeyCode::Char(c) => {
-                                app.search_query.push(c);
-                                app.search_results = crate::document::search_document(
-                                    &app.document,
-                                    &app.search_query,
-                                );
-                                app.current_search_index = 0;
-                            }
-                            KeyCode::Backspace => {
-                                app.search_query.pop();
-                                app.search_results = crate::document::search_document(
-                                    &app.document,
-                                    &app.search_query,
-                                );
-                                app.current_search_index = 0;
+                            (KeyCode::Enter, _) => app.apply_table_filter(),
+                            (KeyCode::Char(c), Some(input)) => input.push(c),
+                            (KeyCode::Backspace, Some(input)) => {
+                                input.pop();
                             }
-                            KeyCode::Enter | KeyCode::Down => app.next_search_result(),
-                            KeyCode::Up => app.prev_search_result(),
                             _ => {}
-                        },
-                        ViewMode::Help => match key.code {
-                            KeyCode::Char('q')
-                            | KeyCode::Esc
-                            | KeyCode::Char('h')
-                            | KeyCode::F(1) => {
-                                app.show_help = false;
-                                app.current_view = ViewMode::Document;
-                            }
-                            _ => {}
-                        },
+                        }
                     }
+                    ViewMode::Table => match key.code {
+                        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('T') => {
+                            app.close_table_mode()
+                        }
+                        KeyCode::Left => app.select_table_column(false),
+                        KeyCode::Right => app.select_table_column(true),
+                        KeyCode::Char('s') => app.cycle_table_sort(),
+                        KeyCode::Char('f') | KeyCode::Char('/') => app.open_table_filter(),
+                        KeyCode::Char('x') => app.clear_table_filter(),
+                        KeyCode::Up | KeyCode::Char('k') => app.scroll_table(-1),
+                        KeyCode::Down | KeyCode::Char('j') => app.scroll_table(1),
+                        KeyCode::PageUp => app.scroll_table(-(app.table_page() as isize)),
+                        KeyCode::PageDown => app.scroll_table(app.table_page() as isize),
+                        KeyCode::Home => app.scroll_table(isize::MIN),
+                        KeyCode::End => app.scroll_table(isize::MAX),
+                        KeyCode::Char('y') => app.copy_table_view(),
+                        KeyCode::Char('Y') => app.cycle_table_copy_format(),
+                        KeyCode::Char('w') => app.save_table_view(),
+                        KeyCode::Char('h') | KeyCode::F(1) => app.show_help = !app.show_help,
+                        _ => {}
+                    },
+                    ViewMode::Palette => match key.code {
+                        KeyCode::Esc => app.current_view = ViewMode::Document,
+                        KeyCode::Enter if app.run_palette_selection() => break,
+                        KeyCode::Down => app.next_palette_match(),
+                        KeyCode::Up => app.prev_palette_match(),
+                        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.next_palette_match()
+                        }
+                        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.prev_palette_match()
+                        }
+                        KeyCode::Char(c) => {
+                            app.palette_query.push(c);
+                            app.update_palette_matches();
+                        }
+                        KeyCode::Backspace => {
+                            app.palette_query.pop();
+                            app.update_palette_matches();
+                        }
+                        _ => {}
+                    },
+                    ViewMode::Help => match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc | KeyCode::Char('h') | KeyCode::F(1) => {
+                            app.show_help = false;
+                            app.current_view = ViewMode::Document;
+                        }
+                        _ => {}
+                    },
+                    ViewMode::Annotate => match key.code {
+                        KeyCode::Esc => app.current_view = ViewMode::Document,
+                        KeyCode::Enter => app.save_note(),
+                        KeyCode::Char(c) => app.note_input.push(c),
+                        KeyCode::Backspace => {
+                            app.note_input.pop();
+                        }
+                        _ => {}
+                    },
+                    ViewMode::Present => match key.code {
+                        KeyCode::Char('q') => {
+                            app.stop_presenting();
+                            break;
+                        }
+                        KeyCode::Esc | KeyCode::Char('P') => app.stop_presenting(),
+                        KeyCode::Char(' ')
+                        | KeyCode::Right
+                        | KeyCode::Down
+                        | KeyCode::PageDown
+                        | KeyCode::Enter
+                        | KeyCode::Char('n') => app.next_slide(),
+                        KeyCode::Left
+                        | KeyCode::Up
+                        | KeyCode::PageUp
+                        | KeyCode::Backspace
+                        | KeyCode::Char('p') => app.prev_slide(),
+                        KeyCode::Char('j') => app.scroll_slide(true),
+                        KeyCode::Char('k') => app.scroll_slide(false),
+                        KeyCode::Home => app.first_slide(),
+                        KeyCode::End => app.last_slide(),
+                        KeyCode::Char('t') => app.toggle_timer(),
+                        KeyCode::Char('h') | KeyCode::F(1) => app.show_help = !app.show_help,
+                        _ => {}
+                    },
                 }
             }
             Event::Mouse(mouse) => {
@@ -594,6 +2653,10 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Resul
                                 }
                             }
                             ViewMode::Search => app.prev_search_result(),
+                            ViewMode::HeadingJump => app.prev_heading_match(),
+                            ViewMode::Palette => app.prev_palette_match(),
+                            ViewMode::Table => app.scroll_table(-3),
+                            ViewMode::Present => app.scroll_slide(false),
                             _ => {}
                         }
                     }
@@ -607,13 +2670,15 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Resul
                             }
                             ViewMode::Outline => {
                                 let selected = app.outline_state.selected().unwrap_or(0);
-                                if selected + 1
-                                    < crate::document::generate_outline(&app.document).len()
-                                {
+                                if selected + 1 < outline_view(&app.document).len() {
                                     app.outline_state.select(Some(selected + 1));
                                 }
                             }
                             ViewMode::Search => app.next_search_result(),
+                            ViewMode::HeadingJump => app.next_heading_match(),
+                            ViewMode::Palette => app.next_palette_match(),
+                            ViewMode::Table => app.scroll_table(3),
+                            ViewMode::Present => app.scroll_slide(true),
                             _ => {}
                         }
                     }
@@ -628,6 +2693,15 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Resul
 }
 
 fn ui(f: &mut Frame, app: &mut App) {
+    // Presenter mode has the whole screen, with a footer of its own
+    if matches!(app.current_view, ViewMode::Present) {
+        render_presenter(f, f.area(), app);
+        if app.show_help {
+            render_help_overlay(f, app);
+        }
+        return;
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
@@ -635,15 +2709,41 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     // Main content area
     match app.current_view {
-        ViewMode::Document => render_document(f, chunks[0], app),
+        ViewMode::Document => render_panes(f, chunks[0], app),
         ViewMode::Outline => render_outline(f, chunks[0], app),
         ViewMode::Search => render_search(f, chunks[0], app),
+        ViewMode::HeadingJump => {
+            render_panes(f, chunks[0], app);
+            render_heading_jump(f, chunks[0], app);
+        }
         ViewMode::Help => render_help(f, chunks[0]),
+        ViewMode::Present => {}
+        ViewMode::Annotate => {
+            render_panes(f, chunks[0], app);
+            render_note_prompt(f, chunks[0], app);
+        }
+        ViewMode::Palette => {
+            render_panes(f, chunks[0], app);
+            render_palette(f, chunks[0], app);
+        }
+        ViewMode::Table => render_table_mode(f, chunks[0], app),
+    }
+
+    if matches!(app.current_view, ViewMode::Document) && !app.load_losses.is_empty() {
+        render_losses_banner(f, chunks[0], app);
     }
 
     // Status bar
     render_status_bar(f, chunks[1], app);
 
+    if app.show_inspector && matches!(app.current_view, ViewMode::Document) {
+        render_inspector(f, chunks[0], app);
+    }
+
+    if app.show_debug {
+        render_debug_overlay(f, chunks[0], app);
+    }
+
     // Help overlay
     if app.show_help {
         render_help_overlay(f, app);
@@ -659,58 +2759,455 @@ fn render_document(f: &mut Frame, area: Rect, app: &mut App) {
 
     let inner = block.inner(area);
     f.render_widget(block, area);
+    app.viewport_height = inner.height as usize;
 
     // Use DocumentWidget for unified rendering with proper text wrapping + images
     let mut doc_widget = DocumentWidget::new(&app.document.elements[..])
         .scroll_offset(app.scroll_offset)
+        .scroll_line_offset(app.scroll_line_offset)
         .color_enabled(app.color_enabled)
         .search_results(&app.search_results[..])
-        .current_search_index(app.current_search_index);
+        .current_search_index(app.current_search_index)
+        .sections(&app.document.sections)
+        .hidden(&app.hidden)
+        .charts(app.charts, app.document.cell_locale())
+        .misspellings(&app.misspellings);
 
-    // Render the document content (text + images in single pass)
-    doc_widget.render(inner, f, &mut app.image_protocols, &mut app.layout_cache);
+    // Render the document content (text + images in single pass); with images
+    // hidden from the palette, none have a protocol to draw with
+    let mut hidden = ImageProtocols::new();
+    let protocols = if app.show_images {
+        &mut app.image_protocols
+    } else {
+        &mut hidden
+    };
+    doc_widget.render(inner, f, protocols, &mut app.layout_cache);
     let scrollbar = Scrollbar::default()
         .orientation(ScrollbarOrientation::VerticalRight)
         .begin_symbol(Some("↑"))
         .end_symbol(Some("↓"));
 
-    let mut scrollbar_state = ScrollbarState::default()
-        .content_length(app.document.elements.len())
-        .position(app.scroll_offset);
-
-    f.render_stateful_widget(
-        scrollbar,
-        area.inner(Margin {
-            vertical: 1,
-            horizontal: 0,
-        }),
-        &mut scrollbar_state,
+    // Scroll by rows once the widget has laid the document out, by elements before that
+    let mut scrollbar_state = match app.current_line() {
+        Some(line) => ScrollbarState::default()
+            .content_length(app.layout_cache.total_lines())
+            .position(line),
+        None => ScrollbarState::default()
+            .content_length(app.document.elements.len())
+            .position(app.scroll_offset),
+    };
+
+    let track = area.inner(Margin {
+        vertical: 1,
+        horizontal: 0,
+    });
+    f.render_stateful_widget(scrollbar, track, &mut scrollbar_state);
+    render_bookmark_markers(f, track, app);
+}
+
+/// Mark where the bookmarks are on the scrollbar
+fn render_bookmark_markers(f: &mut Frame, track: Rect, app: &App) {
+    let Ok(notes) = &app.notes else {
+        return;
+    };
+    // Rows between the arrows at either end
+    let rows = track.height.saturating_sub(2) as usize;
+    if rows == 0 || track.width == 0 {
+        return;
+    }
+    let laid_out = app.layout_cache.has_layout(app.document.elements.len());
+    let length = if laid_out {
+        app.layout_cache.total_lines()
+    } else {
+        app.document.elements.len()
+    };
+    let style = Style::default().fg(Color::Yellow);
+    for annotation in notes.annotations() {
+        let position = if laid_out {
+            app.layout_cache.line_of_element(annotation.element)
+        } else {
+            annotation.element
+        };
+        let row = position * rows / length.max(1);
+        f.buffer_mut().set_string(
+            track.right() - 1,
+            track.y + 1 + row.min(rows - 1) as u16,
+            "◆",
+            style,
+        );
+    }
+}
+
+/// One section, centred, under a large title, with a footer for position and time
+fn render_presenter(f: &mut Frame, area: Rect, app: &mut App) {
+    let Some(presenter) = &mut app.presenter else {
+        return;
+    };
+    let elements = &app.document.elements;
+    let slide = presenter.slide().clone();
+
+    let [content, footer] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(2)])
+        .areas(area);
+    let width = content.width.min(SLIDE_WIDTH);
+    let column = Rect {
+        x: content.x + (content.width - width) / 2,
+        y: content.y + content.height / 8,
+        width,
+        height: content.height - content.height / 8,
+    };
+
+    let title_style = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let title = slide
+        .heading
+        .and_then(|index| match &elements[index] {
+            DocumentElement::Heading { text, number, .. } => Some(match number {
+                Some(number) => format!("{number} {text}"),
+                None => text.clone(),
+            }),
+            _ => None,
+        })
+        .unwrap_or_else(|| app.document.title.clone());
+
+    // The banner font if the title has room in it, plain bold text otherwise
+    let banner_lines = banner(&title)
+        .filter(|rows| rows[0].width() <= column.width as usize)
+        .map(|rows| rows.map(Line::from).to_vec());
+    let title_lines = banner_lines.unwrap_or_else(|| vec![Line::from(title)]);
+    let title_height = (title_lines.len().max(BANNER_HEIGHT) as u16 + 1).min(column.height);
+    f.render_widget(
+        Paragraph::new(title_lines)
+            .style(title_style)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        Rect {
+            height: title_height,
+            ..column
+        },
+    );
+
+    if !slide.body.is_empty() {
+        let body = Rect {
+            y: column.y + title_height,
+            height: column.height - title_height,
+            ..column
+        };
+        let mut widget = DocumentWidget::new(&elements[..slide.body.end])
+            .scroll_offset(slide.body.start + presenter.scroll)
+            .color_enabled(app.color_enabled)
+            .charts(app.charts, app.document.cell_locale())
+            .misspellings(&app.misspellings);
+        let mut hidden = ImageProtocols::new();
+        let protocols = if app.show_images {
+            &mut app.image_protocols
+        } else {
+            &mut hidden
+        };
+        widget.render(body, f, protocols, &mut presenter.layout_cache);
+    }
+
+    let position = format!(" {} / {} ", presenter.current + 1, presenter.slides.len());
+    let (clock, overtime) = match presenter.timer_started {
+        Some(started) => {
+            let elapsed = started.elapsed();
+            match app.talk_length {
+                Some(length) => (
+                    format!("{} / {} ", format_clock(elapsed), format_clock(length)),
+                    elapsed > length,
+                ),
+                None => (format!("{} ", format_clock(elapsed)), false),
+            }
+        }
+        None => (String::new(), false),
+    };
+    let clock_style = if overtime {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    let name_width = (footer.width as usize).saturating_sub(position.width() + clock.width());
+    let name = format!("{:<name_width$}", app.document.title);
+    let status = Line::from(vec![
+        Span::styled(position, Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(name.chars().take(name_width).collect::<String>()),
+        Span::styled(clock, clock_style),
+    ]);
+    let help = "[Space/→] Next [←] Back [j/k] Scroll [t] Timer [Esc] Document [q] Quit";
+    f.render_widget(
+        Paragraph::new(vec![
+            status,
+            Line::styled(help, Style::default().fg(Color::Gray)),
+        ])
+        .style(Style::default().fg(Color::White).bg(Color::DarkGray)),
+        footer,
     );
 }
 
+/// A duration as `m:ss`, or `h:mm:ss` from an hour up
+fn format_clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
 fn render_outline(f: &mut Frame, area: Rect, app: &mut App) {
     let outline = crate::document::generate_outline(&app.document);
-    let items: Vec<ListItem> = outline
+    let captions = crate::document::caption_outline(&app.document);
+    let items = outline_items(&outline, app)
+        .into_iter()
+        .chain(captions.iter().map(|item| {
+            let indent = "  ".repeat((item.level.saturating_sub(1)) as usize);
+            ListItem::new(format!("{}{}", indent, item.title))
+        }));
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title("📋 Document Outline")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Green)),
+        )
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+        .highlight_symbol("➤ ");
+
+    f.render_stateful_widget(list, area, &mut app.outline_state);
+}
+
+/// The outline view's entries: the headings, then the lists of tables and figures
+fn outline_view(document: &Document) -> Vec<OutlineItem> {
+    let mut outline = crate::document::generate_outline(document);
+    outline.extend(crate::document::caption_outline(document));
+    outline
+}
+
+/// Outline entries, indented by level, with the number of bookmarks under each
+fn outline_items(outline: &[OutlineItem], app: &App) -> Vec<ListItem<'static>> {
+    let annotations = app
+        .notes
+        .as_ref()
+        .map_or(&[][..], |notes| notes.annotations());
+    outline
         .iter()
-        .map(|item| {
+        .enumerate()
+        .map(|(i, item)| {
             let indent = "  ".repeat((item.level.saturating_sub(1)) as usize);
             let text = format!("{}{}", indent, item.title);
-            ListItem::new(text)
+
+            // Bookmarks from this heading up to the next one
+            let end = outline
+                .get(i + 1)
+                .map_or(usize::MAX, |next| next.element_index);
+            let bookmarks = annotations
+                .iter()
+                .filter(|a| (item.element_index..end).contains(&a.element))
+                .count();
+            match bookmarks {
+                0 => ListItem::new(text),
+                1 => ListItem::new(Line::from(vec![
+                    Span::raw(text),
+                    Span::styled(" 🔖", Style::default().fg(Color::Yellow)),
+                ])),
+                n => ListItem::new(Line::from(vec![
+                    Span::raw(text),
+                    Span::styled(format!(" 🔖{n}"), Style::default().fg(Color::Yellow)),
+                ])),
+            }
         })
-        .collect();
+        .collect()
+}
+
+/// The outline entry for the section containing an element
+fn current_outline_item(outline: &[OutlineItem], element: usize) -> Option<usize> {
+    outline
+        .iter()
+        .rposition(|item| item.element_index <= element)
+        .or((!outline.is_empty()).then_some(0))
+}
+
+/// The document between the outline and preview panes, whichever are shown
+fn render_panes(f: &mut Frame, area: Rect, app: &mut App) {
+    let layout = app.layout;
+    let mut constraints = Vec::new();
+    if layout.outline {
+        constraints.push(Constraint::Percentage(layout.outline_width));
+    }
+    constraints.push(Constraint::Min(20));
+    if layout.preview {
+        constraints.push(Constraint::Percentage(layout.preview_width));
+    }
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(constraints)
+        .split(area);
+
+    let content = usize::from(layout.outline);
+    if layout.outline {
+        render_outline_pane(f, panes[0], app);
+    }
+    render_document(f, panes[content], app);
+    if layout.preview {
+        render_preview_pane(f, panes[content + 1], app);
+    }
+}
+
+/// The outline beside the document, following the section in view unless it has focus
+fn render_outline_pane(f: &mut Frame, area: Rect, app: &mut App) {
+    let outline = crate::document::generate_outline(&app.document);
+    let (border, highlight) = if app.outline_focus {
+        (
+            Color::Green,
+            Style::default().bg(Color::Blue).fg(Color::White),
+        )
+    } else {
+        app.outline_pane_state
+            .select(current_outline_item(&outline, app.scroll_offset));
+        (
+            Color::DarkGray,
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+    };
 
-    let list = List::new(items)
+    let list = List::new(outline_items(&outline, app))
         .block(
             Block::default()
-                .title("📋 Document Outline")
+                .title("📋 Outline")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Green)),
+                .border_style(Style::default().fg(border)),
         )
-        .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+        .highlight_style(highlight)
         .highlight_symbol("➤ ");
+    f.render_stateful_widget(list, area, &mut app.outline_pane_state);
+}
 
-    f.render_stateful_widget(list, area, &mut app.outline_state);
+/// Document details, the section in view and the first image on screen
+fn render_preview_pane(f: &mut Frame, area: Rect, app: &mut App) {
+    let block = Block::default()
+        .title("📇 Details")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let label = Style::default().fg(Color::Gray);
+    let field = |name: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{name}: "), label),
+            Span::raw(value),
+        ])
+    };
+    let metadata = &app.document.metadata;
+    let mut lines = vec![
+        Line::styled(
+            app.document.title.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Line::default(),
+    ];
+    if let Some(author) = &metadata.author {
+        lines.push(field("Author", author.clone()));
+    }
+    if let Some(created) = &metadata.created {
+        lines.push(field("Created", created.clone()));
+    }
+    if let Some(modified) = &metadata.modified {
+        lines.push(field("Modified", modified.clone()));
+    }
+    if let Some(language) = &metadata.language {
+        lines.push(field("Language", language.tag.clone()));
+    }
+    if let Some(protection) = &metadata.protection {
+        lines.push(Line::from(vec![
+            Span::styled("Protection: ", label),
+            Span::styled(protection.summary(), Style::default().fg(Color::Yellow)),
+        ]));
+    }
+    lines.push(field("Pages", metadata.page_count.to_string()));
+    lines.push(field("Words", metadata.word_count.to_string()));
+    lines.push(field(
+        "Reading",
+        format!("about {} min", reading_minutes(&app.document)),
+    ));
+    lines.push(field(
+        "Size",
+        format!("{:.1} KB", metadata.file_size as f64 / 1024.0),
+    ));
+
+    let outline = crate::document::generate_outline(&app.document);
+    if let Some(item) = current_outline_item(&outline, app.scroll_offset)
+        .map(|index| &outline[index])
+        .filter(|item| item.element_index <= app.scroll_offset)
+    {
+        lines.push(Line::default());
+        lines.push(field("Section", item.title.clone()));
+    }
+
+    let image = app.image_in_view();
+    if let Some(DocumentElement::Image {
+        description,
+        placement,
+        ..
+    }) = image.map(|index| &app.document.elements[index])
+    {
+        lines.push(Line::default());
+        lines.push(Line::styled(
+            format!("🖼 {description}"),
+            Style::default().fg(Color::Cyan),
+        ));
+        if let Some(note) = placement.as_ref().and_then(ImagePlacement::describe) {
+            lines.push(Line::styled(note, Style::default().fg(Color::DarkGray)));
+        }
+    }
+
+    let text_height = (lines.len() as u16).min(inner.height);
+    f.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: true }),
+        Rect {
+            height: text_height,
+            ..inner
+        },
+    );
+
+    // The picture itself, below the details, when images are on
+    let Some(index) = image else {
+        return;
+    };
+    let image_area = Rect {
+        y: inner.y + text_height + 1,
+        height: inner.height.saturating_sub(text_height + 1),
+        ..inner
+    };
+    if image_area.height < 3 {
+        return;
+    }
+    if app.preview_image.as_ref().map(|(shown, _)| *shown) != Some(index) {
+        app.preview_image = preview_protocol(app, index).map(|protocol| (index, protocol));
+    }
+    if let Some((_, protocol)) = &mut app.preview_image {
+        f.render_stateful_widget(StatefulImage::new(), image_area, protocol);
+    }
+}
+
+/// Decode an image element for the preview pane
+fn preview_protocol(app: &App, index: usize) -> Option<StatefulProtocol> {
+    let picker = app.image_picker.as_ref()?;
+    let DocumentElement::Image {
+        image_path: Some(path),
+        ..
+    } = &app.document.elements[index]
+    else {
+        return None;
+    };
+    let image = image::ImageReader::open(path).ok()?.decode().ok()?;
+    Some(picker.new_resize_protocol(image))
 }
 
 fn render_search(f: &mut Frame, area: Rect, app: &App) {
@@ -784,6 +3281,434 @@ fn render_search(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(results_list, chunks[1]);
 }
 
+fn render_heading_jump(f: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(60, 60, area);
+    f.render_widget(Clear, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(popup);
+
+    let input = Paragraph::new(app.heading_query.as_str())
+        .style(Style::default().fg(Color::Cyan))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("🧭 Go to Heading")
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+    f.render_widget(input, chunks[0]);
+
+    let match_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let items: Vec<ListItem> = app
+        .heading_matches
+        .iter()
+        .map(|heading| {
+            let indent = "  ".repeat((heading.item.level.saturating_sub(1)) as usize);
+            let mut spans = vec![Span::raw(indent)];
+            spans.extend(heading.item.title.chars().enumerate().map(|(i, c)| {
+                if heading.positions.contains(&i) {
+                    Span::styled(c.to_string(), match_style)
+                } else {
+                    Span::raw(c.to_string())
+                }
+            }));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Headings ({}/{})",
+                    app.heading_matches.len(),
+                    crate::document::generate_outline(&app.document).len()
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+        .highlight_symbol("➤ ");
+
+    let mut state = ListState::default();
+    if !app.heading_matches.is_empty() {
+        state.select(Some(app.heading_selected));
+    }
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+/// Table mode: the table full screen, with its sort and filter
+fn render_table_mode(f: &mut Frame, area: Rect, app: &mut App) {
+    let (Some(table), Some(mode)) = (
+        match app
+            .table_mode
+            .as_ref()
+            .map(|mode| &app.document.elements[mode.element])
+        {
+            Some(DocumentElement::Table { table }) => Some(table),
+            _ => None,
+        },
+        app.table_mode.as_mut(),
+    ) else {
+        return;
+    };
+
+    let header_name = |column: usize| table.headers[column].content.trim().to_string();
+    let mut title = format!(
+        "📊 Table {} • {} of {} rows",
+        mode.number,
+        mode.rows.len(),
+        table.rows.len()
+    );
+    if let Some((column, descending)) = mode.sort {
+        let arrow = if descending { "▼" } else { "▲" };
+        title.push_str(&format!(" • sorted by {} {arrow}", header_name(column)));
+    }
+    if let Some(filter) = &mode.filter {
+        title.push_str(&format!(
+            " • {} {} {}",
+            header_name(filter.column),
+            filter.op.symbol(),
+            filter.value
+        ));
+    }
+    let block = Block::default()
+        .title(title)
+        .title_bottom(" ←/→ column • s sort • f filter • x clear • y copy • w save CSV • Esc back ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Blue));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // Columns as wide as their contents (up to a limit), scrolled sideways to
+    // keep the selected one on screen
+    const MAX_COLUMN_WIDTH: usize = 40;
+    let widths: Vec<u16> = (0..table.headers.len())
+        .map(|column| {
+            std::iter::once(&table.headers)
+                .chain(&table.rows)
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.content.width())
+                .max()
+                .unwrap_or(0)
+                .clamp(3, MAX_COLUMN_WIDTH) as u16
+                + 2 // room for the sort arrow
+        })
+        .collect();
+    mode.first_column = mode.first_column.min(mode.column);
+    while mode.first_column < mode.column
+        && widths[mode.first_column..=mode.column]
+            .iter()
+            .map(|width| width + 1)
+            .sum::<u16>()
+            > inner.width
+    {
+        mode.first_column += 1;
+    }
+    let columns = mode.first_column..widths.len();
+
+    let selected = Style::default()
+        .fg(Color::Black)
+        .bg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let header = Row::new(columns.clone().map(|column| {
+        let arrow = match mode.sort {
+            Some((sorted, descending)) if sorted == column => {
+                if descending {
+                    " ▼"
+                } else {
+                    " ▲"
+                }
+            }
+            _ => "",
+        };
+        let cell = Cell::from(format!("{}{arrow}", header_name(column)));
+        if column == mode.column {
+            cell.style(selected)
+        } else {
+            cell
+        }
+    }))
+    .style(Style::default().add_modifier(Modifier::BOLD))
+    .bottom_margin(1);
+
+    mode.page = inner.height.saturating_sub(2) as usize;
+    let rows = mode
+        .rows
+        .iter()
+        .skip(mode.scroll)
+        .take(mode.page)
+        .map(|&row| {
+            Row::new(columns.clone().map(|column| {
+                let cell = table.rows[row].get(column);
+                let text = cell.map(|cell| cell.content.as_str()).unwrap_or_default();
+                let alignment = match table.metadata.column_alignments.get(column) {
+                    Some(TextAlignment::Right) => Alignment::Right,
+                    Some(TextAlignment::Center) => Alignment::Center,
+                    _ => Alignment::Left,
+                };
+                Cell::from(Line::from(text.to_string()).alignment(alignment))
+            }))
+        })
+        .collect::<Vec<_>>();
+
+    let widget = Table::new(
+        rows,
+        columns.map(|column| Constraint::Length(widths[column])),
+    )
+    .header(header)
+    .column_spacing(1);
+    f.render_widget(widget, inner);
+
+    if let Some(input) = &mode.filter_input {
+        let width = area.width.saturating_sub(4).min(80);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.bottom().saturating_sub(5).max(area.y),
+            width,
+            height: 3.min(area.height),
+        };
+        f.render_widget(Clear, popup);
+        let prompt = Paragraph::new(format!("{input}▏"))
+            .style(Style::default().fg(Color::Yellow))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("🔎 Filter rows")
+                    .title_bottom(" contains ~ = != < <= > >= • Enter apply • Esc cancel ")
+                    .border_style(Style::default().fg(Color::Yellow)),
+            );
+        f.render_widget(prompt, popup);
+    }
+}
+
+/// The command palette: a query box over the entries it matches
+fn render_palette(f: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(60, 60, area);
+    f.render_widget(Clear, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(popup);
+
+    let input = Paragraph::new(app.palette_query.as_str())
+        .style(Style::default().fg(Color::Cyan))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("⚡ Command Palette")
+                .title_bottom(" Enter go • Esc cancel ")
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+    f.render_widget(input, chunks[0]);
+
+    let match_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let items: Vec<ListItem> = app
+        .palette_matches
+        .iter()
+        .map(|m| {
+            let entry = &app.palette_entries[m.entry];
+            let mut spans = vec![Span::raw(format!("{} ", entry.icon))];
+            spans.extend(entry.label.chars().enumerate().map(|(i, c)| {
+                if m.positions.contains(&i) {
+                    Span::styled(c.to_string(), match_style)
+                } else {
+                    Span::raw(c.to_string())
+                }
+            }));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Matches ({}/{})",
+                    app.palette_matches.len(),
+                    app.palette_entries.len()
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+        .highlight_symbol("➤ ");
+
+    let mut state = ListState::default();
+    if !app.palette_matches.is_empty() {
+        state.select(Some(app.palette_selected));
+    }
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+/// Everything the command palette offers: the document's headings, tables and
+/// images in order, then its bookmarks, then the commands
+fn palette_entries(document: &Document, notes: Option<&Notes>) -> Vec<PaletteEntry> {
+    let mut entries: Vec<PaletteEntry> = crate::document::navigation_targets(document)
+        .into_iter()
+        .map(|target| PaletteEntry {
+            icon: match target.kind {
+                TargetKind::Heading => "📑",
+                TargetKind::Table => "📊",
+                TargetKind::Image => "📷",
+            },
+            label: target.title,
+            action: PaletteAction::Jump(target.element_index),
+        })
+        .collect();
+
+    let annotations = notes.map(Notes::annotations).unwrap_or_default();
+    entries.extend(annotations.iter().map(|annotation| {
+        let label = match &annotation.note {
+            Some(note) => note.clone(),
+            None if annotation.excerpt.is_empty() => {
+                format!("Bookmark at element {}", annotation.element + 1)
+            }
+            None => annotation.excerpt.clone(),
+        };
+        PaletteEntry {
+            icon: "🔖",
+            label,
+            action: PaletteAction::Jump(annotation.element),
+        }
+    }));
+
+    let exports = [
+        ("Markdown", ExportFormat::Markdown, "md"),
+        ("text", ExportFormat::Text, "txt"),
+        ("HTML", ExportFormat::Html, "html"),
+        ("JSON", ExportFormat::Json, "json"),
+        ("AsciiDoc", ExportFormat::Asciidoc, "adoc"),
+        ("reStructuredText", ExportFormat::Rst, "rst"),
+        ("CSV", ExportFormat::Csv, "csv"),
+    ];
+    let mut commands: Vec<(String, PaletteCommand)> = exports
+        .into_iter()
+        .map(|(name, format, extension)| {
+            (
+                format!("Export as {name} (.{extension})"),
+                PaletteCommand::Export(format, extension),
+            )
+        })
+        .collect();
+    commands.push((
+        "Export notes as Markdown (.notes.md)".to_string(),
+        PaletteCommand::Export(ExportFormat::Notes, "notes.md"),
+    ));
+    commands.extend(
+        [
+            ("Toggle images", PaletteCommand::ToggleImages),
+            ("Toggle colors", PaletteCommand::ToggleColor),
+            (
+                "Cycle table charts (sparklines, bars, off)",
+                PaletteCommand::CycleCharts,
+            ),
+            (
+                "Toggle spellcheck (underline unknown words)",
+                PaletteCommand::ToggleSpellcheck,
+            ),
+            (
+                "Sort and filter the table in view",
+                PaletteCommand::TableMode,
+            ),
+            ("Toggle outline pane", PaletteCommand::ToggleOutlinePane),
+            ("Toggle details pane", PaletteCommand::TogglePreviewPane),
+            ("Show outline", PaletteCommand::Outline),
+            ("Search", PaletteCommand::Search),
+            ("Present", PaletteCommand::Present),
+            ("Bookmark the top of the screen", PaletteCommand::Bookmark),
+            ("Write a note", PaletteCommand::Note),
+            ("Copy document", PaletteCommand::CopyDocument),
+            (
+                "Copy command for this view",
+                PaletteCommand::CopyViewCommand,
+            ),
+            ("Copy table in view", PaletteCommand::CopyTable),
+            ("Toggle performance overlay", PaletteCommand::Debug),
+            ("Inspect element source (XML)", PaletteCommand::Inspect),
+            ("Help", PaletteCommand::Help),
+            ("Quit", PaletteCommand::Quit),
+        ]
+        .map(|(label, command)| (label.to_string(), command)),
+    );
+    entries.extend(commands.into_iter().map(|(label, command)| PaletteEntry {
+        icon: "⚡",
+        label,
+        action: PaletteAction::Run(command),
+    }));
+    entries
+}
+
+/// The note prompt, along the bottom of the document
+fn render_note_prompt(f: &mut Frame, area: Rect, app: &App) {
+    let width = area.width.saturating_sub(4).min(80);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.bottom().saturating_sub(5).max(area.y),
+        width,
+        height: 3.min(area.height),
+    };
+    f.render_widget(Clear, popup);
+
+    let excerpt = app
+        .document
+        .elements
+        .get(app.scroll_offset)
+        .map(notes::excerpt)
+        .unwrap_or_default();
+    // Keep the end of a long note, where the typing is, in view
+    let room = (width as usize).saturating_sub(3);
+    let mut start = 0;
+    while app.note_input[start..].width() > room {
+        start += app.note_input[start..]
+            .chars()
+            .next()
+            .map_or(1, char::len_utf8);
+    }
+    let input = Paragraph::new(format!("{}▏", &app.note_input[start..]))
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("📝 Note on \"{excerpt}\""))
+                .title_bottom(" Enter save • Esc cancel ")
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+    f.render_widget(input, popup);
+}
+
+/// What the loader couldn't show, across the top of the document
+fn render_losses_banner(f: &mut Frame, area: Rect, app: &App) {
+    let width = area.width.saturating_sub(4).min(100);
+    let text = app.load_losses.join(", ");
+    let rows = (text.width() as u16).div_ceil(width.saturating_sub(2).max(1));
+    let banner = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + 1,
+        width,
+        height: (rows + 2).min(area.height.saturating_sub(1)),
+    };
+    f.render_widget(Clear, banner);
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" ⚠ Not everything could be shown ")
+                .title_bottom(" Esc dismiss ")
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+    f.render_widget(paragraph, banner);
+}
+
 fn render_help(f: &mut Frame, area: Rect) {
     let help_text = vec![
         "🆘 doxx - Help",
@@ -791,10 +3716,15 @@ fn render_help(f: &mut Frame, area: Rect) {
         "📖 Document Navigation:",
         "  ↑/k        Scroll up",
         "  ↓/j        Scroll down",
+        "  Ctrl+U/D   Half a page up/down",
         "  Page Up    Page up",
         "  Page Down  Page down",
-        "  Home       Go to start",
-        "  End        Go to end",
+        "  g/Home     Go to start (5g: row 5)",
+        "  G/End      Go to end",
+        "  50%        Go halfway through",
+        "  3j, 2]...  Type a count to repeat a move",
+        "  ma, 'a     Set mark a, go to mark a",
+        "  ''         Back to before the last jump",
         "",
         "🔍 Search:",
         "  s          Open search",
@@ -804,10 +3734,45 @@ fn render_help(f: &mut Frame, area: Rect) {
         "",
         "📋 Other Features:",
         "  o          Show outline",
+        "  t/Ctrl+J   Jump to heading (fuzzy)",
+        "  za/zo/zc   Toggle/open/close the fold of the section in view",
+        "  zM/zR      Fold every section to its heading/open all folds",
+        "  Ctrl+P     Command palette: jump anywhere, export, toggle images",
+        "  P          Present one section per screen",
+        "  T          Sort and filter the table in view",
+        "  O          Show/hide the outline pane (Tab to focus it)",
+        "  I          Show/hide the details and image preview pane",
+        "  </>        Narrow/widen the outline pane",
+        "  {/}        Widen/narrow the preview pane",
+        "  B          Bookmark the top of the screen (again to remove)",
+        "  a          Write a note on the top of the screen",
+        "  ]/[        Next/previous bookmark",
         "  c          Copy content to clipboard",
+        "  C          Copy a doxx command that reopens this view",
+        "  y          Copy the table in view (TSV by default)",
+        "  Y          Switch table copy format (TSV/Markdown/CSV)",
+        "  v          Chart tables' numeric columns (sparklines/bars/off)",
+        "  D          Toggle performance overlay",
+        "  X          Inspect the source XML of the element in view",
+        "  Esc        Dismiss the note of content that couldn't be shown",
         "  h/F1       Toggle help",
         "  q          Quit",
         "",
+        "🎤 Presenter Mode:",
+        "  Space/→/↓  Next section",
+        "  ←/↑        Previous section",
+        "  j/k        Scroll a long section",
+        "  t          Show/hide the timer",
+        "  Esc/P      Back to the document",
+        "",
+        "📊 Table Mode:",
+        "  ←/→        Select a column",
+        "  s          Sort by it (again: descending, then unsorted)",
+        "  f or /     Filter, e.g. 'Revenue >= 1000' or 'Region contains north'",
+        "  x          Clear the filter",
+        "  y/w        Copy the rows shown, or save them as CSV",
+        "  Esc/T      Back to the document",
+        "",
         "📄 Copy Functionality:",
         "  Document:  Copies full document as text",
         "  Outline:   Copies document structure",
@@ -834,13 +3799,128 @@ fn render_help_overlay(f: &mut Frame, _app: &App) {
     render_help(f, area);
 }
 
+/// Fingerprint of every element, for matching them up after a reload
+fn fingerprints(document: &Document) -> Vec<u64> {
+    document
+        .elements
+        .iter()
+        .map(crate::document::diff::fingerprint)
+        .collect()
+}
+
+/// Lines of the performance overlay
+///
+/// Frame times cover the whole draw, flush included; the other counters come
+/// from the last frame of the document view. Images are decoded up front, so
+/// there is no decode queue: the overlay shows how many decoded and how long it took.
+fn debug_overlay_lines(
+    timer: &FrameTimer,
+    stats: FrameStats,
+    images: (usize, usize, Duration),
+) -> Vec<String> {
+    let ms = |time: Option<Duration>| match time {
+        Some(time) => format!("{:.1}", time.as_secs_f64() * 1000.0),
+        None => "-".to_string(),
+    };
+    let hit_rate = match stats.hit_rate() {
+        Some(rate) => format!("{:.0}%", rate * 100.0),
+        None => "-".to_string(),
+    };
+    let (decoded, total, decode_time) = images;
+
+    vec![
+        format!(
+            "frame  {} ms (avg {}, max {})",
+            ms(timer.last()),
+            ms(timer.average()),
+            ms(timer.max())
+        ),
+        format!(
+            "cache  {} hit / {} miss ({hit_rate})",
+            stats.cache_hits, stats.cache_misses
+        ),
+        format!(
+            "drawn  {} elements, {} images",
+            stats.elements_rendered, stats.images_drawn
+        ),
+        format!(
+            "images {decoded}/{total} decoded in {} ms",
+            ms(Some(decode_time))
+        ),
+    ]
+}
+
+/// Performance numbers in the top-right corner of the content area
+fn render_debug_overlay(f: &mut Frame, area: Rect, app: &App) {
+    let lines = debug_overlay_lines(
+        &app.frame_timer,
+        app.layout_cache.frame_stats(),
+        (
+            app.image_protocols.len(),
+            app.image_count,
+            app.image_decode_time,
+        ),
+    );
+
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count() as u16 + 2)
+        .max()
+        .unwrap_or(0)
+        .min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let overlay = Rect {
+        x: area.x + area.width - width,
+        y: area.y,
+        width,
+        height,
+    };
+
+    let debug = Paragraph::new(lines.join("\n")).block(
+        Block::default()
+            .title("Debug")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Magenta)),
+    );
+    f.render_widget(Clear, overlay);
+    f.render_widget(debug, overlay);
+}
+
+/// The source of the element at the top of the view, over the right half of the document
+fn render_inspector(f: &mut Frame, area: Rect, app: &mut App) {
+    let width = (area.width / 2).max(40).min(area.width);
+    let overlay = Rect {
+        x: area.x + area.width - width,
+        width,
+        ..area
+    };
+    let lines: Vec<Line> = app
+        .inspection_lines()
+        .iter()
+        .map(|line| Line::from(line.clone()))
+        .collect();
+    let inspector = Paragraph::new(lines).block(
+        Block::default()
+            .title("Source (X to close)")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_widget(Clear, overlay);
+    f.render_widget(inspector, overlay);
+}
+
 fn render_status_bar(f: &mut Frame, area: Rect, app: &App) {
     let metadata = &app.document.metadata;
     let view_indicator = match app.current_view {
         ViewMode::Document => "📄 Document",
         ViewMode::Outline => "📋 Outline",
         ViewMode::Search => "🔍 Search",
+        ViewMode::HeadingJump => "🧭 Go to Heading",
         ViewMode::Help => "❓ Help",
+        ViewMode::Present => "🎤 Presenting",
+        ViewMode::Annotate => "📝 Note",
+        ViewMode::Palette => "⚡ Command Palette",
+        ViewMode::Table => "📊 Table",
     };
 
     let search_info = if !app.search_results.is_empty() {
@@ -855,12 +3935,27 @@ fn render_status_bar(f: &mut Frame, area: Rect, app: &App) {
         String::new()
     };
 
+    let bookmark_info = match app.annotation_in_view() {
+        Some(notes::Annotation {
+            note: Some(note), ..
+        }) => format!(" • 📝 {note}"),
+        Some(_) => " • 🔖".to_string(),
+        None => String::new(),
+    };
+
+    let table_info = match app.table_row_in_view() {
+        Some((row, rows)) if matches!(app.current_view, ViewMode::Document) => {
+            format!(" • ▦ row {row} of {rows}")
+        }
+        _ => String::new(),
+    };
+
     let status_text = if let Some(status_msg) = &app.status_message {
         // Show status message (like copy confirmation) with higher priority
         status_msg.clone()
     } else {
         format!(
-            "{} • 📄 {} • {} pages • {} words • {}/{}{}",
+            "{} • 📄 {} • {} pages • {} words • {}/{}{}{}{}",
             view_indicator,
             metadata
                 .file_path
@@ -871,6 +3966,8 @@ fn render_status_bar(f: &mut Frame, area: Rect, app: &App) {
             metadata.word_count,
             app.scroll_offset + 1,
             app.document.elements.len(),
+            table_info,
+            bookmark_info,
             search_info
         )
     };
@@ -885,14 +3982,29 @@ fn render_status_bar(f: &mut Frame, area: Rect, app: &App) {
         Style::default().fg(Color::White).bg(Color::DarkGray)
     };
 
-    let status = Paragraph::new(status_text)
+    // Protection stays in view, ahead of everything else, so that nobody
+    // reviews a final or locked document as a draft
+    let mut spans = Vec::new();
+    if let Some(protection) = &metadata.protection {
+        spans.push(Span::styled(
+            format!(" 🔒 {} ", protection.summary()),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::raw(status_text));
+    let status = Paragraph::new(Line::from(spans))
         .style(status_style)
         .block(Block::default());
 
     f.render_widget(status, area);
 
     // Navigation help
-    let help_text = "[↕] Scroll [o] Outline [s] Search [c] Copy [h] Help [q] Quit";
+    let help_text =
+        "[↕] Scroll [o] Outline [s] Search [t] Go to [m] Mark [a] Note [c] Copy [h] Help [q] Quit";
     let help_area = Rect {
         x: area.x,
         y: area.y + 1,
@@ -907,6 +4019,19 @@ fn render_status_bar(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(help, help_area);
 }
 
+/// Quote an argument for a POSIX shell, leaving plain words unquoted
+fn shell_quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@%+=,".contains(c));
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -926,3 +4051,193 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_reload_keeps_unchanged_elements() {
+        let path = "tests/fixtures/business-report.docx";
+        let document = load_document(std::path::Path::new(path), ImageOptions::default()).unwrap();
+        let cli = Cli::parse_from(["doxx", path, "--watch"]);
+        let mut app = App::new(document.clone(), &cli);
+        assert_eq!(app.fingerprints.len(), document.elements.len());
+
+        // Cache the wrapped lines of the element at the top of the view
+        app.scroll_offset = 3;
+        app.layout_cache.insert(3, 80, vec![Line::from("cached")]);
+
+        // The first element is deleted and the second edited
+        let mut edited = document.clone();
+        edited.elements.remove(0);
+        edited.elements[0] = DocumentElement::Heading {
+            level: 1,
+            text: "Edited".to_string(),
+            number: None,
+        };
+        app.reload(edited);
+
+        assert_eq!(app.scroll_offset, 2);
+        assert_eq!(
+            app.layout_cache.get(2, 80).unwrap()[0],
+            Line::from("cached")
+        );
+        let kept = document.elements.len() - 2;
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some(format!("Reloaded: 1 changed, 2 removed, {kept} unchanged.").as_str())
+        );
+    }
+
+    #[test]
+    fn test_counts_and_marks_move_as_in_less() {
+        let path = "tests/fixtures/business-report.docx";
+        let document = load_document(std::path::Path::new(path), ImageOptions::default()).unwrap();
+        let mut app = App::new(document, &Cli::parse_from(["doxx", path]));
+        let elements = app.document.elements.len();
+        let press = |app: &mut App, keys: &str| {
+            for c in keys.chars() {
+                assert!(app.navigation_key(KeyEvent::from(KeyCode::Char(c))));
+            }
+        };
+
+        // Before the first frame, rows are counted in elements
+        press(&mut app, "3j");
+        assert_eq!(app.scroll_offset, 3);
+        press(&mut app, "ma");
+        press(&mut app, "G");
+        assert_eq!(app.scroll_offset, elements - 1);
+        press(&mut app, "50%");
+        assert_eq!(app.scroll_offset, elements / 2);
+        press(&mut app, "'a");
+        assert_eq!(app.scroll_offset, 3);
+        press(&mut app, "''");
+        assert_eq!(app.scroll_offset, elements / 2);
+        press(&mut app, "2g");
+        assert_eq!(app.scroll_offset, 1);
+
+        app.viewport_height = 10;
+        let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        press(&mut app, "2");
+        assert!(app.navigation_key(ctrl_d));
+        assert_eq!(app.scroll_offset, 11);
+
+        // Keys for the view drop the count
+        press(&mut app, "4");
+        assert!(!app.navigation_key(KeyEvent::from(KeyCode::Char('o'))));
+        press(&mut app, "j");
+        assert_eq!(app.scroll_offset, 12);
+        press(&mut app, "'b");
+        assert_eq!(app.status_message.as_deref(), Some("No mark b"));
+    }
+
+    #[test]
+    fn test_banner_lists_what_was_skipped_until_dismissed() {
+        let path = "tests/fixtures/lossy-content.docx";
+        let document = load_document(std::path::Path::new(path), ImageOptions::default()).unwrap();
+        let mut app = App::new(document, &Cli::parse_from(["doxx", path]));
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(100, 20)).unwrap();
+        let screen = |terminal: &mut Terminal<ratatui::backend::TestBackend>, app: &mut App| {
+            terminal.draw(|f| ui(f, app)).unwrap();
+            let buffer = terminal.backend().buffer();
+            buffer
+                .content
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+        };
+
+        let shown = screen(&mut terminal, &mut app);
+        assert!(shown.contains("Not everything could be shown"));
+        assert!(shown.contains("1 chart skipped, 1 nested table flattened, 1 field unevaluated"));
+
+        app.load_losses.clear();
+        assert!(!screen(&mut terminal, &mut app).contains("Not everything"));
+    }
+
+    #[test]
+    fn test_file_watch_sees_changes() {
+        let path = std::env::temp_dir().join(format!("doxx-watch-{}.txt", std::process::id()));
+        std::fs::write(&path, "one").unwrap();
+        let mut watch = FileWatch::new(path.clone(), false, FieldOptions::default());
+        assert!(!watch.changed());
+
+        std::fs::write(&path, "longer").unwrap();
+        assert!(watch.changed());
+        assert!(!watch.changed());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_debug_overlay_lines() {
+        let mut timer = FrameTimer::default();
+        let empty = debug_overlay_lines(&timer, FrameStats::default(), (0, 0, Duration::ZERO));
+        assert_eq!(empty[0], "frame  - ms (avg -, max -)");
+        assert_eq!(empty[1], "cache  0 hit / 0 miss (-)");
+
+        for ms in [4, 2, 3] {
+            timer.record(Duration::from_millis(ms));
+        }
+        let stats = FrameStats {
+            cache_hits: 3,
+            cache_misses: 1,
+            elements_rendered: 12,
+            images_drawn: 1,
+        };
+        let lines = debug_overlay_lines(&timer, stats, (2, 3, Duration::from_millis(150)));
+        assert_eq!(
+            lines,
+            [
+                "frame  3.0 ms (avg 3.0, max 4.0)",
+                "cache  3 hit / 1 miss (75%)",
+                "drawn  12 elements, 1 images",
+                "images 2/3 decoded in 150.0 ms",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frame_timer_keeps_recent_frames() {
+        let mut timer = FrameTimer::default();
+        timer.record(Duration::from_millis(100));
+        for _ in 0..FRAME_HISTORY {
+            timer.record(Duration::from_millis(1));
+        }
+        assert_eq!(timer.max(), Some(Duration::from_millis(1)));
+        assert_eq!(timer.average(), Some(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn test_view_command_needs_a_file_to_reopen() {
+        let path = "tests/fixtures/business-report.docx";
+        let mut document =
+            load_document(std::path::Path::new(path), ImageOptions::default()).unwrap();
+        let app = App::new(document.clone(), &Cli::parse_from(["doxx", path]));
+        assert!(
+            app.view_command().is_some_and(
+                |command| command.starts_with("doxx tests/fixtures/business-report.docx")
+            )
+        );
+
+        // Piped input keeps the pipe's name, which can't be opened again
+        document.metadata.file_path = "/dev/fd/63".to_string();
+        let mut app = App::new(document, &Cli::parse_from(["doxx", "/dev/fd/63"]));
+        assert_eq!(app.view_command(), None);
+        app.copy_view_command();
+        assert!(app
+            .status_message
+            .as_deref()
+            .is_some_and(|message| message.contains("standard input")));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("report.docx"), "report.docx");
+        assert_eq!(shell_quote("docs/Q4 report.docx"), "'docs/Q4 report.docx'");
+        assert_eq!(shell_quote("Market Risks"), "'Market Risks'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+}
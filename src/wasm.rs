@@ -0,0 +1,53 @@
+//! Browser bindings
+//!
+//! The parser and the file exporters behind `wasm-bindgen`, for previewing
+//! documents in a web page. Each function takes the raw bytes of a .docx (a
+//! `Uint8Array` from `File.arrayBuffer()`) and returns the export as a string,
+//! or throws an `Error` with the reason the document couldn't be read.
+//!
+//! Images aren't extracted, since there's no file system to put them on; they
+//! come out as their descriptions.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    document::{Document, ImageOptions},
+    export::{self, json, ExportOptions},
+    JsonCase,
+};
+
+fn load(bytes: &[u8]) -> Result<Document, JsError> {
+    Document::from_bytes(bytes, ImageOptions::default()).map_err(to_js_error)
+}
+
+/// The error with its causes, as the CLI prints it
+fn to_js_error(error: anyhow::Error) -> JsError {
+    JsError::new(&format!("{error:#}"))
+}
+
+/// Convert a .docx to a standalone HTML page
+#[wasm_bindgen(js_name = docxToHtml)]
+pub fn docx_to_html(bytes: &[u8]) -> Result<String, JsError> {
+    Ok(export::html::render_html(&load(bytes)?))
+}
+
+/// Convert a .docx to Markdown
+#[wasm_bindgen(js_name = docxToMarkdown)]
+pub fn docx_to_markdown(bytes: &[u8]) -> Result<String, JsError> {
+    Ok(export::render_markdown(
+        &load(bytes)?,
+        &ExportOptions::default(),
+    ))
+}
+
+/// Convert a .docx to doxx's JSON export, with camelCase keys if `camel_case`
+/// is set, as JavaScript code usually expects
+#[wasm_bindgen(js_name = docxToJson)]
+pub fn docx_to_json(bytes: &[u8], camel_case: bool) -> Result<String, JsError> {
+    let case = if camel_case {
+        JsonCase::Camel
+    } else {
+        JsonCase::Snake
+    };
+    json::render_json(&load(bytes)?, case).map_err(to_js_error)
+}
@@ -0,0 +1,109 @@
+//! Large section titles for presenter mode
+//!
+//! A three-row font drawn with box-drawing characters, in the spirit of
+//! figlet's `future` font. It covers ASCII letters (shown in capitals), digits
+//! and common punctuation; titles with anything else are left to the caller to
+//! show as ordinary text.
+
+/// Rows in every glyph
+pub const BANNER_HEIGHT: usize = 3;
+
+/// Columns between glyphs
+const GLYPH_GAP: usize = 1;
+
+/// The three rows of a character's glyph, all the same width
+fn glyph(c: char) -> Option<[&'static str; BANNER_HEIGHT]> {
+    Some(match c.to_ascii_uppercase() {
+        'A' => ["┏━┓", "┣━┫", "╹ ╹"],
+        'B' => ["┏┓ ", "┣┻┓", "┗━┛"],
+        'C' => ["┏━╸", "┃  ", "┗━╸"],
+        'D' => ["╺┳┓", " ┃┃", "╺┻┛"],
+        'E' => ["┏━╸", "┣╸ ", "┗━╸"],
+        'F' => ["┏━╸", "┣╸ ", "╹  "],
+        'G' => ["┏━╸", "┃╺┓", "┗━┛"],
+        'H' => ["╻ ╻", "┣━┫", "╹ ╹"],
+        'I' => ["╻", "┃", "╹"],
+        'J' => ["  ╻", "  ┃", "┗━┛"],
+        'K' => ["╻┏ ", "┣┻┓", "╹ ╹"],
+        'L' => ["╻  ", "┃  ", "┗━╸"],
+        'M' => ["┏┳┓", "┃┃┃", "╹ ╹"],
+        'N' => ["┏┓╻", "┃┗┫", "╹ ╹"],
+        'O' => ["┏━┓", "┃ ┃", "┗━┛"],
+        'P' => ["┏━┓", "┣━┛", "╹  "],
+        'Q' => ["┏━┓", "┃┓┃", "┗┻┛"],
+        'R' => ["┏━┓", "┣┳┛", "╹┗╸"],
+        'S' => ["┏━┓", "┗━┓", "┗━┛"],
+        'T' => ["╺┳╸", " ┃ ", " ╹ "],
+        'U' => ["╻ ╻", "┃ ┃", "┗━┛"],
+        'V' => ["╻ ╻", "┃┏┛", "┗┛ "],
+        'W' => ["╻ ╻", "┃╻┃", "┗┻┛"],
+        'X' => ["╻ ╻", "┏╋┛", "╹ ╹"],
+        'Y' => ["╻ ╻", "┗┳┛", " ╹ "],
+        'Z' => ["╺━┓", "┏━┛", "┗━╸"],
+        '0' => ["┏━┓", "┃┃┃", "┗━┛"],
+        '1' => ["╺┓ ", " ┃ ", "╺┻╸"],
+        '2' => ["┏━┓", "┏━┛", "┗━╸"],
+        '3' => ["┏━┓", "╺━┫", "┗━┛"],
+        '4' => ["╻ ╻", "┗━┫", "  ╹"],
+        '5' => ["┏━╸", "┗━┓", "┗━┛"],
+        '6' => ["┏━┓", "┣━┓", "┗━┛"],
+        '7' => ["┏━┓", "  ┃", "  ╹"],
+        '8' => ["┏━┓", "┣━┫", "┗━┛"],
+        '9' => ["┏━┓", "┗━┫", "┗━┛"],
+        ' ' => ["  ", "  ", "  "],
+        '.' => [" ", " ", "╹"],
+        ',' => [" ", " ", "┛"],
+        ':' => ["╻", " ", "╹"],
+        '!' => ["╻", "┃", "╹"],
+        '?' => ["┏━┓", " ┏┛", " ╹ "],
+        '-' | '–' | '—' => ["   ", "╺━╸", "   "],
+        '+' => ["   ", "╺╋╸", "   "],
+        '/' => ["  ╻", " ┏┛", "┏┛ "],
+        '(' => ["┏╸", "┃ ", "┗╸"],
+        ')' => ["╺┓", " ┃", "╺┛"],
+        '\'' | '’' => ["╻", " ", " "],
+        '&' => ["┏┓ ", "┣╋╸", "┗┛ "],
+        _ => return None,
+    })
+}
+
+/// `text` in the banner font, one string per row, or `None` if it has a
+/// character the font doesn't cover
+pub fn banner(text: &str) -> Option<[String; BANNER_HEIGHT]> {
+    let mut rows: [String; BANNER_HEIGHT] = Default::default();
+    for (i, c) in text.trim().chars().enumerate() {
+        let glyph = glyph(c)?;
+        for (row, part) in rows.iter_mut().zip(glyph) {
+            if i > 0 {
+                row.push_str(&" ".repeat(GLYPH_GAP));
+            }
+            row.push_str(part);
+        }
+    }
+    Some(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unicode_width::UnicodeWidthStr;
+
+    #[test]
+    fn test_glyph_rows_line_up() {
+        for c in (' '..='~').chain("–—’".chars()) {
+            if let Some(rows) = glyph(c) {
+                let width = rows[0].width();
+                assert!(rows.iter().all(|row| row.width() == width), "{c:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_banner() {
+        assert_eq!(
+            banner("Hi 2").unwrap(),
+            ["╻ ╻ ╻    ┏━┓", "┣━┫ ┃    ┏━┛", "╹ ╹ ╹    ┗━╸"]
+        );
+        assert_eq!(banner("Über"), None);
+    }
+}
@@ -10,17 +10,176 @@ use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use super::LayoutCache;
+use crate::bidi;
 use crate::document::*;
+use crate::wrap::{labeled_rule, tab_fills};
+use crate::ChartStyle;
 
 /// Context for rendering document elements
 struct RenderContext<'a> {
     color_enabled: bool,
     search_matches: &'a [(usize, usize)],
     is_current_match: bool,
+    /// Misspelled words to underline, with the list item or cell they're in
+    misspellings: &'a [&'a Misspelling],
     element_index: usize,
     layout_cache: &'a mut LayoutCache,
 }
 
+/// How a paragraph's lines are laid out, besides their width
+#[derive(Clone, Copy, Default)]
+struct LineLayout<'a> {
+    /// The paragraph's base direction is right to left
+    rtl: bool,
+    /// Where its tabs stop
+    tab_stops: &'a [TabStop],
+}
+
+/// How a table row is laid out and drawn, besides its cells
+#[derive(Clone, Copy)]
+struct RowLayout<'a> {
+    col_widths: &'a [usize],
+    borders: TableBorders,
+    glyphs: &'a BoxGlyphs,
+    /// The row's fill, if colour is on and its style shades it
+    fill: Option<&'a str>,
+}
+
+/// Which of a table's horizontal lines is being drawn
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Rule {
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Box-drawing characters for a table's lines
+struct BoxGlyphs {
+    horizontal: char,
+    vertical: char,
+    /// The left end, crossing and right end of the top, middle and bottom lines
+    joins: [[char; 3]; 3],
+}
+
+impl BoxGlyphs {
+    fn new(line: BorderLine) -> Self {
+        match line {
+            BorderLine::Light => Self {
+                horizontal: '─',
+                vertical: '│',
+                joins: [['┌', '┬', '┐'], ['├', '┼', '┤'], ['└', '┴', '┘']],
+            },
+            BorderLine::Heavy => Self {
+                horizontal: '━',
+                vertical: '┃',
+                joins: [['┏', '┳', '┓'], ['┣', '╋', '┫'], ['┗', '┻', '┛']],
+            },
+            BorderLine::Double => Self {
+                horizontal: '═',
+                vertical: '║',
+                joins: [['╔', '╦', '╗'], ['╠', '╬', '╣'], ['╚', '╩', '╝']],
+            },
+        }
+    }
+
+    /// A horizontal line across the columns, joined to the vertical ones
+    fn rule(&self, position: Rule, col_widths: &[usize], borders: TableBorders) -> String {
+        let [start, cross, end] = self.joins[position as usize];
+        let horizontal = self.horizontal.to_string();
+        let mut rule = String::new();
+        if borders.left {
+            rule.push(start);
+        }
+        for (i, width) in col_widths.iter().enumerate() {
+            if i > 0 {
+                rule.push(if borders.inside_vertical {
+                    cross
+                } else {
+                    self.horizontal
+                });
+            }
+            rule.push_str(&horizontal.repeat(*width));
+        }
+        if borders.right {
+            rule.push(end);
+        }
+        rule
+    }
+}
+
+/// One line of a table as the viewer lays it out
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TableLine {
+    Title,
+    Rule(Rule),
+    Header,
+    /// A body row, by index
+    Row(usize),
+    /// The blank line after the table
+    Blank,
+}
+
+/// The lines a table takes in the viewer, top to bottom
+fn table_lines(table: &TableData) -> Vec<TableLine> {
+    let borders = table.metadata.style.borders;
+    let mut lines = Vec::with_capacity(2 * table.rows.len() + 6);
+    if table.metadata.title.is_some() {
+        lines.push(TableLine::Title);
+    }
+    if borders.top {
+        lines.push(TableLine::Rule(Rule::Top));
+    }
+    if table.metadata.has_headers && !table.headers.is_empty() {
+        lines.push(TableLine::Header);
+        if borders.header || borders.inside_horizontal {
+            lines.push(TableLine::Rule(Rule::Middle));
+        }
+    }
+    for index in 0..table.rows.len() {
+        if index > 0 && borders.inside_horizontal {
+            lines.push(TableLine::Rule(Rule::Middle));
+        }
+        lines.push(TableLine::Row(index));
+    }
+    if borders.bottom {
+        lines.push(TableLine::Rule(Rule::Bottom));
+    }
+    lines.push(TableLine::Blank);
+    lines
+}
+
+/// The lines shown of a table scrolled `skip` lines in
+///
+/// Once the header row is scrolled off the top it's repeated there, with the
+/// lines above and below it, over the rows that have scrolled under it, for as
+/// long as rows remain below it.
+fn visible_table_lines(lines: &[TableLine], skip: usize) -> Vec<TableLine> {
+    let visible = lines.get(skip..).unwrap_or_default();
+    let Some(header) = lines.iter().position(|line| *line == TableLine::Header) else {
+        return visible.to_vec();
+    };
+    if skip <= header {
+        return visible.to_vec();
+    }
+
+    let start = usize::from(lines[0] == TableLine::Title);
+    let end = match lines.get(header + 1) {
+        Some(TableLine::Rule(_)) => header + 2,
+        _ => header + 1,
+    };
+    let head = &lines[start..end];
+    let mut rest = lines.get(skip + head.len()..).unwrap_or_default();
+    // The header's own rule stands in for a line between rows right under it
+    if end > header + 1 && rest.first() == Some(&TableLine::Rule(Rule::Middle)) {
+        rest = &rest[1..];
+    }
+    if rest.iter().any(|line| matches!(line, TableLine::Row(_))) {
+        head.iter().chain(rest).copied().collect()
+    } else {
+        visible.to_vec()
+    }
+}
+
 /// Custom widget for rendering document content with proper text wrapping and inline images.
 ///
 /// This widget handles the complete layout of document elements including:
@@ -32,12 +191,26 @@ struct RenderContext<'a> {
 /// Unlike using the Paragraph widget with image overlays, this widget renders everything
 /// in a single pass with full control over layout, ensuring images are positioned correctly
 /// even when text wraps across multiple lines.
+///
+/// Rendering is virtualized: only elements on screen (plus a small margin used to firm up
+/// the height index in [`LayoutCache`]) are wrapped, so the cost of a frame does not grow
+/// with the length of the document.
 pub struct DocumentWidget<'a> {
     elements: &'a [DocumentElement],
     scroll_offset: usize,
+    scroll_line_offset: usize,
     color_enabled: bool,
     search_results: &'a [SearchResult],
     current_search_index: usize,
+    sections: &'a [Section],
+    /// Whether each element is hidden by a folded heading
+    hidden: &'a [bool],
+    /// How to chart the numeric columns below each table, if at all
+    charts: Option<ChartStyle>,
+    /// How numbers in table cells are written
+    locale: CellLocale,
+    /// Words to underline as misspelled, in element order
+    misspellings: &'a [Misspelling],
 }
 
 impl<'a> DocumentWidget<'a> {
@@ -46,9 +219,15 @@ impl<'a> DocumentWidget<'a> {
         Self {
             elements,
             scroll_offset: 0,
+            scroll_line_offset: 0,
             color_enabled: false,
             search_results: &[],
             current_search_index: 0,
+            sections: &[],
+            hidden: &[],
+            charts: None,
+            locale: CellLocale::default(),
+            misspellings: &[],
         }
     }
 
@@ -58,6 +237,12 @@ impl<'a> DocumentWidget<'a> {
         self
     }
 
+    /// Set how many rows of the first visible element are scrolled off the top
+    pub fn scroll_line_offset(mut self, offset: usize) -> Self {
+        self.scroll_line_offset = offset;
+        self
+    }
+
     /// Enable or disable color rendering
     pub fn color_enabled(mut self, enabled: bool) -> Self {
         self.color_enabled = enabled;
@@ -76,6 +261,49 @@ impl<'a> DocumentWidget<'a> {
         self
     }
 
+    /// Set the document's sections, so a marker is drawn where each new one starts
+    pub fn sections(mut self, sections: &'a [Section]) -> Self {
+        self.sections = sections;
+        self
+    }
+
+    /// Hide the elements of folded sections, one flag per element
+    pub fn hidden(mut self, hidden: &'a [bool]) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Chart the numeric columns below each table, reading cells in `locale`
+    pub fn charts(mut self, style: Option<ChartStyle>, locale: CellLocale) -> Self {
+        self.charts = style;
+        self.locale = locale;
+        self
+    }
+
+    /// Underline misspelled words in paragraphs, quotes and list items
+    pub fn misspellings(mut self, misspellings: &'a [Misspelling]) -> Self {
+        self.misspellings = misspellings;
+        self
+    }
+
+    /// Number of elements folded away under a heading
+    fn folded_count(&self, element_index: usize) -> usize {
+        self.hidden
+            .iter()
+            .skip(element_index + 1)
+            .take_while(|&&hidden| hidden)
+            .count()
+    }
+
+    /// Marker for the section starting at this element, other than the first
+    fn section_marker(&self, element_index: usize) -> Option<String> {
+        let index = self
+            .sections
+            .iter()
+            .position(|section| section.start == element_index && section.end > section.start)?;
+        (index > 0).then(|| format!("── {} ──", self.sections[index].label(index + 1)))
+    }
+
     /// Wrap formatted text runs into lines that fit within the given width.
     ///
     /// This function properly handles:
@@ -83,23 +311,40 @@ impl<'a> DocumentWidget<'a> {
     /// - Preserving text formatting (bold, italic, colors) across wrapped lines
     /// - Calculating visual width correctly for all unicode characters
     /// - Search result highlighting (current match and other matches)
+    /// - Underlining misspelled words, by character range like search matches
+    /// - Putting each line into visual order for right-to-left text, with the
+    ///   layout's base direction
+    /// - Advancing each tab to the layout's next tab stop; a tab still counts
+    ///   as one character for search matches
     fn wrap_formatted_runs(
         runs: &[FormattedRun],
         max_width: usize,
         color_enabled: bool,
         search_matches: &[(usize, usize)], // List of (start_pos, end_pos) for matches in this element
         is_current_match: bool,            // True if this element contains the current search match
+        misspelled: &[(usize, usize)],
+        layout: LineLayout,
     ) -> Vec<Line<'static>> {
         if max_width == 0 {
             return vec![];
         }
+        let rtl = layout.rtl;
+
+        let texts: Vec<_> = runs
+            .iter()
+            .map(|run| run.formatting.display_text(&run.text))
+            .collect();
+        let segments: Vec<&str> = texts.iter().map(|text| text.as_ref()).collect();
+        let mut tab_fills = tab_fills(&segments, layout.tab_stops, max_width).into_iter();
 
         let mut lines = Vec::new();
         let mut current_line: Vec<Span> = Vec::new();
+        // Whether each span of the current line comes from a right-to-left run
+        let mut current_rtl: Vec<bool> = Vec::new();
         let mut current_width = 0;
         let mut char_position = 0; // Track absolute character position across all runs
 
-        for run in runs {
+        for (run, text) in runs.iter().zip(&texts) {
             let mut base_style = Style::default();
 
             // Apply formatting
@@ -116,19 +361,24 @@ impl<'a> DocumentWidget<'a> {
                 base_style = base_style.add_modifier(Modifier::CROSSED_OUT);
             }
 
-            // Apply color if enabled
+            // Apply color and highlight if enabled
             if color_enabled {
-                if let Some(color_hex) = &run.formatting.color {
-                    if let Some(color) = hex_to_color(color_hex) {
-                        base_style = base_style.fg(color);
-                    }
+                let background = run.formatting.background_hex();
+                let foreground = run
+                    .formatting
+                    .color
+                    .as_deref()
+                    .or(background.map(contrasting_text_color));
+                if let Some(color) = foreground.and_then(hex_to_color) {
+                    base_style = base_style.fg(color);
+                }
+                if let Some(color) = background.and_then(hex_to_color) {
+                    base_style = base_style.bg(color);
                 }
             }
 
             // Split text into graphemes for proper unicode handling
-            for grapheme in run.text.graphemes(true) {
-                let g_width = grapheme.width();
-
+            for grapheme in text.graphemes(true) {
                 // Determine if this character is within a search match
                 let mut style = base_style;
                 for &(start_pos, end_pos) in search_matches {
@@ -144,27 +394,52 @@ impl<'a> DocumentWidget<'a> {
                         break;
                     }
                 }
-
-                // Check if adding this grapheme would exceed max width
-                if current_width + g_width > max_width && current_width > 0 {
-                    // Finish current line and start a new one
-                    if !current_line.is_empty() {
-                        lines.push(Line::from(current_line.clone()));
-                        current_line.clear();
-                        current_width = 0;
+                if misspelled
+                    .iter()
+                    .any(|&(start, end)| (start..end).contains(&char_position))
+                {
+                    style = style.add_modifier(Modifier::UNDERLINED);
+                    if color_enabled {
+                        style = style.fg(Color::LightRed);
                     }
                 }
 
-                // Add grapheme to current line
-                current_line.push(Span::styled(grapheme.to_string(), style));
-                current_width += g_width;
+                // A tab is drawn as the spaces or leader up to its stop
+                let cells: Vec<String> = if grapheme == "\t" {
+                    let fill = tab_fills.next().unwrap_or_default();
+                    fill.chars().map(String::from).collect()
+                } else {
+                    vec![grapheme.to_string()]
+                };
+                for cell in cells {
+                    let g_width = cell.width();
+
+                    // Check if adding this grapheme would exceed max width
+                    if current_width + g_width > max_width && current_width > 0 {
+                        // Finish current line and start a new one
+                        if !current_line.is_empty() {
+                            lines.push(Self::visual_line(
+                                std::mem::take(&mut current_line),
+                                &current_rtl,
+                                rtl,
+                            ));
+                            current_rtl.clear();
+                            current_width = 0;
+                        }
+                    }
+
+                    // Add grapheme to current line
+                    current_line.push(Span::styled(cell, style));
+                    current_rtl.push(run.formatting.rtl);
+                    current_width += g_width;
+                }
                 char_position += grapheme.chars().count(); // Advance character position
             }
         }
 
         // Add remaining content
         if !current_line.is_empty() {
-            lines.push(Line::from(current_line));
+            lines.push(Self::visual_line(current_line, &current_rtl, rtl));
         }
 
         // Return at least one empty line if no content
@@ -175,6 +450,34 @@ impl<'a> DocumentWidget<'a> {
         lines
     }
 
+    /// Reorder a wrapped line's grapheme spans from logical to visual order
+    fn visual_line(spans: Vec<Span<'static>>, run_rtl: &[bool], rtl: bool) -> Line<'static> {
+        if !rtl
+            && run_rtl.iter().all(|&flag| !flag)
+            && spans.iter().all(|span| span.content.is_ascii())
+        {
+            return Line::from(spans);
+        }
+
+        let pieces: Vec<(&str, bool)> = spans
+            .iter()
+            .zip(run_rtl)
+            .map(|(span, &flag)| (span.content.as_ref(), flag))
+            .collect();
+        let visual: Vec<Span<'static>> = bidi::visual_order(&pieces, rtl)
+            .into_iter()
+            .map(|(i, reversed)| {
+                let span = &spans[i];
+                if reversed {
+                    Span::styled(bidi::mirror(&span.content).to_string(), span.style)
+                } else {
+                    span.clone()
+                }
+            })
+            .collect();
+        Line::from(visual)
+    }
+
     /// Render a heading element at the current position
     fn render_heading(
         heading: &str,
@@ -184,9 +487,9 @@ impl<'a> DocumentWidget<'a> {
         buf: &mut Buffer,
         current_y: &mut u16,
         color_enabled: bool,
-    ) {
+    ) -> u16 {
         if *current_y >= area.y + area.height {
-            return; // Off screen
+            return area.x; // Off screen
         }
 
         // Determine styling based on heading level
@@ -224,19 +527,23 @@ impl<'a> DocumentWidget<'a> {
         };
 
         // Build heading text with optional numbering
-        let text = if let Some(num) = number {
-            format!("{prefix}{num} {heading}")
+        let heading = if let Some(num) = number {
+            format!("{num} {heading}")
         } else {
-            format!("{prefix}{heading}")
+            heading.to_string()
         };
+        let text = format!("{prefix}{}", bidi::visual_line(&heading));
 
-        buf.set_string(area.x, *current_y, &text, style);
+        let (end, _) = buf.set_stringn(area.x, *current_y, &text, area.width as usize, style);
         *current_y += 2; // Heading + blank line
+        end
     }
 
     /// Render a paragraph element at the current position
     fn render_paragraph(
         runs: &[FormattedRun],
+        shading: Option<&str>,
+        layout: LineLayout,
         area: Rect,
         buf: &mut Buffer,
         current_y: &mut u16,
@@ -246,9 +553,15 @@ impl<'a> DocumentWidget<'a> {
             return; // Off screen
         }
 
+        let misspelled: Vec<(usize, usize)> = ctx
+            .misspellings
+            .iter()
+            .map(|misspelling| (misspelling.start, misspelling.end))
+            .collect();
+
         // Try to get cached lines, or wrap if not cached
-        let wrapped_lines = if ctx.search_matches.is_empty() {
-            // Only use cache if there are no search matches (search highlighting changes the output)
+        let wrapped_lines = if ctx.search_matches.is_empty() && misspelled.is_empty() {
+            // Only use cache if nothing is highlighted (highlighting changes the output)
             if let Some(cached) = ctx.layout_cache.get(ctx.element_index, area.width) {
                 cached.clone()
             } else {
@@ -258,6 +571,8 @@ impl<'a> DocumentWidget<'a> {
                     ctx.color_enabled,
                     ctx.search_matches,
                     ctx.is_current_match,
+                    &[],
+                    layout,
                 );
                 ctx.layout_cache
                     .insert(ctx.element_index, area.width, lines.clone());
@@ -271,20 +586,97 @@ impl<'a> DocumentWidget<'a> {
                 ctx.color_enabled,
                 ctx.search_matches,
                 ctx.is_current_match,
+                &misspelled,
+                layout,
             )
         };
 
+        // Shading fills the whole row; runs with their own colours draw over it
+        let shading_style = shading.filter(|_| ctx.color_enabled).and_then(|hex| {
+            let background = hex_to_color(hex)?;
+            let foreground = hex_to_color(contrasting_text_color(hex))?;
+            Some(Style::default().bg(background).fg(foreground))
+        });
+
         // Render each line
         for line in wrapped_lines {
             if *current_y >= area.y + area.height {
                 break; // Stop if we reach bottom of area
             }
 
-            buf.set_line(area.x, *current_y, &line, area.width);
+            if let Some(style) = shading_style {
+                buf.set_style(Rect::new(area.x, *current_y, area.width, 1), style);
+            }
+            // Right-to-left paragraphs are flush with the right edge
+            let indent = if layout.rtl {
+                area.width.saturating_sub(line.width() as u16)
+            } else {
+                0
+            };
+            buf.set_line(area.x + indent, *current_y, &line, area.width - indent);
             *current_y += 1;
         }
+    }
 
-        *current_y += 1; // Blank line after paragraph
+    /// Render a block quote as a paragraph set in behind a bar
+    fn render_block_quote(
+        runs: &[FormattedRun],
+        area: Rect,
+        buf: &mut Buffer,
+        current_y: &mut u16,
+        ctx: &mut RenderContext,
+    ) {
+        let top = *current_y;
+        let inner = Rect {
+            x: area.x + QUOTE_INDENT.min(area.width),
+            width: area.width.saturating_sub(QUOTE_INDENT),
+            ..area
+        };
+        Self::render_paragraph(
+            runs,
+            None,
+            LineLayout::default(),
+            inner,
+            buf,
+            current_y,
+            ctx,
+        );
+
+        let style = if ctx.color_enabled {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default()
+        };
+        let bottom = (*current_y).min(area.y + area.height);
+        for y in top..bottom {
+            buf.set_stringn(area.x, y, "│", area.width as usize, style);
+        }
+        *current_y += 1; // Blank line after the quote
+    }
+
+    /// Render a code block line for line, cutting off lines too long for the screen
+    fn render_code_block(
+        code: &str,
+        area: Rect,
+        buf: &mut Buffer,
+        current_y: &mut u16,
+        color_enabled: bool,
+    ) {
+        let style = if color_enabled {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default()
+        };
+        for line in code.lines() {
+            if *current_y < area.y + area.height {
+                buf.set_stringn(area.x, *current_y, line, area.width as usize, style);
+            }
+            *current_y += 1;
+        }
+        if code.is_empty() {
+            *current_y += 1;
+        }
+        *current_y += 1; // Blank line after the code
     }
 
     /// Render a list element at the current position
@@ -308,7 +700,7 @@ impl<'a> DocumentWidget<'a> {
                 "• ".to_string()
             };
 
-            let bullet_width = bullet_str.len();
+            let bullet_width = bullet_str.width();
             let indent = " ".repeat(bullet_width);
 
             // Render bullet/number
@@ -321,12 +713,24 @@ impl<'a> DocumentWidget<'a> {
 
             // Wrap the item text to fit after the bullet
             let text_width = (area.width as usize).saturating_sub(bullet_width);
+            let item_text: String = item.runs.iter().map(|run| run.text.as_str()).collect();
+            let misspelled: Vec<(usize, usize)> = ctx
+                .misspellings
+                .iter()
+                .filter(|misspelling| misspelling.item == idx)
+                .map(|misspelling| (misspelling.start, misspelling.end))
+                .collect();
             let wrapped_lines = Self::wrap_formatted_runs(
                 &item.runs,
                 text_width,
                 ctx.color_enabled,
                 ctx.search_matches,
                 ctx.is_current_match,
+                &misspelled,
+                LineLayout {
+                    rtl: bidi::is_rtl(&item_text),
+                    ..Default::default()
+                },
             );
 
             // Render first line (on same line as bullet)
@@ -359,9 +763,11 @@ impl<'a> DocumentWidget<'a> {
         *current_y += 1; // Blank line after list
     }
 
-    /// Render a table element at the current position
+    /// Render a table element at the current position, with the lines and row
+    /// shading of its style, from `skip` lines in
     fn render_table(
         table: &TableData,
+        skip: usize,
         area: Rect,
         buf: &mut Buffer,
         current_y: &mut u16,
@@ -371,7 +777,12 @@ impl<'a> DocumentWidget<'a> {
             return; // Off screen
         }
 
-        let available_width = area.width as usize;
+        let style = &table.metadata.style;
+        let borders = style.borders;
+        let glyphs = BoxGlyphs::new(style.line);
+        // Lines down the sides take a column each
+        let available_width = (area.width as usize)
+            .saturating_sub(usize::from(borders.left) + usize::from(borders.right));
 
         // Calculate column widths based on metadata
         let col_widths = &table.metadata.column_widths;
@@ -387,157 +798,255 @@ impl<'a> DocumentWidget<'a> {
             col_widths.clone()
         };
 
-        // Render title if present
-        if let Some(title) = &table.metadata.title {
-            let title_style = if color_enabled {
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().add_modifier(Modifier::BOLD)
-            };
-            buf.set_string(area.x, *current_y, title, title_style);
-            *current_y += 1;
-        }
-
-        // Render headers if present
-        if table.metadata.has_headers && !table.headers.is_empty() {
-            Self::render_table_row(
-                &table.headers,
-                &scaled_widths,
-                area,
-                buf,
-                current_y,
-                color_enabled,
-                true,
-            );
+        let plain = RowLayout {
+            col_widths: &scaled_widths,
+            borders,
+            glyphs: &glyphs,
+            fill: None,
+        };
+        let header_fill = style.header_fill.as_deref().filter(|_| color_enabled);
+        let band_fill = style.band_fill.as_deref().filter(|_| color_enabled);
 
-            // Header separator line
-            if *current_y < area.y + area.height {
-                let separator = "─".repeat(available_width.min(scaled_widths.iter().sum()));
-                buf.set_string(area.x, *current_y, &separator, Style::default());
-                *current_y += 1;
+        for line in visible_table_lines(&table_lines(table), skip) {
+            if *current_y >= area.y + area.height {
+                break;
+            }
+            match line {
+                TableLine::Title => {
+                    let title = table.metadata.title.as_deref().unwrap_or_default();
+                    let title_style = if color_enabled {
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().add_modifier(Modifier::BOLD)
+                    };
+                    buf.set_string(area.x, *current_y, title, title_style);
+                }
+                TableLine::Rule(position) => {
+                    let rule = glyphs.rule(position, &scaled_widths, borders);
+                    buf.set_stringn(
+                        area.x,
+                        *current_y,
+                        &rule,
+                        area.width as usize,
+                        Style::default(),
+                    );
+                }
+                TableLine::Header => Self::render_table_row(
+                    &table.headers,
+                    &RowLayout {
+                        fill: header_fill,
+                        ..plain
+                    },
+                    area,
+                    buf,
+                    *current_y,
+                    color_enabled,
+                    true,
+                ),
+                // Every other row is shaded if the style bands them
+                TableLine::Row(index) => Self::render_table_row(
+                    &table.rows[index],
+                    &RowLayout {
+                        fill: band_fill.filter(|_| index % 2 == 0),
+                        ..plain
+                    },
+                    area,
+                    buf,
+                    *current_y,
+                    color_enabled,
+                    false,
+                ),
+                TableLine::Blank => {}
             }
+            *current_y += 1;
         }
+    }
 
-        // Render rows
-        for row in &table.rows {
+    /// Render charts of a table's numeric columns below it
+    fn render_charts(
+        lines: &[chart::ChartLine],
+        area: Rect,
+        buf: &mut Buffer,
+        current_y: &mut u16,
+        color_enabled: bool,
+    ) {
+        let (graph_style, value_style) = if color_enabled {
+            (
+                Style::default().fg(Color::Cyan),
+                Style::default().fg(Color::DarkGray),
+            )
+        } else {
+            (Style::default(), Style::default())
+        };
+        for line in lines {
             if *current_y >= area.y + area.height {
                 break;
             }
-            Self::render_table_row(
-                row,
-                &scaled_widths,
-                area,
-                buf,
-                current_y,
-                color_enabled,
-                false,
-            );
+            let spans = if line.heading {
+                Line::from(Span::styled(
+                    line.label.as_str(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from(vec![
+                    Span::raw(format!("{}  ", line.label)),
+                    Span::styled(line.graph.as_str(), graph_style),
+                    Span::styled(format!("  {}", line.value), value_style),
+                ])
+            };
+            buf.set_line(area.x, *current_y, &spans, area.width);
+            *current_y += 1;
         }
-
-        *current_y += 1; // Blank line after table
+        *current_y += 1; // Blank line after the charts
     }
 
-    /// Render a single table row
+    /// Render a single table row on line `y`
     fn render_table_row(
         cells: &[TableCell],
-        col_widths: &[usize],
+        layout: &RowLayout,
         area: Rect,
         buf: &mut Buffer,
-        current_y: &mut u16,
+        y: u16,
         color_enabled: bool,
         is_header: bool,
     ) {
-        if *current_y >= area.y + area.height {
+        if y >= area.y + area.height {
             return;
         }
 
+        // The row's fill runs under its cells and the lines between them
+        let fill = layout
+            .fill
+            .and_then(|hex| Some((hex_to_color(hex)?, contrasting_text_color(hex))));
+        let line_style = match fill {
+            Some((background, _)) => Style::default().bg(background),
+            None => Style::default(),
+        };
+        let fill_foreground = fill.and_then(|(_, foreground)| hex_to_color(foreground));
+        let vertical = layout.glyphs.vertical.to_string();
+
+        let area_width = area.width as usize;
         let mut x_offset = 0;
+        // Draw text at the offset, cut off at the edge of the area
+        let mut put = |x_offset: &mut usize, text: &str, style: Style| {
+            if *x_offset < area_width {
+                buf.set_stringn(
+                    area.x + *x_offset as u16,
+                    y,
+                    text,
+                    area_width - *x_offset,
+                    style,
+                );
+            }
+            *x_offset += text.width();
+        };
 
-        for (i, cell) in cells.iter().enumerate() {
-            let width = col_widths.get(i).copied().unwrap_or(10);
+        if layout.borders.left {
+            put(&mut x_offset, &vertical, Style::default());
+        }
 
-            // Apply cell styling
-            let mut style = Style::default();
+        // Rows short of cells are padded out, so the lines down the right line up
+        let columns = cells.len().max(layout.col_widths.len());
+        for i in 0..columns {
+            let width = layout.col_widths.get(i).copied().unwrap_or(10);
+            let Some(cell) = cells.get(i) else {
+                put(&mut x_offset, &" ".repeat(width), line_style);
+                if i + 1 < columns {
+                    put(&mut x_offset, " ", line_style);
+                }
+                continue;
+            };
+
+            // Style each run of the cell over the row's
+            let mut cell_style = line_style;
             if is_header {
-                style = style.add_modifier(Modifier::BOLD);
+                cell_style = cell_style.add_modifier(Modifier::BOLD);
                 if color_enabled {
-                    style = style.fg(Color::Yellow);
+                    cell_style = cell_style.fg(fill_foreground.unwrap_or(Color::Yellow));
                 }
-            } else if color_enabled {
-                if let Some(color_hex) = &cell.formatting.color {
-                    if let Some(color) = hex_to_color(color_hex) {
+            }
+            let run_style = |formatting: &TextFormatting| {
+                let mut style = cell_style;
+                if color_enabled && !is_header {
+                    let color = formatting.color.as_deref().and_then(hex_to_color);
+                    if let Some(color) = color.or(fill_foreground) {
                         style = style.fg(color);
                     }
                 }
-            }
+                if formatting.bold {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+                if formatting.italic {
+                    style = style.add_modifier(Modifier::ITALIC);
+                }
+                if formatting.underline {
+                    style = style.add_modifier(Modifier::UNDERLINED);
+                }
+                if formatting.strikethrough {
+                    style = style.add_modifier(Modifier::CROSSED_OUT);
+                }
+                style
+            };
 
-            // Apply cell formatting
-            if cell.formatting.bold {
-                style = style.add_modifier(Modifier::BOLD);
-            }
-            if cell.formatting.italic {
-                style = style.add_modifier(Modifier::ITALIC);
-            }
-            if cell.formatting.underline {
-                style = style.add_modifier(Modifier::UNDERLINED);
-            }
-            if cell.formatting.strikethrough {
-                style = style.add_modifier(Modifier::CROSSED_OUT);
+            // Truncate content to fit width, at a grapheme boundary, then lay it out
+            // in visual order
+            let truncate = cell.content.width() > width;
+            let mut spans = Vec::new();
+            let mut run_rtl = Vec::new();
+            let mut kept = 0;
+            'runs: for run in cell.formatted_runs().iter() {
+                let style = run_style(&run.formatting);
+                for grapheme in run.text.graphemes(true) {
+                    if truncate && kept + grapheme.width() >= width {
+                        spans.push(Span::styled("…", style));
+                        run_rtl.push(run.formatting.rtl);
+                        break 'runs;
+                    }
+                    kept += grapheme.width();
+                    spans.push(Span::styled(grapheme.to_string(), style));
+                    run_rtl.push(run.formatting.rtl);
+                }
             }
-
-            // Truncate content to fit width
-            let content = if cell.content.len() > width {
-                format!("{}…", &cell.content[..width.saturating_sub(1)])
-            } else {
-                cell.content.clone()
-            };
+            let content = Self::visual_line(spans, &run_rtl, bidi::is_rtl(&cell.content));
 
             // Apply alignment
-            let aligned_content = match cell.alignment {
-                TextAlignment::Left => format!("{content:<width$}"),
-                TextAlignment::Right => format!("{content:>width$}"),
-                TextAlignment::Center => {
-                    let padding = width.saturating_sub(content.len());
-                    let left_pad = padding / 2;
-                    let right_pad = padding - left_pad;
-                    format!(
-                        "{}{}{}",
-                        " ".repeat(left_pad),
-                        content,
-                        " ".repeat(right_pad)
-                    )
-                }
-                TextAlignment::Justify => format!("{content:<width$}"),
+            let padding = width.saturating_sub(content.width());
+            let left_pad = match cell.alignment {
+                TextAlignment::Left | TextAlignment::Justify => 0,
+                TextAlignment::Right => padding,
+                TextAlignment::Center => padding / 2,
             };
-
-            buf.set_string(
-                area.x + x_offset as u16,
-                *current_y,
-                &aligned_content,
-                style,
-            );
-
-            x_offset += width + 1; // +1 for column separator
+            // Cut-off content may fall short of the width
+            let start = x_offset;
+            put(&mut x_offset, &" ".repeat(left_pad), cell_style);
+            for span in &content.spans {
+                put(&mut x_offset, &span.content, span.style);
+            }
+            put(&mut x_offset, &" ".repeat(padding - left_pad), cell_style);
+            x_offset = x_offset.max(start + width);
 
             // Render column separator
-            if i < cells.len() - 1 && x_offset < area.width as usize {
-                buf.set_string(
-                    area.x + x_offset as u16 - 1,
-                    *current_y,
-                    "│",
-                    Style::default(),
-                );
+            if i + 1 < columns {
+                let separator = if layout.borders.inside_vertical {
+                    vertical.as_str()
+                } else {
+                    " "
+                };
+                put(&mut x_offset, separator, line_style);
             }
         }
 
-        *current_y += 1;
+        if layout.borders.right {
+            put(&mut x_offset, &vertical, Style::default());
+        }
     }
 
     /// Render an image placeholder (actual image rendering happens in main render loop)
     fn render_image_placeholder(
         description: &str,
+        note: Option<&str>,
         area: Rect,
         buf: &mut Buffer,
         current_y: &mut u16,
@@ -560,12 +1069,51 @@ impl<'a> DocumentWidget<'a> {
             };
             let desc_text = format!("🖼️  {description}");
             buf.set_string(area.x, *current_y, &desc_text, desc_style);
-            *current_y += 2; // Description + blank line
+            *current_y += 1;
+            Self::render_image_note(note, area, buf, current_y, color_enabled);
+            *current_y += 1; // Blank line
         }
     }
 
-    /// Render a page break element
-    fn render_page_break(area: Rect, buf: &mut Buffer, current_y: &mut u16, color_enabled: bool) {
+    /// Where a floating image was in the document, under its description
+    fn render_image_note(
+        note: Option<&str>,
+        area: Rect,
+        buf: &mut Buffer,
+        current_y: &mut u16,
+        color_enabled: bool,
+    ) {
+        let Some(note) = note else {
+            return;
+        };
+        if *current_y < area.y + area.height {
+            let style = if color_enabled {
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC)
+            } else {
+                Style::default()
+            };
+            buf.set_stringn(
+                area.x,
+                *current_y,
+                format!("   ↳ {note}"),
+                area.width as usize,
+                style,
+            );
+        }
+        *current_y += 1;
+    }
+
+    /// Render a page break, labelled with the page it starts, or a horizontal rule
+    fn render_rule(
+        label: Option<&str>,
+        fill: char,
+        area: Rect,
+        buf: &mut Buffer,
+        current_y: &mut u16,
+        color_enabled: bool,
+    ) {
         if *current_y >= area.y + area.height {
             return;
         }
@@ -576,182 +1124,648 @@ impl<'a> DocumentWidget<'a> {
             Style::default()
         };
 
-        let separator = "─".repeat(area.width as usize);
-        buf.set_string(area.x, *current_y, &separator, style);
-        *current_y += 2; // Page break + blank line
+        let separator = match label {
+            Some(label) => labeled_rule(label, fill, area.width as usize),
+            None => fill.to_string().repeat(area.width as usize),
+        };
+        buf.set_stringn(area.x, *current_y, &separator, area.width as usize, style);
+        *current_y += 2; // Rule + blank line
     }
 
-    /// Custom render method that has access to Frame for complete rendering.
+    /// Number of rows an element occupies, without wrapping any text
     ///
-    /// This method renders all document elements including text (with wrapping) and images.
-    /// Unlike the Widget trait's render method, this has access to Frame which is required
-    /// for rendering StatefulImage widgets.
-    pub fn render(
-        &mut self,
+    /// Exact for everything except paragraphs and lists, where the wrapped line count
+    /// is approximated from the text's display width.
+    fn estimate_height(element: &DocumentElement, width: u16, has_image: bool) -> usize {
+        let width = width.max(1) as usize;
+        let wrapped_rows = |runs: &[FormattedRun], width: usize| {
+            let text_width: usize = runs.iter().map(|run| run.text.width()).sum();
+            text_width.div_ceil(width.max(1)).max(1)
+        };
+
+        match element {
+            DocumentElement::Heading { .. } => 2,
+            DocumentElement::Paragraph { runs, spacing, .. } => {
+                paragraph_rows(wrapped_rows(runs, width), *spacing)
+            }
+            DocumentElement::BlockQuote { runs } => {
+                wrapped_rows(runs, width.saturating_sub(QUOTE_INDENT as usize)) + 1
+            }
+            DocumentElement::CodeBlock { code } => code.lines().count().max(1) + 1,
+            DocumentElement::List { items, ordered } => {
+                let rows: usize = items
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, item)| {
+                        wrapped_rows(
+                            &item.runs,
+                            width.saturating_sub(bullet_width(idx, *ordered)),
+                        )
+                    })
+                    .sum();
+                rows + 1
+            }
+            DocumentElement::Table { table } => table_lines(table).len(),
+            DocumentElement::Image { placement, .. } => {
+                let note = placement.as_ref().is_some_and(|p| p.anchor.is_some());
+                let image = if has_image {
+                    image_size(placement.as_ref(), width as u16).1
+                } else {
+                    0
+                };
+                image as usize + 2 + usize::from(note)
+            }
+            DocumentElement::Equation { .. }
+            | DocumentElement::PageBreak { .. }
+            | DocumentElement::HorizontalRule
+            | DocumentElement::FileBoundary { .. } => 2,
+        }
+    }
+
+    /// Exact number of rows an element occupies, wrapping (and caching) its text if needed
+    ///
+    /// Paragraph lines are cached for rendering, so they are wrapped with the
+    /// viewer's colour setting.
+    fn measure_height(
+        element: &DocumentElement,
+        element_index: usize,
+        width: u16,
+        layout_cache: &mut LayoutCache,
+        has_image: bool,
+        color_enabled: bool,
+    ) -> usize {
+        match element {
+            DocumentElement::Paragraph {
+                runs,
+                rtl,
+                spacing,
+                tab_stops,
+                ..
+            } => {
+                let rows = if let Some(cached) = layout_cache.get(element_index, width) {
+                    cached.len()
+                } else {
+                    let lines = Self::wrap_formatted_runs(
+                        runs,
+                        width as usize,
+                        color_enabled,
+                        &[],
+                        false,
+                        &[],
+                        LineLayout {
+                            rtl: *rtl,
+                            tab_stops,
+                        },
+                    );
+                    let rows = lines.len();
+                    layout_cache.insert(element_index, width, lines);
+                    rows
+                };
+                paragraph_rows(rows, *spacing)
+            }
+            DocumentElement::BlockQuote { runs } => {
+                // Quotes are wrapped and cached like paragraphs, inside the bar
+                let width = width.saturating_sub(QUOTE_INDENT);
+                let rows = if let Some(cached) = layout_cache.get(element_index, width) {
+                    cached.len()
+                } else {
+                    let lines = Self::wrap_formatted_runs(
+                        runs,
+                        width as usize,
+                        color_enabled,
+                        &[],
+                        false,
+                        &[],
+                        LineLayout::default(),
+                    );
+                    let rows = lines.len();
+                    layout_cache.insert(element_index, width, lines);
+                    rows
+                };
+                rows + 1
+            }
+            DocumentElement::List { items, ordered } => {
+                let rows: usize = items
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, item)| {
+                        let text_width =
+                            (width as usize).saturating_sub(bullet_width(idx, *ordered));
+                        // Only the row count matters here, not the direction
+                        Self::wrap_formatted_runs(
+                            &item.runs,
+                            text_width,
+                            false,
+                            &[],
+                            false,
+                            &[],
+                            LineLayout::default(),
+                        )
+                        .len()
+                        .max(1)
+                    })
+                    .sum();
+                rows + 1
+            }
+            _ => Self::estimate_height(element, width, has_image),
+        }
+    }
+
+    /// Build the height index for this document if the cache doesn't have one for this width
+    fn ensure_layout(&self, width: u16, image_count: usize, layout_cache: &mut LayoutCache) {
+        if layout_cache.has_layout(self.elements.len()) {
+            return;
+        }
+
+        let mut next_slot = 0;
+        let image_slots: Vec<Option<usize>> = self
+            .elements
+            .iter()
+            .map(|element| match element {
+                DocumentElement::Image {
+                    image_path: Some(_),
+                    ..
+                } => {
+                    let slot = next_slot;
+                    next_slot += 1;
+                    (slot < image_count).then_some(slot)
+                }
+                _ => None,
+            })
+            .collect();
+
+        let heights = self
+            .elements
+            .iter()
+            .zip(&image_slots)
+            .enumerate()
+            .map(|(element_index, (element, slot))| {
+                Self::estimate_height(element, width, slot.is_some())
+                    + self.section_marker_height(element_index)
+                    + self.chart_height(element)
+            })
+            .collect();
+
+        layout_cache.init_layout(heights, image_slots);
+    }
+
+    /// Measure an element's exact height if only an estimate is known so far
+    fn ensure_measured(&self, element_index: usize, width: u16, layout_cache: &mut LayoutCache) {
+        if layout_cache.is_measured(element_index) {
+            return;
+        }
+        let has_image = layout_cache.image_slot(element_index).is_some();
+        let height = Self::measure_height(
+            &self.elements[element_index],
+            element_index,
+            width,
+            layout_cache,
+            has_image,
+            self.color_enabled,
+        ) + self.section_marker_height(element_index)
+            + self.chart_height(&self.elements[element_index]);
+        layout_cache.set_height(element_index, height);
+    }
+
+    /// Rows taken by the section marker above an element, if it has one
+    fn section_marker_height(&self, element_index: usize) -> usize {
+        if self.section_marker(element_index).is_some() {
+            2 // Marker + blank line
+        } else {
+            0
+        }
+    }
+
+    /// Rows taken by the charts below a table, if they're shown
+    fn chart_height(&self, element: &DocumentElement) -> usize {
+        match (element, self.charts) {
+            (DocumentElement::Table { table }, Some(style)) => {
+                match chart::chart_height(table, self.locale, style) {
+                    0 => 0,
+                    lines => lines + 1, // Charts + blank line
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    /// Render one element at `current_y`, queueing its image (if any) for the Frame pass
+    fn render_element(
+        &self,
+        element_index: usize,
         area: Rect,
-        frame: &mut Frame,
-        image_protocols: &mut [StatefulProtocol],
+        buf: &mut Buffer,
+        current_y: &mut u16,
         layout_cache: &mut LayoutCache,
+        images_to_render: &mut Vec<(Rect, usize)>,
     ) {
-        let buf = frame.buffer_mut();
+        if let Some(marker) = self.section_marker(element_index) {
+            if *current_y < area.y + area.height {
+                let style = if self.color_enabled {
+                    Style::default().fg(Color::DarkGray)
+                } else {
+                    Style::default()
+                };
+                buf.set_stringn(area.x, *current_y, &marker, area.width as usize, style);
+            }
+            *current_y += 2; // Marker + blank line
+        }
 
-        // Check if terminal width changed and invalidate cache if needed
-        layout_cache.check_width(area.width);
+        // Extract search matches for this element
+        let search_matches: Vec<(usize, usize)> = self
+            .search_results
+            .iter()
+            .filter(|result| result.element_index == element_index)
+            .map(|result| (result.start_pos, result.end_pos))
+            .collect();
+
+        let misspellings: Vec<&Misspelling> = self
+            .misspellings
+            .iter()
+            .filter(|misspelling| misspelling.element_index == element_index)
+            .collect();
+
+        // Check if this element contains the current search match
+        let is_current_match = !self.search_results.is_empty()
+            && self.current_search_index < self.search_results.len()
+            && self.search_results[self.current_search_index].element_index == element_index;
+
+        match &self.elements[element_index] {
+            DocumentElement::Heading {
+                level,
+                text,
+                number,
+            } => {
+                let y = *current_y;
+                let end = Self::render_heading(
+                    text,
+                    *level,
+                    number.as_deref(),
+                    area,
+                    buf,
+                    current_y,
+                    self.color_enabled,
+                );
+                let folded = self.folded_count(element_index);
+                if folded > 0 && y < area.y + area.height {
+                    let style = if self.color_enabled {
+                        Style::default().fg(Color::DarkGray)
+                    } else {
+                        Style::default().add_modifier(Modifier::DIM)
+                    };
+                    let noun = if folded == 1 { "block" } else { "blocks" };
+                    let width = (area.x + area.width).saturating_sub(end) as usize;
+                    buf.set_stringn(end, y, format!(" ⋯ {folded} {noun} folded"), width, style);
+                }
+            }
 
-        // Start rendering from the top of the area
-        let mut current_y = area.y;
+            DocumentElement::Paragraph {
+                runs,
+                shading,
+                rtl,
+                spacing,
+                tab_stops,
+                ..
+            } => {
+                let layout = spacing.unwrap_or_default();
+                *current_y = current_y.saturating_add(layout.before as u16);
+                let top = *current_y;
+                let mut ctx = RenderContext {
+                    color_enabled: self.color_enabled,
+                    search_matches: &search_matches,
+                    is_current_match,
+                    misspellings: &misspellings,
+                    element_index,
+                    layout_cache,
+                };
+                Self::render_paragraph(
+                    runs,
+                    shading.as_deref(),
+                    LineLayout {
+                        rtl: *rtl,
+                        tab_stops,
+                    },
+                    area,
+                    buf,
+                    current_y,
+                    &mut ctx,
+                );
+                // An empty paragraph kept for its spacing is a blank line
+                if spacing.is_some() && *current_y == top {
+                    *current_y += 1;
+                }
+                *current_y = current_y.saturating_add(layout.after as u16);
+            }
 
-        // Skip elements based on scroll offset
-        let visible_elements = self.elements.iter().enumerate().skip(self.scroll_offset);
+            DocumentElement::List { items, ordered } => {
+                let mut ctx = RenderContext {
+                    color_enabled: self.color_enabled,
+                    search_matches: &search_matches,
+                    is_current_match,
+                    misspellings: &misspellings,
+                    element_index,
+                    layout_cache,
+                };
+                Self::render_list(items, *ordered, area, buf, current_y, &mut ctx);
+            }
 
-        // Track image positions and protocol indices for rendering
-        let mut images_to_render: Vec<(u16, usize)> = Vec::new(); // (y_position, protocol_index)
-        let mut protocol_idx = 0;
+            DocumentElement::BlockQuote { runs } => {
+                let mut ctx = RenderContext {
+                    color_enabled: self.color_enabled,
+                    search_matches: &search_matches,
+                    is_current_match,
+                    misspellings: &misspellings,
+                    element_index,
+                    layout_cache,
+                };
+                Self::render_block_quote(runs, area, buf, current_y, &mut ctx);
+            }
 
-        // Render each visible element
-        for (element_index, element) in visible_elements {
-            // Stop if we've reached the bottom of the area
-            if current_y >= area.y + area.height {
-                break;
+            DocumentElement::CodeBlock { code } => {
+                Self::render_code_block(code, area, buf, current_y, self.color_enabled);
             }
 
-            // Extract search matches for this element
-            let search_matches: Vec<(usize, usize)> = self
-                .search_results
-                .iter()
-                .filter(|result| result.element_index == element_index)
-                .map(|result| (result.start_pos, result.end_pos))
-                .collect();
+            DocumentElement::Table { table } => {
+                self.render_table_element(table, 0, area, buf, current_y);
+            }
 
-            // Check if this element contains the current search match
-            let is_current_match = !self.search_results.is_empty()
-                && self.current_search_index < self.search_results.len()
-                && self.search_results[self.current_search_index].element_index == element_index;
+            DocumentElement::Image {
+                description,
+                image_path,
+                caption,
+                placement,
+                ..
+            } => {
+                // A caption says more about the figure than the alt text does
+                let description = caption.as_ref().unwrap_or(description);
+                let note = placement.as_ref().and_then(ImagePlacement::describe);
+                // Check if we can render this image
+                if let Some(slot) = layout_cache.image_slot(element_index) {
+                    // Store image position for rendering after text, drawn at
+                    // its size in the document and on the side it floated to
+                    let (columns, rows) = image_size(placement.as_ref(), area.width);
+                    let x = match placement.as_ref().and_then(ImagePlacement::float_side) {
+                        Some("right") => area.x + area.width - columns,
+                        _ => area.x,
+                    };
+                    images_to_render.push((Rect::new(x, *current_y, columns, rows), slot));
 
-            match element {
-                DocumentElement::Heading {
-                    level,
-                    text,
-                    number,
-                } => {
-                    Self::render_heading(
-                        text,
-                        *level,
-                        number.as_deref(),
+                    // Reserve space for the image
+                    Self::render_image_placeholder(
+                        description,
+                        note.as_deref(),
                         area,
                         buf,
-                        &mut current_y,
+                        current_y,
                         self.color_enabled,
+                        rows,
                     );
-                }
-
-                DocumentElement::Paragraph { runs } => {
-                    let mut ctx = RenderContext {
-                        color_enabled: self.color_enabled,
-                        search_matches: &search_matches,
-                        is_current_match,
-                        element_index,
-                        layout_cache,
+                } else {
+                    // Render text-only placeholder
+                    let status = if image_path.is_some() {
+                        " [Image available - use --images flag]"
+                    } else {
+                        " [Image not extracted]"
                     };
-                    Self::render_paragraph(runs, area, buf, &mut current_y, &mut ctx);
+                    let desc_text = format!("🖼️  {description}{status}");
+                    buf.set_string(area.x, *current_y, &desc_text, Style::default());
+                    *current_y += 1;
+                    Self::render_image_note(
+                        note.as_deref(),
+                        area,
+                        buf,
+                        current_y,
+                        self.color_enabled,
+                    );
+                    *current_y += 1;
                 }
+            }
 
-                DocumentElement::List { items, ordered } => {
-                    let mut ctx = RenderContext {
-                        color_enabled: self.color_enabled,
-                        search_matches: &search_matches,
-                        is_current_match,
-                        element_index,
-                        layout_cache,
-                    };
-                    Self::render_list(items, *ordered, area, buf, &mut current_y, &mut ctx);
+            DocumentElement::Equation { latex, number, .. } => {
+                if *current_y >= area.y + area.height {
+                    return;
                 }
 
-                DocumentElement::Table { table } => {
-                    Self::render_table(table, area, buf, &mut current_y, self.color_enabled);
-                }
+                // Render equation as formatted text with icon
+                let icon_style = if self.color_enabled {
+                    Style::default().fg(Color::Cyan)
+                } else {
+                    Style::default()
+                };
 
-                DocumentElement::Image {
-                    description,
-                    image_path,
-                    ..
-                } => {
-                    // Check if we can render this image
-                    if image_path.is_some() && protocol_idx < image_protocols.len() {
-                        // Store image position for rendering after text
-                        let image_y = current_y;
-                        images_to_render.push((image_y, protocol_idx));
-
-                        // Reserve space for the image
-                        Self::render_image_placeholder(
-                            description,
-                            area,
-                            buf,
-                            &mut current_y,
-                            self.color_enabled,
-                            15, // Standard image height
-                        );
-
-                        protocol_idx += 1;
-                    } else {
-                        // Render text-only placeholder
-                        let status = if image_path.is_some() {
-                            " [Image available - use --images flag]"
-                        } else {
-                            " [Image not extracted]"
-                        };
-                        let desc_text = format!("🖼️  {description}{status}");
-                        buf.set_string(area.x, current_y, &desc_text, Style::default());
-                        current_y += 2;
-                    }
+                let latex_style = if self.color_enabled {
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().add_modifier(Modifier::BOLD)
+                };
+
+                // Build the equation line
+                let line = Line::from(vec![
+                    Span::styled("📐 ", icon_style),
+                    Span::styled(latex, latex_style),
+                ]);
+
+                buf.set_line(area.x, *current_y, &line, area.width);
+
+                // The number sits at the right edge, as at Word's right tab
+                if let Some(number) = number {
+                    let width = (number.width() as u16).min(area.width);
+                    let x = area.x + area.width - width;
+                    buf.set_stringn(x, *current_y, number, width as usize, icon_style);
                 }
+                *current_y += 2; // Equation + blank line
+            }
 
-                DocumentElement::Equation { latex, .. } => {
-                    if current_y >= area.y + area.height {
-                        continue;
-                    }
+            DocumentElement::PageBreak { page } => {
+                let label = format!("page {page}");
+                Self::render_rule(Some(&label), '─', area, buf, current_y, self.color_enabled);
+            }
 
-                    // Render equation as formatted text with icon
-                    let icon_style = if self.color_enabled {
-                        Style::default().fg(Color::Cyan)
-                    } else {
-                        Style::default()
-                    };
+            DocumentElement::HorizontalRule => {
+                Self::render_rule(None, '─', area, buf, current_y, self.color_enabled);
+            }
 
-                    let latex_style = if self.color_enabled {
-                        Style::default()
-                            .fg(Color::White)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().add_modifier(Modifier::BOLD)
-                    };
+            DocumentElement::FileBoundary { name } => {
+                Self::render_rule(Some(name), '═', area, buf, current_y, self.color_enabled);
+            }
+        }
+    }
+
+    /// Render a table from `skip` lines in, and its charts below it if they're shown
+    fn render_table_element(
+        &self,
+        table: &TableData,
+        skip: usize,
+        area: Rect,
+        buf: &mut Buffer,
+        current_y: &mut u16,
+    ) {
+        Self::render_table(table, skip, area, buf, current_y, self.color_enabled);
+        if let Some(style) = self.charts {
+            let lines = chart::table_charts(table, self.locale, style, area.width.into());
+            if !lines.is_empty() {
+                Self::render_charts(&lines, area, buf, current_y, self.color_enabled);
+            }
+        }
+    }
+
+    /// The body row at the top of the view, 1-based, and the number of body rows,
+    /// when the view starts `line_offset` lines into a table at `element_index`
+    ///
+    /// Rows under the repeated header row don't count as in view.
+    pub fn table_row_in_view(
+        &self,
+        element_index: usize,
+        line_offset: usize,
+    ) -> Option<(usize, usize)> {
+        let DocumentElement::Table { table } = self.elements.get(element_index)? else {
+            return None;
+        };
+        let skip = line_offset.saturating_sub(self.section_marker_height(element_index));
+        visible_table_lines(&table_lines(table), skip)
+            .into_iter()
+            .find_map(|line| match line {
+                TableLine::Row(index) => Some((index + 1, table.rows.len())),
+                _ => None,
+            })
+    }
+
+    /// Custom render method that has access to Frame for complete rendering.
+    ///
+    /// This method renders the visible document elements including text (with wrapping) and
+    /// images. Unlike the Widget trait's render method, this has access to Frame which is
+    /// required for rendering StatefulImage widgets.
+    pub fn render(
+        &mut self,
+        area: Rect,
+        frame: &mut Frame,
+        image_protocols: &mut [StatefulProtocol],
+        layout_cache: &mut LayoutCache,
+    ) {
+        if self.elements.is_empty() || area.height == 0 {
+            return;
+        }
+
+        let buf = frame.buffer_mut();
+        layout_cache.begin_frame();
+
+        // Check if terminal width changed and invalidate cache if needed
+        layout_cache.check_width(area.width);
+        self.ensure_layout(area.width, image_protocols.len(), layout_cache);
+        layout_cache.set_hidden(self.hidden);
+
+        let bottom = area.y + area.height;
+        let first = self.scroll_offset.min(self.elements.len() - 1);
+        let mut last = first;
 
-                    // Build the equation line
-                    let line = Line::from(vec![
-                        Span::styled("📐 ", icon_style),
-                        Span::styled(latex, latex_style),
-                    ]);
+        // Start rendering from the top of the area
+        let mut current_y = area.y;
 
-                    buf.set_line(area.x, current_y, &line, area.width);
-                    current_y += 2; // Equation + blank line
+        // Track image positions and protocol indices for rendering
+        let mut images_to_render: Vec<(Rect, usize)> = Vec::new(); // (area, protocol_index)
+
+        for element_index in first..self.elements.len() {
+            // Stop if we've reached the bottom of the area
+            if current_y >= bottom {
+                break;
+            }
+            last = element_index;
+            if layout_cache.is_hidden(element_index) {
+                continue;
+            }
+            self.ensure_measured(element_index, area.width, layout_cache);
+
+            let height = layout_cache.height(element_index).unwrap_or(1);
+            let skip = if element_index == first {
+                self.scroll_line_offset.min(height.saturating_sub(1))
+            } else {
+                0
+            };
+
+            // A table is drawn from where it's scrolled to, so that its header row can
+            // be repeated at the top
+            if let DocumentElement::Table { table } = &self.elements[element_index] {
+                let table_skip = skip
+                    .checked_sub(self.section_marker_height(element_index))
+                    .filter(|&table_skip| table_skip > 0 && table_skip < table_lines(table).len());
+                if let Some(table_skip) = table_skip {
+                    self.render_table_element(table, table_skip, area, buf, &mut current_y);
+                    continue;
                 }
+            }
 
-                DocumentElement::PageBreak => {
-                    Self::render_page_break(area, buf, &mut current_y, self.color_enabled);
+            if skip == 0 {
+                self.render_element(
+                    element_index,
+                    area,
+                    buf,
+                    &mut current_y,
+                    layout_cache,
+                    &mut images_to_render,
+                );
+                continue;
+            }
+
+            // The first element is partly scrolled off: render it into a scratch buffer
+            // and copy over only the rows that remain visible. Its image (if any) is not
+            // drawn while partially hidden; the reserved space and caption still are.
+            let scratch_height = height
+                .min(skip + area.height as usize)
+                .min(u16::MAX as usize);
+            let scratch_area = Rect {
+                x: area.x,
+                y: 0,
+                width: area.width,
+                height: scratch_height as u16,
+            };
+            let mut scratch = Buffer::empty(scratch_area);
+            let mut scratch_y = 0;
+            self.render_element(
+                element_index,
+                scratch_area,
+                &mut scratch,
+                &mut scratch_y,
+                layout_cache,
+                &mut Vec::new(),
+            );
+
+            for row in skip..scratch_height {
+                if current_y >= bottom {
+                    break;
+                }
+                for x in area.x..area.x + area.width {
+                    if let (Some(src), Some(dst)) =
+                        (scratch.cell((x, row as u16)), buf.cell_mut((x, current_y)))
+                    {
+                        *dst = src.clone();
+                    }
                 }
+                current_y += 1;
             }
         }
 
+        // Firm up the height index just beyond the viewport so the next scroll step
+        // (and the scrollbar) work from measured rather than estimated heights
+        let margin_end = (last + 1 + LAYOUT_MARGIN).min(self.elements.len());
+        for element_index in
+            (last + 1..margin_end).chain(first.saturating_sub(LAYOUT_MARGIN)..first)
+        {
+            self.ensure_measured(element_index, area.width, layout_cache);
+        }
+
+        let elements_rendered = last + 1 - first;
+        layout_cache.end_frame(elements_rendered, images_to_render.len());
+
         // Now render all images using Frame (after text has been rendered to buffer)
-        for (y_pos, proto_idx) in images_to_render {
+        for (image_area, proto_idx) in images_to_render {
             if let Some(protocol) = image_protocols.get_mut(proto_idx) {
                 // Ensure image is within visible area
-                if y_pos < area.y + area.height {
+                if image_area.y < bottom {
                     let img_rect = Rect {
-                        x: area.x,
-                        y: y_pos,
-                        width: area.width.min(80),
-                        height: 15.min(area.y + area.height - y_pos),
+                        height: image_area.height.min(bottom - image_area.y),
+                        ..image_area
                     };
 
                     let image_widget = StatefulImage::new();
@@ -762,6 +1776,53 @@ impl<'a> DocumentWidget<'a> {
     }
 }
 
+/// Columns a block quote's bar and the space after it take
+const QUOTE_INDENT: u16 = 2;
+
+/// Rows reserved for an inline image
+const IMAGE_HEIGHT: u16 = 15;
+
+/// Fewest rows an image is drawn in, however small it is in the document
+const MIN_IMAGE_HEIGHT: u16 = 4;
+
+/// Columns and rows to draw an image in: its size in the document, within
+/// the usual bounds, or the default size when the size isn't known
+fn image_size(placement: Option<&ImagePlacement>, width: u16) -> (u16, u16) {
+    let max_columns = width.min(80);
+    match placement {
+        Some(placement) => (
+            placement.columns.clamp(1, max_columns.max(1)),
+            placement.rows.clamp(MIN_IMAGE_HEIGHT, IMAGE_HEIGHT),
+        ),
+        None => (max_columns, IMAGE_HEIGHT),
+    }
+}
+
+/// Elements measured beyond each edge of the viewport on every frame
+const LAYOUT_MARGIN: usize = 8;
+
+/// Rows a paragraph of `lines` wrapped lines takes, with the blank lines around it
+///
+/// With spacing preserved, an empty paragraph is a blank line of its own.
+fn paragraph_rows(lines: usize, spacing: Option<ParagraphSpacing>) -> usize {
+    let layout = spacing.unwrap_or_default();
+    let lines = if spacing.is_some() {
+        lines.max(1)
+    } else {
+        lines
+    };
+    layout.before + lines + layout.after
+}
+
+/// Width of the bullet or number prefix for a list item
+fn bullet_width(idx: usize, ordered: bool) -> usize {
+    if ordered {
+        format!("{}. ", idx + 1).len()
+    } else {
+        "• ".width()
+    }
+}
+
 /// Convert hex color code to ratatui Color
 fn hex_to_color(hex: &str) -> Option<Color> {
     let hex = hex.trim_start_matches('#');
@@ -1,30 +1,96 @@
+mod banner;
 mod document;
 
 use ratatui::text::Line;
+use std::cell::Cell;
 use std::collections::HashMap;
 
+pub use banner::{banner, BANNER_HEIGHT};
 pub use document::DocumentWidget;
 
 /// Cache for wrapped text lines to avoid re-wrapping on every frame
+///
+/// Also keeps a virtual layout of the whole document at the current width: the
+/// height of every element (estimated until the element has been wrapped for real)
+/// in a Fenwick tree, so the top-level line of any element, the element under any
+/// line, and the total document height are all O(log n) lookups.
+///
+/// Elements inside folded sections are hidden: they count as zero rows in the
+/// tree, so every lookup skips them, while their own heights are kept for when
+/// they're shown again.
 #[derive(Debug, Default)]
 pub struct LayoutCache {
     /// Cached wrapped lines: (element_index, terminal_width) -> Vec<Line>
     cache: HashMap<(usize, u16), Vec<Line<'static>>>,
     /// Last known terminal width for invalidation
     last_width: u16,
+    /// Rendered height of each element in rows at `last_width`
+    heights: Vec<usize>,
+    /// Whether each entry of `heights` is exact or still an estimate
+    measured: Vec<bool>,
+    /// 1-based Fenwick tree over `heights`, with hidden elements as zero
+    tree: Vec<usize>,
+    /// Whether each element is hidden by a fold; empty when nothing is
+    hidden: Vec<bool>,
+    /// Image protocol slot for each element that renders an inline image
+    image_slots: Vec<Option<usize>>,
+    /// Counters for the frame being drawn, or the last one
+    frame: Cell<FrameStats>,
+}
+
+/// What the last frame of the document view cost, for the debug overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Wrapped-line lookups answered from the cache
+    pub cache_hits: usize,
+    /// Wrapped-line lookups that had to wrap the element
+    pub cache_misses: usize,
+    /// Elements drawn into the viewport
+    pub elements_rendered: usize,
+    /// Inline images drawn into the viewport
+    pub images_drawn: usize,
+}
+
+impl FrameStats {
+    /// Share of cache lookups that hit, if there were any
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.cache_hits + self.cache_misses;
+        (lookups > 0).then(|| self.cache_hits as f64 / lookups as f64)
+    }
 }
 
 impl LayoutCache {
     pub fn new() -> Self {
-        Self {
-            cache: HashMap::new(),
-            last_width: 0,
-        }
+        Self::default()
     }
 
     /// Get cached lines for an element, if available
     pub fn get(&self, element_index: usize, width: u16) -> Option<&Vec<Line<'static>>> {
-        self.cache.get(&(element_index, width))
+        let lines = self.cache.get(&(element_index, width));
+        let mut frame = self.frame.get();
+        match lines {
+            Some(_) => frame.cache_hits += 1,
+            None => frame.cache_misses += 1,
+        }
+        self.frame.set(frame);
+        lines
+    }
+
+    /// Reset the per-frame counters before drawing a frame
+    pub fn begin_frame(&mut self) {
+        self.frame.set(FrameStats::default());
+    }
+
+    /// Record how many elements and images the frame drew
+    pub fn end_frame(&mut self, elements_rendered: usize, images_drawn: usize) {
+        let frame = self.frame.get_mut();
+        frame.elements_rendered = elements_rendered;
+        frame.images_drawn = images_drawn;
+    }
+
+    /// Counters for the last frame drawn
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame.get()
     }
 
     /// Store wrapped lines for an element
@@ -32,11 +98,289 @@ impl LayoutCache {
         self.cache.insert((element_index, width), lines);
     }
 
+    /// Carry wrapped lines over to a reloaded document
+    ///
+    /// `matches[i]` is the old index of new element `i` if it is unchanged.
+    /// The height index is rebuilt on the next frame, measuring the carried-over
+    /// elements from their cached lines.
+    pub fn remap(&mut self, matches: &[Option<usize>]) {
+        let new_index: HashMap<usize, usize> = matches
+            .iter()
+            .enumerate()
+            .filter_map(|(new, old)| old.map(|old| (old, new)))
+            .collect();
+        self.cache = std::mem::take(&mut self.cache)
+            .into_iter()
+            .filter_map(|((old, width), lines)| {
+                new_index.get(&old).map(|&new| ((new, width), lines))
+            })
+            .collect();
+        self.heights.clear();
+        self.measured.clear();
+        self.tree.clear();
+        self.image_slots.clear();
+    }
+
     /// Invalidate cache if terminal width changed
     pub fn check_width(&mut self, width: u16) {
         if width != self.last_width {
             self.cache.clear();
+            self.heights.clear();
+            self.measured.clear();
+            self.tree.clear();
+            self.image_slots.clear();
             self.last_width = width;
         }
     }
+
+    /// Whether the height index covers a document of `len` elements
+    pub fn has_layout(&self, len: usize) -> bool {
+        len > 0 && self.heights.len() == len
+    }
+
+    /// Seed the height index with estimated heights (one per element)
+    pub fn init_layout(&mut self, heights: Vec<usize>, image_slots: Vec<Option<usize>>) {
+        let n = heights.len();
+        let mut tree = vec![0; n + 1];
+        for (i, &height) in heights.iter().enumerate() {
+            let node = i + 1;
+            if !self.is_hidden(i) {
+                tree[node] += height;
+            }
+            let parent = node + lowest_bit(node);
+            if parent <= n {
+                tree[parent] += tree[node];
+            }
+        }
+
+        self.measured = vec![false; n];
+        self.heights = heights;
+        self.tree = tree;
+        self.image_slots = image_slots;
+    }
+
+    /// Current height of an element, exact or estimated; zero while it's hidden
+    pub fn height(&self, element_index: usize) -> Option<usize> {
+        let height = self.heights.get(element_index).copied()?;
+        Some(if self.is_hidden(element_index) {
+            0
+        } else {
+            height
+        })
+    }
+
+    pub fn is_hidden(&self, element_index: usize) -> bool {
+        self.hidden.get(element_index).copied().unwrap_or(false)
+    }
+
+    /// Hide the elements of folded sections and show the rest, one flag per
+    /// element; an empty slice shows everything
+    pub fn set_hidden(&mut self, hidden: &[bool]) {
+        if hidden == self.hidden.as_slice() {
+            return;
+        }
+        let was_hidden = std::mem::replace(&mut self.hidden, hidden.to_vec());
+        for element_index in 0..self.heights.len() {
+            let was = was_hidden.get(element_index).copied().unwrap_or(false);
+            if was != self.is_hidden(element_index) {
+                let height = self.heights[element_index];
+                if was {
+                    self.add_to_tree(element_index, height as isize);
+                } else {
+                    self.add_to_tree(element_index, -(height as isize));
+                }
+            }
+        }
+    }
+
+    pub fn is_measured(&self, element_index: usize) -> bool {
+        self.measured.get(element_index).copied().unwrap_or(false)
+    }
+
+    pub fn image_slot(&self, element_index: usize) -> Option<usize> {
+        self.image_slots.get(element_index).copied().flatten()
+    }
+
+    /// Replace an element's estimated height with its measured one
+    pub fn set_height(&mut self, element_index: usize, height: usize) {
+        let Some(old) = self.heights.get(element_index).copied() else {
+            return;
+        };
+        self.measured[element_index] = true;
+        if old == height {
+            return;
+        }
+
+        self.heights[element_index] = height;
+        if !self.is_hidden(element_index) {
+            self.add_to_tree(element_index, height as isize - old as isize);
+        }
+    }
+
+    /// Change an element's row count in the tree
+    fn add_to_tree(&mut self, element_index: usize, delta: isize) {
+        let mut node = element_index + 1;
+        while node < self.tree.len() {
+            self.tree[node] = self.tree[node].saturating_add_signed(delta);
+            node += lowest_bit(node);
+        }
+    }
+
+    /// Row at which an element starts, counted from the top of the document
+    pub fn line_of_element(&self, element_index: usize) -> usize {
+        let mut node = element_index.min(self.heights.len());
+        let mut sum = 0;
+        while node > 0 {
+            sum += self.tree[node];
+            node -= lowest_bit(node);
+        }
+        sum
+    }
+
+    /// Total height of the document in rows
+    pub fn total_lines(&self) -> usize {
+        self.line_of_element(self.heights.len())
+    }
+
+    /// Element containing the given document row, and the row's offset within it
+    ///
+    /// Rows past the end clamp to the last row of the last element.
+    pub fn element_at_line(&self, line: usize) -> (usize, usize) {
+        let n = self.heights.len();
+        if n == 0 {
+            return (0, 0);
+        }
+
+        // Walk down the tree to the last element whose start row is <= line
+        let mut pos = 0;
+        let mut remaining = line;
+        let mut step = 1 << (usize::BITS - 1 - n.leading_zeros());
+        while step > 0 {
+            if pos + step <= n && self.tree[pos + step] <= remaining {
+                pos += step;
+                remaining -= self.tree[pos];
+            }
+            step >>= 1;
+        }
+
+        if pos >= n {
+            // The last element that's shown
+            let last = (0..n).rev().find(|&i| !self.is_hidden(i)).unwrap_or(n - 1);
+            (last, self.height(last).unwrap_or(0).saturating_sub(1))
+        } else {
+            (pos, remaining)
+        }
+    }
+}
+
+fn lowest_bit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(heights: &[usize]) -> LayoutCache {
+        let mut cache = LayoutCache::new();
+        cache.init_layout(heights.to_vec(), vec![None; heights.len()]);
+        cache
+    }
+
+    #[test]
+    fn test_remap_keeps_lines_of_unchanged_elements() {
+        let mut cache = layout(&[1, 1, 1]);
+        for index in 0..3 {
+            cache.insert(index, 80, vec![Line::from(format!("element {index}"))]);
+        }
+
+        // Element 0 was deleted and a new element inserted after element 2
+        cache.remap(&[Some(1), Some(2), None]);
+        assert_eq!(cache.get(0, 80).unwrap()[0], Line::from("element 1"));
+        assert_eq!(cache.get(1, 80).unwrap()[0], Line::from("element 2"));
+        assert!(cache.get(2, 80).is_none());
+        assert!(!cache.has_layout(3));
+    }
+
+    #[test]
+    fn test_prefix_sums_and_lookup() {
+        let cache = layout(&[2, 3, 1, 4, 2]);
+        assert_eq!(cache.total_lines(), 12);
+        assert_eq!(cache.line_of_element(0), 0);
+        assert_eq!(cache.line_of_element(3), 6);
+
+        assert_eq!(cache.element_at_line(0), (0, 0));
+        assert_eq!(cache.element_at_line(1), (0, 1));
+        assert_eq!(cache.element_at_line(2), (1, 0));
+        assert_eq!(cache.element_at_line(5), (2, 0));
+        assert_eq!(cache.element_at_line(9), (3, 3));
+        assert_eq!(cache.element_at_line(11), (4, 1));
+        // Past the end clamps to the last row
+        assert_eq!(cache.element_at_line(100), (4, 1));
+    }
+
+    #[test]
+    fn test_measured_heights_update_index() {
+        let mut cache = layout(&[2; 1000]);
+        assert!(!cache.is_measured(500));
+
+        cache.set_height(500, 10);
+        cache.set_height(10, 1);
+        assert!(cache.is_measured(500));
+        assert_eq!(cache.total_lines(), 2000 + 8 - 1);
+        assert_eq!(cache.line_of_element(501), 1000 - 1 + 10);
+        assert_eq!(cache.element_at_line(999 + 9), (500, 9));
+        assert_eq!(cache.element_at_line(999 + 10), (501, 0));
+    }
+
+    #[test]
+    fn test_hidden_elements_take_no_rows() {
+        let mut cache = layout(&[2, 3, 1, 4, 2]);
+        cache.set_hidden(&[false, true, true, false, false]);
+        assert_eq!(cache.total_lines(), 8);
+        assert_eq!(cache.height(1), Some(0));
+        assert_eq!(cache.line_of_element(3), 2);
+        assert_eq!(cache.element_at_line(2), (3, 0));
+
+        // Measuring a hidden element keeps it out of the index until it's shown
+        cache.set_height(2, 5);
+        assert_eq!(cache.total_lines(), 8);
+        cache.set_hidden(&[]);
+        assert_eq!(cache.total_lines(), 16);
+        assert_eq!(cache.element_at_line(5), (2, 0));
+
+        // Hiding the tail clamps lookups past the end to the last element shown
+        cache.set_hidden(&[false, false, false, true, true]);
+        assert_eq!(cache.element_at_line(100), (2, 4));
+    }
+
+    #[test]
+    fn test_frame_stats_count_cache_lookups() {
+        let mut cache = LayoutCache::new();
+        cache.begin_frame();
+        assert_eq!(cache.frame_stats().hit_rate(), None);
+
+        assert!(cache.get(0, 80).is_none());
+        cache.insert(0, 80, vec![Line::from("text")]);
+        assert!(cache.get(0, 80).is_some());
+        assert!(cache.get(0, 80).is_some());
+        cache.end_frame(5, 1);
+
+        let stats = cache.frame_stats();
+        assert_eq!((stats.cache_hits, stats.cache_misses), (2, 1));
+        assert_eq!((stats.elements_rendered, stats.images_drawn), (5, 1));
+        assert!((stats.hit_rate().unwrap() - 2.0 / 3.0).abs() < 1e-9);
+
+        cache.begin_frame();
+        assert_eq!(cache.frame_stats(), FrameStats::default());
+    }
+
+    #[test]
+    fn test_width_change_discards_layout() {
+        let mut cache = layout(&[1, 2, 3]);
+        cache.check_width(80);
+        assert!(!cache.has_layout(3));
+        assert_eq!(cache.total_lines(), 0);
+        assert_eq!(cache.element_at_line(5), (0, 0));
+    }
 }
@@ -0,0 +1,988 @@
+//! Line wrapping shared by the text, Markdown and ANSI exporters
+//!
+//! Text arrives as a list of segments (one per formatted run), so words that
+//! span several runs stay intact and callers can re-apply each run's styling
+//! to the pieces of every output line.
+
+use std::ops::Range;
+use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::document::{TabAlignment, TabStop, TextAlignment, TWIPS_PER_COLUMN};
+use crate::LineBreaking;
+
+/// How exported paragraphs are broken into lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    /// Reflow to at most this many columns
+    Columns(usize),
+    /// Keep each paragraph on one line (explicit line breaks are kept)
+    None,
+    /// One sentence per line ("semantic line breaks")
+    Semantic,
+}
+
+impl FromStr for WrapMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "semantic" => Ok(Self::Semantic),
+            cols => match cols.parse() {
+                Ok(cols) if cols > 0 => Ok(Self::Columns(cols)),
+                _ => Err(format!(
+                    "expected a column count, 'none' or 'semantic', got '{s}'"
+                )),
+            },
+        }
+    }
+}
+
+/// How [`WrapMode::Columns`] picks the points where lines break
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BreakOptions {
+    /// Greedy first-fit, or optimal over the whole paragraph
+    pub breaking: LineBreaking,
+    /// Also break after hyphens and at soft hyphens inside words (optimal breaking only)
+    pub hyphenate: bool,
+    /// Score lines by how far justification will stretch their gaps (optimal breaking only)
+    pub justify: bool,
+    /// Also break between Chinese, Japanese and Korean characters, which are
+    /// written without spaces between words
+    pub ideographic: bool,
+}
+
+/// A whitespace-delimited word, possibly made of pieces from several segments
+#[derive(Debug, Clone, PartialEq)]
+pub struct Word<'a> {
+    /// `(segment index, text)` pieces in reading order
+    pub pieces: Vec<(usize, &'a str)>,
+    /// Display width in terminal columns
+    pub width: usize,
+}
+
+impl Word<'_> {
+    pub fn text(&self) -> String {
+        self.pieces.iter().map(|(_, text)| *text).collect()
+    }
+
+    /// Whether the word ends a sentence, ignoring closing quotes, brackets and
+    /// Markdown emphasis markers after the punctuation
+    fn ends_sentence(&self) -> bool {
+        const ABBREVIATIONS: &[&str] = &[
+            "e.g.", "i.e.", "mr.", "mrs.", "ms.", "dr.", "vs.", "cf.", "no.", "fig.", "inc.",
+        ];
+
+        let text = self.text();
+        let core = text.trim_end_matches(['"', '\'', ')', ']', '”', '’', '*', '_', '~']);
+        if !core.ends_with(['.', '!', '?']) {
+            return false;
+        }
+        let lower = core.to_lowercase();
+        let lower = lower.trim_start_matches(['"', '\'', '(', '[', '“', '‘', '*', '_', '~']);
+        // Initials such as "J." are not sentence ends either
+        let is_initial = lower.chars().count() == 2 && lower.starts_with(char::is_alphabetic);
+        !ABBREVIATIONS.contains(&lower) && !is_initial
+    }
+}
+
+/// Whitespace that separates words; non-breaking spaces stay inside words
+fn is_break_space(c: char) -> bool {
+    c.is_whitespace() && c != '\u{a0}' && c != '\u{202f}'
+}
+
+/// Split segments into hard lines (at `\n`) of words
+///
+/// Indentation at the start of a hard line is kept as part of its first word.
+pub fn split_words<'a>(segments: &[&'a str]) -> Vec<Vec<Word<'a>>> {
+    let mut lines = vec![Vec::new()];
+    let mut word: Option<Word<'a>> = None;
+    // Whether the current hard line has any visible text yet
+    let mut in_text = false;
+
+    for (segment, text) in segments.iter().enumerate() {
+        let mut start = None;
+        let push_piece = |word: &mut Option<Word<'a>>, piece: &'a str| {
+            let word = word.get_or_insert_with(|| Word {
+                pieces: Vec::new(),
+                width: 0,
+            });
+            word.pieces.push((segment, piece));
+            word.width += UnicodeWidthStr::width(piece);
+        };
+
+        for (i, c) in text.char_indices() {
+            if !is_break_space(c) || (c != '\n' && !in_text) {
+                in_text |= !is_break_space(c);
+                start.get_or_insert(i);
+                continue;
+            }
+            if let Some(start) = start.take() {
+                push_piece(&mut word, &text[start..i]);
+            }
+            if let Some(word) = word.take().filter(|_| in_text) {
+                lines.last_mut().unwrap().push(word);
+            }
+            if c == '\n' {
+                lines.push(Vec::new());
+                in_text = false;
+            }
+        }
+        // A word may continue into the next segment
+        if let Some(start) = start {
+            push_piece(&mut word, &text[start..]);
+        }
+    }
+    if let Some(word) = word.filter(|_| in_text) {
+        lines.last_mut().unwrap().push(word);
+    }
+
+    lines
+}
+
+/// Greedy line breaking: ranges of `widths` that fit in `max_width` columns
+/// with one column between words. Words wider than a line get a line of their own.
+pub fn greedy_breaks(widths: &[usize], max_width: usize) -> Vec<Range<usize>> {
+    first_fit(widths, |_| 1, max_width)
+}
+
+/// Greedy line breaking where `gap(i)` is the width of the space before item
+/// `i` when it shares a line with the item before it
+fn first_fit(
+    widths: &[usize],
+    gap: impl Fn(usize) -> usize,
+    max_width: usize,
+) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut line_width = 0;
+
+    for (i, &width) in widths.iter().enumerate() {
+        if i > start && line_width + gap(i) + width > max_width {
+            ranges.push(start..i);
+            start = i;
+            line_width = 0;
+        }
+        line_width += if i > start { gap(i) + width } else { width };
+    }
+    if start < widths.len() {
+        ranges.push(start..widths.len());
+    }
+
+    ranges
+}
+
+/// Demerits for ending a line on a hyphen, in squared columns of slack
+const HYPHEN_PENALTY: f64 = 25.0;
+
+/// A piece of a word that a line may end after
+#[derive(Debug, Clone)]
+struct Fragment<'a> {
+    /// Index of the word this fragment belongs to
+    word: usize,
+    pieces: Vec<(usize, &'a str)>,
+    width: usize,
+    /// How the word continues after this fragment; `None` for the end of the word
+    hyphen: Option<Hyphen>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hyphen {
+    /// A visible hyphen ending the fragment
+    Hard,
+    /// A soft hyphen, only shown when the line breaks there
+    Soft,
+    /// No hyphen: Chinese, Japanese and Korean text may break between any two
+    /// characters
+    Ideographic,
+}
+
+/// Whether a character belongs to a script written without spaces between
+/// words, so lines may break on either side of it: CJK ideographs and
+/// punctuation, kana, Hangul and fullwidth forms
+fn breaks_anywhere(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{11FF}'
+        | '\u{2E80}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FFEF}'
+        | '\u{20000}'..='\u{3FFFF}')
+}
+
+/// Closing brackets and punctuation that may not start a line (kinsoku)
+fn no_break_before(c: char) -> bool {
+    matches!(
+        c,
+        '、' | '。'
+            | '，'
+            | '．'
+            | '：'
+            | '；'
+            | '？'
+            | '！'
+            | '）'
+            | '」'
+            | '』'
+            | '】'
+            | '〕'
+            | '〉'
+            | '》'
+            | '〟'
+            | '・'
+            | 'ー'
+            | '々'
+            | '…'
+            | '％'
+            | ','
+            | '.'
+            | ')'
+            | '!'
+            | '?'
+            | ':'
+            | ';'
+    )
+}
+
+/// Opening brackets that may not end a line (kinsoku)
+fn no_break_after(c: char) -> bool {
+    matches!(
+        c,
+        '（' | '「' | '『' | '【' | '〔' | '〈' | '《' | '〝' | '('
+    )
+}
+
+/// Split a word at the places a line may break inside it: between CJK
+/// characters if `ideographic` is set, and if `hyphenate` is set after a hyphen
+/// between letters and at soft hyphens (which are dropped from the text)
+fn word_fragments<'a>(
+    index: usize,
+    word: &Word<'a>,
+    hyphenate: bool,
+    ideographic: bool,
+) -> Vec<Fragment<'a>> {
+    // (piece, byte range) for every character of the word
+    let chars: Vec<(usize, Range<usize>, char)> = word
+        .pieces
+        .iter()
+        .enumerate()
+        .flat_map(|(piece, (_, text))| {
+            text.char_indices()
+                .map(move |(i, c)| (piece, i..i + c.len_utf8(), c))
+        })
+        .collect();
+    let is_letter = |k: Option<usize>| {
+        k.and_then(|k| chars.get(k))
+            .is_some_and(|(_, _, c)| c.is_alphabetic())
+    };
+
+    let mut fragments = Vec::new();
+    // Byte ranges of the current fragment, at most one per piece
+    let mut spans: Vec<(usize, Range<usize>)> = Vec::new();
+    let mut finish = |spans: &mut Vec<(usize, Range<usize>)>, hyphen| {
+        let pieces: Vec<(usize, &'a str)> = spans
+            .drain(..)
+            .map(|(piece, range)| {
+                let (segment, text) = word.pieces[piece];
+                (segment, &text[range])
+            })
+            .collect();
+        fragments.push(Fragment {
+            word: index,
+            width: pieces
+                .iter()
+                .map(|(_, text)| UnicodeWidthStr::width(*text))
+                .sum(),
+            pieces,
+            hyphen,
+        });
+    };
+
+    for (k, (piece, range, c)) in chars.iter().enumerate() {
+        let between_letters = is_letter(k.checked_sub(1)) && is_letter(Some(k + 1));
+        if let Some(&(_, _, previous)) = k.checked_sub(1).and_then(|k| chars.get(k)) {
+            if ideographic
+                && (breaks_anywhere(previous) || breaks_anywhere(*c))
+                && !spans.is_empty()
+                && c.width().is_some_and(|width| width > 0)
+                && !no_break_before(*c)
+                && !no_break_after(previous)
+            {
+                finish(&mut spans, Some(Hyphen::Ideographic));
+            }
+        }
+        if !hyphenate {
+            match spans.last_mut() {
+                Some((last, span)) if last == piece => span.end = range.end,
+                _ => spans.push((*piece, range.clone())),
+            }
+            continue;
+        }
+        if *c == '\u{ad}' {
+            if between_letters {
+                finish(&mut spans, Some(Hyphen::Soft));
+            }
+            continue;
+        }
+        match spans.last_mut() {
+            Some((last, span)) if last == piece => span.end = range.end,
+            _ => spans.push((*piece, range.clone())),
+        }
+        if matches!(c, '-' | '\u{2010}') && between_letters {
+            finish(&mut spans, Some(Hyphen::Hard));
+        }
+    }
+    finish(&mut spans, None);
+    fragments
+}
+
+/// Optimal line breaking in the spirit of Knuth and Plass: choose the breaks that
+/// minimise the total squared slack of every line but the last, rather than
+/// filling each line as far as it goes
+///
+/// Breaking on a hyphen costs [`HYPHEN_PENALTY`]. When `justify` is set the
+/// slack of a line is shared between its gaps, so lines with few gaps (which
+/// justification would stretch the most) are penalised accordingly.
+fn optimal_breaks(fragments: &[Fragment], max_width: usize, justify: bool) -> Vec<Range<usize>> {
+    let n = fragments.len();
+    // best[j]: (demerits, start of the last line) for the fragments before j
+    let mut best: Vec<(f64, usize)> = vec![(f64::INFINITY, 0); n + 1];
+    best[0] = (0.0, 0);
+
+    for end in 1..=n {
+        let is_last = end == n;
+        let hyphen = fragments[end - 1].hyphen.filter(|_| !is_last);
+        let mut width = usize::from(hyphen == Some(Hyphen::Soft));
+        let mut gaps = 0;
+
+        for start in (0..end).rev() {
+            width += fragments[start].width;
+            if start + 1 < end && fragments[start].hyphen.is_none() {
+                width += 1;
+                gaps += 1;
+            }
+            // A fragment too wide for any line gets one of its own
+            if width > max_width && start + 1 < end {
+                break;
+            }
+
+            let slack = max_width.saturating_sub(width) as f64;
+            let mut demerits = if is_last {
+                0.0
+            } else if justify {
+                slack * slack / gaps.max(1) as f64
+            } else {
+                slack * slack
+            };
+            if matches!(hyphen, Some(Hyphen::Hard | Hyphen::Soft)) {
+                demerits += HYPHEN_PENALTY;
+            }
+            // One extra point per line prefers fewer lines among equals
+            let total = best[start].0 + demerits + 1.0;
+            if total < best[end].0 {
+                best[end] = (total, start);
+            }
+        }
+    }
+
+    let mut ranges = Vec::new();
+    let mut end = n;
+    while end > 0 {
+        let start = best[end].1;
+        ranges.push(start..end);
+        end = start;
+    }
+    ranges.reverse();
+    ranges
+}
+
+/// Join a line's fragments back into words, adding a hyphen at a soft-hyphen break
+fn join_fragments<'a>(fragments: &[Fragment<'a>], is_last: bool) -> Vec<Word<'a>> {
+    let mut words: Vec<Word<'a>> = Vec::new();
+    let mut previous = None;
+    for fragment in fragments {
+        if previous != Some(fragment.word) {
+            words.push(Word {
+                pieces: Vec::new(),
+                width: 0,
+            });
+            previous = Some(fragment.word);
+        }
+        let word = words.last_mut().unwrap();
+        word.pieces.extend(fragment.pieces.iter().copied());
+        word.width += fragment.width;
+    }
+
+    if let (Some(last), Some(word)) = (fragments.last(), words.last_mut()) {
+        if last.hyphen == Some(Hyphen::Soft) && !is_last {
+            let segment = last.pieces.last().map_or(0, |(segment, _)| *segment);
+            word.pieces.push((segment, "-"));
+            word.width += 1;
+        }
+    }
+    words
+}
+
+/// Break segments into output lines of words according to `mode`
+///
+/// Each hard line break in the input starts a new output line; a hard line with
+/// no words yields an empty line.
+pub fn wrap_words<'a>(segments: &[&'a str], mode: WrapMode) -> Vec<Vec<Word<'a>>> {
+    wrap_words_with(segments, mode, BreakOptions::default())
+}
+
+/// [`wrap_words`] with control over how column wrapping picks its break points
+pub fn wrap_words_with<'a>(
+    segments: &[&'a str],
+    mode: WrapMode,
+    options: BreakOptions,
+) -> Vec<Vec<Word<'a>>> {
+    let mut lines = Vec::new();
+
+    for words in split_words(segments) {
+        match mode {
+            WrapMode::None => lines.push(words),
+            WrapMode::Columns(max_width) if options.breaking == LineBreaking::Optimal => {
+                if words.is_empty() {
+                    lines.push(Vec::new());
+                    continue;
+                }
+                let fragments: Vec<Fragment> = words
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, word)| {
+                        word_fragments(i, word, options.hyphenate, options.ideographic)
+                    })
+                    .collect();
+                let breaks = optimal_breaks(&fragments, max_width, options.justify);
+                lines.extend(breaks.iter().map(|range| {
+                    join_fragments(&fragments[range.clone()], range.end == fragments.len())
+                }));
+            }
+            WrapMode::Columns(max_width) => {
+                if words.is_empty() {
+                    lines.push(Vec::new());
+                    continue;
+                }
+                let fragments: Vec<Fragment> = words
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, word)| word_fragments(i, word, false, options.ideographic))
+                    .collect();
+                let widths: Vec<usize> = fragments.iter().map(|fragment| fragment.width).collect();
+                // Fragments of one word are not separated by a space
+                let gap = |i: usize| usize::from(fragments[i - 1].hyphen.is_none());
+                let breaks = first_fit(&widths, gap, max_width);
+                lines.extend(breaks.iter().map(|range| {
+                    join_fragments(&fragments[range.clone()], range.end == fragments.len())
+                }));
+            }
+            WrapMode::Semantic => {
+                let mut sentence = Vec::new();
+                let mut any_sentence = false;
+                let mut words = words.into_iter().peekable();
+                while let Some(word) = words.next() {
+                    // A sentence never continues in lowercase ("Inc. delivered")
+                    let next_lowercase = words
+                        .peek()
+                        .and_then(|next| next.text().chars().next())
+                        .is_some_and(char::is_lowercase);
+                    let ends = word.ends_sentence() && !next_lowercase;
+                    sentence.push(word);
+                    if ends {
+                        lines.push(std::mem::take(&mut sentence));
+                        any_sentence = true;
+                    }
+                }
+                if !sentence.is_empty() || !any_sentence {
+                    lines.push(sentence);
+                }
+            }
+        }
+    }
+
+    lines
+}
+
+/// Wrap width when none is given: `$COLUMNS`, or 80
+pub fn default_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(80)
+}
+
+/// Plain-text convenience over [`wrap_words`]: each line's words joined by single spaces
+pub fn wrap_text(text: &str, mode: WrapMode) -> Vec<String> {
+    wrap_text_with(text, mode, BreakOptions::default())
+}
+
+/// [`wrap_text`] with control over how column wrapping picks its break points
+pub fn wrap_text_with(text: &str, mode: WrapMode, options: BreakOptions) -> Vec<String> {
+    wrap_words_with(&[text], mode, options)
+        .iter()
+        .map(|line| {
+            line.iter()
+                .map(|word| word.text())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+/// Split a line into escape sequences (invisible) and graphemes (visible)
+fn ansi_tokens(line: &str) -> Vec<(&str, bool)> {
+    let mut tokens = Vec::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        if rest.starts_with('\x1b') {
+            let end = rest
+                .bytes()
+                .enumerate()
+                .skip(2)
+                .find(|(_, b)| b.is_ascii_alphabetic())
+                .map(|(i, _)| i + 1)
+                .unwrap_or(rest.len());
+            tokens.push((&rest[..end], false));
+            rest = &rest[end..];
+        } else {
+            let end = rest.find('\x1b').unwrap_or(rest.len());
+            tokens.extend(rest[..end].graphemes(true).map(|g| (g, true)));
+            rest = &rest[end..];
+        }
+    }
+    tokens
+}
+
+/// A line without its ANSI escape sequences
+pub fn strip_ansi(line: &str) -> String {
+    ansi_tokens(line)
+        .into_iter()
+        .filter(|(_, visible)| *visible)
+        .map(|(text, _)| text)
+        .collect()
+}
+
+/// Display width of a line, not counting ANSI escape sequences
+pub fn visible_width(line: &str) -> usize {
+    ansi_tokens(line)
+        .iter()
+        .filter(|(_, visible)| *visible)
+        .map(|(text, _)| UnicodeWidthStr::width(*text))
+        .sum()
+}
+
+/// Pad plain text with spaces to `width` columns, positioned according to
+/// `alignment` (justified text is padded like left-aligned text)
+///
+/// Unlike `format!` padding this counts terminal columns, so CJK text lines up.
+pub fn pad_to_width(text: &str, alignment: TextAlignment, width: usize) -> String {
+    let padding = width.saturating_sub(UnicodeWidthStr::width(text));
+    let left = match alignment {
+        TextAlignment::Right => padding,
+        TextAlignment::Center => padding / 2,
+        TextAlignment::Left | TextAlignment::Justify => 0,
+    };
+    format!("{}{text}{}", " ".repeat(left), " ".repeat(padding - left))
+}
+
+/// Columns between Word's default tab stops, every half inch
+const DEFAULT_TAB_COLUMNS: usize = (720 / TWIPS_PER_COLUMN) as usize;
+
+/// What each tab in a paragraph's segments advances over, in order: spaces,
+/// or the leader of its stop
+///
+/// Columns count from the start of each hard line, with the paragraph's
+/// `stops` past `width` pulled back to it. Text after a right, centred or
+/// decimal stop lines up against it up to the next tab or line break; if it
+/// doesn't fit before the stop, the tab is a single space. Past the last stop
+/// tabs go to Word's default stops.
+pub fn tab_fills(segments: &[&str], stops: &[TabStop], width: usize) -> Vec<String> {
+    let text = segments.concat();
+    let mut fills = Vec::new();
+    let mut column = 0;
+    let mut rest = text.as_str();
+    while let Some(at) = rest.find(['\t', '\n']) {
+        let after = &rest[at + 1..];
+        if rest[at..].starts_with('\n') {
+            column = 0;
+            rest = after;
+            continue;
+        }
+        column += UnicodeWidthStr::width(&rest[..at]);
+        let following = &after[..after.find(['\t', '\n']).unwrap_or(after.len())];
+
+        let fill = match stops.iter().find(|stop| stop.column().min(width) > column) {
+            Some(stop) => {
+                let stop_column = stop.column().min(width);
+                // Columns of the following text that go before the stop
+                let before_stop = match stop.alignment {
+                    TabAlignment::Left => 0,
+                    TabAlignment::Center => UnicodeWidthStr::width(following) / 2,
+                    TabAlignment::Right => UnicodeWidthStr::width(following),
+                    TabAlignment::Decimal => {
+                        let whole = following
+                            .find('.')
+                            .map_or(following, |dot| &following[..dot]);
+                        UnicodeWidthStr::width(whole)
+                    }
+                };
+                match stop_column.checked_sub(column + before_stop) {
+                    Some(gap) if gap > 0 => stop.leader.fill(gap),
+                    _ => " ".to_string(),
+                }
+            }
+            None => " ".repeat(DEFAULT_TAB_COLUMNS - column % DEFAULT_TAB_COLUMNS),
+        };
+        column += UnicodeWidthStr::width(fill.as_str());
+        fills.push(fill);
+        rest = after;
+    }
+    fills
+}
+
+/// Segments with their tabs laid out by [`tab_fills`]
+pub fn expand_tabs(segments: &[&str], stops: &[TabStop], width: usize) -> Vec<String> {
+    let mut fills = tab_fills(segments, stops, width).into_iter();
+    segments
+        .iter()
+        .map(|segment| {
+            segment
+                .split('\t')
+                .enumerate()
+                .fold(String::new(), |mut expanded, (i, piece)| {
+                    if i > 0 {
+                        expanded.push_str(&fills.next().unwrap_or_default());
+                    }
+                    expanded.push_str(piece);
+                    expanded
+                })
+        })
+        .collect()
+}
+
+/// A rule `width` columns wide with a label in the middle, like `─── page 4 ───`
+///
+/// The rule is never so short that the label loses its three fill
+/// characters either side.
+pub fn labeled_rule(label: &str, fill: char, width: usize) -> String {
+    let sides = width.saturating_sub(UnicodeWidthStr::width(label) + 2);
+    let left = (sides / 2).max(3);
+    let right = sides.saturating_sub(sides / 2).max(3);
+    format!(
+        "{} {label} {}",
+        fill.to_string().repeat(left),
+        fill.to_string().repeat(right)
+    )
+}
+
+/// A line with `number` at the right edge of `width` columns, as Word sets an
+/// equation's number at a right tab stop
+///
+/// The line may contain ANSI escape sequences. A line too long to leave room
+/// keeps two spaces before the number.
+pub fn number_line(line: &str, number: &str, width: usize) -> String {
+    let gap = width
+        .saturating_sub(visible_width(line) + UnicodeWidthStr::width(number))
+        .max(2);
+    format!("{line}{}{number}", " ".repeat(gap))
+}
+
+/// Position an already-wrapped line within `width` columns according to its paragraph alignment
+///
+/// The line may contain ANSI escape sequences; they are kept in place and excluded from the
+/// width math. Justified lines are stretched by widening the gaps between words, except for the
+/// last line of the paragraph, which stays flush left as it does in Word.
+pub fn align_line(
+    line: &str,
+    alignment: TextAlignment,
+    width: usize,
+    is_last_line: bool,
+) -> String {
+    if alignment == TextAlignment::Left {
+        return line.to_string();
+    }
+
+    let mut tokens = ansi_tokens(line);
+
+    // Drop trailing spaces left behind by the wrapper so they don't count toward the width
+    while let Some(pos) = tokens.iter().rposition(|(_, visible)| *visible) {
+        if tokens[pos].0 != " " {
+            break;
+        }
+        tokens.remove(pos);
+    }
+
+    let visible_width: usize = tokens
+        .iter()
+        .filter(|(_, visible)| *visible)
+        .map(|(text, _)| UnicodeWidthStr::width(*text))
+        .sum();
+    let extra = width.saturating_sub(visible_width);
+
+    let mut result = String::new();
+    match alignment {
+        TextAlignment::Center => result.push_str(&" ".repeat(extra / 2)),
+        TextAlignment::Right => result.push_str(&" ".repeat(extra)),
+        _ => {}
+    }
+
+    // Inter-word gaps: visible spaces that follow visible text
+    let mut gaps = Vec::new();
+    if alignment == TextAlignment::Justify && !is_last_line {
+        let mut seen_text = false;
+        for (i, (text, visible)) in tokens.iter().enumerate() {
+            if !visible {
+                continue;
+            }
+            if *text == " " {
+                if seen_text {
+                    gaps.push(i);
+                }
+            } else {
+                seen_text = true;
+            }
+        }
+    }
+
+    let mut gap_number = 0;
+    for (i, (text, _)) in tokens.iter().enumerate() {
+        result.push_str(text);
+        if gaps.contains(&i) {
+            let padding = extra / gaps.len() + usize::from(gap_number < extra % gaps.len());
+            result.push_str(&" ".repeat(padding));
+            gap_number += 1;
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wrap_mode() {
+        assert_eq!("72".parse(), Ok(WrapMode::Columns(72)));
+        assert_eq!("None".parse(), Ok(WrapMode::None));
+        assert_eq!("semantic".parse(), Ok(WrapMode::Semantic));
+        assert!("0".parse::<WrapMode>().is_err());
+        assert!("wide".parse::<WrapMode>().is_err());
+    }
+
+    #[test]
+    fn test_words_span_segments() {
+        let lines = split_words(&["Hello wor", "ld! Next\nline"]);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0][1].pieces, vec![(0, "wor"), (1, "ld!")]);
+        assert_eq!(lines[0][1].width, 6);
+        assert_eq!(lines[1][0].text(), "line");
+    }
+
+    #[test]
+    fn test_column_wrapping() {
+        assert_eq!(
+            wrap_text("the quick brown fox jumps", WrapMode::Columns(10)),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+        // Overlong words are not split, and hard breaks are kept
+        assert_eq!(
+            wrap_text("a supercalifragilistic b\n\nc", WrapMode::Columns(5)),
+            vec!["a", "supercalifragilistic", "b", "", "c"]
+        );
+        assert_eq!(wrap_text("", WrapMode::Columns(5)), vec![""]);
+    }
+
+    #[test]
+    fn test_optimal_wrapping_balances_lines() {
+        let optimal = BreakOptions {
+            breaking: LineBreaking::Optimal,
+            ..Default::default()
+        };
+        assert_eq!(
+            wrap_text("aaa bb cc ddddd", WrapMode::Columns(6)),
+            vec!["aaa bb", "cc", "ddddd"]
+        );
+        assert_eq!(
+            wrap_text_with("aaa bb cc ddddd", WrapMode::Columns(6), optimal),
+            vec!["aaa", "bb cc", "ddddd"]
+        );
+        // Same edge cases as greedy wrapping
+        assert_eq!(
+            wrap_text_with(
+                "a supercalifragilistic b\n\nc",
+                WrapMode::Columns(5),
+                optimal
+            ),
+            vec!["a", "supercalifragilistic", "b", "", "c"]
+        );
+        assert_eq!(wrap_text_with("", WrapMode::Columns(5), optimal), vec![""]);
+    }
+
+    #[test]
+    fn test_optimal_wrapping_breaks_at_hyphens() {
+        let hyphenate = BreakOptions {
+            breaking: LineBreaking::Optimal,
+            hyphenate: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            wrap_text_with("a well-known fact", WrapMode::Columns(10), hyphenate),
+            vec!["a well-", "known fact"]
+        );
+        // Soft hyphens show only where a line breaks
+        assert_eq!(
+            wrap_text_with(
+                "an extra\u{ad}ordinary day",
+                WrapMode::Columns(12),
+                hyphenate
+            ),
+            vec!["an extra-", "ordinary day"]
+        );
+        assert_eq!(
+            wrap_text_with(
+                "an extra\u{ad}ordinary day",
+                WrapMode::Columns(40),
+                hyphenate
+            ),
+            vec!["an extraordinary day"]
+        );
+        // A dash that isn't between letters is not a break point
+        assert_eq!(
+            wrap_text_with("from 1-2 pm", WrapMode::Columns(5), hyphenate),
+            vec!["from", "1-2", "pm"]
+        );
+    }
+
+    #[test]
+    fn test_cjk_text_breaks_between_characters() {
+        let cjk = BreakOptions {
+            ideographic: true,
+            ..Default::default()
+        };
+        // Ideographs are two columns wide and need no space to break
+        assert_eq!(
+            wrap_text_with("文档查看器支持中文。", WrapMode::Columns(8), cjk),
+            vec!["文档查看", "器支持中", "文。"]
+        );
+        // Closing punctuation stays with the text before it
+        assert_eq!(
+            wrap_text_with("日本語のテキスト。", WrapMode::Columns(16), cjk),
+            vec!["日本語のテキス", "ト。"]
+        );
+        // Latin words inside CJK text keep their spaces
+        assert_eq!(
+            wrap_text_with("使用 doxx 查看文档", WrapMode::Columns(10), cjk),
+            vec!["使用 doxx", "查看文档"]
+        );
+        // Without the option CJK text is one unbreakable word
+        assert_eq!(
+            wrap_text_with("文档查看器", WrapMode::Columns(4), BreakOptions::default()),
+            vec!["文档查看器"]
+        );
+
+        // Optimal breaking sees the same break points
+        let optimal = BreakOptions {
+            breaking: LineBreaking::Optimal,
+            ..cjk
+        };
+        assert_eq!(
+            wrap_text_with("文档查看器支持中文", WrapMode::Columns(12), optimal),
+            vec!["文档查看器支", "持中文"]
+        );
+    }
+
+    #[test]
+    fn test_hyphen_breaks_keep_run_pieces() {
+        let hyphenate = BreakOptions {
+            breaking: LineBreaking::Optimal,
+            hyphenate: true,
+            ..Default::default()
+        };
+        let lines = wrap_words_with(&["a well", "-known fact"], WrapMode::Columns(10), hyphenate);
+        assert_eq!(lines[0][1].pieces, vec![(0, "well"), (1, "-")]);
+        assert_eq!(lines[1][0].pieces, vec![(1, "known")]);
+    }
+
+    #[test]
+    fn test_semantic_wrapping() {
+        assert_eq!(
+            wrap_text(
+                "First one. See e.g. this, by J. Smith! Is it \"quoted?\" **Bold.** Acme Inc. said no.",
+                WrapMode::Semantic
+            ),
+            vec![
+                "First one.",
+                "See e.g. this, by J. Smith!",
+                "Is it \"quoted?\"",
+                "**Bold.**",
+                "Acme Inc. said no."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_indentation_is_kept() {
+        assert_eq!(
+            wrap_text("   (a) item\n  \n\tnext", WrapMode::Columns(20)),
+            vec!["   (a) item", "", "\tnext"]
+        );
+    }
+
+    #[test]
+    fn test_no_wrapping_collapses_spaces_only() {
+        assert_eq!(
+            wrap_text("one  two\nthree\u{a0}four", WrapMode::None),
+            vec!["one two", "three\u{a0}four"]
+        );
+    }
+
+    #[test]
+    fn test_tabs_advance_to_their_stops() {
+        use crate::document::TabLeader;
+        let stop = |column: u32, alignment, leader| TabStop {
+            position: column * TWIPS_PER_COLUMN,
+            alignment,
+            leader,
+        };
+        // A table of contents line, its page number against a right stop
+        // pulled back to the width
+        let toc = [stop(65, TabAlignment::Right, TabLeader::Dot)];
+        assert_eq!(
+            expand_tabs(&["Introduction", "\t12"], &toc, 30),
+            ["Introduction", " .............. 12"]
+        );
+        // Amounts lined up on their decimal points, then a default stop
+        let prices = [
+            stop(10, TabAlignment::Left, TabLeader::Underscore),
+            stop(20, TabAlignment::Decimal, TabLeader::None),
+        ];
+        assert_eq!(
+            expand_tabs(&["Tea\t\t3.50\tx\nCoffee\t\t12.00"], &prices, 80),
+            ["Tea_______         3.50  x\nCoffee____        12.00"]
+        );
+        // Text too wide for a right stop is only a space away
+        assert_eq!(
+            tab_fills(
+                &["A long heading\t1"],
+                &[stop(10, TabAlignment::Right, TabLeader::Dot)],
+                80
+            ),
+            [" "]
+        );
+    }
+
+    #[test]
+    fn test_numbers_sit_at_the_right_edge() {
+        assert_eq!(number_line("x=1", "(1)", 10), "x=1    (1)");
+        assert_eq!(
+            number_line("\x1b[1mx=1\x1b[0m", "(1)", 10),
+            "\x1b[1mx=1\x1b[0m    (1)"
+        );
+        // Too long a line still leaves a gap
+        assert_eq!(number_line("a+b+c=d", "(12)", 10), "a+b+c=d  (12)");
+    }
+}
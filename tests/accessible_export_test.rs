@@ -0,0 +1,59 @@
+use doxx::{
+    document::{load_document, ImageOptions},
+    export::accessible::render_accessible_text,
+};
+use std::path::Path;
+
+fn render_fixture(name: &str) -> String {
+    let document = load_document(
+        Path::new(&format!("tests/fixtures/{name}")),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    render_accessible_text(&document)
+}
+
+#[test]
+fn test_structure_is_announced() {
+    let text = render_fixture("tables-heavy.docx");
+
+    assert!(text.starts_with("Document: tables-heavy\nAbout 1 page, 33 words\n\n"));
+    assert!(text.contains("Heading level 1: Simple Table\n"));
+    assert!(text.contains(
+        "Table with 3 columns and 3 rows\n\
+         Columns: Name; Age; City\n\
+         Row 1: Name: John; Age: 25; City: New York\n"
+    ));
+    assert!(text.contains("Row 3: Name: Bob; Age: 35; City: Chicago\nEnd of table\n"));
+}
+
+#[test]
+fn test_list_numbering_is_expanded() {
+    let text = render_fixture("simple-numbering.docx");
+
+    assert!(text.contains("Item 2: This is the second item, and it’s bold!\n"));
+    assert!(text.contains("Level 2 item 2.1: This is a nested item, and it’s italic!\n"));
+    // Roman numerals and lettered markers are expanded
+    assert!(text.contains("Level 3 item 2: And yet another one\n"));
+    assert!(text.contains("Level 3 item B: B\n"));
+
+    let text = render_fixture("lists-comprehensive.docx");
+    assert!(text.contains("List with 6 items\nBullet: Top level item one\n"));
+    assert!(text.contains("Level 3 bullet: Third level item i\n"));
+}
+
+#[test]
+fn test_no_box_drawing_characters() {
+    for name in [
+        "tables-heavy.docx",
+        "business-report.docx",
+        "headings-hierarchy.docx",
+    ] {
+        let text = render_fixture(name);
+        assert!(
+            !text.chars().any(|c| ('\u{2500}'..='\u{257F}').contains(&c)),
+            "{name}"
+        );
+        assert!(!text.contains("==="), "{name}");
+    }
+}
@@ -0,0 +1,100 @@
+use doxx::{
+    document::{load_document, Document, ImageOptions},
+    export::{
+        html::render_html_with_options, render_markdown, table_of_contents_with, ExportOptions,
+    },
+    AnchorStyle,
+};
+use std::path::Path;
+use std::process::Command;
+
+fn cross_references() -> Document {
+    load_document(
+        Path::new("tests/fixtures/cross-references.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_bookmarks_on_headings_are_recorded() {
+    let document = cross_references();
+    let bookmarks: Vec<(&str, usize)> = document
+        .heading_bookmarks
+        .iter()
+        .map(|(name, &heading)| (name.as_str(), heading))
+        .collect();
+    assert_eq!(
+        bookmarks,
+        [
+            ("_Ref100", 0),
+            ("_Ref300", 2),
+            ("_Ref400", 3),
+            ("_Toc200", 1)
+        ]
+    );
+}
+
+#[test]
+fn test_cross_references_link_to_their_headings_in_markdown() {
+    let markdown = render_markdown(&cross_references(), &ExportOptions::default());
+    assert!(markdown.contains(
+        "See [Details](#3-details) for the figures and [What's New & FAQ](#2-whats-new--faq)"
+    ));
+    assert!(markdown.contains("Back to the [Introduction](#1-introduction);"));
+    // A bookmark that isn't on a heading has nothing to link to
+    assert!(markdown.contains("see also Table 1."));
+}
+
+#[test]
+fn test_cross_references_give_html_headings_ids() {
+    let html = render_html_with_options(&cross_references(), &ExportOptions::default());
+    assert!(html.contains("<h2 id=\"3-details\">3 Details</h2>"));
+    assert!(html.contains("<a href=\"#3-details\">Details</a>"));
+    assert!(html.contains("<a href=\"#2-whats-new--faq\">What's New &amp; FAQ</a>"));
+    assert!(!html.contains("<nav"));
+}
+
+#[test]
+fn test_anchor_styles_follow_each_site() {
+    let document = cross_references();
+    let anchors = |style| -> Vec<String> {
+        table_of_contents_with(&document, false, style)
+            .into_iter()
+            .map(|entry| entry.anchor)
+            .collect()
+    };
+    assert_eq!(
+        anchors(AnchorStyle::Gitlab),
+        [
+            "1-introduction",
+            "2-whats-new-faq",
+            "21-details",
+            "3-details"
+        ]
+    );
+    // Pandoc drops the leading numbers, so the two "Details" headings meet
+    assert_eq!(
+        anchors(AnchorStyle::Pandoc),
+        ["introduction", "whats-new--faq", "details", "details-1"]
+    );
+}
+
+#[test]
+fn test_anchors_option_changes_the_links() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/cross-references.docx",
+            "--export",
+            "markdown",
+            "--toc",
+            "--anchors",
+            "pandoc",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let markdown = String::from_utf8(output.stdout).unwrap();
+    assert!(markdown.contains("- [3 Details](#details-1)"));
+    assert!(markdown.contains("See [Details](#details-1) for the figures"));
+}
@@ -1,6 +1,8 @@
+#![cfg(feature = "terminal")]
+
 use doxx::{
     ansi::{export_to_ansi_with_options, AnsiOptions},
-    document::{Document, DocumentElement, FormattedRun, TextFormatting},
+    document::{Document, DocumentElement, FormattedRun, TextAlignment, TextFormatting},
     ColorDepth,
 };
 
@@ -10,6 +12,7 @@ fn test_ansi_export_basic() {
     let options = AnsiOptions {
         terminal_width: 80,
         color_depth: ColorDepth::TrueColor,
+        ..Default::default()
     };
 
     let result = export_to_ansi_with_options(&document, &options);
@@ -26,6 +29,7 @@ fn test_ansi_export_formatting() {
     let options = AnsiOptions {
         terminal_width: 80,
         color_depth: ColorDepth::TrueColor,
+        ..Default::default()
     };
 
     let result = export_to_ansi_with_options(&document, &options);
@@ -50,24 +54,44 @@ fn test_ansi_export_color_depths() {
     let monochrome_options = AnsiOptions {
         terminal_width: 80,
         color_depth: ColorDepth::Monochrome,
+        ..Default::default()
     };
     let mono_output = export_to_ansi_with_options(&document, &monochrome_options).unwrap();
     assert!(!mono_output.contains("[38;2;")); // No RGB colors
     assert!(!mono_output.contains("[38;5;")); // No ANSI colors
 
+    // Coloured runs are emphasised in bold instead
+    assert!(mono_output.contains("\x1b[1mRed text"));
+
     // Test 16 colors
     let standard_options = AnsiOptions {
         terminal_width: 80,
         color_depth: ColorDepth::Standard,
+        ..Default::default()
     };
     let standard_output = export_to_ansi_with_options(&document, &standard_options).unwrap();
-    assert!(standard_output.contains("[38;5;")); // ANSI colors
+    // The nearest of the 16 colours, as SGR codes every colour terminal knows
+    assert!(standard_output.contains("\x1b[91mRed text"));
+    assert!(standard_output.contains("\x1b[34mBlue text"));
+    assert!(!standard_output.contains("[38;5;")); // No 256-colour palette
     assert!(!standard_output.contains("[38;2;")); // No RGB colors
 
+    // Test 256 colors
+    let extended_options = AnsiOptions {
+        terminal_width: 80,
+        color_depth: ColorDepth::Extended,
+        ..Default::default()
+    };
+    let extended_output = export_to_ansi_with_options(&document, &extended_options).unwrap();
+    assert!(extended_output.contains("\x1b[38;5;196mRed text"));
+    assert!(extended_output.contains("\x1b[38;5;21mBlue text"));
+    assert!(!extended_output.contains("[38;2;"));
+
     // Test true color
     let true_color_options = AnsiOptions {
         terminal_width: 80,
         color_depth: ColorDepth::TrueColor,
+        ..Default::default()
     };
     let true_color_output = export_to_ansi_with_options(&document, &true_color_options).unwrap();
     assert!(true_color_output.contains("[38;2;")); // RGB colors
@@ -81,6 +105,7 @@ fn test_ansi_export_terminal_width() {
     let narrow_options = AnsiOptions {
         terminal_width: 40,
         color_depth: ColorDepth::Auto,
+        ..Default::default()
     };
     let narrow_output = export_to_ansi_with_options(&document, &narrow_options).unwrap();
 
@@ -95,6 +120,7 @@ fn test_ansi_export_terminal_width() {
     let wide_options = AnsiOptions {
         terminal_width: 120,
         color_depth: ColorDepth::Auto,
+        ..Default::default()
     };
     let wide_output = export_to_ansi_with_options(&document, &wide_options).unwrap();
     let wide_lines: Vec<&str> = wide_output.lines().collect();
@@ -134,8 +160,94 @@ fn test_ansi_export_tables() {
     assert!(output.contains("📊")); // Table icon
 }
 
+#[test]
+fn test_ansi_export_paragraph_alignment() {
+    let options = AnsiOptions {
+        terminal_width: 40,
+        color_depth: ColorDepth::Monochrome,
+        ..Default::default()
+    };
+
+    let centered = export_to_ansi_with_options(
+        &create_aligned_document("Centered text", TextAlignment::Center),
+        &options,
+    )
+    .unwrap();
+    assert!(strip_ansi(&centered).contains(&format!("\n{}Centered text\n", " ".repeat(13))));
+
+    let right = export_to_ansi_with_options(
+        &create_aligned_document("Right text", TextAlignment::Right),
+        &options,
+    )
+    .unwrap();
+    assert!(strip_ansi(&right).contains(&format!("\n{}Right text\n", " ".repeat(30))));
+}
+
+#[test]
+fn test_ansi_export_justified_paragraph() {
+    let options = AnsiOptions {
+        terminal_width: 20,
+        color_depth: ColorDepth::Monochrome,
+        ..Default::default()
+    };
+    let document = create_aligned_document(
+        "one two three four five six seven eight nine ten",
+        TextAlignment::Justify,
+    );
+
+    let output = strip_ansi(&export_to_ansi_with_options(&document, &options).unwrap());
+    let body: Vec<&str> = output
+        .lines()
+        .skip_while(|line| !line.starts_with("one"))
+        .take_while(|line| !line.is_empty())
+        .collect();
+
+    assert!(body.len() > 1);
+    // Every line but the last fills the width exactly, with words at both edges
+    for line in &body[..body.len() - 1] {
+        assert_eq!(line.chars().count(), 20, "line not justified: {line:?}");
+        assert!(!line.ends_with(' '));
+    }
+    // The last line stays flush left
+    let last = body[body.len() - 1];
+    assert!(!last.contains("  "));
+}
+
 // Helper functions to create test documents
 
+fn strip_ansi(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn create_aligned_document(text: &str, alignment: TextAlignment) -> Document {
+    let mut document = create_test_document();
+    document.elements = vec![DocumentElement::Paragraph {
+        runs: vec![FormattedRun {
+            text: text.to_string(),
+            formatting: TextFormatting::default(),
+        }],
+        alignment,
+        shading: None,
+        rtl: false,
+        spacing: None,
+        tab_stops: Vec::new(),
+    }];
+    document
+}
+
 fn create_test_document() -> Document {
     use doxx::document::DocumentMetadata;
 
@@ -149,14 +261,28 @@ fn create_test_document() -> Document {
             created: None,
             modified: None,
             author: Some("Test Author".to_string()),
+            language: None,
+            locale: None,
+            protection: None,
         },
         elements: vec![DocumentElement::Paragraph {
             runs: vec![FormattedRun {
                 text: "This is a simple paragraph.".to_string(),
                 formatting: TextFormatting::default(),
             }],
+            alignment: TextAlignment::Left,
+            shading: None,
+            rtl: false,
+            spacing: None,
+            tab_stops: Vec::new(),
         }],
+        sections: Vec::new(),
         image_options: Default::default(),
+        fidelity: Default::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
     }
 }
 
@@ -193,6 +319,9 @@ fn create_formatted_document() -> Document {
             created: None,
             modified: None,
             author: None,
+            language: None,
+            locale: None,
+            protection: None,
         },
         elements: vec![DocumentElement::Paragraph {
             runs: vec![
@@ -213,8 +342,19 @@ fn create_formatted_document() -> Document {
                     formatting: strikethrough_formatting,
                 },
             ],
+            alignment: TextAlignment::Left,
+            shading: None,
+            rtl: false,
+            spacing: None,
+            tab_stops: Vec::new(),
         }],
+        sections: Vec::new(),
         image_options: Default::default(),
+        fidelity: Default::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
     }
 }
 
@@ -241,6 +381,9 @@ fn create_colored_document() -> Document {
             created: None,
             modified: None,
             author: None,
+            language: None,
+            locale: None,
+            protection: None,
         },
         elements: vec![DocumentElement::Paragraph {
             runs: vec![
@@ -253,8 +396,19 @@ fn create_colored_document() -> Document {
                     formatting: blue_formatting,
                 },
             ],
+            alignment: TextAlignment::Left,
+            shading: None,
+            rtl: false,
+            spacing: None,
+            tab_stops: Vec::new(),
         }],
+        sections: Vec::new(),
         image_options: Default::default(),
+        fidelity: Default::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
     }
 }
 
@@ -271,6 +425,9 @@ fn create_list_document() -> Document {
             created: None,
             modified: None,
             author: None,
+            language: None,
+            locale: None,
+            protection: None,
         },
         elements: vec![
             DocumentElement::List {
@@ -310,13 +467,20 @@ fn create_list_document() -> Document {
                 ordered: false,
             },
         ],
+        sections: Vec::new(),
         image_options: Default::default(),
+        fidelity: Default::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
     }
 }
 
 fn create_table_document() -> Document {
     use doxx::document::{
-        CellDataType, DocumentMetadata, TableCell, TableData, TableMetadata, TextAlignment,
+        CellDataType, DocumentMetadata, TableCell, TableData, TableMetadata, TableStyle,
+        TextAlignment,
     };
 
     let table = TableData {
@@ -326,12 +490,14 @@ fn create_table_document() -> Document {
                 alignment: TextAlignment::Left,
                 formatting: TextFormatting::default(),
                 data_type: CellDataType::Text,
+                runs: Vec::new(),
             },
             TableCell {
                 content: "Age".to_string(),
                 alignment: TextAlignment::Right,
                 formatting: TextFormatting::default(),
                 data_type: CellDataType::Number,
+                runs: Vec::new(),
             },
         ],
         rows: vec![vec![
@@ -340,12 +506,14 @@ fn create_table_document() -> Document {
                 alignment: TextAlignment::Left,
                 formatting: TextFormatting::default(),
                 data_type: CellDataType::Text,
+                runs: Vec::new(),
             },
             TableCell {
                 content: "30".to_string(),
                 alignment: TextAlignment::Right,
                 formatting: TextFormatting::default(),
                 data_type: CellDataType::Number,
+                runs: Vec::new(),
             },
         ]],
         metadata: TableMetadata {
@@ -355,6 +523,7 @@ fn create_table_document() -> Document {
             column_count: 2,
             row_count: 1,
             has_headers: true,
+            style: TableStyle::default(),
         },
     };
 
@@ -368,9 +537,18 @@ fn create_table_document() -> Document {
             created: None,
             modified: None,
             author: None,
+            language: None,
+            locale: None,
+            protection: None,
         },
         elements: vec![DocumentElement::Table { table }],
+        sections: Vec::new(),
         image_options: Default::default(),
+        fidelity: Default::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
     }
 }
 
@@ -0,0 +1,152 @@
+use doxx::{
+    document::{
+        load_document, Document, DocumentElement, DocumentMetadata, FormattedRun, ImageOptions,
+        TextAlignment, TextFormatting,
+    },
+    export::{asciidoc::render_asciidoc, infer_export_format},
+    ExportFormat,
+};
+use std::path::Path;
+
+fn paragraph(runs: Vec<FormattedRun>) -> DocumentElement {
+    DocumentElement::Paragraph {
+        runs,
+        alignment: TextAlignment::Left,
+        shading: None,
+        rtl: false,
+        spacing: None,
+        tab_stops: Vec::new(),
+    }
+}
+
+fn run(text: &str, formatting: TextFormatting) -> FormattedRun {
+    FormattedRun {
+        text: text.to_string(),
+        formatting,
+    }
+}
+
+fn create_document(elements: Vec<DocumentElement>) -> Document {
+    Document {
+        title: "Guide".to_string(),
+        metadata: DocumentMetadata {
+            file_path: "guide.docx".to_string(),
+            file_size: 1024,
+            word_count: 10,
+            page_count: 1,
+            created: None,
+            modified: None,
+            author: Some("Test Author".to_string()),
+            language: None,
+            locale: None,
+            protection: None,
+        },
+        elements,
+        sections: Vec::new(),
+        image_options: Default::default(),
+        fidelity: Default::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
+    }
+}
+
+#[test]
+fn test_asciidoc_tables_keep_column_alignment() {
+    let document = load_document(
+        Path::new("tests/fixtures/tables-heavy.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let asciidoc = render_asciidoc(&document);
+
+    assert!(asciidoc.starts_with("= tables-heavy\n"));
+    // Numeric columns are right aligned, text columns left aligned
+    assert!(asciidoc.contains(
+        "[%header,cols=\"<4,>3,<11\"]\n|===\n| Name | Age | City\n\n| John | 25 | New York\n"
+    ));
+    assert!(asciidoc.contains("| Q1 2024 | $150,000 |"));
+}
+
+#[test]
+fn test_asciidoc_blocks() {
+    let bold = TextFormatting {
+        bold: true,
+        ..Default::default()
+    };
+    let document = create_document(vec![
+        DocumentElement::Heading {
+            level: 2,
+            text: "Setup".to_string(),
+            number: Some("1.1".to_string()),
+        },
+        paragraph(vec![
+            run("Warning: ", bold.clone()),
+            run("back up first.", TextFormatting::default()),
+        ]),
+        paragraph(vec![
+            run("Area is ", TextFormatting::default()),
+            run("$\\pi r^{2}$", TextFormatting::default()),
+            run(" where ", TextFormatting::default()),
+            run("r", bold),
+            run(" is the *radius*.", TextFormatting::default()),
+        ]),
+        DocumentElement::Equation {
+            latex: "a^{2}+b^{2}=c^{2}".to_string(),
+            fallback: String::new(),
+            number: None,
+        },
+        DocumentElement::PageBreak { page: 2 },
+        DocumentElement::Image {
+            description: "Diagram, first draft".to_string(),
+            width: Some(320),
+            height: Some(200),
+            relationship_id: None,
+            image_path: Some("images/diagram.png".into()),
+            caption: None,
+            placement: None,
+        },
+    ]);
+    let asciidoc = render_asciidoc(&document);
+
+    assert!(asciidoc.contains(":author: Test Author\n:stem: latexmath\n\n"));
+    assert!(asciidoc.contains("=== 1.1 Setup\n\n"));
+    assert!(asciidoc.contains("WARNING: back up first.\n\n"));
+    assert!(
+        asciidoc.contains("Area is latexmath:[\\pi r^{2}] where **r** ++is the *radius*.++\n\n")
+    );
+    assert!(asciidoc.contains("[latexmath]\n++++\na^{2}+b^{2}=c^{2}\n++++\n\n<<<\n\n"));
+    assert!(asciidoc.contains("image::images/diagram.png[\"Diagram, first draft\",320,200]\n"));
+}
+
+#[test]
+fn test_asciidoc_adjacent_lists_stay_separate() {
+    let item = |text: &str, level| doxx::document::ListItem {
+        runs: vec![run(text, TextFormatting::default())],
+        level,
+    };
+    let document = create_document(vec![
+        DocumentElement::List {
+            items: vec![item("Install", 0), item("Configure", 1)],
+            ordered: true,
+        },
+        DocumentElement::List {
+            items: vec![item("Notes", 0)],
+            ordered: false,
+        },
+    ]);
+    let asciidoc = render_asciidoc(&document);
+
+    assert!(asciidoc.contains(". Install\n.. Configure\n\n//-\n\n* Notes\n"));
+}
+
+#[test]
+fn test_asciidoc_format_is_inferred_from_extension() {
+    for name in ["guide.adoc", "guide.asciidoc"] {
+        assert!(matches!(
+            infer_export_format(Path::new(name)),
+            Some(ExportFormat::Asciidoc)
+        ));
+    }
+}
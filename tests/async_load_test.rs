@@ -0,0 +1,26 @@
+#![cfg(feature = "async")]
+
+use doxx::document::{load_document, load_document_async, ImageOptions};
+use std::path::Path;
+
+#[tokio::test]
+async fn test_async_load_matches_sync_load() {
+    let path = Path::new("tests/fixtures/business-report.docx");
+    let document = load_document_async(path, ImageOptions::default())
+        .await
+        .unwrap();
+    let expected = load_document(path, ImageOptions::default()).unwrap();
+
+    assert_eq!(document.elements.len(), expected.elements.len());
+    assert_eq!(document.metadata.word_count, expected.metadata.word_count);
+}
+
+#[tokio::test]
+async fn test_async_load_reports_errors() {
+    let result = load_document_async(
+        Path::new("tests/fixtures/missing.docx"),
+        ImageOptions::default(),
+    )
+    .await;
+    assert!(result.is_err());
+}
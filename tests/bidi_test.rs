@@ -0,0 +1,142 @@
+#![cfg(feature = "terminal")]
+
+use doxx::{
+    ansi::{export_to_ansi_with_options, AnsiOptions},
+    document::{load_document, Document, DocumentElement, ImageOptions, TextAlignment},
+    widgets::{DocumentWidget, LayoutCache},
+    ColorDepth,
+};
+use ratatui::{backend::TestBackend, Terminal};
+use std::path::Path;
+
+fn load_fixture() -> Document {
+    load_document(
+        Path::new("tests/fixtures/bidi-mixed.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+fn paragraph(element: &DocumentElement) -> (TextAlignment, bool, String) {
+    match element {
+        DocumentElement::Paragraph {
+            runs,
+            alignment,
+            rtl,
+            ..
+        } => (
+            *alignment,
+            *rtl,
+            runs.iter().map(|run| run.text.as_str()).collect(),
+        ),
+        other => panic!("Expected a paragraph, got {other:?}"),
+    }
+}
+
+/// ANSI export with the escape sequences stripped
+fn ansi_lines(document: &Document, width: usize) -> Vec<String> {
+    let options = AnsiOptions {
+        terminal_width: width,
+        color_depth: ColorDepth::Monochrome,
+        ..Default::default()
+    };
+    let output = export_to_ansi_with_options(document, &options).unwrap();
+    let escapes = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+    escapes
+        .replace_all(&output, "")
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_direction_is_parsed() {
+    let document = load_fixture();
+
+    // Text stays in logical order in the model
+    let (alignment, rtl, text) = paragraph(&document.elements[1]);
+    assert!(rtl);
+    assert_eq!(alignment, TextAlignment::Right);
+    assert_eq!(text, "שלום עולם, זו בדיקה של doxx גרסה 2.0 (טיוטה).");
+
+    let DocumentElement::Paragraph { runs, .. } = &document.elements[1] else {
+        unreachable!()
+    };
+    assert!(runs[0].formatting.rtl);
+    assert!(!runs[1].formatting.rtl);
+
+    let (_, rtl, _) = paragraph(&document.elements[3]);
+    assert!(!rtl);
+
+    // `w:jc` edges are relative to the reading direction
+    assert_eq!(paragraph(&document.elements[4]).0, TextAlignment::Center);
+    assert_eq!(paragraph(&document.elements[5]).0, TextAlignment::Left);
+}
+
+#[test]
+fn test_ansi_export_reorders_and_right_aligns() {
+    let lines = ansi_lines(&load_fixture(), 50);
+
+    assert!(lines.contains(&"■ ינועבר חוד".to_string()));
+    // Right-aligned; the Latin word and number keep their order and the
+    // brackets are mirrored so they still enclose the word
+    assert!(lines.contains(&format!(
+        "{}.(הטויט) 2.0 הסרג doxx לש הקידב וז ,םלוע םולש",
+        " ".repeat(5)
+    )));
+    assert!(lines
+        .iter()
+        .any(|line| line.ends_with(".يبرع صن اذه ،ملاعلاب ابحرم")));
+    // Hebrew inside an English paragraph is reversed in place
+    assert!(lines.contains(&"The Hebrew word םולש means peace.".to_string()));
+    assert!(lines.contains(&"│ טירפ │ ריחמ │".to_string()));
+}
+
+#[test]
+fn test_wrapped_rtl_lines_read_top_to_bottom() {
+    let lines = ansi_lines(&load_fixture(), 20);
+    let start = lines
+        .iter()
+        .position(|line| line.ends_with("םולש"))
+        .unwrap();
+    assert_eq!(
+        &lines[start..start + 3],
+        &[
+            " הקידב וז ,םלוע םולש",
+            "    2.0 הסרג doxx לש",
+            "            .(הטויט)",
+        ]
+    );
+}
+
+#[test]
+fn test_viewer_renders_rtl_paragraphs_flush_right() {
+    let document = load_fixture();
+    let mut terminal = Terminal::new(TestBackend::new(30, 12)).unwrap();
+    let mut layout_cache = LayoutCache::new();
+    terminal
+        .draw(|frame| {
+            DocumentWidget::new(&document.elements[1..4]).render(
+                frame.area(),
+                frame,
+                &mut [],
+                &mut layout_cache,
+            );
+        })
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let row = |y: u16| -> String {
+        (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect::<String>()
+    };
+
+    // The viewer wraps by grapheme; each line is reordered on its own and
+    // right-to-left paragraphs end flush with the right edge
+    assert_eq!(row(0), "רג doxx לש הקידב וז ,םלוע םולש");
+    assert_eq!(row(1), format!("{}.(הטויט) 2.0 הס", " ".repeat(15)));
+    assert_eq!(row(3), "   .يبرع صن اذه ،ملاعلاب ابحرم");
+    assert_eq!(row(5), "The Hebrew word םולש means pea");
+    assert_eq!(row(6).trim_end(), "ce.");
+}
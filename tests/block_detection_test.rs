@@ -0,0 +1,59 @@
+use doxx::document::{load_document, DocumentElement, ImageOptions};
+use doxx::export::{render_markdown, ExportOptions};
+use std::path::Path;
+
+const FIXTURE: &str = "tests/fixtures/quotes-and-code.docx";
+
+#[test]
+fn test_quote_style_paragraphs_are_block_quotes() {
+    // The quote style's ID is German, but its name is the built-in "Quote"
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let quotes: Vec<String> = document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::BlockQuote { runs } => {
+                Some(runs.iter().map(|run| run.text.as_str()).collect())
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        quotes,
+        ["Write the way you would explain it to a colleague across the desk."]
+    );
+}
+
+#[test]
+fn test_consecutive_code_paragraphs_form_one_block() {
+    // One block from a monospace style, keeping its blank line, and one from
+    // runs set in Courier New; a paragraph with one monospace run stays prose
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let blocks: Vec<&str> = document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::CodeBlock { code } => Some(code.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        blocks,
+        [
+            "fn area(width: f64, height: f64) -> f64 {\n    width * height\n}\n\n// Call it with two sides",
+            "cargo build --release\ncargo test",
+        ]
+    );
+    assert!(matches!(
+        document.elements.last(),
+        Some(DocumentElement::Paragraph { .. })
+    ));
+}
+
+#[test]
+fn test_markdown_export_of_quotes_and_code() {
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let markdown = render_markdown(&document, &ExportOptions::default());
+    assert!(markdown.contains("> Write the way you would explain it"));
+    assert!(markdown.contains("```\ncargo build --release\ncargo test\n```\n"));
+}
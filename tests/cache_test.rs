@@ -0,0 +1,112 @@
+use doxx::document::cache::ParseCache;
+use doxx::document::{FieldOptions, ImageOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A cache in a directory of its own under the system temp directory
+fn cache(name: &str, max_bytes: u64) -> ParseCache {
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("doxx-cache-test-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    ParseCache { dir, max_bytes }
+}
+
+fn entries(cache: &ParseCache) -> Vec<PathBuf> {
+    fs::read_dir(&cache.dir)
+        .map(|listing| listing.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_cached_document_matches_a_fresh_parse() {
+    let cache = cache("hit", u64::MAX);
+    let path = Path::new("tests/fixtures/business-report.docx");
+    let options = FieldOptions::default();
+    let parsed = cache.load(path, ImageOptions::default(), options).unwrap();
+    assert_eq!(entries(&cache).len(), 1);
+
+    let cached = cache.load(path, ImageOptions::default(), options).unwrap();
+    assert_eq!(
+        serde_json::to_value(&cached).unwrap(),
+        serde_json::to_value(&parsed).unwrap()
+    );
+    assert_eq!(cache.usage().unwrap().entries, 1);
+
+    // Other options are another entry
+    let showing = FieldOptions {
+        show_unknown: true,
+        ..options
+    };
+    cache.load(path, ImageOptions::default(), showing).unwrap();
+    assert_eq!(cache.usage().unwrap().entries, 2);
+    assert_eq!(cache.clear().unwrap().entries, 2);
+    assert!(entries(&cache).is_empty());
+    let _ = fs::remove_dir_all(&cache.dir);
+}
+
+#[test]
+fn test_unreadable_entries_are_parsed_again() {
+    let cache = cache("corrupt", u64::MAX);
+    let path = Path::new("tests/fixtures/minimal.docx");
+    cache
+        .load(path, ImageOptions::default(), FieldOptions::default())
+        .unwrap();
+    let entry = &entries(&cache)[0];
+    fs::write(entry, b"not a document").unwrap();
+
+    let document = cache
+        .load(path, ImageOptions::default(), FieldOptions::default())
+        .unwrap();
+    assert_eq!(document.title, "minimal");
+    let _ = fs::remove_dir_all(&cache.dir);
+}
+
+#[test]
+fn test_oldest_entries_are_evicted_over_the_limit() {
+    let mut cache = cache("evict", u64::MAX);
+    let options = (ImageOptions::default(), FieldOptions::default());
+    for name in ["minimal", "example", "business-report"] {
+        let path = PathBuf::from(format!("tests/fixtures/{name}.docx"));
+        cache.load(&path, options.0.clone(), options.1).unwrap();
+        // Modification times only tell entries apart if they differ
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    let usage = cache.usage().unwrap();
+    assert_eq!(usage.entries, 3);
+
+    let newest = fs::metadata(
+        entries(&cache)
+            .iter()
+            .max_by_key(|path| fs::metadata(path).unwrap().modified().unwrap())
+            .unwrap(),
+    )
+    .unwrap()
+    .len();
+    cache.max_bytes = newest;
+    let removed = cache.evict().unwrap();
+    assert_eq!(removed.entries, 2);
+    assert_eq!(cache.usage().unwrap().bytes, newest);
+    let _ = fs::remove_dir_all(&cache.dir);
+}
+
+#[test]
+fn test_documents_with_date_and_file_name_fields_are_not_cached() {
+    let cache = cache("volatile", u64::MAX);
+    let options = (ImageOptions::default(), FieldOptions::default());
+    let path = Path::new("tests/fixtures/fields.docx");
+    let parsed = cache.load(path, options.0.clone(), options.1).unwrap();
+    assert!(parsed.volatile_fields > 0);
+    // DATE is read at load time, so no entry may keep today's date
+    assert!(entries(&cache).is_empty());
+
+    // The same bytes under another name show that name in FILENAME
+    let renamed = cache.dir.with_extension("renamed.docx");
+    fs::copy(path, &renamed).unwrap();
+    let document = cache.load(&renamed, options.0, options.1).unwrap();
+    let text = serde_json::to_string(&document.elements).unwrap();
+    let renamed_name = renamed.file_name().unwrap().to_string_lossy().into_owned();
+    assert!(text.contains(&renamed_name));
+    assert!(!text.contains("fields.docx"));
+    assert!(entries(&cache).is_empty());
+    fs::remove_file(renamed).unwrap();
+}
@@ -0,0 +1,83 @@
+use doxx::{
+    document::{caption_list, caption_outline, load_document, CaptionKind, ImageOptions},
+    export::outline::render_outline,
+    DocumentElement, OutlineFormat,
+};
+use std::path::Path;
+
+fn load(images: bool) -> doxx::Document {
+    load_document(
+        Path::new("tests/fixtures/captions.docx"),
+        ImageOptions {
+            enabled: images,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_table_captions_become_numbered_titles() {
+    let document = load(false);
+    let titles: Vec<_> = document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Table { table } => table.metadata.title.as_deref(),
+            _ => None,
+        })
+        .collect();
+    // The first caption's cached number is stale; SEQ fields count afresh
+    assert_eq!(
+        titles,
+        ["Table 1: Sales by region", "Table 2: Costs by quarter"]
+    );
+
+    // Attached captions aren't repeated as paragraphs
+    let markdown = doxx::export::render_markdown(&document, &Default::default());
+    assert_eq!(markdown.matches("Sales by region").count(), 1);
+}
+
+#[test]
+fn test_figure_captions_attach_to_images() {
+    let document = load(true);
+    let captions = caption_list(&document);
+    let figures: Vec<_> = captions
+        .iter()
+        .filter(|entry| entry.kind == CaptionKind::Figure)
+        .collect();
+    assert_eq!(figures.len(), 1);
+    assert_eq!(figures[0].text, "Figure 1: Growth since 2020");
+    assert!(matches!(
+        &document.elements[figures[0].element_index],
+        DocumentElement::Image { caption: Some(caption), .. } if caption == &figures[0].text
+    ));
+
+    // A caption with no figure beside it stays in the text
+    let markdown = doxx::export::render_markdown(&document, &Default::default());
+    assert!(markdown.contains("*Figure 1: Growth since 2020*"));
+    assert!(markdown.contains("Figure 2: Map of the offices"));
+}
+
+#[test]
+fn test_outline_lists_tables_and_figures() {
+    let document = load(true);
+    let outline = caption_outline(&document);
+    let titles: Vec<_> = outline.iter().map(|item| item.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        [
+            "List of Tables",
+            "Table 1: Sales by region",
+            "Table 2: Costs by quarter",
+            "List of Figures",
+            "Figure 1: Growth since 2020",
+        ]
+    );
+
+    let tree = render_outline(&document, OutlineFormat::Tree).unwrap();
+    assert!(tree.contains("List of Tables\n  Table 1: Sales by region\n"));
+    let json = render_outline(&document, OutlineFormat::Json).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["figures"][0]["text"], "Figure 1: Growth since 2020");
+}
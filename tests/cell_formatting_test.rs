@@ -0,0 +1,148 @@
+#![cfg(feature = "terminal")]
+
+use doxx::{
+    document::{load_document, Document, DocumentElement, ImageOptions, TableData},
+    export::json::to_json_value,
+    widgets::{DocumentWidget, LayoutCache},
+    JsonCase,
+};
+use ratatui::{backend::TestBackend, buffer::Buffer, style::Modifier, Terminal};
+use std::path::Path;
+use std::process::Command;
+
+fn cell_formatting() -> Document {
+    load_document(
+        Path::new("tests/fixtures/cell-formatting.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+fn table(document: &Document) -> &TableData {
+    document
+        .elements
+        .iter()
+        .find_map(|element| match element {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        })
+        .unwrap()
+}
+
+fn export(format: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["tests/fixtures/cell-formatting.docx", "--export", format])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_cells_keep_the_formatting_of_each_run() {
+    let document = cell_formatting();
+    let table = table(&document);
+
+    let notes = &table.rows[0][2];
+    assert_eq!(notes.content, "Ships Monday, not Friday");
+    let runs: Vec<(&str, bool, bool)> = notes
+        .runs
+        .iter()
+        .map(|run| {
+            (
+                run.text.as_str(),
+                run.formatting.bold,
+                run.formatting.italic,
+            )
+        })
+        .collect();
+    assert_eq!(
+        runs,
+        [
+            ("Ships ", false, false),
+            ("Monday", false, true),
+            (", ", false, false),
+            ("not", true, false),
+            (" Friday", false, false),
+        ]
+    );
+    assert_eq!(
+        table.rows[1][2].runs[1].formatting.color.as_deref(),
+        Some("FF0000")
+    );
+
+    // A cell formatted throughout has no runs of its own
+    let done = &table.rows[0][1];
+    assert!(done.runs.is_empty() && done.formatting.bold);
+    assert_eq!(done.formatted_runs().len(), 1);
+
+    // Runs split in the middle of a word are joined without a space, and
+    // paragraphs with one
+    assert_eq!(table.rows[2][0].content, "Gizmo");
+    assert_eq!(table.rows[2][2].content, "First line Second line");
+    assert!(table.rows[2][2].runs[1].formatting.underline);
+}
+
+#[test]
+fn test_viewer_draws_each_run_of_a_cell_in_its_style() {
+    let document = cell_formatting();
+    let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+    let mut layout_cache = LayoutCache::new();
+    terminal
+        .draw(|frame| {
+            DocumentWidget::new(&document.elements).render(
+                frame.area(),
+                frame,
+                &mut [],
+                &mut layout_cache,
+            );
+        })
+        .unwrap();
+    let buffer: &Buffer = terminal.backend().buffer();
+
+    let find = |text: &str| {
+        (0..buffer.area.height)
+            .find_map(|y| {
+                let row: String = (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect();
+                row.find(text)
+                    .map(|at| (row[..at].chars().count() as u16, y))
+            })
+            .unwrap()
+    };
+    let modifier = |(x, y): (u16, u16)| buffer[(x, y)].modifier;
+
+    assert!(modifier(find("Monday")).contains(Modifier::ITALIC));
+    assert!(!modifier(find("Ships")).contains(Modifier::ITALIC));
+    assert!(modifier(find("not")).contains(Modifier::BOLD));
+    assert!(!modifier(find("Friday")).contains(Modifier::BOLD));
+    assert!(modifier(find("Second")).contains(Modifier::UNDERLINED));
+    assert!(!modifier(find("First")).contains(Modifier::UNDERLINED));
+}
+
+#[test]
+fn test_exports_keep_cell_formatting_and_csv_stays_plain() {
+    let markdown = export("markdown");
+    assert!(markdown.contains("| Widget | **Done** | Ships *Monday*, **not** Friday |"));
+    // The header row is bold already
+    assert!(markdown.contains("| Item | Status | Notes |"));
+
+    let html = export("html");
+    assert!(html.contains("<td>Ships <em>Monday</em>, <strong>not</strong> Friday</td>"));
+
+    let csv = export("csv");
+    assert!(csv.contains("Widget,Done,\"Ships Monday, not Friday\""));
+
+    let json = to_json_value(&cell_formatting(), JsonCase::Snake).unwrap();
+    let table = json["elements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|element| element["type"] == "table")
+        .unwrap();
+    let row = &table["rows"][0];
+    assert_eq!(row[2]["runs"][1]["text"], "Monday");
+    assert_eq!(row[2]["runs"][1]["italic"], true);
+    assert!(row[1].get("runs").is_none());
+}
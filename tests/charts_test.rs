@@ -0,0 +1,170 @@
+#![cfg(feature = "terminal")]
+
+use doxx::{
+    ansi::{export_to_ansi_with_options, AnsiOptions},
+    document::{
+        chart, load_document, load_document_with_fields, CellLocale, Document, DocumentElement,
+        FieldOptions, ImageOptions,
+    },
+    widgets::{DocumentWidget, LayoutCache},
+    ChartStyle, ColorDepth,
+};
+use ratatui::{backend::TestBackend, Terminal};
+use std::path::Path;
+
+fn load(fixture: &str) -> Document {
+    load_document(
+        &Path::new("tests/fixtures").join(fixture),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+/// The quarterly figures: Quarter, Revenue, Expenses, Profit and Margin %
+fn financial_table(document: &Document) -> &doxx::document::TableData {
+    document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        })
+        .nth(1)
+        .unwrap()
+}
+
+/// ANSI export with the escape sequences stripped
+fn ansi_lines(document: &Document, charts: ChartStyle) -> Vec<String> {
+    let options = AnsiOptions {
+        terminal_width: 60,
+        color_depth: ColorDepth::Monochrome,
+        charts: Some(charts),
+        ..Default::default()
+    };
+    let output = export_to_ansi_with_options(document, &options).unwrap();
+    let escapes = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+    escapes
+        .replace_all(&output, "")
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_numeric_columns_skip_labels_and_text() {
+    let document = load("tables-heavy.docx");
+    let table = financial_table(&document);
+    let columns = chart::numeric_columns(table, document.cell_locale());
+    let names: Vec<&str> = columns.iter().map(|series| series.name.as_str()).collect();
+    assert_eq!(names, ["Revenue", "Expenses", "Profit", "Margin %"]);
+    assert_eq!(columns[2].values[1], Some((45000.0, "$45,000".to_string())));
+
+    // Name, Age and City: only the ages are numbers
+    let people = match &document
+        .elements
+        .iter()
+        .find(|element| matches!(element, DocumentElement::Table { .. }))
+    {
+        Some(DocumentElement::Table { table }) => table,
+        _ => unreachable!(),
+    };
+    let columns = chart::numeric_columns(people, CellLocale::default());
+    assert_eq!(columns.len(), 1);
+    assert_eq!(columns[0].name, "Age");
+}
+
+#[test]
+fn test_ansi_export_draws_sparklines_below_tables() {
+    let lines = ansi_lines(&load("tables-heavy.docx"), ChartStyle::Sparkline);
+    let revenue = lines
+        .iter()
+        .position(|line| line == "Revenue   ▁▃▆█  $150,000 – $225,000")
+        .unwrap();
+    assert!(lines[revenue - 2].starts_with("│ Q4 2024"));
+    assert!(lines[revenue - 1].is_empty());
+    assert_eq!(lines[revenue + 3], "Margin %  ▁▄▆█  20% – 33.3%");
+}
+
+#[test]
+fn test_ansi_export_draws_bars_by_row() {
+    let lines = ansi_lines(&load("tables-heavy.docx"), ChartStyle::Bars);
+    let profit = lines.iter().position(|line| line == "Profit").unwrap();
+    let bars: Vec<&str> = lines[profit + 1..profit + 5]
+        .iter()
+        .map(|line| line.trim_end())
+        .collect();
+    assert!(bars[0].starts_with("  Q1 2024  ███"));
+    assert!(bars[3].ends_with("█  $75,000"));
+    // A quarter of the way down the bar runs 40% as far as the largest
+    let bar = |line: &str| line.chars().filter(|&c| c == '█').count();
+    assert_eq!(bar(bars[0]) * 5, bar(bars[3]) * 2);
+    assert!(lines.iter().all(|line| line.chars().count() <= 60));
+}
+
+#[test]
+fn test_charts_follow_the_cell_locale() {
+    // Cells are typed in the locale they're read in
+    let locale: CellLocale = "fr-FR".parse().unwrap();
+    let document = load_document_with_fields(
+        Path::new("tests/fixtures/european-figures.docx"),
+        ImageOptions::default(),
+        FieldOptions {
+            locale: Some(locale),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let table = match document
+        .elements
+        .iter()
+        .find(|element| matches!(element, DocumentElement::Table { .. }))
+    {
+        Some(DocumentElement::Table { table }) => table,
+        _ => unreachable!(),
+    };
+    let lines = chart::table_charts(table, locale, ChartStyle::Sparkline, 60);
+    assert_eq!(lines[0].graph, "▁▁█");
+    assert_eq!(lines[0].value, "987,10 – 12.050,00");
+}
+
+#[test]
+fn test_viewer_draws_charts_below_tables() {
+    let document = load("tables-heavy.docx");
+    let start = document
+        .elements
+        .iter()
+        .position(|element| matches!(element, DocumentElement::Table { table } if table.headers[0].content == "Quarter"))
+        .unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+    let mut layout_cache = LayoutCache::new();
+    terminal
+        .draw(|frame| {
+            DocumentWidget::new(&document.elements)
+                .scroll_offset(start)
+                .charts(Some(ChartStyle::Sparkline), document.cell_locale())
+                .render(frame.area(), frame, &mut [], &mut layout_cache);
+        })
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let rows: Vec<String> = (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+    let revenue = rows
+        .iter()
+        .position(|row| row.starts_with("Revenue   ▁▃▆█"))
+        .unwrap();
+    // The table's grid closes under its last row
+    assert!(rows[revenue - 3].contains("Q4 2024"));
+    assert!(rows[revenue - 2].starts_with('└'));
+    assert!(rows[revenue + 3].starts_with("Margin %"));
+    // The next element starts after the charts and a blank line
+    assert!(rows[revenue + 4].is_empty());
+    assert!(!rows[revenue + 5].is_empty());
+}
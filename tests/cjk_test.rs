@@ -0,0 +1,156 @@
+#![cfg(feature = "terminal")]
+
+use doxx::{
+    ansi::{export_to_ansi_with_options, AnsiOptions},
+    document::{load_document, reading_minutes, Document, DocumentElement, ImageOptions},
+    export::format_as_text,
+    widgets::{DocumentWidget, LayoutCache},
+    ColorDepth,
+};
+use ratatui::{backend::TestBackend, Terminal};
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
+
+fn load_fixture() -> Document {
+    load_document(
+        Path::new("tests/fixtures/cjk-text.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+/// ANSI export with the escape sequences stripped
+fn ansi_lines(document: &Document, width: usize) -> Vec<String> {
+    let options = AnsiOptions {
+        terminal_width: width,
+        color_depth: ColorDepth::Monochrome,
+        ..Default::default()
+    };
+    let output = export_to_ansi_with_options(document, &options).unwrap();
+    let escapes = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+    escapes
+        .replace_all(&output, "")
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Assert that every line of a table is as wide as the first, so the borders line up
+fn assert_aligned(lines: &[&str]) {
+    let width = lines[0].width();
+    for line in lines {
+        assert_eq!(line.width(), width, "misaligned table line: {line:?}");
+    }
+}
+
+#[test]
+fn test_column_widths_count_columns() {
+    let document = load_fixture();
+    let table = document
+        .elements
+        .iter()
+        .find_map(|element| match element {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        })
+        .unwrap();
+
+    // "ノートパソコン" is seven characters but fourteen columns
+    assert_eq!(table.rows[2][1].display_width(), 14);
+    assert_eq!(table.metadata.column_widths, vec![4, 14, 10]);
+}
+
+#[test]
+fn test_text_table_borders_line_up() {
+    let text = format_as_text(&load_fixture());
+    let table: Vec<&str> = text
+        .lines()
+        .skip_while(|line| !line.starts_with('┌'))
+        .take_while(|line| !line.is_empty())
+        .collect();
+
+    assert_eq!(table.len(), 7);
+    assert_aligned(&table);
+    assert_eq!(table[4], "│ 华南 │ Tablet         │ ¥850,000   │");
+}
+
+#[test]
+fn test_ansi_export_wraps_and_aligns_by_column() {
+    let lines = ansi_lines(&load_fixture(), 30);
+
+    // CJK text breaks between characters and closing punctuation stays on the
+    // line before it; Latin words keep breaking at spaces
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("本报告"))
+        .unwrap();
+    assert_eq!(
+        &lines[start..start + 4],
+        &[
+            "本报告总结了第三季度的销售情",
+            "况，所有金额均以人民币计算。各",
+            "地区的销售额均有所增长，其中华",
+            "东地区增长最快。",
+        ]
+    );
+    assert!(lines.contains(&"Word 文档 in the terminal.".to_string()));
+
+    // Centred by display width: six ideographs take twelve of the thirty columns
+    assert!(lines.contains(&format!("{}居中的标题行", " ".repeat(9))));
+
+    let table: Vec<&str> = lines
+        .iter()
+        .map(String::as_str)
+        .filter(|line| line.starts_with(['│', '├']))
+        .collect();
+    assert_eq!(table.len(), 5);
+    assert_aligned(&table);
+    assert_eq!(table[4], "│ 東京 │ ノートパソコン │ ¥98,000    │");
+}
+
+#[test]
+fn test_viewer_table_borders_line_up() {
+    let document = load_fixture();
+    let table = document
+        .elements
+        .iter()
+        .position(|element| matches!(element, DocumentElement::Table { .. }))
+        .unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+    let mut layout_cache = LayoutCache::new();
+    terminal
+        .draw(|frame| {
+            DocumentWidget::new(&document.elements[table..]).render(
+                frame.area(),
+                frame,
+                &mut [],
+                &mut layout_cache,
+            );
+        })
+        .unwrap();
+
+    // Wide characters fill two cells, so each column border sits at the same x
+    // on every row
+    let buffer = terminal.backend().buffer();
+    let borders = |y: u16| -> Vec<u16> {
+        (0..buffer.area.width)
+            .filter(|&x| buffer[(x, y)].symbol() == "│")
+            .collect()
+    };
+    let rows: Vec<u16> = (0..buffer.area.height)
+        .filter(|&y| !borders(y).is_empty())
+        .collect();
+    assert!(rows.len() >= 4, "expected a header and three rows");
+    for &y in &rows {
+        assert_eq!(borders(y), borders(rows[0]), "row {y} is misaligned");
+    }
+}
+
+#[test]
+fn test_words_are_counted_by_character() {
+    let document = load_fixture();
+    // Fourteen words split at spaces, but each Chinese character and kana is
+    // a word of its own
+    assert_eq!(document.metadata.word_count, 114);
+    assert_eq!(reading_minutes(&document), 1);
+}
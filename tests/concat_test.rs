@@ -0,0 +1,62 @@
+use doxx::document::{
+    concat_documents, generate_outline, load_document, search_document, Document, DocumentElement,
+    ImageOptions,
+};
+use std::path::Path;
+
+fn load(fixture: &str) -> Document {
+    load_document(
+        &Path::new("tests/fixtures").join(fixture),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_concat_puts_each_file_behind_a_boundary() {
+    let (report, sections) = (load("business-report.docx"), load("sections.docx"));
+    let merged = concat_documents(vec![report.clone(), sections.clone()]).unwrap();
+
+    let second = report.elements.len() + 1;
+    assert_eq!(merged.elements.len(), second + 1 + sections.elements.len());
+    assert!(matches!(&merged.elements[0],
+        DocumentElement::FileBoundary { name } if name == "business-report.docx"));
+    assert!(matches!(&merged.elements[second],
+        DocumentElement::FileBoundary { name } if name == "sections.docx"));
+    assert_eq!(merged.title, report.title);
+    assert_eq!(
+        merged.metadata.word_count,
+        report.metadata.word_count + sections.metadata.word_count
+    );
+
+    // Sections keep covering their own file's elements
+    let first_section = merged.sections.len() - sections.sections.len();
+    assert_eq!(merged.sections[first_section].start, second);
+    assert_eq!(
+        merged.sections.last().unwrap().end,
+        second + 1 + sections.sections.last().unwrap().end
+    );
+}
+
+#[test]
+fn test_concat_outline_and_search_span_the_files() {
+    let (report, sections) = (load("business-report.docx"), load("sections.docx"));
+    let report_outline = generate_outline(&report);
+    let merged = concat_documents(vec![report, sections]).unwrap();
+
+    let outline = generate_outline(&merged);
+    assert_eq!(outline[0].title, "business-report.docx");
+    assert_eq!(outline[0].level, 1);
+    assert_eq!(outline[1].title, report_outline[0].title);
+    assert_eq!(outline[1].level, report_outline[0].level + 1);
+    let file = outline
+        .iter()
+        .position(|item| item.title == "sections.docx")
+        .unwrap();
+    assert_eq!(outline[file].level, 1);
+
+    let results = search_document(&merged, "landscape");
+    assert!(results
+        .iter()
+        .any(|result| result.element_index > outline[file].element_index));
+}
@@ -0,0 +1,65 @@
+use doxx::{
+    document::{load_document, ImageOptions},
+    export::csv::{write_csv, CsvOptions},
+    CsvDelimiter, DecimalSeparator,
+};
+use std::path::Path;
+
+fn export_csv(fixture: &str, csv: CsvOptions) -> String {
+    let document = load_document(
+        &Path::new("tests/fixtures").join(fixture),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let mut output = Vec::new();
+    write_csv(&mut output, &document, &csv).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_cells_are_written_by_type() {
+    let csv = export_csv("tables-heavy.docx", CsvOptions::default());
+    // Currency symbols, digit grouping and percent signs are dropped
+    assert!(csv.contains("Quarter,Revenue,Expenses,Profit,Margin %\n"));
+    assert!(csv.contains("Q1 2024,150000,120000,30000,20\n"));
+    assert!(csv.contains("Q2 2024,175000,130000,45000,25.7\n"));
+    // Text stays as written
+    assert!(csv.contains("John,25,New York\n"));
+}
+
+#[test]
+fn test_decimal_comma_reads_and_writes_european_numbers() {
+    let csv = export_csv("german-report.docx", CsvOptions::default());
+    assert!(csv.contains("Region,Umsatz,Kunden,Geprüft am\n"));
+    assert!(csv.contains("Nord,1234.5,1204,2024-09-30\n"));
+
+    let csv = export_csv(
+        "german-report.docx",
+        CsvOptions {
+            decimal: DecimalSeparator::Comma,
+            types: true,
+            ..Default::default()
+        },
+    );
+    // Semicolons between fields unless a delimiter is given
+    assert!(csv.contains(
+        "Region;Umsatz;Kunden;Geprüft am\ntext;currency;number;date\nNord;1234,5;1204;2024-09-30\n"
+    ));
+    assert!(csv.contains("West;876,25;1010;2024-10-15\n"));
+
+    let csv = export_csv(
+        "german-report.docx",
+        CsvOptions {
+            delimiter: Some(CsvDelimiter::Tab),
+            decimal: DecimalSeparator::Comma,
+            ..Default::default()
+        },
+    );
+    assert!(csv.contains("Nord\t1234,5\t1204\t2024-09-30\n"));
+}
+
+#[test]
+fn test_raw_cells_are_kept_as_written() {
+    let csv = export_csv("tables-heavy.docx", CsvOptions::as_written());
+    assert!(csv.contains("Q1 2024,\"$150,000\",\"$120,000\",\"$30,000\",20%\n"));
+}
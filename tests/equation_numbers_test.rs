@@ -0,0 +1,134 @@
+#![cfg(feature = "terminal")]
+
+use doxx::{
+    document::{load_document, Document, DocumentElement, ImageOptions},
+    export::json::to_json_value,
+    widgets::{DocumentWidget, LayoutCache},
+    JsonCase,
+};
+use ratatui::{backend::TestBackend, Terminal};
+use std::path::Path;
+use std::process::Command;
+
+fn equation_numbers() -> Document {
+    load_document(
+        Path::new("tests/fixtures/equation-numbers.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+fn export(format: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["tests/fixtures/equation-numbers.docx", "--export", format])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_display_equations_take_the_numbers_beside_them() {
+    let document = equation_numbers();
+    let equations: Vec<(&str, Option<&str>)> = document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Equation { latex, number, .. } => {
+                Some((latex.as_str(), number.as_deref()))
+            }
+            _ => None,
+        })
+        .collect();
+    // From a SEQ field, from plain text and from Word's own `#(3)`
+    assert_eq!(
+        equations,
+        [
+            ("E=mc^{2}", Some("(1)")),
+            ("A=\\pi r^{2}", Some("(2)")),
+            ("x+y=z", Some("(3)")),
+        ]
+    );
+
+    // The numbers no longer stand as paragraphs of their own, and the
+    // equations are where their paragraphs were
+    let kinds: Vec<&str> = document
+        .elements
+        .iter()
+        .map(|element| match element {
+            DocumentElement::Equation { .. } => "equation",
+            DocumentElement::Paragraph { .. } => "paragraph",
+            _ => "other",
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            "other",
+            "paragraph",
+            "equation",
+            "paragraph",
+            "equation",
+            "paragraph",
+            "equation"
+        ]
+    );
+
+    assert_eq!(
+        document.equation_bookmarks.get("_Ref2").map(String::as_str),
+        Some("(2)")
+    );
+}
+
+#[test]
+fn test_viewer_sets_the_number_at_the_right_edge() {
+    let document = equation_numbers();
+    let mut terminal = Terminal::new(TestBackend::new(40, 20)).unwrap();
+    let mut layout_cache = LayoutCache::new();
+    terminal
+        .draw(|frame| {
+            DocumentWidget::new(&document.elements).render(
+                frame.area(),
+                frame,
+                &mut [],
+                &mut layout_cache,
+            );
+        })
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+
+    let row = (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+        })
+        .find(|row| row.contains("E=mc^{2}"))
+        .unwrap();
+    assert!(row.ends_with(" (1)"), "{row:?}");
+}
+
+#[test]
+fn test_exports_number_equations_and_link_cross_references() {
+    let markdown = export("markdown");
+    assert!(markdown.contains("<a id=\"eq-1\"></a>\n\n$$E=mc^{2} \\tag{1}$$"));
+    assert!(markdown.contains("Equation [(2)](#eq-2) holds"));
+
+    let html = export("html");
+    assert!(html.contains("<div class=\"math\" id=\"eq-2\">\\[A=\\pi r^{2} \\tag{2}\\]</div>"));
+    assert!(html.contains("Energy follows <a href=\"#eq-1\">(1)</a>"));
+
+    let text = export("text");
+    assert!(text
+        .lines()
+        .any(|line| line.starts_with("Equation: x+y=z") && line.ends_with("  (3)")));
+
+    let json = to_json_value(&equation_numbers(), JsonCase::Snake).unwrap();
+    let equations: Vec<&serde_json::Value> = json["elements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|element| element["type"] == "equation")
+        .collect();
+    assert_eq!(equations[0]["number"], "(1)");
+}
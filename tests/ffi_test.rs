@@ -0,0 +1,65 @@
+#![cfg(feature = "ffi")]
+
+use doxx::ffi::{
+    doxx_export_markdown, doxx_last_error, doxx_parse, doxx_string_free, doxx_version,
+};
+use std::ffi::{c_char, CStr};
+
+/// Copy a returned string and free it, as a C caller would
+unsafe fn take(string: *mut c_char) -> Option<String> {
+    if string.is_null() {
+        return None;
+    }
+    let owned = CStr::from_ptr(string).to_str().unwrap().to_string();
+    doxx_string_free(string);
+    Some(owned)
+}
+
+fn last_error() -> Option<String> {
+    let error = doxx_last_error();
+    (!error.is_null()).then(|| {
+        unsafe { CStr::from_ptr(error) }
+            .to_string_lossy()
+            .into_owned()
+    })
+}
+
+#[test]
+fn test_parse_returns_json() {
+    let bytes = std::fs::read("tests/fixtures/business-report.docx").unwrap();
+    let json = unsafe { take(doxx_parse(bytes.as_ptr(), bytes.len())) }.unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert!(parsed["elements"].as_array().is_some_and(|e| !e.is_empty()));
+    assert_eq!(last_error(), None);
+}
+
+#[test]
+fn test_export_markdown() {
+    let bytes = std::fs::read("tests/fixtures/minimal.docx").unwrap();
+    let markdown = unsafe { take(doxx_export_markdown(bytes.as_ptr(), bytes.len())) }.unwrap();
+    assert!(markdown.starts_with("# Untitled Document\n"));
+}
+
+#[test]
+fn test_errors_are_reported_through_last_error() {
+    let garbage = b"not a zip file";
+    let result = unsafe { take(doxx_parse(garbage.as_ptr(), garbage.len())) };
+    assert_eq!(result, None);
+    assert!(last_error().unwrap().contains("Not a .docx file"));
+
+    let result = unsafe { take(doxx_parse(std::ptr::null(), 10)) };
+    assert_eq!(result, None);
+    assert!(last_error().unwrap().contains("NULL"));
+
+    // A success clears the error
+    let bytes = std::fs::read("tests/fixtures/minimal.docx").unwrap();
+    unsafe { take(doxx_parse(bytes.as_ptr(), bytes.len())) }.unwrap();
+    assert_eq!(last_error(), None);
+}
+
+#[test]
+fn test_version_and_null_free() {
+    let version = unsafe { CStr::from_ptr(doxx_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    unsafe { doxx_string_free(std::ptr::null_mut()) };
+}
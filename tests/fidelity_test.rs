@@ -0,0 +1,120 @@
+use doxx::{
+    document::{load_document, DocumentElement, Fidelity, ImageOptions},
+    export::fidelity::fidelity_summary,
+    ExportFormat,
+};
+use std::path::Path;
+use std::process::Command;
+
+fn images_on() -> ImageOptions {
+    ImageOptions {
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_loader_records_what_it_could_not_keep() {
+    let document =
+        load_document(Path::new("tests/fixtures/lossy-content.docx"), images_on()).unwrap();
+    assert_eq!(
+        document.fidelity,
+        Fidelity {
+            unsupported_drawings: 0,
+            charts: 1,
+            diagrams: 0,
+            embedded_objects: 0,
+            omitted_images: 0,
+            flattened_tables: 1,
+            unevaluated_fields: 1,
+        }
+    );
+
+    // The nested table's text is kept in its cell
+    let table = document
+        .elements
+        .iter()
+        .find_map(|element| match element {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(table.rows[0][1].content, "Split: North 120 South 95");
+}
+
+#[test]
+fn test_summary_adds_the_format_fallbacks() {
+    let document =
+        load_document(Path::new("tests/fixtures/lossy-content.docx"), images_on()).unwrap();
+
+    let markdown = fidelity_summary(&document, &ExportFormat::Markdown, false);
+    assert_eq!(
+        markdown.to_string(),
+        "4 elements exported, 1 chart skipped, 1 nested table flattened, 1 field unevaluated"
+    );
+
+    let csv = fidelity_summary(&document, &ExportFormat::Csv, false);
+    assert_eq!(csv.exported, 1);
+    assert_eq!(csv.losses.last().unwrap(), "3 non-table elements left out");
+}
+
+#[test]
+fn test_images_left_out_without_image_support() {
+    let document = load_document(
+        Path::new("tests/fixtures/images.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let summary = fidelity_summary(&document, &ExportFormat::Text, true);
+    // Its SmartArt and shape aren't pictures, so they're skipped either way
+    assert_eq!(
+        summary.losses,
+        [
+            "1 SmartArt diagram skipped",
+            "1 unsupported drawing skipped",
+            "3 images left out without --images"
+        ]
+    );
+}
+
+#[test]
+fn test_a_chart_is_not_an_image_left_out() {
+    // Without image support a chart still counts as a chart
+    let document = load_document(
+        Path::new("tests/fixtures/lossy-content.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(document.fidelity.charts, 1);
+    assert_eq!(document.fidelity.omitted_images, 0);
+    assert_eq!(document.fidelity.unsupported_drawings, 0);
+}
+
+#[test]
+fn test_strict_fails_when_anything_is_skipped() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/lossy-content.docx",
+            "--strict",
+            "--export",
+            "markdown",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("1 chart skipped, 1 nested table flattened, 1 field unevaluated"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/minimal.docx",
+            "--strict",
+            "--export",
+            "text",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!output.stdout.is_empty());
+}
@@ -0,0 +1,95 @@
+use doxx::document::{
+    load_document, load_document_with_fields, Document, DocumentElement, FieldOptions,
+    FormattedRun, ImageOptions,
+};
+use std::path::Path;
+
+const FIXTURE: &str = "tests/fixtures/fields.docx";
+
+fn load_fixture(field_options: FieldOptions) -> Document {
+    load_document_with_fields(Path::new(FIXTURE), ImageOptions::default(), field_options).unwrap()
+}
+
+/// Text of each paragraph or heading, in order
+fn texts(document: &Document) -> Vec<String> {
+    document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Paragraph { runs, .. } => {
+                Some(runs.iter().map(|run| run.text.as_str()).collect())
+            }
+            DocumentElement::Heading { text, .. } => Some(text.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn runs_of<'a>(document: &'a Document, text: &str) -> &'a [FormattedRun] {
+    document
+        .elements
+        .iter()
+        .find_map(|element| match element {
+            DocumentElement::Paragraph { runs, .. }
+                if runs.iter().any(|run| run.text.contains(text)) =>
+            {
+                Some(runs.as_slice())
+            }
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_known_fields_are_evaluated() {
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let texts = texts(&document);
+
+    // The stale cached date is replaced by today's, in the field's own format
+    let date = regex::Regex::new(r"^Printed on \d{1,2} [A-Z][a-z]+ \d{4}\.$").unwrap();
+    assert!(texts.iter().any(|text| date.is_match(text)), "{texts:?}");
+    assert!(!texts.iter().any(|text| text.contains("2019")));
+
+    assert!(texts.contains(&"This is page 1 of 1.".to_string()));
+    assert!(texts.contains(&"File: fields.docx, in capitals: FIELDS.DOCX".to_string()));
+
+    // A field's value takes the formatting of the run that ends it
+    let page = runs_of(&document, "This is page ");
+    assert!(page
+        .iter()
+        .any(|run| run.text == "1" && run.formatting.bold));
+}
+
+#[test]
+fn test_unknown_fields_keep_their_cached_result() {
+    let texts = texts(&load_fixture(FieldOptions::default()));
+    assert!(texts.contains(&"See Section 2 for details.".to_string()));
+    assert!(texts.contains(&"Author: Jane Doe (draft)".to_string()));
+    assert!(!texts.iter().any(|text| text.contains("REF")));
+}
+
+#[test]
+fn test_unknown_fields_can_show_their_codes() {
+    let texts = texts(&load_fixture(FieldOptions {
+        show_unknown: true,
+        ..Default::default()
+    }));
+    assert!(texts.contains(&r"See Section 2 {REF _Ref1234 \h} for details.".to_string()));
+    assert!(texts.contains(&"Author: Jane Doe {AUTHOR} (draft)".to_string()));
+    // Evaluated fields are shown as usual
+    assert!(texts.contains(&"This is page 1 of 1.".to_string()));
+}
+
+#[test]
+fn test_runs_after_simple_fields_keep_their_formatting() {
+    // docx-rs reads the runs inside `w:fldSimple` as ordinary runs, so the
+    // properties docx-rs doesn't parse have to count them too
+    let document = load_fixture(FieldOptions::default());
+    let runs = runs_of(&document, "Author: ");
+    let draft = runs.iter().find(|run| run.text == " (draft)").unwrap();
+    assert!(draft.formatting.small_caps);
+    assert!(runs
+        .iter()
+        .filter(|run| run.text != " (draft)")
+        .all(|run| !run.formatting.small_caps));
+}
@@ -0,0 +1,86 @@
+use doxx::document::forms::{form_report, FormFieldKind};
+use std::path::Path;
+use std::process::Command;
+
+const FORM: &str = "tests/fixtures/onboarding-form.docx";
+
+#[test]
+fn test_legacy_fields_and_content_controls_are_read_in_order() {
+    let report = form_report(Path::new(FORM)).unwrap();
+    let fields: Vec<(&str, FormFieldKind, bool, Option<&str>)> = report
+        .fields
+        .iter()
+        .map(|field| {
+            (
+                field.name.as_str(),
+                field.kind,
+                field.legacy,
+                field.value.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            (
+                "FullName",
+                FormFieldKind::Text,
+                true,
+                Some("Dana Whitfield")
+            ),
+            ("Office", FormFieldKind::Dropdown, true, Some("Singapore")),
+            ("Remote", FormFieldKind::Checkbox, true, Some("true")),
+            (
+                "Department",
+                FormFieldKind::Dropdown,
+                false,
+                Some("Legal, Compliance")
+            ),
+            ("Start date", FormFieldKind::Date, false, Some("2024-03-05")),
+            ("Laptop", FormFieldKind::Checkbox, false, Some("false")),
+            ("Manager", FormFieldKind::Text, false, Some("Sam Ortiz")),
+            // Only showing its placeholder
+            ("Notes", FormFieldKind::RichText, false, None),
+        ]
+    );
+    let department = &report.fields[3];
+    assert_eq!(department.tag.as_deref(), Some("dept"));
+    assert_eq!(department.options[1].text, "Sales");
+    assert_eq!(department.options[1].value.as_deref(), Some("SAL"));
+    assert_eq!(report.fields[4].format.as_deref(), Some("d MMMM yyyy"));
+}
+
+#[test]
+fn test_form_command_writes_json_and_csv() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["form", FORM, "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["fields"][1]["options"][2]["text"], "Singapore");
+    assert_eq!(report["fields"][7]["kind"], "rich-text");
+    assert!(report["fields"][7]["value"].is_null());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["form", FORM, "--format", "csv"])
+        .output()
+        .unwrap();
+    let csv = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "name,tag,kind,legacy,format,value,options");
+    assert_eq!(
+        lines[4],
+        "Department,dept,dropdown,false,,\"Legal, Compliance\",\"Choose an item.|Sales|Legal, Compliance\""
+    );
+}
+
+#[test]
+fn test_documents_without_forms_have_no_fields() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["form", "tests/fixtures/minimal.docx"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No form fields"));
+}
@@ -0,0 +1,121 @@
+use doxx::document::{load_document, Document, DocumentElement, ImageOptions};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+const FIXTURE: &str = "tests/fixtures/business-report.docx";
+
+fn elements_json(document: &Document) -> String {
+    serde_json::to_string(&document.elements).unwrap()
+}
+
+/// The fixture's parts, each passed through `edit` on its way into a new package
+fn repackage(edit: impl Fn(&str, Vec<u8>) -> Vec<u8>) -> Vec<u8> {
+    let mut archive = ZipArchive::new(std::fs::File::open(FIXTURE).unwrap()).unwrap();
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).unwrap();
+        let name = entry.name().to_string();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).unwrap();
+        zip.start_file(name.as_str(), SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(&edit(&name, data)).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+/// The package with the compressed bytes in the middle of a part overwritten
+fn damage_part(mut package: Vec<u8>, name: &str) -> Vec<u8> {
+    let mut archive = ZipArchive::new(Cursor::new(package.clone())).unwrap();
+    let entry = archive.by_name(name).unwrap();
+    let middle = (entry.data_start() + entry.compressed_size() / 2) as usize;
+    package[middle..middle + 16].fill(0xff);
+    package
+}
+
+#[test]
+fn test_from_bytes_matches_loading_from_a_path() {
+    let from_path = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let bytes = std::fs::read(FIXTURE).unwrap();
+    let from_bytes = Document::from_bytes(&bytes, ImageOptions::default()).unwrap();
+
+    assert_eq!(elements_json(&from_bytes), elements_json(&from_path));
+    assert_eq!(
+        from_bytes.metadata.word_count,
+        from_path.metadata.word_count
+    );
+    assert_eq!(from_bytes.metadata.file_size, bytes.len() as u64);
+    // Nothing names the document
+    assert_eq!(from_bytes.title, "Untitled Document");
+    assert_eq!(from_bytes.metadata.file_path, "");
+}
+
+#[test]
+fn test_from_reader_starts_at_the_current_position() {
+    let from_path = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+
+    let mut data = b"envelope header".to_vec();
+    data.extend(std::fs::read(FIXTURE).unwrap());
+    let mut reader = Cursor::new(data);
+    reader.seek(SeekFrom::Start(15)).unwrap();
+    let from_reader = Document::from_reader(reader, ImageOptions::default()).unwrap();
+
+    assert_eq!(elements_json(&from_reader), elements_json(&from_path));
+}
+
+#[test]
+fn test_from_bytes_rejects_other_data() {
+    let error = Document::from_bytes(b"plain text", ImageOptions::default()).unwrap_err();
+    assert!(error.to_string().contains("isn't a ZIP package"));
+}
+
+#[test]
+fn test_damaged_parts_are_errors() {
+    let package = repackage(|name, data| match name {
+        "word/styles.xml" => Vec::new(),
+        _ => data,
+    });
+    let error = Document::from_bytes(&package, ImageOptions::default()).unwrap_err();
+    assert!(error.to_string().contains("word/styles.xml is empty"));
+
+    let package = damage_part(repackage(|_, data| data), "word/document.xml");
+    let error = Document::from_bytes(&package, ImageOptions::default()).unwrap_err();
+    assert!(error.to_string().contains("word/document.xml is damaged"));
+}
+
+#[test]
+fn test_packages_with_several_directories_are_read_as_opened() {
+    // ZIP readers pick different directories here; docx-rs mustn't reach the
+    // damaged copy after doxx checked the sound one
+    let sound = std::fs::read(FIXTURE).unwrap();
+    let mut package = sound.clone();
+    package.extend(damage_part(repackage(|_, data| data), "word/document.xml"));
+
+    let document = Document::from_bytes(&package, ImageOptions::default()).unwrap();
+    let expected = Document::from_bytes(&sound, ImageOptions::default()).unwrap();
+    assert_eq!(elements_json(&document), elements_json(&expected));
+}
+
+#[test]
+fn test_equations_with_text_outside_runs() {
+    let omml = "<m:oMathPara><m:oMath><m:r><m:t>x</m:t></m:r>é→<m:unknown>ü</m:unknown></m:oMath></m:oMathPara>";
+    let package = repackage(|name, data| match name {
+        "word/document.xml" => {
+            let xml = String::from_utf8(data).unwrap();
+            let (head, tail) = xml.split_once("<w:body>").unwrap();
+            format!(
+                r#"{}<w:body><w:p xmlns:m="http://schemas.openxmlformats.org/officeDocument/2006/math">{omml}</w:p>{tail}"#,
+                head.replace("<w:document ", "<w:document xmlns:m=\"http://schemas.openxmlformats.org/officeDocument/2006/math\" ")
+            )
+            .into_bytes()
+        }
+        _ => data,
+    });
+    let document = Document::from_bytes(&package, ImageOptions::default()).unwrap();
+    assert!(document
+        .elements
+        .iter()
+        .any(|element| matches!(element, DocumentElement::Equation { .. })));
+}
@@ -0,0 +1,260 @@
+use doxx::{
+    document::{grep_document, load_document, Document, GrepOptions, ImageOptions},
+    export::grep::{
+        find_documents, grep_files, render_grep, render_grep_files, sort_by_relevance, GrepFile,
+    },
+    GrepFormat,
+};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// The same number of texts either side, as `-C` asks
+fn context(texts: usize) -> GrepOptions {
+    GrepOptions {
+        before: texts,
+        after: texts,
+        max_count: None,
+    }
+}
+
+fn report() -> Document {
+    load_document(
+        Path::new("tests/fixtures/business-report.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_grep_finds_paragraphs_and_cells_under_their_headings() {
+    let document = report();
+    let matches = grep_document(
+        &document,
+        &Regex::new("Revenue").unwrap(),
+        &GrepOptions::default(),
+    );
+    let cell = &matches[0];
+    assert_eq!(cell.text, "Revenue");
+    assert_eq!(
+        cell.headings,
+        ["Executive Summary", "Key Performance Indicators"]
+    );
+    // A heading sits under the headings above its own level
+    let heading = matches
+        .iter()
+        .find(|found| found.text == "Revenue Analysis")
+        .unwrap();
+    assert!(heading.headings.is_empty());
+    let subscription = matches
+        .iter()
+        .find(|found| found.text.starts_with("Subscription"))
+        .unwrap();
+    assert_eq!(subscription.headings, ["Revenue Analysis"]);
+    assert_eq!(subscription.ranges, vec![13..20]);
+}
+
+#[test]
+fn test_grep_text_output_numbers_matches_and_context_like_grep() {
+    let document = report();
+    let matches = grep_document(&document, &Regex::new("Subscription").unwrap(), &context(1));
+    let text = render_grep(
+        "report.docx",
+        "Subscription",
+        &matches,
+        GrepFormat::Text,
+        false,
+    )
+    .unwrap();
+    assert_eq!(
+        text,
+        "Revenue Analysis\n\
+         8-Enterprise Sales: $850,000 (+45% from Q3)\n\
+         8:Subscription Revenue: $720,000 (+15% from Q3)\n\
+         8-Professional Services: $380,000 (+8% from Q3)\n"
+    );
+
+    // Overlapping context is printed once
+    let matches = grep_document(&document, &Regex::new("from Q3").unwrap(), &context(1));
+    let text = render_grep("report.docx", "from Q3", &matches, GrepFormat::Text, false).unwrap();
+    assert_eq!(text.matches("Subscription").count(), 1);
+    assert!(!text.contains("--\n"));
+}
+
+#[test]
+fn test_grep_context_on_one_side_and_match_limit() {
+    let document = report();
+    let options = GrepOptions {
+        before: 2,
+        after: 0,
+        max_count: None,
+    };
+    let matches = grep_document(&document, &Regex::new("Subscription").unwrap(), &options);
+    assert_eq!(matches[0].before.len(), 2);
+    assert!(matches[0].after.is_empty());
+
+    let all = grep_document(&document, &Regex::new("from Q3").unwrap(), &context(0));
+    let options = GrepOptions {
+        max_count: Some(2),
+        ..context(0)
+    };
+    let limited = grep_document(&document, &Regex::new("from Q3").unwrap(), &options);
+    assert!(all.len() > 2);
+    assert_eq!(limited, all[..2]);
+
+    // As ripgrep takes them on the command line
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["grep", "from Q3", "tests/fixtures/business-report.docx"])
+        .args(["-B", "1", "-A", "0", "-m", "1", "-j", "1"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Revenue Analysis\n\
+         7-Our revenue growth of 20% quarter-over-quarter demonstrates strong market demand for our products. The primary growth drivers include:\n\
+         8:Enterprise Sales: $850,000 (+45% from Q3)\n"
+    );
+
+    // Nothing matching exits with 1, as grep does
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "grep",
+            "no such text",
+            "tests/fixtures/business-report.docx",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_grep_markdown_and_json() {
+    let document = report();
+    let matches = grep_document(
+        &document,
+        &Regex::new("(?i)target").unwrap(),
+        &GrepOptions::default(),
+    );
+    let markdown = render_grep(
+        "report.docx",
+        "target",
+        &matches,
+        GrepFormat::Markdown,
+        false,
+    )
+    .unwrap();
+    assert!(markdown.contains("## 2025 Objectives\n\n> Revenue **Target**: $12M (400% growth)\n"));
+
+    let json = render_grep("report.docx", "target", &matches, GrepFormat::Json, false).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["file"], "report.docx");
+    assert_eq!(value["matches"][0]["headings"][0], "2025 Objectives");
+    assert_eq!(value["matches"][0]["matches"][0]["start"], 8);
+}
+
+/// A directory of fixtures: the report at the top, meeting notes and a lock
+/// file in a subdirectory
+fn notes_folder(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("doxx-grep-{test}-{}", std::process::id()));
+    let sub = dir.join("2024");
+    std::fs::create_dir_all(&sub).unwrap();
+    let fixture = |name: &str| Path::new("tests/fixtures").join(name);
+    std::fs::copy(fixture("business-report.docx"), dir.join("report.docx")).unwrap();
+    std::fs::copy(fixture("meeting-notes.docx"), sub.join("sync-03-14.docx")).unwrap();
+    std::fs::copy(fixture("sections.docx"), sub.join("sync-03-21.docx")).unwrap();
+    std::fs::write(sub.join("~$sync-03-14.docx"), "lock").unwrap();
+    std::fs::write(sub.join("agenda.txt"), "budget").unwrap();
+    dir
+}
+
+#[test]
+fn test_grep_finds_documents_in_directories_and_patterns() {
+    let dir = notes_folder("find");
+    let names = |paths: Vec<PathBuf>| -> Vec<String> {
+        paths
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    };
+
+    let found = find_documents(std::slice::from_ref(&dir), true).unwrap();
+    assert_eq!(
+        names(found),
+        ["sync-03-14.docx", "sync-03-21.docx", "report.docx"]
+    );
+    let found = find_documents(&[dir.join("2024/sync-*-14.docx")], false).unwrap();
+    assert_eq!(names(found), ["sync-03-14.docx"]);
+    assert!(find_documents(std::slice::from_ref(&dir), false).is_err());
+    assert!(find_documents(&[dir.join("2024/*.pdf")], false).is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_grep_across_files_prints_one_line_per_match_and_a_summary() {
+    let dir = notes_folder("files");
+    let documents = find_documents(std::slice::from_ref(&dir), true).unwrap();
+    let regex = Regex::new("(?i)budget|subscription").unwrap();
+    let files: Vec<GrepFile> = grep_files(&documents, &regex, &GrepOptions::default(), 0)
+        .unwrap()
+        .into_iter()
+        .map(|(path, found)| GrepFile {
+            path: path.strip_prefix(&dir).unwrap().display().to_string(),
+            matches: found.unwrap(),
+        })
+        .collect();
+
+    let text =
+        render_grep_files("budget", &files, documents.len(), GrepFormat::Text, false).unwrap();
+    assert_eq!(
+        text,
+        "2024/sync-03-14.docx::Budget review: spending is on track for the quarter.\n\
+         report.docx:Revenue Analysis:Subscription Revenue: $720,000 (+15% from Q3)\n\
+         \n\
+         2 matches in 2 of 3 files searched\n"
+    );
+
+    let json =
+        render_grep_files("budget", &files, documents.len(), GrepFormat::Json, false).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["files"].as_array().unwrap().len(), 2);
+    assert_eq!(value["summary"]["files_searched"], 3);
+    assert_eq!(value["summary"]["matches"], 2);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_grep_across_files_sorts_by_relevance() {
+    let dir = notes_folder("relevance");
+    let documents = find_documents(std::slice::from_ref(&dir), true).unwrap();
+    let regex = Regex::new("(?i)budget|revenue").unwrap();
+    let mut files: Vec<GrepFile> = grep_files(&documents, &regex, &GrepOptions::default(), 2)
+        .unwrap()
+        .into_iter()
+        .map(|(path, found)| GrepFile {
+            path: path.strip_prefix(&dir).unwrap().display().to_string(),
+            matches: found.unwrap(),
+        })
+        .collect();
+    let paths = |files: &[GrepFile]| -> Vec<String> {
+        files.iter().map(|file| file.path.clone()).collect()
+    };
+    assert_eq!(
+        paths(&files),
+        [
+            "2024/sync-03-14.docx",
+            "2024/sync-03-21.docx",
+            "report.docx"
+        ]
+    );
+
+    // The report mentions revenue far more often than the notes mention budgets
+    sort_by_relevance(&mut files);
+    assert_eq!(
+        paths(&files),
+        [
+            "report.docx",
+            "2024/sync-03-14.docx",
+            "2024/sync-03-21.docx"
+        ]
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,41 @@
+use doxx::document::{load_document, DocumentElement, ImageOptions};
+use std::path::Path;
+
+fn headings(path: &str) -> Vec<(u8, String)> {
+    let document = load_document(Path::new(path), ImageOptions::default()).unwrap();
+    document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Heading { level, text, .. } => Some((*level, text.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_headings_are_levelled_by_size_against_the_body_text() {
+    // Body text is 11pt; the headings are 20, 16 and 14pt, bold 12pt, and an
+    // 18pt style. A large sentence and small print aren't headings.
+    assert_eq!(
+        headings("tests/fixtures/font-size-headings.docx"),
+        [
+            (1, "Annual Review".to_string()),
+            (2, "Regional Results".to_string()),
+            (3, "Northern Office".to_string()),
+            (4, "Staff Notes".to_string()),
+            (2, "Looking Ahead".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_custom_heading_styles_with_outline_levels_are_headings() {
+    // "Überschrift 1 Side Letter" sets outline level 0, but the numbered
+    // paragraph styles based on it don't become headings too
+    let headings = headings("tests/fixtures/advanced-numbering.docx");
+    assert!(headings.contains(&(1, "Point number one".to_string())));
+    assert!(!headings
+        .iter()
+        .any(|(_, text)| text.contains("multiple sub paragraphs")));
+}
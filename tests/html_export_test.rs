@@ -0,0 +1,154 @@
+use doxx::{
+    document::{
+        load_document, Document, DocumentElement, DocumentMetadata, FormattedRun, ImageOptions,
+        TextAlignment, TextFormatting,
+    },
+    export::{
+        html::{render_html, render_html_with_options},
+        infer_export_format, ExportOptions,
+    },
+    ExportFormat,
+};
+use std::path::Path;
+
+fn document(elements: Vec<DocumentElement>) -> Document {
+    Document {
+        title: "Q3 <Draft>".to_string(),
+        metadata: DocumentMetadata {
+            file_path: "draft.docx".to_string(),
+            file_size: 0,
+            word_count: 0,
+            page_count: 1,
+            created: None,
+            modified: None,
+            author: Some("A & B".to_string()),
+            language: None,
+            locale: None,
+            protection: None,
+        },
+        elements,
+        image_options: ImageOptions::default(),
+        sections: Vec::new(),
+        fidelity: Default::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
+    }
+}
+
+#[test]
+fn test_html_page_escapes_text_and_formats_runs() {
+    let runs = vec![
+        FormattedRun {
+            text: "Revenue ".to_string(),
+            formatting: TextFormatting::default(),
+        },
+        FormattedRun {
+            text: "<up>".to_string(),
+            formatting: TextFormatting {
+                bold: true,
+                italic: true,
+                ..Default::default()
+            },
+        },
+        FormattedRun {
+            text: "$x^2$".to_string(),
+            formatting: TextFormatting::default(),
+        },
+    ];
+    let html = render_html(&document(vec![
+        DocumentElement::Heading {
+            level: 1,
+            text: "Summary".to_string(),
+            number: Some("1.".to_string()),
+        },
+        DocumentElement::Paragraph {
+            runs,
+            alignment: TextAlignment::Center,
+            shading: None,
+            rtl: false,
+            spacing: None,
+            tab_stops: Vec::new(),
+        },
+    ]));
+
+    assert!(html.starts_with("<!DOCTYPE html>\n<html>\n"));
+    assert!(html.contains("<title>Q3 &lt;Draft&gt;</title>"));
+    assert!(html.contains("<meta name=\"author\" content=\"A &amp; B\">"));
+    assert!(html.contains("<h2>1. Summary</h2>"));
+    assert!(html.contains(
+        "<p style=\"text-align: center\">Revenue <strong><em>&lt;up&gt;</em></strong>\
+         <span class=\"math\">\\(x^2\\)</span></p>"
+    ));
+    assert!(html.ends_with("</body>\n</html>\n"));
+}
+
+#[test]
+fn test_html_breadcrumbs_link_to_enclosing_sections() {
+    let heading = |level, text: &str| DocumentElement::Heading {
+        level,
+        text: text.to_string(),
+        number: None,
+    };
+    let document = document(vec![
+        heading(1, "Plan"),
+        heading(2, "Costs & risks"),
+        heading(3, "Staff"),
+        heading(2, "Timeline"),
+    ]);
+    let options = ExportOptions {
+        breadcrumbs: true,
+        ..Default::default()
+    };
+    let html = render_html_with_options(&document, &options);
+
+    let staff = html.find("<h4 id=\"staff\">Staff</h4>").unwrap();
+    let trail = html[..staff].trim_end().lines().last().unwrap();
+    assert_eq!(
+        trail,
+        "<nav class=\"breadcrumbs\"><a href=\"#plan\">Plan</a> › \
+         <a href=\"#costs--risks\">Costs &amp; risks</a></nav>"
+    );
+    // A sibling section closes the one before it
+    let timeline = html.find("<h3 id=\"timeline\">").unwrap();
+    let trail = html[..timeline].trim_end().lines().last().unwrap();
+    assert_eq!(
+        trail,
+        "<nav class=\"breadcrumbs\"><a href=\"#plan\">Plan</a></nav>"
+    );
+    assert_eq!(html.matches("class=\"breadcrumbs\"").count(), 3);
+    assert!(!render_html(&document).contains("breadcrumbs"));
+}
+
+#[test]
+fn test_html_tables_have_header_rows() {
+    let document = load_document(
+        Path::new("tests/fixtures/tables-heavy.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let html = render_html(&document);
+
+    let tables = document
+        .elements
+        .iter()
+        .filter(|element| matches!(element, DocumentElement::Table { .. }))
+        .count();
+    assert!(tables > 0);
+    assert_eq!(html.matches("<table>").count(), tables);
+    assert_eq!(html.matches("</table>").count(), tables);
+    assert!(html.contains("<thead>\n<tr><th"));
+}
+
+#[test]
+fn test_html_extension_is_inferred() {
+    assert!(matches!(
+        infer_export_format(Path::new("report.html")),
+        Some(ExportFormat::Html)
+    ));
+    assert!(matches!(
+        infer_export_format(Path::new("report.HTM")),
+        Some(ExportFormat::Html)
+    ));
+}
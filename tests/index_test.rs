@@ -0,0 +1,104 @@
+use doxx::document::{
+    index::append_index, load_document, load_document_with_fields, searchable_text, FieldOptions,
+    ImageOptions, IndexEntry,
+};
+use std::path::Path;
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/legal-index.docx";
+
+#[test]
+fn test_index_entries_and_citations_are_collected() {
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let entries: Vec<(Vec<&str>, Option<u8>, usize)> = document
+        .index_entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.levels.iter().map(String::as_str).collect(),
+                entry.category,
+                entry.page,
+            )
+        })
+        .collect();
+    let smith = "Smith v. Jones, 123 F.3d 456 (9th Cir. 1997)";
+    assert_eq!(
+        entries,
+        [
+            (vec!["Contracts"], None, 1),
+            (vec!["Contracts", "formation"], None, 1),
+            (vec![smith], Some(1), 1),
+            (vec!["15 U.S.C. § 1"], Some(2), 1),
+            // The short form takes the long form's citation
+            (vec![smith], Some(1), 2),
+            (vec!["Contracts", "breach"], None, 2),
+            (vec!["Damages"], None, 2),
+            (vec!["arbitration"], None, 2),
+        ]
+    );
+    assert_eq!(
+        document.index_entries[6],
+        IndexEntry {
+            levels: vec!["Damages".to_string()],
+            category: None,
+            see: Some("See Remedies".to_string()),
+            page: 2,
+        }
+    );
+}
+
+#[test]
+fn test_markers_leave_nothing_in_the_text() {
+    let options = FieldOptions {
+        show_unknown: true,
+        ..Default::default()
+    };
+    let document =
+        load_document_with_fields(Path::new(FIXTURE), ImageOptions::default(), options).unwrap();
+    let texts: Vec<String> = searchable_text(&document)
+        .into_iter()
+        .map(|searchable| searchable.text)
+        .collect();
+    assert_eq!(
+        texts[2],
+        "As held in Smith v. Jones, a party who stops paying is in breach."
+    );
+    assert!(texts
+        .iter()
+        .all(|text| !text.contains("XE") && !text.contains("TA ")));
+    assert_eq!(document.fidelity.unevaluated_fields, 0);
+}
+
+#[test]
+fn test_index_is_appended_by_category_and_entry() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([FIXTURE, "--index", "--export", "markdown"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let markdown = String::from_utf8(output.stdout).unwrap();
+    let index = &markdown[markdown.find("## Table of Authorities").unwrap()..];
+    assert_eq!(
+        index,
+        "## Table of Authorities\n\n\
+         ### Cases\n\n\
+         - Smith v. Jones, 123 F.3d 456 (9th Cir. 1997), 1, 2\n\n\
+         ### Statutes\n\n\
+         - 15 U.S.C. § 1, 1\n\n\
+         ## Index\n\n\
+         - arbitration, 2\n\
+         - Contracts, 1\n  \
+         - breach, 2\n  \
+         - formation, 1\n\
+         - Damages, See Remedies\n\n"
+    );
+
+    let mut document = load_document(
+        Path::new("tests/fixtures/minimal.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let elements = document.elements.len();
+    assert_eq!(append_index(&mut document), 0);
+    assert_eq!(document.elements.len(), elements);
+}
@@ -0,0 +1,79 @@
+use doxx::document::{load_document, search_document, ImageOptions, SourceInspector};
+use std::path::Path;
+
+#[test]
+fn test_inspecting_a_list_paragraph_shows_its_styles_and_numbering() {
+    let path = Path::new("tests/fixtures/advanced-numbering.docx");
+    let document = load_document(path, ImageOptions::default()).unwrap();
+    let inspector = SourceInspector::open(path).unwrap();
+
+    // The generated "(iv)" isn't in the stored text, but the paragraph is still found
+    let index = search_document(&document, "Roman;")[0].element_index;
+    let inspection = inspector.inspect(&document, index).unwrap();
+    assert_eq!(inspection.kind, "Paragraph");
+    let [source] = &inspection.sources[..] else {
+        panic!("expected one source, got {}", inspection.sources.len());
+    };
+    assert!(source.xml.starts_with("<w:p "));
+    assert!(source.xml.contains("Roman</w:t>"));
+
+    let chain: Vec<_> = source
+        .style_chain
+        .iter()
+        .map(|link| link.id.as_str())
+        .collect();
+    assert_eq!(chain.first(), Some(&"UnterpunktEbene2SideLetter"));
+    assert_eq!(chain.last(), Some(&"Normal"));
+
+    let numbering = source.numbering.as_ref().unwrap();
+    assert_eq!((numbering.num_id, numbering.level), (1, 3));
+    assert_eq!(numbering.format.as_deref(), Some("lowerRoman"));
+    assert_eq!(numbering.text.as_deref(), Some("(%4)"));
+
+    let lines = inspection.lines();
+    assert_eq!(lines[0], format!("Element {index}: Paragraph"));
+    assert!(lines[1].starts_with("Style: UnterpunktEbene2SideLetter ("));
+}
+
+#[test]
+fn test_tables_match_the_nth_table_in_the_body() {
+    let path = Path::new("tests/fixtures/captions.docx");
+    let document = load_document(path, ImageOptions::default()).unwrap();
+    let inspector = SourceInspector::open(path).unwrap();
+
+    let tables: Vec<_> = document
+        .elements
+        .iter()
+        .enumerate()
+        .filter(|(_, element)| matches!(element, doxx::DocumentElement::Table { .. }))
+        .map(|(index, _)| inspector.inspect(&document, index).unwrap())
+        .collect();
+    assert_eq!(tables.len(), 2);
+    assert!(tables[0].sources[0].xml.contains("North"));
+    assert!(tables[1].sources[0].xml.contains("Q1"));
+    assert!(inspector
+        .inspect(&document, document.elements.len())
+        .is_none());
+}
+
+#[test]
+fn test_each_element_is_inspected_once_in_the_order_given() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "inspect",
+            "tests/fixtures/captions.docx",
+            "2",
+            "0",
+            "2",
+            "0",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let headers: Vec<_> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.starts_with("Element "))
+        .map(|line| line.split(':').next().unwrap().to_string())
+        .collect();
+    assert_eq!(headers, ["Element 2", "Element 0"]);
+}
@@ -74,6 +74,165 @@ fn test_formatting_markdown_export() {
     );
 }
 
+#[test]
+fn test_markdown_export_breadcrumbs() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/headings-hierarchy.docx",
+            "--export",
+            "markdown",
+            "--breadcrumbs",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // A level-3 heading is preceded by links to its level-1 and level-2 ancestors
+    let heading = stdout
+        .find("#### 1.1.1 Level 3: Prerequisites")
+        .expect("Should contain the level-3 heading");
+    let breadcrumb = stdout[..heading].trim_end().lines().last().unwrap();
+    assert_eq!(
+        breadcrumb,
+        "_[1 Level 1: Introduction](#1-level-1-introduction) › \
+         [1.1 Level 2: Getting Started](#11-level-2-getting-started)_"
+    );
+
+    // Breadcrumbs are opt-in
+    let plain = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/headings-hierarchy.docx",
+            "--export",
+            "markdown",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(!String::from_utf8_lossy(&plain.stdout).contains("](#"));
+}
+
+#[test]
+fn test_markdown_chunked_export() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/business-report.docx",
+            "--export",
+            "markdown",
+            "--chunk-by",
+            "heading",
+            "--max-tokens",
+            "40",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let records: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Each line should be a JSON record"))
+        .collect();
+    assert!(
+        records.len() > 10,
+        "Sections should be split into many chunks"
+    );
+
+    for record in &records {
+        let text = record["text"].as_str().unwrap();
+        assert!(
+            record["tokens"].as_u64().unwrap() <= 40,
+            "Chunk too large: {text}"
+        );
+    }
+
+    // Nested sections carry their ancestors, and continuation chunks repeat the heading
+    let kpi: Vec<_> = records
+        .iter()
+        .filter(|r| {
+            r["heading_path"]
+                == serde_json::json!(["Executive Summary", "Key Performance Indicators"])
+        })
+        .collect();
+    assert!(kpi.len() > 1);
+    assert!(kpi.iter().all(|r| r["text"]
+        .as_str()
+        .unwrap()
+        .starts_with("### Key Performance Indicators")));
+
+    // Chunking only applies to Markdown export
+    let text = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/business-report.docx",
+            "--export",
+            "text",
+            "--chunk-by",
+            "heading",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(!text.status.success());
+
+    // No chunk fits in zero tokens, and JSONL records don't go in a .md file
+    for args in [["--max-tokens", "0"], ["--output", "report.md"]] {
+        let rejected = Command::new(env!("CARGO_BIN_EXE_doxx"))
+            .args([
+                "tests/fixtures/business-report.docx",
+                "--export",
+                "markdown",
+                "--chunk-by",
+                "heading",
+            ])
+            .args(args)
+            .output()
+            .expect("Failed to execute doxx");
+        assert!(!rejected.status.success());
+    }
+    assert!(!std::path::Path::new("report.md").exists());
+}
+
+#[test]
+fn test_markdown_chunk_dir_export() {
+    let dir = std::env::temp_dir().join(format!("doxx-chunks-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/headings-hierarchy.docx",
+            "--export",
+            "markdown",
+            "--chunk-by",
+            "heading",
+            "--chunk-dir",
+        ])
+        .arg(&dir)
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(output.status.success());
+
+    let mut files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert!(
+        files[0].starts_with("001-"),
+        "Files should be numbered: {files:?}"
+    );
+    assert!(files
+        .iter()
+        .any(|f| f.ends_with("-1-level-1-introduction.md")));
+
+    let intro = files
+        .iter()
+        .find(|f| f.ends_with("-1-level-1-introduction.md"))
+        .unwrap();
+    let intro = std::fs::read_to_string(dir.join(intro)).unwrap();
+    assert!(intro.starts_with("## 1 Level 1: Introduction\n\n"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 #[test]
 fn test_unicode_document() {
     let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
@@ -140,6 +299,72 @@ fn test_search_functionality() {
     );
 }
 
+#[test]
+fn test_goto_heading_preview() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/business-report.docx",
+            "--goto",
+            "market risks",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx should accept --goto");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let preview = stdout
+        .split("================")
+        .nth(1)
+        .expect("Should contain a content preview");
+    assert!(
+        preview.trim_start().starts_with("# Market Risks"),
+        "Preview should start at the requested heading"
+    );
+    assert!(
+        !preview.contains("Executive Summary"),
+        "Preview should skip content before the heading"
+    );
+}
+
+#[test]
+fn test_color_modes_in_piped_preview() {
+    let preview = |args: &[&str], env: &[(&str, &str)]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+            .arg("tests/fixtures/example.docx")
+            .args(args)
+            .env_remove("NO_COLOR")
+            .env_remove("CLICOLOR")
+            .env_remove("CLICOLOR_FORCE")
+            .envs(env.iter().copied())
+            .output()
+            .expect("Failed to execute doxx");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).contains('\x1b')
+    };
+
+    // Piped output is plain unless colour is forced
+    assert!(!preview(&[], &[]));
+    assert!(preview(&["--color=always"], &[]));
+    assert!(preview(&["--color"], &[]));
+    assert!(preview(&[], &[("CLICOLOR_FORCE", "1")]));
+    // NO_COLOR wins over CLICOLOR_FORCE, but not over an explicit flag
+    assert!(!preview(&[], &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]));
+    assert!(preview(&["--color=always"], &[("NO_COLOR", "1")]));
+
+    // ANSI export keeps its colours when piped, except with --color=never
+    let ansi = |color: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+            .args(["tests/fixtures/example.docx", "--export", "ansi"])
+            .args(["--color-depth", "4", color])
+            .env_remove("NO_COLOR")
+            .output()
+            .expect("Failed to execute doxx");
+        String::from_utf8_lossy(&output.stdout).contains("\x1b[94m")
+    };
+    assert!(ansi("--color=auto"));
+    assert!(!ansi("--color=never"));
+}
+
 #[test]
 fn test_empty_search_functionality() {
     let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
@@ -194,3 +419,310 @@ fn test_all_fixtures_exist() {
         );
     }
 }
+
+#[test]
+fn test_export_format_inferred_from_output_extension() {
+    let dir = std::env::temp_dir().join(format!("doxx-output-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let run = |output: &Path, extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_doxx"))
+            .arg("tests/fixtures/business-report.docx")
+            .arg("--output")
+            .arg(output)
+            .args(extra)
+            .output()
+            .expect("Failed to execute doxx")
+    };
+
+    let markdown = dir.join("report.md");
+    assert!(run(&markdown, &[]).status.success());
+    assert!(std::fs::read_to_string(&markdown)
+        .unwrap()
+        .starts_with("# business-report\n\n## Document Information"));
+
+    let json = dir.join("report.JSON");
+    assert!(run(&json, &[]).status.success());
+    let parsed: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+    assert_eq!(parsed["title"], "business-report");
+
+    // An explicit --export overrides the extension
+    let text = dir.join("override.md");
+    assert!(run(&text, &["--export", "csv"]).status.success());
+    assert!(std::fs::read_to_string(&text).unwrap().contains("Metric,"));
+
+    // Unknown extensions are an error rather than a guess
+    let pdf = dir.join("report.pdf");
+    let output = run(&pdf, &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Cannot infer an export format"));
+    assert!(!pdf.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_wrap_modes_for_text_and_markdown() {
+    let export = |format: &str, wrap: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+            .args([
+                "tests/fixtures/business-report.docx",
+                "--export",
+                format,
+                "--wrap",
+                wrap,
+            ])
+            .output()
+            .expect("Failed to execute doxx");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    // Fixed-width reflow keeps body prose within the limit (headings and tables
+    // are left alone)
+    let markdown = export("markdown", "40");
+    assert!(markdown.contains("TechCorp Inc. delivered exceptional\nresults"));
+    let (_, body) = markdown.split_once("\n---\n").unwrap();
+    for line in body.lines().filter(|line| !line.starts_with(['|', '#'])) {
+        let is_long_word = !line.trim().contains(' ');
+        assert!(
+            line.chars().count() <= 40 || is_long_word,
+            "Line too long: {line}"
+        );
+    }
+
+    // Semantic wrapping puts each sentence on its own line
+    let text = export("text", "semantic");
+    assert!(text.contains("by 34%.\nKey highlights include"));
+    assert!(text.contains("TechCorp Inc. delivered"));
+
+    // No wrapping keeps each paragraph on a single line
+    let text = export("text", "none");
+    assert!(text.contains("by 34%. Key highlights include"));
+
+    let invalid = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/minimal.docx",
+            "--export",
+            "text",
+            "--wrap",
+            "wide",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_forensic_text_export() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/tracked-changes.docx",
+            "--export",
+            "text",
+            "--forensic",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // Tracked changes keep both sides, with author and date
+    assert!(stdout.contains(
+        "The buyer shall pay [deleted by Alice, 2024-03-01T10:00:00Z: $10,000]\
+         [inserted by Bob, 2024-03-02T11:00:00Z: $12,000] within 30 days.[comment 0]"
+    ));
+    assert!(stdout
+        .contains("[deleted by Alice, 2024-03-01T10:05:00Z: This whole paragraph was removed.]"));
+    assert!(stdout.contains("Visible text.[hidden:  Secret clause.]"));
+    assert!(stdout.contains(r#"[field code:  DATE \@ "yyyy" ]2024."#));
+
+    // Every text-bearing part is included under a provenance heading
+    for heading in [
+        "== Header (word/header1.xml) ==\n[p1] CONFIDENTIAL",
+        "== Footer (word/footer1.xml) ==\n[p1] Draft for review",
+        "== Footnotes (word/footnotes.xml) ==\n[footnote 1]\n[p1]  Payment terms are net 30.",
+        "== Comments (word/comments.xml) ==\n[comment 0 by Carol, 2024-03-03T09:00:00Z]\n\
+         [p1] Check the amount with finance.",
+    ] {
+        assert!(stdout.contains(heading), "Missing section: {heading}");
+    }
+
+    let markdown = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "tests/fixtures/tracked-changes.docx",
+            "--export",
+            "markdown",
+            "--forensic",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(!markdown.status.success());
+}
+
+/// Run doxx on `/dev/stdin`, fed `input` through a pipe
+#[cfg(unix)]
+fn run_on_pipe(input: Vec<u8>, args: &[&str]) -> std::process::Output {
+    run_on_pipe_in(&std::env::temp_dir(), input, args)
+}
+
+/// [`run_on_pipe`], with `temp_dir` as doxx's temporary directory
+#[cfg(unix)]
+fn run_on_pipe_in(temp_dir: &Path, input: Vec<u8>, args: &[&str]) -> std::process::Output {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .arg("/dev/stdin")
+        .args(args)
+        .env("TMPDIR", temp_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute doxx");
+    let mut stdin = child.stdin.take().unwrap();
+    std::thread::spawn(move || stdin.write_all(&input));
+    child.wait_with_output().unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_document_from_a_pipe() {
+    let input = std::fs::read("tests/fixtures/minimal.docx").unwrap();
+    let output = run_on_pipe(input, &["--export", "text"]);
+
+    assert!(
+        output.status.success(),
+        "doxx should read a document from a pipe: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Minimal Test"));
+    // The pipe is named, not the temporary copy it was read into
+    assert!(stdout.contains("- File: /dev/stdin"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_piped_copy_is_removed() {
+    let temp_dir = std::env::temp_dir().join(format!("doxx-pipe-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).unwrap();
+
+    let input = std::fs::read("tests/fixtures/minimal.docx").unwrap();
+    let output = run_on_pipe_in(&temp_dir, input, &["--export", "text"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Minimal Test"));
+    // Neither the copy nor the directory made for it is left behind
+    assert_eq!(std::fs::read_dir(&temp_dir).unwrap().count(), 0);
+    std::fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pipe_without_a_document() {
+    let output = run_on_pipe(b"plain text".to_vec(), &["--export", "text"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("/dev/stdin did not send a .docx file"),
+        "{stderr}"
+    );
+
+    let output = run_on_pipe(Vec::new(), &["--export", "text"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("/dev/stdin sent no data"));
+}
+
+#[test]
+fn test_shell_completions_and_man_page() {
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+            .args(args)
+            .output()
+            .expect("Failed to execute doxx");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let bash = run(&["completions", "bash"]);
+    assert!(bash.contains("complete -F _doxx"));
+    // Export formats are offered as values
+    assert!(bash.contains("asciidoc"));
+
+    let fish = run(&["completions", "fish"]);
+    assert!(fish.contains("-l export"));
+
+    let man = run(&["manpage"]);
+    assert!(man.contains(".TH doxx 1"));
+    assert!(man.contains("\\-\\-export"));
+}
+
+#[test]
+fn test_exports_copy_or_embed_images() {
+    let dir = std::env::temp_dir().join(format!("doxx-export-images-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let export = |output: &Path, mode: &str| {
+        let status = Command::new(env!("CARGO_BIN_EXE_doxx"))
+            .arg("tests/fixtures/images.docx")
+            .arg("--output")
+            .arg(output)
+            .args(["--export-images-mode", mode])
+            .status()
+            .expect("Failed to execute doxx");
+        assert!(status.success());
+        std::fs::read_to_string(output).unwrap()
+    };
+
+    // Copied images sit in an assets directory named after the output
+    let markdown = export(&dir.join("report.md"), "copy");
+    assert!(markdown.contains("](report_assets/image3.png)"));
+    assert!(dir.join("report_assets/image3.png").is_file());
+
+    let html = export(&dir.join("report.html"), "embed");
+    assert_eq!(html.matches("<img src=\"data:image/").count(), 3);
+    assert!(html.contains("src=\"data:image/png;base64,iVBORw0KGgo"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_inline_prints_the_whole_document_between_rules() {
+    let inline = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+            .args(["tests/fixtures/business-report.docx", "--inline"])
+            .args(args)
+            .env_remove("NO_COLOR")
+            .output()
+            .expect("Failed to execute doxx");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // Piped, it's plain text at the width asked for
+    let plain = inline(&["-w", "60"]);
+    let lines: Vec<&str> = plain.lines().collect();
+    assert_eq!(
+        lines[0],
+        "─────────────────── business-report.docx ───────────────────"
+    );
+    assert_eq!(
+        lines[1],
+        "business-report · 1 page · 221 words · about 2 min"
+    );
+    assert_eq!(*lines.last().unwrap(), "─".repeat(60));
+    assert!(!plain.contains('\x1b'));
+    assert!(plain.contains("│ Revenue              │ $2,100,000 │"));
+    // All of it, unlike the preview printed without --inline
+    assert!(plain.contains("Date: January 10, 2025"));
+    assert!(!plain.contains("more elements"));
+
+    let colored = inline(&["-w", "60", "--color=always", "--color-depth", "4"]);
+    assert!(colored.contains("\x1b[1m"));
+    assert!(colored.contains("\x1b[93m■ Executive Summary"));
+}
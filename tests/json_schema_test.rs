@@ -0,0 +1,87 @@
+#![cfg(feature = "test-support")]
+
+//! Golden tests for the versioned JSON export schema
+//!
+//! The JSON files in `tests/golden` are the contract with downstream tools:
+//! if one of these tests fails, either fix the regression or, for an intended
+//! schema change, bump `SCHEMA_VERSION` and regenerate the goldens with
+//! `DOXX_BLESS=1 cargo test --features test-support --test json_schema_test`.
+
+use doxx::document::{load_document, ImageOptions};
+use doxx::export::json::{render_json, to_json_value, SCHEMA_VERSION};
+use doxx::testing::assert_golden;
+use doxx::JsonCase;
+use std::path::{Path, PathBuf};
+
+fn check_golden(fixture: &str, case: JsonCase, golden: &str) {
+    // Built by hand so the recorded file_path is the same on every platform
+    let fixture = PathBuf::from(format!("tests/fixtures/{fixture}"));
+    let document =
+        load_document(&fixture, ImageOptions::default()).expect("Failed to load fixture");
+    let actual = render_json(&document, case).unwrap() + "\n";
+    assert_golden(&Path::new("tests/golden").join(golden), &actual);
+}
+
+#[test]
+fn test_golden_minimal() {
+    check_golden("minimal.docx", JsonCase::Snake, "minimal.json");
+}
+
+#[test]
+fn test_golden_minimal_camel_case() {
+    check_golden("minimal.docx", JsonCase::Camel, "minimal.camel.json");
+}
+
+#[test]
+fn test_golden_headings() {
+    check_golden(
+        "headings-hierarchy.docx",
+        JsonCase::Snake,
+        "headings-hierarchy.json",
+    );
+}
+
+#[test]
+fn test_golden_tables() {
+    check_golden(
+        "business-report.docx",
+        JsonCase::Snake,
+        "business-report.json",
+    );
+}
+
+#[test]
+fn test_golden_equations() {
+    check_golden("equations.docx", JsonCase::Snake, "equations.json");
+}
+
+#[test]
+fn test_schema_version_and_element_tags() {
+    let document = load_document(
+        Path::new("tests/fixtures/business-report.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let value = to_json_value(&document, JsonCase::Snake).unwrap();
+
+    assert_eq!(value["schema_version"], SCHEMA_VERSION);
+    let known = [
+        "heading",
+        "paragraph",
+        "list",
+        "block_quote",
+        "code_block",
+        "table",
+        "image",
+        "equation",
+        "page_break",
+        "horizontal_rule",
+        "file_boundary",
+    ];
+    for element in value["elements"].as_array().unwrap() {
+        let tag = element["type"]
+            .as_str()
+            .expect("Every element has a type tag");
+        assert!(known.contains(&tag), "Undocumented element type: {tag}");
+    }
+}
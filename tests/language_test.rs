@@ -0,0 +1,137 @@
+use doxx::document::{
+    load_document, load_document_with_fields, CellDataType, CellLocale, Document, DocumentElement,
+    FieldOptions, ImageOptions, NumberStyle, TableCell, TableData, TextAlignment,
+};
+use doxx::export::{export_document_to_string, ExportOptions};
+use doxx::ExportFormat;
+use std::path::Path;
+
+fn load_fixture(name: &str) -> Document {
+    load_document(
+        Path::new(&format!("tests/fixtures/{name}")),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_declared_language_is_read_from_runs() {
+    let document = load_fixture("advanced-numbering.docx");
+    let language = document.metadata.language.unwrap();
+    assert_eq!(language.tag, "en-GB");
+    assert!(!language.detected);
+}
+
+#[test]
+fn test_undeclared_language_is_detected() {
+    let document = load_fixture("german-report.docx");
+    let language = document.metadata.language.as_ref().unwrap();
+    assert_eq!(language.tag, "de");
+    assert!(language.detected);
+    assert_eq!(language.number_style(), NumberStyle::DecimalComma);
+    assert!(document.hyphenates());
+
+    // Mostly Chinese, but the kana in it make it Japanese; neither hyphenates
+    let document = load_fixture("cjk-text.docx");
+    assert_eq!(document.metadata.language.as_ref().unwrap().tag, "ja");
+    assert!(!document.hyphenates());
+}
+
+#[test]
+fn test_tables_read_numbers_in_the_document_language() {
+    let document = load_fixture("german-report.docx");
+    let table = document
+        .elements
+        .iter()
+        .find_map(|element| match element {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        })
+        .unwrap();
+
+    let types: Vec<CellDataType> = table.rows[0].iter().map(|cell| cell.data_type).collect();
+    assert_eq!(
+        types,
+        [
+            CellDataType::Text,
+            CellDataType::Currency,
+            CellDataType::Number,
+            CellDataType::Date
+        ]
+    );
+    assert_eq!(
+        table.metadata.column_alignments,
+        [
+            TextAlignment::Left,
+            TextAlignment::Right,
+            TextAlignment::Right,
+            TextAlignment::Left
+        ]
+    );
+
+    // The same text read as English is not a number
+    let cell = TableCell::new("1 204".to_string());
+    assert_eq!(cell.data_type, CellDataType::Text);
+    let cell = TableCell::with_number_style("12.5".to_string(), NumberStyle::DecimalComma);
+    assert_eq!(cell.data_type, CellDataType::Text);
+}
+
+fn first_table(document: &Document) -> &TableData {
+    document
+        .elements
+        .iter()
+        .find_map(|element| match element {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_locale_overrides_the_document_language() {
+    // Declared American English, but written with French numbers
+    let path = Path::new("tests/fixtures/european-figures.docx");
+    let english = load_document(path, ImageOptions::default()).unwrap();
+    let types: Vec<CellDataType> = first_table(&english).rows[0]
+        .iter()
+        .map(|cell| cell.data_type)
+        .collect();
+    assert_eq!(types[1..3], [CellDataType::Text, CellDataType::Text]);
+
+    let locale: CellLocale = "fr-FR".parse().unwrap();
+    let french = load_document_with_fields(
+        path,
+        ImageOptions::default(),
+        FieldOptions {
+            locale: Some(locale),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(french.cell_locale(), locale);
+    let table = first_table(&french);
+    assert!(table
+        .rows
+        .iter()
+        .all(|row| row[1].data_type == CellDataType::Number));
+    assert_eq!(table.metadata.column_alignments[1], TextAlignment::Right);
+
+    let csv =
+        export_document_to_string(&french, &ExportFormat::Csv, &ExportOptions::default()).unwrap();
+    assert!(csv.contains("Lyon,1234.56,3.5,2024-12-31\n"));
+    assert!(csv.contains("Lille,987.1,-1.25,2024-11-05\n"));
+}
+
+#[test]
+fn test_dates_are_read_in_the_locale_order() {
+    let date = |text: &str, tag: &str| {
+        TableCell::with_locale(text.to_string(), tag.parse().unwrap()).data_type
+    };
+    assert_eq!(date("05/11/2024", "en-GB"), CellDataType::Date);
+    assert_eq!(date("31.12.2024", "de-CH"), CellDataType::Date);
+    assert_eq!(date("2024.12.31", "ja"), CellDataType::Date);
+    // Dots only separate dates where the day or year comes first
+    assert_eq!(date("1.2.3", "en-US"), CellDataType::Text);
+    assert_eq!(date("13/13/2024", "en-GB"), CellDataType::Text);
+    assert!("12".parse::<CellLocale>().is_err());
+}
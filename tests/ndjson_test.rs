@@ -0,0 +1,86 @@
+use doxx::{
+    document::{load_document, Document, ImageOptions},
+    export::{export_document_to_string, infer_export_format, json::to_json_value, ExportOptions},
+    ExportFormat, JsonCase,
+};
+use serde_json::Value;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+fn report() -> Document {
+    load_document(
+        Path::new("tests/fixtures/business-report.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+fn ndjson_lines(document: &Document, options: &ExportOptions) -> Vec<Value> {
+    export_document_to_string(document, &ExportFormat::Ndjson, options)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_lines_are_the_json_schema_one_element_at_a_time() {
+    let document = report();
+    let lines = ndjson_lines(&document, &ExportOptions::default());
+    let json = to_json_value(&document, JsonCase::Snake).unwrap();
+
+    assert_eq!(lines[0]["type"], "document");
+    assert_eq!(lines[0]["schema_version"], json["schema_version"]);
+    assert_eq!(lines[0]["metadata"], json["metadata"]);
+
+    let elements = json["elements"].as_array().unwrap();
+    assert_eq!(lines.len(), elements.len() + 1);
+    for (index, (line, element)) in lines[1..].iter().zip(elements).enumerate() {
+        let mut line = line.clone();
+        assert_eq!(line["index"], index);
+        line.as_object_mut().unwrap().remove("index");
+        assert_eq!(&line, element);
+    }
+}
+
+#[test]
+fn test_camel_case_applies_to_every_line() {
+    let options = ExportOptions {
+        json_case: JsonCase::Camel,
+        ..Default::default()
+    };
+    let lines = ndjson_lines(&report(), &options);
+    assert_eq!(lines[0]["schemaVersion"], 1);
+    assert!(lines[0]["metadata"]["wordCount"].is_number());
+}
+
+#[test]
+fn test_jsonl_and_ndjson_files_are_ndjson() {
+    for name in ["out.ndjson", "out.jsonl"] {
+        assert!(matches!(
+            infer_export_format(Path::new(name)),
+            Some(ExportFormat::Ndjson)
+        ));
+    }
+}
+
+#[test]
+fn test_a_reader_stopping_early_is_not_an_error() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["tests/fixtures/tiny-runs.docx", "--export", "ndjson"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut first = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut first)
+        .unwrap();
+    assert!(first.starts_with("{\"type\":\"document\""));
+
+    // The rest of the output is more than a pipe holds, so doxx is still writing
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
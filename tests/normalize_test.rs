@@ -0,0 +1,51 @@
+use doxx::{
+    document::{load_document, normalize_document, normalize_text, ImageOptions},
+    notes::element_text,
+    Normalization,
+};
+use std::path::Path;
+
+#[test]
+fn test_unicode_clean_keeps_typography() {
+    let text = "Soft\u{00AD}ware\u{200B} costs\u{00A0}€5 \u{201C}today\u{201D} \u{2014} see\u{0007} p.\u{202F}4\u{2026}";
+    assert_eq!(
+        normalize_text(text, Normalization::UnicodeClean),
+        "Software costs €5 \u{201C}today\u{201D} \u{2014} see p. 4\u{2026}"
+    );
+    assert_eq!(
+        normalize_text(text, Normalization::Ascii),
+        "Software costs €5 \"today\" -- see p. 4..."
+    );
+    assert_eq!(normalize_text(text, Normalization::None), text);
+}
+
+#[test]
+fn test_normalization_keeps_joiners_and_line_breaks() {
+    // The family emoji is joined with U+200D, and the Persian word needs U+200C
+    let text = "👨\u{200D}👩\u{200D}👧 می\u{200C}خواهم\r\nline\ttwo";
+    assert_eq!(
+        normalize_text(text, Normalization::Ascii),
+        "👨\u{200D}👩\u{200D}👧 می\u{200C}خواهم\nline\ttwo"
+    );
+}
+
+#[test]
+fn test_normalize_document_straightens_quotes() {
+    let mut document = load_document(
+        Path::new("tests/fixtures/example.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let text = |document: &doxx::Document| {
+        document
+            .elements
+            .iter()
+            .map(element_text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    assert!(text(&document).contains("it\u{2019}s blue"));
+
+    normalize_document(&mut document, Normalization::Ascii);
+    assert!(text(&document).contains("it's blue"));
+}
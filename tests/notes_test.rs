@@ -0,0 +1,94 @@
+use doxx::{
+    document::{load_document, DocumentElement, ImageOptions},
+    export::notes::render_notes,
+    notes::{Notes, NOTES_FILE},
+};
+use std::path::{Path, PathBuf};
+
+/// A copy of a fixture in a directory of its own, for its notes file
+fn copy_fixture(test: &str, fixture: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("doxx-notes-{test}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("report.docx");
+    std::fs::copy(Path::new("tests/fixtures").join(fixture), &path).unwrap();
+    path
+}
+
+#[test]
+fn test_notes_are_saved_beside_the_document() {
+    let path = copy_fixture("saved", "business-report.docx");
+    let document = load_document(&path, ImageOptions::default()).unwrap();
+
+    let mut notes = Notes::load(&path, &document).unwrap();
+    assert!(notes.is_empty());
+    assert!(notes.toggle_bookmark(&document, 1));
+    notes.set_note(&document, 2, "  Check against Q3  ");
+    notes.save().unwrap();
+    assert_eq!(notes.path(), path.with_file_name(NOTES_FILE));
+
+    let notes = Notes::load(&path, &document).unwrap();
+    let annotations = notes.annotations();
+    assert_eq!(annotations.len(), 2);
+    assert_eq!(annotations[0].element, 1);
+    assert_eq!(annotations[0].excerpt, "Executive Summary");
+    assert_eq!(annotations[0].note, None);
+    assert_eq!(annotations[1].note.as_deref(), Some("Check against Q3"));
+    assert_eq!(notes.next_after(1), Some(2));
+    assert_eq!(notes.prev_before(1), None);
+
+    assert_eq!(
+        render_notes(&document, &notes),
+        "# Notes on report\n\n\
+         ## Executive Summary\n\n\
+         *Bookmarked*\n\n\
+         > TechCorp Inc. delivered exceptional results in Q4 2024, achieving record revenue of \
+         $2.1M and expanding our customer base by 34%. Key highlights include successful product \
+         launches, strategic partnerships, and improved operational efficiency.\n\n\
+         Check against Q3\n"
+    );
+}
+
+#[test]
+fn test_notes_follow_an_edited_document() {
+    let path = copy_fixture("edited", "headings-hierarchy.docx");
+    let mut document = load_document(&path, ImageOptions::default()).unwrap();
+    let mut notes = Notes::load(&path, &document).unwrap();
+    notes.set_note(&document, 3, "Reword this");
+    notes.save().unwrap();
+    let excerpt = notes.annotations()[0].excerpt.clone();
+
+    // Another element added at the top, and the file's contents changed with it
+    document
+        .elements
+        .insert(0, DocumentElement::PageBreak { page: 2 });
+    std::fs::copy("tests/fixtures/minimal.docx", &path).unwrap();
+    let notes = Notes::load(&path, &document).unwrap();
+    assert_eq!(notes.annotations().len(), 1);
+    assert_eq!(notes.annotations()[0].element, 4);
+    assert_eq!(notes.annotations()[0].excerpt, excerpt);
+
+    // Saving replaces the notes on the old version
+    notes.save().unwrap();
+    let saved = std::fs::read_to_string(notes.path()).unwrap();
+    assert_eq!(saved.matches("\"file\": \"report.docx\"").count(), 1);
+}
+
+#[test]
+fn test_removing_the_last_bookmark_removes_the_notes_file() {
+    let path = copy_fixture("removed", "minimal.docx");
+    let document = load_document(&path, ImageOptions::default()).unwrap();
+    let mut notes = Notes::load(&path, &document).unwrap();
+
+    notes.toggle_bookmark(&document, 0);
+    notes.save().unwrap();
+    assert!(notes.path().exists());
+
+    assert!(!notes.toggle_bookmark(&document, 0));
+    notes.save().unwrap();
+    assert!(!notes.path().exists());
+    assert_eq!(
+        render_notes(&document, &notes),
+        "# Notes on report\n\nNo bookmarks or notes yet.\n"
+    );
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "test-support")]
+
+//! OMML conversion against the corpus in `tests/fixtures/omml`
+//!
+//! Each `<case>.xml` there holds the content of an `m:oMath`; in
+//! `tests/golden/omml`, `<case>.tex` is the LaTeX it converts to and
+//! `<case>.txt` the Unicode the terminal shows. After an intended change,
+//! regenerate them with
+//! `DOXX_BLESS=1 cargo test --features test-support --test omml_test`.
+
+use doxx::testing::assert_golden;
+use doxx::Equation;
+use std::path::Path;
+
+const CORPUS: &str = "tests/fixtures/omml";
+const GOLDEN: &str = "tests/golden/omml";
+
+#[test]
+fn test_corpus_converts_to_its_goldens() {
+    let mut cases: Vec<_> = std::fs::read_dir(CORPUS)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "xml"))
+        .collect();
+    cases.sort();
+    assert!(!cases.is_empty());
+
+    for case in cases {
+        let omml = std::fs::read_to_string(&case).unwrap();
+        let equation = Equation::from_omml(omml)
+            .unwrap_or_else(|error| panic!("{} didn't parse: {error}", case.display()));
+        let golden = Path::new(GOLDEN).join(case.file_name().unwrap());
+        assert_golden(
+            &golden.with_extension("tex"),
+            &format!("{}\n", equation.latex),
+        );
+        assert_golden(
+            &golden.with_extension("txt"),
+            &format!("{}\n", equation.unicode),
+        );
+    }
+}
+
+#[test]
+fn test_malformed_markup_is_an_error_not_a_panic() {
+    for omml in [
+        "<m:r><m:t>x</m:r></m:t>",
+        "<m:f><m:num><m:r><m:t>1</m:t></m:r></m:den></m:f>",
+        "<m:r><m:t>a &bogus; b</m:t></m:r>",
+        "<m:nary><m:naryPr><m:chr m:val=\"∑/></m:naryPr></m:nary>",
+    ] {
+        assert!(Equation::from_omml(omml.to_string()).is_err(), "{omml}");
+    }
+}
+
+#[test]
+fn test_truncated_markup_keeps_what_was_read() {
+    let omml = "<m:sSup><m:e><m:r><m:t>x</m:t></m:r></m:e><m:sup><m:r><m:t>2";
+    let equation = Equation::from_omml(omml.to_string()).unwrap();
+    assert_eq!(equation.latex, "x^{2}");
+    assert_eq!(equation.fallback, "x2");
+}
+
+#[test]
+fn test_deep_nesting_keeps_its_text() {
+    // Far deeper than any equation, and than the stack would allow were
+    // every level converted
+    let depth = 100_000;
+    let omml = "<m:sSup><m:e><m:r><m:t>x</m:t></m:r></m:e><m:sup>".repeat(depth)
+        + "<m:r><m:t>y</m:t></m:r>"
+        + &"</m:sup></m:sSup>".repeat(depth);
+    let equation = Equation::from_omml(omml).unwrap();
+    assert!(equation.latex.starts_with("x^{x^{x^{"));
+    assert!(equation.latex.contains("xxxx"));
+    assert_eq!(equation.fallback.len(), depth + 1);
+}
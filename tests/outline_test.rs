@@ -0,0 +1,95 @@
+use doxx::{
+    document::{folded_elements, load_document, section_heading, section_outline, ImageOptions},
+    export::outline::render_outline,
+    Document, OutlineFormat,
+};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+fn load(fixture: &str) -> Document {
+    load_document(
+        &Path::new("tests/fixtures").join(fixture),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_sections_nest_and_count_their_words() {
+    let outline = section_outline(&load("numbered-headings.docx"));
+    assert_eq!(outline.len(), 3);
+
+    let introduction = &outline[0];
+    assert_eq!(introduction.number.as_deref(), Some("1"));
+    assert_eq!(introduction.text, "Introduction");
+    assert_eq!(introduction.children.len(), 2);
+    // A section's total takes in its subsections
+    assert_eq!(
+        introduction.total_words,
+        introduction.words
+            + introduction
+                .children
+                .iter()
+                .map(|child| child.total_words)
+                .sum::<usize>()
+    );
+    assert_eq!(
+        introduction.children[0].children[1].text,
+        "Success Criteria"
+    );
+}
+
+#[test]
+fn test_skipped_heading_levels_nest_under_the_nearest_heading() {
+    let document = load("headings-hierarchy.docx");
+    let outline = section_outline(&document);
+    let mut section = &outline[1];
+    for level in 2..=6 {
+        section = &section.children[0];
+        assert_eq!(section.level, level);
+    }
+}
+
+#[test]
+fn test_outline_formats() {
+    let document = load("numbered-headings.docx");
+
+    let tree = render_outline(&document, OutlineFormat::Tree).unwrap();
+    assert!(tree.starts_with("numbered-headings  ("));
+    assert!(tree.contains("\n├── 1 Introduction  (43 words)\n│   ├── 1.1 Project Overview  ("));
+    assert!(tree.contains("\n└── 3 Testing Strategy  ("));
+
+    let markdown = render_outline(&document, OutlineFormat::Markdown).unwrap();
+    assert!(markdown.contains("\n- 1 Introduction (43 words)\n  - 1.1 Project Overview ("));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&render_outline(&document, OutlineFormat::Json).unwrap()).unwrap();
+    assert_eq!(json["sections"][0]["number"], "1");
+    assert_eq!(json["sections"][0]["sections"][1]["text"], "Timeline");
+    assert_eq!(json["sections"][0]["total_words"], 43);
+}
+
+#[test]
+fn test_folding_a_heading_hides_its_section() {
+    let document = load("numbered-headings.docx");
+    let outline = section_outline(&document);
+    let introduction = outline[0].element_index;
+    let next = outline[1].element_index;
+
+    // An element belongs to the section of the nearest heading above it
+    assert_eq!(
+        section_heading(&document, introduction + 1),
+        Some(introduction)
+    );
+
+    let hidden = folded_elements(&document, &BTreeSet::from([introduction]));
+    assert!(!hidden[introduction]);
+    assert!(hidden[introduction + 1..next].iter().all(|&hidden| hidden));
+    assert!(hidden[next..].iter().all(|&hidden| !hidden));
+
+    // Folding a subsection stops at the next one
+    let subsection = outline[0].children[0].element_index;
+    let hidden = folded_elements(&document, &BTreeSet::from([subsection]));
+    assert!(!hidden[subsection] && hidden[subsection + 1]);
+    assert!(!hidden[outline[0].children[1].element_index]);
+}
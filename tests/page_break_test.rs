@@ -0,0 +1,53 @@
+use doxx::document::{load_document, DocumentElement, ImageOptions};
+use doxx::export::{render_markdown, ExportOptions};
+use std::path::Path;
+
+const FIXTURE: &str = "tests/fixtures/breaks-and-rules.docx";
+
+/// Page breaks by the page they start, and rules as 0
+fn breaks_and_rules(path: &str) -> Vec<usize> {
+    let document = load_document(Path::new(path), ImageOptions::default()).unwrap();
+    document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::PageBreak { page } => Some(*page),
+            DocumentElement::HorizontalRule => Some(0),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_explicit_page_breaks_are_numbered_from_word_layout() {
+    // Word's layout put a page break inside the second paragraph, so the
+    // first hard break starts page 3. A bottom border under an empty
+    // paragraph, one under two paragraphs bordered alike and a VML line are
+    // rules; a boxed paragraph isn't.
+    assert_eq!(breaks_and_rules(FIXTURE), [3, 0, 0, 0, 4, 5]);
+}
+
+#[test]
+fn test_page_break_before_goes_ahead_of_its_paragraph() {
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let appendix = document
+        .elements
+        .iter()
+        .position(|element| {
+            matches!(element, DocumentElement::Paragraph { runs, .. }
+                if runs.iter().any(|run| run.text.starts_with("The appendix")))
+        })
+        .unwrap();
+    assert!(matches!(
+        document.elements[appendix - 1],
+        DocumentElement::PageBreak { page: 4 }
+    ));
+}
+
+#[test]
+fn test_markdown_marks_page_breaks_and_rules() {
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let markdown = render_markdown(&document, &ExportOptions::default());
+    assert!(markdown.contains("\n---\n<!-- page 3 -->\n"));
+    assert!(markdown.contains("Signed off by the board\n\nand the auditors\n\n\n---\n\n"));
+}
@@ -0,0 +1,163 @@
+use doxx::document::{
+    load_document, presentation_slides, Document, DocumentElement, DocumentMetadata, FormattedRun,
+    ImageOptions, Slide, TextAlignment,
+};
+use std::path::Path;
+
+fn paragraph(text: &str) -> DocumentElement {
+    DocumentElement::Paragraph {
+        runs: vec![FormattedRun {
+            text: text.to_string(),
+            formatting: Default::default(),
+        }],
+        alignment: TextAlignment::Left,
+        shading: None,
+        rtl: false,
+        spacing: None,
+        tab_stops: Vec::new(),
+    }
+}
+
+fn heading(level: u8, text: &str) -> DocumentElement {
+    DocumentElement::Heading {
+        level,
+        text: text.to_string(),
+        number: None,
+    }
+}
+
+fn create_document(elements: Vec<DocumentElement>) -> Document {
+    Document {
+        title: "Talk".to_string(),
+        metadata: DocumentMetadata {
+            file_path: "talk.docx".to_string(),
+            file_size: 1024,
+            word_count: 10,
+            page_count: 1,
+            created: None,
+            modified: None,
+            author: None,
+            language: None,
+            locale: None,
+            protection: None,
+        },
+        elements,
+        sections: Vec::new(),
+        image_options: Default::default(),
+        fidelity: Default::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
+    }
+}
+
+#[test]
+fn test_single_title_heading_splits_at_chapters() {
+    let document = create_document(vec![
+        heading(1, "Talk"),
+        paragraph("Welcome"),
+        heading(2, "Why"),
+        paragraph("Because"),
+        heading(3, "Details"),
+        paragraph("More"),
+        heading(2, "How"),
+    ]);
+
+    assert_eq!(
+        presentation_slides(&document),
+        [
+            Slide {
+                heading: Some(0),
+                body: 1..2,
+            },
+            Slide {
+                heading: Some(2),
+                body: 3..6,
+            },
+            Slide {
+                heading: Some(6),
+                body: 7..7,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_content_before_first_heading_is_a_slide() {
+    let document = create_document(vec![
+        paragraph("Preface"),
+        heading(1, "One"),
+        heading(1, "Two"),
+        heading(2, "Two and a half"),
+    ]);
+    let slides = presentation_slides(&document);
+    assert_eq!(
+        slides[0],
+        Slide {
+            heading: None,
+            body: 0..1
+        }
+    );
+    assert_eq!(
+        slides[1..],
+        [
+            Slide {
+                heading: Some(1),
+                body: 2..2,
+            },
+            Slide {
+                heading: Some(2),
+                body: 3..4,
+            },
+        ]
+    );
+
+    // A blank line before the first heading isn't worth a slide
+    let document = create_document(vec![paragraph(""), heading(1, "One")]);
+    assert_eq!(
+        presentation_slides(&document),
+        [Slide {
+            heading: Some(1),
+            body: 2..2,
+        }]
+    );
+}
+
+#[test]
+fn test_document_without_headings_is_one_slide() {
+    let document = create_document(vec![paragraph("One"), paragraph("Two")]);
+    assert_eq!(
+        presentation_slides(&document),
+        [Slide {
+            heading: None,
+            body: 0..2,
+        }]
+    );
+}
+
+#[test]
+fn test_slides_cover_the_document() {
+    let document = load_document(
+        Path::new("tests/fixtures/headings-hierarchy.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let slides = presentation_slides(&document);
+
+    assert!(slides.len() > 1);
+    let mut next = 0;
+    for slide in &slides {
+        if let Some(heading) = slide.heading {
+            assert!(matches!(
+                document.elements[heading],
+                DocumentElement::Heading { .. }
+            ));
+            assert_eq!(heading, next);
+            next += 1;
+        }
+        assert!(slide.body.start >= next);
+        next = slide.body.end;
+    }
+    assert_eq!(next, document.elements.len());
+}
@@ -0,0 +1,118 @@
+use doxx::document::{load_document, Document, ImageOptions};
+use doxx::export::print::{paginate, render_postscript, PrintOptions};
+use std::path::Path;
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/breaks-and-rules.docx";
+
+fn breaks_and_rules() -> Document {
+    load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap()
+}
+
+#[test]
+fn test_pages_end_at_page_breaks() {
+    let document = breaks_and_rules();
+    let options = PrintOptions::for_document(&document);
+    assert_eq!((options.columns(), options.lines_per_page()), (78, 54));
+    let pages = paginate(&document, &options).unwrap();
+    assert_eq!(pages.len(), 4);
+    assert_eq!(pages[0][0], "The first page opens the report.");
+    assert_eq!(pages[1][0], "Page three starts with the details.");
+}
+
+#[test]
+fn test_full_pages_continue_on_the_next() {
+    let document = breaks_and_rules();
+    let mut options = PrintOptions::for_document(&document);
+    options.margins = [36.0; 4];
+    options.height = 36.0 * 2.0 + 12.0 * 3.0;
+    options.width = 36.0 * 2.0 + 6.0 * 20.0;
+    assert_eq!((options.columns(), options.lines_per_page()), (20, 3));
+    let pages = paginate(&document, &options).unwrap();
+    // Each page holds three lines at most, none opening on a blank one
+    assert!(pages.iter().all(|page| page.len() <= 3));
+    assert!(pages.iter().all(|page| !page[0].is_empty()));
+    assert_eq!(pages[0][0], "The first page opens");
+    // Rules are folded rather than cut off
+    assert!(pages
+        .iter()
+        .flatten()
+        .all(|line| line.chars().count() <= 20));
+
+    options.margins = [200.0; 4];
+    let error = paginate(&document, &options).unwrap_err();
+    assert!(error.to_string().starts_with("The margins leave no room"));
+}
+
+#[test]
+fn test_postscript_numbers_each_page() {
+    let document = breaks_and_rules();
+    let options = PrintOptions::for_document(&document);
+    let pages = paginate(&document, &options).unwrap();
+    let postscript = render_postscript(&document, &pages, &options);
+    assert!(postscript.starts_with("%!PS-Adobe-3.0\n"));
+    assert!(postscript.contains("%%Pages: 4\n"));
+    assert!(postscript.contains("<< /PageSize [612 792] >> setpagedevice\n"));
+    assert!(postscript.contains("%%Page: 2 2\n"));
+    assert!(postscript.contains("72 710 moveto (Page three starts with the details.) show\n"));
+    assert!(postscript.contains("(Page 4 of 4) show\nshowpage\n%%Trailer\n"));
+}
+
+#[test]
+fn test_print_writes_postscript_in_the_size_asked_for() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([
+            "print",
+            FIXTURE,
+            "--output",
+            "-",
+            "--paper",
+            "a4",
+            "--landscape",
+        ])
+        .args(["--margin", "2cm", "--font-size", "12"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let postscript = String::from_utf8(output.stdout).unwrap();
+    assert!(postscript.contains("<< /PageSize [841.89 595.28] >> setpagedevice\n"));
+    assert!(postscript.contains("/Courier-Latin1 findfont 12 scalefont setfont\n"));
+    assert!(postscript.contains("56.69 526.59 moveto (The first page opens the report.) show\n"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["print", FIXTURE, "--margin", "3em"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown unit em"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_print_sends_postscript_to_lp() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // An lp that keeps what it was given
+    let dir = std::env::temp_dir().join(format!("doxx-print-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let lp = dir.join("lp");
+    std::fs::write(
+        &lp,
+        "#!/bin/sh\necho \"$@\" > \"$(dirname \"$0\")/args\"\ncat > \"$(dirname \"$0\")/job.ps\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&lp, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", dir.display(), std::env::var("PATH").unwrap());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["print", FIXTURE, "-d", "office", "-n", "2"])
+        .env("PATH", path)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let args = std::fs::read_to_string(dir.join("args")).unwrap();
+    assert_eq!(args.trim(), "-t breaks-and-rules -n 2 -d office");
+    let job = std::fs::read_to_string(dir.join("job.ps")).unwrap();
+    assert!(job.starts_with("%!PS-Adobe-3.0"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,119 @@
+use doxx::document::{
+    load_document_with_progress, FieldOptions, ImageOptions, Phase, ProgressEvent,
+};
+use doxx::export::export_document_to_file_with_progress;
+use doxx::ExportFormat;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Load a fixture, collecting every event it reports
+fn load_events(name: &str, image_options: ImageOptions) -> Vec<ProgressEvent> {
+    let events = Mutex::new(Vec::new());
+    let path = Path::new("tests/fixtures").join(name);
+    load_document_with_progress(&path, image_options, FieldOptions::default(), &|event| {
+        events.lock().unwrap().push(event)
+    })
+    .unwrap();
+    events.into_inner().unwrap()
+}
+
+fn phases(events: &[ProgressEvent]) -> Vec<(Phase, bool)> {
+    events
+        .iter()
+        .filter_map(|event| match *event {
+            ProgressEvent::Started { phase, .. } => Some((phase, true)),
+            ProgressEvent::Finished { phase } => Some((phase, false)),
+            ProgressEvent::Advanced { .. } => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_load_reports_each_phase_in_order() {
+    let events = load_events("business-report.docx", ImageOptions::default());
+    assert_eq!(
+        phases(&events),
+        [
+            (Phase::Opening, true),
+            (Phase::Opening, false),
+            (Phase::Parsing, true),
+            (Phase::Parsing, false),
+            (Phase::Building, true),
+            (Phase::Building, false),
+        ]
+    );
+
+    // The body is counted out block by block, up to its length
+    let total = events.iter().find_map(|event| match *event {
+        ProgressEvent::Started {
+            phase: Phase::Building,
+            total,
+        } => total,
+        _ => None,
+    });
+    let steps: Vec<u64> = events
+        .iter()
+        .filter_map(|event| match *event {
+            ProgressEvent::Advanced {
+                phase: Phase::Building,
+                done,
+            } => Some(done),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(steps.len() as u64, total.unwrap());
+    assert!(steps.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_image_extraction_is_counted_by_media_file() {
+    let image_options = ImageOptions {
+        enabled: true,
+        ..ImageOptions::default()
+    };
+    let events = load_events("images.docx", image_options);
+    let phases = phases(&events);
+    let started = phases.iter().position(|&p| p == (Phase::Images, true));
+    let finished = phases.iter().position(|&p| p == (Phase::Images, false));
+    assert!(started.unwrap() < finished.unwrap());
+    assert!(events.iter().any(|event| matches!(
+        event,
+        ProgressEvent::Started {
+            phase: Phase::Images,
+            total: Some(total),
+        } if *total > 0
+    )));
+}
+
+#[test]
+fn test_export_reports_bytes_written() {
+    let document = doxx::document::load_document(
+        Path::new("tests/fixtures/business-report.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let output = std::env::temp_dir().join(format!("doxx-progress-{}.md", std::process::id()));
+    let events = Mutex::new(Vec::new());
+    export_document_to_file_with_progress(
+        &document,
+        &ExportFormat::Markdown,
+        &Default::default(),
+        &output,
+        &|event| events.lock().unwrap().push(event),
+    )
+    .unwrap();
+    let written = std::fs::metadata(&output).unwrap().len();
+    std::fs::remove_file(&output).unwrap();
+
+    let events = events.into_inner().unwrap();
+    assert_eq!(
+        events.last(),
+        Some(&ProgressEvent::Finished {
+            phase: Phase::Exporting
+        })
+    );
+    assert!(events.contains(&ProgressEvent::Advanced {
+        phase: Phase::Exporting,
+        done: written,
+    }));
+}
@@ -0,0 +1,54 @@
+use doxx::document::{load_document, EditRestriction, ImageOptions};
+use std::path::Path;
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/final-protected.docx";
+
+#[test]
+fn test_protection_is_read_from_settings_and_custom_properties() {
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let protection = document.metadata.protection.unwrap();
+    assert_eq!(protection.editing, Some(EditRestriction::Comments));
+    assert!(protection.marked_final);
+    assert!(protection.read_only_recommended);
+    // The hash on `w:documentProtection` guards the restriction, not saving
+    assert!(!protection.password_to_modify);
+    assert_eq!(
+        protection.summary(),
+        "Marked as final, Comments only, Read-only recommended"
+    );
+
+    let document = load_document(
+        Path::new("tests/fixtures/minimal.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(document.metadata.protection, None);
+}
+
+#[test]
+fn test_protection_is_reported_in_exports_and_the_preview() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([FIXTURE, "--export", "markdown"])
+        .output()
+        .unwrap();
+    let markdown = String::from_utf8(output.stdout).unwrap();
+    assert!(markdown
+        .contains("- **Protection**: Marked as final, Comments only, Read-only recommended\n"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([FIXTURE, "--export", "json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["metadata"]["protection"]["editing"], "comments");
+    assert_eq!(json["metadata"]["protection"]["marked_final"], true);
+
+    // Piped, the viewer prints a preview, which warns of it too
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .arg(FIXTURE)
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("⚠ Protected: Marked as final, Comments only"));
+}
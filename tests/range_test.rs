@@ -0,0 +1,103 @@
+use doxx::document::{
+    load_document, load_document_with_fields, Document, DocumentElement, DocumentRange,
+    FieldOptions, ImageOptions, RangeUnit,
+};
+use std::path::Path;
+
+fn load_range(fixture: &str, range: &str, images: bool) -> anyhow::Result<Document> {
+    load_document_with_fields(
+        &Path::new("tests/fixtures").join(fixture),
+        ImageOptions {
+            enabled: images,
+            ..Default::default()
+        },
+        FieldOptions {
+            range: Some(range.parse().unwrap()),
+            ..Default::default()
+        },
+    )
+}
+
+fn paragraphs(document: &Document) -> Vec<String> {
+    document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Paragraph { runs, .. } => {
+                Some(runs.iter().map(|run| run.text.as_str()).collect())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_range_parsing() {
+    let range: DocumentRange = "pages 10-20".parse().unwrap();
+    assert_eq!(
+        (range.unit, range.first, range.last),
+        (RangeUnit::Pages, 10, Some(20))
+    );
+    let range: DocumentRange = "section 3".parse().unwrap();
+    assert_eq!((range.first, range.last), (3, Some(3)));
+    assert_eq!(range.to_string(), "section 3");
+    let range: DocumentRange = "pages 50-".parse().unwrap();
+    assert_eq!(range.last, None);
+    assert!("chapters 1-2".parse::<DocumentRange>().is_err());
+    assert!("pages 0-3".parse::<DocumentRange>().is_err());
+    assert!("pages 5-2".parse::<DocumentRange>().is_err());
+}
+
+#[test]
+fn test_section_range_keeps_its_page_setup() {
+    let whole = load_document(
+        Path::new("tests/fixtures/sections.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let document = load_range("sections.docx", "section 2", false).unwrap();
+    assert_eq!(document.sections.len(), 1);
+    assert_eq!(document.sections[0].page, whole.sections[1].page);
+    let text = paragraphs(&document);
+    assert!(text[0].starts_with("The second section is a landscape page"));
+    assert!(text.last().unwrap().starts_with("Returns fell"));
+    assert!(document.metadata.word_count < whole.metadata.word_count);
+}
+
+#[test]
+fn test_page_range_numbers_breaks_as_in_the_whole_document() {
+    let document = load_range("breaks-and-rules.docx", "pages 3-4", false).unwrap();
+    let text = paragraphs(&document);
+    assert_eq!(text[0], "Page three starts with the details.");
+    assert_eq!(text.last().unwrap(), "The appendix begins on a new page.");
+    let breaks: Vec<usize> = document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::PageBreak { page } => Some(*page),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(breaks, [4]);
+
+    let error = load_range("breaks-and-rules.docx", "pages 9-", false).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Nothing to read in pages 9-: the document has 5 pages"
+    );
+}
+
+#[test]
+fn test_page_range_only_extracts_its_images() {
+    let images = |document: &Document| {
+        document
+            .elements
+            .iter()
+            .filter(|element| matches!(element, DocumentElement::Image { .. }))
+            .count()
+    };
+    let whole = load_range("images.docx", "pages 1-", true).unwrap();
+    let first_page = load_range("images.docx", "page 1", true).unwrap();
+    assert_eq!(images(&first_page), 1);
+    assert!(images(&whole) > 1);
+}
@@ -0,0 +1,158 @@
+use doxx::{
+    document::{
+        load_document_with_fields, Document, DocumentElement, FieldOptions, ImageOptions, TextWrap,
+    },
+    notes::element_text,
+    ReadingOrder,
+};
+use std::path::Path;
+
+fn load(fixture: &str, reading_order: ReadingOrder, images: bool) -> Document {
+    load_document_with_fields(
+        &Path::new("tests/fixtures").join(fixture),
+        ImageOptions {
+            enabled: images,
+            ..Default::default()
+        },
+        FieldOptions {
+            reading_order,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+/// Text of each element of a fixture, in order
+fn texts(fixture: &str, reading_order: ReadingOrder) -> Vec<String> {
+    let document = load(fixture, reading_order, false);
+    document.elements.iter().map(element_text).collect()
+}
+
+/// Text of each element of the floating images fixture, images by description
+fn floating_images(reading_order: ReadingOrder) -> Vec<String> {
+    let document = load("floating-images.docx", reading_order, true);
+    document.elements[1..]
+        .iter()
+        .map(|element| match element {
+            DocumentElement::Image { description, .. } => description.clone(),
+            element => element_text(element),
+        })
+        .collect()
+}
+
+#[test]
+fn test_xml_order_reads_floats_where_they_are_stored() {
+    assert_eq!(
+        texts("floating-content.docx", ReadingOrder::Xml),
+        [
+            "Harbour Newsletter",
+            "The harbour reopened on Monday after two weeks of repairs.",
+            "Printed on recycled paper.",
+            "Ferries run on the summer timetable from May.",
+            "“The busiest spring in a decade.”",
+            "Parking near the pier is limited while the works finish.",
+            "Issue 14, Spring",
+            "Tide tables are posted at the harbour office.",
+            "Fishing permits renew in April.",
+            "Volunteers meet at the lifeboat station on Saturdays.",
+        ]
+    );
+}
+
+#[test]
+fn test_visual_order_reads_floats_where_they_are_positioned() {
+    assert_eq!(
+        texts("floating-content.docx", ReadingOrder::Visual),
+        [
+            "Harbour Newsletter",
+            // Near the bottom of the page, so after its anchor
+            "The harbour reopened on Monday after two weeks of repairs.",
+            "Printed on recycled paper.",
+            // Moved up above the paragraph it's anchored in
+            "“The busiest spring in a decade.”",
+            "Ferries run on the summer timetable from May.",
+            // At the top of the page
+            "Issue 14, Spring",
+            "Parking near the pier is limited while the works finish.",
+            // A frame set below the top of the paragraph after it
+            "Fishing permits renew in April.",
+            "Tide tables are posted at the harbour office.",
+            "Volunteers meet at the lifeboat station on Saturdays.",
+        ]
+    );
+}
+
+#[test]
+fn test_text_in_grouped_shapes_is_read() {
+    let texts = texts("unicode_panic_test.docx", ReadingOrder::Xml);
+    assert!(texts.iter().any(|text| text == "Wintertime Rebus Story"));
+}
+
+#[test]
+fn test_floating_images_follow_their_anchor_paragraph() {
+    assert_eq!(
+        floating_images(ReadingOrder::Xml),
+        [
+            "An inline photo of the site:",
+            "Image 1",
+            "The survey team photographed the northern wall, shown beside this paragraph.",
+            "Image 2",
+            "Conditions were dry for most of the week.",
+            "Samples were sent to the laboratory on Friday.",
+            "Image 3",
+            "The results are expected next month.",
+            "Image 4",
+        ]
+    );
+    // Moved up above its paragraph, so read before it
+    assert_eq!(
+        floating_images(ReadingOrder::Visual)[7..],
+        ["Image 4", "The results are expected next month."]
+    );
+}
+
+#[test]
+fn test_images_keep_their_size_and_placement() {
+    let document = load("floating-images.docx", ReadingOrder::Xml, true);
+    let placements: Vec<_> = document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Image {
+                width,
+                height,
+                placement,
+                ..
+            } => Some((*width, *height, placement.clone().unwrap())),
+            _ => None,
+        })
+        .collect();
+
+    // Two inches by one: 192 × 96 pixels, 20 columns by 5 rows
+    let (width, height, inline) = &placements[0];
+    assert_eq!((*width, *height), (Some(192), Some(96)));
+    assert_eq!((inline.columns, inline.rows), (20, 5));
+    assert_eq!(inline.anchor, None);
+    assert_eq!(inline.describe(), None);
+
+    let beside = &placements[1].2;
+    let anchor = beside.anchor.as_ref().unwrap();
+    assert_eq!(anchor.align_h.as_deref(), Some("right"));
+    assert_eq!(anchor.wrap, TextWrap::Square);
+    assert_eq!(beside.float_side(), Some("right"));
+    assert_eq!(
+        beside.describe().unwrap(),
+        "Floating at the right of the margin, level with the paragraph; text wraps around it"
+    );
+
+    let watermark = placements[2].2.anchor.as_ref().unwrap();
+    assert_eq!(watermark.wrap, TextWrap::BehindText);
+    assert_eq!((watermark.x, watermark.y), (10, 45));
+    assert_eq!(placements[2].2.float_side(), None);
+
+    // Floating images float beside the text in HTML and are noted elsewhere
+    let html = doxx::export::html::render_html(&document);
+    assert!(html.contains("<p style=\"float: right\"><img"));
+    let markdown = doxx::export::render_markdown(&document, &Default::default());
+    assert!(markdown.contains("*(Floating at the center of the column, 2 rows above"));
+}
@@ -0,0 +1,139 @@
+use doxx::document::{
+    load_document, load_document_lenient, Document, DocumentElement, ImageOptions,
+};
+use std::path::Path;
+use std::process::Command;
+
+fn repair(fixture: &str) -> (Document, Vec<String>) {
+    let path = Path::new("tests/fixtures").join(fixture);
+    assert!(
+        load_document(&path, ImageOptions::default()).is_err(),
+        "{fixture} should be rejected by the normal loader"
+    );
+    let (document, report) = load_document_lenient(&path, ImageOptions::default())
+        .unwrap_or_else(|error| panic!("{fixture} was not recovered: {error:#}"));
+    (document, report.notes)
+}
+
+fn text_of(document: &Document) -> String {
+    let mut text = String::new();
+    for element in &document.elements {
+        match element {
+            DocumentElement::Heading { text: heading, .. } => text.push_str(heading),
+            DocumentElement::Paragraph { runs, .. } => {
+                text.extend(runs.iter().map(|run| run.text.as_str()))
+            }
+            DocumentElement::List { items, .. } => {
+                for item in items {
+                    text.extend(item.runs.iter().map(|run| run.text.as_str()));
+                    text.push('\n');
+                }
+            }
+            DocumentElement::Table { table } => {
+                for cell in table.headers.iter().chain(table.rows.iter().flatten()) {
+                    text.push_str(&cell.content);
+                    text.push('|');
+                }
+            }
+            _ => {}
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[test]
+fn test_clean_documents_load_unchanged() {
+    let path = Path::new("tests/fixtures/business-report.docx");
+    let (document, report) = load_document_lenient(path, ImageOptions::default()).unwrap();
+    assert!(report.is_clean());
+    let normal = load_document(path, ImageOptions::default()).unwrap();
+    assert_eq!(document.elements.len(), normal.elements.len());
+}
+
+#[test]
+fn test_missing_package_parts_are_rebuilt() {
+    let (document, notes) = repair("damaged-missing-parts.docx");
+
+    assert!(notes.contains(&"Rebuilt the missing [Content_Types].xml".to_string()));
+    assert!(notes.contains(&"Rebuilt the missing word/_rels/document.xml.rels".to_string()));
+    assert!(notes
+        .iter()
+        .any(|note| note.starts_with("Dropped word/comments.xml: malformed XML")));
+    assert_eq!(notes.last().unwrap(), "Loaded the repaired package");
+
+    // The normal loader ran on the rebuilt package, so structure survives
+    assert!(matches!(
+        &document.elements[0],
+        DocumentElement::Heading { level: 1, text, .. } if text == "Recovered Report"
+    ));
+    assert!(text_of(&document).contains("Closing paragraph after the damage."));
+}
+
+#[test]
+fn test_malformed_body_is_scanned() {
+    let (document, notes) = repair("damaged-malformed.docx");
+
+    assert!(notes
+        .iter()
+        .any(|note| note.starts_with("word/document.xml is malformed")));
+    assert!(notes
+        .last()
+        .unwrap()
+        .starts_with("Recovered 7 elements by scanning the body XML"));
+
+    assert!(matches!(
+        &document.elements[0],
+        DocumentElement::Heading { level: 1, text, .. } if text == "Recovered Report"
+    ));
+    let DocumentElement::Paragraph { runs, .. } = &document.elements[1] else {
+        panic!("Expected the opening paragraph");
+    };
+    assert!(runs
+        .iter()
+        .any(|run| run.text == "bold words" && run.formatting.bold));
+    assert!(matches!(
+        &document.elements[4],
+        DocumentElement::Table { .. }
+    ));
+
+    // Text on both sides of the damage is kept, the stray `&` as written
+    let text = text_of(&document);
+    assert!(text.contains("* First point\n* Second point\n"));
+    assert!(text.contains("Item|Cost|Paper|$5|"));
+    assert!(text.contains("Fish & chips were served at the end."));
+    assert!(text.contains("Closing paragraph after the damage."));
+}
+
+#[test]
+fn test_truncated_zip_is_salvaged() {
+    let (document, notes) = repair("damaged-truncated.docx");
+
+    assert!(notes[1].starts_with("The ZIP directory is unreadable"));
+    assert_eq!(notes.last().unwrap(), "Loaded the repaired package");
+    assert!(text_of(&document).contains("1. First point\n2. Second point\n"));
+}
+
+#[test]
+fn test_repair_flag_reports_to_stderr() {
+    let run = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_doxx"))
+            .args([
+                "tests/fixtures/damaged-missing-parts.docx",
+                "--export",
+                "text",
+            ])
+            .args(extra)
+            .output()
+            .expect("Failed to execute doxx")
+    };
+
+    assert!(!run(&[]).status.success());
+
+    let output = run(&["--repair"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Repaired tests/fixtures/damaged-missing-parts.docx:"));
+    assert!(stderr.contains("  - Rebuilt the missing [Content_Types].xml"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Recovered Report"));
+}
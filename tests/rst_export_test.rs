@@ -0,0 +1,150 @@
+use doxx::{
+    document::{
+        load_document, Document, DocumentElement, DocumentMetadata, FormattedRun, ImageOptions,
+        ListItem, TextAlignment, TextFormatting,
+    },
+    export::{infer_export_format, rst::render_rst},
+    ExportFormat,
+};
+use std::path::Path;
+
+fn paragraph(runs: Vec<FormattedRun>) -> DocumentElement {
+    DocumentElement::Paragraph {
+        runs,
+        alignment: TextAlignment::Left,
+        shading: None,
+        rtl: false,
+        spacing: None,
+        tab_stops: Vec::new(),
+    }
+}
+
+fn run(text: &str, formatting: TextFormatting) -> FormattedRun {
+    FormattedRun {
+        text: text.to_string(),
+        formatting,
+    }
+}
+
+fn heading(level: u8, text: &str) -> DocumentElement {
+    DocumentElement::Heading {
+        level,
+        text: text.to_string(),
+        number: None,
+    }
+}
+
+fn create_document(elements: Vec<DocumentElement>) -> Document {
+    Document {
+        title: "Guide".to_string(),
+        metadata: DocumentMetadata {
+            file_path: "guide.docx".to_string(),
+            file_size: 1024,
+            word_count: 10,
+            page_count: 1,
+            created: None,
+            modified: None,
+            author: Some("Test Author".to_string()),
+            language: None,
+            locale: None,
+            protection: None,
+        },
+        elements,
+        sections: Vec::new(),
+        image_options: Default::default(),
+        fidelity: Default::default(),
+        index_entries: Vec::new(),
+        heading_bookmarks: Default::default(),
+        equation_bookmarks: Default::default(),
+        volatile_fields: 0,
+    }
+}
+
+#[test]
+fn test_rst_grid_tables() {
+    let document = load_document(
+        Path::new("tests/fixtures/tables-heavy.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let rst = render_rst(&document);
+
+    assert!(rst.starts_with("============\ntables-heavy\n============\n\n"));
+    assert!(rst.contains(
+        "+------+-----+-------------+\n\
+         | Name | Age | City        |\n\
+         +======+=====+=============+\n\
+         | John |  25 | New York    |\n\
+         +------+-----+-------------+\n"
+    ));
+}
+
+#[test]
+fn test_rst_headings_never_skip_a_level() {
+    let document = create_document(vec![
+        heading(2, "Overview"),
+        heading(4, "Detail"),
+        heading(1, "Setup"),
+        heading(3, "Install"),
+    ]);
+    let rst = render_rst(&document);
+
+    assert!(rst.contains(":Author: Test Author\n\n"));
+    assert!(rst.contains("Overview\n========\n\nDetail\n------\n\n"));
+    assert!(rst.contains("Setup\n=====\n\nInstall\n-------\n\n"));
+}
+
+#[test]
+fn test_rst_blocks() {
+    let bold = TextFormatting {
+        bold: true,
+        ..Default::default()
+    };
+    let item = |text: &str, level| ListItem {
+        runs: vec![run(text, TextFormatting::default())],
+        level,
+    };
+    let document = create_document(vec![
+        paragraph(vec![
+            run("Area is ", TextFormatting::default()),
+            run("$\\pi r^{2}$", TextFormatting::default()),
+            run(" where ", TextFormatting::default()),
+            run("r", bold),
+            run(" is the *radius*.", TextFormatting::default()),
+        ]),
+        DocumentElement::List {
+            items: vec![item("Install", 0), item("Configure", 1), item("Run", 0)],
+            ordered: true,
+        },
+        DocumentElement::Equation {
+            latex: "a^{2}+b^{2}=c^{2}".to_string(),
+            fallback: String::new(),
+            number: None,
+        },
+        DocumentElement::Image {
+            description: "Diagram, first draft".to_string(),
+            width: Some(320),
+            height: Some(200),
+            relationship_id: None,
+            image_path: Some("images/diagram.png".into()),
+            caption: None,
+            placement: None,
+        },
+    ]);
+    let rst = render_rst(&document);
+
+    assert!(rst.contains("Area is :math:`\\pi r^{2}` where **r** is the \\*radius\\*.\n\n"));
+    assert!(rst.contains("#. Install\n\n   #. Configure\n\n#. Run\n\n"));
+    assert!(rst.contains(".. math::\n\n   a^{2}+b^{2}=c^{2}\n\n"));
+    assert!(rst.contains(
+        ".. image:: images/diagram.png\n   :alt: Diagram, first draft\n   :width: 320px\n   :height: 200px\n"
+    ));
+}
+
+#[test]
+fn test_rst_format_is_inferred_from_extension() {
+    assert!(matches!(
+        infer_export_format(Path::new("guide.rst")),
+        Some(ExportFormat::Rst)
+    ));
+}
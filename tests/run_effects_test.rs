@@ -0,0 +1,202 @@
+#![cfg(feature = "terminal")]
+
+use doxx::{
+    ansi::{export_to_ansi_with_options, AnsiOptions},
+    document::{
+        contrasting_text_color, load_document, DocumentElement, FormattedRun, ImageOptions,
+        TextFormatting, VerticalAlign,
+    },
+    ColorDepth,
+};
+use std::path::Path;
+
+fn paragraph_runs(element: &DocumentElement) -> &[FormattedRun] {
+    match element {
+        DocumentElement::Paragraph { runs, .. } => runs,
+        other => panic!("Expected a paragraph, got {other:?}"),
+    }
+}
+
+fn find_run<'a>(runs: &'a [FormattedRun], text: &str) -> &'a FormattedRun {
+    runs.iter()
+        .find(|run| run.text == text)
+        .unwrap_or_else(|| panic!("No run with text {text:?}"))
+}
+
+#[test]
+fn test_run_effects_are_parsed() {
+    let document = load_document(
+        Path::new("tests/fixtures/run-effects.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+
+    // The drop cap frame is joined to the paragraph it starts
+    let first = paragraph_runs(&document.elements[0]);
+    let first_text: String = first.iter().map(|run| run.text.as_str()).collect();
+    assert!(first_text.starts_with("Once upon a time"));
+
+    let science = paragraph_runs(&document.elements[1]);
+    assert_eq!(science.len(), 5);
+    assert_eq!(
+        science[1].formatting.vertical_align,
+        VerticalAlign::Superscript
+    );
+    assert_eq!(
+        science[3].formatting.vertical_align,
+        VerticalAlign::Subscript
+    );
+    assert_eq!(
+        science[4].formatting.vertical_align,
+        VerticalAlign::Baseline
+    );
+
+    let styled = paragraph_runs(&document.elements[2]);
+    assert!(find_run(styled, "Small caps heading").formatting.small_caps);
+    assert!(find_run(styled, "all caps").formatting.all_caps);
+    assert_eq!(
+        find_run(styled, "highlighted text")
+            .formatting
+            .highlight
+            .as_deref(),
+        Some("yellow")
+    );
+
+    // An explicit w:val="0" turns small caps off
+    let plain = paragraph_runs(&document.elements[3]);
+    assert!(!plain[0].formatting.small_caps);
+
+    // Paragraph and run shading
+    let DocumentElement::Paragraph { runs, shading, .. } = &document.elements[4] else {
+        panic!("Expected the shaded paragraph");
+    };
+    assert_eq!(shading.as_deref(), Some("#DDEEFF"));
+    assert_eq!(
+        find_run(runs, "shaded words")
+            .formatting
+            .background
+            .as_deref(),
+        Some("#FFCC00")
+    );
+    assert_eq!(runs[0].formatting.background, None);
+}
+
+#[test]
+fn test_background_colours() {
+    let highlighted = TextFormatting {
+        highlight: Some("darkBlue".to_string()),
+        background: Some("#FFCC00".to_string()),
+        ..Default::default()
+    };
+    // Highlight is painted over shading
+    assert_eq!(highlighted.background_hex(), Some("#000080"));
+    assert_eq!(contrasting_text_color("#000080"), "#FFFFFF");
+    assert_eq!(contrasting_text_color("#FFFF00"), "#000000");
+
+    let none = TextFormatting {
+        highlight: Some("none".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(none.background_hex(), None);
+}
+
+#[test]
+fn test_display_text_maps_scripts_and_caps() {
+    let superscript = TextFormatting {
+        vertical_align: VerticalAlign::Superscript,
+        ..Default::default()
+    };
+    assert_eq!(superscript.display_text("2nd"), "²ⁿᵈ");
+    // Characters without a superscript form fall back to the plain character
+    assert_eq!(superscript.display_text("q"), "q");
+
+    let subscript = TextFormatting {
+        vertical_align: VerticalAlign::Subscript,
+        ..Default::default()
+    };
+    assert_eq!(subscript.display_text("x1"), "ₓ₁");
+
+    let small_caps = TextFormatting {
+        small_caps: true,
+        ..Default::default()
+    };
+    assert_eq!(small_caps.display_text("Straße"), "STRAßE");
+
+    assert_eq!(TextFormatting::default().display_text("plain"), "plain");
+}
+
+#[test]
+fn test_ansi_export_renders_run_effects() {
+    let document = load_document(
+        Path::new("tests/fixtures/run-effects.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let options = AnsiOptions {
+        terminal_width: 120,
+        color_depth: ColorDepth::TrueColor,
+        ..Default::default()
+    };
+    let output = export_to_ansi_with_options(&document, &options).unwrap();
+
+    assert!(output.contains("E=mc²"));
+    assert!(output.contains("H₂O"));
+    assert!(output.contains("SMALL CAPS HEADING then ALL CAPS"));
+    // Yellow highlight as a background colour, with dark text on top
+    assert!(output.contains("\x1b[38;2;0;0;0m\x1b[48;2;255;255;0mhighlighted text"));
+
+    // Shaded paragraphs are padded to the full width in the shading colour
+    let shaded = output
+        .lines()
+        .find(|line| line.contains("Shaded note"))
+        .unwrap();
+    assert!(shaded.starts_with("\x1b[48;2;221;238;255m"));
+    assert_eq!(doxx::wrap::visible_width(shaded), 120);
+}
+
+#[test]
+fn test_monochrome_ansi_drops_backgrounds() {
+    let document = load_document(
+        Path::new("tests/fixtures/run-effects.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let options = AnsiOptions {
+        terminal_width: 120,
+        color_depth: ColorDepth::Monochrome,
+        ..Default::default()
+    };
+    let output = export_to_ansi_with_options(&document, &options).unwrap();
+
+    assert!(!output.contains("\x1b[48;"));
+    // Highlighted and shaded runs are underlined instead
+    assert!(output.contains("Shaded note with \x1b[4mshaded words\x1b[0m."));
+    assert!(output.contains("\x1b[4mhighlighted text"));
+}
+
+#[test]
+fn test_theme_colours_resolve_from_theme_part() {
+    let document = load_document(
+        Path::new("tests/fixtures/run-effects.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+
+    let themed = paragraph_runs(&document.elements[5]);
+    // accent1 darkened by themeShade="BF", matching Word's "Darker 25%"
+    assert_eq!(
+        find_run(themed, "Themed heading")
+            .formatting
+            .color
+            .as_deref(),
+        Some("2F5496")
+    );
+    // The theme wins over a stale literal w:val
+    assert_eq!(
+        find_run(themed, "stale literal")
+            .formatting
+            .color
+            .as_deref(),
+        Some("ED7D31")
+    );
+}
@@ -0,0 +1,157 @@
+use doxx::document::{
+    load_document,
+    scan::{resolve_rules, scan, scan_texts, Redactor, ScanReport, ScanRule},
+    searchable_text, CellDataType, DocumentElement, ImageOptions,
+};
+use doxx::Redaction;
+use std::path::Path;
+use std::process::Command;
+
+const PII: &str = "tests/fixtures/pii.docx";
+
+fn pii_report(rules: &[&str]) -> ScanReport {
+    let path = Path::new(PII);
+    let document = load_document(path, ImageOptions::default()).unwrap();
+    let names: Vec<String> = rules.iter().map(|rule| rule.to_string()).collect();
+    let rules = resolve_rules(&names, &[]).unwrap();
+    ScanReport {
+        source: "pii.docx".to_string(),
+        rules: rules.iter().map(|rule| rule.name.clone()).collect(),
+        findings: scan(&scan_texts(&document, Some(path)).unwrap(), &rules),
+    }
+}
+
+#[test]
+fn test_pii_is_found_in_the_body_tables_headers_footers_and_notes() {
+    let report = pii_report(&["pii"]);
+    let found: Vec<(String, &str, &str)> = report
+        .findings
+        .iter()
+        .map(|finding| {
+            (
+                finding.location(),
+                finding.rule.as_str(),
+                finding.matched.as_str(),
+            )
+        })
+        .collect();
+    // Not the date, the reference number, the SSN that's never issued or the
+    // card number failing its check digit
+    assert_eq!(
+        found,
+        [
+            ("#1".to_string(), "email", "hr.team@example.com"),
+            ("#1".to_string(), "phone", "(555) 010-4477"),
+            ("#3".to_string(), "ssn", "123-45-6789"),
+            ("#3".to_string(), "credit-card", "4111 1111 1111 1111"),
+            ("#4".to_string(), "phone", "+44 20 7946 0958"),
+            ("header1 ¶1".to_string(), "email", "payroll@example.com"),
+            ("footer1 ¶2".to_string(), "phone", "555-010-9000"),
+            ("footnotes ¶1".to_string(), "phone", "555.010.2222"),
+            ("footnotes ¶1".to_string(), "ssn", "078-05-1120"),
+        ]
+    );
+    let phone = &report.findings[1];
+    assert_eq!(phone.range, 46..60);
+    assert_eq!(
+        phone.context,
+        "…o hr.team@example.com or call (555) 010-4477 before the start date."
+    );
+}
+
+#[test]
+fn test_report_counts_findings_by_rule() {
+    let lines = pii_report(&["email", "ssn"]).lines();
+    assert_eq!(lines[0], "Scan of pii.docx (email, ssn)");
+    assert_eq!(lines[2], "#1 email: hr.team@example.com");
+    assert_eq!(
+        lines[3],
+        "    Send questions to hr.team@example.com or call (555) 010-4477 before…"
+    );
+    assert_eq!(lines.last().unwrap(), "4 findings: 2 email, 2 ssn");
+}
+
+#[test]
+fn test_rules_are_resolved_by_name() {
+    let custom = [ScanRule::custom("ticket", r"\bref \d{8}\b").unwrap()];
+    let names = ["ticket".to_string(), "pii".to_string(), "email".to_string()];
+    let rules = resolve_rules(&names, &custom).unwrap();
+    let names: Vec<&str> = rules.iter().map(|rule| rule.name.as_str()).collect();
+    assert_eq!(names, ["ticket", "credit-card", "ssn", "email", "phone"]);
+
+    let error = resolve_rules(&["passport".to_string()], &custom).unwrap_err();
+    assert!(error.to_string().starts_with("No scan rule named passport"));
+}
+
+#[test]
+fn test_scan_command_reports_json_and_exit_status() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["scan", PII, "--rules", "ssn", "--rule", "ticket=ref \\d{8}"])
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["rules"], serde_json::json!(["ssn", "ticket"]));
+    let ticket = &report["findings"][0];
+    assert_eq!(ticket["rule"], "ticket");
+    assert_eq!(ticket["match"], "ref 12345678");
+    assert_eq!(ticket["element_index"], 2);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["scan", "tests/fixtures/minimal.docx"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No findings"));
+}
+
+#[test]
+fn test_redaction_masks_the_body_and_tables() {
+    let mut document = load_document(Path::new(PII), ImageOptions::default()).unwrap();
+    let redactor = Redactor {
+        rules: resolve_rules(&["pii".to_string()], &[]).unwrap(),
+        style: Redaction::Block,
+    };
+    assert_eq!(redactor.redact_document(&mut document), 5);
+    let texts: Vec<String> = searchable_text(&document)
+        .into_iter()
+        .map(|searchable| searchable.text)
+        .collect();
+    assert_eq!(
+        texts[1],
+        "Send questions to ████ or call ████ before the start date."
+    );
+    let DocumentElement::Table { table } = &document.elements[3] else {
+        panic!("expected the table");
+    };
+    let cells: Vec<&str> = table.rows[0]
+        .iter()
+        .map(|cell| cell.content.as_str())
+        .collect();
+    assert_eq!(cells, ["Dana Whitfield", "████", "████"]);
+    assert_eq!(table.rows[0][2].data_type, CellDataType::Text);
+    // What isn't valid stays as it is
+    assert_eq!(table.rows[1][1].content, "987-65-4321");
+}
+
+#[test]
+fn test_redacted_export_leaves_nothing_behind() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([PII, "--redact=ssn,email", "--redact-with", "placeholder"])
+        .args(["--export", "markdown"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let markdown = String::from_utf8(output.stdout).unwrap();
+    assert!(markdown.contains("Send questions to [EMAIL] or call (555) 010-4477"));
+    assert!(markdown.contains("| Dana Whitfield | [SSN] | 4111 1111 1111 1111 |"));
+    assert!(!markdown.contains("123-45-6789"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([PII, "--redact=passport", "--export", "text"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No scan rule named passport"));
+}
@@ -1,4 +1,7 @@
-use doxx::document::{load_document, search_document, ImageOptions};
+use doxx::document::{
+    fuzzy_find_headings, fuzzy_match, generate_outline, load_document, navigation_targets,
+    search_document, ImageOptions, TargetKind,
+};
 use std::path::Path;
 
 async fn load_test_document() -> doxx::document::Document {
@@ -143,4 +146,70 @@ mod edge_case_tests {
             "Should find search terms across different element types"
         );
     }
+
+    #[tokio::test]
+    async fn test_fuzzy_heading_jump_ranks_word_starts() {
+        let document = load_test_document().await;
+
+        let matches = fuzzy_find_headings(&document, "exsum");
+        assert!(
+            !matches.is_empty(),
+            "Should fuzzy-match 'Executive Summary'"
+        );
+        assert_eq!(matches[0].item.title, "Executive Summary");
+        assert_eq!(matches[0].positions, vec![0, 1, 10, 11, 12]);
+
+        // Case-insensitive, and whitespace in the query is ignored
+        let matches = fuzzy_find_headings(&document, "KEY act");
+        assert_eq!(matches[0].item.title, "Key Action Items");
+
+        // Fuzzy lookup only covers headings, not body text
+        let matches = fuzzy_find_headings(&document, "zzzz");
+        assert!(matches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fuzzy_heading_jump_empty_query_lists_outline() {
+        let document = load_test_document().await;
+
+        let matches = fuzzy_find_headings(&document, "");
+        let outline = generate_outline(&document);
+        assert_eq!(matches.len(), outline.len());
+        for (heading, item) in matches.iter().zip(&outline) {
+            assert_eq!(heading.item.element_index, item.element_index);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_palette_targets_include_tables_in_order() {
+        let path = Path::new("tests/fixtures/tables-heavy.docx");
+        let document = load_document(path, ImageOptions::default()).unwrap();
+
+        let targets = navigation_targets(&document);
+        let listed: Vec<(TargetKind, &str)> = targets
+            .iter()
+            .take(5)
+            .map(|target| (target.kind, target.title.as_str()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (TargetKind::Heading, "Tables Heavy Test Document"),
+                (TargetKind::Heading, "Simple Table"),
+                (TargetKind::Table, "Table 1: Name | Age | City"),
+                (TargetKind::Heading, "Financial Data Table"),
+                (
+                    TargetKind::Table,
+                    "Table 2: Quarter | Revenue | Expenses | Profit | Margin %"
+                ),
+            ]
+        );
+        assert!(targets
+            .windows(2)
+            .all(|pair| pair[0].element_index < pair[1].element_index));
+
+        let (_, positions) = fuzzy_match("Table 2: Quarter", "t2q").unwrap();
+        assert_eq!(positions, vec![0, 6, 9]);
+        assert!(fuzzy_match("Table 2", "zz").is_none());
+    }
 }
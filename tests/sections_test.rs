@@ -0,0 +1,147 @@
+#![cfg(feature = "terminal")]
+
+use doxx::{
+    ansi::{export_to_ansi_with_options, AnsiOptions},
+    document::{load_document, Document, DocumentElement, ImageOptions, Orientation},
+    widgets::{DocumentWidget, LayoutCache},
+    ColorDepth,
+};
+use ratatui::{backend::TestBackend, Terminal};
+use std::path::Path;
+
+fn load_fixture() -> Document {
+    load_document(
+        Path::new("tests/fixtures/sections.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+/// ANSI export with the escape sequences stripped
+fn ansi_lines(document: &Document, width: usize) -> Vec<String> {
+    let options = AnsiOptions {
+        terminal_width: width,
+        color_depth: ColorDepth::Monochrome,
+        ..Default::default()
+    };
+    let output = export_to_ansi_with_options(document, &options).unwrap();
+    let escapes = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+    escapes
+        .replace_all(&output, "")
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_sections_are_parsed() {
+    let document = load_fixture();
+    let sections = &document.sections;
+    assert_eq!(sections.len(), 3);
+
+    // Each section ends with the paragraph carrying its properties
+    assert_eq!((sections[0].start, sections[0].end), (0, 3));
+    assert_eq!((sections[1].start, sections[1].end), (3, 7));
+    assert_eq!((sections[2].start, sections[2].end), (7, 8));
+    assert_eq!(sections[2].end, document.elements.len());
+    assert!(matches!(
+        &document.elements[3],
+        DocumentElement::Heading { text, .. } if text == "Regional Results"
+    ));
+
+    let landscape = &sections[1].page;
+    assert_eq!(landscape.orientation, Orientation::Landscape);
+    assert_eq!(
+        (landscape.width, landscape.height),
+        (Some(15840), Some(12240))
+    );
+    assert_eq!(landscape.columns, 2);
+    assert_eq!(landscape.column_gap, Some(720));
+    assert_eq!(landscape.margins.unwrap().left, 1080);
+    assert_eq!(sections[1].label(2), "Section 2: Landscape, 2 columns");
+
+    assert_eq!(sections[0].page.orientation, Orientation::Portrait);
+    assert_eq!(sections[2].label(3), "Section 3: Portrait");
+}
+
+#[test]
+fn test_single_section_documents_have_no_markers() {
+    let document = load_document(
+        Path::new("tests/fixtures/minimal.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    assert!(document.sections.len() <= 1);
+    assert!(!ansi_lines(&document, 80)
+        .iter()
+        .any(|line| line.contains("── Section")));
+}
+
+#[test]
+fn test_ansi_export_sets_columns_side_by_side() {
+    let lines = ansi_lines(&load_fixture(), 80);
+    let marker = lines
+        .iter()
+        .position(|line| line == "── Section 2: Landscape, 2 columns ──")
+        .unwrap();
+    assert!(lines.contains(&"── Section 3: Portrait ──".to_string()));
+
+    // The section's text runs down the left column and on into the right one
+    let heading = &lines[marker + 2];
+    assert!(heading.starts_with("  ▶ Regional Results"));
+    assert!(heading.ends_with("quarter, led by the east coast, where"));
+    // Two 38-column halves of the width, three spaces apart
+    let column = |line: &str, text: &str| line[..line.find(text).unwrap()].chars().count();
+    assert_eq!(column(heading, "quarter"), 41);
+    assert!(lines[marker + 5].starts_with("set in two newspaper columns"));
+    assert_eq!(column(&lines[marker + 5], "Returns"), 41);
+
+    // The portrait section after it is back to the full width
+    assert!(lines.contains(&"Back to a single portrait column for the closing notes.".to_string()));
+}
+
+#[test]
+fn test_ansi_export_linearizes_columns_when_narrow() {
+    let lines = ansi_lines(&load_fixture(), 40);
+    let marker = lines
+        .iter()
+        .position(|line| line == "── Section 2: Landscape, 2 columns ──")
+        .unwrap();
+    assert_eq!(lines[marker + 2], "  ▶ Regional Results");
+    assert_eq!(lines[marker + 4], "The second section is a landscape page");
+    assert!(lines.iter().all(|line| line.chars().count() <= 40));
+}
+
+#[test]
+fn test_viewer_marks_section_boundaries() {
+    let document = load_fixture();
+    let mut terminal = Terminal::new(TestBackend::new(60, 30)).unwrap();
+    let mut layout_cache = LayoutCache::new();
+    terminal
+        .draw(|frame| {
+            DocumentWidget::new(&document.elements)
+                .sections(&document.sections)
+                .render(frame.area(), frame, &mut [], &mut layout_cache);
+        })
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let rows: Vec<String> = (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+
+    let marker = rows
+        .iter()
+        .position(|row| row == "── Section 2: Landscape, 2 columns ──")
+        .unwrap();
+    assert!(rows[marker + 2].contains("Regional Results"));
+    assert!(rows.iter().any(|row| row == "── Section 3: Portrait ──"));
+    // The first section starts without a marker
+    assert!(!rows[..marker].iter().any(|row| row.contains("Section 1")));
+}
@@ -0,0 +1,55 @@
+use doxx::document::{load_document, DocumentElement, ImageOptions, TextAlignment};
+use std::path::Path;
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/signature-draft.docx";
+
+fn paragraph_text(element: &DocumentElement) -> String {
+    let DocumentElement::Paragraph { runs, .. } = element else {
+        panic!("expected a paragraph, got {element:?}");
+    };
+    runs.iter().map(|run| run.text.as_str()).collect()
+}
+
+#[test]
+fn test_watermark_is_noted_once_at_the_top() {
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let DocumentElement::Paragraph {
+        runs, alignment, ..
+    } = &document.elements[0]
+    else {
+        panic!("expected the watermark note");
+    };
+    assert_eq!(runs[0].text, "[Watermark: DRAFT]");
+    assert!(runs[0].formatting.italic);
+    assert_eq!(*alignment, TextAlignment::Center);
+    assert_eq!(document.sections.len(), 1);
+    assert_eq!(document.sections[0].end, document.elements.len());
+}
+
+#[test]
+fn test_signature_lines_are_noted_where_they_are_drawn() {
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let texts: Vec<String> = document.elements[3..].iter().map(paragraph_text).collect();
+    assert_eq!(
+        texts,
+        [
+            "[Signature line: Jordan Lee, CFO]",
+            "Signed for the supplier: [Signature line]"
+        ]
+    );
+    // The notes aren't counted as the document's words
+    assert_eq!(document.metadata.word_count, 17);
+}
+
+#[test]
+fn test_notes_survive_export() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args([FIXTURE, "--export", "markdown"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let markdown = String::from_utf8(output.stdout).unwrap();
+    assert!(markdown.contains("*[Watermark: DRAFT]*"));
+    assert!(markdown.contains("*[Signature line: Jordan Lee, CFO]*"));
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "test-support")]
+
+//! Every export of a few fixtures, compared with the golden files in
+//! tests/golden. After an intended change to an exporter, regenerate them
+//! with `DOXX_BLESS=1 cargo test --features test-support --test snapshot_test`
+//! and review the diff.
+
+use doxx::testing::{check_export_snapshot, snapshot_formats};
+use std::path::Path;
+
+const FIXTURES: [&str; 4] = [
+    "tests/fixtures/example.docx",
+    "tests/fixtures/business-report.docx",
+    "tests/fixtures/run-effects.docx",
+    "tests/fixtures/captions.docx",
+];
+
+#[test]
+fn test_exports_match_golden_files() {
+    // Every mismatch is reported, not just the first
+    let mut failures = Vec::new();
+    for fixture in FIXTURES {
+        for format in snapshot_formats() {
+            match check_export_snapshot(Path::new(fixture), &format, Path::new("tests/golden")) {
+                Ok(None) => {}
+                Ok(Some(diff)) => failures.push(diff),
+                Err(error) => failures.push(format!("{fixture}: {error:#}")),
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\nIf the changes are intended, rerun with DOXX_BLESS=1",
+        failures.join("\n")
+    );
+}
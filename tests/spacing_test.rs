@@ -0,0 +1,87 @@
+use doxx::{
+    document::{
+        load_document_with_fields, Document, DocumentElement, FieldOptions, ImageOptions,
+        ParagraphSpacing,
+    },
+    export::{export_document_to_string, ExportOptions},
+    ExportFormat, SpacingMode,
+};
+use std::path::Path;
+
+const FIXTURE: &str = "tests/fixtures/meeting-notes.docx";
+
+fn load(spacing: SpacingMode) -> Document {
+    load_document_with_fields(
+        Path::new(FIXTURE),
+        ImageOptions::default(),
+        FieldOptions {
+            spacing,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+/// The body of the text export, after the document information
+fn text_body(document: &Document) -> String {
+    let text = export_document_to_string(document, &ExportFormat::Text, &ExportOptions::default())
+        .unwrap();
+    let (_, body) = text.split_once("=\n\n").unwrap();
+    body.to_string()
+}
+
+#[test]
+fn test_compact_spacing_drops_empty_paragraphs() {
+    let document = load(SpacingMode::Compact);
+    assert_eq!(document.elements.len(), 6);
+    assert!(document
+        .elements
+        .iter()
+        .all(|element| matches!(element, DocumentElement::Paragraph { spacing: None, .. })));
+    assert!(text_body(&document).starts_with("Weekly sync, 14 March\n\nAttendees"));
+}
+
+#[test]
+fn test_preserved_spacing_comes_from_paragraphs_and_styles() {
+    let document = load(SpacingMode::Preserve);
+    let spacing: Vec<(String, ParagraphSpacing)> = document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Paragraph { runs, spacing, .. } => Some((
+                runs.iter().map(|run| run.text.as_str()).collect(),
+                (*spacing)?,
+            )),
+            _ => None,
+        })
+        .collect();
+    let lines: Vec<(&str, usize, usize)> = spacing
+        .iter()
+        .map(|(text, spacing)| (text.as_str(), spacing.before, spacing.after))
+        .collect();
+    // Normal sets no space after, overriding the document default; the agenda
+    // style adds a line on either side and the last paragraph two before
+    assert_eq!(
+        lines,
+        [
+            ("Weekly sync, 14 March", 0, 0),
+            ("Attendees: Ana, Ben, Chloe", 0, 0),
+            ("", 0, 0),
+            ("Agenda", 1, 1),
+            ("Budget review: spending is on track for the quarter.", 0, 0),
+            ("Hiring plan: two roles open, interviews next week.", 0, 0),
+            ("", 0, 0),
+            ("", 0, 0),
+            ("Next meeting on Friday.", 2, 0),
+        ]
+    );
+}
+
+#[test]
+fn test_preserved_spacing_in_text_export() {
+    let body = text_body(&load(SpacingMode::Preserve));
+    assert!(body.starts_with(
+        "Weekly sync, 14 March\nAttendees: Ana, Ben, Chloe\n\n\nAgenda\n\nBudget review"
+    ));
+    assert!(body.contains("interviews next week.\n\n\n\n\nNext meeting on Friday.\n"));
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "spellcheck")]
+
+use doxx::document::{
+    load_document,
+    spelling::{check_document, Dictionary, SpellChecker, SpellingReport},
+    Document, ImageOptions,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const DICTIONARIES: &str = "tests/fixtures/dictionaries";
+
+fn spelling_fixture() -> Document {
+    load_document(
+        Path::new("tests/fixtures/spelling.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+fn dictionary() -> Dictionary {
+    Dictionary::load(&Path::new(DICTIONARIES).join("en_US")).unwrap()
+}
+
+#[test]
+fn test_unknown_words_are_found_in_headings_paragraphs_and_cells() {
+    let misspellings = check_document(&spelling_fixture(), &dictionary());
+    let words: Vec<&str> = misspellings
+        .iter()
+        .map(|misspelling| misspelling.word.as_str())
+        .collect();
+    // Not the acronym, the number, the address or "don’t" with a curly apostrophe
+    assert_eq!(words, ["Sumary", "recieved", "Teh", "recieved", "aproval"]);
+
+    let received = &misspellings[1];
+    assert_eq!(
+        (received.element_index, received.start, received.end),
+        (1, 9, 17)
+    );
+    // Offsets run across runs: "… the **costs** we recieved late."
+    assert_eq!((misspellings[3].start, misspellings[3].end), (39, 47));
+    // The second cell of the first row below the header
+    let approval = &misspellings[4];
+    assert_eq!((approval.element_index, approval.item), (4, 3));
+    assert_eq!(approval.start, 8);
+}
+
+#[test]
+fn test_dictionaries_are_found_by_language() {
+    let checker = SpellChecker::new(BTreeMap::new(), vec![PathBuf::from(DICTIONARIES)]);
+    let found = Path::new(DICTIONARIES).join("en_US");
+    assert_eq!(checker.find("en-US"), Some(found.clone()));
+    assert_eq!(checker.find("EN_us"), Some(found.clone()));
+
+    // A dictionary chosen for a language covers its regions
+    let chosen = BTreeMap::from([("de".to_string(), PathBuf::from("/opt/de_DE_frami"))]);
+    let checker = SpellChecker::new(chosen, vec![PathBuf::from(DICTIONARIES)]);
+    assert_eq!(
+        checker.find("de-AT"),
+        Some(PathBuf::from("/opt/de_DE_frami"))
+    );
+    assert_eq!(checker.find("en-US"), Some(found));
+}
+
+#[test]
+fn test_report_lists_each_word_once_with_where_it_is() {
+    let document = spelling_fixture();
+    let report = SpellingReport {
+        source: "spelling.docx".to_string(),
+        language: "en-US".to_string(),
+        dictionary: Path::new(DICTIONARIES).join("en_US"),
+        misspellings: check_document(&document, &dictionary()),
+    };
+    let lines = report.lines(&document);
+    assert_eq!(
+        lines[0],
+        "Spelling in spelling.docx (en-US, tests/fixtures/dictionaries/en_US.dic)"
+    );
+    assert_eq!(lines[3], "  recieved    2  #1, #3");
+    assert_eq!(lines[5], "  aproval     1  #4 cell 4");
+    assert_eq!(lines.last().unwrap(), "5 unknown words, 4 different");
+}
+
+#[test]
+fn test_lint_exits_with_1_when_words_are_unknown() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["lint", "--spelling", "--format", "json"])
+        .arg("tests/fixtures/spelling.docx")
+        .env("DICPATH", DICTIONARIES)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["language"], "en-US");
+    assert_eq!(report["misspellings"][0]["word"], "Sumary");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["lint", "tests/fixtures/spelling.docx"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--spelling"));
+}
+
+#[cfg(feature = "terminal")]
+#[test]
+fn test_viewer_underlines_unknown_words() {
+    use doxx::widgets::{DocumentWidget, LayoutCache};
+    use ratatui::{backend::TestBackend, style::Modifier, Terminal};
+
+    let document = spelling_fixture();
+    let misspellings = check_document(&document, &dictionary());
+    let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+    let mut layout_cache = LayoutCache::new();
+    terminal
+        .draw(|frame| {
+            DocumentWidget::new(&document.elements)
+                .scroll_offset(1)
+                .misspellings(&misspellings)
+                .render(frame.area(), frame, &mut [], &mut layout_cache);
+        })
+        .unwrap();
+
+    // "The team recieved the new figures on" fills the first row
+    let buffer = terminal.backend().buffer();
+    let underlined: String = (0..40)
+        .filter(|&x| {
+            buffer[(x, 0)]
+                .style()
+                .add_modifier
+                .contains(Modifier::UNDERLINED)
+        })
+        .map(|x| buffer[(x, 0)].symbol())
+        .collect();
+    assert_eq!(underlined, "recieved");
+}
@@ -14,6 +14,7 @@ mod strikethrough_tests {
             strikethrough: true,
             font_size: None,
             color: None,
+            ..Default::default()
         };
 
         assert!(formatting.strikethrough);
@@ -39,6 +40,7 @@ mod strikethrough_tests {
             strikethrough: true,
             font_size: None,
             color: None,
+            ..Default::default()
         };
 
         let run = FormattedRun {
@@ -60,6 +62,7 @@ mod strikethrough_tests {
             strikethrough: true,
             font_size: Some(12.0),
             color: Some("#FF0000".to_string()),
+            ..Default::default()
         };
 
         assert!(formatting.bold);
@@ -80,6 +83,7 @@ mod strikethrough_tests {
             strikethrough: true,
             font_size: Some(14.0),
             color: Some("#0000FF".to_string()),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&formatting).expect("Failed to serialize");
@@ -104,6 +108,7 @@ mod strikethrough_tests {
             strikethrough: true,
             font_size: None,
             color: None,
+            ..Default::default()
         };
 
         let formatting2 = formatting1.clone();
@@ -135,6 +140,7 @@ mod strikethrough_tests {
             strikethrough: true,
             font_size: None,
             color: None,
+            ..Default::default()
         };
 
         let formatting2 = TextFormatting {
@@ -144,6 +150,7 @@ mod strikethrough_tests {
             strikethrough: false, // Different strikethrough setting
             font_size: None,
             color: None,
+            ..Default::default()
         };
 
         let runs = vec![
@@ -0,0 +1,25 @@
+use doxx::document::style_report;
+use std::path::Path;
+
+#[test]
+fn test_style_report_counts_heading_styles_and_flags_bold_paragraphs() {
+    let report = style_report(Path::new("tests/fixtures/headings-hierarchy.docx")).unwrap();
+    let heading1 = report
+        .used
+        .iter()
+        .find(|style| style.id == "Heading1")
+        .unwrap();
+    assert_eq!(heading1.uses, 2);
+    assert!(report.direct_headings.is_empty());
+
+    // Its headings are bold body paragraphs, so each is flagged, but not its table cells
+    let report = style_report(Path::new("tests/fixtures/business-report.docx")).unwrap();
+    let flagged: Vec<&str> = report
+        .direct_headings
+        .iter()
+        .map(|heading| heading.text.as_str())
+        .collect();
+    assert!(flagged.contains(&"Executive Summary"));
+    assert!(!flagged.contains(&"Metric"));
+    assert!(report.lines()[0].starts_with("Styles in "));
+}
@@ -0,0 +1,65 @@
+use doxx::{
+    document::{load_document, Document, DocumentElement, ImageOptions},
+    export::format_as_text,
+};
+use std::path::Path;
+
+fn symbol_fonts() -> Document {
+    load_document(
+        Path::new("tests/fixtures/symbol-fonts.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+fn paragraphs(document: &Document) -> Vec<String> {
+    document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Paragraph { runs, .. } => {
+                Some(runs.iter().map(|run| run.text.as_str()).collect())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_runs_in_symbol_fonts_show_what_the_font_draws() {
+    assert_eq!(
+        paragraphs(&symbol_fonts()),
+        [
+            "Budget ☺ on track, schedule ☹ behind.",
+            "Next step 🡺 sign-off.",
+            "The area is πr² where α ≤ β.",
+            "•\tManual bullet in the private use area",
+        ]
+    );
+}
+
+#[test]
+fn test_table_cells_map_symbol_fonts() {
+    let document = symbol_fonts();
+    let table = document
+        .elements
+        .iter()
+        .find_map(|element| match element {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        })
+        .unwrap();
+    let done: Vec<&str> = table
+        .rows
+        .iter()
+        .map(|row| row[1].content.as_str())
+        .collect();
+    assert_eq!(done, ["☑", "☐"]);
+}
+
+#[test]
+fn test_plain_text_export_has_no_stray_letters() {
+    let text = format_as_text(&symbol_fonts());
+    assert!(text.contains("Budget ☺ on track"));
+    assert!(!text.contains('\u{F0B7}'));
+}
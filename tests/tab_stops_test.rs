@@ -0,0 +1,112 @@
+#![cfg(feature = "terminal")]
+
+use doxx::{
+    document::{
+        load_document, Document, DocumentElement, ImageOptions, TabAlignment, TabLeader, TabStop,
+    },
+    widgets::{DocumentWidget, LayoutCache},
+};
+use ratatui::{backend::TestBackend, Terminal};
+use std::path::Path;
+use std::process::Command;
+use unicode_width::UnicodeWidthStr;
+
+fn tab_stops() -> Document {
+    load_document(
+        Path::new("tests/fixtures/tab-stops.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+/// The rows the viewer draws, without trailing spaces
+fn viewer_rows(document: &Document, width: u16) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, 40)).unwrap();
+    let mut layout_cache = LayoutCache::new();
+    terminal
+        .draw(|frame| {
+            DocumentWidget::new(&document.elements).render(
+                frame.area(),
+                frame,
+                &mut [],
+                &mut layout_cache,
+            );
+        })
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| {
+            let row: String = (0..width).map(|x| buffer[(x, y)].symbol()).collect();
+            row.trim_end().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_tab_stops_come_from_styles_and_paragraphs() {
+    let document = tab_stops();
+    let stops: Vec<&[TabStop]> = document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Paragraph { tab_stops, .. } => Some(tab_stops.as_slice()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(stops.len(), 8);
+    assert_eq!(
+        stops[0],
+        [TabStop {
+            position: 9350,
+            alignment: TabAlignment::Right,
+            leader: TabLeader::Dot,
+        }]
+    );
+    assert_eq!(stops[3].len(), 2);
+    assert_eq!(stops[5][0].alignment, TabAlignment::Decimal);
+}
+
+#[test]
+fn test_viewer_sets_page_numbers_against_the_right_stop() {
+    let rows = viewer_rows(&tab_stops(), 100);
+    let entry = |title: &str| rows.iter().find(|row| row.starts_with(title)).unwrap();
+    // 9350 twips is column 65
+    assert_eq!(
+        entry("Introduction"),
+        "Introduction .................................................. 1"
+    );
+    assert_eq!(entry("Results and Discussion").width(), 65);
+    assert!(entry("Results and Discussion").ends_with(" 12"));
+    assert_eq!(
+        entry("Name:"),
+        "Name:     ________________________________________"
+    );
+
+    // A stop past the edge of a narrow window is pulled back to it
+    let rows = viewer_rows(&tab_stops(), 40);
+    let methods = rows.iter().find(|row| row.starts_with("Methods")).unwrap();
+    assert_eq!(methods.width(), 40);
+    assert!(methods.ends_with(". 4"));
+}
+
+#[test]
+fn test_text_export_lines_up_decimal_points() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["tests/fixtures/tab-stops.docx", "--export", "text"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    let points: Vec<usize> = ["Tea", "Coffee"]
+        .iter()
+        .map(|item| {
+            let line = text.lines().find(|line| line.starts_with(item)).unwrap();
+            line.rfind('.').unwrap()
+        })
+        .collect();
+    // 4320 twips is column 30, where the whole numbers end
+    assert_eq!(points, [30, 30]);
+    assert!(text.contains("Cake ....................... 4\n"));
+    // Contents entries aren't mistaken for headings now that their tabs are kept
+    assert!(text.contains("\nIntroduction ...."));
+}
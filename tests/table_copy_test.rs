@@ -0,0 +1,67 @@
+use doxx::{
+    document::{load_document, DocumentElement, ImageOptions, TableData},
+    export::format_table,
+    TableCopyFormat,
+};
+use std::path::Path;
+
+fn first_table() -> TableData {
+    let document = load_document(
+        Path::new("tests/fixtures/business-report.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+    document
+        .elements
+        .into_iter()
+        .find_map(|element| match element {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        })
+        .expect("business-report.docx has a table")
+}
+
+#[test]
+fn test_table_copies_as_tsv() {
+    let tsv = format_table(&first_table(), TableCopyFormat::Tsv);
+    let lines: Vec<&str> = tsv.lines().collect();
+
+    assert_eq!(lines[0], "Metric\tQ4 2024\tQ3 2024\tChange");
+    // Commas stay inside their cell, unquoted
+    assert_eq!(lines[1], "Revenue\t$2,100,000\t$1,750,000\t+20%");
+    assert!(tsv.ends_with('\n'));
+}
+
+#[test]
+fn test_table_copies_as_markdown_and_csv() {
+    let table = first_table();
+
+    let markdown = format_table(&table, TableCopyFormat::Markdown);
+    assert!(markdown.starts_with("| Metric | Q4 2024 | Q3 2024 | Change |\n"));
+    assert!(markdown.contains("| Revenue | $2,100,000 | $1,750,000 | +20% |"));
+
+    let csv = format_table(&table, TableCopyFormat::Csv);
+    assert!(csv.contains("Revenue,\"$2,100,000\",\"$1,750,000\",+20%\n"));
+}
+
+#[test]
+fn test_tsv_cells_cannot_shift_columns() {
+    let mut table = first_table();
+    table.rows[0][0].content = "Multi\tline\nlabel".to_string();
+
+    let tsv = format_table(&table, TableCopyFormat::Tsv);
+    let row = tsv.lines().nth(1).unwrap();
+    assert_eq!(row.split('\t').count(), table.headers.len());
+    assert!(row.starts_with("Multi line label\t"));
+}
+
+#[test]
+fn test_table_copy_format_cycles() {
+    let mut format = TableCopyFormat::default();
+    assert_eq!(format, TableCopyFormat::Tsv);
+    format = format.next();
+    assert_eq!(format, TableCopyFormat::Markdown);
+    format = format.next();
+    assert_eq!(format, TableCopyFormat::Csv);
+    assert_eq!(format.next(), TableCopyFormat::Tsv);
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "terminal")]
+
+use doxx::{
+    document::{load_document, Document, DocumentElement, ImageOptions},
+    widgets::{DocumentWidget, LayoutCache},
+};
+use ratatui::{backend::TestBackend, Terminal};
+use std::path::Path;
+
+fn table_styles() -> Document {
+    load_document(
+        Path::new("tests/fixtures/table-styles.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+/// Index of the table whose header starts with `first`
+fn table_index(document: &Document, first: &str) -> usize {
+    document
+        .elements
+        .iter()
+        .position(|element| {
+            matches!(element, DocumentElement::Table { table } if table.headers[0].content == first)
+        })
+        .unwrap()
+}
+
+/// The rows drawn with the view scrolled `line_offset` lines into element `start`
+fn viewer_rows(document: &Document, start: usize, line_offset: usize) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(40, 6)).unwrap();
+    let mut layout_cache = LayoutCache::new();
+    terminal
+        .draw(|frame| {
+            DocumentWidget::new(&document.elements)
+                .scroll_offset(start)
+                .scroll_line_offset(line_offset)
+                .render(frame.area(), frame, &mut [], &mut layout_cache);
+        })
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_header_row_repeats_while_scrolling_through_a_table() {
+    let document = table_styles();
+    let grid = table_index(&document, "Region");
+
+    // Five lines in, the header sits over the rows scrolled under it
+    let rows = viewer_rows(&document, grid, 5);
+    assert!(rows[0].starts_with("┌─"));
+    assert!(rows[1].starts_with("│Region"));
+    assert!(rows[2].starts_with("├─"));
+    assert!(rows[3].starts_with("│West"));
+    assert!(rows[4].starts_with("└─"));
+    assert_eq!(rows[5], "");
+
+    // Scrolled to the bottom line, with no rows left, it isn't repeated
+    let rows = viewer_rows(&document, grid, 10);
+    assert!(rows[0].starts_with("└─"));
+    assert!(!rows.iter().any(|row| row.contains("Region")));
+
+    // A header that would cover the last rows isn't repeated either
+    let plain = table_index(&document, "Name");
+    let rows = viewer_rows(&document, plain, 2);
+    assert!(rows[0].starts_with("Ada"));
+    assert!(rows[1].starts_with("Grace"));
+}
+
+#[test]
+fn test_row_in_view_counts_rows_below_the_repeated_header() {
+    let document = table_styles();
+    let grid = table_index(&document, "Region");
+    let widget = DocumentWidget::new(&document.elements);
+    assert_eq!(widget.table_row_in_view(grid, 0), Some((1, 4)));
+    assert_eq!(widget.table_row_in_view(grid, 3), Some((3, 4)));
+    assert_eq!(widget.table_row_in_view(grid, 5), Some((4, 4)));
+    assert_eq!(widget.table_row_in_view(grid, 10), None);
+    assert_eq!(widget.table_row_in_view(grid - 1, 0), None);
+}
@@ -0,0 +1,98 @@
+use doxx::document::{
+    load_document, table_rows, table_with_rows, Document, FilterOp, ImageOptions, TableData,
+    TableFilter,
+};
+use std::path::Path;
+
+fn tables(path: &str) -> (Document, Vec<TableData>) {
+    let document = load_document(Path::new(path), ImageOptions::default()).unwrap();
+    let tables = document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            doxx::DocumentElement::Table { table } => Some(table.clone()),
+            _ => None,
+        })
+        .collect();
+    (document, tables)
+}
+
+fn column(table: &TableData, rows: &[usize], column: usize) -> Vec<String> {
+    rows.iter()
+        .map(|&row| table.rows[row][column].content.clone())
+        .collect()
+}
+
+#[test]
+fn test_sort_by_currency_and_text() {
+    let (document, tables) = tables("tests/fixtures/tables-heavy.docx");
+    let locale = document.cell_locale();
+    let financial = &tables[1];
+
+    let rows = table_rows(financial, Some((3, true)), None, locale);
+    assert_eq!(
+        column(financial, &rows, 3),
+        vec!["$75,000", "$60,000", "$45,000", "$30,000"]
+    );
+
+    let people = &tables[0];
+    let rows = table_rows(people, Some((2, false)), None, locale);
+    assert_eq!(
+        column(people, &rows, 2),
+        vec!["Chicago", "Los Angeles", "New York"]
+    );
+}
+
+#[test]
+fn test_filter_compares_by_cell_type() {
+    let (document, tables) = tables("tests/fixtures/tables-heavy.docx");
+    let locale = document.cell_locale();
+    let financial = &tables[1];
+
+    let filter = TableFilter::parse("revenue >= 175000", financial).unwrap();
+    assert_eq!(filter.op, FilterOp::GreaterOrEqual);
+    let rows = table_rows(financial, None, Some(&filter), locale);
+    assert_eq!(
+        column(financial, &rows, 0),
+        vec!["Q2 2024", "Q3 2024", "Q4 2024"]
+    );
+
+    // Percentages compare by value, and columns can be given by number
+    let filter = TableFilter::parse("5 < 30", financial).unwrap();
+    let rows = table_rows(financial, None, Some(&filter), locale);
+    assert_eq!(column(financial, &rows, 4), vec!["20%", "25.7%"]);
+
+    let filter = TableFilter::parse("Quarter CONTAINS q4", financial).unwrap();
+    let rows = table_rows(financial, None, Some(&filter), locale);
+    let view = table_with_rows(financial, &rows);
+    assert_eq!(view.rows.len(), 1);
+    assert_eq!(view.metadata.row_count, 1);
+    assert_eq!(view.rows[0][0].content, "Q4 2024");
+}
+
+#[test]
+fn test_filter_errors() {
+    let (_, tables) = tables("tests/fixtures/tables-heavy.docx");
+    let financial = &tables[1];
+
+    let error = TableFilter::parse("Nope = 3", financial).unwrap_err();
+    assert_eq!(error.to_string(), "No column named 'Nope'");
+    assert!(TableFilter::parse("Revenue", financial).is_err());
+    assert!(TableFilter::parse("Revenue >=", financial).is_err());
+}
+
+#[test]
+fn test_decimal_comma_amounts_and_dates() {
+    let (document, tables) = tables("tests/fixtures/german-report.docx");
+    let locale = document.cell_locale();
+    let regions = tables.last().unwrap();
+
+    // "1.234,50 €" is over a thousand, and "876,25 €" under
+    let rows = table_rows(regions, Some((1, false)), None, locale);
+    assert_eq!(column(regions, &rows, 0), vec!["West", "Nord", "Süd"]);
+
+    // Day-first dates across a month boundary
+    let filter = TableFilter::parse("Geprüft >= 01.10.2024", regions).unwrap();
+    let rows = table_rows(regions, Some((3, true)), Some(&filter), locale);
+    assert_eq!(column(regions, &rows, 0), vec!["West", "Süd"]);
+}
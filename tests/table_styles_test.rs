@@ -0,0 +1,163 @@
+#![cfg(feature = "terminal")]
+
+use doxx::{
+    document::{
+        load_document, BorderLine, Document, DocumentElement, ImageOptions, TableBorders, TableData,
+    },
+    widgets::{DocumentWidget, LayoutCache},
+};
+use ratatui::{backend::TestBackend, buffer::Buffer, style::Color, Terminal};
+use std::path::Path;
+
+fn table_styles() -> Document {
+    load_document(
+        Path::new("tests/fixtures/table-styles.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+fn tables(document: &Document) -> Vec<&TableData> {
+    document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Table { table } => Some(table),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Draw the document in the viewer
+fn draw(document: &Document, color: bool) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(60, 50)).unwrap();
+    let mut layout_cache = LayoutCache::new();
+    terminal
+        .draw(|frame| {
+            DocumentWidget::new(&document.elements)
+                .color_enabled(color)
+                .render(frame.area(), frame, &mut [], &mut layout_cache);
+        })
+        .unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// The rows drawn, without trailing spaces
+fn rows(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.area.height)
+        .map(|y| {
+            let row: String = (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect();
+            row.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// The rows from the one starting with `first` to the blank line after it
+fn block(rows: &[String], first: &str) -> Vec<String> {
+    rows.iter()
+        .skip_while(|row| !row.starts_with(first))
+        .take_while(|row| !row.is_empty())
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn test_table_styles_and_borders_are_read() {
+    let document = table_styles();
+    let styles: Vec<_> = tables(&document)
+        .iter()
+        .map(|table| table.metadata.style.clone())
+        .collect();
+    assert_eq!(styles.len(), 4);
+
+    // Grid Table 4: lines everywhere, an accent header and tinted bands
+    assert_eq!(
+        styles[0].borders,
+        TableBorders {
+            top: true,
+            bottom: true,
+            left: true,
+            right: true,
+            inside_horizontal: true,
+            inside_vertical: true,
+            header: true,
+        }
+    );
+    assert_eq!(styles[0].header_fill.as_deref(), Some("#4472C4"));
+    assert_eq!(styles[0].band_fill.as_deref(), Some("#D9E2F3"));
+
+    // Plain Table 3: only a rule under the header
+    assert_eq!(
+        styles[1].borders,
+        TableBorders {
+            header: true,
+            ..TableBorders::NONE
+        }
+    );
+
+    // A double box of the table's own
+    assert_eq!(styles[2].line, BorderLine::Double);
+    assert!(styles[2].borders.top && !styles[2].borders.inside_vertical);
+
+    // The table's nil borders win over its style's, and its look turns banding off
+    assert_eq!(
+        styles[3].borders,
+        TableBorders {
+            header: true,
+            ..TableBorders::NONE
+        }
+    );
+    assert!(styles[3].header_fill.is_some());
+    assert_eq!(styles[3].band_fill, None);
+}
+
+#[test]
+fn test_viewer_draws_each_table_in_its_style() {
+    let rows = rows(&draw(&table_styles(), false));
+
+    let grid = block(&rows, "┌");
+    assert_eq!(grid.len(), 11);
+    assert!(grid[0].starts_with("┌─") && grid[0].ends_with("┐"));
+    assert!(grid[1].starts_with("│Region") && grid[1].contains("│Q1"));
+    assert!(grid[2].starts_with("├─") && grid[2].contains('┼'));
+    assert!(grid[10].starts_with("└─") && grid[10].contains('┴'));
+
+    let plain = block(&rows, "Name");
+    assert_eq!(plain.len(), 4);
+    assert!(!plain[0].contains('│'));
+    assert!(plain[1].chars().all(|c| c == '─'));
+
+    let boxed = block(&rows, "╔");
+    assert_eq!(boxed.len(), 5);
+    assert!(boxed[0].chars().skip(1).all(|c| c == '═' || c == '╗'));
+    assert!(boxed[1].starts_with("║Item") && boxed[1].ends_with('║'));
+    assert!(boxed[4].starts_with('╚'));
+}
+
+#[test]
+fn test_viewer_shades_header_and_banded_rows_with_color() {
+    let document = table_styles();
+    let buffer = draw(&document, true);
+    let rows = rows(&buffer);
+    let y = |first: &str| rows.iter().position(|row| row.starts_with(first)).unwrap() as u16;
+    let background = |x: u16, y: u16| buffer[(x, y)].bg;
+
+    // The header on the accent fill, in white
+    let header = y("│Region");
+    assert_eq!(background(1, header), Color::Rgb(0x44, 0x72, 0xC4));
+    assert_eq!(buffer[(1, header)].fg, Color::Rgb(0xFF, 0xFF, 0xFF));
+    // First and third body rows banded, the second not
+    let band = Color::Rgb(0xD9, 0xE2, 0xF3);
+    assert_eq!(background(1, y("│North")), band);
+    assert_eq!(background(1, y("│South")), Color::Reset);
+    assert_eq!(background(1, y("│East")), band);
+
+    // Banding turned off in the table's look
+    assert_eq!(background(0, y("Draft")), Color::Reset);
+
+    // Without colour, nothing is shaded
+    let plain = draw(&document, false);
+    assert_eq!(plain[(1, header)].bg, Color::Reset);
+}
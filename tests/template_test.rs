@@ -0,0 +1,81 @@
+use doxx::{
+    document::{load_document, Document, ImageOptions},
+    export::{template::render_template, ExportOptions},
+};
+use std::path::Path;
+use std::process::Command;
+
+fn load(name: &str) -> Document {
+    load_document(
+        Path::new(&format!("tests/fixtures/{name}")),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_html_template_sees_outline_and_tables() {
+    let html = render_template(
+        &load("business-report.docx"),
+        Path::new("tests/fixtures/templates/dashboard.html.j2"),
+        &ExportOptions::default(),
+    )
+    .unwrap();
+    // Pulled in with {% include %} from beside the template
+    assert!(html.contains("<p class=\"stats\">221 words, 1 pages, 16 headings</p>"));
+    assert!(html.contains(
+        "<a class=\"depth-1\" href=\"#key-performance-indicators\">Key Performance Indicators</a>"
+    ));
+    assert!(html
+        .contains("<tr><td>Revenue</td><td>$2,100,000</td><td>$1,750,000</td><td>+20%</td></tr>"));
+}
+
+#[test]
+fn test_html_templates_are_escaped_and_others_are_not() {
+    let document = load("cross-references.docx");
+    let html = render_template(
+        &document,
+        Path::new("tests/fixtures/templates/dashboard.html.j2"),
+        &ExportOptions::default(),
+    )
+    .unwrap();
+    assert!(html.contains(">2 What&#x27;s New &amp; FAQ</a>"));
+
+    let wiki = render_template(
+        &document,
+        Path::new("tests/fixtures/templates/wiki.txt"),
+        &ExportOptions::default(),
+    )
+    .unwrap();
+    assert!(wiki.contains("====== What's New & FAQ ======"));
+    assert!(wiki.contains("See Details for the figures and What's New & FAQ for the changes."));
+}
+
+#[test]
+fn test_template_errors_name_the_template() {
+    std::fs::create_dir_all(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("unclosed.md");
+    std::fs::write(&path, "{% for element in elements %}").unwrap();
+    let error = render_template(
+        &load("cross-references.docx"),
+        &path,
+        &ExportOptions::default(),
+    )
+    .unwrap_err();
+    assert!(format!("{error:#}").contains("unclosed.md"));
+}
+
+#[test]
+fn test_template_option_writes_the_output_file() {
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join("report.wiki");
+    let status = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["tests/fixtures/cross-references.docx", "--template"])
+        .arg("tests/fixtures/templates/wiki.txt")
+        .arg("--output")
+        .arg(&output)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let wiki = std::fs::read_to_string(&output).unwrap();
+    assert!(wiki.starts_with("\n====== Introduction ======"));
+}
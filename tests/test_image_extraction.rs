@@ -36,6 +36,7 @@ fn test_image_extraction_from_images_docx() {
 }
 
 #[test]
+#[cfg(feature = "terminal")]
 fn test_terminal_image_capabilities() {
     use doxx::terminal_image::{TerminalImageRenderer, TerminalImageSupport};
 
@@ -0,0 +1,36 @@
+use doxx::document::{load_document, DocumentElement, ImageOptions};
+use std::path::Path;
+
+#[test]
+fn test_one_run_per_character_is_consolidated() {
+    let document = load_document(
+        Path::new("tests/fixtures/tiny-runs.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap();
+
+    assert!(document.elements.iter().any(|element| matches!(
+        element,
+        DocumentElement::Heading { text, .. } if text == "Tiny Runs Stress Test"
+    )));
+
+    let paragraphs: Vec<_> = document
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            DocumentElement::Paragraph { runs, .. } => Some(runs),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(paragraphs.len(), 250);
+
+    for runs in paragraphs {
+        // 70 words with every tenth bold: one run per formatting change, not per character
+        assert_eq!(runs.len(), 15, "{runs:?}");
+        let text: String = runs.iter().map(|run| run.text.as_str()).collect();
+        assert_eq!(text.split_whitespace().count(), 70);
+        for run in runs.iter().filter(|run| run.formatting.bold) {
+            assert!(!run.text.contains(' '), "{:?}", run.text);
+        }
+    }
+}
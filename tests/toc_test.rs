@@ -0,0 +1,76 @@
+use doxx::{
+    document::{load_document, Document, ImageOptions},
+    export::{
+        export_document_to_string, html::render_html_with_options, render_markdown,
+        table_of_contents, ExportOptions,
+    },
+    ExportFormat,
+};
+use std::path::Path;
+
+fn report() -> Document {
+    load_document(
+        Path::new("tests/fixtures/business-report.docx"),
+        ImageOptions::default(),
+    )
+    .unwrap()
+}
+
+fn toc() -> ExportOptions {
+    ExportOptions {
+        toc: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_contents_nest_under_earlier_higher_headings() {
+    let entries = table_of_contents(&report(), true);
+    let depths: Vec<(&str, usize)> = entries
+        .iter()
+        .take(4)
+        .map(|entry| (entry.title.as_str(), entry.depth))
+        .collect();
+    // The report opens with a level 3 heading, which nothing is listed above
+    assert_eq!(
+        depths,
+        [
+            ("Q4 2024 Business Performance Report", 0),
+            ("Executive Summary", 0),
+            ("Key Performance Indicators", 1),
+            ("Financial Performance", 1),
+        ]
+    );
+}
+
+#[test]
+fn test_markdown_and_html_link_headings_by_the_same_anchors() {
+    let document = report();
+    let markdown = render_markdown(&document, &toc());
+    let html = render_html_with_options(&document, &toc());
+
+    let contents = markdown.split_once("## Contents\n\n").unwrap().1;
+    assert!(contents.starts_with(
+        "- [Q4 2024 Business Performance Report](#q4-2024-business-performance-report)\n\
+         - [Executive Summary](#executive-summary)\n  \
+         - [Key Performance Indicators](#key-performance-indicators)\n"
+    ));
+    for entry in table_of_contents(&document, true) {
+        assert!(html.contains(&format!("<a href=\"#{}\">", entry.anchor)));
+        assert!(html.contains(&format!(" id=\"{}\">", entry.anchor)));
+    }
+    assert_eq!(html.matches("<ul>").count(), html.matches("</ul>").count());
+
+    // Without --toc, HTML headings carry no ids
+    let plain = render_html_with_options(&document, &ExportOptions::default());
+    assert!(!plain.contains(" id=\"") && !plain.contains("<nav"));
+}
+
+#[test]
+fn test_text_export_lists_contents_after_document_information() {
+    let text = export_document_to_string(&report(), &ExportFormat::Text, &toc()).unwrap();
+    let (_, after_information) = text.split_once("=\n\nContents:\n").unwrap();
+    assert!(after_information.starts_with(
+        "- Q4 2024 Business Performance Report\n- Executive Summary\n  - Key Performance Indicators\n"
+    ));
+}
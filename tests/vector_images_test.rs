@@ -0,0 +1,67 @@
+use doxx::{
+    document::{load_document, ImageOptions},
+    DocumentElement,
+};
+use std::path::Path;
+
+/// Each image's description and the file name of its extracted path
+fn images() -> Vec<(String, Option<String>)> {
+    let document = load_document(
+        Path::new("tests/fixtures/vector-images.docx"),
+        ImageOptions {
+            enabled: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    document
+        .elements
+        .into_iter()
+        .filter_map(|element| match element {
+            DocumentElement::Image {
+                description,
+                image_path,
+                ..
+            } => Some((
+                description,
+                image_path.map(|path| path.file_name().unwrap().to_string_lossy().into_owned()),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_pictures_follow_their_relationships() {
+    let images = images();
+    assert_eq!(images.len(), 5);
+    // The PNG saved alongside an SVG is still the PNG the picture points at,
+    // even though the SVG sorts between it and the next picture
+    assert_eq!(images[1].1.as_deref(), Some("image2.png"));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn test_svg_images_are_rasterized() {
+    let images = images();
+    assert_eq!(images[2].1.as_deref(), Some("image4.svg.png"));
+    let path = std::env::temp_dir().join("doxx_images/image4.svg.png");
+    let png = std::fs::read(path).unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+    // Scaled up from 200 × 100 so the long side is 1024 pixels
+    let size = |offset: usize| u32::from_be_bytes(png[offset..offset + 4].try_into().unwrap());
+    assert_eq!((size(16), size(20)), (1024, 512));
+}
+
+#[test]
+fn test_metafiles_are_described_by_size() {
+    let images = images();
+    assert_eq!(
+        images[3],
+        ("Image 4 (EMF drawing, 120 × 80 mm)".to_string(), None)
+    );
+    assert_eq!(
+        images[4],
+        ("Image 5 (WMF drawing, 25 × 13 mm)".to_string(), None)
+    );
+}
@@ -0,0 +1,120 @@
+use doxx::document::{load_document, ImageOptions};
+use doxx::export::verify::{verify_export, DifferenceKind, VerifyReport};
+use doxx::export::{export_document_to_string, ExportOptions};
+use doxx::ExportFormat;
+use std::path::Path;
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/business-report.docx";
+
+fn exported() -> String {
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    let options = ExportOptions {
+        toc: true,
+        ..Default::default()
+    };
+    export_document_to_string(&document, &ExportFormat::Markdown, &options).unwrap()
+}
+
+fn verify(export: &str) -> VerifyReport {
+    let document = load_document(Path::new(FIXTURE), ImageOptions::default()).unwrap();
+    VerifyReport {
+        source: "business-report.docx".to_string(),
+        export: "report.md".to_string(),
+        differences: verify_export(&document, export).unwrap(),
+    }
+}
+
+#[test]
+fn test_an_export_with_other_options_is_up_to_date() {
+    // Contents and wrapping aren't what the document says
+    let export = exported().replace(
+        "record revenue of $2.1M and expanding",
+        "record revenue\nof $2.1M and expanding",
+    );
+    let report = verify(&export);
+    assert_eq!(report.differences, []);
+    assert_eq!(report.lines()[1], "No differences: report.md is up to date");
+}
+
+#[test]
+fn test_missing_headings_dropped_tables_and_changed_text_are_reported() {
+    let export = exported()
+        .replace("## Market Risks\n", "")
+        .replace("$2.1M", "$2.0M")
+        .replace("| Net Profit | $420,000", "| Net Profit | $410,000")
+        + "\nA line the document no longer has.\n";
+    let report = verify(&export);
+    let kinds: Vec<DifferenceKind> = report
+        .differences
+        .iter()
+        .map(|difference| difference.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            DifferenceKind::ChangedText,
+            DifferenceKind::ChangedTable,
+            DifferenceKind::MissingHeading,
+            DifferenceKind::ExtraText,
+        ]
+    );
+    let table = &report.differences[1];
+    assert_eq!(
+        table.detail.as_deref(),
+        Some("row 3, column 2: document has \"$420,000\", export has \"$410,000\"")
+    );
+    let heading = &report.differences[2];
+    assert_eq!(heading.document.as_deref(), Some("## Market Risks"));
+    // Where it would go: before the list that followed it
+    assert!(export
+        .lines()
+        .nth(heading.line - 1)
+        .unwrap()
+        .contains("Increased Competition"));
+
+    let lines = report.lines();
+    assert_eq!(
+        lines[3],
+        "    document: …ecord revenue of $2.1M and expanding our customer base by 34%. Key high…"
+    );
+    assert_eq!(
+        lines.last().unwrap(),
+        "4 differences: 1 missing heading, 1 changed table, 1 extra text, 1 changed text"
+    );
+}
+
+#[test]
+fn test_verify_command_exits_with_1_on_differences() {
+    let dir = std::env::temp_dir().join(format!("doxx-verify-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("report.md");
+
+    std::fs::write(&path, exported()).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["verify", FIXTURE])
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    std::fs::write(
+        &path,
+        exported().replace("### Key Performance Indicators\n", ""),
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_doxx"))
+        .args(["verify", FIXTURE])
+        .arg(&path)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["differences"][0]["kind"], "missing-heading");
+    assert_eq!(
+        report["differences"][0]["document"],
+        "### Key Performance Indicators"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}